@@ -46,6 +46,17 @@ pub fn rand_array(len: usize, null_every: usize, str_len: usize) -> BytesMut {
   })
 }
 
+pub fn monitor_lines(count: usize) -> BytesMut {
+  let mut buf = BytesMut::new();
+
+  for i in 0..count {
+    buf.put_u8(b'+');
+    buf.extend_from_slice(format!("1339518083.{:06} [0 127.0.0.1:60866] \"set\" \"{}\"", i, rand_chars(16)).as_bytes());
+    buf.extend_from_slice(CRLF.as_bytes());
+  }
+  buf
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -209,4 +220,19 @@ mod tests {
       black_box(resp2_decode(&buf));
     });
   }
+
+  // simple string decoding
+
+  #[bench]
+  fn bench_decode_1000_monitor_lines(b: &mut Bencher) {
+    let buf = monitor_lines(1000);
+
+    b.iter(|| {
+      let mut offset = 0;
+      while let Some((frame, len)) = resp3_decode(&buf[offset..]).unwrap() {
+        black_box(frame);
+        offset += len;
+      }
+    });
+  }
 }
@@ -15,7 +15,7 @@ pub fn rand_array(len: usize, null_every: usize, str_len: usize) -> Vec<Frame> {
 
   for i in 0..len {
     if i + 1 % null_every == 0 {
-      v.push(Frame::Null);
+      v.push(Frame::Null { attributes: None });
     } else {
       v.push(Frame::BulkString(rand_chars(str_len).into_bytes()));
     }
@@ -0,0 +1,211 @@
+//! Types and functions for parsing the response to the `ACL GETUSER` and `ACL WHOAMI` commands.
+//!
+//! <https://redis.io/topics/acl>
+
+use crate::resp3::types::Frame;
+use crate::resp3::utils::{frame_to_pairs, frame_to_strings, pairs_get};
+use crate::types::RedisProtocolError;
+use crate::utils;
+use std::convert::TryFrom;
+
+/// A set of additional command, key, and channel permissions on a user. Added in Redis 7.0.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AclSelector {
+  /// The command rules, such as `-@all +get`.
+  pub commands: String,
+  /// The key patterns, such as `~foo:*` or `%R~bar:*`.
+  pub keys: Vec<String>,
+  /// The channel patterns, such as `&events:*`.
+  pub channels: Vec<String>,
+}
+
+/// An ACL user, as returned by `ACL GETUSER`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AclUser {
+  /// The user flags, such as `on`, `off`, `nopass`, or `allkeys`.
+  pub flags: Vec<String>,
+  /// The SHA-256 hashes of the user's passwords.
+  pub passwords: Vec<String>,
+  /// The command rules, such as `+@all -debug`.
+  pub commands: String,
+  /// The key patterns, such as `~*`.
+  pub keys: Vec<String>,
+  /// The channel patterns, such as `&*`. Added in Redis 6.2.
+  pub channels: Vec<String>,
+  /// Added in Redis 7.0.
+  pub selectors: Vec<AclSelector>,
+}
+
+impl AclUser {
+  /// Whether or not the user has the provided flag, ignoring case.
+  pub fn has_flag(&self, flag: &str) -> bool {
+    self.flags.iter().any(|f| f.eq_ignore_ascii_case(flag))
+  }
+
+  /// Whether or not the user is enabled.
+  pub fn is_enabled(&self) -> bool {
+    self.has_flag("on")
+  }
+
+  /// Whether or not the user can authenticate with any password.
+  pub fn is_nopass(&self) -> bool {
+    self.has_flag("nopass")
+  }
+}
+
+/// Read a list of patterns, sent as a space-separated string in Redis 7.0 and as an array in earlier versions.
+fn read_patterns(frame: Option<&Frame>, field: &str) -> Result<Vec<String>, RedisProtocolError> {
+  let frame = match frame {
+    Some(Frame::Null { .. }) | None => return Ok(Vec::new()),
+    Some(frame) => frame,
+  };
+
+  if let Some(s) = frame.as_str() {
+    Ok(s.split_whitespace().map(|s| s.to_owned()).collect())
+  } else {
+    frame_to_strings(frame).ok_or_else(|| utils::invalid_response("ACL response", format!("Invalid {}.", field)))
+  }
+}
+
+fn read_strings(frame: Option<&Frame>, field: &str) -> Result<Vec<String>, RedisProtocolError> {
+  match frame {
+    Some(frame) => {
+      frame_to_strings(frame).ok_or_else(|| utils::invalid_response("ACL response", format!("Invalid {}.", field)))
+    },
+    None => Ok(Vec::new()),
+  }
+}
+
+fn read_string(frame: Option<&Frame>) -> String {
+  frame.and_then(|f| f.to_string()).unwrap_or_default()
+}
+
+impl TryFrom<&Frame> for AclSelector {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let pairs = frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("ACL response", "Invalid selector."))?;
+
+    Ok(AclSelector {
+      commands: read_string(pairs_get(&pairs, "commands")),
+      keys: read_patterns(pairs_get(&pairs, "keys"), "selector keys")?,
+      channels: read_patterns(pairs_get(&pairs, "channels"), "selector channels")?,
+    })
+  }
+}
+
+impl TryFrom<&Frame> for AclUser {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let pairs = frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("ACL response", "Expected map."))?;
+    let selectors = match pairs_get(&pairs, "selectors") {
+      Some(Frame::Array { ref data, .. }) => data.iter().map(AclSelector::try_from).collect::<Result<_, _>>()?,
+      Some(_) => return Err(utils::invalid_response("ACL response", "Invalid selectors.")),
+      None => Vec::new(),
+    };
+
+    Ok(AclUser {
+      flags: read_strings(pairs_get(&pairs, "flags"), "flags")?,
+      passwords: read_strings(pairs_get(&pairs, "passwords"), "passwords")?,
+      commands: read_string(pairs_get(&pairs, "commands")),
+      keys: read_patterns(pairs_get(&pairs, "keys"), "keys")?,
+      channels: read_patterns(pairs_get(&pairs, "channels"), "channels")?,
+      selectors,
+    })
+  }
+}
+
+/// Parse the response to `ACL GETUSER`, returning `None` if the user does not exist.
+pub fn parse_acl_getuser(frame: &Frame) -> Result<Option<AclUser>, RedisProtocolError> {
+  match *frame {
+    Frame::Null { .. } => Ok(None),
+    _ => AclUser::try_from(frame).map(Some),
+  }
+}
+
+/// Parse the response to `ACL WHOAMI`.
+pub fn parse_acl_whoami(frame: &Frame) -> Result<String, RedisProtocolError> {
+  frame
+    .to_string()
+    .ok_or_else(|| utils::invalid_response("ACL response", "Expected string."))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::test_frames::*;
+
+  #[test]
+  fn should_parse_resp3_acl_getuser() {
+    let frame = map(vec![
+      ("flags", Frame::Set {
+        data: {
+          let mut set = crate::resp3::utils::new_set(None);
+          set.insert(blob("on"));
+          set
+        },
+        attributes: None,
+      }),
+      ("passwords", array(vec![blob("2d9c75")])),
+      ("commands", blob("+@all -debug")),
+      ("keys", blob("~foo:* ~bar:*")),
+      ("channels", blob("&*")),
+      (
+        "selectors",
+        array(vec![map(vec![
+          ("commands", blob("-@all +get")),
+          ("keys", blob("%R~baz:*")),
+          ("channels", blob("")),
+        ])]),
+      ),
+    ]);
+    let user = parse_acl_getuser(&frame).unwrap().unwrap();
+
+    assert!(user.is_enabled());
+    assert!(!user.is_nopass());
+    assert_eq!(user.passwords, vec!["2d9c75"]);
+    assert_eq!(user.commands, "+@all -debug");
+    assert_eq!(user.keys, vec!["~foo:*", "~bar:*"]);
+    assert_eq!(user.channels, vec!["&*"]);
+    assert_eq!(user.selectors, vec![AclSelector {
+      commands: "-@all +get".into(),
+      keys: vec!["%R~baz:*".into()],
+      channels: vec![],
+    }]);
+  }
+
+  #[test]
+  fn should_parse_resp2_acl_getuser() {
+    // the Redis 6.0 format, where keys are an array and channels are not included
+    let frame = array(vec![
+      blob("flags"),
+      array(vec![blob("on"), blob("allkeys"), blob("nopass")]),
+      blob("passwords"),
+      array(vec![]),
+      blob("commands"),
+      blob("+@all"),
+      blob("keys"),
+      array(vec![blob("*")]),
+    ]);
+    let user = parse_acl_getuser(&frame).unwrap().unwrap();
+
+    assert!(user.is_nopass());
+    assert!(user.has_flag("ALLKEYS"));
+    assert_eq!(user.keys, vec!["*"]);
+    assert!(user.channels.is_empty());
+    assert!(user.selectors.is_empty());
+  }
+
+  #[test]
+  fn should_parse_acl_getuser_missing_user() {
+    assert_eq!(parse_acl_getuser(&Frame::Null { attributes: None }).unwrap(), None);
+    assert!(parse_acl_getuser(&array(vec![blob("flags")])).is_err());
+  }
+
+  #[test]
+  fn should_parse_acl_whoami() {
+    assert_eq!(parse_acl_whoami(&blob("default")).unwrap(), "default");
+    assert!(parse_acl_whoami(&Frame::Null { attributes: None }).is_err());
+  }
+}
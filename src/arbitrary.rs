@@ -0,0 +1,213 @@
+//! [Arbitrary] implementations for RESP2 and RESP3 frames, for use in fuzz targets.
+//!
+//! The generated frames can always be encoded and then decoded back to an equal frame:
+//!
+//! * Simple strings and simple errors do not contain `\r` or `\n`.
+//! * Doubles are never `NaN`.
+//! * Big numbers contain an optional `-` followed by at least one digit.
+//! * Attributes are not empty and do not contain attributes themselves.
+//! * `HELLO` and chunked string frames are not generated since they cannot be decoded by the complete decoder.
+//! * Aggregate frames are nested at most 4 levels deep.
+
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::{Attributes, Frame, VerbatimStringFormat};
+use crate::resp3::utils as resp3_utils;
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+const MAX_DEPTH: usize = 4;
+const MAX_AGGREGATE_LEN: usize = 4;
+
+fn arbitrary_line(u: &mut Unstructured<'_>) -> Result<String> {
+  let data = String::arbitrary(u)?;
+  Ok(data.chars().filter(|c| *c != '\r' && *c != '\n').collect())
+}
+
+fn arbitrary_double(u: &mut Unstructured<'_>) -> Result<f64> {
+  let data = f64::arbitrary(u)?;
+  Ok(if data.is_nan() { 0.0 } else { data })
+}
+
+fn arbitrary_big_number(u: &mut Unstructured<'_>) -> Result<Vec<u8>> {
+  let mut data = if bool::arbitrary(u)? { vec![b'-'] } else { Vec::new() };
+  data.push(b'0' + u.int_in_range(1..=9)?);
+  for _ in 0..u.int_in_range(0..=40)? {
+    data.push(b'0' + u.int_in_range(0..=9)?);
+  }
+
+  Ok(data)
+}
+
+fn arbitrary_len(u: &mut Unstructured<'_>, depth: usize) -> Result<usize> {
+  if depth >= MAX_DEPTH {
+    Ok(0)
+  } else {
+    u.int_in_range(0..=MAX_AGGREGATE_LEN)
+  }
+}
+
+fn arbitrary_attributes(u: &mut Unstructured<'_>, depth: usize, allowed: bool) -> Result<Option<Attributes>> {
+  if !allowed || depth >= MAX_DEPTH || !bool::arbitrary(u)? {
+    return Ok(None);
+  }
+
+  let mut attributes = resp3_utils::new_map(None);
+  for _ in 0..u.int_in_range(1..=MAX_AGGREGATE_LEN)? {
+    let key = arbitrary_frame(u, depth + 1, false)?;
+    let value = arbitrary_frame(u, depth + 1, false)?;
+    attributes.insert(key, value);
+  }
+
+  Ok(Some(attributes))
+}
+
+fn arbitrary_frames(u: &mut Unstructured<'_>, depth: usize, allow_attributes: bool) -> Result<Vec<Frame>> {
+  (0..arbitrary_len(u, depth)?)
+    .map(|_| arbitrary_frame(u, depth + 1, allow_attributes))
+    .collect()
+}
+
+fn arbitrary_frame(u: &mut Unstructured<'_>, depth: usize, allow_attributes: bool) -> Result<Frame> {
+  let attributes = arbitrary_attributes(u, depth, allow_attributes)?;
+
+  Ok(match u.int_in_range(0..=13)? {
+    0 => Frame::BlobString {
+      data: Vec::arbitrary(u)?,
+      attributes,
+    },
+    1 => Frame::BlobError {
+      data: Vec::arbitrary(u)?,
+      attributes,
+    },
+    2 => Frame::SimpleString {
+      data: arbitrary_line(u)?.into(),
+      attributes,
+    },
+    3 => Frame::SimpleError {
+      data: arbitrary_line(u)?.into(),
+      attributes,
+    },
+    4 => Frame::Boolean {
+      data: bool::arbitrary(u)?,
+      attributes,
+    },
+    5 => Frame::Null { attributes },
+    6 => Frame::Number {
+      data: i64::arbitrary(u)?,
+      attributes,
+    },
+    7 => Frame::Double {
+      data: arbitrary_double(u)?,
+      attributes,
+    },
+    8 => Frame::BigNumber {
+      data: arbitrary_big_number(u)?,
+      attributes,
+    },
+    9 => Frame::VerbatimString {
+      data: Vec::arbitrary(u)?,
+      format: if bool::arbitrary(u)? {
+        VerbatimStringFormat::Text
+      } else {
+        VerbatimStringFormat::Markdown
+      },
+      attributes,
+    },
+    10 => Frame::Array {
+      data: arbitrary_frames(u, depth, allow_attributes)?,
+      attributes,
+    },
+    11 => {
+      let mut data = resp3_utils::new_map(None);
+      for _ in 0..arbitrary_len(u, depth)? {
+        let key = arbitrary_frame(u, depth + 1, allow_attributes)?;
+        let value = arbitrary_frame(u, depth + 1, allow_attributes)?;
+        data.insert(key, value);
+      }
+
+      Frame::Map { data, attributes }
+    }
+    12 => Frame::Set {
+      data: arbitrary_frames(u, depth, allow_attributes)?.into_iter().collect(),
+      attributes,
+    },
+    _ => Frame::Push {
+      data: arbitrary_frames(u, depth, allow_attributes)?,
+      attributes,
+    },
+  })
+}
+
+fn arbitrary_resp2_frame(u: &mut Unstructured<'_>, depth: usize) -> Result<Resp2Frame> {
+  Ok(match u.int_in_range(0..=5)? {
+    0 => Resp2Frame::SimpleString(arbitrary_line(u)?),
+    1 => Resp2Frame::Error(arbitrary_line(u)?),
+    2 => Resp2Frame::Integer(i64::arbitrary(u)?),
+    3 => Resp2Frame::BulkString(Vec::arbitrary(u)?),
+    4 => Resp2Frame::Null,
+    _ => Resp2Frame::Array(
+      (0..arbitrary_len(u, depth)?)
+        .map(|_| arbitrary_resp2_frame(u, depth + 1))
+        .collect::<Result<_>>()?,
+    ),
+  })
+}
+
+impl<'a> Arbitrary<'a> for Frame {
+  fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+    arbitrary_frame(u, 0, true)
+  }
+}
+
+impl<'a> Arbitrary<'a> for Resp2Frame {
+  fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+    arbitrary_resp2_frame(u, 0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp2::decode::decode as resp2_decode;
+  use crate::resp2::encode::encode_bytes as resp2_encode_bytes;
+  use crate::resp3::decode::complete::decode;
+  use crate::resp3::encode::complete::encode_bytes;
+  use bytes::BytesMut;
+
+  fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+      .map(|_| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 56) as u8
+      })
+      .collect()
+  }
+
+  #[test]
+  fn should_round_trip_arbitrary_resp3_frames() {
+    for seed in 0..200 {
+      let data = random_bytes(seed, 4096);
+      let frame = Frame::arbitrary(&mut Unstructured::new(&data)).unwrap();
+
+      let mut buf = BytesMut::new();
+      encode_bytes(&mut buf, &frame).unwrap();
+      let (decoded, len) = decode(&buf).unwrap().unwrap();
+
+      assert_eq!(len, buf.len());
+      assert_eq!(decoded, frame);
+      assert_eq!(decoded.attributes(), frame.attributes());
+    }
+  }
+
+  #[test]
+  fn should_round_trip_arbitrary_resp2_frames() {
+    for seed in 0..200 {
+      let data = random_bytes(seed, 4096);
+      let frame = Resp2Frame::arbitrary(&mut Unstructured::new(&data)).unwrap();
+
+      let mut buf = BytesMut::new();
+      resp2_encode_bytes(&mut buf, &frame).unwrap();
+      assert_eq!(resp2_decode(&buf).unwrap(), Some((frame, buf.len())));
+    }
+  }
+}
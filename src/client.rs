@@ -0,0 +1,257 @@
+//! Types and functions for parsing the response to the `CLIENT LIST` and `CLIENT INFO` commands.
+//!
+//! <https://redis.io/commands/client-list>
+
+use crate::resp3::types::Frame;
+use crate::resp3::utils::frame_to_pairs;
+use crate::types::RedisProtocolError;
+use crate::utils;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// A connected client, as described by one line of `CLIENT LIST` or `CLIENT INFO` output.
+///
+/// Fields that are missing from the response use their default value. All fields, including those without a typed
+/// accessor, are also available in [fields](Self::fields).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ClientInfo {
+  /// The unique client ID.
+  pub id: u64,
+  /// The address and port of the client.
+  pub addr: String,
+  /// The address and port of the local side of the connection.
+  pub laddr: Option<String>,
+  /// The file descriptor for the socket, or `-1` for internal clients.
+  pub fd: i64,
+  /// The name set with `CLIENT SETNAME`.
+  pub name: Option<String>,
+  /// The total duration of the connection, in seconds.
+  pub age: u64,
+  /// The idle time of the connection, in seconds.
+  pub idle: u64,
+  /// The client flags, such as `N` or `Sx`.
+  pub flags: String,
+  /// The selected database index.
+  pub db: u32,
+  /// The number of channel subscriptions.
+  pub sub: u64,
+  /// The number of pattern subscriptions.
+  pub psub: u64,
+  /// The number of commands in a `MULTI` block, or `-1` if not in a transaction.
+  pub multi: i64,
+  /// The last command run by the client.
+  pub cmd: Option<String>,
+  /// The authenticated ACL user.
+  pub user: Option<String>,
+  /// The RESP protocol version used by the client.
+  pub resp: Option<u8>,
+  /// Every key/value pair in the response, in the order they were received.
+  pub fields: Vec<(String, String)>,
+}
+
+impl ClientInfo {
+  /// Read the value for `key`.
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self
+      .fields
+      .iter()
+      .find(|(k, _)| k == key)
+      .map(|(_, v)| v.as_str())
+  }
+
+  /// Whether or not the client has the provided flag, such as `M` for primary nodes or `P` for pubsub clients.
+  pub fn has_flag(&self, flag: char) -> bool {
+    self.flags.contains(flag)
+  }
+
+  /// Create a `ClientInfo` from key/value pairs.
+  pub fn from_fields<K, V, I>(fields: I) -> Result<Self, RedisProtocolError>
+  where
+    K: Into<String>,
+    V: Into<String>,
+    I: IntoIterator<Item = (K, V)>,
+  {
+    let fields: Vec<(String, String)> = fields.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+    let mut info = ClientInfo {
+      multi: -1,
+      ..Default::default()
+    };
+
+    for (key, value) in fields.iter() {
+      match key.as_str() {
+        "id" => info.id = parse_field(key, value)?,
+        "addr" => info.addr = value.to_owned(),
+        "laddr" => info.laddr = Some(value.to_owned()),
+        "fd" => info.fd = parse_field(key, value)?,
+        "name" => info.name = non_empty(value),
+        "age" => info.age = parse_field(key, value)?,
+        "idle" => info.idle = parse_field(key, value)?,
+        "flags" => info.flags = value.to_owned(),
+        "db" => info.db = parse_field(key, value)?,
+        "sub" => info.sub = parse_field(key, value)?,
+        "psub" => info.psub = parse_field(key, value)?,
+        "multi" => info.multi = parse_field(key, value)?,
+        "cmd" => info.cmd = non_empty(value).filter(|s| s != "NULL"),
+        "user" => info.user = non_empty(value),
+        "resp" => info.resp = Some(parse_field(key, value)?),
+        _ => {},
+      };
+    }
+
+    if info.addr.is_empty() {
+      return Err(utils::invalid_response("client info", "Missing addr field."));
+    }
+    info.fields = fields;
+    Ok(info)
+  }
+}
+
+impl FromStr for ClientInfo {
+  type Err = RedisProtocolError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    parse_client_info(s)
+  }
+}
+
+impl TryFrom<&Frame> for ClientInfo {
+  type Error = RedisProtocolError;
+
+  /// Parse a `CLIENT INFO` response, as either a string or a map.
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    if let Some(s) = frame.as_str() {
+      return parse_client_info(s);
+    }
+    let pairs =
+      frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("client info", "Expected string or map."))?;
+
+    let mut fields = Vec::with_capacity(pairs.len());
+    for (key, value) in pairs.into_iter() {
+      let key = key
+        .to_string()
+        .ok_or_else(|| utils::invalid_response("client info", "Invalid key."))?;
+      let value = match *value {
+        Frame::Null { .. } => String::new(),
+        _ => value
+          .to_string()
+          .ok_or_else(|| utils::invalid_response("client info", "Invalid value."))?,
+      };
+      fields.push((key, value));
+    }
+    ClientInfo::from_fields(fields)
+  }
+}
+
+fn parse_field<T: FromStr>(key: &str, value: &str) -> Result<T, RedisProtocolError> {
+  value
+    .parse()
+    .map_err(|_| utils::invalid_response("client info", format!("Invalid {} field.", key)))
+}
+
+fn non_empty(value: &str) -> Option<String> {
+  if value.is_empty() {
+    None
+  } else {
+    Some(value.to_owned())
+  }
+}
+
+/// Parse one line of `CLIENT LIST` output, or the response to `CLIENT INFO`.
+pub fn parse_client_info(line: &str) -> Result<ClientInfo, RedisProtocolError> {
+  let fields = line.split_whitespace().filter_map(|pair| {
+    let idx = pair.find('=')?;
+    Some((&pair[0..idx], &pair[idx + 1..]))
+  });
+
+  ClientInfo::from_fields(fields)
+}
+
+/// Parse the response to `CLIENT LIST`, ignoring empty lines.
+pub fn parse_client_list(list: &str) -> Result<Vec<ClientInfo>, RedisProtocolError> {
+  list
+    .lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(parse_client_info)
+    .collect()
+}
+
+/// Parse a `CLIENT LIST` response frame.
+///
+/// A string response is parsed with [parse_client_list]. Arrays are parsed as a list of strings or maps, and a map is
+/// parsed as a single client.
+pub fn parse_client_list_frame(frame: &Frame) -> Result<Vec<ClientInfo>, RedisProtocolError> {
+  match *frame {
+    Frame::Map { .. } => Ok(vec![ClientInfo::try_from(frame)?]),
+    Frame::Array { ref data, .. } => data.iter().map(ClientInfo::try_from).collect(),
+    _ => match frame.as_str() {
+      Some(s) => parse_client_list(s),
+      None => Err(utils::invalid_response(
+        "client info",
+        "Expected string, array, or map.",
+      )),
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const CLIENT_LIST: &str = "id=3 addr=127.0.0.1:51980 laddr=127.0.0.1:6379 fd=8 name= age=10 idle=0 flags=N db=0 sub=0 psub=0 multi=-1 qbuf=26 qbuf-free=40928 obl=0 oll=0 omem=0 events=r cmd=client user=default resp=2\nid=4 addr=[::1]:51982 laddr=[::1]:6379 fd=9 name=worker age=5 idle=5 flags=P db=2 sub=1 psub=2 multi=-1 qbuf=0 qbuf-free=0 obl=0 oll=0 omem=0 events=r cmd=NULL user=app resp=3\n";
+
+  #[test]
+  fn should_parse_client_list() {
+    let clients = parse_client_list(CLIENT_LIST).unwrap();
+
+    assert_eq!(clients.len(), 2);
+    assert_eq!(clients[0].id, 3);
+    assert_eq!(clients[0].addr, "127.0.0.1:51980");
+    assert_eq!(clients[0].laddr.as_deref(), Some("127.0.0.1:6379"));
+    assert_eq!(clients[0].name, None);
+    assert_eq!(clients[0].cmd.as_deref(), Some("client"));
+    assert_eq!(clients[0].resp, Some(2));
+    assert_eq!(clients[0].get("qbuf-free"), Some("40928"));
+
+    assert_eq!(clients[1].addr, "[::1]:51982");
+    assert_eq!(clients[1].name.as_deref(), Some("worker"));
+    assert_eq!(clients[1].db, 2);
+    assert_eq!(clients[1].sub, 1);
+    assert_eq!(clients[1].psub, 2);
+    assert_eq!(clients[1].cmd, None);
+    assert_eq!(clients[1].user.as_deref(), Some("app"));
+    assert!(clients[1].has_flag('P'));
+  }
+
+  #[test]
+  fn should_parse_client_info_frame() {
+    let line = CLIENT_LIST.lines().next().unwrap();
+    let frame = Frame::new_verbatim(crate::resp3::types::VerbatimStringFormat::Text, line);
+
+    assert_eq!(ClientInfo::try_from(&frame).unwrap(), line.parse::<ClientInfo>().unwrap());
+  }
+
+  #[test]
+  fn should_parse_client_info_map() {
+    let frame = Frame::new_map(vec![
+      (Frame::new_blob("id"), Frame::new_number(7)),
+      (Frame::new_blob("addr"), Frame::new_blob("10.0.0.1:1234")),
+      (Frame::new_blob("name"), Frame::new_null()),
+      (Frame::new_blob("flags"), Frame::new_blob("M")),
+    ]);
+
+    let clients = parse_client_list_frame(&frame).unwrap();
+    assert_eq!(clients.len(), 1);
+    assert_eq!(clients[0].id, 7);
+    assert_eq!(clients[0].addr, "10.0.0.1:1234");
+    assert_eq!(clients[0].name, None);
+    assert_eq!(clients[0].multi, -1);
+    assert!(clients[0].has_flag('M'));
+  }
+
+  #[test]
+  fn should_error_on_invalid_client_info() {
+    assert!(parse_client_info("id=1").is_err());
+    assert!(parse_client_info("id=abc addr=127.0.0.1:1").is_err());
+    assert!(parse_client_list_frame(&Frame::new_number(1)).is_err());
+  }
+}
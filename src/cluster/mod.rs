@@ -0,0 +1,8 @@
+/// Types and functions for parsing `CLUSTER SLOTS` responses.
+pub mod slots;
+/// Types and functions for parsing `CLUSTER SHARDS` responses.
+pub mod shards;
+/// Types and functions for parsing `CLUSTER NODES` responses.
+pub mod nodes;
+/// A slot to node lookup structure for cluster clients.
+pub mod slot_map;
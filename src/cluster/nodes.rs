@@ -0,0 +1,277 @@
+//! Types and functions for parsing the response to the `CLUSTER NODES` command.
+//!
+//! <https://redis.io/commands/cluster-nodes>
+
+use crate::types::RedisProtocolError;
+use crate::utils::{self, REDIS_CLUSTER_SLOTS};
+use std::str::FromStr;
+
+/// The state of the cluster bus link to a node.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LinkState {
+  Connected,
+  Disconnected,
+}
+
+/// A slot assignment on a node.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NodeSlot {
+  /// An inclusive range of slots served by the node. Single slots use the same start and end.
+  Range(u16, u16),
+  /// A slot being moved from this node to the node with the provided ID.
+  Migrating { slot: u16, to: String },
+  /// A slot being moved to this node from the node with the provided ID.
+  Importing { slot: u16, from: String },
+}
+
+/// A node in the cluster, as described by one line of `CLUSTER NODES` output.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClusterNodeInfo {
+  pub id: String,
+  /// The node IP address. This is empty when the address is unknown.
+  pub ip: String,
+  pub port: u16,
+  /// The cluster bus port.
+  pub cluster_port: Option<u16>,
+  /// Added in Redis 7.0.
+  pub hostname: Option<String>,
+  /// The node flags, such as `myself`, `master`, `slave`, `fail?`, `fail`, or `handshake`.
+  pub flags: Vec<String>,
+  /// The ID of the primary node, if this node is a replica.
+  pub master_id: Option<String>,
+  /// The unix time, in milliseconds, when the last ping was sent.
+  pub ping_sent: u64,
+  /// The unix time, in milliseconds, when the last pong was received.
+  pub pong_recv: u64,
+  pub config_epoch: u64,
+  pub link_state: LinkState,
+  pub slots: Vec<NodeSlot>,
+}
+
+impl ClusterNodeInfo {
+  /// Whether or not the node has the provided flag.
+  pub fn has_flag(&self, flag: &str) -> bool {
+    self.flags.iter().any(|f| f == flag)
+  }
+
+  /// Whether or not this is the node that sent the response.
+  pub fn is_myself(&self) -> bool {
+    self.has_flag("myself")
+  }
+
+  /// Whether or not the node is a primary node.
+  pub fn is_primary(&self) -> bool {
+    self.has_flag("master")
+  }
+
+  /// Whether or not the node is a replica.
+  pub fn is_replica(&self) -> bool {
+    self.has_flag("slave")
+  }
+
+  /// Whether or not the node is in the `PFAIL` or `FAIL` state.
+  pub fn is_failed(&self) -> bool {
+    self.has_flag("fail") || self.has_flag("fail?")
+  }
+
+  /// Read the inclusive slot ranges served by the node, ignoring migrating and importing slots.
+  pub fn slot_ranges(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+    self.slots.iter().filter_map(|slot| match *slot {
+      NodeSlot::Range(start, end) => Some((start, end)),
+      _ => None,
+    })
+  }
+
+  /// Read the `ip:port` string for the node, wrapping IPv6 addresses in brackets.
+  pub fn server(&self) -> String {
+    if self.ip.contains(':') {
+      format!("[{}]:{}", self.ip, self.port)
+    } else {
+      format!("{}:{}", self.ip, self.port)
+    }
+  }
+}
+
+fn parse_slot(s: &str) -> Result<u16, RedisProtocolError> {
+  s.parse::<u16>()
+    .ok()
+    .filter(|s| *s < REDIS_CLUSTER_SLOTS)
+    .ok_or_else(|| utils::invalid_response("cluster response", "Invalid slot."))
+}
+
+fn parse_node_slot(s: &str) -> Result<NodeSlot, RedisProtocolError> {
+  if s.starts_with('[') && s.ends_with(']') {
+    let inner = &s[1..s.len() - 1];
+
+    if let Some(idx) = inner.find("->-") {
+      Ok(NodeSlot::Migrating {
+        slot: parse_slot(&inner[0..idx])?,
+        to: inner[idx + 3..].to_owned(),
+      })
+    } else if let Some(idx) = inner.find("-<-") {
+      Ok(NodeSlot::Importing {
+        slot: parse_slot(&inner[0..idx])?,
+        from: inner[idx + 3..].to_owned(),
+      })
+    } else {
+      Err(utils::invalid_response("cluster response", "Invalid migrating slot."))
+    }
+  } else if let Some(idx) = s.find('-') {
+    let (start, end) = (parse_slot(&s[0..idx])?, parse_slot(&s[idx + 1..])?);
+    if end < start {
+      return Err(utils::invalid_response("cluster response", "Invalid slot range."));
+    }
+    Ok(NodeSlot::Range(start, end))
+  } else {
+    let slot = parse_slot(s)?;
+    Ok(NodeSlot::Range(slot, slot))
+  }
+}
+
+/// Parse an address in the `ip:port@cport[,hostname]` form.
+fn parse_address(s: &str) -> Result<(String, u16, Option<u16>, Option<String>), RedisProtocolError> {
+  let (address, hostname) = match s.find(',') {
+    Some(idx) => (&s[0..idx], Some(&s[idx + 1..]).filter(|h| !h.is_empty())),
+    None => (s, None),
+  };
+  let (address, cluster_port) = match address.find('@') {
+    Some(idx) => (
+      &address[0..idx],
+      Some(
+        address[idx + 1..]
+          .parse::<u16>()
+          .map_err(|_| utils::invalid_response("cluster response", "Invalid cluster port."))?,
+      ),
+    ),
+    None => (address, None),
+  };
+  let idx = address
+    .rfind(':')
+    .ok_or_else(|| utils::invalid_response("cluster response", "Invalid address."))?;
+  let port = address[idx + 1..]
+    .parse::<u16>()
+    .map_err(|_| utils::invalid_response("cluster response", "Invalid port."))?;
+
+  Ok((
+    address[0..idx].to_owned(),
+    port,
+    cluster_port,
+    hostname.map(|h| h.to_owned()),
+  ))
+}
+
+fn parse_number(s: &str, field: &str) -> Result<u64, RedisProtocolError> {
+  s.parse::<u64>()
+    .map_err(|_| utils::invalid_response("cluster response", format!("Invalid {}.", field)))
+}
+
+impl FromStr for ClusterNodeInfo {
+  type Err = RedisProtocolError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let parts: Vec<&str> = s.split_whitespace().collect();
+    if parts.len() < 8 {
+      return Err(utils::invalid_response(
+        "cluster response",
+        "Expected at least 8 fields.",
+      ));
+    }
+
+    let (ip, port, cluster_port, hostname) = parse_address(parts[1])?;
+    let link_state = match parts[7] {
+      "connected" => LinkState::Connected,
+      "disconnected" => LinkState::Disconnected,
+      _ => return Err(utils::invalid_response("cluster response", "Invalid link state.")),
+    };
+
+    Ok(ClusterNodeInfo {
+      id: parts[0].to_owned(),
+      ip,
+      port,
+      cluster_port,
+      hostname,
+      flags: parts[2]
+        .split(',')
+        .filter(|f| !f.is_empty() && *f != "noflags")
+        .map(|f| f.to_owned())
+        .collect(),
+      master_id: if parts[3] == "-" {
+        None
+      } else {
+        Some(parts[3].to_owned())
+      },
+      ping_sent: parse_number(parts[4], "ping sent")?,
+      pong_recv: parse_number(parts[5], "pong received")?,
+      config_epoch: parse_number(parts[6], "config epoch")?,
+      link_state,
+      slots: parts[8..].iter().map(|s| parse_node_slot(s)).collect::<Result<_, _>>()?,
+    })
+  }
+}
+
+/// Parse the response to `CLUSTER NODES` or `CLUSTER REPLICAS`, ignoring empty lines.
+pub fn parse_cluster_nodes(nodes: &str) -> Result<Vec<ClusterNodeInfo>, RedisProtocolError> {
+  nodes
+    .lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(|line| line.parse())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const CLUSTER_NODES: &str = "07c37dfeb235213a872192d90877d0cd55635b91 127.0.0.1:30004@31004,host-4 slave e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca 0 1426238317239 4 connected\n67ed2db8d677e59ec4a4cefb06858cf2a1a89fa1 127.0.0.1:30002@31002 master - 0 1426238316232 2 connected 5461-10922 [10923-<-292f8b365bb7edb5e285caf0b7e6ddc7265d2f4f]\ne7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca ::1:30001@31001 myself,master - 0 0 1 connected 0-5460 [93->-292f8b365bb7edb5e285caf0b7e6ddc7265d2f4f] 16383\n6ec23923021cf3ffec47632106199cb7f496ce01 :0@0 master,fail?,noaddr - 1426238316232 0 5 disconnected\n";
+
+  #[test]
+  fn should_parse_cluster_nodes() {
+    let nodes = parse_cluster_nodes(CLUSTER_NODES).unwrap();
+    assert_eq!(nodes.len(), 4);
+
+    assert!(nodes[0].is_replica());
+    assert_eq!(nodes[0].hostname.as_deref(), Some("host-4"));
+    assert_eq!(nodes[0].cluster_port, Some(31004));
+    assert_eq!(
+      nodes[0].master_id.as_deref(),
+      Some("e7d1eecce10fd6bb5eb35b9f99a514335d9ba9ca")
+    );
+    assert_eq!(nodes[0].pong_recv, 1426238317239);
+    assert!(nodes[0].slots.is_empty());
+
+    assert_eq!(nodes[1].slots, vec![NodeSlot::Range(5461, 10922), NodeSlot::Importing {
+      slot: 10923,
+      from: "292f8b365bb7edb5e285caf0b7e6ddc7265d2f4f".into(),
+    }]);
+
+    assert!(nodes[2].is_myself());
+    assert!(nodes[2].is_primary());
+    assert_eq!(nodes[2].server(), "[::1]:30001");
+    assert_eq!(nodes[2].config_epoch, 1);
+    assert_eq!(nodes[2].slot_ranges().collect::<Vec<_>>(), vec![(0, 5460), (16383, 16383)]);
+    assert_eq!(nodes[2].slots[1], NodeSlot::Migrating {
+      slot: 93,
+      to: "292f8b365bb7edb5e285caf0b7e6ddc7265d2f4f".into(),
+    });
+
+    assert!(nodes[3].is_failed());
+    assert!(nodes[3].has_flag("noaddr"));
+    assert_eq!(nodes[3].ip, "");
+    assert_eq!(nodes[3].link_state, LinkState::Disconnected);
+  }
+
+  #[test]
+  fn should_error_on_invalid_cluster_nodes() {
+    assert!("abc 127.0.0.1:30001@31001 master - 0 0 1".parse::<ClusterNodeInfo>().is_err());
+    assert!("abc 127.0.0.1 master - 0 0 1 connected"
+      .parse::<ClusterNodeInfo>()
+      .is_err());
+    assert!("abc 127.0.0.1:1@2 master - 0 0 1 connected 10-5"
+      .parse::<ClusterNodeInfo>()
+      .is_err());
+    assert!("abc 127.0.0.1:1@2 master - 0 0 1 connected 16384"
+      .parse::<ClusterNodeInfo>()
+      .is_err());
+    assert!("abc 127.0.0.1:1@2 master - 0 0 1 unknown".parse::<ClusterNodeInfo>().is_err());
+  }
+}
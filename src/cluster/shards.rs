@@ -0,0 +1,277 @@
+//! Types and functions for parsing the response to the `CLUSTER SHARDS` command.
+//!
+//! <https://redis.io/commands/cluster-shards>
+
+use crate::resp3::types::Frame;
+use crate::resp3::utils::{frame_to_pairs, pairs_get};
+use crate::types::RedisProtocolError;
+use crate::utils::{self, REDIS_CLUSTER_SLOTS};
+use std::convert::TryFrom;
+
+/// The role of a node in a shard.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NodeRole {
+  Primary,
+  Replica,
+}
+
+/// The health of a node in a shard.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NodeHealth {
+  Online,
+  Failed,
+  Loading,
+  /// A health status not known to this crate.
+  Unknown(String),
+}
+
+impl<'a> From<&'a str> for NodeHealth {
+  fn from(s: &'a str) -> Self {
+    match s {
+      "online" => NodeHealth::Online,
+      "failed" => NodeHealth::Failed,
+      "loading" => NodeHealth::Loading,
+      _ => NodeHealth::Unknown(s.to_owned()),
+    }
+  }
+}
+
+/// A node in a shard.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClusterNode {
+  pub id: String,
+  /// The preferred endpoint for the node, which may be an IP address, a hostname, or `?` if unknown.
+  pub endpoint: String,
+  pub ip: String,
+  pub hostname: Option<String>,
+  /// The plaintext port. This is `None` on nodes that only accept TLS connections.
+  pub port: Option<u16>,
+  pub tls_port: Option<u16>,
+  pub role: NodeRole,
+  pub replication_offset: i64,
+  pub health: NodeHealth,
+}
+
+impl ClusterNode {
+  /// Whether or not the node is a primary node.
+  pub fn is_primary(&self) -> bool {
+    self.role == NodeRole::Primary
+  }
+
+  /// Whether or not the node is online.
+  pub fn is_online(&self) -> bool {
+    self.health == NodeHealth::Online
+  }
+}
+
+/// A shard in a cluster, as returned by `CLUSTER SHARDS`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClusterShard {
+  /// The inclusive slot ranges served by the shard.
+  pub slots: Vec<(u16, u16)>,
+  pub nodes: Vec<ClusterNode>,
+}
+
+impl ClusterShard {
+  /// Read the primary node in the shard.
+  pub fn primary(&self) -> Option<&ClusterNode> {
+    self.nodes.iter().find(|n| n.is_primary())
+  }
+
+  /// Read the replica nodes in the shard.
+  pub fn replicas(&self) -> impl Iterator<Item = &ClusterNode> {
+    self.nodes.iter().filter(|n| !n.is_primary())
+  }
+
+  /// Whether or not the shard serves the provided slot.
+  pub fn contains_slot(&self, slot: u16) -> bool {
+    self.slots.iter().any(|(start, end)| slot >= *start && slot <= *end)
+  }
+}
+
+fn read_string(frame: Option<&Frame>, field: &str) -> Result<String, RedisProtocolError> {
+  frame
+    .and_then(|f| f.to_string())
+    .ok_or_else(|| utils::invalid_response("cluster response", format!("Invalid {}.", field)))
+}
+
+fn read_port(frame: Option<&Frame>) -> Result<Option<u16>, RedisProtocolError> {
+  match frame.and_then(|f| f.as_i64()) {
+    Some(port) if port >= 0 && port <= u16::MAX as i64 => Ok(Some(port as u16)),
+    Some(_) => Err(utils::invalid_response("cluster response", "Invalid port.")),
+    None => Ok(None),
+  }
+}
+
+fn read_slots(frame: Option<&Frame>) -> Result<Vec<(u16, u16)>, RedisProtocolError> {
+  let data = match frame {
+    Some(Frame::Array { ref data, .. }) if data.len() % 2 == 0 => data,
+    Some(_) => return Err(utils::invalid_response("cluster response", "Invalid slots.")),
+    None => return Ok(Vec::new()),
+  };
+
+  data
+    .chunks(2)
+    .map(|pair| {
+      let start = pair[0].as_i64();
+      let end = pair[1].as_i64();
+
+      match (start, end) {
+        (Some(start), Some(end)) if start >= 0 && start <= end && end < REDIS_CLUSTER_SLOTS as i64 => {
+          Ok((start as u16, end as u16))
+        },
+        _ => Err(utils::invalid_response("cluster response", "Invalid slot range.")),
+      }
+    })
+    .collect()
+}
+
+impl TryFrom<&Frame> for ClusterNode {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let pairs =
+      frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("cluster response", "Expected node map."))?;
+    let role = match read_string(pairs_get(&pairs, "role"), "role")?.as_str() {
+      "master" | "primary" => NodeRole::Primary,
+      "replica" | "slave" => NodeRole::Replica,
+      _ => return Err(utils::invalid_response("cluster response", "Invalid role.")),
+    };
+
+    Ok(ClusterNode {
+      id: read_string(pairs_get(&pairs, "id"), "id")?,
+      endpoint: read_string(pairs_get(&pairs, "endpoint"), "endpoint")?,
+      ip: read_string(pairs_get(&pairs, "ip"), "ip")?,
+      hostname: pairs_get(&pairs, "hostname").and_then(|f| f.to_string()),
+      port: read_port(pairs_get(&pairs, "port"))?,
+      tls_port: read_port(pairs_get(&pairs, "tls-port"))?,
+      role,
+      replication_offset: pairs_get(&pairs, "replication-offset")
+        .and_then(|f| f.as_i64())
+        .unwrap_or(0),
+      health: NodeHealth::from(read_string(pairs_get(&pairs, "health"), "health")?.as_str()),
+    })
+  }
+}
+
+impl TryFrom<&Frame> for ClusterShard {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let pairs =
+      frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("cluster response", "Expected shard map."))?;
+    let nodes = match pairs_get(&pairs, "nodes") {
+      Some(Frame::Array { ref data, .. }) => data.iter().map(ClusterNode::try_from).collect::<Result<_, _>>()?,
+      _ => return Err(utils::invalid_response("cluster response", "Invalid nodes.")),
+    };
+
+    Ok(ClusterShard {
+      slots: read_slots(pairs_get(&pairs, "slots"))?,
+      nodes,
+    })
+  }
+}
+
+/// Parse the response to `CLUSTER SHARDS`.
+pub fn parse_cluster_shards(frame: &Frame) -> Result<Vec<ClusterShard>, RedisProtocolError> {
+  match *frame {
+    Frame::Array { ref data, .. } => data.iter().map(ClusterShard::try_from).collect(),
+    _ => Err(utils::invalid_response("cluster response", "Expected array.")),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::test_frames::*;
+
+  fn node(id: &str, port: i64, role: &str, health: &str) -> Frame {
+    map(vec![
+      ("id", blob(id)),
+      ("port", int(port)),
+      ("ip", blob("127.0.0.1")),
+      ("endpoint", blob("127.0.0.1")),
+      ("role", blob(role)),
+      ("replication-offset", int(72156)),
+      ("health", blob(health)),
+    ])
+  }
+
+  #[test]
+  fn should_parse_cluster_shards() {
+    let frame = array(vec![map(vec![
+      ("slots", array(vec![int(0), int(5460), int(10923), int(11000)])),
+      (
+        "nodes",
+        array(vec![
+          node("e10b7051d6bf2d5febd39a2be297bbaea6084111", 30001, "master", "online"),
+          node("1901f5962d865341e81c85f9f596b1e7160c35ce", 30006, "replica", "loading"),
+        ]),
+      ),
+    ])]);
+    let shards = parse_cluster_shards(&frame).unwrap();
+
+    assert_eq!(shards.len(), 1);
+    let shard = &shards[0];
+    assert_eq!(shard.slots, vec![(0, 5460), (10923, 11000)]);
+    assert!(shard.contains_slot(10999));
+    assert!(!shard.contains_slot(6000));
+
+    let primary = shard.primary().unwrap();
+    assert_eq!(primary.id, "e10b7051d6bf2d5febd39a2be297bbaea6084111");
+    assert_eq!(primary.port, Some(30001));
+    assert_eq!(primary.tls_port, None);
+    assert_eq!(primary.replication_offset, 72156);
+    assert!(primary.is_online());
+
+    let replicas: Vec<_> = shard.replicas().collect();
+    assert_eq!(replicas.len(), 1);
+    assert_eq!(replicas[0].health, NodeHealth::Loading);
+  }
+
+  #[test]
+  fn should_parse_flat_cluster_shards() {
+    let frame = array(vec![array(vec![
+      blob("slots"),
+      array(vec![]),
+      blob("nodes"),
+      array(vec![array(vec![
+        blob("id"),
+        blob("abc"),
+        blob("tls-port"),
+        int(6380),
+        blob("ip"),
+        blob("::1"),
+        blob("endpoint"),
+        blob("redis-1.example.com"),
+        blob("hostname"),
+        blob("redis-1.example.com"),
+        blob("role"),
+        blob("master"),
+        blob("health"),
+        blob("failed"),
+      ])]),
+    ])]);
+    let shards = parse_cluster_shards(&frame).unwrap();
+    let node = &shards[0].nodes[0];
+
+    assert!(shards[0].slots.is_empty());
+    assert_eq!(node.port, None);
+    assert_eq!(node.tls_port, Some(6380));
+    assert_eq!(node.hostname.as_deref(), Some("redis-1.example.com"));
+    assert_eq!(node.health, NodeHealth::Failed);
+  }
+
+  #[test]
+  fn should_error_on_invalid_cluster_shards() {
+    let bad_slots = array(vec![map(vec![("slots", array(vec![int(0)])), ("nodes", array(vec![]))])]);
+    let bad_role = array(vec![map(vec![
+      ("slots", array(vec![])),
+      ("nodes", array(vec![node("abc", 1, "leader", "online")])),
+    ])]);
+
+    assert!(parse_cluster_shards(&bad_slots).is_err());
+    assert!(parse_cluster_shards(&bad_role).is_err());
+    assert!(parse_cluster_shards(&array(vec![map(vec![("slots", array(vec![]))])])).is_err());
+  }
+}
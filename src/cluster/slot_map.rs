@@ -0,0 +1,321 @@
+//! A slot to node lookup structure, built from the response to `CLUSTER SLOTS` or `CLUSTER SHARDS`.
+//!
+//! <https://redis.io/topics/cluster-spec#key-distribution-model>
+
+use crate::cluster::shards::{ClusterNode, ClusterShard};
+use crate::cluster::slots::{parse_cluster_slots, SlotNode, SlotRange};
+use crate::resp3::types::Frame;
+use crate::types::RedisProtocolError;
+use crate::utils::{self, redis_keyslot, REDIS_CLUSTER_SLOTS};
+use std::cmp::Ordering;
+
+/// A change in the primary node that serves a range of slots.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SlotChange {
+  /// The first slot in the range.
+  pub start: u16,
+  /// The last slot in the range, inclusive.
+  pub end: u16,
+  /// The previous primary node, or `None` if the slots were not covered.
+  pub old: Option<SlotNode>,
+  /// The new primary node, or `None` if the slots are no longer covered.
+  pub new: Option<SlotNode>,
+}
+
+/// A mapping of hash slots to the nodes that serve them.
+///
+/// Ranges are sorted and non-overlapping, so lookups use a binary search.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SlotMap {
+  ranges: Vec<SlotRange>,
+}
+
+impl SlotMap {
+  /// Create a slot map from a list of slot ranges, returning an error if any ranges are invalid or overlap.
+  pub fn new(mut ranges: Vec<SlotRange>) -> Result<Self, RedisProtocolError> {
+    for range in ranges.iter() {
+      if range.start > range.end || range.end >= REDIS_CLUSTER_SLOTS {
+        return Err(utils::invalid_response(
+          "cluster response",
+          format!("Invalid slot range {}-{}.", range.start, range.end),
+        ));
+      }
+    }
+    ranges.sort_by_key(|range| range.start);
+
+    for pair in ranges.windows(2) {
+      if pair[1].start <= pair[0].end {
+        return Err(utils::invalid_response(
+          "cluster response",
+          format!(
+            "Overlapping slot ranges {}-{} and {}-{}.",
+            pair[0].start, pair[0].end, pair[1].start, pair[1].end
+          ),
+        ));
+      }
+    }
+
+    Ok(SlotMap { ranges })
+  }
+
+  /// Create a slot map from the response to `CLUSTER SLOTS`.
+  pub fn from_cluster_slots(frame: &Frame) -> Result<Self, RedisProtocolError> {
+    SlotMap::new(parse_cluster_slots(frame)?)
+  }
+
+  /// Create a slot map from the parsed response to `CLUSTER SHARDS`.
+  ///
+  /// Nodes use their preferred endpoint as the host, and the TLS port if no plaintext port is available. Shards
+  /// without a primary node are skipped.
+  pub fn from_shards(shards: &[ClusterShard]) -> Result<Self, RedisProtocolError> {
+    let mut ranges = Vec::new();
+
+    for shard in shards.iter() {
+      let primary = match shard.primary() {
+        Some(primary) => shard_node_to_slot_node(primary)?,
+        None => continue,
+      };
+      let replicas = shard
+        .replicas()
+        .map(shard_node_to_slot_node)
+        .collect::<Result<Vec<_>, _>>()?;
+
+      for (start, end) in shard.slots.iter() {
+        ranges.push(SlotRange {
+          start: *start,
+          end: *end,
+          primary: primary.clone(),
+          replicas: replicas.clone(),
+        });
+      }
+    }
+
+    SlotMap::new(ranges)
+  }
+
+  /// Read the sorted slot ranges.
+  pub fn ranges(&self) -> &[SlotRange] {
+    &self.ranges
+  }
+
+  /// Whether or not the map contains any slot ranges.
+  pub fn is_empty(&self) -> bool {
+    self.ranges.is_empty()
+  }
+
+  /// Read the slot range that includes the provided slot.
+  pub fn get(&self, slot: u16) -> Option<&SlotRange> {
+    self
+      .ranges
+      .binary_search_by(|range| {
+        if range.end < slot {
+          Ordering::Less
+        } else if range.start > slot {
+          Ordering::Greater
+        } else {
+          Ordering::Equal
+        }
+      })
+      .ok()
+      .map(|idx| &self.ranges[idx])
+  }
+
+  /// Read the primary node that serves the provided slot.
+  pub fn primary(&self, slot: u16) -> Option<&SlotNode> {
+    self.get(slot).map(|range| &range.primary)
+  }
+
+  /// Read the primary node that serves the provided key.
+  pub fn primary_for_key(&self, key: &str) -> Option<&SlotNode> {
+    self.primary(redis_keyslot(key))
+  }
+
+  /// Read the inclusive ranges of slots that are not served by any node.
+  pub fn missing_slots(&self) -> Vec<(u16, u16)> {
+    let mut missing = Vec::new();
+    let mut next = 0_u16;
+
+    for range in self.ranges.iter() {
+      if range.start > next {
+        missing.push((next, range.start - 1));
+      }
+      next = range.end + 1;
+    }
+    if next < REDIS_CLUSTER_SLOTS {
+      missing.push((next, REDIS_CLUSTER_SLOTS - 1));
+    }
+
+    missing
+  }
+
+  /// Whether or not every hash slot is served by a node.
+  pub fn is_covered(&self) -> bool {
+    self.missing_slots().is_empty()
+  }
+
+  /// Return an error describing the first missing slot range if any slots are not served by a node.
+  pub fn validate(&self) -> Result<(), RedisProtocolError> {
+    match self.missing_slots().first() {
+      Some((start, end)) => Err(utils::invalid_response(
+        "cluster response",
+        format!("Slots {}-{} are not covered.", start, end),
+      )),
+      None => Ok(()),
+    }
+  }
+
+  /// Read the ranges of slots where the primary node differs between `self` and a newer slot map.
+  pub fn diff(&self, new: &SlotMap) -> Vec<SlotChange> {
+    let mut changes: Vec<SlotChange> = Vec::new();
+
+    for slot in 0..REDIS_CLUSTER_SLOTS {
+      let old_primary = self.primary(slot);
+      let new_primary = new.primary(slot);
+      if old_primary == new_primary {
+        continue;
+      }
+
+      if let Some(last) = changes.last_mut() {
+        if last.end + 1 == slot && last.old.as_ref() == old_primary && last.new.as_ref() == new_primary {
+          last.end = slot;
+          continue;
+        }
+      }
+      changes.push(SlotChange {
+        start: slot,
+        end: slot,
+        old: old_primary.cloned(),
+        new: new_primary.cloned(),
+      });
+    }
+
+    changes
+  }
+}
+
+fn shard_node_to_slot_node(node: &ClusterNode) -> Result<SlotNode, RedisProtocolError> {
+  let port = node
+    .port
+    .or(node.tls_port)
+    .ok_or_else(|| utils::invalid_response("cluster response", "Missing port."))?;
+
+  Ok(SlotNode {
+    host: node.endpoint.clone(),
+    port,
+    id: Some(node.id.clone()),
+    hostname: node.hostname.clone(),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::cluster::shards::{NodeHealth, NodeRole};
+
+  fn node(port: u16) -> SlotNode {
+    SlotNode {
+      host: "127.0.0.1".into(),
+      port,
+      id: None,
+      hostname: None,
+    }
+  }
+
+  fn range(start: u16, end: u16, port: u16) -> SlotRange {
+    SlotRange {
+      start,
+      end,
+      primary: node(port),
+      replicas: vec![],
+    }
+  }
+
+  #[test]
+  fn should_lookup_slots() {
+    let map = SlotMap::new(vec![range(10923, 16383, 3), range(0, 5460, 1), range(5461, 10922, 2)]).unwrap();
+
+    assert_eq!(map.ranges()[0].start, 0);
+    assert_eq!(map.primary(0).unwrap().port, 1);
+    assert_eq!(map.primary(5460).unwrap().port, 1);
+    assert_eq!(map.primary(5461).unwrap().port, 2);
+    assert_eq!(map.primary(16383).unwrap().port, 3);
+    assert_eq!(map.primary(16384), None);
+    // "foo" hashes to slot 12182
+    assert_eq!(map.primary_for_key("foo").unwrap().port, 3);
+    assert!(map.is_covered());
+    assert!(map.validate().is_ok());
+  }
+
+  #[test]
+  fn should_find_missing_slots() {
+    let map = SlotMap::new(vec![range(100, 200, 1), range(300, 16000, 2)]).unwrap();
+
+    assert_eq!(map.missing_slots(), vec![(0, 99), (201, 299), (16001, 16383)]);
+    assert!(!map.is_covered());
+    assert!(map.validate().is_err());
+    assert_eq!(map.get(250), None);
+    assert_eq!(SlotMap::default().missing_slots(), vec![(0, 16383)]);
+  }
+
+  #[test]
+  fn should_reject_overlapping_ranges() {
+    assert!(SlotMap::new(vec![range(0, 100, 1), range(100, 200, 2)]).is_err());
+  }
+
+  #[test]
+  fn should_reject_invalid_ranges() {
+    assert!(SlotMap::new(vec![range(200, 100, 1)]).is_err());
+    assert!(SlotMap::new(vec![range(0, REDIS_CLUSTER_SLOTS, 1)]).is_err());
+    assert!(SlotMap::new(vec![range(u16::MAX, u16::MAX, 1)]).is_err());
+
+    let map = SlotMap::new(vec![range(100, REDIS_CLUSTER_SLOTS - 1, 1)]).unwrap();
+    assert_eq!(map.missing_slots(), vec![(0, 99)]);
+  }
+
+  #[test]
+  fn should_diff_slot_maps() {
+    let old = SlotMap::new(vec![range(0, 8191, 1), range(8192, 16383, 2)]).unwrap();
+    let new = SlotMap::new(vec![range(0, 8000, 1), range(8001, 8191, 3), range(8192, 16000, 2)]).unwrap();
+
+    assert!(old.diff(&old).is_empty());
+    assert_eq!(old.diff(&new), vec![
+      SlotChange {
+        start: 8001,
+        end: 8191,
+        old: Some(node(1)),
+        new: Some(node(3)),
+      },
+      SlotChange {
+        start: 16001,
+        end: 16383,
+        old: Some(node(2)),
+        new: None,
+      },
+    ]);
+  }
+
+  #[test]
+  fn should_create_slot_map_from_shards() {
+    let shard_node = |id: &str, port: u16, role: NodeRole| ClusterNode {
+      id: id.into(),
+      endpoint: "10.0.0.1".into(),
+      ip: "10.0.0.1".into(),
+      hostname: None,
+      port: Some(port),
+      tls_port: None,
+      role,
+      replication_offset: 0,
+      health: NodeHealth::Online,
+    };
+    let shards = vec![ClusterShard {
+      slots: vec![(0, 100), (200, 300)],
+      nodes: vec![shard_node("b", 2, NodeRole::Replica), shard_node("a", 1, NodeRole::Primary)],
+    }];
+    let map = SlotMap::from_shards(&shards).unwrap();
+
+    assert_eq!(map.ranges().len(), 2);
+    assert_eq!(map.primary(250).unwrap().id.as_deref(), Some("a"));
+    assert_eq!(map.get(50).unwrap().replicas[0].port, 2);
+    assert_eq!(map.primary(150), None);
+  }
+}
@@ -0,0 +1,227 @@
+//! Types and functions for parsing the response to the `CLUSTER SLOTS` command.
+//!
+//! <https://redis.io/commands/cluster-slots>
+
+use crate::resp3::types::Frame;
+use crate::resp3::utils::{frame_to_pairs, pairs_get};
+use crate::types::RedisProtocolError;
+use crate::utils::{self, key_for_slot, REDIS_CLUSTER_SLOTS};
+use std::convert::TryFrom;
+
+/// A node serving a range of hash slots.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SlotNode {
+  /// The preferred endpoint for the node. This is empty when the endpoint is unknown.
+  pub host: String,
+  pub port: u16,
+  /// The node ID, added in Redis 4.0.
+  pub id: Option<String>,
+  /// The node hostname, added in Redis 7.0.
+  pub hostname: Option<String>,
+}
+
+impl SlotNode {
+  /// Whether or not the endpoint is unknown, in which case clients should use the endpoint of the node that sent
+  /// the response.
+  pub fn is_unknown_endpoint(&self) -> bool {
+    self.host.is_empty() || self.host == "?"
+  }
+
+  /// Read the `host:port` string for the node, wrapping IPv6 addresses in brackets.
+  pub fn server(&self) -> String {
+    if self.host.contains(':') {
+      format!("[{}]:{}", self.host, self.port)
+    } else {
+      format!("{}:{}", self.host, self.port)
+    }
+  }
+}
+
+impl TryFrom<&Frame> for SlotNode {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let data = match *frame {
+      Frame::Array { ref data, .. } if data.len() >= 2 => data,
+      _ => {
+        return Err(utils::invalid_response(
+          "cluster response",
+          "Expected node array with at least 2 elements.",
+        ))
+      },
+    };
+    let host = match data[0] {
+      Frame::Null { .. } => String::new(),
+      ref frame => frame
+        .to_string()
+        .ok_or_else(|| utils::invalid_response("cluster response", "Invalid host."))?,
+    };
+    let port = data[1]
+      .as_i64()
+      .filter(|p| *p >= 0 && *p <= u16::MAX as i64)
+      .ok_or_else(|| utils::invalid_response("cluster response", "Invalid port."))? as u16;
+    let id = data.get(2).and_then(|f| f.to_string()).filter(|s| !s.is_empty());
+    let hostname = data
+      .get(3)
+      .and_then(frame_to_pairs)
+      .and_then(|pairs| pairs_get(&pairs, "hostname").and_then(|f| f.to_string()));
+
+    Ok(SlotNode {
+      host,
+      port,
+      id,
+      hostname,
+    })
+  }
+}
+
+/// A range of hash slots and the nodes that serve them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SlotRange {
+  /// The first slot in the range.
+  pub start: u16,
+  /// The last slot in the range, inclusive.
+  pub end: u16,
+  pub primary: SlotNode,
+  pub replicas: Vec<SlotNode>,
+}
+
+impl SlotRange {
+  /// Whether or not the range includes the provided slot.
+  pub fn contains(&self, slot: u16) -> bool {
+    slot >= self.start && slot <= self.end
+  }
+
+  /// The number of slots in the range.
+  pub fn num_slots(&self) -> usize {
+    (self.end - self.start) as usize + 1
+  }
+
+  /// Generate a key in the form `{tag}base` that maps to the first slot in the range.
+  pub fn generate_key(&self, base: &str) -> Option<String> {
+    key_for_slot(base, self.start)
+  }
+}
+
+fn read_slot(frame: &Frame) -> Result<u16, RedisProtocolError> {
+  frame
+    .as_i64()
+    .filter(|s| *s >= 0 && *s < REDIS_CLUSTER_SLOTS as i64)
+    .map(|s| s as u16)
+    .ok_or_else(|| utils::invalid_response("cluster response", "Invalid slot."))
+}
+
+impl TryFrom<&Frame> for SlotRange {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let data = match *frame {
+      Frame::Array { ref data, .. } if data.len() >= 3 => data,
+      _ => {
+        return Err(utils::invalid_response(
+          "cluster response",
+          "Expected slot array with at least 3 elements.",
+        ))
+      },
+    };
+    let start = read_slot(&data[0])?;
+    let end = read_slot(&data[1])?;
+    if end < start {
+      return Err(utils::invalid_response("cluster response", "Invalid slot range."));
+    }
+
+    Ok(SlotRange {
+      start,
+      end,
+      primary: SlotNode::try_from(&data[2])?,
+      replicas: data[3..].iter().map(SlotNode::try_from).collect::<Result<_, _>>()?,
+    })
+  }
+}
+
+/// Parse the response to `CLUSTER SLOTS`.
+pub fn parse_cluster_slots(frame: &Frame) -> Result<Vec<SlotRange>, RedisProtocolError> {
+  match *frame {
+    Frame::Array { ref data, .. } => data.iter().map(SlotRange::try_from).collect(),
+    _ => Err(utils::invalid_response("cluster response", "Expected array.")),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::test_frames::*;
+
+  #[test]
+  fn should_parse_cluster_slots() {
+    let frame = array(vec![
+      array(vec![
+        int(0),
+        int(5460),
+        array(vec![
+          blob("127.0.0.1"),
+          int(30001),
+          blob("09dbe9720cda62f7865eabc5fd8857c5d2678366"),
+        ]),
+        array(vec![
+          blob("127.0.0.1"),
+          int(30004),
+          blob("821d8ca00d7ccf931ed3ffc7e3db0599d2271abf"),
+        ]),
+      ]),
+      array(vec![int(5461), int(16383), array(vec![blob("::1"), int(30002)])]),
+    ]);
+    let slots = parse_cluster_slots(&frame).unwrap();
+
+    assert_eq!(slots.len(), 2);
+    assert_eq!((slots[0].start, slots[0].end, slots[0].num_slots()), (0, 5460, 5461));
+    assert!(slots[0].contains(5460));
+    assert!(!slots[0].contains(5461));
+    assert_eq!(slots[0].primary.server(), "127.0.0.1:30001");
+    assert_eq!(
+      slots[0].primary.id.as_deref(),
+      Some("09dbe9720cda62f7865eabc5fd8857c5d2678366")
+    );
+    assert_eq!(slots[0].replicas[0].port, 30004);
+    assert_eq!(slots[1].primary.server(), "[::1]:30002");
+    assert_eq!(crate::redis_keyslot(&slots[1].generate_key("foo").unwrap()), 5461);
+    assert_eq!(slots[1].primary.id, None);
+    assert!(slots[1].replicas.is_empty());
+  }
+
+  #[test]
+  fn should_parse_cluster_slots_with_metadata() {
+    let frame = array(vec![array(vec![
+      int(0),
+      int(16383),
+      array(vec![
+        Frame::Null { attributes: None },
+        int(30001),
+        blob("09dbe9720cda62f7865eabc5fd8857c5d2678366"),
+        map(vec![("hostname", blob("host-1.redis.example.com"))]),
+      ]),
+    ])]);
+    let slots = parse_cluster_slots(&frame).unwrap();
+
+    assert!(slots[0].primary.is_unknown_endpoint());
+    assert_eq!(slots[0].primary.hostname.as_deref(), Some("host-1.redis.example.com"));
+  }
+
+  #[test]
+  fn should_error_on_invalid_cluster_slots() {
+    assert!(parse_cluster_slots(&array(vec![array(vec![
+      int(0),
+      int(16384),
+      array(vec![blob("a"), int(1)])
+    ])]))
+    .is_err());
+    assert!(parse_cluster_slots(&array(vec![array(vec![
+      int(10),
+      int(5),
+      array(vec![blob("a"), int(1)])
+    ])]))
+    .is_err());
+    assert!(parse_cluster_slots(&array(vec![array(vec![int(0), int(5)])])).is_err());
+    assert!(parse_cluster_slots(&blob("foo")).is_err());
+  }
+}
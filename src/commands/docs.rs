@@ -0,0 +1,348 @@
+//! Types and functions for parsing the response to the `COMMAND DOCS` command.
+//!
+//! <https://redis.io/commands/command-docs>
+
+use crate::resp3::types::Frame;
+use crate::resp3::utils::{frame_to_pairs, frame_to_strings, pairs_get};
+use crate::types::RedisProtocolError;
+use crate::utils;
+use std::convert::TryFrom;
+
+/// The type of a command argument.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ArgumentType {
+  String,
+  Integer,
+  Double,
+  Key,
+  Pattern,
+  UnixTime,
+  PureToken,
+  /// Exactly one of the nested arguments.
+  OneOf,
+  /// A group of nested arguments.
+  Block,
+  /// An argument type not known to this crate.
+  Unknown(String),
+}
+
+impl<'a> From<&'a str> for ArgumentType {
+  fn from(s: &'a str) -> Self {
+    match s {
+      "string" => ArgumentType::String,
+      "integer" => ArgumentType::Integer,
+      "double" => ArgumentType::Double,
+      "key" => ArgumentType::Key,
+      "pattern" => ArgumentType::Pattern,
+      "unix-time" => ArgumentType::UnixTime,
+      "pure-token" => ArgumentType::PureToken,
+      "oneof" => ArgumentType::OneOf,
+      "block" => ArgumentType::Block,
+      _ => ArgumentType::Unknown(s.to_owned()),
+    }
+  }
+}
+
+/// The documentation for one command argument.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommandArgument {
+  pub name: String,
+  pub kind: ArgumentType,
+  pub display_text: Option<String>,
+  /// The index of the associated key spec in the `COMMAND INFO` response, for `key` arguments.
+  pub key_spec_index: Option<i64>,
+  /// The literal token that precedes the argument, if any.
+  pub token: Option<String>,
+  pub summary: Option<String>,
+  pub since: Option<String>,
+  pub deprecated_since: Option<String>,
+  /// The flags on the argument, such as `optional`, `multiple`, or `multiple_token`.
+  pub flags: Vec<String>,
+  /// The nested arguments for `oneof` and `block` arguments.
+  pub arguments: Vec<CommandArgument>,
+}
+
+impl CommandArgument {
+  /// Whether or not the argument has the provided flag, ignoring case.
+  pub fn has_flag(&self, flag: &str) -> bool {
+    self.flags.iter().any(|f| f.eq_ignore_ascii_case(flag))
+  }
+
+  /// Whether or not the argument is optional.
+  pub fn is_optional(&self) -> bool {
+    self.has_flag("optional")
+  }
+
+  /// Whether or not the argument can be repeated.
+  pub fn is_multiple(&self) -> bool {
+    self.has_flag("multiple")
+  }
+}
+
+/// The documentation for a command, as returned by `COMMAND DOCS`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CommandDoc {
+  /// The command name. Subcommands use the `container|subcommand` form.
+  pub name: String,
+  pub summary: Option<String>,
+  pub since: Option<String>,
+  pub group: Option<String>,
+  pub complexity: Option<String>,
+  pub module: Option<String>,
+  pub doc_flags: Vec<String>,
+  pub deprecated_since: Option<String>,
+  pub replaced_by: Option<String>,
+  /// Pairs of versions and the changes made in that version.
+  pub history: Vec<(String, String)>,
+  pub arguments: Vec<CommandArgument>,
+  pub subcommands: Vec<CommandDoc>,
+}
+
+impl CommandDoc {
+  /// Whether or not the command is deprecated.
+  pub fn is_deprecated(&self) -> bool {
+    self.deprecated_since.is_some() || self.doc_flags.iter().any(|f| f.eq_ignore_ascii_case("deprecated"))
+  }
+
+  /// Read a subcommand by name, ignoring case. Both `get` and `config|get` forms are accepted.
+  pub fn subcommand(&self, name: &str) -> Option<&CommandDoc> {
+    self.subcommands.iter().find(|c| {
+      c.name.eq_ignore_ascii_case(name)
+        || c
+          .name
+          .rsplit('|')
+          .next()
+          .map(|n| n.eq_ignore_ascii_case(name))
+          .unwrap_or(false)
+    })
+  }
+}
+
+fn read_optional_string(frame: Option<&Frame>) -> Option<String> {
+  frame.and_then(|f| f.to_string())
+}
+
+fn read_strings(frame: Option<&Frame>, field: &str) -> Result<Vec<String>, RedisProtocolError> {
+  match frame {
+    Some(frame) => frame_to_strings(frame)
+      .ok_or_else(|| utils::invalid_response("COMMAND DOCS response", format!("Invalid {}.", field))),
+    None => Ok(Vec::new()),
+  }
+}
+
+fn read_arguments(frame: Option<&Frame>) -> Result<Vec<CommandArgument>, RedisProtocolError> {
+  match frame {
+    Some(Frame::Array { ref data, .. }) => data.iter().map(CommandArgument::try_from).collect(),
+    Some(_) => Err(utils::invalid_response("COMMAND DOCS response", "Invalid arguments.")),
+    None => Ok(Vec::new()),
+  }
+}
+
+fn read_history(frame: Option<&Frame>) -> Result<Vec<(String, String)>, RedisProtocolError> {
+  let data = match frame {
+    Some(Frame::Array { ref data, .. }) => data,
+    Some(_) => return Err(utils::invalid_response("COMMAND DOCS response", "Invalid history.")),
+    None => return Ok(Vec::new()),
+  };
+
+  data
+    .iter()
+    .map(|entry| match frame_to_strings(entry) {
+      Some(mut entry) if entry.len() == 2 => {
+        let description = entry.pop().unwrap_or_default();
+        let version = entry.pop().unwrap_or_default();
+        Ok((version, description))
+      },
+      _ => Err(utils::invalid_response(
+        "COMMAND DOCS response",
+        "Invalid history entry.",
+      )),
+    })
+    .collect()
+}
+
+impl TryFrom<&Frame> for CommandArgument {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let pairs =
+      frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("COMMAND DOCS response", "Invalid argument."))?;
+    let name = read_optional_string(pairs_get(&pairs, "name"))
+      .ok_or_else(|| utils::invalid_response("COMMAND DOCS response", "Missing argument name."))?;
+    let kind = read_optional_string(pairs_get(&pairs, "type"))
+      .ok_or_else(|| utils::invalid_response("COMMAND DOCS response", "Missing argument type."))?;
+
+    Ok(CommandArgument {
+      name,
+      kind: ArgumentType::from(kind.as_str()),
+      display_text: read_optional_string(pairs_get(&pairs, "display_text")),
+      key_spec_index: pairs_get(&pairs, "key_spec_index").and_then(|f| f.as_i64()),
+      token: read_optional_string(pairs_get(&pairs, "token")),
+      summary: read_optional_string(pairs_get(&pairs, "summary")),
+      since: read_optional_string(pairs_get(&pairs, "since")),
+      deprecated_since: read_optional_string(pairs_get(&pairs, "deprecated_since")),
+      flags: read_strings(pairs_get(&pairs, "flags"), "argument flags")?,
+      arguments: read_arguments(pairs_get(&pairs, "arguments"))?,
+    })
+  }
+}
+
+/// Parse the documentation for one command from its name and the associated map of fields.
+pub fn parse_command_doc(name: &Frame, frame: &Frame) -> Result<CommandDoc, RedisProtocolError> {
+  let name = name
+    .to_string()
+    .ok_or_else(|| utils::invalid_response("COMMAND DOCS response", "Invalid command name."))?;
+  let pairs = frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("COMMAND DOCS response", "Expected map."))?;
+
+  Ok(CommandDoc {
+    name,
+    summary: read_optional_string(pairs_get(&pairs, "summary")),
+    since: read_optional_string(pairs_get(&pairs, "since")),
+    group: read_optional_string(pairs_get(&pairs, "group")),
+    complexity: read_optional_string(pairs_get(&pairs, "complexity")),
+    module: read_optional_string(pairs_get(&pairs, "module")),
+    doc_flags: read_strings(pairs_get(&pairs, "doc_flags"), "doc flags")?,
+    deprecated_since: read_optional_string(pairs_get(&pairs, "deprecated_since")),
+    replaced_by: read_optional_string(pairs_get(&pairs, "replaced_by")),
+    history: read_history(pairs_get(&pairs, "history"))?,
+    arguments: read_arguments(pairs_get(&pairs, "arguments"))?,
+    subcommands: match pairs_get(&pairs, "subcommands") {
+      Some(frame) => parse_command_docs(frame)?,
+      None => Vec::new(),
+    },
+  })
+}
+
+/// Parse the response to `COMMAND DOCS`, as either a RESP3 map or a flat RESP2 array.
+pub fn parse_command_docs(frame: &Frame) -> Result<Vec<CommandDoc>, RedisProtocolError> {
+  let pairs = frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("COMMAND DOCS response", "Expected map."))?;
+
+  pairs
+    .into_iter()
+    .map(|(name, value)| parse_command_doc(name, value))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::test_frames::*;
+
+  fn set_docs() -> Frame {
+    map(vec![
+      ("summary", blob("Sets the string value of a key.")),
+      ("since", blob("1.0.0")),
+      ("group", blob("string")),
+      ("complexity", blob("O(1)")),
+      (
+        "history",
+        array(vec![array(vec![blob("2.6.12"), blob("Added the `EX` and `PX` options.")])]),
+      ),
+      (
+        "arguments",
+        array(vec![
+          map(vec![
+            ("name", blob("key")),
+            ("type", blob("key")),
+            ("display_text", blob("key")),
+            ("key_spec_index", int(0)),
+          ]),
+          map(vec![
+            ("name", blob("condition")),
+            ("type", blob("oneof")),
+            ("flags", array(vec![simple("optional")])),
+            (
+              "arguments",
+              array(vec![
+                map(vec![
+                  ("name", blob("nx")),
+                  ("type", blob("pure-token")),
+                  ("token", blob("NX")),
+                ]),
+                map(vec![
+                  ("name", blob("xx")),
+                  ("type", blob("pure-token")),
+                  ("token", blob("XX")),
+                ]),
+              ]),
+            ),
+          ]),
+        ]),
+      ),
+    ])
+  }
+
+  #[test]
+  fn should_parse_command_docs() {
+    let frame = map(vec![("set", set_docs())]);
+    let docs = parse_command_docs(&frame).unwrap();
+
+    assert_eq!(docs.len(), 1);
+    let doc = &docs[0];
+    assert_eq!(doc.name, "set");
+    assert_eq!(doc.summary.as_deref(), Some("Sets the string value of a key."));
+    assert_eq!(doc.since.as_deref(), Some("1.0.0"));
+    assert_eq!(doc.group.as_deref(), Some("string"));
+    assert_eq!(doc.history, vec![(
+      "2.6.12".to_owned(),
+      "Added the `EX` and `PX` options.".to_owned()
+    )]);
+    assert!(!doc.is_deprecated());
+
+    assert_eq!(doc.arguments.len(), 2);
+    assert_eq!(doc.arguments[0].kind, ArgumentType::Key);
+    assert_eq!(doc.arguments[0].key_spec_index, Some(0));
+    assert_eq!(doc.arguments[1].kind, ArgumentType::OneOf);
+    assert!(doc.arguments[1].is_optional());
+    assert_eq!(doc.arguments[1].arguments[1].token.as_deref(), Some("XX"));
+    assert_eq!(doc.arguments[1].arguments[1].kind, ArgumentType::PureToken);
+  }
+
+  #[test]
+  fn should_parse_flat_command_docs() {
+    let frame = array(vec![
+      blob("getset"),
+      array(vec![
+        blob("summary"),
+        blob("Returns the previous string value of a key after setting it to a new value."),
+        blob("doc_flags"),
+        array(vec![simple("deprecated")]),
+        blob("deprecated_since"),
+        blob("6.2.0"),
+        blob("replaced_by"),
+        blob("`SET` with the `!GET` argument"),
+      ]),
+    ]);
+    let docs = parse_command_docs(&frame).unwrap();
+
+    assert_eq!(docs[0].name, "getset");
+    assert!(docs[0].is_deprecated());
+    assert_eq!(docs[0].replaced_by.as_deref(), Some("`SET` with the `!GET` argument"));
+    assert!(docs[0].arguments.is_empty());
+  }
+
+  #[test]
+  fn should_parse_command_docs_subcommands() {
+    let frame = map(vec![(
+      "config",
+      map(vec![
+        ("summary", blob("A container for server configuration commands.")),
+        ("subcommands", map(vec![("config|get", set_docs())])),
+      ]),
+    )]);
+    let docs = parse_command_docs(&frame).unwrap();
+
+    assert_eq!(docs[0].subcommand("GET").unwrap().name, "config|get");
+  }
+
+  #[test]
+  fn should_error_on_invalid_command_docs() {
+    assert!(parse_command_docs(&array(vec![blob("set")])).is_err());
+    assert!(parse_command_docs(&map(vec![("set", map(vec![("arguments", blob("foo"))]))])).is_err());
+    assert!(parse_command_docs(&map(vec![("set", map(vec![(
+      "arguments",
+      array(vec![map(vec![("name", blob("key"))])])
+    )]))]))
+    .is_err());
+  }
+}
@@ -0,0 +1,551 @@
+//! Types and functions for parsing the response to the `COMMAND` and `COMMAND INFO` commands.
+//!
+//! <https://redis.io/commands/command>
+
+use crate::resp3::types::Frame;
+use crate::resp3::utils::{frame_to_pairs, frame_to_strings, pairs_get};
+use crate::types::RedisProtocolError;
+use crate::utils;
+use std::convert::TryFrom;
+
+/// How to find the first key in the arguments of a command.
+///
+/// <https://redis.io/topics/key-specs>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BeginSearch {
+  /// The first key is at a fixed argument index.
+  Index(i64),
+  /// The first key follows a keyword argument. A negative `start_from` searches backwards from the end of the
+  /// arguments.
+  Keyword { keyword: String, start_from: i64 },
+  /// The keys cannot be found without parsing the command.
+  Unknown,
+}
+
+/// How to find the remaining keys in the arguments of a command, relative to the first key.
+///
+/// <https://redis.io/topics/key-specs>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FindKeys {
+  /// The keys are in a range of arguments. A negative `last_key` is relative to the end of the arguments.
+  Range { last_key: i64, key_step: i64, limit: i64 },
+  /// The number of keys is read from an argument.
+  KeyNum {
+    key_num_idx: i64,
+    first_key: i64,
+    key_step: i64,
+  },
+  /// The keys cannot be found without parsing the command.
+  Unknown,
+}
+
+/// A key specification describing the position of some keys in the arguments of a command.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeySpec {
+  pub notes: Option<String>,
+  pub flags: Vec<String>,
+  pub begin_search: BeginSearch,
+  pub find_keys: FindKeys,
+}
+
+impl KeySpec {
+  /// Read the indexes of the keys matched by this spec, where `args` includes the command name.
+  ///
+  /// Returns `None` if the spec uses an unknown search type.
+  pub fn key_indexes<T: AsRef<[u8]>>(&self, args: &[T]) -> Option<Vec<usize>> {
+    let argc = args.len() as i64;
+    let first = match self.begin_search {
+      BeginSearch::Index(idx) => idx,
+      BeginSearch::Keyword {
+        ref keyword,
+        start_from,
+      } => {
+        let (start, end, step) = if start_from >= 0 {
+          (start_from, argc, 1)
+        } else {
+          (argc + start_from, 0, -1)
+        };
+        let mut idx = start;
+        let mut found = None;
+        while idx >= 0 && idx < argc && (step > 0 || idx > end) {
+          if args[idx as usize].as_ref().eq_ignore_ascii_case(keyword.as_bytes()) {
+            found = Some(idx + 1);
+            break;
+          }
+          idx += step;
+        }
+
+        match found {
+          Some(idx) => idx,
+          None => return Some(Vec::new()),
+        }
+      },
+      BeginSearch::Unknown => return None,
+    };
+
+    let (first, last, step) = match self.find_keys {
+      FindKeys::Range {
+        last_key,
+        key_step,
+        limit,
+      } => {
+        let last = if last_key >= 0 {
+          first + last_key
+        } else if limit <= 1 {
+          argc + last_key
+        } else {
+          first + ((argc - first) / limit + last_key)
+        };
+        (first, last, key_step)
+      },
+      FindKeys::KeyNum {
+        key_num_idx,
+        first_key,
+        key_step,
+      } => {
+        let num_keys = args
+          .get((first + key_num_idx) as usize)
+          .and_then(|arg| std::str::from_utf8(arg.as_ref()).ok())
+          .and_then(|arg| arg.parse::<i64>().ok())
+          .unwrap_or(0);
+        let first = first + first_key;
+        (first, first + (num_keys - 1) * key_step, key_step)
+      },
+      FindKeys::Unknown => return None,
+    };
+
+    Some(range_indexes(first, last, step, argc))
+  }
+}
+
+/// Information about a command, as returned by `COMMAND` or `COMMAND INFO`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommandInfo {
+  /// The lowercase command name. Subcommands use the `container|subcommand` form.
+  pub name: String,
+  /// The number of arguments, including the command name. A negative value means at least `-arity` arguments.
+  pub arity: i64,
+  pub flags: Vec<String>,
+  pub first_key: i64,
+  pub last_key: i64,
+  pub key_step: i64,
+  /// Added in Redis 6.0.
+  pub acl_categories: Vec<String>,
+  /// Added in Redis 7.0.
+  pub tips: Vec<String>,
+  /// Added in Redis 7.0.
+  pub key_specs: Vec<KeySpec>,
+  /// Added in Redis 7.0.
+  pub subcommands: Vec<CommandInfo>,
+}
+
+impl CommandInfo {
+  /// Whether or not the command has the provided flag, ignoring case.
+  pub fn has_flag(&self, flag: &str) -> bool {
+    self.flags.iter().any(|f| f.eq_ignore_ascii_case(flag))
+  }
+
+  /// Whether or not the command may modify data.
+  pub fn is_write(&self) -> bool {
+    self.has_flag("write")
+  }
+
+  /// Whether or not the command only reads data.
+  pub fn is_readonly(&self) -> bool {
+    self.has_flag("readonly")
+  }
+
+  /// Whether or not the position of the keys can only be found by parsing the command.
+  pub fn is_movablekeys(&self) -> bool {
+    self.has_flag("movablekeys")
+  }
+
+  /// Whether or not the provided number of arguments, including the command name, satisfies the command's arity.
+  pub fn check_arity(&self, argc: usize) -> bool {
+    if self.arity >= 0 {
+      argc as i64 == self.arity
+    } else {
+      argc as i64 >= -self.arity
+    }
+  }
+
+  /// Read a subcommand by name, ignoring case. Both `get` and `config|get` forms are accepted.
+  pub fn subcommand(&self, name: &str) -> Option<&CommandInfo> {
+    self.subcommands.iter().find(|c| {
+      c.name.eq_ignore_ascii_case(name)
+        || c
+          .name
+          .rsplit('|')
+          .next()
+          .map(|n| n.eq_ignore_ascii_case(name))
+          .unwrap_or(false)
+    })
+  }
+
+  /// Read the indexes of the keys in `args`, where `args` includes the command name.
+  ///
+  /// The key specs are used when available, otherwise the legacy `first_key`, `last_key`, and `key_step` values are
+  /// used. Returns `None` if the keys cannot be found without parsing the command.
+  pub fn key_indexes<T: AsRef<[u8]>>(&self, args: &[T]) -> Option<Vec<usize>> {
+    if !self.key_specs.is_empty() {
+      let mut out = Vec::new();
+      for spec in self.key_specs.iter() {
+        for idx in spec.key_indexes(args)? {
+          if !out.contains(&idx) {
+            out.push(idx);
+          }
+        }
+      }
+      return Some(out);
+    }
+    if self.is_movablekeys() {
+      return None;
+    }
+    if self.first_key <= 0 {
+      return Some(Vec::new());
+    }
+
+    let argc = args.len() as i64;
+    let last = if self.last_key < 0 {
+      argc + self.last_key
+    } else {
+      self.last_key
+    };
+    Some(range_indexes(self.first_key, last, self.key_step, argc))
+  }
+
+  /// Read the keys in `args`, where `args` includes the command name.
+  pub fn keys<'a, T: AsRef<[u8]>>(&self, args: &'a [T]) -> Option<Vec<&'a [u8]>> {
+    self
+      .key_indexes(args)
+      .map(|indexes| indexes.into_iter().map(|idx| args[idx].as_ref()).collect())
+  }
+}
+
+fn range_indexes(first: i64, last: i64, step: i64, argc: i64) -> Vec<usize> {
+  if step <= 0 || first <= 0 {
+    return Vec::new();
+  }
+
+  let mut out = Vec::new();
+  let mut idx = first;
+  while idx <= last && idx < argc {
+    out.push(idx as usize);
+    idx += step;
+  }
+  out
+}
+
+fn read_i64(frame: Option<&Frame>, field: &str) -> Result<i64, RedisProtocolError> {
+  frame
+    .and_then(|f| f.as_i64())
+    .ok_or_else(|| utils::invalid_response("COMMAND response", format!("Invalid {}.", field)))
+}
+
+fn read_string(frame: Option<&Frame>, field: &str) -> Result<String, RedisProtocolError> {
+  frame
+    .and_then(|f| f.to_string())
+    .ok_or_else(|| utils::invalid_response("COMMAND response", format!("Invalid {}.", field)))
+}
+
+fn read_strings(frame: Option<&Frame>, field: &str) -> Result<Vec<String>, RedisProtocolError> {
+  match frame {
+    Some(frame) => {
+      frame_to_strings(frame).ok_or_else(|| utils::invalid_response("COMMAND response", format!("Invalid {}.", field)))
+    },
+    None => Ok(Vec::new()),
+  }
+}
+
+fn parse_begin_search(frame: &Frame) -> Result<BeginSearch, RedisProtocolError> {
+  let pairs =
+    frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("COMMAND response", "Invalid begin_search."))?;
+  let kind = read_string(pairs_get(&pairs, "type"), "begin_search type")?;
+  let spec = pairs_get(&pairs, "spec").and_then(frame_to_pairs).unwrap_or_default();
+
+  Ok(match kind.as_str() {
+    "index" => BeginSearch::Index(read_i64(pairs_get(&spec, "index"), "index")?),
+    "keyword" => BeginSearch::Keyword {
+      keyword: read_string(pairs_get(&spec, "keyword"), "keyword")?,
+      start_from: read_i64(pairs_get(&spec, "startfrom"), "startfrom")?,
+    },
+    _ => BeginSearch::Unknown,
+  })
+}
+
+fn parse_find_keys(frame: &Frame) -> Result<FindKeys, RedisProtocolError> {
+  let pairs = frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("COMMAND response", "Invalid find_keys."))?;
+  let kind = read_string(pairs_get(&pairs, "type"), "find_keys type")?;
+  let spec = pairs_get(&pairs, "spec").and_then(frame_to_pairs).unwrap_or_default();
+
+  Ok(match kind.as_str() {
+    "range" => FindKeys::Range {
+      last_key: read_i64(pairs_get(&spec, "lastkey"), "lastkey")?,
+      key_step: read_i64(pairs_get(&spec, "keystep"), "keystep")?,
+      limit: read_i64(pairs_get(&spec, "limit"), "limit")?,
+    },
+    "keynum" => FindKeys::KeyNum {
+      key_num_idx: read_i64(pairs_get(&spec, "keynumidx"), "keynumidx")?,
+      first_key: read_i64(pairs_get(&spec, "firstkey"), "firstkey")?,
+      key_step: read_i64(pairs_get(&spec, "keystep"), "keystep")?,
+    },
+    _ => FindKeys::Unknown,
+  })
+}
+
+impl TryFrom<&Frame> for KeySpec {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let pairs =
+      frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("COMMAND response", "Invalid key spec."))?;
+
+    Ok(KeySpec {
+      notes: pairs_get(&pairs, "notes").and_then(|f| f.to_string()),
+      flags: read_strings(pairs_get(&pairs, "flags"), "key spec flags")?,
+      begin_search: match pairs_get(&pairs, "begin_search") {
+        Some(frame) => parse_begin_search(frame)?,
+        None => BeginSearch::Unknown,
+      },
+      find_keys: match pairs_get(&pairs, "find_keys") {
+        Some(frame) => parse_find_keys(frame)?,
+        None => FindKeys::Unknown,
+      },
+    })
+  }
+}
+
+impl TryFrom<&Frame> for CommandInfo {
+  type Error = RedisProtocolError;
+
+  /// Parse one entry in a `COMMAND` or `COMMAND INFO` response.
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let data = match *frame {
+      Frame::Array { ref data, .. } => data,
+      _ => return Err(utils::invalid_response("COMMAND response", "Expected array.")),
+    };
+    if data.len() < 6 {
+      return Err(utils::invalid_response(
+        "COMMAND response",
+        "Expected at least 6 elements.",
+      ));
+    }
+
+    let key_specs = match data.get(8) {
+      Some(Frame::Array { ref data, .. }) => data.iter().map(KeySpec::try_from).collect::<Result<_, _>>()?,
+      _ => Vec::new(),
+    };
+    let subcommands = match data.get(9) {
+      Some(frame) => parse_command_info(frame)?,
+      None => Vec::new(),
+    };
+
+    Ok(CommandInfo {
+      name: read_string(data.first(), "name")?,
+      arity: read_i64(data.get(1), "arity")?,
+      flags: read_strings(data.get(2), "flags")?,
+      first_key: read_i64(data.get(3), "first key")?,
+      last_key: read_i64(data.get(4), "last key")?,
+      key_step: read_i64(data.get(5), "key step")?,
+      acl_categories: read_strings(data.get(6), "ACL categories")?,
+      tips: read_strings(data.get(7), "tips")?,
+      key_specs,
+      subcommands,
+    })
+  }
+}
+
+/// Parse the response to `COMMAND` or `COMMAND INFO`.
+///
+/// Null entries, returned by `COMMAND INFO` for unknown commands, are skipped.
+pub fn parse_command_info(frame: &Frame) -> Result<Vec<CommandInfo>, RedisProtocolError> {
+  match *frame {
+    Frame::Array { ref data, .. } => data
+      .iter()
+      .filter(|f| !matches!(**f, Frame::Null { .. }))
+      .map(CommandInfo::try_from)
+      .collect(),
+    _ => Err(utils::invalid_response("COMMAND response", "Expected array.")),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::test_frames::*;
+
+  fn get_command() -> Frame {
+    array(vec![
+      blob("get"),
+      int(2),
+      array(vec![simple("readonly"), simple("fast")]),
+      int(1),
+      int(1),
+      int(1),
+      array(vec![simple("@read"), simple("@string"), simple("@fast")]),
+      array(vec![]),
+      array(vec![map(vec![
+        ("flags", array(vec![simple("RO"), simple("access")])),
+        (
+          "begin_search",
+          map(vec![("type", simple("index")), ("spec", map(vec![("index", int(1))]))]),
+        ),
+        (
+          "find_keys",
+          map(vec![
+            ("type", simple("range")),
+            (
+              "spec",
+              map(vec![("lastkey", int(0)), ("keystep", int(1)), ("limit", int(0))]),
+            ),
+          ]),
+        ),
+      ])]),
+      array(vec![]),
+    ])
+  }
+
+  fn eval_command() -> Frame {
+    // the RESP2 form, where key specs are flat arrays
+    array(vec![
+      blob("eval"),
+      int(-3),
+      array(vec![simple("noscript"), simple("movablekeys")]),
+      int(0),
+      int(0),
+      int(0),
+      array(vec![simple("@scripting")]),
+      array(vec![]),
+      array(vec![array(vec![
+        simple("flags"),
+        array(vec![simple("RW")]),
+        simple("begin_search"),
+        array(vec![
+          simple("type"),
+          simple("index"),
+          simple("spec"),
+          array(vec![simple("index"), int(2)]),
+        ]),
+        simple("find_keys"),
+        array(vec![
+          simple("type"),
+          simple("keynum"),
+          simple("spec"),
+          array(vec![
+            simple("keynumidx"),
+            int(0),
+            simple("firstkey"),
+            int(1),
+            simple("keystep"),
+            int(1),
+          ]),
+        ]),
+      ])]),
+      array(vec![]),
+    ])
+  }
+
+  #[test]
+  fn should_parse_command_info() {
+    let info = CommandInfo::try_from(&get_command()).unwrap();
+
+    assert_eq!(info.name, "get");
+    assert_eq!(info.arity, 2);
+    assert!(info.is_readonly());
+    assert!(!info.is_write());
+    assert_eq!((info.first_key, info.last_key, info.key_step), (1, 1, 1));
+    assert_eq!(info.acl_categories, vec!["@read", "@string", "@fast"]);
+    assert_eq!(info.key_specs, vec![KeySpec {
+      notes: None,
+      flags: vec!["RO".into(), "access".into()],
+      begin_search: BeginSearch::Index(1),
+      find_keys: FindKeys::Range {
+        last_key: 0,
+        key_step: 1,
+        limit: 0,
+      },
+    }]);
+    assert!(info.check_arity(2));
+    assert!(!info.check_arity(3));
+    assert_eq!(info.keys(&["GET", "foo"]), Some(vec![&b"foo"[..]]));
+  }
+
+  #[test]
+  fn should_parse_flat_key_specs() {
+    let info = CommandInfo::try_from(&eval_command()).unwrap();
+
+    assert!(info.is_movablekeys());
+    assert!(info.check_arity(5));
+    assert_eq!(
+      info.keys(&["EVAL", "return 1", "2", "a", "b", "c"]),
+      Some(vec![&b"a"[..], &b"b"[..]])
+    );
+    assert_eq!(info.keys(&["EVAL", "return 1", "0"]), Some(vec![]));
+  }
+
+  #[test]
+  fn should_parse_legacy_command_reply() {
+    let frame = array(vec![
+      array(vec![
+        blob("mset"),
+        int(-3),
+        array(vec![simple("write"), simple("denyoom")]),
+        int(1),
+        int(-1),
+        int(2),
+      ]),
+      Frame::Null { attributes: None },
+    ]);
+    let commands = parse_command_info(&frame).unwrap();
+
+    assert_eq!(commands.len(), 1);
+    assert!(commands[0].acl_categories.is_empty());
+    assert_eq!(
+      commands[0].key_indexes(&["MSET", "a", "1", "b", "2"]),
+      Some(vec![1, 3])
+    );
+  }
+
+  #[test]
+  fn should_find_keys_with_keyword_search() {
+    let spec = KeySpec {
+      notes: None,
+      flags: vec![],
+      begin_search: BeginSearch::Keyword {
+        keyword: "STREAMS".into(),
+        start_from: 1,
+      },
+      find_keys: FindKeys::Range {
+        last_key: -1,
+        key_step: 1,
+        limit: 2,
+      },
+    };
+    let args = ["XREAD", "COUNT", "2", "STREAMS", "a", "b", "0", "0"];
+
+    assert_eq!(spec.key_indexes(&args), Some(vec![4, 5]));
+  }
+
+  #[test]
+  fn should_parse_subcommands() {
+    let mut container = get_command();
+    if let Frame::Array { ref mut data, .. } = container {
+      data[0] = blob("config");
+      let mut sub = get_command();
+      if let Frame::Array { ref mut data, .. } = sub {
+        data[0] = blob("config|get");
+      }
+      data[9] = array(vec![sub]);
+    }
+    let info = CommandInfo::try_from(&container).unwrap();
+
+    assert_eq!(info.subcommand("GET").unwrap().name, "config|get");
+    assert!(info.subcommand("set").is_none());
+  }
+
+  #[test]
+  fn should_error_on_invalid_command_info() {
+    assert!(CommandInfo::try_from(&array(vec![blob("get"), int(2)])).is_err());
+    assert!(parse_command_info(&int(1)).is_err());
+  }
+}
@@ -0,0 +1,14 @@
+/// Types and functions for parsing `COMMAND` and `COMMAND INFO` responses.
+pub mod info;
+/// Types and functions for parsing `COMMAND DOCS` responses.
+pub mod docs;
+/// Types and functions for routing commands to cluster nodes.
+pub mod routing;
+/// Types and functions for parsing commands sent by clients.
+pub mod request;
+/// A table of command specs for validating commands sent by clients.
+pub mod spec;
+/// Functions for rewriting the keys in commands sent by clients.
+pub mod rewrite;
+/// Functions for redacting credentials from commands sent by clients.
+pub mod redact;
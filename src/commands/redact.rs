@@ -0,0 +1,253 @@
+//! Functions for redacting credentials from commands sent by clients so that they can be logged safely.
+//!
+//! The following arguments are redacted:
+//!
+//! * The password in `AUTH [username] password`.
+//! * The password in `HELLO protover AUTH username password`.
+//! * The password in `MIGRATE ... AUTH password` and `MIGRATE ... AUTH2 username password`.
+//! * The value of credential parameters in `CONFIG SET`, such as `requirepass` or `masterauth`.
+//! * Password rules in `ACL SETUSER`, such as `>password` or `#<sha256>`.
+
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::{Auth, Frame};
+use std::borrow::Cow;
+
+/// The value used in place of redacted arguments.
+pub const REDACTED: &str = "<redacted>";
+
+/// `CONFIG SET` parameters with values that should be redacted.
+const SENSITIVE_CONFIG_PARAMS: &[&str] = &[
+  "requirepass",
+  "masterauth",
+  "tls-key-file-pass",
+  "tls-client-key-file-pass",
+];
+
+fn is(arg: &[u8], name: &str) -> bool {
+  arg.eq_ignore_ascii_case(name.as_bytes())
+}
+
+fn position_from(args: &[&[u8]], start: usize, name: &str) -> Option<usize> {
+  args.iter().skip(start).position(|arg| is(arg, name)).map(|idx| idx + start)
+}
+
+/// Read the indexes of any arguments in a command that contain credentials, where the first argument is the command
+/// name.
+pub(crate) fn sensitive_indexes(args: &[&[u8]]) -> Vec<usize> {
+  let name = match args.first() {
+    Some(name) => *name,
+    None => return Vec::new(),
+  };
+
+  if is(name, "AUTH") {
+    if args.len() > 1 {
+      vec![args.len() - 1]
+    } else {
+      Vec::new()
+    }
+  } else if is(name, "HELLO") {
+    position_from(args, 2, "AUTH")
+      .map(|idx| idx + 2)
+      .filter(|idx| *idx < args.len())
+      .into_iter()
+      .collect()
+  } else if is(name, "MIGRATE") {
+    // options start after `host port key db timeout`, and everything after `KEYS` is a key name
+    let end = position_from(args, 6, "KEYS").unwrap_or(args.len());
+    let options = &args[..end];
+
+    let mut out = Vec::new();
+    if let Some(idx) = position_from(options, 6, "AUTH") {
+      out.push(idx + 1);
+    }
+    if let Some(idx) = position_from(options, 6, "AUTH2") {
+      out.push(idx + 2);
+    }
+    out.retain(|idx| *idx < end);
+    out
+  } else if is(name, "CONFIG") && args.len() > 1 && is(args[1], "SET") {
+    (2..args.len())
+      .step_by(2)
+      .filter(|idx| SENSITIVE_CONFIG_PARAMS.iter().any(|param| is(args[*idx], param)))
+      .map(|idx| idx + 1)
+      .filter(|idx| *idx < args.len())
+      .collect()
+  } else if is(name, "ACL") && args.len() > 1 && is(args[1], "SETUSER") {
+    (3..args.len())
+      .filter(|idx| matches!(args[*idx].first(), Some(b'>') | Some(b'<') | Some(b'#') | Some(b'!')))
+      .collect()
+  } else {
+    Vec::new()
+  }
+}
+
+/// Copy a RESP3 frame, replacing any credentials with [REDACTED].
+///
+/// Commands are redacted according to the rules in the [module docs](self), and the password in `HELLO` frames is
+/// always redacted. All other frames are returned unchanged.
+pub fn redact(frame: &Frame) -> Frame {
+  match *frame {
+    Frame::Array {
+      ref data,
+      ref attributes,
+    } => {
+      let args: Vec<&[u8]> = data
+        .iter()
+        .map(|f| match *f {
+          Frame::BlobString { ref data, .. } => data.as_slice(),
+          Frame::SimpleString { ref data, .. } => data.as_bytes(),
+          _ => &[],
+        })
+        .collect();
+      let indexes = sensitive_indexes(&args);
+
+      let data = data
+        .iter()
+        .enumerate()
+        .map(|(idx, f)| {
+          if indexes.contains(&idx) {
+            Frame::BlobString {
+              data: REDACTED.as_bytes().to_vec(),
+              attributes: None,
+            }
+          } else {
+            f.clone()
+          }
+        })
+        .collect();
+
+      Frame::Array {
+        data,
+        attributes: attributes.clone(),
+      }
+    }
+    Frame::Hello {
+      ref version,
+      auth: Some(ref auth),
+    } => Frame::Hello {
+      version: version.clone(),
+      auth: Some(Auth {
+        username: auth.username.clone(),
+        password: Cow::Borrowed(REDACTED),
+      }),
+    },
+    _ => frame.clone(),
+  }
+}
+
+/// Copy a RESP2 frame, replacing any credentials with [REDACTED].
+///
+/// Commands are redacted according to the rules in the [module docs](self). All other frames are returned unchanged.
+pub fn redact_resp2(frame: &Resp2Frame) -> Resp2Frame {
+  match *frame {
+    Resp2Frame::Array(ref data) => {
+      let args: Vec<&[u8]> = data
+        .iter()
+        .map(|f| match *f {
+          Resp2Frame::BulkString(ref data) => data.as_slice(),
+          Resp2Frame::SimpleString(ref data) => data.as_bytes(),
+          _ => &[],
+        })
+        .collect();
+      let indexes = sensitive_indexes(&args);
+
+      Resp2Frame::Array(
+        data
+          .iter()
+          .enumerate()
+          .map(|(idx, f)| {
+            if indexes.contains(&idx) {
+              Resp2Frame::BulkString(REDACTED.as_bytes().to_vec())
+            } else {
+              f.clone()
+            }
+          })
+          .collect(),
+      )
+    }
+    _ => frame.clone(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::types::RespVersion;
+
+  fn resp2_command(args: &[&[u8]]) -> Resp2Frame {
+    Resp2Frame::Array(args.iter().map(|a| Resp2Frame::BulkString(a.to_vec())).collect())
+  }
+
+  fn assert_redacted(args: &[&[u8]], expected: &[&[u8]]) {
+    assert_eq!(redact_resp2(&resp2_command(args)), resp2_command(expected));
+  }
+
+  #[test]
+  fn should_redact_auth_commands() {
+    let r = REDACTED.as_bytes();
+
+    assert_redacted(&[b"auth", b"foo"], &[b"auth", r]);
+    assert_redacted(&[b"AUTH", b"user", b"foo"], &[b"AUTH", b"user", r]);
+    assert_redacted(&[b"HELLO", b"3", b"AUTH", b"user", b"foo", b"SETNAME", b"bar"], &[
+      b"HELLO", b"3", b"AUTH", b"user", r, b"SETNAME", b"bar",
+    ]);
+    assert_redacted(&[b"HELLO", b"3", b"AUTH", b"user"], &[b"HELLO", b"3", b"AUTH", b"user"]);
+    assert_redacted(
+      &[b"MIGRATE", b"host", b"6379", b"", b"0", b"5000", b"AUTH2", b"user", b"foo", b"KEYS", b"AUTH", b"a"],
+      &[b"MIGRATE", b"host", b"6379", b"", b"0", b"5000", b"AUTH2", b"user", r, b"KEYS", b"AUTH", b"a"],
+    );
+  }
+
+  #[test]
+  fn should_redact_config_and_acl_commands() {
+    let r = REDACTED.as_bytes();
+
+    assert_redacted(&[b"CONFIG", b"SET", b"maxmemory", b"1mb", b"requirepass", b"foo"], &[
+      b"CONFIG",
+      b"SET",
+      b"maxmemory",
+      b"1mb",
+      b"requirepass",
+      r,
+    ]);
+    assert_redacted(&[b"CONFIG", b"GET", b"requirepass"], &[b"CONFIG", b"GET", b"requirepass"]);
+    assert_redacted(&[b"ACL", b"SETUSER", b"bob", b"on", b">foo", b"~*", b"#abc"], &[
+      b"ACL", b"SETUSER", b"bob", b"on", r, b"~*", r,
+    ]);
+    assert_redacted(&[b"GET", b">foo"], &[b"GET", b">foo"]);
+  }
+
+  #[test]
+  fn should_redact_resp3_hello_and_debug_output() {
+    let frame = Frame::Hello {
+      version: RespVersion::RESP3,
+      auth: Some(Auth::from_password("foo")),
+    };
+    assert!(!format!("{:?}", frame).contains("foo"));
+
+    let redacted = redact(&frame);
+    assert_eq!(redacted, Frame::Hello {
+      version: RespVersion::RESP3,
+      auth: Some(Auth {
+        username: "default".into(),
+        password: REDACTED.into(),
+      }),
+    });
+
+    let frame = Frame::Array {
+      data: vec![
+        Frame::SimpleString {
+          data: "AUTH".into(),
+          attributes: None,
+        },
+        Frame::BlobString {
+          data: b"foo".to_vec(),
+          attributes: None,
+        },
+      ],
+      attributes: None,
+    };
+    assert!(!format!("{:?}", redact(&frame)).contains("foo"));
+    assert_eq!(redact(&Frame::from(1)), Frame::from(1));
+  }
+}
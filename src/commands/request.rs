@@ -0,0 +1,237 @@
+//! Types and functions for parsing the commands sent by clients, for use in server implementations.
+//!
+//! <https://redis.io/topics/protocol#sending-commands-to-a-redis-server>
+
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::Frame;
+use crate::types::RedisProtocolError;
+use crate::utils;
+use std::convert::TryFrom;
+use std::str;
+
+/// Commands where the second argument is a subcommand name.
+const CONTAINER_COMMANDS: &[&str] = &[
+  "ACL", "CLIENT", "CLUSTER", "COMMAND", "CONFIG", "FUNCTION", "LATENCY", "MEMORY", "MODULE", "OBJECT", "PUBSUB",
+  "SCRIPT", "SLOWLOG", "XGROUP", "XINFO",
+];
+
+/// A command sent by a client.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedisCommand {
+  /// The command name, in uppercase.
+  pub name: String,
+  /// The subcommand name, in uppercase, for commands such as `CLIENT LIST` or `CONFIG GET`.
+  pub subcommand: Option<String>,
+  /// The arguments after the command and subcommand names.
+  pub args: Vec<Vec<u8>>,
+}
+
+impl RedisCommand {
+  /// Create a command from its arguments, where the first argument is the command name.
+  pub fn from_args(args: Vec<Vec<u8>>) -> Result<Self, RedisProtocolError> {
+    let mut args = args.into_iter();
+    let name = match args.next() {
+      Some(name) => read_name(name)?,
+      None => return Err(utils::invalid_response("command", "Empty command.")),
+    };
+    let mut args: Vec<Vec<u8>> = args.collect();
+
+    let subcommand = if CONTAINER_COMMANDS.contains(&name.as_str()) && !args.is_empty() {
+      Some(read_name(args.remove(0))?)
+    } else {
+      None
+    };
+
+    Ok(RedisCommand { name, subcommand, args })
+  }
+
+  /// Whether or not the command has the provided name, ignoring case.
+  pub fn is(&self, name: &str) -> bool {
+    self.name.eq_ignore_ascii_case(name)
+  }
+
+  /// Whether or not the command has the provided name and subcommand, ignoring case.
+  pub fn is_subcommand(&self, name: &str, subcommand: &str) -> bool {
+    self.is(name)
+      && self
+        .subcommand
+        .as_ref()
+        .map(|s| s.eq_ignore_ascii_case(subcommand))
+        .unwrap_or(false)
+  }
+
+  /// The total number of arguments, including the command and subcommand names.
+  pub fn argc(&self) -> usize {
+    1 + self.subcommand.iter().len() + self.args.len()
+  }
+
+  /// Read the argument at `idx` as a string, if it is valid UTF-8.
+  pub fn arg_str(&self, idx: usize) -> Option<&str> {
+    self.args.get(idx).and_then(|arg| str::from_utf8(arg).ok())
+  }
+
+  /// Read the lowercase command name in the form used by `COMMAND INFO` and ACL rules, such as `get` or
+  /// `client|list`.
+  pub fn full_name(&self) -> String {
+    match self.subcommand {
+      Some(ref subcommand) => format!("{}|{}", self.name, subcommand).to_ascii_lowercase(),
+      None => self.name.to_ascii_lowercase(),
+    }
+  }
+}
+
+/// Whether or not the second argument of the command is a subcommand name, ignoring case.
+pub(crate) fn is_container_command(name: &str) -> bool {
+  CONTAINER_COMMANDS.iter().any(|c| c.eq_ignore_ascii_case(name))
+}
+
+fn read_name(name: Vec<u8>) -> Result<String, RedisProtocolError> {
+  String::from_utf8(name)
+    .map(|name| name.to_ascii_uppercase())
+    .map_err(|_| utils::invalid_response("command", "Invalid command name."))
+}
+
+impl TryFrom<&Frame> for RedisCommand {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let args = match *frame {
+      Frame::Array { ref data, .. } => data
+        .iter()
+        .map(|f| match *f {
+          Frame::BlobString { ref data, .. } => Ok(data.clone()),
+          Frame::SimpleString { ref data, .. } => Ok(data.as_bytes().to_vec()),
+          _ => Err(utils::invalid_response("command", "Expected bulk string arguments.")),
+        })
+        .collect::<Result<Vec<_>, _>>()?,
+      _ => return Err(utils::invalid_response("command", "Expected array.")),
+    };
+
+    RedisCommand::from_args(args)
+  }
+}
+
+impl TryFrom<&Resp2Frame> for RedisCommand {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Resp2Frame) -> Result<Self, Self::Error> {
+    let args = match *frame {
+      Resp2Frame::Array(ref data) => data
+        .iter()
+        .map(|f| match *f {
+          Resp2Frame::BulkString(ref data) => Ok(data.clone()),
+          Resp2Frame::SimpleString(ref data) => Ok(data.as_bytes().to_vec()),
+          _ => Err(utils::invalid_response("command", "Expected bulk string arguments.")),
+        })
+        .collect::<Result<Vec<_>, _>>()?,
+      _ => return Err(utils::invalid_response("command", "Expected array.")),
+    };
+
+    RedisCommand::from_args(args)
+  }
+}
+
+/// Parse a RESP3 frame sent by a client into a command.
+pub fn parse_command(frame: &Frame) -> Result<RedisCommand, RedisProtocolError> {
+  RedisCommand::try_from(frame)
+}
+
+/// Parse a RESP2 frame sent by a client into a command.
+pub fn parse_resp2_command(frame: &Resp2Frame) -> Result<RedisCommand, RedisProtocolError> {
+  RedisCommand::try_from(frame)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn bulk(s: &[u8]) -> Resp2Frame {
+    Resp2Frame::BulkString(s.to_vec())
+  }
+
+  #[test]
+  fn should_parse_command() {
+    let frame = Resp2Frame::Array(vec![bulk(b"set"), bulk(b"foo"), bulk(b"\xff\x00bar")]);
+    let command = parse_resp2_command(&frame).unwrap();
+
+    assert_eq!(command.name, "SET");
+    assert_eq!(command.subcommand, None);
+    assert_eq!(command.args, vec![b"foo".to_vec(), b"\xff\x00bar".to_vec()]);
+    assert!(command.is("Set"));
+    assert_eq!(command.argc(), 3);
+    assert_eq!(command.arg_str(0), Some("foo"));
+    assert_eq!(command.arg_str(1), None);
+    assert_eq!(command.full_name(), "set");
+  }
+
+  #[test]
+  fn should_parse_subcommand() {
+    let frame = Frame::Array {
+      data: vec![
+        Frame::BlobString {
+          data: b"client".to_vec(),
+          attributes: None,
+        },
+        Frame::BlobString {
+          data: b"kill".to_vec(),
+          attributes: None,
+        },
+        Frame::BlobString {
+          data: b"ID".to_vec(),
+          attributes: None,
+        },
+      ],
+      attributes: None,
+    };
+    let command = parse_command(&frame).unwrap();
+
+    assert_eq!(command.subcommand.as_deref(), Some("KILL"));
+    assert!(command.is_subcommand("CLIENT", "kill"));
+    assert_eq!(command.args, vec![b"ID".to_vec()]);
+    assert_eq!(command.argc(), 3);
+    assert_eq!(command.full_name(), "client|kill");
+
+    let command = RedisCommand::from_args(vec![b"CLIENT".to_vec()]).unwrap();
+    assert_eq!(command.subcommand, None);
+    assert_eq!(command.argc(), 1);
+  }
+
+  #[test]
+  fn should_error_on_invalid_command() {
+    assert!(parse_resp2_command(&Resp2Frame::Array(vec![])).is_err());
+    assert!(parse_resp2_command(&Resp2Frame::Array(vec![bulk(b"GET"), Resp2Frame::Integer(1)])).is_err());
+    assert!(parse_resp2_command(&bulk(b"PING")).is_err());
+    assert!(parse_resp2_command(&Resp2Frame::Array(vec![bulk(b"\xff")])).is_err());
+  }
+
+  #[test]
+  fn should_read_command_names_from_frames() {
+    let frame = Resp2Frame::Array(vec![bulk(b"config"), bulk(b"get"), bulk(b"maxmemory")]);
+    assert_eq!(frame.command_name().as_deref(), Some("CONFIG"));
+    assert_eq!(frame.subcommand_name().as_deref(), Some("GET"));
+
+    let frame = Resp2Frame::Array(vec![bulk(b"get"), bulk(b"foo")]);
+    assert_eq!(frame.command_name().as_deref(), Some("GET"));
+    assert_eq!(frame.subcommand_name(), None);
+
+    let frame = Frame::Array {
+      data: vec![
+        Frame::SimpleString {
+          data: "xInfo".into(),
+          attributes: None,
+        },
+        Frame::BlobString {
+          data: b"stream".to_vec(),
+          attributes: None,
+        },
+      ],
+      attributes: None,
+    };
+    assert_eq!(frame.command_name().as_deref(), Some("XINFO"));
+    assert_eq!(frame.subcommand_name().as_deref(), Some("STREAM"));
+
+    assert_eq!(Resp2Frame::Array(vec![bulk(b"client")]).subcommand_name(), None);
+    assert_eq!(Resp2Frame::Array(vec![Resp2Frame::Integer(1)]).command_name(), None);
+    assert_eq!(bulk(b"GET").command_name(), None);
+  }
+}
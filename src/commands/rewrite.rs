@@ -0,0 +1,239 @@
+//! Functions for rewriting the keys in commands sent by clients, such as adding a namespace prefix in a proxy.
+//!
+//! Keys are found with the same table used by [RoutingInfo](crate::commands::routing::RoutingInfo), and every other
+//! argument is left unchanged.
+
+use crate::commands::routing::{key_indexes, requires_keys};
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::Frame;
+use crate::types::RedisProtocolError;
+use crate::utils;
+use std::mem;
+
+fn read_indexes(args: Option<Vec<&[u8]>>) -> Result<Vec<usize>, RedisProtocolError> {
+  let args = args.ok_or_else(|| utils::invalid_response("command", "Expected an array of strings."))?;
+  let indexes = key_indexes(&args).ok_or_else(|| utils::invalid_response("command", "Unknown command."))?;
+
+  // a keyed command without keys is malformed, and rewriting it would silently leave the keys unchanged
+  let name = String::from_utf8_lossy(args[0]).to_ascii_uppercase();
+  if indexes.is_empty() && requires_keys(&name) {
+    return Err(utils::invalid_response("command", "Missing keys."));
+  }
+  Ok(indexes)
+}
+
+/// Call `func` with each key in a RESP3 command, returning the number of keys.
+///
+/// Keys sent as simple strings are converted to blob strings since `func` may not produce valid UTF-8.
+pub fn rewrite_keys<F>(frame: &mut Frame, mut func: F) -> Result<usize, RedisProtocolError>
+where
+  F: FnMut(&mut Vec<u8>),
+{
+  let args = match *frame {
+    Frame::Array { ref mut data, .. } => data,
+    _ => return Err(utils::invalid_response("command", "Expected array.")),
+  };
+  let indexes = read_indexes(
+    args
+      .iter()
+      .map(|f| match *f {
+        Frame::BlobString { ref data, .. } => Some(data.as_slice()),
+        Frame::SimpleString { ref data, .. } => Some(data.as_bytes()),
+        _ => None,
+      })
+      .collect(),
+  )?;
+
+  for idx in indexes.iter() {
+    if let Frame::SimpleString {
+      ref mut data,
+      ref mut attributes,
+    } = args[*idx]
+    {
+      let data = mem::take(data).into_bytes();
+      let attributes = attributes.take();
+      args[*idx] = Frame::BlobString { data, attributes };
+    }
+    if let Frame::BlobString { ref mut data, .. } = args[*idx] {
+      func(data);
+    }
+  }
+
+  Ok(indexes.len())
+}
+
+/// Call `func` with each key in a RESP2 command, returning the number of keys.
+///
+/// Keys sent as simple strings are converted to bulk strings since `func` may not produce valid UTF-8.
+pub fn rewrite_resp2_keys<F>(frame: &mut Resp2Frame, mut func: F) -> Result<usize, RedisProtocolError>
+where
+  F: FnMut(&mut Vec<u8>),
+{
+  let args = match *frame {
+    Resp2Frame::Array(ref mut data) => data,
+    _ => return Err(utils::invalid_response("command", "Expected array.")),
+  };
+  let indexes = read_indexes(
+    args
+      .iter()
+      .map(|f| match *f {
+        Resp2Frame::BulkString(ref data) => Some(data.as_slice()),
+        Resp2Frame::SimpleString(ref data) => Some(data.as_bytes()),
+        _ => None,
+      })
+      .collect(),
+  )?;
+
+  for idx in indexes.iter() {
+    if let Resp2Frame::SimpleString(ref mut data) = args[*idx] {
+      args[*idx] = Resp2Frame::BulkString(mem::take(data).into_bytes());
+    }
+    if let Resp2Frame::BulkString(ref mut data) = args[*idx] {
+      func(data);
+    }
+  }
+
+  Ok(indexes.len())
+}
+
+/// Add `prefix` to each key in a RESP3 command, returning the number of keys.
+pub fn add_key_prefix(frame: &mut Frame, prefix: &[u8]) -> Result<usize, RedisProtocolError> {
+  rewrite_keys(frame, |key| {
+    key.splice(0..0, prefix.iter().cloned());
+  })
+}
+
+/// Remove `prefix` from each key in a RESP3 command that starts with it, returning the number of keys.
+pub fn strip_key_prefix(frame: &mut Frame, prefix: &[u8]) -> Result<usize, RedisProtocolError> {
+  rewrite_keys(frame, |key| {
+    if key.starts_with(prefix) {
+      key.drain(0..prefix.len());
+    }
+  })
+}
+
+/// Add `prefix` to each key in a RESP2 command, returning the number of keys.
+pub fn add_resp2_key_prefix(frame: &mut Resp2Frame, prefix: &[u8]) -> Result<usize, RedisProtocolError> {
+  rewrite_resp2_keys(frame, |key| {
+    key.splice(0..0, prefix.iter().cloned());
+  })
+}
+
+/// Remove `prefix` from each key in a RESP2 command that starts with it, returning the number of keys.
+pub fn strip_resp2_key_prefix(frame: &mut Resp2Frame, prefix: &[u8]) -> Result<usize, RedisProtocolError> {
+  rewrite_resp2_keys(frame, |key| {
+    if key.starts_with(prefix) {
+      key.drain(0..prefix.len());
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn resp2_command(args: &[&[u8]]) -> Resp2Frame {
+    Resp2Frame::Array(args.iter().map(|a| Resp2Frame::BulkString(a.to_vec())).collect())
+  }
+
+  #[test]
+  fn should_add_and_strip_key_prefix() {
+    let mut frame = resp2_command(&[b"MSET", b"a", b"a", b"b", b"\xff"]);
+
+    assert_eq!(add_resp2_key_prefix(&mut frame, b"tenant:").unwrap(), 2);
+    assert_eq!(frame, resp2_command(&[b"MSET", b"tenant:a", b"a", b"tenant:b", b"\xff"]));
+    assert_eq!(strip_resp2_key_prefix(&mut frame, b"tenant:").unwrap(), 2);
+    assert_eq!(frame, resp2_command(&[b"MSET", b"a", b"a", b"b", b"\xff"]));
+  }
+
+  #[test]
+  fn should_rewrite_numkeys_and_streams_commands() {
+    let mut frame = resp2_command(&[b"EVAL", b"return KEYS[1]", b"1", b"foo", b"foo"]);
+    add_resp2_key_prefix(&mut frame, b"ns:").unwrap();
+    assert_eq!(frame, resp2_command(&[b"EVAL", b"return KEYS[1]", b"1", b"ns:foo", b"foo"]));
+
+    let mut frame = resp2_command(&[b"XREAD", b"COUNT", b"1", b"STREAMS", b"a", b"b", b"0", b"0"]);
+    add_resp2_key_prefix(&mut frame, b"ns:").unwrap();
+    assert_eq!(
+      frame,
+      resp2_command(&[b"XREAD", b"COUNT", b"1", b"STREAMS", b"ns:a", b"ns:b", b"0", b"0"])
+    );
+  }
+
+  #[test]
+  fn should_add_key_prefix_to_numkeys_commands() {
+    let cases = [
+      ("ZUNION 2 a b WEIGHTS 1 2", "ZUNION 2 ns:a ns:b WEIGHTS 1 2"),
+      ("ZINTER 1 a", "ZINTER 1 ns:a"),
+      ("ZDIFF 2 a b", "ZDIFF 2 ns:a ns:b"),
+      ("SINTERCARD 1 a LIMIT 1", "SINTERCARD 1 ns:a LIMIT 1"),
+      ("LMPOP 1 a LEFT", "LMPOP 1 ns:a LEFT"),
+      ("ZMPOP 2 a b MIN", "ZMPOP 2 ns:a ns:b MIN"),
+      ("BLMPOP 0 1 a LEFT", "BLMPOP 0 1 ns:a LEFT"),
+      ("BZMPOP 0 2 a b MAX", "BZMPOP 0 2 ns:a ns:b MAX"),
+    ];
+    let split = |s: &'static str| s.split(' ').map(str::as_bytes).collect::<Vec<_>>();
+
+    for (input, expected) in cases.iter() {
+      let (input, expected) = (split(input), split(expected));
+      let mut frame = resp2_command(&input);
+      let keys = input.iter().zip(expected.iter()).filter(|(a, b)| a != b).count();
+
+      assert_eq!(add_resp2_key_prefix(&mut frame, b"ns:").unwrap(), keys);
+      assert_eq!(frame, resp2_command(&expected));
+    }
+  }
+
+  #[test]
+  fn should_rewrite_resp3_simple_string_keys() {
+    let mut frame = Frame::Array {
+      data: vec![
+        Frame::SimpleString {
+          data: "GET".into(),
+          attributes: None,
+        },
+        Frame::SimpleString {
+          data: "foo".into(),
+          attributes: None,
+        },
+      ],
+      attributes: None,
+    };
+
+    assert_eq!(rewrite_keys(&mut frame, |key| key.push(0xff)).unwrap(), 1);
+    assert_eq!(frame, Frame::Array {
+      data: vec![
+        Frame::SimpleString {
+          data: "GET".into(),
+          attributes: None,
+        },
+        Frame::BlobString {
+          data: b"foo\xff".to_vec(),
+          attributes: None,
+        },
+      ],
+      attributes: None,
+    });
+    assert_eq!(strip_key_prefix(&mut frame, b"bar").unwrap(), 1);
+  }
+
+  #[test]
+  fn should_error_rewriting_invalid_commands() {
+    assert!(add_resp2_key_prefix(&mut resp2_command(&[b"FOO", b"bar"]), b"ns:").is_err());
+    assert!(add_resp2_key_prefix(&mut Resp2Frame::Integer(1), b"ns:").is_err());
+    assert!(add_resp2_key_prefix(&mut Resp2Frame::Array(vec![Resp2Frame::Integer(1)]), b"ns:").is_err());
+    assert_eq!(add_resp2_key_prefix(&mut resp2_command(&[b"PING"]), b"ns:").unwrap(), 0);
+    assert_eq!(
+      add_resp2_key_prefix(&mut resp2_command(&[b"EVAL", b"return 1", b"0"]), b"ns:").unwrap(),
+      0
+    );
+    assert_eq!(
+      add_resp2_key_prefix(&mut resp2_command(&[b"OBJECT", b"HELP"]), b"ns:").unwrap(),
+      0
+    );
+
+    assert!(add_resp2_key_prefix(&mut resp2_command(&[b"GET"]), b"ns:").is_err());
+    assert!(add_resp2_key_prefix(&mut resp2_command(&[b"ZUNION", b"x", b"a"]), b"ns:").is_err());
+    assert!(add_resp2_key_prefix(&mut resp2_command(&[b"XREAD", b"COUNT", b"1"]), b"ns:").is_err());
+  }
+}
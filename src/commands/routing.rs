@@ -0,0 +1,491 @@
+//! Types and functions for deciding which cluster nodes should receive a command.
+//!
+//! <https://redis.io/topics/cluster-spec#key-distribution-model>
+
+use crate::commands::info::{BeginSearch, FindKeys, KeySpec};
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::Frame;
+use crate::utils::redis_keyslot_bytes;
+
+/// Where a command should be sent in a cluster.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RoutingInfo {
+  /// The command should be sent to the primary node that serves the slot.
+  SpecificSlot(u16),
+  /// The command does not use any keys and can be sent to any node.
+  AnyNode,
+  /// The command should be sent to every primary node.
+  AllPrimaries,
+  /// The command should be sent to every node.
+  AllNodes,
+  /// The command cannot be routed, such as when its keys map to different slots.
+  Unroutable(String),
+}
+
+/// The position of some keys in the arguments of a command, where the command name is at index 0.
+#[derive(Clone, Copy)]
+enum Keys {
+  /// The keys start at `first` and continue until `last` every `step` arguments. A negative `last` is relative to
+  /// the end of the arguments, otherwise it's relative to `first`.
+  Range { first: i64, last: i64, step: i64 },
+  /// The number of keys is at `index`, and the keys follow it.
+  KeyNum { index: i64 },
+  /// The first half of the arguments after the `STREAMS` keyword.
+  Streams,
+}
+
+impl Keys {
+  fn to_spec(self) -> KeySpec {
+    let (begin_search, find_keys) = match self {
+      Keys::Range { first, last, step } => (BeginSearch::Index(first), FindKeys::Range {
+        last_key: last,
+        key_step: step,
+        limit: 0,
+      }),
+      Keys::KeyNum { index } => (BeginSearch::Index(index), FindKeys::KeyNum {
+        key_num_idx: 0,
+        first_key: 1,
+        key_step: 1,
+      }),
+      Keys::Streams => (
+        BeginSearch::Keyword {
+          keyword: "STREAMS".into(),
+          start_from: 1,
+        },
+        FindKeys::Range {
+          last_key: -1,
+          key_step: 1,
+          limit: 2,
+        },
+      ),
+    };
+
+    KeySpec {
+      notes: None,
+      flags: Vec::new(),
+      begin_search,
+      find_keys,
+    }
+  }
+}
+
+const FIRST: &[Keys] = &[Keys::Range {
+  first: 1,
+  last: 0,
+  step: 1,
+}];
+const FIRST_TWO: &[Keys] = &[Keys::Range {
+  first: 1,
+  last: 1,
+  step: 1,
+}];
+const SECOND: &[Keys] = &[Keys::Range {
+  first: 2,
+  last: 0,
+  step: 1,
+}];
+const ALL: &[Keys] = &[Keys::Range {
+  first: 1,
+  last: -1,
+  step: 1,
+}];
+const ALL_BUT_LAST: &[Keys] = &[Keys::Range {
+  first: 1,
+  last: -2,
+  step: 1,
+}];
+const ALL_AFTER_SECOND: &[Keys] = &[Keys::Range {
+  first: 2,
+  last: -1,
+  step: 1,
+}];
+const PAIRS: &[Keys] = &[Keys::Range {
+  first: 1,
+  last: -1,
+  step: 2,
+}];
+const NUMKEYS_FIRST: &[Keys] = &[Keys::KeyNum { index: 1 }];
+const NUMKEYS_SECOND: &[Keys] = &[Keys::KeyNum { index: 2 }];
+const NUMKEYS_AFTER_TIMEOUT: &[Keys] = &[Keys::KeyNum { index: 2 }];
+const STORE_NUMKEYS: &[Keys] = &[
+  Keys::Range {
+    first: 1,
+    last: 0,
+    step: 1,
+  },
+  Keys::KeyNum { index: 2 },
+];
+const STREAMS: &[Keys] = &[Keys::Streams];
+
+/// Whether or not the command runs a script or function, which can be called without any keys.
+fn is_script_command(name: &str) -> bool {
+  matches!(name, "EVAL" | "EVALSHA" | "EVAL_RO" | "EVALSHA_RO" | "FCALL" | "FCALL_RO")
+}
+
+/// Whether or not the keys of the command follow a subcommand, such as `OBJECT ENCODING`, where some subcommands do
+/// not use keys.
+fn is_container_command(name: &str) -> bool {
+  matches!(name, "OBJECT" | "MEMORY" | "XGROUP" | "XINFO")
+}
+
+/// Whether or not a command must be called with at least one key, where `name` is uppercase.
+pub(crate) fn requires_keys(name: &str) -> bool {
+  key_positions(name).is_some() && !is_script_command(name) && !is_container_command(name)
+}
+
+/// Read the key positions for commands that use keys, where `name` is uppercase.
+///
+/// This covers the common data type commands rather than every command known to the server. Callers that need full
+/// coverage can use the key specs from `COMMAND INFO` instead.
+fn key_positions(name: &str) -> Option<&'static [Keys]> {
+  let keys = match name {
+    "GET" | "SET" | "SETNX" | "SETEX" | "PSETEX" | "GETSET" | "GETDEL" | "GETEX" | "APPEND" | "STRLEN" | "INCR"
+    | "DECR" | "INCRBY" | "DECRBY" | "INCRBYFLOAT" | "GETRANGE" | "SETRANGE" | "GETBIT" | "SETBIT" | "BITCOUNT"
+    | "BITPOS" | "BITFIELD" | "BITFIELD_RO" | "EXPIRE" | "PEXPIRE" | "EXPIREAT" | "PEXPIREAT" | "EXPIRETIME"
+    | "PEXPIRETIME" | "TTL" | "PTTL" | "PERSIST" | "TYPE" | "DUMP" | "RESTORE" | "SORT" | "SORT_RO" | "HGET" | "HSET"
+    | "HSETNX" | "HMSET" | "HMGET" | "HDEL" | "HGETALL" | "HKEYS" | "HVALS" | "HLEN" | "HEXISTS" | "HINCRBY"
+    | "HINCRBYFLOAT" | "HSTRLEN" | "HSCAN" | "HRANDFIELD" | "LPUSH" | "RPUSH" | "LPUSHX" | "RPUSHX" | "LPOP" | "RPOP"
+    | "LLEN" | "LRANGE" | "LINDEX" | "LSET" | "LREM" | "LTRIM" | "LINSERT" | "LPOS" | "SADD" | "SREM" | "SMEMBERS"
+    | "SISMEMBER" | "SMISMEMBER" | "SCARD" | "SPOP" | "SRANDMEMBER" | "SSCAN" | "ZADD" | "ZREM" | "ZSCORE"
+    | "ZMSCORE" | "ZINCRBY" | "ZCARD" | "ZCOUNT" | "ZRANGE" | "ZREVRANGE" | "ZRANGEBYSCORE" | "ZREVRANGEBYSCORE"
+    | "ZRANGEBYLEX" | "ZREVRANGEBYLEX" | "ZRANK" | "ZREVRANK" | "ZREMRANGEBYRANK" | "ZREMRANGEBYSCORE"
+    | "ZREMRANGEBYLEX" | "ZLEXCOUNT" | "ZPOPMIN" | "ZPOPMAX" | "ZSCAN" | "ZRANDMEMBER" | "XADD" | "XLEN" | "XRANGE"
+    | "XREVRANGE" | "XDEL" | "XTRIM" | "XACK" | "XCLAIM" | "XAUTOCLAIM" | "XPENDING" | "XSETID" | "PFADD"
+    | "GEOADD" | "GEODIST" | "GEOHASH" | "GEOPOS" | "GEOSEARCH" | "GEORADIUS" | "GEORADIUS_RO"
+    | "GEORADIUSBYMEMBER" | "GEORADIUSBYMEMBER_RO" | "SPUBLISH" => FIRST,
+    "RENAME" | "RENAMENX" | "COPY" | "SMOVE" | "LMOVE" | "BLMOVE" | "RPOPLPUSH" | "BRPOPLPUSH" | "ZRANGESTORE"
+    | "GEOSEARCHSTORE" => FIRST_TWO,
+    "DEL" | "UNLINK" | "EXISTS" | "TOUCH" | "MGET" | "WATCH" | "SINTER" | "SUNION" | "SDIFF" | "SINTERSTORE"
+    | "SUNIONSTORE" | "SDIFFSTORE" | "PFCOUNT" | "PFMERGE" | "SSUBSCRIBE" | "SUNSUBSCRIBE" => ALL,
+    "BLPOP" | "BRPOP" | "BZPOPMIN" | "BZPOPMAX" => ALL_BUT_LAST,
+    "MSET" | "MSETNX" => PAIRS,
+    "BITOP" => ALL_AFTER_SECOND,
+    name if is_container_command(name) => SECOND,
+    "ZUNION" | "ZINTER" | "ZDIFF" | "ZINTERCARD" | "SINTERCARD" | "LMPOP" | "ZMPOP" => NUMKEYS_FIRST,
+    name if is_script_command(name) => NUMKEYS_SECOND,
+    "BLMPOP" | "BZMPOP" => NUMKEYS_AFTER_TIMEOUT,
+    "ZUNIONSTORE" | "ZINTERSTORE" | "ZDIFFSTORE" => STORE_NUMKEYS,
+    "XREAD" | "XREADGROUP" => STREAMS,
+    _ => return None,
+  };
+
+  Some(keys)
+}
+
+/// Read the routing for commands that do not use keys, where `name` and `subcommand` are uppercase.
+fn keyless_routing(name: &str, subcommand: &str) -> Option<RoutingInfo> {
+  let routing = match (name, subcommand) {
+    ("MULTI", _) | ("EXEC", _) | ("DISCARD", _) => RoutingInfo::Unroutable(
+      "Transaction commands must be routed with the commands in the transaction.".into(),
+    ),
+    ("FLUSHALL", _) | ("FLUSHDB", _) | ("DBSIZE", _) | ("KEYS", _) | ("WAIT", _) => RoutingInfo::AllPrimaries,
+    ("SCRIPT", "LOAD") | ("SCRIPT", "FLUSH") | ("SCRIPT", "EXISTS") | ("SCRIPT", "KILL") => RoutingInfo::AllPrimaries,
+    ("FUNCTION", "LOAD") | ("FUNCTION", "DELETE") | ("FUNCTION", "FLUSH") | ("FUNCTION", "RESTORE")
+    | ("FUNCTION", "KILL") => RoutingInfo::AllPrimaries,
+    ("CONFIG", "SET") | ("CONFIG", "RESETSTAT") | ("CONFIG", "REWRITE") => RoutingInfo::AllNodes,
+    ("ACL", "SETUSER") | ("ACL", "DELUSER") | ("ACL", "LOAD") | ("ACL", "SAVE") => RoutingInfo::AllNodes,
+    ("SLOWLOG", "RESET") | ("LATENCY", "RESET") => RoutingInfo::AllNodes,
+    // messages sent with PUBLISH are propagated to every node by the cluster bus
+    ("PUBLISH", _) | ("SUBSCRIBE", _) | ("PSUBSCRIBE", _) | ("UNSUBSCRIBE", _) | ("PUNSUBSCRIBE", _)
+    | ("PUBSUB", _) => RoutingInfo::AnyNode,
+    ("PING", _) | ("ECHO", _) | ("INFO", _) | ("TIME", _) | ("LASTSAVE", _) | ("CLUSTER", _) | ("COMMAND", _)
+    | ("CLIENT", _) | ("HELLO", _) | ("AUTH", _) | ("SELECT", _) | ("READONLY", _) | ("READWRITE", _)
+    | ("ROLE", _) | ("RANDOMKEY", _) | ("SCAN", _) | ("CONFIG", _) | ("SCRIPT", _) | ("FUNCTION", _) | ("ACL", _)
+    | ("SLOWLOG", _) | ("LATENCY", _) | ("LOLWUT", _) | ("QUIT", _) | ("RESET", _) => RoutingInfo::AnyNode,
+    _ => return None,
+  };
+
+  Some(routing)
+}
+
+/// Read the indexes of the keys in a command, where `args` includes the command name.
+///
+/// This uses the same table as [RoutingInfo::for_args]. Returns `None` if the command is not known, or an empty list
+/// if the command does not use keys.
+pub fn key_indexes<T: AsRef<[u8]>>(args: &[T]) -> Option<Vec<usize>> {
+  let name = String::from_utf8_lossy(args.first()?.as_ref()).to_ascii_uppercase();
+  let subcommand = args
+    .get(1)
+    .map(|s| String::from_utf8_lossy(s.as_ref()).to_ascii_uppercase())
+    .unwrap_or_default();
+
+  if keyless_routing(&name, &subcommand).is_some() {
+    return Some(Vec::new());
+  }
+
+  let mut out = Vec::new();
+  for keys in key_positions(&name)?.iter() {
+    for idx in keys.to_spec().key_indexes(args).unwrap_or_default() {
+      if !out.contains(&idx) {
+        out.push(idx);
+      }
+    }
+  }
+  Some(out)
+}
+
+impl RoutingInfo {
+  /// Read the routing for a command, where `args` includes the command name.
+  pub fn for_args<T: AsRef<[u8]>>(args: &[T]) -> RoutingInfo {
+    let name = match args.first() {
+      Some(name) => String::from_utf8_lossy(name.as_ref()).to_ascii_uppercase(),
+      None => return RoutingInfo::Unroutable("Empty command.".into()),
+    };
+    let subcommand = args
+      .get(1)
+      .map(|s| String::from_utf8_lossy(s.as_ref()).to_ascii_uppercase())
+      .unwrap_or_default();
+
+    if let Some(routing) = keyless_routing(&name, &subcommand) {
+      return routing;
+    }
+    let positions = match key_positions(&name) {
+      Some(positions) => positions,
+      None => return RoutingInfo::Unroutable(format!("Unknown command {}.", name)),
+    };
+
+    let mut slot = None;
+    for keys in positions.iter() {
+      for idx in keys.to_spec().key_indexes(args).unwrap_or_default() {
+        let key_slot = redis_keyslot_bytes(args[idx].as_ref());
+
+        match slot {
+          Some(slot) if slot != key_slot => {
+            return RoutingInfo::Unroutable("Keys in request don't hash to the same slot.".into())
+          },
+          _ => slot = Some(key_slot),
+        }
+      }
+    }
+
+    match slot {
+      Some(slot) => RoutingInfo::SpecificSlot(slot),
+      None => RoutingInfo::AnyNode,
+    }
+  }
+
+  /// Read the routing for a command, which must be an array of strings.
+  pub fn for_command(frame: &Frame) -> RoutingInfo {
+    let args: Option<Vec<&[u8]>> = match *frame {
+      Frame::Array { ref data, .. } => data.iter().map(|f| f.as_bytes()).collect(),
+      _ => None,
+    };
+
+    match args {
+      Some(args) => RoutingInfo::for_args(&args),
+      None => RoutingInfo::Unroutable("Expected an array of strings.".into()),
+    }
+  }
+
+  /// Read the routing for a RESP2 command, which must be an array of strings.
+  pub fn for_resp2_command(frame: &Resp2Frame) -> RoutingInfo {
+    let args: Option<Vec<&[u8]>> = match *frame {
+      Resp2Frame::Array(ref data) => data
+        .iter()
+        .map(|f| match *f {
+          Resp2Frame::BulkString(ref b) => Some(b.as_slice()),
+          Resp2Frame::SimpleString(ref s) => Some(s.as_bytes()),
+          _ => None,
+        })
+        .collect(),
+      _ => None,
+    };
+
+    match args {
+      Some(args) => RoutingInfo::for_args(&args),
+      None => RoutingInfo::Unroutable("Expected an array of strings.".into()),
+    }
+  }
+
+  /// Read the routing for the commands in a transaction, ignoring any `MULTI`, `EXEC`, or `DISCARD` commands.
+  ///
+  /// Every command must use keys in the same slot or no keys at all.
+  pub fn for_transaction<'a, I>(commands: I) -> RoutingInfo
+  where
+    I: IntoIterator<Item = &'a Frame>,
+  {
+    let mut slot = None;
+
+    for command in commands.into_iter() {
+      let is_transaction_command = match *command {
+        Frame::Array { ref data, .. } => data
+          .first()
+          .and_then(|f| f.as_bytes())
+          .map(|name| {
+            name.eq_ignore_ascii_case(b"MULTI")
+              || name.eq_ignore_ascii_case(b"EXEC")
+              || name.eq_ignore_ascii_case(b"DISCARD")
+          })
+          .unwrap_or(false),
+        _ => false,
+      };
+      if is_transaction_command {
+        continue;
+      }
+
+      match RoutingInfo::for_command(command) {
+        RoutingInfo::AnyNode => continue,
+        RoutingInfo::SpecificSlot(command_slot) => match slot {
+          Some(slot) if slot != command_slot => {
+            return RoutingInfo::Unroutable("Commands in transaction don't hash to the same slot.".into())
+          },
+          _ => slot = Some(command_slot),
+        },
+        RoutingInfo::Unroutable(reason) => return RoutingInfo::Unroutable(reason),
+        _ => {
+          return RoutingInfo::Unroutable("Commands sent to multiple nodes cannot be used in a transaction.".into())
+        },
+      }
+    }
+
+    match slot {
+      Some(slot) => RoutingInfo::SpecificSlot(slot),
+      None => RoutingInfo::AnyNode,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::redis_keyslot;
+
+  fn command(args: &[&str]) -> Frame {
+    Frame::Array {
+      data: args
+        .iter()
+        .map(|s| Frame::BlobString {
+          data: s.as_bytes().to_vec(),
+          attributes: None,
+        })
+        .collect(),
+      attributes: None,
+    }
+  }
+
+  fn route(args: &[&str]) -> RoutingInfo {
+    RoutingInfo::for_command(&command(args))
+  }
+
+  #[test]
+  fn should_route_keyed_commands() {
+    let foo = RoutingInfo::SpecificSlot(redis_keyslot("foo"));
+
+    assert_eq!(route(&["get", "foo"]), foo);
+    assert_eq!(route(&["MGET", "{foo}1", "{foo}2"]), foo);
+    assert_eq!(route(&["MSET", "{foo}1", "bar", "{foo}2", "baz"]), foo);
+    assert_eq!(route(&["BLPOP", "foo", "0"]), foo);
+    assert_eq!(route(&["ZUNIONSTORE", "foo", "2", "{foo}a", "{foo}b", "WEIGHTS", "1", "2"]), foo);
+    assert_eq!(route(&["XREAD", "COUNT", "2", "STREAMS", "foo", "{foo}2", "0", "0"]), foo);
+    assert_eq!(route(&["OBJECT", "ENCODING", "foo"]), foo);
+    assert_eq!(route(&["SPUBLISH", "foo", "message"]), foo);
+    assert_eq!(route(&["EVAL", "return 1", "0"]), RoutingInfo::AnyNode);
+  }
+
+  #[test]
+  fn should_not_route_cross_slot_commands() {
+    assert!(matches!(route(&["MGET", "foo", "bar"]), RoutingInfo::Unroutable(_)));
+    assert!(matches!(
+      route(&["MSET", "foo", "1", "bar", "2"]),
+      RoutingInfo::Unroutable(_)
+    ));
+    assert!(matches!(route(&["EVAL", "", "2", "foo", "bar"]), RoutingInfo::Unroutable(_)));
+  }
+
+  #[test]
+  fn should_route_keyless_commands() {
+    assert_eq!(route(&["FLUSHALL"]), RoutingInfo::AllPrimaries);
+    assert_eq!(route(&["script", "load", "return 1"]), RoutingInfo::AllPrimaries);
+    assert_eq!(route(&["SCRIPT", "DEBUG", "NO"]), RoutingInfo::AnyNode);
+    assert_eq!(route(&["CONFIG", "SET", "maxmemory", "0"]), RoutingInfo::AllNodes);
+    assert_eq!(route(&["PUBLISH", "foo", "message"]), RoutingInfo::AnyNode);
+    assert_eq!(route(&["PING"]), RoutingInfo::AnyNode);
+    assert!(matches!(route(&["MULTI"]), RoutingInfo::Unroutable(_)));
+    assert!(matches!(route(&["FOO", "bar"]), RoutingInfo::Unroutable(_)));
+    assert!(matches!(route(&[]), RoutingInfo::Unroutable(_)));
+  }
+
+  #[test]
+  fn should_read_key_indexes() {
+    assert_eq!(key_indexes(&["SET", "foo", "bar"]), Some(vec![1]));
+    assert_eq!(key_indexes(&["MSET", "a", "1", "b", "2"]), Some(vec![1, 3]));
+    assert_eq!(key_indexes(&["ZUNIONSTORE", "out", "2", "a", "b"]), Some(vec![1, 3, 4]));
+    assert_eq!(key_indexes(&["PING"]), Some(vec![]));
+    assert_eq!(key_indexes(&["FOO", "bar"]), None);
+    assert_eq!(key_indexes::<&str>(&[]), None);
+  }
+
+  #[test]
+  fn should_route_numkeys_commands() {
+    let foo = RoutingInfo::SpecificSlot(redis_keyslot("foo"));
+    let is_unroutable = |args: &[&str]| matches!(route(args), RoutingInfo::Unroutable(_));
+
+    for name in ["ZUNION", "ZINTER", "ZDIFF", "ZINTERCARD", "SINTERCARD"].iter() {
+      assert_eq!(route(&[name, "2", "{foo}a", "{foo}b"]), foo);
+      assert!(is_unroutable(&[name, "2", "foo", "bar"]), "{} is unroutable", name);
+    }
+    assert_eq!(route(&["LMPOP", "2", "{foo}a", "{foo}b", "LEFT"]), foo);
+    assert!(is_unroutable(&["ZMPOP", "2", "foo", "bar", "MIN"]));
+
+    assert_eq!(route(&["FCALL", "f", "2", "{foo}a", "{foo}b", "arg"]), foo);
+    assert!(is_unroutable(&["EVALSHA", "abc", "2", "foo", "bar"]));
+
+    assert_eq!(route(&["BLMPOP", "0", "2", "{foo}a", "{foo}b", "LEFT"]), foo);
+    assert!(is_unroutable(&["BZMPOP", "0", "2", "foo", "bar", "MIN"]));
+
+    assert_eq!(route(&["ZINTERSTORE", "foo", "2", "{foo}a", "{foo}b"]), foo);
+    assert!(is_unroutable(&["ZDIFFSTORE", "foo", "1", "bar"]));
+  }
+
+  #[test]
+  fn should_read_numkeys_key_indexes() {
+    assert_eq!(key_indexes(&["ZUNION", "2", "a", "b", "WEIGHTS", "1", "2"]), Some(vec![2, 3]));
+    assert_eq!(key_indexes(&["SINTERCARD", "2", "a", "b", "LIMIT", "1"]), Some(vec![2, 3]));
+    assert_eq!(key_indexes(&["LMPOP", "1", "a", "LEFT"]), Some(vec![2]));
+    assert_eq!(key_indexes(&["EVAL", "return 1", "2", "a", "b", "arg"]), Some(vec![3, 4]));
+    assert_eq!(key_indexes(&["BLMPOP", "0", "2", "a", "b", "LEFT"]), Some(vec![3, 4]));
+    assert_eq!(key_indexes(&["BZMPOP", "0.5", "1", "a", "MAX"]), Some(vec![3]));
+    assert_eq!(key_indexes(&["ZDIFFSTORE", "out", "2", "a", "b"]), Some(vec![1, 3, 4]));
+  }
+
+  #[test]
+  fn should_route_resp2_commands() {
+    let frame = Resp2Frame::Array(vec![
+      Resp2Frame::BulkString(b"GET".to_vec()),
+      Resp2Frame::BulkString(b"foo".to_vec()),
+    ]);
+
+    assert_eq!(
+      RoutingInfo::for_resp2_command(&frame),
+      RoutingInfo::SpecificSlot(redis_keyslot("foo"))
+    );
+    assert!(matches!(
+      RoutingInfo::for_resp2_command(&Resp2Frame::Integer(1)),
+      RoutingInfo::Unroutable(_)
+    ));
+  }
+
+  #[test]
+  fn should_route_transactions() {
+    let same_slot = vec![
+      command(&["MULTI"]),
+      command(&["SET", "{foo}1", "a"]),
+      command(&["PING"]),
+      command(&["INCR", "{foo}2"]),
+      command(&["EXEC"]),
+    ];
+    let cross_slot = vec![command(&["SET", "foo", "a"]), command(&["SET", "bar", "b"])];
+    let broadcast = vec![command(&["SET", "foo", "a"]), command(&["FLUSHALL"])];
+
+    assert_eq!(
+      RoutingInfo::for_transaction(&same_slot),
+      RoutingInfo::SpecificSlot(redis_keyslot("foo"))
+    );
+    assert!(matches!(
+      RoutingInfo::for_transaction(&cross_slot),
+      RoutingInfo::Unroutable(_)
+    ));
+    assert!(matches!(RoutingInfo::for_transaction(&broadcast), RoutingInfo::Unroutable(_)));
+    assert_eq!(RoutingInfo::for_transaction(&[command(&["PING"])]), RoutingInfo::AnyNode);
+  }
+}
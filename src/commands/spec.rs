@@ -0,0 +1,332 @@
+//! A table of command specs and functions for validating commands sent by clients.
+//!
+//! The table is generated from the output of `COMMAND` on Redis 7.2, limited to the commands most commonly
+//! implemented by Redis-compatible servers.
+//!
+//! <https://redis.io/commands/command>
+
+use crate::commands::request::RedisCommand;
+
+/// The arity, flags, and legacy key positions of a command.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CommandSpec {
+  /// The lowercase command name, such as `get` or `client|kill`.
+  pub name: &'static str,
+  /// The number of arguments, including the command name. A negative value is the minimum number of arguments.
+  pub arity: i64,
+  pub flags: &'static [&'static str],
+  pub first_key: i64,
+  /// The last key index. A negative value is relative to the end of the arguments.
+  pub last_key: i64,
+  pub key_step: i64,
+}
+
+impl CommandSpec {
+  /// Whether or not the command has the provided flag.
+  pub fn has_flag(&self, flag: &str) -> bool {
+    self.flags.iter().any(|f| f.eq_ignore_ascii_case(flag))
+  }
+
+  /// Whether or not the command may modify data.
+  pub fn is_write(&self) -> bool {
+    self.has_flag("write")
+  }
+
+  /// Whether or not the command only reads data.
+  pub fn is_readonly(&self) -> bool {
+    self.has_flag("readonly")
+  }
+
+  /// Whether or not `argc` arguments, including the command name, satisfy the arity of the command.
+  pub fn check_arity(&self, argc: usize) -> bool {
+    let argc = argc as i64;
+
+    if self.arity >= 0 {
+      argc == self.arity
+    } else {
+      argc >= -self.arity
+    }
+  }
+}
+
+macro_rules! spec {
+  ($name:expr, $arity:expr, [$($flag:expr),*], $first:expr, $last:expr, $step:expr) => {
+    CommandSpec {
+      name: $name,
+      arity: $arity,
+      flags: &[$($flag),*],
+      first_key: $first,
+      last_key: $last,
+      key_step: $step,
+    }
+  };
+}
+
+/// The command specs, sorted by name.
+#[rustfmt::skip]
+static COMMAND_SPECS: &[CommandSpec] = &[
+  spec!("append", 3, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("auth", -2, ["noscript", "loading", "stale", "fast", "no_auth", "sentinel", "allow_busy"], 0, 0, 0),
+  spec!("blpop", -3, ["write", "blocking"], 1, -2, 1),
+  spec!("brpop", -3, ["write", "blocking"], 1, -2, 1),
+  spec!("client", -2, [], 0, 0, 0),
+  spec!("client|getname", 2, ["noscript", "loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("client|id", 2, ["noscript", "loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("client|info", 2, ["noscript", "loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("client|kill", -3, ["admin", "noscript", "loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("client|list", -2, ["admin", "noscript", "loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("client|setname", 3, ["noscript", "loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("cluster", -2, [], 0, 0, 0),
+  spec!("cluster|info", 2, ["loading", "stale"], 0, 0, 0),
+  spec!("cluster|keyslot", 3, ["stale"], 0, 0, 0),
+  spec!("cluster|nodes", 2, ["loading", "stale"], 0, 0, 0),
+  spec!("cluster|shards", 2, ["loading", "stale"], 0, 0, 0),
+  spec!("cluster|slots", 2, ["loading", "stale"], 0, 0, 0),
+  spec!("command", -1, ["loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("command|count", 2, ["loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("command|docs", -2, ["loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("command|info", -2, ["loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("config", -2, [], 0, 0, 0),
+  spec!("config|get", -3, ["admin", "noscript", "loading", "stale"], 0, 0, 0),
+  spec!("config|set", -4, ["admin", "noscript", "loading", "stale"], 0, 0, 0),
+  spec!("dbsize", 1, ["readonly", "fast"], 0, 0, 0),
+  spec!("decr", 2, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("decrby", 3, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("del", -2, ["write"], 1, -1, 1),
+  spec!("discard", 1, ["noscript", "loading", "stale", "fast", "allow_busy"], 0, 0, 0),
+  spec!("echo", 2, ["fast"], 0, 0, 0),
+  spec!("eval", -3, ["noscript", "stale", "skip_monitor", "may_replicate", "no_mandatory_keys", "movablekeys"],
+    0, 0, 0),
+  spec!("evalsha", -3, ["noscript", "stale", "skip_monitor", "may_replicate", "no_mandatory_keys", "movablekeys"],
+    0, 0, 0),
+  spec!("exec", 1, ["noscript", "loading", "stale", "skip_slowlog"], 0, 0, 0),
+  spec!("exists", -2, ["readonly", "fast"], 1, -1, 1),
+  spec!("expire", -3, ["write", "fast"], 1, 1, 1),
+  spec!("expireat", -3, ["write", "fast"], 1, 1, 1),
+  spec!("flushall", -1, ["write"], 0, 0, 0),
+  spec!("flushdb", -1, ["write"], 0, 0, 0),
+  spec!("get", 2, ["readonly", "fast"], 1, 1, 1),
+  spec!("getdel", 2, ["write", "fast"], 1, 1, 1),
+  spec!("getex", -2, ["write", "fast"], 1, 1, 1),
+  spec!("getrange", 4, ["readonly"], 1, 1, 1),
+  spec!("getset", 3, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("hdel", -3, ["write", "fast"], 1, 1, 1),
+  spec!("hello", -1, ["noscript", "loading", "stale", "fast", "no_auth", "sentinel", "allow_busy"], 0, 0, 0),
+  spec!("hexists", 3, ["readonly", "fast"], 1, 1, 1),
+  spec!("hget", 3, ["readonly", "fast"], 1, 1, 1),
+  spec!("hgetall", 2, ["readonly"], 1, 1, 1),
+  spec!("hincrby", 4, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("hincrbyfloat", 4, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("hkeys", 2, ["readonly"], 1, 1, 1),
+  spec!("hlen", 2, ["readonly", "fast"], 1, 1, 1),
+  spec!("hmget", -3, ["readonly", "fast"], 1, 1, 1),
+  spec!("hmset", -4, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("hscan", -3, ["readonly"], 1, 1, 1),
+  spec!("hset", -4, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("hsetnx", 4, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("hvals", 2, ["readonly"], 1, 1, 1),
+  spec!("incr", 2, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("incrby", 3, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("incrbyfloat", 3, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("info", -1, ["loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("keys", 2, ["readonly"], 0, 0, 0),
+  spec!("lindex", 3, ["readonly"], 1, 1, 1),
+  spec!("llen", 2, ["readonly", "fast"], 1, 1, 1),
+  spec!("lpop", -2, ["write", "fast"], 1, 1, 1),
+  spec!("lpush", -3, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("lrange", 4, ["readonly"], 1, 1, 1),
+  spec!("lrem", 4, ["write"], 1, 1, 1),
+  spec!("lset", 4, ["write", "denyoom"], 1, 1, 1),
+  spec!("ltrim", 4, ["write"], 1, 1, 1),
+  spec!("mget", -2, ["readonly", "fast"], 1, -1, 1),
+  spec!("mset", -3, ["write", "denyoom"], 1, -1, 2),
+  spec!("msetnx", -3, ["write", "denyoom"], 1, -1, 2),
+  spec!("multi", 1, ["noscript", "loading", "stale", "fast", "allow_busy"], 0, 0, 0),
+  spec!("persist", 2, ["write", "fast"], 1, 1, 1),
+  spec!("pexpire", -3, ["write", "fast"], 1, 1, 1),
+  spec!("pexpireat", -3, ["write", "fast"], 1, 1, 1),
+  spec!("ping", -1, ["fast", "sentinel"], 0, 0, 0),
+  spec!("psetex", 4, ["write", "denyoom"], 1, 1, 1),
+  spec!("psubscribe", -2, ["pubsub", "noscript", "loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("pttl", 2, ["readonly", "fast"], 1, 1, 1),
+  spec!("publish", 3, ["pubsub", "loading", "stale", "fast", "may_replicate", "sentinel"], 0, 0, 0),
+  spec!("punsubscribe", -1, ["pubsub", "noscript", "loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("quit", -1, ["allow_busy", "noscript", "loading", "stale", "fast", "no_auth"], 0, 0, 0),
+  spec!("rename", 3, ["write"], 1, 2, 1),
+  spec!("renamenx", 3, ["write", "fast"], 1, 2, 1),
+  spec!("rpop", -2, ["write", "fast"], 1, 1, 1),
+  spec!("rpush", -3, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("sadd", -3, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("scan", -2, ["readonly"], 0, 0, 0),
+  spec!("scard", 2, ["readonly", "fast"], 1, 1, 1),
+  spec!("script", -2, [], 0, 0, 0),
+  spec!("script|exists", -3, ["noscript"], 0, 0, 0),
+  spec!("script|flush", -2, ["noscript"], 0, 0, 0),
+  spec!("script|load", 3, ["noscript", "stale"], 0, 0, 0),
+  spec!("sdiff", -2, ["readonly"], 1, -1, 1),
+  spec!("select", 2, ["loading", "stale", "fast"], 0, 0, 0),
+  spec!("set", -3, ["write", "denyoom"], 1, 1, 1),
+  spec!("setex", 4, ["write", "denyoom"], 1, 1, 1),
+  spec!("setnx", 3, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("setrange", 4, ["write", "denyoom"], 1, 1, 1),
+  spec!("sinter", -2, ["readonly"], 1, -1, 1),
+  spec!("sismember", 3, ["readonly", "fast"], 1, 1, 1),
+  spec!("smembers", 2, ["readonly"], 1, 1, 1),
+  spec!("spop", -2, ["write", "fast"], 1, 1, 1),
+  spec!("srandmember", -2, ["readonly"], 1, 1, 1),
+  spec!("srem", -3, ["write", "fast"], 1, 1, 1),
+  spec!("sscan", -3, ["readonly"], 1, 1, 1),
+  spec!("strlen", 2, ["readonly", "fast"], 1, 1, 1),
+  spec!("subscribe", -2, ["pubsub", "noscript", "loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("sunion", -2, ["readonly"], 1, -1, 1),
+  spec!("time", 1, ["loading", "stale", "fast"], 0, 0, 0),
+  spec!("ttl", 2, ["readonly", "fast"], 1, 1, 1),
+  spec!("type", 2, ["readonly", "fast"], 1, 1, 1),
+  spec!("unlink", -2, ["write", "fast"], 1, -1, 1),
+  spec!("unsubscribe", -1, ["pubsub", "noscript", "loading", "stale", "sentinel"], 0, 0, 0),
+  spec!("unwatch", 1, ["noscript", "loading", "stale", "fast", "allow_busy"], 0, 0, 0),
+  spec!("watch", -2, ["noscript", "loading", "stale", "fast", "allow_busy"], 1, -1, 1),
+  spec!("xadd", -5, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("xlen", 2, ["readonly", "fast"], 1, 1, 1),
+  spec!("xrange", -4, ["readonly"], 1, 1, 1),
+  spec!("xread", -4, ["readonly", "blocking", "movablekeys"], 0, 0, 0),
+  spec!("zadd", -4, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("zcard", 2, ["readonly", "fast"], 1, 1, 1),
+  spec!("zcount", 4, ["readonly", "fast"], 1, 1, 1),
+  spec!("zincrby", 4, ["write", "denyoom", "fast"], 1, 1, 1),
+  spec!("zpopmin", -2, ["write", "fast"], 1, 1, 1),
+  spec!("zrange", -4, ["readonly"], 1, 1, 1),
+  spec!("zrangebyscore", -4, ["readonly"], 1, 1, 1),
+  spec!("zrank", -3, ["readonly", "fast"], 1, 1, 1),
+  spec!("zrem", -3, ["write", "fast"], 1, 1, 1),
+  spec!("zscan", -3, ["readonly"], 1, 1, 1),
+  spec!("zscore", 3, ["readonly", "fast"], 1, 1, 1),
+];
+
+/// Read every command spec in the table, sorted by name.
+pub fn command_specs() -> &'static [CommandSpec] {
+  COMMAND_SPECS
+}
+
+/// Read the spec for a command, where `name` is in the form used by `COMMAND INFO`, such as `get` or `client|kill`.
+pub fn command_spec(name: &str) -> Option<&'static CommandSpec> {
+  let name = name.to_ascii_lowercase();
+
+  COMMAND_SPECS
+    .binary_search_by(|spec| spec.name.cmp(name.as_str()))
+    .ok()
+    .map(|idx| &COMMAND_SPECS[idx])
+}
+
+/// Format an unknown command error, including up to 128 bytes of the arguments.
+fn unknown_command(command: &RedisCommand) -> String {
+  let mut args = String::new();
+
+  for arg in command.subcommand.iter().map(|s| s.as_bytes()).chain(command.args.iter().map(|a| a.as_slice())) {
+    if args.len() >= 128 {
+      break;
+    }
+    let arg = String::from_utf8_lossy(arg);
+    let mut end = arg.len().min(128 - args.len());
+    while !arg.is_char_boundary(end) {
+      end -= 1;
+    }
+
+    args.push('\'');
+    args.push_str(&arg[0..end]);
+    args.push_str("' ");
+  }
+
+  format!(
+    "ERR unknown command '{}', with args beginning with: {}",
+    command.name.to_ascii_lowercase(),
+    args
+  )
+}
+
+/// Check that a command is known and has a valid number of arguments.
+///
+/// The error is the message that Redis returns in the same situation, such as `ERR wrong number of arguments for
+/// 'get' command`, which can be sent to the client as a simple error. Command and subcommand names are reported in
+/// lowercase.
+pub fn validate_command(command: &RedisCommand) -> Result<&'static CommandSpec, String> {
+  let spec = match command_spec(&command.full_name()) {
+    Some(spec) => spec,
+    None => match command.subcommand {
+      Some(ref subcommand) if command_spec(&command.name).is_some() => {
+        return Err(format!(
+          "ERR unknown subcommand '{}'. Try {} HELP.",
+          subcommand.to_ascii_lowercase(),
+          command.name
+        ));
+      },
+      _ => return Err(unknown_command(command)),
+    },
+  };
+
+  if spec.check_arity(command.argc()) {
+    Ok(spec)
+  } else {
+    Err(format!("ERR wrong number of arguments for '{}' command", spec.name))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn command(args: &[&str]) -> RedisCommand {
+    RedisCommand::from_args(args.iter().map(|a| a.as_bytes().to_vec()).collect()).unwrap()
+  }
+
+  #[test]
+  fn should_sort_command_specs() {
+    for pair in command_specs().windows(2) {
+      assert!(pair[0].name < pair[1].name, "{} {}", pair[0].name, pair[1].name);
+    }
+  }
+
+  #[test]
+  fn should_validate_commands() {
+    let spec = validate_command(&command(&["GET", "foo"])).unwrap();
+    assert_eq!(spec.name, "get");
+    assert!(spec.is_readonly());
+    assert_eq!((spec.first_key, spec.last_key, spec.key_step), (1, 1, 1));
+
+    assert!(validate_command(&command(&["set", "foo", "bar", "EX", "10"])).unwrap().is_write());
+    assert_eq!(validate_command(&command(&["client", "kill", "ID", "1"])).unwrap().name, "client|kill");
+    assert_eq!(command_spec("MSET").unwrap().key_step, 2);
+  }
+
+  #[test]
+  fn should_return_redis_error_messages() {
+    assert_eq!(
+      validate_command(&command(&["get"])).unwrap_err(),
+      "ERR wrong number of arguments for 'get' command"
+    );
+    assert_eq!(
+      validate_command(&command(&["client", "kill"])).unwrap_err(),
+      "ERR wrong number of arguments for 'client|kill' command"
+    );
+    assert_eq!(
+      validate_command(&command(&["client"])).unwrap_err(),
+      "ERR wrong number of arguments for 'client' command"
+    );
+    assert_eq!(
+      validate_command(&command(&["foo", "bar", "baz"])).unwrap_err(),
+      "ERR unknown command 'foo', with args beginning with: 'bar' 'baz' "
+    );
+    assert_eq!(
+      validate_command(&command(&["foo"])).unwrap_err(),
+      "ERR unknown command 'foo', with args beginning with: "
+    );
+    assert_eq!(
+      validate_command(&command(&["client", "foo"])).unwrap_err(),
+      "ERR unknown subcommand 'foo'. Try CLIENT HELP."
+    );
+
+    let long = "a".repeat(200);
+    let err = validate_command(&command(&["foo", &long, "b"])).unwrap_err();
+    assert!(err.ends_with(&format!("'{}' ", "a".repeat(128))));
+  }
+}
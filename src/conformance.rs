@@ -0,0 +1,345 @@
+//! A canonical set of RESP2 and RESP3 test vectors, for validating other implementations or FFI bindings against
+//! this crate.
+//!
+//! Each vector is a `(description, bytes, frame)` tuple where `bytes` decodes to `frame`. The bytes are not always the
+//! same as the output of the encoder, such as the RESP2 null array which is encoded as a null bulk string.
+//!
+//! ```
+//! # use redis_protocol::conformance::resp3_vectors;
+//! # use redis_protocol::resp3::decode::complete::decode;
+//! for (description, bytes, frame) in resp3_vectors() {
+//!   assert_eq!(decode(&bytes).unwrap(), Some((frame, bytes.len())), "{}", description);
+//! }
+//! ```
+
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::{Attributes, Frame, VerbatimStringFormat};
+use crate::resp3::utils as resp3_utils;
+
+/// A `(description, bytes, frame)` test vector.
+pub type TestVector<F> = (&'static str, Vec<u8>, F);
+
+fn vector<F>(description: &'static str, bytes: &[u8], frame: F) -> TestVector<F> {
+  (description, bytes.to_vec(), frame)
+}
+
+fn blob(data: &[u8]) -> Frame {
+  Frame::BlobString {
+    data: data.to_vec(),
+    attributes: None,
+  }
+}
+
+fn simple(data: &str) -> Frame {
+  Frame::SimpleString {
+    data: data.into(),
+    attributes: None,
+  }
+}
+
+fn double(data: f64) -> Frame {
+  Frame::Double { data, attributes: None }
+}
+
+fn array(data: Vec<Frame>) -> Frame {
+  Frame::Array { data, attributes: None }
+}
+
+fn map(data: Vec<(Frame, Frame)>) -> Frame {
+  Frame::Map {
+    data: data.into_iter().collect(),
+    attributes: None,
+  }
+}
+
+fn set(data: Vec<Frame>) -> Frame {
+  Frame::Set {
+    data: data.into_iter().collect(),
+    attributes: None,
+  }
+}
+
+fn attributes(data: Vec<(Frame, Frame)>) -> Attributes {
+  let mut out = resp3_utils::new_map(None);
+  out.extend(data);
+  out
+}
+
+/// Read the RESP2 test vectors.
+///
+/// Every frame kind is covered, including empty and binary payloads, integer limits, and both forms of null.
+pub fn resp2_vectors() -> Vec<TestVector<Resp2Frame>> {
+  let bulk = |data: &[u8]| Resp2Frame::BulkString(data.to_vec());
+  let long = vec![b'a'; 1024];
+  let mut long_bytes = b"$1024\r\n".to_vec();
+  long_bytes.extend_from_slice(&long);
+  long_bytes.extend_from_slice(b"\r\n");
+
+  vec![
+    vector("simple string", b"+OK\r\n", Resp2Frame::SimpleString("OK".into())),
+    vector("empty simple string", b"+\r\n", Resp2Frame::SimpleString("".into())),
+    vector(
+      "error",
+      b"-ERR unknown command\r\n",
+      Resp2Frame::Error("ERR unknown command".into()),
+    ),
+    vector("integer", b":1000\r\n", Resp2Frame::Integer(1000)),
+    vector("negative integer", b":-1\r\n", Resp2Frame::Integer(-1)),
+    vector("zero", b":0\r\n", Resp2Frame::Integer(0)),
+    vector("max integer", b":9223372036854775807\r\n", Resp2Frame::Integer(i64::MAX)),
+    vector("min integer", b":-9223372036854775808\r\n", Resp2Frame::Integer(i64::MIN)),
+    vector("bulk string", b"$6\r\nfoobar\r\n", bulk(b"foobar")),
+    vector("empty bulk string", b"$0\r\n\r\n", bulk(b"")),
+    vector("bulk string containing CRLF", b"$4\r\na\r\nb\r\n", bulk(b"a\r\nb")),
+    vector("binary bulk string", b"$3\r\n\x00\xff\x80\r\n", bulk(b"\x00\xff\x80")),
+    ("1024 byte bulk string", long_bytes, Resp2Frame::BulkString(long)),
+    vector("null bulk string", b"$-1\r\n", Resp2Frame::Null),
+    vector("null array", b"*-1\r\n", Resp2Frame::Null),
+    vector("empty array", b"*0\r\n", Resp2Frame::Array(vec![])),
+    vector(
+      "array of bulk strings",
+      b"*2\r\n$3\r\nfoo\r\n$3\r\nbar\r\n",
+      Resp2Frame::Array(vec![bulk(b"foo"), bulk(b"bar")]),
+    ),
+    vector(
+      "array of mixed types",
+      b"*3\r\n:1\r\n+OK\r\n$-1\r\n",
+      Resp2Frame::Array(vec![
+        Resp2Frame::Integer(1),
+        Resp2Frame::SimpleString("OK".into()),
+        Resp2Frame::Null,
+      ]),
+    ),
+    vector(
+      "nested arrays",
+      b"*2\r\n*1\r\n:1\r\n*0\r\n",
+      Resp2Frame::Array(vec![
+        Resp2Frame::Array(vec![Resp2Frame::Integer(1)]),
+        Resp2Frame::Array(vec![]),
+      ]),
+    ),
+  ]
+}
+
+/// Read the RESP3 test vectors for frames that are not streamed.
+///
+/// Every frame kind that can be sent by a server is covered, including empty aggregates, binary payloads, special
+/// doubles, and attributes. `HELLO` frames are not included since they are only sent by clients.
+pub fn resp3_vectors() -> Vec<TestVector<Frame>> {
+  let long = vec![b'a'; 1024];
+  let mut long_bytes = b"$1024\r\n".to_vec();
+  long_bytes.extend_from_slice(&long);
+  long_bytes.extend_from_slice(b"\r\n");
+
+  vec![
+    vector("simple string", b"+OK\r\n", simple("OK")),
+    vector("empty simple string", b"+\r\n", simple("")),
+    vector("simple error", b"-ERR unknown command\r\n", Frame::SimpleError {
+      data: "ERR unknown command".into(),
+      attributes: None,
+    }),
+    vector("blob string", b"$11\r\nhello world\r\n", blob(b"hello world")),
+    vector("empty blob string", b"$0\r\n\r\n", blob(b"")),
+    vector("blob string containing CRLF", b"$4\r\na\r\nb\r\n", blob(b"a\r\nb")),
+    vector("binary blob string", b"$3\r\n\x00\xff\x80\r\n", blob(b"\x00\xff\x80")),
+    ("1024 byte blob string", long_bytes, Frame::BlobString {
+      data: long,
+      attributes: None,
+    }),
+    vector("blob error", b"!21\r\nSYNTAX invalid syntax\r\n", Frame::BlobError {
+      data: b"SYNTAX invalid syntax".to_vec(),
+      attributes: None,
+    }),
+    vector("number", b":1234\r\n", Frame::from(1234)),
+    vector("negative number", b":-1\r\n", Frame::from(-1)),
+    vector("max number", b":9223372036854775807\r\n", Frame::from(i64::MAX)),
+    vector("min number", b":-9223372036854775808\r\n", Frame::from(i64::MIN)),
+    vector("double", b",1.23\r\n", double(1.23)),
+    vector("integral double", b",10\r\n", double(10.0)),
+    vector("negative double", b",-0.5\r\n", double(-0.5)),
+    vector("exponent double", b",1.5e-10\r\n", double(1.5e-10)),
+    vector("infinite double", b",inf\r\n", double(f64::INFINITY)),
+    vector("negative infinite double", b",-inf\r\n", double(f64::NEG_INFINITY)),
+    vector("true", b"#t\r\n", Frame::from(true)),
+    vector("false", b"#f\r\n", Frame::from(false)),
+    vector("null", b"_\r\n", Frame::Null { attributes: None }),
+    vector(
+      "big number",
+      b"(3492890328409238509324850943850943825024385\r\n",
+      Frame::BigNumber {
+        data: b"3492890328409238509324850943850943825024385".to_vec(),
+        attributes: None,
+      },
+    ),
+    vector(
+      "negative big number",
+      b"(-3492890328409238509324850943850943825024385\r\n",
+      Frame::BigNumber {
+        data: b"-3492890328409238509324850943850943825024385".to_vec(),
+        attributes: None,
+      },
+    ),
+    vector("text verbatim string", b"=15\r\ntxt:Some string\r\n", Frame::VerbatimString {
+      data: b"Some string".to_vec(),
+      format: VerbatimStringFormat::Text,
+      attributes: None,
+    }),
+    vector("markdown verbatim string", b"=9\r\nmkd:# foo\r\n", Frame::VerbatimString {
+      data: b"# foo".to_vec(),
+      format: VerbatimStringFormat::Markdown,
+      attributes: None,
+    }),
+    vector("empty array", b"*0\r\n", array(vec![])),
+    vector(
+      "array of mixed types",
+      b"*3\r\n:1\r\n$3\r\nfoo\r\n_\r\n",
+      array(vec![Frame::from(1), blob(b"foo"), Frame::Null { attributes: None }]),
+    ),
+    vector(
+      "nested arrays",
+      b"*2\r\n*1\r\n:1\r\n*0\r\n",
+      array(vec![array(vec![Frame::from(1)]), array(vec![])]),
+    ),
+    vector("empty map", b"%0\r\n", map(vec![])),
+    vector(
+      "map",
+      b"%2\r\n+first\r\n:1\r\n+second\r\n:2\r\n",
+      map(vec![(simple("first"), Frame::from(1)), (simple("second"), Frame::from(2))]),
+    ),
+    vector(
+      "map with aggregate key",
+      b"%1\r\n*1\r\n:1\r\n#t\r\n",
+      map(vec![(array(vec![Frame::from(1)]), Frame::from(true))]),
+    ),
+    vector("empty set", b"~0\r\n", set(vec![])),
+    vector(
+      "set",
+      b"~3\r\n+a\r\n:1\r\n#f\r\n",
+      set(vec![simple("a"), Frame::from(1), Frame::from(false)]),
+    ),
+    vector(
+      "push",
+      b">3\r\n+message\r\n+channel\r\n$5\r\nhello\r\n",
+      Frame::Push {
+        data: vec![simple("message"), simple("channel"), blob(b"hello")],
+        attributes: None,
+      },
+    ),
+    vector(
+      "attributes on a scalar",
+      b"|1\r\n+ttl\r\n:3600\r\n$3\r\nfoo\r\n",
+      Frame::BlobString {
+        data: b"foo".to_vec(),
+        attributes: Some(attributes(vec![(simple("ttl"), Frame::from(3600))])),
+      },
+    ),
+    vector(
+      "attributes on an aggregate",
+      b"|1\r\n+key-popularity\r\n%1\r\n$1\r\na\r\n,0.1923\r\n*1\r\n:1\r\n",
+      Frame::Array {
+        data: vec![Frame::from(1)],
+        attributes: Some(attributes(vec![(
+          simple("key-popularity"),
+          map(vec![(blob(b"a"), double(0.1923))]),
+        )])),
+      },
+    ),
+    vector(
+      "attributes on an inner frame",
+      b"*2\r\n:1\r\n|1\r\n+ttl\r\n:60\r\n:2\r\n",
+      array(vec![Frame::from(1), Frame::Number {
+        data: 2,
+        attributes: Some(attributes(vec![(simple("ttl"), Frame::from(60))])),
+      }]),
+    ),
+  ]
+}
+
+/// Read the RESP3 test vectors for streamed frames.
+///
+/// The bytes in each vector contain one streamed blob string or aggregate, and the frame is the result of combining
+/// the decoded chunks with [StreamedFrame::into_frame](crate::resp3::types::StreamedFrame::into_frame).
+pub fn resp3_streamed_vectors() -> Vec<TestVector<Frame>> {
+  vec![
+    vector(
+      "streamed blob string",
+      b"$?\r\n;4\r\nHell\r\n;6\r\no worl\r\n;1\r\nd\r\n;0\r\n",
+      blob(b"Hello world"),
+    ),
+    vector("empty streamed blob string", b"$?\r\n;0\r\n", blob(b"")),
+    vector(
+      "streamed array",
+      b"*?\r\n:1\r\n$3\r\nfoo\r\n.\r\n",
+      array(vec![Frame::from(1), blob(b"foo")]),
+    ),
+    vector("empty streamed array", b"*?\r\n.\r\n", array(vec![])),
+    vector(
+      "streamed map",
+      b"%?\r\n+a\r\n:1\r\n+b\r\n:2\r\n.\r\n",
+      map(vec![(simple("a"), Frame::from(1)), (simple("b"), Frame::from(2))]),
+    ),
+    vector(
+      "streamed set",
+      b"~?\r\n+a\r\n:1\r\n.\r\n",
+      set(vec![simple("a"), Frame::from(1)]),
+    ),
+  ]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp2::decode::decode as resp2_decode;
+  use crate::resp2::encode::encode_bytes as resp2_encode_bytes;
+  use crate::resp3::decode::{complete, streaming};
+  use crate::resp3::encode::complete::encode_bytes;
+  use crate::resp3::types::{DecodedFrame, StreamedFrame};
+  use bytes::BytesMut;
+
+  #[test]
+  fn should_decode_resp2_vectors() {
+    for (description, bytes, frame) in resp2_vectors() {
+      assert_eq!(resp2_decode(&bytes).unwrap(), Some((frame.clone(), bytes.len())), "{}", description);
+
+      let mut buf = BytesMut::new();
+      resp2_encode_bytes(&mut buf, &frame).unwrap();
+      assert_eq!(resp2_decode(&buf).unwrap(), Some((frame, buf.len())), "{}", description);
+    }
+  }
+
+  #[test]
+  fn should_decode_resp3_vectors() {
+    for (description, bytes, frame) in resp3_vectors() {
+      let (decoded, len) = complete::decode(&bytes).unwrap().unwrap();
+      assert_eq!(decoded, frame, "{}", description);
+      assert_eq!(decoded.attributes(), frame.attributes(), "{}", description);
+      assert_eq!(len, bytes.len(), "{}", description);
+
+      let mut buf = BytesMut::new();
+      encode_bytes(&mut buf, &frame).unwrap();
+      assert_eq!(complete::decode(&buf).unwrap(), Some((frame, buf.len())), "{}", description);
+    }
+  }
+
+  #[test]
+  fn should_decode_resp3_streamed_vectors() {
+    for (description, bytes, frame) in resp3_streamed_vectors() {
+      let mut streamed: Option<StreamedFrame> = None;
+      let mut offset = 0;
+
+      while offset < bytes.len() {
+        let (decoded, len) = streaming::decode(&bytes[offset..]).unwrap().unwrap();
+        offset += len;
+
+        match decoded {
+          DecodedFrame::Streaming(inner) => streamed = Some(inner),
+          DecodedFrame::Complete(inner) => streamed.as_mut().unwrap().add_frame(inner).unwrap(),
+        }
+      }
+
+      let mut streamed = streamed.unwrap();
+      assert!(streamed.is_finished(), "{}", description);
+      assert_eq!(streamed.into_frame().unwrap(), frame, "{}", description);
+    }
+  }
+}
@@ -0,0 +1,591 @@
+//! Traits for converting between frames and other value types.
+//!
+//! Client libraries can implement these traits for their own value types so that callers can move between frames and
+//! those types without this crate depending on the client library, or vice versa.
+//!
+//! The `FromResp*Frame` traits construct `Self` and so cannot be used as trait objects. The `IntoResp*Frame` traits
+//! are object-safe, so values of different types can be stored as `Box<dyn IntoResp3Frame>` and converted by
+//! reference. Implementations only need to provide the by-reference method, but can override the by-value method to
+//! avoid copying any data.
+//!
+//! ```
+//! # use redis_protocol::convert::{FromResp3Frame, IntoResp3Frame};
+//! # use redis_protocol::resp3::types::Frame;
+//! # use redis_protocol::types::RedisProtocolError;
+//! enum Value {
+//!   Int(i64),
+//!   Data(Vec<u8>),
+//! }
+//!
+//! impl FromResp3Frame for Value {
+//!   fn from_resp3_frame(frame: Frame) -> Result<Self, RedisProtocolError> {
+//!     match frame {
+//!       Frame::Number { data, .. } => Ok(Value::Int(data)),
+//!       frame => Vec::<u8>::from_resp3_frame(frame).map(Value::Data),
+//!     }
+//!   }
+//! }
+//!
+//! impl IntoResp3Frame for Value {
+//!   fn to_resp3_frame(&self) -> Result<Frame, RedisProtocolError> {
+//!     match *self {
+//!       Value::Int(ref i) => i.to_resp3_frame(),
+//!       Value::Data(ref d) => d.to_resp3_frame(),
+//!     }
+//!   }
+//! }
+//!
+//! let values: Vec<Value> = Vec::from_resp3_frame(Frame::Array {
+//!   data: vec![Frame::from(1)],
+//!   attributes: None,
+//! })
+//! .unwrap();
+//! assert_eq!(values.to_resp3_frame().unwrap(), Frame::Array {
+//!   data: vec![Frame::from(1)],
+//!   attributes: None,
+//! });
+//! ```
+
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::Frame as Resp3Frame;
+use crate::types::{RedisProtocolError, RedisProtocolErrorKind};
+use crate::utils;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str;
+
+fn cannot_convert<K: fmt::Display>(kind: K, target: &str) -> RedisProtocolError {
+  RedisProtocolError::new(
+    RedisProtocolErrorKind::Unknown,
+    format!("Cannot convert {} to {}.", kind, target),
+  )
+}
+
+fn invalid_utf8() -> RedisProtocolError {
+  RedisProtocolError::new(RedisProtocolErrorKind::Unknown, "Cannot convert non UTF-8 string to String.")
+}
+
+/// Convert each element with `func`, adding the index of the element to the first error.
+fn convert_elements<I, T, F>(elements: I, func: F) -> Result<Vec<T>, RedisProtocolError>
+where
+  I: IntoIterator,
+  F: Fn(I::Item) -> Result<T, RedisProtocolError>,
+{
+  elements
+    .into_iter()
+    .enumerate()
+    .map(|(idx, element)| func(element).map_err(|e| e.with_context(format_args!("Element {}", idx))))
+    .collect()
+}
+
+/// A type that can be created from a RESP3 frame.
+pub trait FromResp3Frame: Sized {
+  /// Convert the frame to `Self`.
+  fn from_resp3_frame(frame: Resp3Frame) -> Result<Self, RedisProtocolError>;
+}
+
+/// A type that can be created from a RESP2 frame.
+pub trait FromResp2Frame: Sized {
+  /// Convert the frame to `Self`.
+  fn from_resp2_frame(frame: Resp2Frame) -> Result<Self, RedisProtocolError>;
+}
+
+/// A type that can be converted to a RESP3 frame.
+pub trait IntoResp3Frame {
+  /// Convert a reference to the value to a frame.
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError>;
+
+  /// Convert the value to a frame.
+  fn into_resp3_frame(self) -> Result<Resp3Frame, RedisProtocolError>
+  where
+    Self: Sized,
+  {
+    self.to_resp3_frame()
+  }
+}
+
+/// A type that can be converted to a RESP2 frame.
+pub trait IntoResp2Frame {
+  /// Convert a reference to the value to a frame.
+  fn to_resp2_frame(&self) -> Result<Resp2Frame, RedisProtocolError>;
+
+  /// Convert the value to a frame.
+  fn into_resp2_frame(self) -> Result<Resp2Frame, RedisProtocolError>
+  where
+    Self: Sized,
+  {
+    self.to_resp2_frame()
+  }
+}
+
+impl FromResp3Frame for Resp3Frame {
+  fn from_resp3_frame(frame: Resp3Frame) -> Result<Self, RedisProtocolError> {
+    Ok(frame)
+  }
+}
+
+impl FromResp3Frame for Resp2Frame {
+  fn from_resp3_frame(frame: Resp3Frame) -> Result<Self, RedisProtocolError> {
+    utils::resp3_frame_to_resp2(frame)
+  }
+}
+
+impl FromResp3Frame for String {
+  fn from_resp3_frame(frame: Resp3Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp3Frame::SimpleString { data, .. } => data.into_string().map_err(|_| invalid_utf8()),
+      Resp3Frame::BlobString { data, .. } | Resp3Frame::VerbatimString { data, .. } => {
+        String::from_utf8(data).map_err(|_| invalid_utf8())
+      }
+      Resp3Frame::Number { .. } | Resp3Frame::Double { .. } | Resp3Frame::BigNumber { .. } => {
+        frame.to_string().ok_or_else(|| cannot_convert(frame.kind(), "String"))
+      }
+      _ => Err(cannot_convert(frame.kind(), "String")),
+    }
+  }
+}
+
+impl FromResp3Frame for Vec<u8> {
+  fn from_resp3_frame(frame: Resp3Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp3Frame::SimpleString { data, .. } => Ok(data.into_bytes()),
+      Resp3Frame::BlobString { data, .. } | Resp3Frame::VerbatimString { data, .. } => Ok(data),
+      Resp3Frame::Number { .. } | Resp3Frame::Double { .. } | Resp3Frame::BigNumber { .. } => frame
+        .to_string()
+        .map(|s| s.into_bytes())
+        .ok_or_else(|| cannot_convert(frame.kind(), "bytes")),
+      _ => Err(cannot_convert(frame.kind(), "bytes")),
+    }
+  }
+}
+
+impl FromResp3Frame for i64 {
+  fn from_resp3_frame(frame: Resp3Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp3Frame::Number { data, .. } => Ok(data),
+      Resp3Frame::SimpleString { .. } | Resp3Frame::BlobString { .. } => {
+        frame.as_i64().ok_or_else(|| cannot_convert(frame.kind(), "i64"))
+      }
+      _ => Err(cannot_convert(frame.kind(), "i64")),
+    }
+  }
+}
+
+impl FromResp3Frame for f64 {
+  fn from_resp3_frame(frame: Resp3Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp3Frame::Double { .. }
+      | Resp3Frame::Number { .. }
+      | Resp3Frame::SimpleString { .. }
+      | Resp3Frame::BlobString { .. } => frame.as_f64().ok_or_else(|| cannot_convert(frame.kind(), "f64")),
+      _ => Err(cannot_convert(frame.kind(), "f64")),
+    }
+  }
+}
+
+impl FromResp3Frame for bool {
+  fn from_resp3_frame(frame: Resp3Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp3Frame::Boolean { data, .. } => Ok(data),
+      Resp3Frame::Number { data: 0, .. } => Ok(false),
+      Resp3Frame::Number { data: 1, .. } => Ok(true),
+      _ => Err(cannot_convert(frame.kind(), "bool")),
+    }
+  }
+}
+
+impl<T: FromResp3Frame> FromResp3Frame for Option<T> {
+  fn from_resp3_frame(frame: Resp3Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp3Frame::Null { .. } => Ok(None),
+      frame => T::from_resp3_frame(frame).map(Some),
+    }
+  }
+}
+
+impl<T: FromResp3Frame> FromResp3Frame for Vec<T> {
+  fn from_resp3_frame(frame: Resp3Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp3Frame::Array { data, .. } | Resp3Frame::Push { data, .. } => convert_elements(data, T::from_resp3_frame),
+      Resp3Frame::Set { data, .. } => convert_elements(data, T::from_resp3_frame),
+      _ => Err(cannot_convert(frame.kind(), "Vec")),
+    }
+  }
+}
+
+impl FromResp2Frame for Resp2Frame {
+  fn from_resp2_frame(frame: Resp2Frame) -> Result<Self, RedisProtocolError> {
+    Ok(frame)
+  }
+}
+
+impl FromResp2Frame for Resp3Frame {
+  fn from_resp2_frame(frame: Resp2Frame) -> Result<Self, RedisProtocolError> {
+    Ok(utils::resp2_frame_to_resp3(frame))
+  }
+}
+
+impl FromResp2Frame for String {
+  fn from_resp2_frame(frame: Resp2Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp2Frame::SimpleString(data) => Ok(data),
+      Resp2Frame::BulkString(data) => String::from_utf8(data).map_err(|_| invalid_utf8()),
+      Resp2Frame::Integer(data) => Ok(data.to_string()),
+      _ => Err(cannot_convert(frame.kind(), "String")),
+    }
+  }
+}
+
+impl FromResp2Frame for Vec<u8> {
+  fn from_resp2_frame(frame: Resp2Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp2Frame::SimpleString(data) => Ok(data.into_bytes()),
+      Resp2Frame::BulkString(data) => Ok(data),
+      Resp2Frame::Integer(data) => Ok(data.to_string().into_bytes()),
+      _ => Err(cannot_convert(frame.kind(), "bytes")),
+    }
+  }
+}
+
+impl FromResp2Frame for i64 {
+  fn from_resp2_frame(frame: Resp2Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp2Frame::Integer(data) => Ok(data),
+      Resp2Frame::SimpleString(ref data) => data.parse().map_err(|_| cannot_convert(frame.kind(), "i64")),
+      Resp2Frame::BulkString(ref data) => str::from_utf8(data)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| cannot_convert(frame.kind(), "i64")),
+      _ => Err(cannot_convert(frame.kind(), "i64")),
+    }
+  }
+}
+
+impl FromResp2Frame for f64 {
+  fn from_resp2_frame(frame: Resp2Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp2Frame::Integer(data) => Ok(data as f64),
+      Resp2Frame::SimpleString(ref data) => data.parse().map_err(|_| cannot_convert(frame.kind(), "f64")),
+      Resp2Frame::BulkString(ref data) => str::from_utf8(data)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| cannot_convert(frame.kind(), "f64")),
+      _ => Err(cannot_convert(frame.kind(), "f64")),
+    }
+  }
+}
+
+impl FromResp2Frame for bool {
+  fn from_resp2_frame(frame: Resp2Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp2Frame::Integer(0) => Ok(false),
+      Resp2Frame::Integer(1) => Ok(true),
+      _ => Err(cannot_convert(frame.kind(), "bool")),
+    }
+  }
+}
+
+impl<T: FromResp2Frame> FromResp2Frame for Option<T> {
+  fn from_resp2_frame(frame: Resp2Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp2Frame::Null => Ok(None),
+      frame => T::from_resp2_frame(frame).map(Some),
+    }
+  }
+}
+
+impl<T: FromResp2Frame> FromResp2Frame for Vec<T> {
+  fn from_resp2_frame(frame: Resp2Frame) -> Result<Self, RedisProtocolError> {
+    match frame {
+      Resp2Frame::Array(data) => convert_elements(data, T::from_resp2_frame),
+      _ => Err(cannot_convert(frame.kind(), "Vec")),
+    }
+  }
+}
+
+impl IntoResp3Frame for Resp3Frame {
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError> {
+    Ok(self.clone())
+  }
+
+  fn into_resp3_frame(self) -> Result<Resp3Frame, RedisProtocolError> {
+    Ok(self)
+  }
+}
+
+impl IntoResp3Frame for Resp2Frame {
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError> {
+    Ok(utils::resp2_frame_to_resp3(self.clone()))
+  }
+
+  fn into_resp3_frame(self) -> Result<Resp3Frame, RedisProtocolError> {
+    Ok(utils::resp2_frame_to_resp3(self))
+  }
+}
+
+impl IntoResp3Frame for str {
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError> {
+    self.as_bytes().to_resp3_frame()
+  }
+}
+
+impl IntoResp3Frame for String {
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError> {
+    self.as_bytes().to_resp3_frame()
+  }
+
+  fn into_resp3_frame(self) -> Result<Resp3Frame, RedisProtocolError> {
+    self.into_bytes().into_resp3_frame()
+  }
+}
+
+impl IntoResp3Frame for [u8] {
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError> {
+    Ok(Resp3Frame::BlobString {
+      data: self.to_vec(),
+      attributes: None,
+    })
+  }
+}
+
+impl IntoResp3Frame for Vec<u8> {
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError> {
+    self.as_slice().to_resp3_frame()
+  }
+
+  fn into_resp3_frame(self) -> Result<Resp3Frame, RedisProtocolError> {
+    Ok(Resp3Frame::BlobString {
+      data: self,
+      attributes: None,
+    })
+  }
+}
+
+impl IntoResp3Frame for i64 {
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError> {
+    Ok(Resp3Frame::from(*self))
+  }
+}
+
+impl IntoResp3Frame for f64 {
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError> {
+    Resp3Frame::try_from(*self)
+  }
+}
+
+impl IntoResp3Frame for bool {
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError> {
+    Ok(Resp3Frame::from(*self))
+  }
+}
+
+impl<T: IntoResp3Frame> IntoResp3Frame for Option<T> {
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError> {
+    match *self {
+      Some(ref value) => value.to_resp3_frame(),
+      None => Ok(Resp3Frame::Null { attributes: None }),
+    }
+  }
+
+  fn into_resp3_frame(self) -> Result<Resp3Frame, RedisProtocolError> {
+    match self {
+      Some(value) => value.into_resp3_frame(),
+      None => Ok(Resp3Frame::Null { attributes: None }),
+    }
+  }
+}
+
+impl<T: IntoResp3Frame> IntoResp3Frame for Vec<T> {
+  fn to_resp3_frame(&self) -> Result<Resp3Frame, RedisProtocolError> {
+    Ok(Resp3Frame::Array {
+      data: self.iter().map(|v| v.to_resp3_frame()).collect::<Result<_, _>>()?,
+      attributes: None,
+    })
+  }
+
+  fn into_resp3_frame(self) -> Result<Resp3Frame, RedisProtocolError> {
+    Ok(Resp3Frame::Array {
+      data: self.into_iter().map(T::into_resp3_frame).collect::<Result<_, _>>()?,
+      attributes: None,
+    })
+  }
+}
+
+impl IntoResp2Frame for Resp2Frame {
+  fn to_resp2_frame(&self) -> Result<Resp2Frame, RedisProtocolError> {
+    Ok(self.clone())
+  }
+
+  fn into_resp2_frame(self) -> Result<Resp2Frame, RedisProtocolError> {
+    Ok(self)
+  }
+}
+
+impl IntoResp2Frame for Resp3Frame {
+  fn to_resp2_frame(&self) -> Result<Resp2Frame, RedisProtocolError> {
+    utils::resp3_frame_to_resp2(self.clone())
+  }
+
+  fn into_resp2_frame(self) -> Result<Resp2Frame, RedisProtocolError> {
+    utils::resp3_frame_to_resp2(self)
+  }
+}
+
+impl IntoResp2Frame for str {
+  fn to_resp2_frame(&self) -> Result<Resp2Frame, RedisProtocolError> {
+    Ok(Resp2Frame::BulkString(self.as_bytes().to_vec()))
+  }
+}
+
+impl IntoResp2Frame for String {
+  fn to_resp2_frame(&self) -> Result<Resp2Frame, RedisProtocolError> {
+    self.as_str().to_resp2_frame()
+  }
+
+  fn into_resp2_frame(self) -> Result<Resp2Frame, RedisProtocolError> {
+    Ok(Resp2Frame::BulkString(self.into_bytes()))
+  }
+}
+
+impl IntoResp2Frame for [u8] {
+  fn to_resp2_frame(&self) -> Result<Resp2Frame, RedisProtocolError> {
+    Ok(Resp2Frame::BulkString(self.to_vec()))
+  }
+}
+
+impl IntoResp2Frame for Vec<u8> {
+  fn to_resp2_frame(&self) -> Result<Resp2Frame, RedisProtocolError> {
+    self.as_slice().to_resp2_frame()
+  }
+
+  fn into_resp2_frame(self) -> Result<Resp2Frame, RedisProtocolError> {
+    Ok(Resp2Frame::BulkString(self))
+  }
+}
+
+impl IntoResp2Frame for i64 {
+  fn to_resp2_frame(&self) -> Result<Resp2Frame, RedisProtocolError> {
+    Ok(Resp2Frame::Integer(*self))
+  }
+}
+
+impl IntoResp2Frame for bool {
+  fn to_resp2_frame(&self) -> Result<Resp2Frame, RedisProtocolError> {
+    Ok(Resp2Frame::Integer(*self as i64))
+  }
+}
+
+impl<T: IntoResp2Frame> IntoResp2Frame for Option<T> {
+  fn to_resp2_frame(&self) -> Result<Resp2Frame, RedisProtocolError> {
+    match *self {
+      Some(ref value) => value.to_resp2_frame(),
+      None => Ok(Resp2Frame::Null),
+    }
+  }
+
+  fn into_resp2_frame(self) -> Result<Resp2Frame, RedisProtocolError> {
+    match self {
+      Some(value) => value.into_resp2_frame(),
+      None => Ok(Resp2Frame::Null),
+    }
+  }
+}
+
+impl<T: IntoResp2Frame> IntoResp2Frame for Vec<T> {
+  fn to_resp2_frame(&self) -> Result<Resp2Frame, RedisProtocolError> {
+    Ok(Resp2Frame::Array(
+      self.iter().map(|v| v.to_resp2_frame()).collect::<Result<_, _>>()?,
+    ))
+  }
+
+  fn into_resp2_frame(self) -> Result<Resp2Frame, RedisProtocolError> {
+    Ok(Resp2Frame::Array(
+      self.into_iter().map(T::into_resp2_frame).collect::<Result<_, _>>()?,
+    ))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_convert_from_resp3_frames() {
+    let frame = Resp3Frame::Array {
+      data: vec![
+        Resp3Frame::from(1),
+        Resp3Frame::Null { attributes: None },
+        Resp3Frame::SimpleString {
+          data: "3".into(),
+          attributes: None,
+        },
+      ],
+      attributes: None,
+    };
+
+    let values: Vec<Option<i64>> = FromResp3Frame::from_resp3_frame(frame.clone()).unwrap();
+    assert_eq!(values, vec![Some(1), None, Some(3)]);
+    let values: Vec<Option<String>> = FromResp3Frame::from_resp3_frame(frame.clone()).unwrap();
+    assert_eq!(values, vec![Some("1".into()), None, Some("3".into())]);
+    assert_eq!(
+      Vec::<i64>::from_resp3_frame(frame).unwrap_err().description(),
+      "Element 1: Cannot convert Null to i64."
+    );
+    assert!(String::from_resp3_frame(Resp3Frame::SimpleError {
+      data: "ERR".into(),
+      attributes: None,
+    })
+    .is_err());
+    assert!(bool::from_resp3_frame(Resp3Frame::from(1)).unwrap());
+  }
+
+  #[test]
+  fn should_convert_from_resp2_frames() {
+    let frame = Resp2Frame::Array(vec![
+      Resp2Frame::BulkString(b"1.5".to_vec()),
+      Resp2Frame::Integer(2),
+      Resp2Frame::Null,
+    ]);
+
+    let values: Vec<Option<f64>> = FromResp2Frame::from_resp2_frame(frame.clone()).unwrap();
+    assert_eq!(values, vec![Some(1.5), Some(2.0), None]);
+    assert_eq!(
+      Vec::<i64>::from_resp2_frame(frame.clone()).unwrap_err().description(),
+      "Element 0: Cannot convert Bulk String to i64."
+    );
+    assert_eq!(
+      Resp3Frame::from_resp2_frame(frame.clone()).unwrap(),
+      utils::resp2_frame_to_resp3(frame)
+    );
+    assert!(Vec::<u8>::from_resp2_frame(Resp2Frame::Error("ERR".into())).is_err());
+  }
+
+  #[test]
+  fn should_convert_into_frames() {
+    let values: Vec<Box<dyn IntoResp3Frame>> = vec![Box::new(1), Box::new("foo".to_owned()), Box::new(None::<bool>)];
+    let frames: Vec<Resp3Frame> = values.iter().map(|v| v.to_resp3_frame().unwrap()).collect();
+    assert_eq!(frames, vec![
+      Resp3Frame::from(1),
+      Resp3Frame::BlobString {
+        data: b"foo".to_vec(),
+        attributes: None,
+      },
+      Resp3Frame::Null { attributes: None },
+    ]);
+
+    let value = vec![Some(b"a".to_vec()), None];
+    assert_eq!(
+      value.to_resp2_frame().unwrap(),
+      Resp2Frame::Array(vec![Resp2Frame::BulkString(b"a".to_vec()), Resp2Frame::Null])
+    );
+    assert_eq!(value.into_resp2_frame().unwrap(), Resp2Frame::Array(vec![
+      Resp2Frame::BulkString(b"a".to_vec()),
+      Resp2Frame::Null
+    ]));
+    assert_eq!(1.5.to_resp3_frame().unwrap(), Resp3Frame::Double {
+      data: 1.5,
+      attributes: None,
+    });
+    assert_eq!(true.to_resp2_frame().unwrap(), Resp2Frame::Integer(1));
+  }
+}
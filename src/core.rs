@@ -0,0 +1,272 @@
+//! Protocol machinery shared by the RESP2 and RESP3 implementations.
+//!
+//! Both protocol versions write frames as a type byte followed by a line or a length prefix, and every line ends with
+//! a CRLF. This module contains the parsers, length calculations, and buffer helpers for these shared building
+//! blocks so that they can be reused by new protocol dialects. The
+//! [ProtocolFrame](crate::core::ProtocolFrame) and [ProtocolFrameKind](crate::core::ProtocolFrameKind) traits are
+//! implemented by the frames of each protocol version, for code that works with either protocol version.
+//!
+//! ```
+//! # use redis_protocol::core::{self, ProtocolFrame, ProtocolFrameKind};
+//! # use redis_protocol::{resp2, resp3};
+//! fn describe<F: ProtocolFrame>(frame: &F) -> String {
+//!   format!("{} ({} bytes)", frame.kind().name(), frame.encode_len().unwrap())
+//! }
+//!
+//! assert_eq!(describe(&resp2::types::Frame::BulkString("foo".into())), "Bulk String (9 bytes)");
+//! assert_eq!(describe(&resp3::types::Frame::new_blob("foo")), "Blob String (9 bytes)");
+//! assert_eq!(core::blob_encode_len(3), 9);
+//! ```
+
+use crate::types::{RedisProtocolError, RedisProtocolErrorKind, CRLF};
+use bytes::BytesMut;
+use cookie_factory::GenError;
+use nom::bytes::streaming::{take, take_until};
+use nom::combinator::map_res;
+use nom::error::{FromExternalError, ParseError};
+use nom::sequence::terminated;
+use nom::IResult;
+use std::fmt;
+use std::hash::Hash;
+use std::str::{self, Utf8Error};
+
+pub const KB: usize = 1024;
+/// A pre-defined zeroed out KB of data, used to speed up extending buffers while encoding.
+pub const ZEROED_KB: &[u8; 1024] = &[0; 1024];
+
+/// Common operations on the kinds of frames in each protocol version.
+pub trait ProtocolFrameKind: Copy + Eq + Hash + fmt::Debug + fmt::Display + Sized + 'static {
+  /// Every kind of frame in the protocol version.
+  fn all() -> &'static [Self];
+
+  /// Attempt to detect the kind of frame from the first byte.
+  fn from_byte(byte: u8) -> Option<Self>;
+
+  /// Read the byte prefix for the kind of frame, or `None` if the frame is not written with a byte prefix.
+  fn try_to_byte(&self) -> Option<u8>;
+
+  /// Read the name of the kind of frame from the protocol specification.
+  fn name(&self) -> &'static str;
+
+  /// Whether or not the frame is an aggregate type that contains other frames.
+  fn is_aggregate(&self) -> bool;
+}
+
+/// Common operations on the frames of each protocol version.
+pub trait ProtocolFrame {
+  /// The type describing the kind of frame.
+  type Kind: ProtocolFrameKind;
+
+  /// Read the kind of frame.
+  fn kind(&self) -> Self::Kind;
+
+  /// Read the number of bytes necessary to encode the frame.
+  fn encode_len(&self) -> Result<usize, RedisProtocolError>;
+
+  /// Whether or not the frame is an error.
+  fn is_error(&self) -> bool;
+}
+
+/// Returns the number of bytes necessary to encode a string representation of `d`.
+pub fn digits_in_number(d: usize) -> usize {
+  if d == 0 {
+    return 1;
+  }
+
+  ((d as f64).log10()).floor() as usize + 1
+}
+
+/// Returns the number of bytes necessary to encode a type byte and `len` bytes on one line.
+pub fn line_encode_len(len: usize) -> usize {
+  1 + len + 2
+}
+
+/// Returns the number of bytes necessary to encode a type byte and the length prefix `len`.
+pub fn length_prefix_encode_len(len: usize) -> usize {
+  1 + digits_in_number(len) + 2
+}
+
+/// Returns the number of bytes necessary to encode a type byte, length prefix, and `len` bytes of binary data.
+pub fn blob_encode_len(len: usize) -> usize {
+  length_prefix_encode_len(len) + len + 2
+}
+
+/// Returns the number of bytes necessary to encode a type byte and `i` on one line.
+pub fn integer_encode_len(i: i64) -> usize {
+  let sign = if i < 0 { 1 } else { 0 };
+  length_prefix_encode_len(i.unsigned_abs() as usize) + sign
+}
+
+/// Read the bytes before the next CRLF, consuming the CRLF.
+pub fn read_to_crlf<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], &'a [u8], E>
+where
+  E: ParseError<&'a [u8]>,
+{
+  terminated(take_until(CRLF), take(2_usize))(input)
+}
+
+/// Read the UTF-8 string before the next CRLF, consuming the CRLF.
+pub fn read_to_crlf_str<'a, E>(input: &'a [u8]) -> IResult<&'a [u8], &'a str, E>
+where
+  E: ParseError<&'a [u8]> + FromExternalError<&'a [u8], Utf8Error>,
+{
+  map_res(read_to_crlf, str::from_utf8)(input)
+}
+
+/// Read `len` bytes of binary data followed by a CRLF, consuming the CRLF.
+///
+/// The CRLF is not checked, since binary data may contain CRLF sequences.
+pub fn read_blob<'a, E>(input: &'a [u8], len: usize) -> IResult<&'a [u8], &'a [u8], E>
+where
+  E: ParseError<&'a [u8]>,
+{
+  terminated(take(len), take(2_usize))(input)
+}
+
+pub fn check_offset(x: &(&mut [u8], usize)) -> Result<(), GenError> {
+  if x.1 > x.0.len() {
+    _error!("Invalid offset of {} with buf len {}", x.1, x.0.len());
+    Err(GenError::InvalidOffset)
+  } else {
+    Ok(())
+  }
+}
+
+/// Write a type byte and `data` followed by a CRLF.
+pub fn gen_line<'a>(x: (&'a mut [u8], usize), prefix: u8, data: &[u8]) -> Result<(&'a mut [u8], usize), GenError> {
+  do_gen!(x, gen_be_u8!(prefix) >> gen_slice!(data) >> gen_slice!(CRLF.as_bytes()))
+}
+
+/// Write a type byte and the length prefix `len` followed by a CRLF.
+pub fn gen_length_prefix(x: (&mut [u8], usize), prefix: u8, len: usize) -> Result<(&mut [u8], usize), GenError> {
+  gen_line(x, prefix, len.to_string().as_bytes())
+}
+
+/// Write a type byte, the length of `data`, and `data`, each followed by a CRLF.
+pub fn gen_blob<'a>(x: (&'a mut [u8], usize), prefix: u8, data: &[u8]) -> Result<(&'a mut [u8], usize), GenError> {
+  let x = gen_length_prefix(x, prefix, data.len())?;
+  do_gen!(x, gen_slice!(data) >> gen_slice!(CRLF.as_bytes()))
+}
+
+/// Convert an error from encoding into a slice of `buf_len` bytes, reporting the total length required by the frame
+/// if the slice is too small.
+pub fn encode_error(buf_len: usize, e: GenError) -> RedisProtocolError {
+  match e {
+    GenError::BufferTooSmall(amt) => RedisProtocolError::slice_too_small(buf_len + amt),
+    e => e.into(),
+  }
+}
+
+/// Call `attempt` with `buf` and the offset of the end of the buffer, extending the buffer and retrying until it is
+/// large enough.
+///
+/// `attempt` returns the offset after the encoded bytes, which is also returned here.
+pub fn encode_extend<F>(buf: &mut BytesMut, mut attempt: F) -> Result<usize, RedisProtocolError>
+where
+  F: FnMut(&mut [u8], usize) -> Result<usize, GenError>,
+{
+  let offset = buf.len();
+
+  loop {
+    match attempt(buf, offset) {
+      Ok(amt) => return Ok(amt),
+      Err(GenError::BufferTooSmall(amt)) => zero_extend(buf, amt),
+      Err(e) => return Err(e.into()),
+    }
+  }
+}
+
+/// Compare the number of bytes written into `buf` by an encoding function with the reported and pre-computed lengths.
+pub fn check_encoded_len(
+  buf: &BytesMut,
+  written: usize,
+  computed: usize,
+  expected: usize,
+) -> Result<(), RedisProtocolError> {
+  let desc = if written != buf.len() {
+    format!("Encoded {} bytes but reported {} bytes.", buf.len(), written)
+  } else if written != computed {
+    format!("Encoded {} bytes but encode_len returned {} bytes.", written, computed)
+  } else if written != expected {
+    format!("Encoded {} bytes but expected {} bytes.", written, expected)
+  } else {
+    return Ok(());
+  };
+
+  Err(RedisProtocolError::new(RedisProtocolErrorKind::EncodeError, desc))
+}
+
+// this is faster than repeat(0).take(amt) at the cost of some memory
+pub fn zero_extend(buf: &mut BytesMut, mut amt: usize) {
+  _trace!("allocating more, len: {}, amt: {}", buf.len(), amt);
+
+  buf.reserve(amt);
+  while amt >= KB {
+    buf.extend_from_slice(ZEROED_KB);
+    amt -= KB;
+  }
+  if amt > 0 {
+    buf.extend_from_slice(&ZEROED_KB[0..amt]);
+  }
+}
+
+/// Write the decimal representation of `n` followed by a CRLF without allocating.
+pub(crate) fn extend_with_length(buf: &mut BytesMut, prefix: u8, mut n: usize) {
+  // a prefix byte, at most 20 digits, and the CRLF
+  let mut scratch = [0_u8; 23];
+  let mut idx = scratch.len() - 2;
+  scratch[idx..].copy_from_slice(CRLF.as_bytes());
+
+  loop {
+    idx -= 1;
+    scratch[idx] = b'0' + (n % 10) as u8;
+    n /= 10;
+    if n == 0 {
+      break;
+    }
+  }
+  idx -= 1;
+  scratch[idx] = prefix;
+
+  buf.extend_from_slice(&scratch[idx..]);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use nom::error::Error as NomError;
+
+  #[test]
+  fn should_compute_shared_encode_lengths() {
+    assert_eq!(line_encode_len(2), 5);
+    assert_eq!(length_prefix_encode_len(0), 4);
+    assert_eq!(length_prefix_encode_len(10), 5);
+    assert_eq!(blob_encode_len(3), 9);
+    assert_eq!(integer_encode_len(-74834), 9);
+    assert_eq!(integer_encode_len(i64::MIN), 23);
+  }
+
+  #[test]
+  fn should_read_lines_and_blobs() {
+    let (rest, line) = read_to_crlf::<NomError<_>>(b"foo\r\nbar").unwrap();
+    assert_eq!((rest, line), (&b"bar"[..], &b"foo"[..]));
+    assert!(read_to_crlf::<NomError<_>>(b"foo\r").unwrap_err().is_incomplete());
+    assert!(read_to_crlf_str::<NomError<_>>(b"\xff\r\n").is_err());
+
+    let (rest, blob) = read_blob::<NomError<_>>(b"a\r\nb\r\n:", 4).unwrap();
+    assert_eq!((rest, blob), (&b":"[..], &b"a\r\nb"[..]));
+  }
+
+  #[test]
+  fn should_encode_shared_building_blocks() {
+    let mut buf = BytesMut::new();
+    let amt = encode_extend(&mut buf, |buf, offset| {
+      let x = gen_line((buf, offset), b'+', b"OK")?;
+      let x = gen_length_prefix(x, b'*', 10)?;
+      gen_blob(x, b'$', b"foo").map(|(_, amt)| amt)
+    })
+    .unwrap();
+
+    assert_eq!(&buf[..amt], b"+OK\r\n*10\r\n$3\r\nfoo\r\n");
+  }
+}
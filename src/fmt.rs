@@ -0,0 +1,254 @@
+//! Functions for rendering frames the same way as `redis-cli`, for use in REPLs and debugging tools.
+//!
+//! ```text
+//! 1) (integer) 1
+//! 2) "foo"
+//! 3) 1# "bar" => (nil)
+//!    2# "baz" => 1) (double) 1.5
+//!          2) (error) ERR unknown
+//! ```
+//!
+//! Attributes are not rendered.
+
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::Frame as Resp3Frame;
+use crate::resp3::utils as resp3_utils;
+use std::fmt::Write;
+
+/// Append `data` as a quoted string, escaping any characters that are not printable.
+fn write_quoted(out: &mut String, data: &[u8]) {
+  out.push('"');
+  for b in data.iter() {
+    match *b {
+      b'\\' => out.push_str("\\\\"),
+      b'"' => out.push_str("\\\""),
+      b'\n' => out.push_str("\\n"),
+      b'\r' => out.push_str("\\r"),
+      b'\t' => out.push_str("\\t"),
+      0x07 => out.push_str("\\a"),
+      0x08 => out.push_str("\\b"),
+      0x20..=0x7e => out.push(*b as char),
+      _ => {
+        let _ = write!(out, "\\x{:02x}", b);
+      }
+    }
+  }
+  out.push_str("\"\n");
+}
+
+/// Append the entries of an aggregate frame, where `prefix` is the indentation of the parent aggregate.
+///
+/// The first entry is not indented since the parent has already written its index on the same line.
+fn write_aggregate<'a, T, I, F>(out: &mut String, prefix: &str, len: usize, separator: char, entries: I, write: &F)
+where
+  T: 'a,
+  I: Iterator<Item = (&'a T, Option<&'a T>)>,
+  F: Fn(&mut String, &T, &str),
+{
+  let width = len.to_string().len();
+  let inner_prefix = format!("{}{}", prefix, " ".repeat(width + 2));
+
+  for (idx, (key, value)) in entries.enumerate() {
+    let _ = write!(
+      out,
+      "{}{:>width$}{} ",
+      if idx == 0 { "" } else { prefix },
+      idx + 1,
+      separator,
+      width = width
+    );
+    write(out, key, &inner_prefix);
+
+    if let Some(value) = value {
+      out.pop();
+      out.push_str(" => ");
+      write(out, value, &inner_prefix);
+    }
+  }
+}
+
+fn write_resp3(out: &mut String, frame: &Resp3Frame, prefix: &str) {
+  match *frame {
+    Resp3Frame::SimpleString { ref data, .. } => {
+      out.push_str(&data.to_str_lossy());
+      out.push('\n');
+    }
+    Resp3Frame::SimpleError { ref data, .. } => {
+      let _ = writeln!(out, "(error) {}", data);
+    }
+    Resp3Frame::BlobError { ref data, .. } => {
+      let _ = writeln!(out, "(error) {}", String::from_utf8_lossy(data));
+    }
+    Resp3Frame::BlobString { ref data, .. } => write_quoted(out, data),
+    Resp3Frame::ChunkedString(ref data) => write_quoted(out, data),
+    Resp3Frame::VerbatimString { ref data, .. } => {
+      out.push_str(&String::from_utf8_lossy(data));
+      out.push('\n');
+    }
+    Resp3Frame::Number { ref data, .. } => {
+      let _ = writeln!(out, "(integer) {}", data);
+    }
+    Resp3Frame::Double { ref data, .. } => {
+      let _ = writeln!(out, "(double) {}", resp3_utils::f64_to_redis_string(data));
+    }
+    Resp3Frame::BigNumber { ref data, .. } => {
+      let _ = writeln!(out, "(big number) {}", String::from_utf8_lossy(data));
+    }
+    Resp3Frame::Boolean { ref data, .. } => {
+      let _ = writeln!(out, "({})", data);
+    }
+    Resp3Frame::Null { .. } => out.push_str("(nil)\n"),
+    Resp3Frame::Array { ref data, .. } if data.is_empty() => out.push_str("(empty array)\n"),
+    Resp3Frame::Push { ref data, .. } if data.is_empty() => out.push_str("(empty push)\n"),
+    Resp3Frame::Map { ref data, .. } if data.is_empty() => out.push_str("(empty hash)\n"),
+    Resp3Frame::Set { ref data, .. } if data.is_empty() => out.push_str("(empty set)\n"),
+    Resp3Frame::Array { ref data, .. } | Resp3Frame::Push { ref data, .. } => write_aggregate(
+      out,
+      prefix,
+      data.len(),
+      ')',
+      data.iter().map(|f| (f, None)),
+      &write_resp3,
+    ),
+    Resp3Frame::Map { ref data, .. } => write_aggregate(
+      out,
+      prefix,
+      data.len(),
+      '#',
+      data.iter().map(|(k, v)| (k, Some(v))),
+      &write_resp3,
+    ),
+    Resp3Frame::Set { ref data, .. } => write_aggregate(
+      out,
+      prefix,
+      data.len(),
+      '~',
+      data.iter().map(|f| (f, None)),
+      &write_resp3,
+    ),
+    Resp3Frame::Hello { ref version, .. } => {
+      let _ = writeln!(out, "HELLO {}", version.to_byte() as char);
+    }
+  }
+}
+
+fn write_resp2(out: &mut String, frame: &Resp2Frame, prefix: &str) {
+  match *frame {
+    Resp2Frame::SimpleString(ref data) => {
+      out.push_str(data);
+      out.push('\n');
+    }
+    Resp2Frame::Error(ref data) => {
+      let _ = writeln!(out, "(error) {}", data);
+    }
+    Resp2Frame::BulkString(ref data) => write_quoted(out, data),
+    Resp2Frame::Integer(ref data) => {
+      let _ = writeln!(out, "(integer) {}", data);
+    }
+    Resp2Frame::Null => out.push_str("(nil)\n"),
+    Resp2Frame::Array(ref data) if data.is_empty() => out.push_str("(empty array)\n"),
+    Resp2Frame::Array(ref data) => write_aggregate(
+      out,
+      prefix,
+      data.len(),
+      ')',
+      data.iter().map(|f| (f, None)),
+      &write_resp2,
+    ),
+  }
+}
+
+/// Render a RESP3 frame the same way as `redis-cli`.
+///
+/// Every line, including the last, ends with a newline.
+pub fn format_frame(frame: &Resp3Frame) -> String {
+  let mut out = String::new();
+  write_resp3(&mut out, frame, "");
+  out
+}
+
+/// Render a RESP2 frame the same way as `redis-cli`.
+///
+/// Every line, including the last, ends with a newline.
+pub fn format_resp2_frame(frame: &Resp2Frame) -> String {
+  let mut out = String::new();
+  write_resp2(&mut out, frame, "");
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::types::{FrameMap, FrameSet};
+
+  #[test]
+  fn should_format_resp2_frames() {
+    let frame = Resp2Frame::Array(vec![
+      Resp2Frame::SimpleString("OK".into()),
+      Resp2Frame::Integer(1),
+      Resp2Frame::BulkString(b"a \"b\"\r\n\xff".to_vec()),
+      Resp2Frame::Null,
+      Resp2Frame::Array(vec![Resp2Frame::Error("ERR foo".into()), Resp2Frame::Array(vec![])]),
+    ]);
+    let expected = "1) OK\n2) (integer) 1\n3) \"a \\\"b\\\"\\r\\n\\xff\"\n4) (nil)\n5) 1) (error) ERR \
+                    foo\n   2) (empty array)\n";
+
+    assert_eq!(format_resp2_frame(&frame), expected);
+  }
+
+  #[test]
+  fn should_align_indexes_in_large_arrays() {
+    let frame = Resp2Frame::Array(vec![Resp2Frame::Array((0..10).map(Resp2Frame::Integer).collect())]);
+    let formatted = format_resp2_frame(&frame);
+    let lines: Vec<&str> = formatted.lines().collect();
+
+    assert_eq!(lines[0], "1)  1) (integer) 0");
+    assert_eq!(lines[1], "    2) (integer) 1");
+    assert_eq!(lines[9], "   10) (integer) 9");
+  }
+
+  #[test]
+  fn should_format_resp3_frames() {
+    let mut map = FrameMap::new();
+    map.insert(
+      Resp3Frame::BlobString {
+        data: b"foo".to_vec(),
+        attributes: None,
+      },
+      Resp3Frame::Array {
+        data: vec![
+          Resp3Frame::Double {
+            data: 1.5,
+            attributes: None,
+          },
+          Resp3Frame::Boolean {
+            data: true,
+            attributes: None,
+          },
+        ],
+        attributes: None,
+      },
+    );
+    let frame = Resp3Frame::Push {
+      data: vec![
+        Resp3Frame::Map {
+          data: map,
+          attributes: None,
+        },
+        Resp3Frame::Set {
+          data: FrameSet::new(),
+          attributes: None,
+        },
+        Resp3Frame::BigNumber {
+          data: b"123".to_vec(),
+          attributes: None,
+        },
+      ],
+      attributes: None,
+    };
+    let expected = "1) 1# \"foo\" => 1) (double) 1.5\n      2) (true)\n2) (empty set)\n3) (big number) 123\n";
+
+    assert_eq!(format_frame(&frame), expected);
+    assert_eq!(format_frame(&Resp3Frame::Null { attributes: None }), "(nil)\n");
+  }
+}
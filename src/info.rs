@@ -0,0 +1,363 @@
+//! Types and functions for parsing the response to the `INFO` command.
+//!
+//! <https://redis.io/commands/info>
+
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::Frame as Resp3Frame;
+use crate::types::RedisProtocolError;
+use crate::utils;
+use std::convert::TryFrom;
+use std::str::{self, FromStr};
+
+/// The replication role of a server.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InfoRole {
+  Master,
+  Replica,
+}
+
+/// A replica connected to a primary node, read from the `slave<n>` fields in the `replication` section.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InfoReplica {
+  pub ip: String,
+  pub port: u16,
+  pub state: Option<String>,
+  pub offset: i64,
+  pub lag: Option<i64>,
+}
+
+/// Key counts for one database, read from the `db<n>` fields in the `keyspace` section.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InfoKeyspace {
+  pub db: u32,
+  pub keys: u64,
+  pub expires: u64,
+  pub avg_ttl: u64,
+}
+
+/// One section in an `INFO` response, such as `# Server` or `# Replication`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InfoSection {
+  /// The section name, without the leading `#`. Fields that appear before any section header are in a section with an empty name.
+  pub name: String,
+  /// The key/value pairs in the section, in the order they were received.
+  pub fields: Vec<(String, String)>,
+}
+
+impl InfoSection {
+  /// Read the value for `key` in this section.
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self
+      .fields
+      .iter()
+      .find(|(k, _)| k == key)
+      .map(|(_, v)| v.as_str())
+  }
+}
+
+/// The sections in an `INFO` response.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct InfoSections {
+  /// The sections, in the order they were received.
+  pub sections: Vec<InfoSection>,
+}
+
+impl InfoSections {
+  /// Read a section by name, ignoring case.
+  pub fn section(&self, name: &str) -> Option<&InfoSection> {
+    self.sections.iter().find(|s| s.name.eq_ignore_ascii_case(name))
+  }
+
+  /// Read the value for `key` from the first section that contains it.
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self.sections.iter().find_map(|s| s.get(key))
+  }
+
+  /// Read and parse the value for `key` from the first section that contains it.
+  pub fn get_parsed<T: FromStr>(&self, key: &str) -> Option<T> {
+    self.get(key).and_then(|v| v.parse().ok())
+  }
+
+  /// Read the `redis_version` field.
+  pub fn redis_version(&self) -> Option<&str> {
+    self.get("redis_version")
+  }
+
+  /// Read the `role` field.
+  pub fn role(&self) -> Option<InfoRole> {
+    match self.get("role")? {
+      "master" => Some(InfoRole::Master),
+      "slave" | "replica" => Some(InfoRole::Replica),
+      _ => None,
+    }
+  }
+
+  /// Read the `connected_clients` field.
+  pub fn connected_clients(&self) -> Option<u64> {
+    self.get_parsed("connected_clients")
+  }
+
+  /// Read the `used_memory` field, in bytes.
+  pub fn used_memory(&self) -> Option<u64> {
+    self.get_parsed("used_memory")
+  }
+
+  /// Read the `maxmemory` field, in bytes.
+  pub fn maxmemory(&self) -> Option<u64> {
+    self.get_parsed("maxmemory")
+  }
+
+  /// Read the `connected_slaves` field.
+  pub fn connected_replicas(&self) -> Option<u64> {
+    self.get_parsed("connected_slaves")
+  }
+
+  /// Read the `master_repl_offset` field.
+  pub fn master_repl_offset(&self) -> Option<i64> {
+    self.get_parsed("master_repl_offset")
+  }
+
+  /// Read the `slave_repl_offset` field, only present on replicas.
+  pub fn replica_repl_offset(&self) -> Option<i64> {
+    self.get_parsed("slave_repl_offset")
+  }
+
+  /// Read the `master_host` and `master_port` fields, only present on replicas.
+  pub fn master_addr(&self) -> Option<(&str, u16)> {
+    let host = self.get("master_host")?;
+    let port = self.get_parsed("master_port")?;
+    Some((host, port))
+  }
+
+  /// Read the replicas from the `slave<n>` fields in the `replication` section.
+  pub fn replicas(&self) -> Vec<InfoReplica> {
+    let section = match self.section("replication") {
+      Some(section) => section,
+      None => return Vec::new(),
+    };
+
+    section
+      .fields
+      .iter()
+      .filter(|(k, _)| k.starts_with("slave") && k[5..].parse::<u32>().is_ok())
+      .filter_map(|(_, v)| {
+        let fields = parse_info_fields(v);
+        let get = |key: &str| fields.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+
+        Some(InfoReplica {
+          ip: get("ip")?.to_owned(),
+          port: get("port")?.parse().ok()?,
+          state: get("state").map(|s| s.to_owned()),
+          offset: get("offset").and_then(|s| s.parse().ok()).unwrap_or(0),
+          lag: get("lag").and_then(|s| s.parse().ok()),
+        })
+      })
+      .collect()
+  }
+
+  /// Read the per-database key counts from the `keyspace` section.
+  pub fn keyspace(&self) -> Vec<InfoKeyspace> {
+    let section = match self.section("keyspace") {
+      Some(section) => section,
+      None => return Vec::new(),
+    };
+
+    section
+      .fields
+      .iter()
+      .filter_map(|(k, v)| {
+        if !k.starts_with("db") {
+          return None;
+        }
+        let fields = parse_info_fields(v);
+        let get = |key: &str| {
+          fields
+            .iter()
+            .find(|(k, _)| *k == key)
+            .and_then(|(_, v)| v.parse::<u64>().ok())
+            .unwrap_or(0)
+        };
+
+        Some(InfoKeyspace {
+          db: k[2..].parse().ok()?,
+          keys: get("keys"),
+          expires: get("expires"),
+          avg_ttl: get("avg_ttl"),
+        })
+      })
+      .collect()
+  }
+}
+
+impl TryFrom<&Resp2Frame> for InfoSections {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Resp2Frame) -> Result<Self, Self::Error> {
+    match *frame {
+      Resp2Frame::BulkString(ref b) => Ok(parse_info(
+        str::from_utf8(b).map_err(|e| utils::invalid_response("INFO response", e))?,
+      )),
+      Resp2Frame::SimpleString(ref s) => Ok(parse_info(s)),
+      _ => Err(utils::invalid_response("INFO response", "Expected string frame.")),
+    }
+  }
+}
+
+impl TryFrom<&Resp3Frame> for InfoSections {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Resp3Frame) -> Result<Self, Self::Error> {
+    match *frame {
+      Resp3Frame::BlobString { ref data, .. } | Resp3Frame::VerbatimString { ref data, .. } => Ok(parse_info(
+        str::from_utf8(data).map_err(|e| utils::invalid_response("INFO response", e))?,
+      )),
+      Resp3Frame::SimpleString { ref data, .. } => Ok(parse_info(
+        str::from_utf8(data.as_bytes()).map_err(|e| utils::invalid_response("INFO response", e))?,
+      )),
+      _ => Err(utils::invalid_response("INFO response", "Expected string frame.")),
+    }
+  }
+}
+
+/// Split a nested value such as `ip=127.0.0.1,port=6380,state=online` into key/value pairs.
+pub fn parse_info_fields(value: &str) -> Vec<(&str, &str)> {
+  value
+    .split(',')
+    .filter_map(|pair| {
+      let idx = pair.find('=')?;
+      Some((&pair[0..idx], &pair[idx + 1..]))
+    })
+    .collect()
+}
+
+/// Parse the response to the `INFO` command into sections and key/value pairs.
+///
+/// Empty lines and lines without a `:` separator are ignored.
+pub fn parse_info(info: &str) -> InfoSections {
+  let mut sections: Vec<InfoSection> = Vec::new();
+
+  for line in info.lines() {
+    let line = line.trim();
+    if line.is_empty() {
+      continue;
+    }
+
+    if let Some(name) = line.strip_prefix('#') {
+      sections.push(InfoSection {
+        name: name.trim().to_owned(),
+        fields: Vec::new(),
+      });
+      continue;
+    }
+
+    let idx = match line.find(':') {
+      Some(idx) => idx,
+      None => continue,
+    };
+    if sections.is_empty() {
+      sections.push(InfoSection::default());
+    }
+    if let Some(section) = sections.last_mut() {
+      section
+        .fields
+        .push((line[0..idx].to_owned(), line[idx + 1..].to_owned()));
+    }
+  }
+
+  InfoSections { sections }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const PRIMARY_INFO: &str = "# Server\r\nredis_version:6.2.6\r\nredis_mode:standalone\r\n\r\n# Clients\r\nconnected_clients:3\r\n\r\n# Memory\r\nused_memory:873432\r\nmaxmemory:0\r\n\r\n# Replication\r\nrole:master\r\nconnected_slaves:2\r\nslave0:ip=127.0.0.1,port=6380,state=online,offset=1410,lag=0\r\nslave1:ip=::1,port=6381,state=wait_bgsave,offset=0,lag=1\r\nmaster_repl_offset:1410\r\n\r\n# Keyspace\r\ndb0:keys=5,expires=1,avg_ttl=3000\r\ndb3:keys=1,expires=0,avg_ttl=0\r\n";
+
+  #[test]
+  fn should_parse_info_sections() {
+    let info = parse_info(PRIMARY_INFO);
+
+    assert_eq!(info.sections.len(), 5);
+    assert_eq!(info.sections[0].name, "Server");
+    assert_eq!(info.section("server").unwrap().get("redis_mode"), Some("standalone"));
+    assert_eq!(info.redis_version(), Some("6.2.6"));
+    assert_eq!(info.role(), Some(InfoRole::Master));
+    assert_eq!(info.connected_clients(), Some(3));
+    assert_eq!(info.used_memory(), Some(873432));
+    assert_eq!(info.maxmemory(), Some(0));
+    assert_eq!(info.connected_replicas(), Some(2));
+    assert_eq!(info.master_repl_offset(), Some(1410));
+    assert_eq!(info.replica_repl_offset(), None);
+    assert_eq!(info.get("missing"), None);
+  }
+
+  #[test]
+  fn should_parse_info_replicas_and_keyspace() {
+    let info = parse_info(PRIMARY_INFO);
+
+    assert_eq!(info.replicas(), vec![
+      InfoReplica {
+        ip: "127.0.0.1".into(),
+        port: 6380,
+        state: Some("online".into()),
+        offset: 1410,
+        lag: Some(0),
+      },
+      InfoReplica {
+        ip: "::1".into(),
+        port: 6381,
+        state: Some("wait_bgsave".into()),
+        offset: 0,
+        lag: Some(1),
+      },
+    ]);
+    assert_eq!(info.keyspace(), vec![
+      InfoKeyspace {
+        db: 0,
+        keys: 5,
+        expires: 1,
+        avg_ttl: 3000,
+      },
+      InfoKeyspace {
+        db: 3,
+        keys: 1,
+        expires: 0,
+        avg_ttl: 0,
+      },
+    ]);
+  }
+
+  #[test]
+  fn should_parse_replica_info() {
+    let info = parse_info("# Replication\nrole:slave\nmaster_host:10.0.0.1\nmaster_port:6379\nslave_repl_offset:42\n");
+
+    assert_eq!(info.role(), Some(InfoRole::Replica));
+    assert_eq!(info.master_addr(), Some(("10.0.0.1", 6379)));
+    assert_eq!(info.replica_repl_offset(), Some(42));
+    assert!(info.replicas().is_empty());
+    assert!(info.keyspace().is_empty());
+  }
+
+  #[test]
+  fn should_parse_info_without_section_header() {
+    let info = parse_info("used_memory:100\r\ngarbage\r\n");
+
+    assert_eq!(info.sections.len(), 1);
+    assert_eq!(info.sections[0].name, "");
+    assert_eq!(info.used_memory(), Some(100));
+  }
+
+  #[test]
+  fn should_parse_info_from_frames() {
+    let frame = Resp3Frame::VerbatimString {
+      data: PRIMARY_INFO.as_bytes().to_vec(),
+      format: crate::resp3::types::VerbatimStringFormat::Text,
+      attributes: None,
+    };
+    assert_eq!(InfoSections::try_from(&frame).unwrap(), parse_info(PRIMARY_INFO));
+
+    let frame = Resp2Frame::BulkString(PRIMARY_INFO.as_bytes().to_vec());
+    assert_eq!(InfoSections::try_from(&frame).unwrap(), parse_info(PRIMARY_INFO));
+    assert!(InfoSections::try_from(&Resp2Frame::Integer(1)).is_err());
+  }
+}
@@ -0,0 +1,245 @@
+//! Conversions between frames and [serde_json::Value].
+//!
+//! JSON cannot represent every frame type, so converting a frame to JSON is lossy:
+//!
+//! * Attributes are dropped.
+//! * Simple strings, verbatim strings, and blob strings become strings. Blob strings that are not valid UTF-8 are
+//!   encoded with standard base64.
+//! * Simple and blob errors become objects with an `error` key, such as `{"error": "ERR unknown command"}`.
+//! * Numbers become numbers. Doubles become numbers, except for `inf`, `-inf`, and `nan` which become strings.
+//! * Big numbers become strings so that they do not lose precision.
+//! * Arrays, push frames, and sets become arrays.
+//! * Maps become objects. String keys are used as-is, and other keys are converted to a string with
+//!   [Frame::to_string](crate::resp3::types::Frame::to_string) or serialized as JSON.
+//! * `HELLO` frames become `null`.
+//!
+//! When converting JSON to a frame, strings become blob strings, objects become maps with blob string keys, and numbers
+//! become numbers if they fit in an `i64` or doubles otherwise.
+
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::{Frame as Resp3Frame, FrameMap};
+use crate::resp3::utils as resp3_utils;
+use serde_json::{Map, Number, Value};
+use std::str;
+
+fn bytes_to_string(data: &[u8]) -> String {
+  match str::from_utf8(data) {
+    Ok(s) => s.to_owned(),
+    Err(_) => base64::encode(data),
+  }
+}
+
+fn bytes_to_value(data: &[u8]) -> Value {
+  Value::String(bytes_to_string(data))
+}
+
+fn error_to_value(data: String) -> Value {
+  let mut out = Map::with_capacity(1);
+  out.insert("error".into(), Value::String(data));
+  Value::Object(out)
+}
+
+fn map_key_to_string(key: &Resp3Frame) -> String {
+  match *key {
+    Resp3Frame::BlobString { ref data, .. } | Resp3Frame::ChunkedString(ref data) => bytes_to_string(data),
+    _ => key.to_string().unwrap_or_else(|| frame_to_json(key).to_string()),
+  }
+}
+
+/// Convert a RESP3 frame to a JSON value according to the rules in the [module docs](self).
+pub fn frame_to_json(frame: &Resp3Frame) -> Value {
+  match *frame {
+    Resp3Frame::SimpleString { ref data, .. } => bytes_to_value(data.as_bytes()),
+    Resp3Frame::BlobString { ref data, .. }
+    | Resp3Frame::VerbatimString { ref data, .. }
+    | Resp3Frame::ChunkedString(ref data) => bytes_to_value(data),
+    Resp3Frame::SimpleError { ref data, .. } => error_to_value(data.to_str_lossy().into_owned()),
+    Resp3Frame::BlobError { ref data, .. } => error_to_value(String::from_utf8_lossy(data).into_owned()),
+    Resp3Frame::Number { ref data, .. } => Value::Number((*data).into()),
+    Resp3Frame::Double { ref data, .. } => match Number::from_f64(*data) {
+      Some(number) => Value::Number(number),
+      None => Value::String(resp3_utils::f64_to_redis_string(data).into_owned()),
+    },
+    Resp3Frame::BigNumber { ref data, .. } => Value::String(String::from_utf8_lossy(data).into_owned()),
+    Resp3Frame::Boolean { ref data, .. } => Value::Bool(*data),
+    Resp3Frame::Null { .. } | Resp3Frame::Hello { .. } => Value::Null,
+    Resp3Frame::Array { ref data, .. } | Resp3Frame::Push { ref data, .. } => {
+      Value::Array(data.iter().map(frame_to_json).collect())
+    }
+    Resp3Frame::Set { ref data, .. } => Value::Array(data.iter().map(frame_to_json).collect()),
+    Resp3Frame::Map { ref data, .. } => Value::Object(
+      data
+        .iter()
+        .map(|(key, value)| (map_key_to_string(key), frame_to_json(value)))
+        .collect(),
+    ),
+  }
+}
+
+/// Convert a RESP2 frame to a JSON value according to the rules in the [module docs](self).
+pub fn resp2_frame_to_json(frame: &Resp2Frame) -> Value {
+  match *frame {
+    Resp2Frame::SimpleString(ref data) => Value::String(data.clone()),
+    Resp2Frame::BulkString(ref data) => bytes_to_value(data),
+    Resp2Frame::Error(ref data) => error_to_value(data.clone()),
+    Resp2Frame::Integer(ref data) => Value::Number((*data).into()),
+    Resp2Frame::Null => Value::Null,
+    Resp2Frame::Array(ref data) => Value::Array(data.iter().map(resp2_frame_to_json).collect()),
+  }
+}
+
+/// Convert a JSON value to a RESP3 frame.
+pub fn json_to_frame(value: Value) -> Resp3Frame {
+  match value {
+    Value::Null => Resp3Frame::Null { attributes: None },
+    Value::Bool(data) => Resp3Frame::Boolean { data, attributes: None },
+    Value::Number(number) => match number.as_i64() {
+      Some(data) => Resp3Frame::Number { data, attributes: None },
+      None => Resp3Frame::Double {
+        data: number.as_f64().unwrap_or(f64::NAN),
+        attributes: None,
+      },
+    },
+    Value::String(data) => Resp3Frame::BlobString {
+      data: data.into_bytes(),
+      attributes: None,
+    },
+    Value::Array(data) => Resp3Frame::Array {
+      data: data.into_iter().map(json_to_frame).collect(),
+      attributes: None,
+    },
+    Value::Object(data) => Resp3Frame::Map {
+      data: data
+        .into_iter()
+        .map(|(key, value)| {
+          let key = Resp3Frame::BlobString {
+            data: key.into_bytes(),
+            attributes: None,
+          };
+          (key, json_to_frame(value))
+        })
+        .collect::<FrameMap>(),
+      attributes: None,
+    },
+  }
+}
+
+impl<'a> From<&'a Resp3Frame> for Value {
+  fn from(frame: &'a Resp3Frame) -> Self {
+    frame_to_json(frame)
+  }
+}
+
+impl From<Resp3Frame> for Value {
+  fn from(frame: Resp3Frame) -> Self {
+    frame_to_json(&frame)
+  }
+}
+
+impl<'a> From<&'a Resp2Frame> for Value {
+  fn from(frame: &'a Resp2Frame) -> Self {
+    resp2_frame_to_json(frame)
+  }
+}
+
+impl From<Resp2Frame> for Value {
+  fn from(frame: Resp2Frame) -> Self {
+    resp2_frame_to_json(&frame)
+  }
+}
+
+impl From<Value> for Resp3Frame {
+  fn from(value: Value) -> Self {
+    json_to_frame(value)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  fn blob(data: &[u8]) -> Resp3Frame {
+    Resp3Frame::BlobString {
+      data: data.to_vec(),
+      attributes: None,
+    }
+  }
+
+  #[test]
+  fn should_convert_resp3_frames_to_json() {
+    let mut map = FrameMap::new();
+    map.insert(blob(b"a"), Resp3Frame::from(1));
+    map.insert(Resp3Frame::from(2), blob(b"\xff\x00"));
+    map.insert(
+      Resp3Frame::Array {
+        data: vec![Resp3Frame::from(true)],
+        attributes: None,
+      },
+      Resp3Frame::Double {
+        data: f64::INFINITY,
+        attributes: None,
+      },
+    );
+    let frame = Resp3Frame::Array {
+      data: vec![
+        Resp3Frame::Map {
+          data: map,
+          attributes: None,
+        },
+        Resp3Frame::SimpleError {
+          data: "ERR foo".into(),
+          attributes: None,
+        },
+        Resp3Frame::Double {
+          data: 1.5,
+          attributes: None,
+        },
+        Resp3Frame::Null { attributes: None },
+      ],
+      attributes: None,
+    };
+
+    assert_eq!(
+      Value::from(&frame),
+      json!([{"a": 1, "2": "/wA=", "[true]": "inf"}, {"error": "ERR foo"}, 1.5, null])
+    );
+  }
+
+  #[test]
+  fn should_convert_resp2_frames_to_json() {
+    let frame = Resp2Frame::Array(vec![
+      Resp2Frame::BulkString(b"foo".to_vec()),
+      Resp2Frame::Integer(-1),
+      Resp2Frame::Null,
+    ]);
+
+    assert_eq!(Value::from(frame), json!(["foo", -1, null]));
+  }
+
+  #[test]
+  fn should_convert_json_to_frames() {
+    let value = json!({"a": [1, 2.5, true, null, "b"]});
+    let mut expected = FrameMap::new();
+    expected.insert(blob(b"a"), Resp3Frame::Array {
+      data: vec![
+        Resp3Frame::from(1),
+        Resp3Frame::Double {
+          data: 2.5,
+          attributes: None,
+        },
+        Resp3Frame::from(true),
+        Resp3Frame::Null { attributes: None },
+        blob(b"b"),
+      ],
+      attributes: None,
+    });
+
+    let frame = Resp3Frame::from(value.clone());
+    assert_eq!(frame, Resp3Frame::Map {
+      data: expected,
+      attributes: None,
+    });
+    assert_eq!(Value::from(frame), value);
+  }
+}
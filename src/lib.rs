@@ -34,12 +34,25 @@
 //!   println!("Hash slot for {}: {}", key, redis_keyslot(key));
 //! }
 //! ```
+//!
+//! ## Map Backends
+//!
+//! RESP3 maps and sets are backed by a `HashMap` and `HashSet` by default. The `index-map` feature uses an
+//! insertion-ordered `IndexMap` and `IndexSet` instead, and the `btree-map` feature uses a sorted `BTreeMap` and
+//! `BTreeSet`. If both features are enabled then `btree-map` takes precedence, so building with `--all-features` uses
+//! the sorted backend. See [FrameMap](crate::resp3::types::FrameMap) for more information.
+//!
+//! ## Frame Storage
+//!
+//! RESP2 frames are generic over the storage used for bulk strings. [Frame](crate::resp2::types::Frame) owns its
+//! bulk strings, while [BytesFrame](crate::resp2::types::BytesFrame) and
+//! [BorrowedFrame](crate::resp2::types::BorrowedFrame) are decoded without copying by
+//! [decode_bytes](crate::resp2::decode::decode_bytes) and [decode_borrowed](crate::resp2::decode::decode_borrowed).
+//! RESP3 frames always own their contents, since map and set keys, attributes, and streamed frames all store RESP3
+//! frames by value. Use a [FrameAllocator](crate::resp3::types::FrameAllocator) or
+//! [decode_into](crate::resp3::decode::complete::decode_into) to control how RESP3 frames are allocated instead.
 
-#[macro_use]
-extern crate log;
 extern crate bytes;
-extern crate crc16;
-extern crate pretty_env_logger;
 #[macro_use]
 extern crate cookie_factory;
 #[macro_use]
@@ -48,15 +61,94 @@ extern crate float_cmp;
 
 #[cfg(feature = "index-map")]
 extern crate indexmap;
+#[cfg(feature = "bignum")]
+extern crate num_bigint;
+#[cfg(feature = "json")]
+extern crate base64;
+#[cfg(feature = "json")]
+extern crate serde_json;
+#[cfg(feature = "logging")]
+extern crate log;
+#[cfg(feature = "tracing")]
+extern crate tracing;
 
 #[macro_use]
 pub(crate) mod utils;
 
+/// Protocol machinery shared by the RESP2 and RESP3 implementations.
+pub mod core;
+
 /// Types and functions for implementing the RESP2 protocol.
 pub mod resp2;
 /// Types and functions for implementing the RESP3 protocol.
 pub mod resp3;
 /// Error types and general redis protocol types.
 pub mod types;
+/// Types and functions for client-side caching.
+pub mod tracking;
+/// Types and functions for parsing `MONITOR` output.
+pub mod monitor;
+/// Types and functions for parsing `INFO` responses.
+pub mod info;
+/// Types and functions for parsing `CLIENT LIST` and `CLIENT INFO` responses.
+pub mod client;
+/// Types and functions for working with redis commands.
+pub mod commands;
+/// Types and functions for working with streams.
+pub mod streams;
+/// Types and functions for parsing ACL responses.
+pub mod acl;
+/// Types and functions for parsing sentinel responses.
+pub mod sentinel;
+/// Types and functions for parsing cluster responses.
+pub mod cluster;
+/// Types and functions for decoding RDB data.
+pub mod rdb;
+/// Functions for rendering frames the same way as `redis-cli`.
+pub mod fmt;
+/// Traits for converting between frames and other value types.
+pub mod convert;
+/// An observer interface for the decoding and encoding functions.
+pub mod observer;
+/// RESP2 and RESP3 test vectors for validating other implementations.
+pub mod conformance;
+/// Record and replay decoding sessions.
+pub mod replay;
+/// Types and functions for parsing Redis connection URLs.
+pub mod url;
+/// A sans-io pipeline for building proxies.
+pub mod proxy;
+/// A sans-io tracker for publish-subscribe state.
+pub mod pubsub;
+/// Conversions between frames and JSON values.
+#[cfg(feature = "json")]
+pub mod json;
+/// A pool of frames that reuses their allocations while decoding.
+#[cfg(feature = "pool")]
+pub mod pool;
+/// Metrics recorded with the `metrics` facade.
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+/// Assertions for testing code that decodes or encodes frames.
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
-pub use utils::{digits_in_number, redis_keyslot, resp2_frame_to_resp3, resp3_frame_to_resp2, ZEROED_KB};
+pub use utils::{
+  crc16_xmodem,
+  crc64_jones,
+  escape_wire_string,
+  hash_tag_for_slot,
+  hex_dump,
+  key_for_slot,
+  parse_command_str,
+  redis_keyslot,
+  redis_keyslot_const,
+  resp2_frame_to_resp3,
+  resp3_frame_to_resp2,
+  unescape_wire_string,
+  verify_dump_payload,
+  KeyslotHasher,
+};
+pub use crate::core::{digits_in_number, ZEROED_KB};
@@ -0,0 +1,212 @@
+//! Metrics recorded with the [metrics](https://crates.io/crates/metrics) facade.
+//!
+//! The decoding and encoding functions record the following metrics when the `metrics` feature is enabled. Every
+//! metric has a `version` label of `resp2` or `resp3`, and the frame metrics also have a `kind` label with the
+//! [FrameKind](crate::resp3::types::FrameKind) of the frame, where RESP2 frames use the equivalent RESP3 kind.
+//!
+//! | Name                                   | Type      | Description                                          |
+//! |----------------------------------------|-----------|------------------------------------------------------|
+//! | `redis_protocol_frames_decoded_total`  | Counter   | The number of frames decoded.                        |
+//! | `redis_protocol_frames_encoded_total`  | Counter   | The number of frames encoded.                        |
+//! | `redis_protocol_decoded_bytes`         | Histogram | The size of each decoded frame, in bytes.            |
+//! | `redis_protocol_encoded_bytes`         | Histogram | The size of each encoded frame, in bytes.            |
+//! | `redis_protocol_decode_errors_total`   | Counter   | The number of decoding errors.                       |
+//! | `redis_protocol_encode_errors_total`   | Counter   | The number of encoding errors.                       |
+//! | `redis_protocol_streamed_frames_total` | Counter   | The number of RESP3 streamed frames that were begun. |
+//!
+//! Incomplete frames are not recorded.
+
+use crate::resp3::types::FrameKind;
+use crate::types::RedisProtocolError;
+
+/// The number of frames decoded.
+pub const FRAMES_DECODED: &str = "redis_protocol_frames_decoded_total";
+/// The number of frames encoded.
+pub const FRAMES_ENCODED: &str = "redis_protocol_frames_encoded_total";
+/// The size of each decoded frame, in bytes.
+pub const DECODED_BYTES: &str = "redis_protocol_decoded_bytes";
+/// The size of each encoded frame, in bytes.
+pub const ENCODED_BYTES: &str = "redis_protocol_encoded_bytes";
+/// The number of decoding errors.
+pub const DECODE_ERRORS: &str = "redis_protocol_decode_errors_total";
+/// The number of encoding errors.
+pub const ENCODE_ERRORS: &str = "redis_protocol_encode_errors_total";
+/// The number of RESP3 streamed frames that were begun.
+pub const STREAMED_FRAMES: &str = "redis_protocol_streamed_frames_total";
+
+pub(crate) const RESP2: &str = "resp2";
+pub(crate) const RESP3: &str = "resp3";
+
+fn kind_label(kind: FrameKind) -> &'static str {
+  match kind {
+    FrameKind::Array => "array",
+    FrameKind::BlobString => "blob_string",
+    FrameKind::SimpleString => "simple_string",
+    FrameKind::SimpleError => "simple_error",
+    FrameKind::Number => "number",
+    FrameKind::Null => "null",
+    FrameKind::Double => "double",
+    FrameKind::Boolean => "boolean",
+    FrameKind::BlobError => "blob_error",
+    FrameKind::VerbatimString => "verbatim_string",
+    FrameKind::Map => "map",
+    FrameKind::Set => "set",
+    FrameKind::Attribute => "attribute",
+    FrameKind::Push => "push",
+    FrameKind::Hello => "hello",
+    FrameKind::BigNumber => "big_number",
+    FrameKind::ChunkedString => "chunked_string",
+    FrameKind::EndStream => "end_stream",
+  }
+}
+
+/// Record the result of a decoding function.
+pub(crate) fn record_decode<T, F>(
+  version: &'static str,
+  result: &Result<Option<(T, usize)>, RedisProtocolError>,
+  kind: F,
+) where
+  F: FnOnce(&T) -> FrameKind,
+{
+  match *result {
+    Ok(Some((ref frame, amt))) => {
+      let kind = kind_label(kind(frame));
+      ::metrics::counter!(FRAMES_DECODED, "version" => version, "kind" => kind).increment(1);
+      ::metrics::histogram!(DECODED_BYTES, "version" => version, "kind" => kind).record(amt as f64);
+    }
+    Ok(None) => {}
+    Err(_) => ::metrics::counter!(DECODE_ERRORS, "version" => version).increment(1),
+  }
+}
+
+/// Record the start of a streamed frame.
+pub(crate) fn record_streamed_frame(kind: FrameKind) {
+  ::metrics::counter!(STREAMED_FRAMES, "version" => RESP3, "kind" => kind_label(kind)).increment(1);
+}
+
+/// Record the result of an encoding function, where `result` contains the offset after the frame.
+pub(crate) fn record_encode(
+  version: &'static str,
+  kind: FrameKind,
+  offset: usize,
+  result: &Result<usize, RedisProtocolError>,
+) {
+  match *result {
+    Ok(amt) => {
+      let kind = kind_label(kind);
+      ::metrics::counter!(FRAMES_ENCODED, "version" => version, "kind" => kind).increment(1);
+      ::metrics::histogram!(ENCODED_BYTES, "version" => version, "kind" => kind)
+        .record(amt.saturating_sub(offset) as f64);
+    }
+    Err(_) => ::metrics::counter!(ENCODE_ERRORS, "version" => version).increment(1),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::resp2::encode::encode_bytes as resp2_encode_bytes;
+  use crate::resp2::types::Frame as Resp2Frame;
+  use crate::resp3::decode::{complete, streaming};
+  use crate::resp3::encode::complete::encode_bytes;
+  use crate::resp3::types::Frame;
+  use bytes::BytesMut;
+  use metrics::{
+    Counter,
+    CounterFn,
+    Gauge,
+    Histogram,
+    HistogramFn,
+    Key,
+    KeyName,
+    Metadata,
+    Recorder,
+    SharedString,
+    Unit,
+  };
+  use std::sync::{Arc, Mutex};
+
+  #[derive(Clone, Default)]
+  struct TestRecorder {
+    events: Arc<Mutex<Vec<(String, u64)>>>,
+  }
+
+  struct TestMetric {
+    key: String,
+    events: Arc<Mutex<Vec<(String, u64)>>>,
+  }
+
+  impl CounterFn for TestMetric {
+    fn increment(&self, value: u64) {
+      self.events.lock().unwrap().push((self.key.clone(), value));
+    }
+
+    fn absolute(&self, _: u64) {}
+  }
+
+  impl HistogramFn for TestMetric {
+    fn record(&self, value: f64) {
+      self.events.lock().unwrap().push((self.key.clone(), value as u64));
+    }
+  }
+
+  impl TestRecorder {
+    fn metric(&self, key: &Key) -> Arc<TestMetric> {
+      let labels: Vec<String> = key.labels().map(|l| format!("{}={}", l.key(), l.value())).collect();
+
+      Arc::new(TestMetric {
+        key: format!("{}{{{}}}", key.name(), labels.join(",")),
+        events: self.events.clone(),
+      })
+    }
+  }
+
+  impl Recorder for TestRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+      Counter::from_arc(self.metric(key))
+    }
+
+    fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+      Gauge::noop()
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+      Histogram::from_arc(self.metric(key))
+    }
+  }
+
+  #[test]
+  fn should_record_frame_metrics() {
+    let recorder = TestRecorder::default();
+
+    metrics::with_local_recorder(&recorder, || {
+      let mut buf = BytesMut::new();
+      encode_bytes(&mut buf, &Frame::from(12)).unwrap();
+      complete::decode(&buf).unwrap();
+      complete::decode(b":1").unwrap();
+      assert!(complete::decode(b"&foo\r\n").is_err());
+      streaming::decode(b"*?\r\n").unwrap();
+      resp2_encode_bytes(&mut BytesMut::new(), &Resp2Frame::Integer(1)).unwrap();
+    });
+
+    let expected: Vec<(String, u64)> = vec![
+      ("redis_protocol_frames_encoded_total{version=resp3,kind=number}", 1),
+      ("redis_protocol_encoded_bytes{version=resp3,kind=number}", 5),
+      ("redis_protocol_frames_decoded_total{version=resp3,kind=number}", 1),
+      ("redis_protocol_decoded_bytes{version=resp3,kind=number}", 5),
+      ("redis_protocol_decode_errors_total{version=resp3}", 1),
+      ("redis_protocol_streamed_frames_total{version=resp3,kind=array}", 1),
+      ("redis_protocol_frames_decoded_total{version=resp3,kind=array}", 1),
+      ("redis_protocol_decoded_bytes{version=resp3,kind=array}", 4),
+      ("redis_protocol_frames_encoded_total{version=resp2,kind=number}", 1),
+      ("redis_protocol_encoded_bytes{version=resp2,kind=number}", 4),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_owned(), value))
+    .collect();
+    assert_eq!(*recorder.events.lock().unwrap(), expected);
+  }
+}
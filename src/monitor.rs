@@ -0,0 +1,209 @@
+//! Types and functions for parsing the output of the `MONITOR` command.
+//!
+//! <https://redis.io/commands/monitor>
+
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::Frame as Resp3Frame;
+use crate::types::RedisProtocolError;
+use crate::utils;
+use std::convert::TryFrom;
+use std::str;
+use std::time::Duration;
+
+/// The client that issued a command seen by `MONITOR`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MonitorClient {
+  /// A client connected over TCP or a unix socket, such as `127.0.0.1:6379`, `[::1]:6379`, or `unix:/tmp/redis.sock`.
+  Address(String),
+  /// A command issued from a Lua script.
+  Lua,
+}
+
+/// A command parsed from one line of `MONITOR` output.
+///
+/// ```text
+/// 1339518083.107412 [0 127.0.0.1:60866] "keys" "*"
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MonitorCommand {
+  /// The time the server processed the command, relative to the unix epoch.
+  pub timestamp: Duration,
+  /// The database index used by the client.
+  pub db: u32,
+  /// The client that issued the command.
+  pub client: MonitorClient,
+  /// The command name.
+  pub command: Vec<u8>,
+  /// The command arguments, not including the command name.
+  pub args: Vec<Vec<u8>>,
+}
+
+impl MonitorCommand {
+  /// Read the command name as a `str`, if it's valid UTF-8.
+  pub fn command_str(&self) -> Option<&str> {
+    str::from_utf8(&self.command).ok()
+  }
+}
+
+impl TryFrom<&Resp2Frame> for MonitorCommand {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Resp2Frame) -> Result<Self, Self::Error> {
+    match *frame {
+      Resp2Frame::SimpleString(ref s) => parse_monitor_line(s),
+      Resp2Frame::BulkString(ref b) => {
+        parse_monitor_line(str::from_utf8(b).map_err(|e| utils::invalid_response("MONITOR line", e))?)
+      },
+      _ => Err(utils::invalid_response("MONITOR line", "Expected string frame.")),
+    }
+  }
+}
+
+impl TryFrom<&Resp3Frame> for MonitorCommand {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Resp3Frame) -> Result<Self, Self::Error> {
+    match *frame {
+      Resp3Frame::SimpleString { ref data, .. } => {
+        parse_monitor_line(str::from_utf8(data.as_bytes()).map_err(|e| utils::invalid_response("MONITOR line", e))?)
+      },
+      Resp3Frame::BlobString { ref data, .. } | Resp3Frame::VerbatimString { ref data, .. } => {
+        parse_monitor_line(str::from_utf8(data).map_err(|e| utils::invalid_response("MONITOR line", e))?)
+      },
+      _ => Err(utils::invalid_response("MONITOR line", "Expected string frame.")),
+    }
+  }
+}
+
+fn parse_timestamp(s: &str) -> Result<Duration, RedisProtocolError> {
+  let (secs, micros) = match s.find('.') {
+    Some(idx) => (&s[0..idx], &s[idx + 1..]),
+    None => (s, ""),
+  };
+  let secs = secs
+    .parse::<u64>()
+    .map_err(|e| utils::invalid_response("MONITOR line", e))?;
+  let nanos = if micros.is_empty() {
+    0
+  } else {
+    if micros.len() > 9 {
+      return Err(utils::invalid_response("MONITOR line", "Invalid timestamp precision."));
+    }
+    let digits = micros
+      .parse::<u32>()
+      .map_err(|e| utils::invalid_response("MONITOR line", e))?;
+    digits * 10_u32.pow(9 - micros.len() as u32)
+  };
+
+  Ok(Duration::new(secs, nanos))
+}
+
+/// Parse one line of `MONITOR` output.
+///
+/// Arguments are unescaped according to the same rules used by `redis-cli`.
+pub fn parse_monitor_line(line: &str) -> Result<MonitorCommand, RedisProtocolError> {
+  let line = line.trim_end_matches(&['\r', '\n'][..]);
+  let ts_end = line
+    .find(' ')
+    .ok_or_else(|| utils::invalid_response("MONITOR line", "Missing timestamp."))?;
+  let timestamp = parse_timestamp(&line[0..ts_end])?;
+
+  let rest = line[ts_end + 1..].trim_start();
+  if !rest.starts_with('[') {
+    return Err(utils::invalid_response("MONITOR line", "Missing client info."));
+  }
+  // IPv6 addresses are wrapped in brackets, so look for the end of the client section before the quoted arguments
+  let client_end = match rest.find("] \"") {
+    Some(idx) => idx,
+    None => rest
+      .rfind(']')
+      .ok_or_else(|| utils::invalid_response("MONITOR line", "Missing client info."))?,
+  };
+  let client_info = &rest[1..client_end];
+  let (db, client) = match client_info.find(' ') {
+    Some(idx) => (&client_info[0..idx], &client_info[idx + 1..]),
+    None => return Err(utils::invalid_response("MONITOR line", "Missing client address.")),
+  };
+  let db = db
+    .parse::<u32>()
+    .map_err(|e| utils::invalid_response("MONITOR line", e))?;
+  let client = if client == "lua" {
+    MonitorClient::Lua
+  } else {
+    MonitorClient::Address(client.to_owned())
+  };
+
+  let mut args = utils::split_args(&rest.as_bytes()[client_end + 1..])?;
+  if args.is_empty() {
+    return Err(utils::invalid_response("MONITOR line", "Missing command."));
+  }
+  let command = args.remove(0);
+
+  Ok(MonitorCommand {
+    timestamp,
+    db,
+    client,
+    command,
+    args,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_monitor_line() {
+    let line = "1339518083.107412 [0 127.0.0.1:60866] \"keys\" \"*\"";
+    let command = parse_monitor_line(line).unwrap();
+
+    assert_eq!(command.timestamp, Duration::new(1339518083, 107412000));
+    assert_eq!(command.db, 0);
+    assert_eq!(command.client, MonitorClient::Address("127.0.0.1:60866".into()));
+    assert_eq!(command.command_str(), Some("keys"));
+    assert_eq!(command.args, vec![b"*".to_vec()]);
+  }
+
+  #[test]
+  fn should_parse_monitor_line_lua() {
+    let line = "1339518100.544926 [3 lua] \"set\" \"foo\" \"bar\"";
+    let command = parse_monitor_line(line).unwrap();
+
+    assert_eq!(command.db, 3);
+    assert_eq!(command.client, MonitorClient::Lua);
+    assert_eq!(command.args, vec![b"foo".to_vec(), b"bar".to_vec()]);
+  }
+
+  #[test]
+  fn should_parse_monitor_line_ipv6_and_escapes() {
+    let line = "1339518083.107412 [0 [::1]:60866] \"set\" \"a \\\"b\\\"\" \"\\x00\\xff\\r\\n\"";
+    let command = parse_monitor_line(line).unwrap();
+
+    assert_eq!(command.client, MonitorClient::Address("[::1]:60866".into()));
+    assert_eq!(command.args, vec![b"a \"b\"".to_vec(), vec![0, 255, b'\r', b'\n']]);
+  }
+
+  #[test]
+  fn should_parse_monitor_frame() {
+    let frame = Resp3Frame::SimpleString {
+      data: "1339518083.107412 [0 unix:/tmp/redis.sock] \"ping\"".into(),
+      attributes: None,
+    };
+    let command = MonitorCommand::try_from(&frame).unwrap();
+
+    assert_eq!(command.client, MonitorClient::Address("unix:/tmp/redis.sock".into()));
+    assert_eq!(command.command_str(), Some("ping"));
+    assert!(command.args.is_empty());
+
+    let frame = Resp2Frame::SimpleString("1339518083.107412 [0 unix:/tmp/redis.sock] \"ping\"".into());
+    assert_eq!(MonitorCommand::try_from(&frame).unwrap(), command);
+  }
+
+  #[test]
+  fn should_error_on_invalid_monitor_line() {
+    assert!(parse_monitor_line("OK").is_err());
+    assert!(parse_monitor_line("abc [0 lua] \"get\"").is_err());
+    assert!(parse_monitor_line("1339518083.107412 [0 lua]").is_err());
+    assert!(parse_monitor_line("1339518083.107412 [0 lua] \"get").is_err());
+  }
+}
@@ -0,0 +1,196 @@
+//! An observer interface for the decoding and encoding functions, for use with tracing, sampling, or audit logging.
+//!
+//! The `*_with_observer` variants of the decoding and encoding functions call the observer after each frame, so
+//! callers do not need to wrap every call site to measure the frames that are processed.
+//!
+//! ```
+//! # use redis_protocol::observer::FrameObserver;
+//! # use redis_protocol::resp3::decode::complete::decode_with_observer;
+//! # use redis_protocol::resp3::types::{DecodeConfig, FrameKind};
+//! # use std::sync::atomic::{AtomicUsize, Ordering};
+//! # use std::time::Duration;
+//! #[derive(Default)]
+//! struct ByteCounter {
+//!   decoded: AtomicUsize,
+//! }
+//!
+//! impl FrameObserver for ByteCounter {
+//!   fn on_frame_decoded(&self, _: FrameKind, bytes: usize, _: Duration) {
+//!     self.decoded.fetch_add(bytes, Ordering::Relaxed);
+//!   }
+//! }
+//!
+//! let observer = ByteCounter::default();
+//! decode_with_observer(b":1\r\n", &DecodeConfig::default(), &observer).unwrap();
+//! assert_eq!(observer.decoded.load(Ordering::Relaxed), 4);
+//! ```
+
+use crate::resp2::types::FrameKind as Resp2FrameKind;
+use crate::resp3::types::FrameKind;
+use crate::types::RedisProtocolError;
+use std::time::Duration;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::Instant;
+
+/// A set of callbacks that are called by the `*_with_observer` decoding and encoding functions.
+///
+/// Every callback does nothing by default. RESP2 frames are reported with the equivalent RESP3 [FrameKind], such as
+/// `BlobString` for bulk strings and `SimpleError` for errors.
+///
+/// The elapsed time is always zero on `wasm32-unknown-unknown` since the standard library clock is not available.
+pub trait FrameObserver {
+  /// Called after a frame is decoded, with the number of bytes consumed and the time spent decoding.
+  ///
+  /// Streamed frames are reported once for each chunk or header, with the kind of the streamed frame.
+  fn on_frame_decoded(&self, _kind: FrameKind, _bytes: usize, _elapsed: Duration) {}
+
+  /// Called after a frame is encoded, with the number of bytes written and the time spent encoding.
+  fn on_frame_encoded(&self, _kind: FrameKind, _bytes: usize, _elapsed: Duration) {}
+
+  /// Called when decoding fails.
+  fn on_decode_error(&self, _error: &RedisProtocolError) {}
+
+  /// Called when encoding fails.
+  fn on_encode_error(&self, _kind: FrameKind, _error: &RedisProtocolError) {}
+}
+
+/// Measures the time spent decoding or encoding a frame.
+///
+/// `Instant` panics on `wasm32-unknown-unknown`, so the elapsed time is always zero on that target.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Stopwatch {
+  #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+  start: Instant,
+}
+
+impl Stopwatch {
+  #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+  pub fn start() -> Self {
+    Stopwatch { start: Instant::now() }
+  }
+
+  #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+  pub fn start() -> Self {
+    Stopwatch {}
+  }
+
+  #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+  pub fn elapsed(&self) -> Duration {
+    self.start.elapsed()
+  }
+
+  #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+  pub fn elapsed(&self) -> Duration {
+    Duration::from_secs(0)
+  }
+}
+
+/// Read the RESP3 frame kind that corresponds to a RESP2 frame kind.
+pub(crate) fn resp2_kind_to_resp3(kind: Resp2FrameKind) -> FrameKind {
+  match kind {
+    Resp2FrameKind::SimpleString => FrameKind::SimpleString,
+    Resp2FrameKind::Error => FrameKind::SimpleError,
+    Resp2FrameKind::Integer => FrameKind::Number,
+    Resp2FrameKind::BulkString => FrameKind::BlobString,
+    Resp2FrameKind::Array => FrameKind::Array,
+    Resp2FrameKind::Null => FrameKind::Null,
+  }
+}
+
+/// Report the result of a decoding function to `observer`.
+pub(crate) fn observe_decode<T, F>(
+  observer: &dyn FrameObserver,
+  start: Stopwatch,
+  result: &Result<Option<(T, usize)>, RedisProtocolError>,
+  kind: F,
+) where
+  F: FnOnce(&T) -> FrameKind,
+{
+  match *result {
+    Ok(Some((ref frame, amt))) => observer.on_frame_decoded(kind(frame), amt, start.elapsed()),
+    Ok(None) => {}
+    Err(ref e) => observer.on_decode_error(e),
+  }
+}
+
+/// Report the result of an encoding function that returns the offset after the frame to `observer`.
+pub(crate) fn observe_encode(
+  observer: &dyn FrameObserver,
+  start: Stopwatch,
+  kind: FrameKind,
+  offset: usize,
+  result: &Result<usize, RedisProtocolError>,
+) {
+  match *result {
+    Ok(amt) => observer.on_frame_encoded(kind, amt.saturating_sub(offset), start.elapsed()),
+    Err(ref e) => observer.on_encode_error(kind, e),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp2::decode::decode_with_observer as resp2_decode;
+  use crate::resp2::encode::encode_bytes_with_observer as resp2_encode;
+  use crate::resp2::types::Frame as Resp2Frame;
+  use crate::resp3::decode::complete::decode_with_observer;
+  use crate::resp3::decode::streaming::decode_with_observer as stream_decode_with_observer;
+  use crate::resp3::encode::complete::encode_bytes_with_observer;
+  use crate::resp3::types::{DecodeConfig, EncodeConfig, Frame};
+  use bytes::BytesMut;
+  use std::sync::Mutex;
+
+  #[derive(Default)]
+  struct Recorder {
+    events: Mutex<Vec<(&'static str, FrameKind, usize)>>,
+  }
+
+  impl FrameObserver for Recorder {
+    fn on_frame_decoded(&self, kind: FrameKind, bytes: usize, _: Duration) {
+      self.events.lock().unwrap().push(("decoded", kind, bytes));
+    }
+
+    fn on_frame_encoded(&self, kind: FrameKind, bytes: usize, _: Duration) {
+      self.events.lock().unwrap().push(("encoded", kind, bytes));
+    }
+
+    fn on_decode_error(&self, _: &RedisProtocolError) {
+      self.events.lock().unwrap().push(("error", FrameKind::Null, 0));
+    }
+  }
+
+  #[test]
+  fn should_observe_resp3_frames() {
+    let observer = Recorder::default();
+    let config = DecodeConfig::default();
+
+    let mut buf = BytesMut::from(&b"prefix"[..]);
+    encode_bytes_with_observer(&mut buf, &Frame::from(12), &EncodeConfig::default(), &observer).unwrap();
+    decode_with_observer(&buf[6..], &config, &observer).unwrap();
+    decode_with_observer(b":1", &config, &observer).unwrap();
+    assert!(decode_with_observer(b"&foo\r\n", &config, &observer).is_err());
+    stream_decode_with_observer(b"*?\r\n", &config, &observer).unwrap();
+
+    assert_eq!(*observer.events.lock().unwrap(), vec![
+      ("encoded", FrameKind::Number, 5),
+      ("decoded", FrameKind::Number, 5),
+      ("error", FrameKind::Null, 0),
+      ("decoded", FrameKind::Array, 4),
+    ]);
+  }
+
+  #[test]
+  fn should_observe_resp2_frames() {
+    let observer = Recorder::default();
+    let mut buf = BytesMut::new();
+    let frame = Resp2Frame::Array(vec![Resp2Frame::BulkString(b"foo".to_vec())]);
+
+    resp2_encode(&mut buf, &frame, &observer).unwrap();
+    resp2_decode(&buf, &observer).unwrap();
+
+    assert_eq!(*observer.events.lock().unwrap(), vec![
+      ("encoded", FrameKind::Array, 13),
+      ("decoded", FrameKind::Array, 13),
+    ]);
+  }
+}
@@ -0,0 +1,207 @@
+//! A pool of RESP3 frames that reuses their allocations across decoding calls.
+//!
+//! Frames decoded with a [FramePool](crate::pool::FramePool) are returned in a [PooledFrame](crate::pool::PooledFrame)
+//! guard. When the guard is dropped the frame goes back to the pool, keeping its payload buffers and child vectors,
+//! and the next call to [decode](crate::pool::FramePool::decode) decodes into it with
+//! [decode_into](crate::resp3::decode::complete::decode_into). This reduces the number of allocations when the same
+//! kinds of frames are decoded repeatedly, such as in a proxy.
+//!
+//! ```
+//! # use redis_protocol::pool::FramePool;
+//! let pool = FramePool::new(16);
+//!
+//! for _ in 0..3 {
+//!   let (frame, len) = pool.decode(b"*2\r\n$3\r\nfoo\r\n:1\r\n").unwrap().unwrap();
+//!   assert_eq!(len, 17);
+//!   assert_eq!(frame.len(), 2);
+//! }
+//! assert_eq!(pool.idle(), 1);
+//! ```
+
+use crate::resp3::decode::complete::decode_into_with_config;
+use crate::resp3::types::{DecodeConfig, Frame};
+use crate::types::RedisProtocolError;
+use std::fmt;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+/// A thread-safe pool of frames that are reused while decoding.
+pub struct FramePool {
+  idle: Mutex<Vec<Frame>>,
+  max_idle: usize,
+  config: DecodeConfig,
+}
+
+impl fmt::Debug for FramePool {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("FramePool")
+      .field("idle", &self.idle())
+      .field("max_idle", &self.max_idle)
+      .finish()
+  }
+}
+
+impl FramePool {
+  /// Create a new pool that keeps at most `max_idle` frames that are not in use.
+  pub fn new(max_idle: usize) -> Self {
+    Self::with_config(max_idle, DecodeConfig::default())
+  }
+
+  /// Create a new pool that decodes frames with the provided decoding options.
+  pub fn with_config(max_idle: usize, config: DecodeConfig) -> Self {
+    FramePool {
+      idle: Mutex::new(Vec::with_capacity(max_idle)),
+      max_idle,
+      config,
+    }
+  }
+
+  /// The maximum number of frames kept in the pool.
+  pub fn max_idle(&self) -> usize {
+    self.max_idle
+  }
+
+  /// The number of frames in the pool that are not in use.
+  pub fn idle(&self) -> usize {
+    self.idle.lock().map(|idle| idle.len()).unwrap_or(0)
+  }
+
+  /// Drop every frame in the pool that is not in use, releasing their allocations.
+  pub fn clear(&self) {
+    if let Ok(mut idle) = self.idle.lock() {
+      idle.clear();
+    }
+  }
+
+  /// Take a frame from the pool, or a `Null` frame if the pool is empty.
+  ///
+  /// The contents of the frame are left over from its last use.
+  pub fn checkout(&self) -> PooledFrame<'_> {
+    let frame = self
+      .idle
+      .lock()
+      .ok()
+      .and_then(|mut idle| idle.pop())
+      .unwrap_or(Frame::Null { attributes: None });
+
+    PooledFrame {
+      frame: Some(frame),
+      pool: self,
+    }
+  }
+
+  /// Attempt to decode the first frame in `buf` into a frame from the pool, returning the frame and the number of
+  /// bytes consumed.
+  ///
+  /// If the byte slice contains an incomplete frame then `None` is returned and the frame goes back to the pool.
+  pub fn decode(&self, buf: &[u8]) -> Result<Option<(PooledFrame<'_>, usize)>, RedisProtocolError> {
+    let mut frame = self.checkout();
+
+    match decode_into_with_config(&mut frame, buf, &self.config)? {
+      Some(len) => Ok(Some((frame, len))),
+      None => Ok(None),
+    }
+  }
+
+  fn release(&self, frame: Frame) {
+    if let Ok(mut idle) = self.idle.lock() {
+      if idle.len() < self.max_idle {
+        idle.push(frame);
+      }
+    }
+  }
+}
+
+/// A frame checked out from a [FramePool] that goes back to the pool when dropped.
+pub struct PooledFrame<'a> {
+  frame: Option<Frame>,
+  pool: &'a FramePool,
+}
+
+impl<'a> PooledFrame<'a> {
+  /// Take the frame without returning it to the pool.
+  pub fn into_inner(mut self) -> Frame {
+    self.frame.take().unwrap_or(Frame::Null { attributes: None })
+  }
+
+  /// Take the frame, leaving an empty frame to go back to the pool.
+  pub fn take(&mut self) -> Frame {
+    mem::replace(self.deref_mut(), Frame::Null { attributes: None })
+  }
+}
+
+impl<'a> Deref for PooledFrame<'a> {
+  type Target = Frame;
+
+  fn deref(&self) -> &Frame {
+    self.frame.as_ref().expect("Missing pooled frame.")
+  }
+}
+
+impl<'a> DerefMut for PooledFrame<'a> {
+  fn deref_mut(&mut self) -> &mut Frame {
+    self.frame.as_mut().expect("Missing pooled frame.")
+  }
+}
+
+impl<'a> fmt::Debug for PooledFrame<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Debug::fmt(self.deref(), f)
+  }
+}
+
+impl<'a> Drop for PooledFrame<'a> {
+  fn drop(&mut self) {
+    if let Some(frame) = self.frame.take() {
+      self.pool.release(frame);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::types::BoundedFrameAllocator;
+
+  #[test]
+  fn should_reuse_pooled_frames() {
+    let pool = FramePool::new(1);
+    let buf = b"*1\r\n$3\r\nfoo\r\n";
+
+    let (frame, _) = pool.decode(buf).unwrap().unwrap();
+    let ptr = match *frame {
+      Frame::Array { ref data, .. } => data.as_ptr(),
+      _ => panic!("Expected array."),
+    };
+    let other = pool.checkout();
+    drop(frame);
+    drop(other);
+    assert_eq!(pool.idle(), 1);
+
+    let (frame, _) = pool.decode(b"*1\r\n$3\r\nbar\r\n").unwrap().unwrap();
+    match *frame {
+      Frame::Array { ref data, .. } => {
+        assert_eq!(data.as_ptr(), ptr);
+        assert_eq!(data[0].as_str(), Some("bar"));
+      },
+      _ => panic!("Expected array."),
+    };
+    assert_eq!(pool.idle(), 0);
+    assert_eq!(frame.into_inner().len(), 1);
+    assert_eq!(pool.idle(), 0);
+
+    assert!(pool.decode(&buf[..6]).unwrap().is_none());
+    assert!(pool.decode(b"~foo\r\n").is_err());
+    assert_eq!(pool.idle(), 1);
+  }
+
+  #[test]
+  fn should_decode_pooled_frames_with_allocator() {
+    let config = DecodeConfig::default().with_allocator(BoundedFrameAllocator::new(10));
+    let pool = FramePool::with_config(1, config);
+
+    assert!(pool.decode(b"$3\r\nfoo\r\n").unwrap().is_some());
+    assert!(pool.decode(b"$20\r\n01234567890123456789\r\n").is_err());
+  }
+}
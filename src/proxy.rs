@@ -0,0 +1,249 @@
+//! A sans-io pipeline for building proxies that inspect or modify the frames sent over a connection.
+//!
+//! A [Proxy](crate::proxy::Proxy) decodes the bytes read from one side of a connection and passes each frame to a
+//! hook. The [ProxyAction](crate::proxy::ProxyAction) returned by the hook decides whether the frame is forwarded to
+//! the other side, replaced, answered directly, or dropped. The proxy does not perform any IO, so the caller reads from
+//! and writes to the sockets with whichever runtime it uses.
+//!
+//! ```
+//! # use redis_protocol::proxy::{Proxy, ProxyAction};
+//! # use redis_protocol::resp3::types::{Frame, RespVersion};
+//! let mut proxy = Proxy::new(RespVersion::RESP2, |frame: &mut Frame| match frame.command_name().as_deref() {
+//!   Some("FLUSHALL") => ProxyAction::Reply(Frame::SimpleError {
+//!     data: "ERR FLUSHALL is disabled".into(),
+//!     attributes: None,
+//!   }),
+//!   _ => ProxyAction::Forward,
+//! });
+//!
+//! proxy.feed(b"*1\r\n$8\r\nFLUSHALL\r\n*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n").unwrap();
+//! assert_eq!(&proxy.take_forward()[..], b"*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n");
+//! assert_eq!(&proxy.take_replies()[..], b"-ERR FLUSHALL is disabled\r\n");
+//! ```
+//!
+//! Frames are passed to the hook as RESP3 frames regardless of the RESP version of the connection. Forwarded frames
+//! are copied from the input without being re-encoded, and RESP3 streamed frames are forwarded chunk by chunk without
+//! being passed to the hook, so large streams are not buffered by the proxy.
+
+use crate::resp2::decode::decode as resp2_decode;
+use crate::resp2::encode::encode_bytes as resp2_encode_bytes;
+use crate::resp3::decode::streaming::decode as resp3_stream_decode;
+use crate::resp3::encode::complete::encode_bytes as resp3_encode_bytes;
+use crate::resp3::types::{DecodedFrame, Frame, RespVersion};
+use crate::types::{RedisProtocolError, RedisProtocolErrorKind};
+use crate::utils::{resp2_frame_to_resp3, resp3_frame_to_resp2};
+use bytes::BytesMut;
+
+fn encode(version: &RespVersion, buf: &mut BytesMut, frame: Frame) -> Result<(), RedisProtocolError> {
+  match *version {
+    RespVersion::RESP2 => resp2_encode_bytes(buf, &resp3_frame_to_resp2(frame)?)?,
+    RespVersion::RESP3 => resp3_encode_bytes(buf, &frame)?,
+  };
+
+  Ok(())
+}
+
+/// The action taken by a [Proxy] after a frame is passed to its hook.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProxyAction {
+  /// Forward the original bytes of the frame. Any changes made by the hook are ignored.
+  Forward,
+  /// Encode and forward the frame as modified by the hook.
+  Rewrite,
+  /// Send the provided frame back to the side that sent the original frame, instead of forwarding it.
+  Reply(Frame),
+  /// Discard the frame.
+  Drop,
+}
+
+/// A sans-io pipeline that decodes frames, passes them to a hook, and encodes the result.
+///
+/// Bytes are added with [feed](Self::feed) and the output is read with [take_forward](Self::take_forward) and
+/// [take_replies](Self::take_replies). Partial frames are buffered until the rest of the frame is fed to the proxy.
+///
+/// Replies are written in the order the hook returns them, so callers that proxy pipelined commands are responsible
+/// for ordering them relative to the responses of forwarded commands.
+pub struct Proxy<F: FnMut(&mut Frame) -> ProxyAction> {
+  version: RespVersion,
+  hook: F,
+  buf: BytesMut,
+  forward: BytesMut,
+  replies: BytesMut,
+  streaming: bool,
+}
+
+impl<F: FnMut(&mut Frame) -> ProxyAction> Proxy<F> {
+  /// Create a new proxy that decodes and encodes frames with the provided RESP version.
+  pub fn new(version: RespVersion, hook: F) -> Self {
+    Proxy {
+      version,
+      hook,
+      buf: BytesMut::new(),
+      forward: BytesMut::new(),
+      replies: BytesMut::new(),
+      streaming: false,
+    }
+  }
+
+  /// Read the RESP version used to decode and encode frames.
+  pub fn version(&self) -> &RespVersion {
+    &self.version
+  }
+
+  /// Change the RESP version used to decode and encode frames, such as after a `HELLO` command is forwarded.
+  pub fn set_version(&mut self, version: RespVersion) {
+    self.version = version;
+  }
+
+  /// The number of bytes buffered while waiting for the rest of a frame.
+  pub fn buffered(&self) -> usize {
+    self.buf.len()
+  }
+
+  /// Whether or not the proxy is forwarding the chunks of a RESP3 streamed frame.
+  pub fn is_streaming(&self) -> bool {
+    self.streaming
+  }
+
+  /// Take the bytes that should be forwarded to the other side of the connection.
+  pub fn take_forward(&mut self) -> BytesMut {
+    self.forward.split()
+  }
+
+  /// Take the bytes that should be sent back to the side that sent the frames.
+  pub fn take_replies(&mut self) -> BytesMut {
+    self.replies.split()
+  }
+
+  fn decode(&mut self) -> Result<Option<(Option<Frame>, usize)>, RedisProtocolError> {
+    let (frame, len) = match self.version {
+      RespVersion::RESP2 => match resp2_decode(&self.buf)? {
+        Some((frame, len)) => (DecodedFrame::Complete(resp2_frame_to_resp3(frame)), len),
+        None => return Ok(None),
+      },
+      RespVersion::RESP3 => match resp3_stream_decode(&self.buf)? {
+        Some(result) => result,
+        None => return Ok(None),
+      },
+    };
+
+    let frame = match frame {
+      DecodedFrame::Streaming(_) if self.streaming => {
+        return Err(RedisProtocolError::new(
+          RedisProtocolErrorKind::DecodeError,
+          "Cannot start a stream while already inside a stream.",
+        ))
+      }
+      DecodedFrame::Streaming(_) => {
+        self.streaming = true;
+        None
+      }
+      DecodedFrame::Complete(frame) if self.streaming => {
+        self.streaming = !frame.is_end_stream_frame();
+        None
+      }
+      DecodedFrame::Complete(frame) => Some(frame),
+    };
+
+    Ok(Some((frame, len)))
+  }
+
+  /// Append `data` to the internal buffer and process every frame that can be decoded.
+  ///
+  /// The connection should be closed after an error since the remaining bytes cannot be decoded reliably. Output
+  /// written before the error can still be taken from the proxy.
+  pub fn feed(&mut self, data: &[u8]) -> Result<(), RedisProtocolError> {
+    self.buf.extend_from_slice(data);
+
+    while let Some((frame, len)) = self.decode()? {
+      let bytes = self.buf.split_to(len);
+      let mut frame = match frame {
+        Some(frame) => frame,
+        None => {
+          self.forward.extend_from_slice(&bytes);
+          continue;
+        }
+      };
+
+      match (self.hook)(&mut frame) {
+        ProxyAction::Forward => self.forward.extend_from_slice(&bytes),
+        ProxyAction::Rewrite => encode(&self.version, &mut self.forward, frame)?,
+        ProxyAction::Reply(reply) => encode(&self.version, &mut self.replies, reply)?,
+        ProxyAction::Drop => {}
+      };
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rename_keys(frame: &mut Frame) -> ProxyAction {
+    match frame.command_name().as_deref() {
+      Some("KEYS") => {
+        if let Frame::Array { ref mut data, .. } = frame {
+          data[0] = Frame::BlobString {
+            data: b"SCAN".to_vec(),
+            attributes: None,
+          };
+        }
+        ProxyAction::Rewrite
+      }
+      Some("DEBUG") => ProxyAction::Drop,
+      Some("PING") => ProxyAction::Reply(Frame::SimpleString {
+        data: "PONG".into(),
+        attributes: None,
+      }),
+      _ => ProxyAction::Forward,
+    }
+  }
+
+  #[test]
+  fn should_apply_proxy_actions_across_chunks() {
+    let mut proxy = Proxy::new(RespVersion::RESP2, rename_keys);
+    let input = b"*2\r\n$4\r\nKEYS\r\n$1\r\n*\r\n*1\r\n$5\r\nDEBUG\r\n*1\r\n$4\r\nPING\r\n*1\r\n$4\r\nINFO\r\n";
+
+    for chunk in input.chunks(3) {
+      proxy.feed(chunk).unwrap();
+    }
+
+    assert_eq!(proxy.buffered(), 0);
+    assert_eq!(
+      &proxy.take_forward()[..],
+      &b"*2\r\n$4\r\nSCAN\r\n$1\r\n*\r\n*1\r\n$4\r\nINFO\r\n"[..]
+    );
+    assert_eq!(&proxy.take_replies()[..], b"+PONG\r\n");
+    assert!(proxy.take_forward().is_empty());
+  }
+
+  #[test]
+  fn should_pass_through_streamed_frames() {
+    let mut hooked = Vec::new();
+    let mut proxy = Proxy::new(RespVersion::RESP3, |frame: &mut Frame| {
+      hooked.push(frame.clone());
+      ProxyAction::Forward
+    });
+    let input = b"$?\r\n;3\r\nfoo\r\n;0\r\n*?\r\n:1\r\n.\r\n:2\r\n";
+
+    proxy.feed(&input[..10]).unwrap();
+    assert!(proxy.is_streaming());
+    proxy.feed(&input[10..]).unwrap();
+    assert!(!proxy.is_streaming());
+
+    assert_eq!(&proxy.take_forward()[..], &input[..]);
+    drop(proxy);
+    assert_eq!(hooked, vec![Frame::from(2)]);
+  }
+
+  #[test]
+  fn should_error_on_invalid_frames() {
+    let mut proxy = Proxy::new(RespVersion::RESP3, |_: &mut Frame| ProxyAction::Forward);
+
+    proxy.feed(b":1\r\n").unwrap();
+    assert!(proxy.feed(b"~foo\r\n").is_err());
+    assert_eq!(&proxy.take_forward()[..], b":1\r\n");
+  }
+}
@@ -0,0 +1,476 @@
+//! A sans-io tracker for the publish-subscribe state of a connection.
+//!
+//! A [SubscriptionTracker](crate::pubsub::SubscriptionTracker) is updated with the commands sent to the server and
+//! the frames received from it. It maintains the set of channels, patterns, and shard channels that the server has
+//! confirmed, and flags confirmations and messages that do not match a request or an active subscription.
+//!
+//! ```
+//! # use redis_protocol::commands::request::RedisCommand;
+//! # use redis_protocol::pubsub::{PubsubEventKind, SubscriptionKind, SubscriptionTracker};
+//! # use redis_protocol::resp3::types::Frame;
+//! let blob = |s: &str| Frame::BlobString {
+//!   data: s.as_bytes().to_vec(),
+//!   attributes: None,
+//! };
+//! let mut tracker = SubscriptionTracker::new();
+//! tracker.track_command(&RedisCommand::from_args(vec![b"SUBSCRIBE".to_vec(), b"foo".to_vec()]).unwrap());
+//!
+//! let confirmation = Frame::Push {
+//!   data: vec![blob("subscribe"), blob("foo"), 1.into()],
+//!   attributes: None,
+//! };
+//! let event = tracker.process(&confirmation).unwrap();
+//! assert_eq!(event.kind, PubsubEventKind::Subscribed(1));
+//! assert!(!event.out_of_band);
+//! assert!(tracker.is_subscribed(SubscriptionKind::Channel, b"foo"));
+//! ```
+//!
+//! Both the `>3 message <channel> <message>` form of push frames sent by Redis and the form with a `pubsub` prefix
+//! are supported.
+//!
+//! <https://redis.io/docs/manual/pubsub/>
+
+use crate::commands::request::RedisCommand;
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::Frame as Resp3Frame;
+use crate::utils::{PATTERN_PUBSUB_PREFIX, PUBSUB_PREFIX, PUBSUB_PUSH_PREFIX};
+use std::collections::BTreeSet;
+
+/// Prefix on messages received on a shard channel.
+pub const SHARD_PUBSUB_PREFIX: &str = "smessage";
+
+/// The kind of a subscription.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum SubscriptionKind {
+  /// A channel, used with `SUBSCRIBE`.
+  Channel,
+  /// A glob-style pattern, used with `PSUBSCRIBE`.
+  Pattern,
+  /// A shard channel, used with `SSUBSCRIBE`.
+  Shard,
+}
+
+impl SubscriptionKind {
+  fn idx(self) -> usize {
+    match self {
+      SubscriptionKind::Channel => 0,
+      SubscriptionKind::Pattern => 1,
+      SubscriptionKind::Shard => 2,
+    }
+  }
+
+  /// Read the name of the command used to subscribe.
+  pub fn subscribe_command(&self) -> &'static str {
+    match *self {
+      SubscriptionKind::Channel => "SUBSCRIBE",
+      SubscriptionKind::Pattern => "PSUBSCRIBE",
+      SubscriptionKind::Shard => "SSUBSCRIBE",
+    }
+  }
+
+  /// Read the name of the command used to unsubscribe.
+  pub fn unsubscribe_command(&self) -> &'static str {
+    match *self {
+      SubscriptionKind::Channel => "UNSUBSCRIBE",
+      SubscriptionKind::Pattern => "PUNSUBSCRIBE",
+      SubscriptionKind::Shard => "SUNSUBSCRIBE",
+    }
+  }
+
+  /// Read the kind of subscription and whether the command subscribes or unsubscribes, ignoring case.
+  fn from_command(name: &str) -> Option<(Self, bool)> {
+    let kind = match name.to_ascii_uppercase().as_str() {
+      "SUBSCRIBE" => (SubscriptionKind::Channel, true),
+      "PSUBSCRIBE" => (SubscriptionKind::Pattern, true),
+      "SSUBSCRIBE" => (SubscriptionKind::Shard, true),
+      "UNSUBSCRIBE" => (SubscriptionKind::Channel, false),
+      "PUNSUBSCRIBE" => (SubscriptionKind::Pattern, false),
+      "SUNSUBSCRIBE" => (SubscriptionKind::Shard, false),
+      _ => return None,
+    };
+
+    Some(kind)
+  }
+}
+
+/// The kind of a publish-subscribe frame received from the server.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PubsubEventKind {
+  /// A subscription was confirmed, with the number of subscriptions on the connection.
+  Subscribed(i64),
+  /// An unsubscription was confirmed, with the number of remaining subscriptions on the connection.
+  Unsubscribed(i64),
+  /// A message was published.
+  Message,
+}
+
+/// A publish-subscribe frame processed by a [SubscriptionTracker].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PubsubEvent {
+  pub kind: PubsubEventKind,
+  pub subscription: SubscriptionKind,
+  /// The channel or pattern of the subscription. Messages matched against a pattern subscription contain the pattern.
+  ///
+  /// This is `None` if an unsubscribe command was sent without any active subscriptions.
+  pub channel: Option<Vec<u8>>,
+  /// Whether or not the frame does not match a command sent on the connection or an active subscription, such as a
+  /// shard channel unsubscription sent by the server after a slot migration.
+  pub out_of_band: bool,
+}
+
+/// Read the fields of publish-subscribe frames from either RESP version.
+trait PubsubFrame {
+  fn name(&self) -> Option<&str>;
+  fn channel(&self) -> Option<&[u8]>;
+  fn count(&self) -> Option<i64>;
+  fn is_null(&self) -> bool;
+}
+
+impl PubsubFrame for Resp3Frame {
+  fn name(&self) -> Option<&str> {
+    self.as_str()
+  }
+
+  fn channel(&self) -> Option<&[u8]> {
+    match *self {
+      Resp3Frame::BlobString { ref data, .. } => Some(data),
+      Resp3Frame::SimpleString { ref data, .. } => Some(data.as_bytes()),
+      _ => None,
+    }
+  }
+
+  fn count(&self) -> Option<i64> {
+    match *self {
+      Resp3Frame::Number { data, .. } => Some(data),
+      _ => None,
+    }
+  }
+
+  fn is_null(&self) -> bool {
+    matches!(*self, Resp3Frame::Null { .. })
+  }
+}
+
+impl PubsubFrame for Resp2Frame {
+  fn name(&self) -> Option<&str> {
+    self.as_str()
+  }
+
+  fn channel(&self) -> Option<&[u8]> {
+    match *self {
+      Resp2Frame::BulkString(ref data) => Some(data),
+      Resp2Frame::SimpleString(ref data) => Some(data.as_bytes()),
+      _ => None,
+    }
+  }
+
+  fn count(&self) -> Option<i64> {
+    match *self {
+      Resp2Frame::Integer(data) => Some(data),
+      _ => None,
+    }
+  }
+
+  fn is_null(&self) -> bool {
+    self.is_null()
+  }
+}
+
+/// Parse the inner frames of a push or array frame, without the subscription state.
+fn parse_event<F: PubsubFrame>(data: &[F]) -> Option<PubsubEvent> {
+  let data = match data.first().and_then(|f| f.name()) {
+    Some(PUBSUB_PUSH_PREFIX) => &data[1..],
+    _ => data,
+  };
+  let name = data.first()?.name()?;
+
+  let (kind, subscription, channel) = match (name, data.len()) {
+    (PUBSUB_PREFIX, 3) => (PubsubEventKind::Message, SubscriptionKind::Channel, data[1].channel()?),
+    (SHARD_PUBSUB_PREFIX, 3) => (PubsubEventKind::Message, SubscriptionKind::Shard, data[1].channel()?),
+    (PATTERN_PUBSUB_PREFIX, 4) => (PubsubEventKind::Message, SubscriptionKind::Pattern, data[1].channel()?),
+    (_, 3) => {
+      let (subscription, subscribe) = SubscriptionKind::from_command(name)?;
+      let count = data[2].count()?;
+
+      if subscribe {
+        (PubsubEventKind::Subscribed(count), subscription, data[1].channel()?)
+      } else if data[1].is_null() {
+        return Some(PubsubEvent {
+          kind: PubsubEventKind::Unsubscribed(count),
+          subscription,
+          channel: None,
+          out_of_band: false,
+        });
+      } else {
+        (PubsubEventKind::Unsubscribed(count), subscription, data[1].channel()?)
+      }
+    },
+    _ => return None,
+  };
+
+  Some(PubsubEvent {
+    kind,
+    subscription,
+    channel: Some(channel.to_vec()),
+    out_of_band: false,
+  })
+}
+
+/// Remove one instance of `channel` from `pending`, returning whether it was found.
+fn remove_pending(pending: &mut Vec<Vec<u8>>, channel: &[u8]) -> bool {
+  match pending.iter().position(|c| c == channel) {
+    Some(idx) => {
+      pending.remove(idx);
+      true
+    },
+    None => false,
+  }
+}
+
+/// A sans-io tracker for the channels, patterns, and shard channels that a connection is subscribed to.
+///
+/// Commands should be passed to [track_command](Self::track_command) when they are sent, and frames should be passed
+/// to [process](Self::process) or [process_resp2](Self::process_resp2) when they are received. Subscriptions are only
+/// considered active once the server confirms them.
+#[derive(Clone, Debug, Default)]
+pub struct SubscriptionTracker {
+  active: [BTreeSet<Vec<u8>>; 3],
+  pending_subscribe: [Vec<Vec<u8>>; 3],
+  pending_unsubscribe: [Vec<Vec<u8>>; 3],
+  /// The number of unsubscribe commands sent without arguments that have not finished.
+  pending_unsubscribe_all: [usize; 3],
+}
+
+impl SubscriptionTracker {
+  /// Create a new tracker without any subscriptions.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Record a command sent to the server. Commands other than the subscribe and unsubscribe commands are ignored.
+  pub fn track_command(&mut self, command: &RedisCommand) {
+    let (kind, subscribe) = match SubscriptionKind::from_command(&command.name) {
+      Some(result) => result,
+      None => return,
+    };
+    let idx = kind.idx();
+
+    if subscribe {
+      self.pending_subscribe[idx].extend(command.args.iter().cloned());
+    } else if command.args.is_empty() {
+      self.pending_unsubscribe_all[idx] += 1;
+    } else {
+      self.pending_unsubscribe[idx].extend(command.args.iter().cloned());
+    }
+  }
+
+  fn update(&mut self, mut event: PubsubEvent) -> PubsubEvent {
+    let idx = event.subscription.idx();
+
+    event.out_of_band = match (&event.kind, &event.channel) {
+      (PubsubEventKind::Subscribed(_), Some(channel)) => {
+        self.active[idx].insert(channel.clone());
+        !remove_pending(&mut self.pending_subscribe[idx], channel)
+      },
+      (PubsubEventKind::Unsubscribed(_), channel) => {
+        let expected = match channel {
+          Some(channel) => {
+            self.active[idx].remove(channel);
+            remove_pending(&mut self.pending_unsubscribe[idx], channel)
+          },
+          None => false,
+        } || self.pending_unsubscribe_all[idx] > 0;
+
+        // the server sends one confirmation per subscription, so an unsubscribe command without arguments is finished
+        // once there are no subscriptions of that kind
+        if self.active[idx].is_empty() && self.pending_unsubscribe_all[idx] > 0 {
+          self.pending_unsubscribe_all[idx] -= 1;
+        }
+        !expected
+      },
+      (_, Some(channel)) => !self.active[idx].contains(channel),
+      _ => true,
+    };
+
+    event
+  }
+
+  /// Update the subscriptions from a frame received from the server.
+  ///
+  /// Returns `None` if the frame is not a subscription confirmation or a published message.
+  pub fn process(&mut self, frame: &Resp3Frame) -> Option<PubsubEvent> {
+    match *frame {
+      Resp3Frame::Push { ref data, .. } | Resp3Frame::Array { ref data, .. } => {
+        parse_event(data).map(|event| self.update(event))
+      },
+      _ => None,
+    }
+  }
+
+  /// Update the subscriptions from a RESP2 frame received from the server.
+  ///
+  /// Returns `None` if the frame is not a subscription confirmation or a published message.
+  pub fn process_resp2(&mut self, frame: &Resp2Frame) -> Option<PubsubEvent> {
+    match *frame {
+      Resp2Frame::Array(ref data) => parse_event(data).map(|event| self.update(event)),
+      _ => None,
+    }
+  }
+
+  /// Whether or not the server confirmed a subscription to `channel`.
+  pub fn is_subscribed(&self, kind: SubscriptionKind, channel: &[u8]) -> bool {
+    self.active[kind.idx()].contains(channel)
+  }
+
+  /// Read the confirmed subscriptions of the provided kind, in sorted order.
+  pub fn subscriptions(&self, kind: SubscriptionKind) -> impl Iterator<Item = &[u8]> {
+    self.active[kind.idx()].iter().map(|c| c.as_slice())
+  }
+
+  /// The number of confirmed subscriptions of every kind.
+  pub fn len(&self) -> usize {
+    self.active.iter().map(|a| a.len()).sum()
+  }
+
+  /// Whether or not there are no confirmed subscriptions.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Whether or not any subscribe or unsubscribe commands are waiting for a confirmation.
+  pub fn has_pending(&self) -> bool {
+    self.pending_subscribe.iter().any(|p| !p.is_empty())
+      || self.pending_unsubscribe.iter().any(|p| !p.is_empty())
+      || self.pending_unsubscribe_all.iter().any(|p| *p > 0)
+  }
+
+  /// Reset the tracker after the connection is re-established, returning the commands that restore the intended
+  /// subscriptions.
+  ///
+  /// The intended subscriptions are the confirmed subscriptions and pending subscribe commands, without the pending
+  /// unsubscribe commands. The returned commands are recorded as if they were passed to
+  /// [track_command](Self::track_command). Shard channels are subscribed to with one command per channel, since every
+  /// channel in a `SSUBSCRIBE` command must belong to the same slot.
+  pub fn reconnect(&mut self) -> Vec<RedisCommand> {
+    let old = std::mem::take(self);
+    let mut commands = Vec::new();
+
+    for kind in [SubscriptionKind::Channel, SubscriptionKind::Pattern, SubscriptionKind::Shard].iter() {
+      let idx = kind.idx();
+      let mut channels = old.active[idx].clone();
+      if old.pending_unsubscribe_all[idx] > 0 {
+        channels.clear();
+      }
+      for channel in old.pending_unsubscribe[idx].iter() {
+        channels.remove(channel);
+      }
+      channels.extend(old.pending_subscribe[idx].iter().cloned());
+      if channels.is_empty() {
+        continue;
+      }
+
+      let groups = if *kind == SubscriptionKind::Shard {
+        channels.into_iter().map(|c| vec![c]).collect()
+      } else {
+        vec![channels.into_iter().collect()]
+      };
+      for args in groups {
+        let command = RedisCommand {
+          name: kind.subscribe_command().to_owned(),
+          subcommand: None,
+          args,
+        };
+
+        self.track_command(&command);
+        commands.push(command);
+      }
+    }
+
+    commands
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::test_frames::*;
+
+  fn command(args: &[&str]) -> RedisCommand {
+    RedisCommand::from_args(args.iter().map(|a| a.as_bytes().to_vec()).collect()).unwrap()
+  }
+
+  #[test]
+  fn should_track_subscriptions() {
+    let mut tracker = SubscriptionTracker::new();
+    tracker.track_command(&command(&["SUBSCRIBE", "a", "b"]));
+    tracker.track_command(&command(&["psubscribe", "c*"]));
+
+    for (name, channel, count) in [("subscribe", "a", 1), ("subscribe", "b", 2), ("psubscribe", "c*", 3)].iter() {
+      let event = tracker.process(&push(vec![blob(name), blob(channel), (*count).into()]));
+      assert!(!event.unwrap().out_of_band);
+    }
+    assert_eq!(tracker.len(), 3);
+    assert!(!tracker.has_pending());
+
+    let message = push(vec![blob("pubsub"), blob("pmessage"), blob("c*"), blob("cd"), blob("foo")]);
+    assert_eq!(
+      tracker.process(&message),
+      Some(PubsubEvent {
+        kind: PubsubEventKind::Message,
+        subscription: SubscriptionKind::Pattern,
+        channel: Some(b"c*".to_vec()),
+        out_of_band: false,
+      })
+    );
+    assert!(tracker.process(&push(vec![blob("message"), blob("z"), blob("foo")])).unwrap().out_of_band);
+    assert_eq!(tracker.process(&Resp3Frame::from(1)), None);
+
+    tracker.track_command(&command(&["UNSUBSCRIBE"]));
+    let frame = Resp2Frame::Array(vec![
+      Resp2Frame::BulkString(b"unsubscribe".to_vec()),
+      Resp2Frame::BulkString(b"a".to_vec()),
+      Resp2Frame::Integer(2),
+    ]);
+    assert!(!tracker.process_resp2(&frame).unwrap().out_of_band);
+    assert!(tracker.has_pending());
+    assert!(!tracker.process(&push(vec![blob("unsubscribe"), blob("b"), 1.into()])).unwrap().out_of_band);
+    assert!(!tracker.has_pending());
+    assert_eq!(tracker.subscriptions(SubscriptionKind::Pattern).collect::<Vec<_>>(), vec![b"c*"]);
+  }
+
+  #[test]
+  fn should_flag_server_unsubscriptions() {
+    let mut tracker = SubscriptionTracker::new();
+    tracker.track_command(&command(&["SSUBSCRIBE", "a"]));
+    tracker.process(&push(vec![blob("ssubscribe"), blob("a"), 1.into()]));
+
+    let event = tracker.process(&push(vec![blob("sunsubscribe"), blob("a"), 0.into()])).unwrap();
+    assert_eq!(event.kind, PubsubEventKind::Unsubscribed(0));
+    assert!(event.out_of_band);
+    assert!(tracker.is_empty());
+  }
+
+  #[test]
+  fn should_resubscribe_on_reconnect() {
+    let mut tracker = SubscriptionTracker::new();
+    tracker.track_command(&command(&["SUBSCRIBE", "a", "b"]));
+    tracker.track_command(&command(&["SSUBSCRIBE", "s1"]));
+    tracker.track_command(&command(&["SSUBSCRIBE", "s2"]));
+    tracker.process(&push(vec![blob("subscribe"), blob("a"), 1.into()]));
+    tracker.process(&push(vec![blob("subscribe"), blob("b"), 2.into()]));
+    tracker.track_command(&command(&["UNSUBSCRIBE", "b"]));
+    tracker.track_command(&command(&["SUBSCRIBE", "c"]));
+
+    let commands = tracker.reconnect();
+    assert_eq!(
+      commands,
+      vec![
+        command(&["SUBSCRIBE", "a", "c"]),
+        command(&["SSUBSCRIBE", "s1"]),
+        command(&["SSUBSCRIBE", "s2"])
+      ]
+    );
+    assert!(tracker.is_empty());
+    assert!(tracker.has_pending());
+  }
+}
@@ -0,0 +1,329 @@
+//! A streaming parser for RDB files, such as those created by `SAVE`, `BGSAVE`, or a replica sync.
+//!
+//! Entries are decoded one at a time and passed to a [RdbVisitor](crate::rdb::file::RdbVisitor), so memory use is
+//! bounded by the size of the largest value rather than the size of the file.
+//!
+//! <https://rdb.fnordig.de/file_format.html>
+
+use crate::rdb::value::{decode_value, RdbValue};
+use crate::rdb::*;
+use crate::types::RedisProtocolError;
+use crate::utils::{self, crc64_jones_update};
+use std::io::Read;
+
+const RDB_OPCODE_SLOT_INFO: u8 = 0xf4;
+const RDB_OPCODE_FUNCTION2: u8 = 0xf5;
+const RDB_OPCODE_MODULE_AUX: u8 = 0xf7;
+const RDB_OPCODE_IDLE: u8 = 0xf8;
+const RDB_OPCODE_FREQ: u8 = 0xf9;
+const RDB_OPCODE_AUX: u8 = 0xfa;
+const RDB_OPCODE_RESIZEDB: u8 = 0xfb;
+const RDB_OPCODE_EXPIRETIME_MS: u8 = 0xfc;
+const RDB_OPCODE_EXPIRETIME: u8 = 0xfd;
+const RDB_OPCODE_SELECTDB: u8 = 0xfe;
+const RDB_OPCODE_EOF: u8 = 0xff;
+
+/// The first RDB version that includes a checksum after the EOF opcode.
+const RDB_CHECKSUM_VERSION: u32 = 5;
+
+/// A key and value read from an RDB file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RdbEntry {
+  /// The database that contains the key.
+  pub db: u64,
+  pub key: Vec<u8>,
+  pub value: RdbValue,
+  /// The expiration time of the key, as a unix timestamp in milliseconds.
+  pub expire_at_ms: Option<u64>,
+  /// The LRU idle time of the key, in seconds.
+  pub idle: Option<u64>,
+  /// The LFU access frequency of the key.
+  pub freq: Option<u8>,
+}
+
+/// A callback interface for the contents of an RDB file.
+///
+/// Every function has a default implementation that ignores its arguments. Returning an error stops the parser and
+/// returns the error to the caller.
+pub trait RdbVisitor {
+  /// Called with the RDB version from the file header.
+  fn version(&mut self, _version: u32) -> Result<(), RedisProtocolError> {
+    Ok(())
+  }
+
+  /// Called for each auxiliary field, such as `redis-ver` or `ctime`.
+  fn aux_field(&mut self, _key: &[u8], _value: &[u8]) -> Result<(), RedisProtocolError> {
+    Ok(())
+  }
+
+  /// Called when subsequent entries belong to a different database.
+  fn select_db(&mut self, _db: u64) -> Result<(), RedisProtocolError> {
+    Ok(())
+  }
+
+  /// Called with the number of keys, and the number of keys with an expiration, in the current database.
+  fn resize_db(&mut self, _db_size: u64, _expires_size: u64) -> Result<(), RedisProtocolError> {
+    Ok(())
+  }
+
+  /// Called with the source code of each function library.
+  fn function(&mut self, _code: &[u8]) -> Result<(), RedisProtocolError> {
+    Ok(())
+  }
+
+  /// Called for each key in the file.
+  fn entry(&mut self, _entry: RdbEntry) -> Result<(), RedisProtocolError> {
+    Ok(())
+  }
+
+  /// Called after the EOF opcode with the checksum, or `None` if the file does not include one.
+  fn end(&mut self, _checksum: Option<u64>) -> Result<(), RedisProtocolError> {
+    Ok(())
+  }
+}
+
+/// A reader that tracks the CRC64 of every byte read.
+struct ChecksumReader<R> {
+  inner: R,
+  crc: u64,
+}
+
+impl<R: Read> Read for ChecksumReader<R> {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    let len = self.inner.read(buf)?;
+    self.crc = crc64_jones_update(self.crc, &buf[0..len]);
+    Ok(len)
+  }
+}
+
+fn read_header<R: Read>(reader: &mut R) -> Result<u32, RedisProtocolError> {
+  let mut header = [0_u8; 9];
+  read_exact(reader, &mut header)?;
+
+  if &header[0..5] != b"REDIS" {
+    return Err(utils::invalid_response("RDB data", "Invalid magic string."));
+  }
+  std::str::from_utf8(&header[5..])
+    .ok()
+    .and_then(|v| v.parse::<u32>().ok())
+    .ok_or_else(|| utils::invalid_response("RDB data", "Invalid version."))
+}
+
+/// Parse an RDB file from `reader`, calling `visitor` with each part of the file.
+///
+/// The checksum is verified if the file includes one. Module values, module auxiliary data, and streams are not
+/// supported. Callers should wrap unbuffered readers, such as files or sockets, in a `BufReader`.
+pub fn parse_rdb<R, V>(reader: R, visitor: &mut V) -> Result<(), RedisProtocolError>
+where
+  R: Read,
+  V: RdbVisitor,
+{
+  let mut reader = ChecksumReader { inner: reader, crc: 0 };
+  let version = read_header(&mut reader)?;
+  visitor.version(version)?;
+
+  let mut db = 0;
+  let (mut expire_at_ms, mut idle, mut freq) = (None, None, None);
+  loop {
+    let opcode = read_u8(&mut reader)?;
+
+    match opcode {
+      RDB_OPCODE_EOF => break,
+      RDB_OPCODE_AUX => {
+        let key = read_string(&mut reader)?;
+        let value = read_string(&mut reader)?;
+        visitor.aux_field(&key, &value)?;
+      },
+      RDB_OPCODE_SELECTDB => {
+        db = read_length(&mut reader)?;
+        visitor.select_db(db)?;
+      },
+      RDB_OPCODE_RESIZEDB => {
+        let db_size = read_length(&mut reader)?;
+        let expires_size = read_length(&mut reader)?;
+        visitor.resize_db(db_size, expires_size)?;
+      },
+      RDB_OPCODE_EXPIRETIME_MS => {
+        let mut buf = [0_u8; 8];
+        read_exact(&mut reader, &mut buf)?;
+        expire_at_ms = Some(u64::from_le_bytes(buf));
+      },
+      RDB_OPCODE_EXPIRETIME => {
+        let mut buf = [0_u8; 4];
+        read_exact(&mut reader, &mut buf)?;
+        expire_at_ms = Some(u32::from_le_bytes(buf) as u64 * 1000);
+      },
+      RDB_OPCODE_IDLE => idle = Some(read_length(&mut reader)?),
+      RDB_OPCODE_FREQ => freq = Some(read_u8(&mut reader)?),
+      RDB_OPCODE_FUNCTION2 => visitor.function(&read_string(&mut reader)?)?,
+      RDB_OPCODE_SLOT_INFO => {
+        // the slot ID, the number of keys in the slot, and the number of keys with an expiration in the slot
+        for _ in 0..3 {
+          read_length(&mut reader)?;
+        }
+      },
+      RDB_OPCODE_MODULE_AUX => {
+        return Err(utils::invalid_response(
+          "RDB data",
+          "Module auxiliary data is not supported.",
+        ))
+      },
+      value_type => {
+        let key = read_string(&mut reader)?;
+        let value = decode_value(value_type, &mut reader)?;
+
+        visitor.entry(RdbEntry {
+          db,
+          key,
+          value,
+          expire_at_ms: expire_at_ms.take(),
+          idle: idle.take(),
+          freq: freq.take(),
+        })?;
+      },
+    }
+  }
+
+  let checksum = if version >= RDB_CHECKSUM_VERSION {
+    let expected = reader.crc;
+    let mut buf = [0_u8; 8];
+    read_exact(&mut reader, &mut buf)?;
+    let checksum = u64::from_le_bytes(buf);
+
+    // a checksum of zero means the server was configured with `rdbchecksum no`
+    if checksum != 0 && checksum != expected {
+      return Err(utils::invalid_response("RDB data", "Invalid checksum."));
+    }
+    Some(checksum)
+  } else {
+    None
+  };
+
+  visitor.end(checksum)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::utils::crc64_jones;
+
+  #[derive(Default)]
+  struct Collector {
+    version: u32,
+    aux: Vec<(Vec<u8>, Vec<u8>)>,
+    dbs: Vec<u64>,
+    entries: Vec<RdbEntry>,
+    checksum: Option<u64>,
+  }
+
+  impl RdbVisitor for Collector {
+    fn version(&mut self, version: u32) -> Result<(), RedisProtocolError> {
+      self.version = version;
+      Ok(())
+    }
+
+    fn aux_field(&mut self, key: &[u8], value: &[u8]) -> Result<(), RedisProtocolError> {
+      self.aux.push((key.to_vec(), value.to_vec()));
+      Ok(())
+    }
+
+    fn select_db(&mut self, db: u64) -> Result<(), RedisProtocolError> {
+      self.dbs.push(db);
+      Ok(())
+    }
+
+    fn entry(&mut self, entry: RdbEntry) -> Result<(), RedisProtocolError> {
+      self.entries.push(entry);
+      Ok(())
+    }
+
+    fn end(&mut self, checksum: Option<u64>) -> Result<(), RedisProtocolError> {
+      self.checksum = checksum;
+      Ok(())
+    }
+  }
+
+  fn rdb_body() -> Vec<u8> {
+    let mut out = b"REDIS0011".to_vec();
+    out.extend_from_slice(b"\xfa\x09redis-ver\x057.2.0");
+    out.extend_from_slice(b"\xfe\x00\xfb\x02\x01");
+    out.extend_from_slice(b"\xfc");
+    out.extend_from_slice(&1_700_000_000_000_u64.to_le_bytes());
+    out.extend_from_slice(b"\x00\x03foo\x03bar");
+    out.extend_from_slice(b"\xfe\x02\xf9\x05\x02\x03set\x02\x01a\xc0\x01");
+    out.push(RDB_OPCODE_EOF);
+    out
+  }
+
+  fn with_checksum(mut body: Vec<u8>, checksum: u64) -> Vec<u8> {
+    body.extend_from_slice(&checksum.to_le_bytes());
+    body
+  }
+
+  #[test]
+  fn should_parse_rdb_file() {
+    let body = rdb_body();
+    let checksum = crc64_jones(&body);
+    let mut collector = Collector::default();
+    parse_rdb(&with_checksum(body, checksum)[..], &mut collector).unwrap();
+
+    assert_eq!(collector.version, 11);
+    assert_eq!(collector.aux, vec![(b"redis-ver".to_vec(), b"7.2.0".to_vec())]);
+    assert_eq!(collector.dbs, vec![0, 2]);
+    assert_eq!(collector.checksum, Some(checksum));
+    assert_eq!(collector.entries, vec![
+      RdbEntry {
+        db: 0,
+        key: b"foo".to_vec(),
+        value: RdbValue::String(b"bar".to_vec()),
+        expire_at_ms: Some(1_700_000_000_000),
+        idle: None,
+        freq: None,
+      },
+      RdbEntry {
+        db: 2,
+        key: b"set".to_vec(),
+        value: RdbValue::Set(vec![b"a".to_vec(), b"1".to_vec()]),
+        expire_at_ms: None,
+        idle: None,
+        freq: Some(5),
+      },
+    ]);
+  }
+
+  #[test]
+  fn should_parse_rdb_file_without_checksum() {
+    let mut collector = Collector::default();
+    parse_rdb(&with_checksum(rdb_body(), 0)[..], &mut collector).unwrap();
+    assert_eq!(collector.entries.len(), 2);
+
+    let mut collector = Collector::default();
+    parse_rdb(&b"REDIS0003\x00\x01a\x01b\xff"[..], &mut collector).unwrap();
+    assert_eq!(collector.entries[0].key, b"a");
+    assert_eq!(collector.checksum, None);
+  }
+
+  #[test]
+  fn should_error_on_invalid_rdb_file() {
+    let mut collector = Collector::default();
+
+    assert!(parse_rdb(&with_checksum(rdb_body(), 1)[..], &mut collector).is_err());
+    assert!(parse_rdb(&rdb_body()[..], &mut collector).is_err());
+    assert!(parse_rdb(&b"RDB000011\xff"[..], &mut collector).is_err());
+    assert!(parse_rdb(&b"REDIS0011\x00\x03foo"[..], &mut collector).is_err());
+  }
+
+  #[test]
+  fn should_stop_on_visitor_error() {
+    struct Fail;
+    impl RdbVisitor for Fail {
+      fn entry(&mut self, _: RdbEntry) -> Result<(), RedisProtocolError> {
+        Err(utils::invalid_response("RDB data", "stop"))
+      }
+    }
+
+    let body = rdb_body();
+    let checksum = crc64_jones(&body);
+    assert!(parse_rdb(&with_checksum(body, checksum)[..], &mut Fail).is_err());
+  }
+}
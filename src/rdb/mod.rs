@@ -0,0 +1,234 @@
+use crate::types::RedisProtocolError;
+use crate::utils;
+use std::io::{ErrorKind as IoErrorKind, Read};
+
+/// Types and functions for decoding the values in `DUMP` payloads.
+pub mod value;
+/// A streaming parser for RDB files.
+pub mod file;
+/// Types and functions for building `RESTORE` commands.
+pub mod restore;
+
+pub(crate) const RDB_TYPE_STRING: u8 = 0;
+pub(crate) const RDB_TYPE_LIST: u8 = 1;
+pub(crate) const RDB_TYPE_SET: u8 = 2;
+pub(crate) const RDB_TYPE_ZSET: u8 = 3;
+pub(crate) const RDB_TYPE_HASH: u8 = 4;
+pub(crate) const RDB_TYPE_ZSET_2: u8 = 5;
+pub(crate) const RDB_TYPE_HASH_ZIPMAP: u8 = 9;
+pub(crate) const RDB_TYPE_LIST_ZIPLIST: u8 = 10;
+pub(crate) const RDB_TYPE_SET_INTSET: u8 = 11;
+pub(crate) const RDB_TYPE_ZSET_ZIPLIST: u8 = 12;
+pub(crate) const RDB_TYPE_HASH_ZIPLIST: u8 = 13;
+pub(crate) const RDB_TYPE_LIST_QUICKLIST: u8 = 14;
+pub(crate) const RDB_TYPE_HASH_LISTPACK: u8 = 16;
+pub(crate) const RDB_TYPE_ZSET_LISTPACK: u8 = 17;
+pub(crate) const RDB_TYPE_LIST_QUICKLIST_2: u8 = 18;
+pub(crate) const RDB_TYPE_SET_LISTPACK: u8 = 20;
+
+const RDB_ENC_INT8: u8 = 0;
+const RDB_ENC_INT16: u8 = 1;
+const RDB_ENC_INT32: u8 = 2;
+const RDB_ENC_LZF: u8 = 3;
+
+/// A length prefix, or the special encoding used for a string.
+pub(crate) enum RdbLength {
+  Length(u64),
+  Encoded(u8),
+}
+
+pub(crate) fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), RedisProtocolError> {
+  reader.read_exact(buf).map_err(|e| {
+    if e.kind() == IoErrorKind::UnexpectedEof {
+      utils::invalid_response("RDB data", "Unexpected end of input.")
+    } else {
+      e.into()
+    }
+  })
+}
+
+pub(crate) fn read_u8<R: Read>(reader: &mut R) -> Result<u8, RedisProtocolError> {
+  let mut buf = [0_u8; 1];
+  read_exact(reader, &mut buf)?;
+  Ok(buf[0])
+}
+
+/// Read `len` bytes without trusting `len` for the initial allocation.
+pub(crate) fn read_bytes<R: Read>(reader: &mut R, len: u64) -> Result<Vec<u8>, RedisProtocolError> {
+  let mut out = Vec::new();
+  reader.take(len).read_to_end(&mut out)?;
+
+  if out.len() as u64 != len {
+    Err(utils::invalid_response("RDB data", "Unexpected end of input."))
+  } else {
+    Ok(out)
+  }
+}
+
+pub(crate) fn read_length_or_encoding<R: Read>(reader: &mut R) -> Result<RdbLength, RedisProtocolError> {
+  let first = read_u8(reader)?;
+
+  match first >> 6 {
+    0 => Ok(RdbLength::Length((first & 0x3f) as u64)),
+    1 => Ok(RdbLength::Length((((first & 0x3f) as u64) << 8) | read_u8(reader)? as u64)),
+    2 => match first {
+      0x80 => {
+        let mut buf = [0_u8; 4];
+        read_exact(reader, &mut buf)?;
+        Ok(RdbLength::Length(u32::from_be_bytes(buf) as u64))
+      },
+      0x81 => {
+        let mut buf = [0_u8; 8];
+        read_exact(reader, &mut buf)?;
+        Ok(RdbLength::Length(u64::from_be_bytes(buf)))
+      },
+      _ => Err(utils::invalid_response("RDB data", "Invalid length encoding.")),
+    },
+    _ => Ok(RdbLength::Encoded(first & 0x3f)),
+  }
+}
+
+pub(crate) fn read_length<R: Read>(reader: &mut R) -> Result<u64, RedisProtocolError> {
+  match read_length_or_encoding(reader)? {
+    RdbLength::Length(len) => Ok(len),
+    RdbLength::Encoded(_) => Err(utils::invalid_response("RDB data", "Expected length.")),
+  }
+}
+
+/// Read a string, which may be stored as an integer or compressed with LZF.
+pub(crate) fn read_string<R: Read>(reader: &mut R) -> Result<Vec<u8>, RedisProtocolError> {
+  match read_length_or_encoding(reader)? {
+    RdbLength::Length(len) => read_bytes(reader, len),
+    RdbLength::Encoded(RDB_ENC_INT8) => Ok((read_u8(reader)? as i8).to_string().into_bytes()),
+    RdbLength::Encoded(RDB_ENC_INT16) => {
+      let mut buf = [0_u8; 2];
+      read_exact(reader, &mut buf)?;
+      Ok(i16::from_le_bytes(buf).to_string().into_bytes())
+    },
+    RdbLength::Encoded(RDB_ENC_INT32) => {
+      let mut buf = [0_u8; 4];
+      read_exact(reader, &mut buf)?;
+      Ok(i32::from_le_bytes(buf).to_string().into_bytes())
+    },
+    RdbLength::Encoded(RDB_ENC_LZF) => {
+      let compressed_len = read_length(reader)?;
+      let len = read_length(reader)?;
+      let compressed = read_bytes(reader, compressed_len)?;
+
+      lzf_decompress(&compressed, len as usize)
+    },
+    RdbLength::Encoded(e) => Err(utils::invalid_response("RDB data", format!("Unknown string encoding {}.", e))),
+  }
+}
+
+pub(crate) fn write_length(buf: &mut Vec<u8>, len: u64) {
+  if len < 1 << 6 {
+    buf.push(len as u8);
+  } else if len < 1 << 14 {
+    buf.extend_from_slice(&[0x40 | (len >> 8) as u8, len as u8]);
+  } else if len <= u32::MAX as u64 {
+    buf.push(0x80);
+    buf.extend_from_slice(&(len as u32).to_be_bytes());
+  } else {
+    buf.push(0x81);
+    buf.extend_from_slice(&len.to_be_bytes());
+  }
+}
+
+/// Write a length-prefixed string without integer encoding or compression.
+pub(crate) fn write_string(buf: &mut Vec<u8>, data: &[u8]) {
+  write_length(buf, data.len() as u64);
+  buf.extend_from_slice(data);
+}
+
+/// Decompress LZF data, returning an error if the output is not `len` bytes.
+pub(crate) fn lzf_decompress(input: &[u8], len: usize) -> Result<Vec<u8>, RedisProtocolError> {
+  let mut out = Vec::new();
+  let mut idx = 0;
+
+  while idx < input.len() {
+    let ctrl = input[idx] as usize;
+    idx += 1;
+
+    if ctrl < 32 {
+      let literal = input
+        .get(idx..idx + ctrl + 1)
+        .ok_or_else(|| utils::invalid_response("RDB data", "Invalid LZF literal."))?;
+      out.extend_from_slice(literal);
+      idx += ctrl + 1;
+    } else {
+      let mut next = || {
+        let byte = input.get(idx).ok_or_else(|| utils::invalid_response("RDB data", "Invalid LZF reference."));
+        idx += 1;
+        byte.map(|b| *b as usize)
+      };
+
+      let mut run = ctrl >> 5;
+      if run == 7 {
+        run += next()?;
+      }
+      let offset = ((ctrl & 0x1f) << 8) + next()? + 1;
+
+      if offset > out.len() {
+        return Err(utils::invalid_response("RDB data", "Invalid LZF reference."));
+      }
+      // the reference may overlap the bytes being copied, so copy one byte at a time
+      let start = out.len() - offset;
+      for i in 0..run + 2 {
+        let byte = out[start + i];
+        out.push(byte);
+      }
+    }
+  }
+
+  if out.len() != len {
+    Err(utils::invalid_response("RDB data", "Invalid LZF length."))
+  } else {
+    Ok(out)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_read_lengths() {
+    assert!(matches!(read_length_or_encoding(&mut &b"\x0a"[..]).unwrap(), RdbLength::Length(10)));
+    assert!(matches!(read_length_or_encoding(&mut &b"\x41\x00"[..]).unwrap(), RdbLength::Length(256)));
+    assert!(matches!(read_length_or_encoding(&mut &b"\x80\x00\x01\x00\x00"[..]).unwrap(), RdbLength::Length(65536)));
+    assert!(matches!(read_length_or_encoding(&mut &b"\xc3"[..]).unwrap(), RdbLength::Encoded(3)));
+    assert!(read_length(&mut &b"\x82"[..]).is_err());
+    assert!(read_length(&mut &b"\x41"[..]).is_err());
+  }
+
+  #[test]
+  fn should_write_lengths() {
+    for len in [0, 63, 64, 16383, 16384, u32::MAX as u64, u32::MAX as u64 + 1].iter() {
+      let mut buf = Vec::new();
+      write_length(&mut buf, *len);
+      assert_eq!(read_length(&mut &buf[..]).unwrap(), *len);
+    }
+
+    let mut buf = Vec::new();
+    write_string(&mut buf, b"foo");
+    assert_eq!(buf, b"\x03foo");
+  }
+
+  #[test]
+  fn should_read_encoded_strings() {
+    assert_eq!(read_string(&mut &b"\x03foo"[..]).unwrap(), b"foo");
+    assert_eq!(read_string(&mut &b"\xc0\xfe"[..]).unwrap(), b"-2");
+    assert_eq!(read_string(&mut &b"\xc1\x39\x30"[..]).unwrap(), b"12345");
+    assert_eq!(read_string(&mut &b"\xc2\x87\xd6\x12\x00"[..]).unwrap(), b"1234567");
+    assert!(read_string(&mut &b"\x05foo"[..]).is_err());
+  }
+
+  #[test]
+  fn should_decompress_lzf_strings() {
+    assert_eq!(read_string(&mut &b"\xc3\x05\x0a\x00a\xe0\x00\x00"[..]).unwrap(), b"aaaaaaaaaa");
+    assert_eq!(lzf_decompress(b"\x02abc\x20\x02", 6).unwrap(), b"abcabc");
+    assert!(lzf_decompress(b"\x20\x00", 2).is_err());
+    assert!(lzf_decompress(b"\x02abc", 4).is_err());
+  }
+}
@@ -0,0 +1,166 @@
+//! Types and functions for building `RESTORE` commands, for use in key migration tools.
+//!
+//! ```
+//! # use redis_protocol::rdb::restore::RestoreCommand;
+//! # use redis_protocol::rdb::value::RdbValue;
+//! let command = RestoreCommand::new("foo", &RdbValue::String(b"bar".to_vec()), 5000).replace();
+//! let args = command.to_args();
+//!
+//! assert_eq!(args[0..3], [b"RESTORE".to_vec(), b"foo".to_vec(), b"5000".to_vec()]);
+//! assert_eq!(args[4], b"REPLACE");
+//! ```
+//!
+//! <https://redis.io/commands/restore>
+
+use crate::rdb::file::RdbEntry;
+use crate::rdb::value::{encode_dump_payload, RdbValue};
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::Frame as Resp3Frame;
+
+/// A `RESTORE` command that creates a key from a serialized value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestoreCommand {
+  pub key: Vec<u8>,
+  /// The TTL of the key in milliseconds, or 0 for no expiration.
+  ///
+  /// This is a unix timestamp in milliseconds if `absttl` is `true`.
+  pub ttl: u64,
+  /// The serialized value, in the format returned by `DUMP`.
+  pub payload: Vec<u8>,
+  /// Whether or not to replace an existing key.
+  pub replace: bool,
+  /// Whether or not `ttl` is an absolute unix timestamp in milliseconds.
+  pub absttl: bool,
+  /// The LRU idle time of the key, in seconds.
+  pub idle: Option<u64>,
+  /// The LFU access frequency of the key.
+  pub freq: Option<u8>,
+}
+
+impl RestoreCommand {
+  /// Create a command that restores `value` with a relative TTL in milliseconds, or 0 for no expiration.
+  pub fn new<K: Into<Vec<u8>>>(key: K, value: &RdbValue, ttl: u64) -> Self {
+    Self::from_payload(key, encode_dump_payload(value), ttl)
+  }
+
+  /// Create a command from a payload returned by `DUMP`.
+  pub fn from_payload<K: Into<Vec<u8>>>(key: K, payload: Vec<u8>, ttl: u64) -> Self {
+    RestoreCommand {
+      key: key.into(),
+      ttl,
+      payload,
+      replace: false,
+      absttl: false,
+      idle: None,
+      freq: None,
+    }
+  }
+
+  /// Create a command from an entry in an RDB file, keeping its expiration time, idle time, and access frequency.
+  pub fn from_entry(entry: &RdbEntry) -> Self {
+    let mut command = Self::new(entry.key.clone(), &entry.value, entry.expire_at_ms.unwrap_or(0));
+    command.absttl = entry.expire_at_ms.is_some();
+    command.idle = entry.idle;
+    command.freq = entry.freq;
+    command
+  }
+
+  /// Replace the key if it already exists.
+  pub fn replace(mut self) -> Self {
+    self.replace = true;
+    self
+  }
+
+  /// Read the command arguments, including the `RESTORE` command name.
+  pub fn to_args(&self) -> Vec<Vec<u8>> {
+    let mut args = vec![
+      b"RESTORE".to_vec(),
+      self.key.clone(),
+      self.ttl.to_string().into_bytes(),
+      self.payload.clone(),
+    ];
+
+    if self.replace {
+      args.push(b"REPLACE".to_vec());
+    }
+    if self.absttl {
+      args.push(b"ABSTTL".to_vec());
+    }
+    if let Some(idle) = self.idle {
+      args.push(b"IDLETIME".to_vec());
+      args.push(idle.to_string().into_bytes());
+    }
+    if let Some(freq) = self.freq {
+      args.push(b"FREQ".to_vec());
+      args.push(freq.to_string().into_bytes());
+    }
+
+    args
+  }
+
+  /// Create a RESP2 command frame.
+  pub fn to_resp2_frame(&self) -> Resp2Frame {
+    Resp2Frame::Array(self.to_args().into_iter().map(Resp2Frame::BulkString).collect())
+  }
+
+  /// Create a RESP3 command frame.
+  pub fn to_resp3_frame(&self) -> Resp3Frame {
+    let data = self
+      .to_args()
+      .into_iter()
+      .map(|arg| Resp3Frame::BlobString {
+        data: arg,
+        attributes: None,
+      })
+      .collect();
+
+    Resp3Frame::Array { data, attributes: None }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::rdb::value::decode_dump_payload;
+
+  #[test]
+  fn should_create_restore_command_from_entry() {
+    let entry = RdbEntry {
+      db: 0,
+      key: b"foo".to_vec(),
+      value: RdbValue::List(vec![b"a".to_vec()]),
+      expire_at_ms: Some(1700000000000),
+      idle: Some(10),
+      freq: None,
+    };
+    let frame = RestoreCommand::from_entry(&entry).replace().to_resp2_frame();
+
+    let args = match frame {
+      Resp2Frame::Array(args) => args,
+      _ => panic!("Expected array."),
+    };
+    let payload = match args[3] {
+      Resp2Frame::BulkString(ref payload) => payload,
+      _ => panic!("Expected bulk string."),
+    };
+    assert_eq!(decode_dump_payload(payload).unwrap(), entry.value);
+
+    let expected: Vec<&[u8]> = vec![
+      b"RESTORE",
+      b"foo",
+      b"1700000000000",
+      payload,
+      b"REPLACE",
+      b"ABSTTL",
+      b"IDLETIME",
+      b"10",
+    ];
+    assert_eq!(
+      args,
+      expected
+        .into_iter()
+        .map(|arg| Resp2Frame::BulkString(arg.to_vec()))
+        .collect::<Vec<_>>()
+    );
+  }
+}
@@ -0,0 +1,570 @@
+//! Types and functions for decoding the serialized values in `DUMP` payloads.
+//!
+//! <https://rdb.fnordig.de/file_format.html>
+
+use crate::rdb::*;
+use crate::types::RedisProtocolError;
+use crate::utils::{self, crc64_jones, verify_dump_payload};
+use std::io::Read;
+
+/// The RDB version written to the footer of encoded `DUMP` payloads.
+///
+/// This is the oldest version that supports every encoding used by [encode_value], so the payloads can be restored by
+/// Redis 4.0 and later.
+pub const DUMP_RDB_VERSION: u16 = 8;
+
+/// The fields and values in a hash.
+pub type HashPairs = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// A decoded value from a `DUMP` payload or RDB file.
+///
+/// Integer-encoded elements are returned as their decimal string representation, matching how Redis returns them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RdbValue {
+  String(Vec<u8>),
+  List(Vec<Vec<u8>>),
+  Set(Vec<Vec<u8>>),
+  /// Members and scores, in the order they were stored.
+  SortedSet(Vec<(Vec<u8>, f64)>),
+  /// Fields and values, in the order they were stored.
+  Hash(HashPairs),
+}
+
+impl RdbValue {
+  /// Read the name of the value type, as returned by the `TYPE` command.
+  pub fn type_name(&self) -> &'static str {
+    match *self {
+      RdbValue::String(_) => "string",
+      RdbValue::List(_) => "list",
+      RdbValue::Set(_) => "set",
+      RdbValue::SortedSet(_) => "zset",
+      RdbValue::Hash(_) => "hash",
+    }
+  }
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], RedisProtocolError> {
+  if buf.len() < len {
+    return Err(utils::invalid_response("RDB data", "Unexpected end of encoded value."));
+  }
+
+  let (head, tail) = buf.split_at(len);
+  *buf = tail;
+  Ok(head)
+}
+
+fn take_u8(buf: &mut &[u8]) -> Result<u8, RedisProtocolError> {
+  take(buf, 1).map(|b| b[0])
+}
+
+fn take_int(buf: &mut &[u8], len: usize) -> Result<i64, RedisProtocolError> {
+  let bytes = take(buf, len)?;
+  let mut padded = [0_u8; 8];
+  padded[8 - len..].copy_from_slice(bytes);
+
+  // shift the little-endian bytes into the high end of an i64 to sign extend them
+  Ok(i64::from_le_bytes(padded) >> (64 - len * 8))
+}
+
+fn int_entry(i: i64) -> Vec<u8> {
+  i.to_string().into_bytes()
+}
+
+/// Decode the entries in a ziplist.
+fn decode_ziplist(data: &[u8]) -> Result<Vec<Vec<u8>>, RedisProtocolError> {
+  let mut buf = data;
+  // zlbytes, zltail, and zllen
+  take(&mut buf, 10)?;
+  let mut out = Vec::new();
+
+  loop {
+    let prev_len = take_u8(&mut buf)?;
+    if prev_len == 0xff {
+      break;
+    }
+    if prev_len == 0xfe {
+      take(&mut buf, 4)?;
+    }
+
+    let encoding = take_u8(&mut buf)?;
+    let entry = match encoding >> 6 {
+      0 => take(&mut buf, (encoding & 0x3f) as usize)?.to_vec(),
+      1 => {
+        let len = (((encoding & 0x3f) as usize) << 8) | take_u8(&mut buf)? as usize;
+        take(&mut buf, len)?.to_vec()
+      },
+      2 => {
+        let len = take(&mut buf, 4)?;
+        let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+        take(&mut buf, len)?.to_vec()
+      },
+      _ => match encoding {
+        0xc0 => int_entry(take_int(&mut buf, 2)?),
+        0xd0 => int_entry(take_int(&mut buf, 4)?),
+        0xe0 => int_entry(take_int(&mut buf, 8)?),
+        0xf0 => int_entry(take_int(&mut buf, 3)?),
+        0xfe => int_entry(take_int(&mut buf, 1)?),
+        0xf1..=0xfd => int_entry((encoding & 0x0f) as i64 - 1),
+        _ => return Err(utils::invalid_response("RDB data", "Invalid ziplist encoding.")),
+      },
+    };
+    out.push(entry);
+  }
+
+  Ok(out)
+}
+
+/// The size of the back-length that follows a listpack entry with the provided encoded size.
+fn listpack_backlen_size(len: usize) -> usize {
+  match len {
+    0..=127 => 1,
+    128..=16382 => 2,
+    16383..=2097150 => 3,
+    2097151..=268435454 => 4,
+    _ => 5,
+  }
+}
+
+/// Decode the entries in a listpack.
+fn decode_listpack(data: &[u8]) -> Result<Vec<Vec<u8>>, RedisProtocolError> {
+  let mut buf = data;
+  // total bytes and number of elements
+  take(&mut buf, 6)?;
+  let mut out = Vec::new();
+
+  loop {
+    let encoding = take_u8(&mut buf)?;
+    if encoding == 0xff {
+      break;
+    }
+
+    let (entry, len) = if encoding & 0x80 == 0 {
+      (int_entry((encoding & 0x7f) as i64), 1)
+    } else if encoding & 0xc0 == 0x80 {
+      let len = (encoding & 0x3f) as usize;
+      (take(&mut buf, len)?.to_vec(), 1 + len)
+    } else if encoding & 0xe0 == 0xc0 {
+      let value = (((encoding & 0x1f) as i64) << 8) | take_u8(&mut buf)? as i64;
+      let value = if value >= 1 << 12 { value - (1 << 13) } else { value };
+      (int_entry(value), 2)
+    } else if encoding & 0xf0 == 0xe0 {
+      let len = (((encoding & 0x0f) as usize) << 8) | take_u8(&mut buf)? as usize;
+      (take(&mut buf, len)?.to_vec(), 2 + len)
+    } else {
+      match encoding {
+        0xf0 => {
+          let len = take(&mut buf, 4)?;
+          let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+          (take(&mut buf, len)?.to_vec(), 5 + len)
+        },
+        0xf1 => (int_entry(take_int(&mut buf, 2)?), 3),
+        0xf2 => (int_entry(take_int(&mut buf, 3)?), 4),
+        0xf3 => (int_entry(take_int(&mut buf, 4)?), 5),
+        0xf4 => (int_entry(take_int(&mut buf, 8)?), 9),
+        _ => return Err(utils::invalid_response("RDB data", "Invalid listpack encoding.")),
+      }
+    };
+
+    take(&mut buf, listpack_backlen_size(len))?;
+    out.push(entry);
+  }
+
+  Ok(out)
+}
+
+/// Decode the members of an intset.
+fn decode_intset(data: &[u8]) -> Result<Vec<Vec<u8>>, RedisProtocolError> {
+  let mut buf = data;
+  let header = take(&mut buf, 8)?;
+  let size = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+  let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+  if size != 2 && size != 4 && size != 8 {
+    return Err(utils::invalid_response("RDB data", "Invalid intset encoding."));
+  }
+
+  (0..len).map(|_| take_int(&mut buf, size).map(int_entry)).collect()
+}
+
+fn take_zipmap_len(buf: &mut &[u8], first: u8) -> Result<usize, RedisProtocolError> {
+  match first {
+    0..=253 => Ok(first as usize),
+    254 => {
+      let len = take(buf, 4)?;
+      Ok(u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize)
+    },
+    _ => Err(utils::invalid_response("RDB data", "Invalid zipmap length.")),
+  }
+}
+
+/// Decode the fields and values in a zipmap, used by hashes before Redis 2.6.
+fn decode_zipmap(data: &[u8]) -> Result<HashPairs, RedisProtocolError> {
+  let mut buf = data;
+  // zmlen
+  take(&mut buf, 1)?;
+  let mut out = Vec::new();
+
+  loop {
+    let first = take_u8(&mut buf)?;
+    if first == 0xff {
+      break;
+    }
+
+    let key_len = take_zipmap_len(&mut buf, first)?;
+    let key = take(&mut buf, key_len)?.to_vec();
+    let first = take_u8(&mut buf)?;
+    let value_len = take_zipmap_len(&mut buf, first)?;
+    let free = take_u8(&mut buf)? as usize;
+    let value = take(&mut buf, value_len)?.to_vec();
+    take(&mut buf, free)?;
+
+    out.push((key, value));
+  }
+
+  Ok(out)
+}
+
+fn into_pairs(entries: Vec<Vec<u8>>) -> Result<HashPairs, RedisProtocolError> {
+  let mut out = Vec::with_capacity(entries.len() / 2);
+  let mut entries = entries.into_iter();
+
+  while let Some(key) = entries.next() {
+    let value = entries
+      .next()
+      .ok_or_else(|| utils::invalid_response("RDB data", "Expected an even number of entries."))?;
+    out.push((key, value));
+  }
+
+  Ok(out)
+}
+
+fn parse_score(score: &[u8]) -> Result<f64, RedisProtocolError> {
+  std::str::from_utf8(score)
+    .ok()
+    .and_then(|s| s.parse::<f64>().ok())
+    .ok_or_else(|| utils::invalid_response("RDB data", "Invalid score."))
+}
+
+fn into_scores(entries: Vec<Vec<u8>>) -> Result<Vec<(Vec<u8>, f64)>, RedisProtocolError> {
+  into_pairs(entries)?
+    .into_iter()
+    .map(|(member, score)| Ok((member, parse_score(&score)?)))
+    .collect()
+}
+
+/// Read a score stored as a length-prefixed string, used by the original sorted set encoding.
+fn read_string_score<R: Read>(reader: &mut R) -> Result<f64, RedisProtocolError> {
+  match read_u8(reader)? {
+    253 => Ok(f64::NAN),
+    254 => Ok(f64::INFINITY),
+    255 => Ok(f64::NEG_INFINITY),
+    len => parse_score(&read_bytes(reader, len as u64)?),
+  }
+}
+
+fn read_binary_score<R: Read>(reader: &mut R) -> Result<f64, RedisProtocolError> {
+  let mut buf = [0_u8; 8];
+  read_exact(reader, &mut buf)?;
+  Ok(f64::from_le_bytes(buf))
+}
+
+fn read_strings<R: Read>(reader: &mut R) -> Result<Vec<Vec<u8>>, RedisProtocolError> {
+  let len = read_length(reader)?;
+  (0..len).map(|_| read_string(reader)).collect()
+}
+
+/// Decode a value with the provided RDB type from `reader`.
+///
+/// Module values and streams are not supported.
+pub fn decode_value<R: Read>(value_type: u8, reader: &mut R) -> Result<RdbValue, RedisProtocolError> {
+  let value = match value_type {
+    RDB_TYPE_STRING => RdbValue::String(read_string(reader)?),
+    RDB_TYPE_LIST => RdbValue::List(read_strings(reader)?),
+    RDB_TYPE_SET => RdbValue::Set(read_strings(reader)?),
+    RDB_TYPE_ZSET | RDB_TYPE_ZSET_2 => {
+      let len = read_length(reader)?;
+      let mut out = Vec::new();
+      for _ in 0..len {
+        let member = read_string(reader)?;
+        let score = if value_type == RDB_TYPE_ZSET_2 {
+          read_binary_score(reader)?
+        } else {
+          read_string_score(reader)?
+        };
+        out.push((member, score));
+      }
+
+      RdbValue::SortedSet(out)
+    },
+    RDB_TYPE_HASH => {
+      let len = read_length(reader)?;
+      let mut out = Vec::new();
+      for _ in 0..len {
+        out.push((read_string(reader)?, read_string(reader)?));
+      }
+
+      RdbValue::Hash(out)
+    },
+    RDB_TYPE_HASH_ZIPMAP => RdbValue::Hash(decode_zipmap(&read_string(reader)?)?),
+    RDB_TYPE_LIST_ZIPLIST => RdbValue::List(decode_ziplist(&read_string(reader)?)?),
+    RDB_TYPE_SET_INTSET => RdbValue::Set(decode_intset(&read_string(reader)?)?),
+    RDB_TYPE_ZSET_ZIPLIST => RdbValue::SortedSet(into_scores(decode_ziplist(&read_string(reader)?)?)?),
+    RDB_TYPE_HASH_ZIPLIST => RdbValue::Hash(into_pairs(decode_ziplist(&read_string(reader)?)?)?),
+    RDB_TYPE_LIST_QUICKLIST => {
+      let nodes = read_length(reader)?;
+      let mut out = Vec::new();
+      for _ in 0..nodes {
+        out.extend(decode_ziplist(&read_string(reader)?)?);
+      }
+
+      RdbValue::List(out)
+    },
+    RDB_TYPE_HASH_LISTPACK => RdbValue::Hash(into_pairs(decode_listpack(&read_string(reader)?)?)?),
+    RDB_TYPE_ZSET_LISTPACK => RdbValue::SortedSet(into_scores(decode_listpack(&read_string(reader)?)?)?),
+    RDB_TYPE_LIST_QUICKLIST_2 => {
+      let nodes = read_length(reader)?;
+      let mut out = Vec::new();
+      for _ in 0..nodes {
+        // 1 is a plain node with a single element, 2 is a listpack
+        match read_length(reader)? {
+          1 => out.push(read_string(reader)?),
+          2 => out.extend(decode_listpack(&read_string(reader)?)?),
+          _ => return Err(utils::invalid_response("RDB data", "Invalid quicklist container.")),
+        }
+      }
+
+      RdbValue::List(out)
+    },
+    RDB_TYPE_SET_LISTPACK => RdbValue::Set(decode_listpack(&read_string(reader)?)?),
+    _ => {
+      return Err(utils::invalid_response(
+        "RDB data",
+        format!("Unsupported value type {}.", value_type),
+      ))
+    },
+  };
+
+  Ok(value)
+}
+
+/// Verify and decode the value in a payload returned by `DUMP`.
+pub fn decode_dump_payload(payload: &[u8]) -> Result<RdbValue, RedisProtocolError> {
+  verify_dump_payload(payload)?;
+
+  let mut body = &payload[0..payload.len() - 10];
+  let value_type = read_u8(&mut body)?;
+  let value = decode_value(value_type, &mut body)?;
+  if !body.is_empty() {
+    return Err(utils::invalid_response("RDB data", "Unexpected trailing bytes."));
+  }
+
+  Ok(value)
+}
+
+fn write_strings(buf: &mut Vec<u8>, elements: &[Vec<u8>]) {
+  write_length(buf, elements.len() as u64);
+  for element in elements.iter() {
+    write_string(buf, element);
+  }
+}
+
+/// Encode a value into `buf`, starting with its RDB type.
+///
+/// Every value is written with its original uncompressed encoding, and sorted set scores are written as binary
+/// doubles. Redis converts the value to its preferred encoding when it is loaded.
+pub fn encode_value(buf: &mut Vec<u8>, value: &RdbValue) {
+  match *value {
+    RdbValue::String(ref data) => {
+      buf.push(RDB_TYPE_STRING);
+      write_string(buf, data);
+    },
+    RdbValue::List(ref elements) => {
+      buf.push(RDB_TYPE_LIST);
+      write_strings(buf, elements);
+    },
+    RdbValue::Set(ref elements) => {
+      buf.push(RDB_TYPE_SET);
+      write_strings(buf, elements);
+    },
+    RdbValue::SortedSet(ref members) => {
+      buf.push(RDB_TYPE_ZSET_2);
+      write_length(buf, members.len() as u64);
+      for (member, score) in members.iter() {
+        write_string(buf, member);
+        buf.extend_from_slice(&score.to_le_bytes());
+      }
+    },
+    RdbValue::Hash(ref pairs) => {
+      buf.push(RDB_TYPE_HASH);
+      write_length(buf, pairs.len() as u64);
+      for (field, value) in pairs.iter() {
+        write_string(buf, field);
+        write_string(buf, value);
+      }
+    },
+  }
+}
+
+/// Encode a value as a `DUMP` payload, including the RDB version and checksum footer.
+///
+/// See [DUMP_RDB_VERSION] for the RDB version written to the payload.
+pub fn encode_dump_payload(value: &RdbValue) -> Vec<u8> {
+  let mut payload = Vec::new();
+  encode_value(&mut payload, value);
+  payload.extend_from_slice(&DUMP_RDB_VERSION.to_le_bytes());
+  let crc = crc64_jones(&payload);
+  payload.extend_from_slice(&crc.to_le_bytes());
+  payload
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn dump(body: &[u8]) -> Vec<u8> {
+    dump_with_version(body, 11)
+  }
+
+  fn dump_with_version(body: &[u8], version: u8) -> Vec<u8> {
+    let mut out = body.to_vec();
+    out.extend_from_slice(&[version, 0]);
+    let crc = crc64_jones(&out);
+    out.extend_from_slice(&crc.to_le_bytes());
+    out
+  }
+
+  fn bytes(values: &[&str]) -> Vec<Vec<u8>> {
+    values.iter().map(|v| v.as_bytes().to_vec()).collect()
+  }
+
+  fn listpack(entries: &[u8]) -> Vec<u8> {
+    let mut out = vec![(entries.len() + 7) as u8];
+    out.extend_from_slice(&[0; 6]);
+    out.extend_from_slice(entries);
+    out.push(0xff);
+    out
+  }
+
+  #[test]
+  fn should_decode_dump_strings() {
+    // DUMP of the integer 10, from https://redis.io/commands/dump
+    let payload = b"\x00\xc0\n\t\x00\xbem\x06\x89Z(\x00\n";
+    assert_eq!(decode_dump_payload(payload).unwrap(), RdbValue::String(b"10".to_vec()));
+
+    assert_eq!(
+      decode_dump_payload(&dump(b"\x00\x03foo")).unwrap(),
+      RdbValue::String(b"foo".to_vec())
+    );
+    assert_eq!(
+      decode_dump_payload(&dump(b"\x00\xc3\x05\x0a\x00a\xe0\x00\x00")).unwrap(),
+      RdbValue::String(b"aaaaaaaaaa".to_vec())
+    );
+  }
+
+  #[test]
+  fn should_decode_dump_lists() {
+    let ziplist = b"\x00\x0f\x00\x00\x00\x00\x00\x00\x00\x00\x00\x02hi\x04\xf3\x02\xfe\x80\xff";
+    let mut body = vec![RDB_TYPE_LIST_ZIPLIST, ziplist.len() as u8];
+    body.extend_from_slice(ziplist);
+    assert_eq!(
+      decode_dump_payload(&dump(&body)).unwrap(),
+      RdbValue::List(bytes(&["hi", "2", "-128"]))
+    );
+
+    let mut body = vec![RDB_TYPE_LIST_QUICKLIST_2, 2, 2];
+    body.extend(listpack(b"\x05\x01\x82ab\x03\xdf\xff\x02"));
+    body.extend_from_slice(b"\x01\x03foo");
+    assert_eq!(
+      decode_dump_payload(&dump(&body)).unwrap(),
+      RdbValue::List(bytes(&["5", "ab", "-1", "foo"]))
+    );
+  }
+
+  #[test]
+  fn should_decode_dump_sets() {
+    let intset = b"\x02\x00\x00\x00\x02\x00\x00\x00\x01\x00\xfe\xff";
+    let mut body = vec![RDB_TYPE_SET_INTSET, intset.len() as u8];
+    body.extend_from_slice(intset);
+    let value = decode_dump_payload(&dump(&body)).unwrap();
+    assert_eq!(value, RdbValue::Set(bytes(&["1", "-2"])));
+    assert_eq!(value.type_name(), "set");
+
+    assert_eq!(
+      decode_dump_payload(&dump(b"\x02\x02\x01a\x01b")).unwrap(),
+      RdbValue::Set(bytes(&["a", "b"]))
+    );
+  }
+
+  #[test]
+  fn should_decode_dump_sorted_sets() {
+    assert_eq!(
+      decode_dump_payload(&dump(b"\x03\x02\x01a\x031.5\x01b\xfe")).unwrap(),
+      RdbValue::SortedSet(vec![(b"a".to_vec(), 1.5), (b"b".to_vec(), f64::INFINITY)])
+    );
+
+    let mut body = vec![RDB_TYPE_ZSET_2, 1, 1, b'a'];
+    body.extend_from_slice(&(-2.5_f64).to_le_bytes());
+    assert_eq!(
+      decode_dump_payload(&dump(&body)).unwrap(),
+      RdbValue::SortedSet(vec![(b"a".to_vec(), -2.5)])
+    );
+
+    let mut body = vec![RDB_TYPE_ZSET_LISTPACK];
+    body.extend(listpack(b"\x81a\x02\x831.5\x04"));
+    assert_eq!(
+      decode_dump_payload(&dump(&body)).unwrap(),
+      RdbValue::SortedSet(vec![(b"a".to_vec(), 1.5)])
+    );
+  }
+
+  #[test]
+  fn should_decode_dump_hashes() {
+    let mut body = vec![RDB_TYPE_HASH_LISTPACK];
+    body.extend(listpack(b"\x81a\x02\x01\x01"));
+    assert_eq!(
+      decode_dump_payload(&dump(&body)).unwrap(),
+      RdbValue::Hash(vec![(b"a".to_vec(), b"1".to_vec())])
+    );
+
+    let zipmap = b"\x01\x01a\x02\x01xy\x00\xff";
+    let mut body = vec![RDB_TYPE_HASH_ZIPMAP, zipmap.len() as u8];
+    body.extend_from_slice(zipmap);
+    assert_eq!(
+      decode_dump_payload(&dump(&body)).unwrap(),
+      RdbValue::Hash(vec![(b"a".to_vec(), b"xy".to_vec())])
+    );
+
+    assert_eq!(
+      decode_dump_payload(&dump(b"\x04\x01\x01f\x01v")).unwrap(),
+      RdbValue::Hash(vec![(b"f".to_vec(), b"v".to_vec())])
+    );
+  }
+
+  #[test]
+  fn should_encode_dump_payloads() {
+    let values = [
+      RdbValue::String(b"foo".to_vec()),
+      RdbValue::List(bytes(&["a", "b", ""])),
+      RdbValue::Set(bytes(&["1", "-2"])),
+      RdbValue::SortedSet(vec![(b"a".to_vec(), 1.5), (b"b".to_vec(), f64::NEG_INFINITY)]),
+      RdbValue::Hash(vec![(b"f".to_vec(), vec![b'v'; 100])]),
+    ];
+
+    for value in values.iter() {
+      let payload = encode_dump_payload(value);
+      assert_eq!(verify_dump_payload(&payload).unwrap(), DUMP_RDB_VERSION);
+      assert_eq!(&decode_dump_payload(&payload).unwrap(), value);
+    }
+    assert_eq!(encode_dump_payload(&values[0]), dump_with_version(b"\x00\x03foo", 8));
+  }
+
+  #[test]
+  fn should_error_on_invalid_dump_payload() {
+    assert!(decode_dump_payload(&dump(b"\x00\x03foo\x00")).is_err());
+    assert!(decode_dump_payload(&dump(b"\x00\x04foo")).is_err());
+    assert!(decode_dump_payload(&dump(b"\x15\x00")).is_err());
+
+    let mut body = vec![RDB_TYPE_HASH_LISTPACK];
+    body.extend(listpack(b"\x81a\x02"));
+    assert!(decode_dump_payload(&dump(&body)).is_err());
+  }
+}
@@ -0,0 +1,381 @@
+//! Record and replay decoding sessions, for capturing intermittent decoding bugs in production and reproducing them
+//! deterministically in tests.
+//!
+//! A [DecodeRecorder](crate::replay::DecodeRecorder) wraps the decoder and writes every input chunk, decoded frame,
+//! and decoding error to a writer. The recording can be read back with [Session::read](crate::replay::Session::read)
+//! and checked against the current decoder with [Session::replay](crate::replay::Session::replay).
+//!
+//! ```
+//! # use redis_protocol::replay::{DecodeRecorder, Session};
+//! # use redis_protocol::resp3::types::RespVersion;
+//! let mut recorder = DecodeRecorder::new(Vec::new(), RespVersion::RESP3).unwrap();
+//! assert!(recorder.decode(b"*2\r\n:1").unwrap().is_empty());
+//! assert_eq!(recorder.decode(b"\r\n:2\r\n").unwrap().len(), 1);
+//!
+//! let session = Session::read(&recorder.into_inner()[..]).unwrap();
+//! session.replay().unwrap();
+//! ```
+//!
+//! ## File Format
+//!
+//! A recording starts with the bytes `RESPREC` followed by the RESP version as an ASCII digit. This is followed by a
+//! sequence of events, each of which is a one byte tag, a 4 byte big-endian length, and the payload:
+//!
+//! * `I` - An input chunk passed to the decoder.
+//! * `F` - A decoded frame, encoded with the same RESP version as the session. Streamed frames are recorded once
+//!   all of their chunks are decoded.
+//! * `E` - The description of a decoding error, as UTF-8.
+
+use crate::resp2::decode::decode as resp2_decode;
+use crate::resp2::encode::encode_bytes as resp2_encode_bytes;
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::decode::complete::decode as resp3_decode;
+use crate::resp3::decode::streaming::decode as resp3_stream_decode;
+use crate::resp3::encode::complete::encode_bytes as resp3_encode_bytes;
+use crate::resp3::types::{DecodedFrame, Frame as Resp3Frame, RespVersion, StreamedFrame};
+use crate::types::{RedisProtocolError, RedisProtocolErrorKind};
+use crate::utils;
+use bytes::BytesMut;
+use std::io::{Read, Write};
+
+const MAGIC: &[u8] = b"RESPREC";
+const INPUT_TAG: u8 = b'I';
+const FRAME_TAG: u8 = b'F';
+const ERROR_TAG: u8 = b'E';
+
+/// A frame decoded while recording or replaying a session.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SessionFrame {
+  Resp2(Resp2Frame),
+  Resp3(Resp3Frame),
+}
+
+impl SessionFrame {
+  fn encode(&self) -> Result<BytesMut, RedisProtocolError> {
+    let mut buf = BytesMut::new();
+    match *self {
+      SessionFrame::Resp2(ref frame) => resp2_encode_bytes(&mut buf, frame)?,
+      SessionFrame::Resp3(ref frame) => resp3_encode_bytes(&mut buf, frame)?,
+    };
+
+    Ok(buf)
+  }
+
+  fn decode(version: &RespVersion, buf: &[u8]) -> Result<Self, RedisProtocolError> {
+    let result = match *version {
+      RespVersion::RESP2 => resp2_decode(buf)?.map(|(frame, len)| (SessionFrame::Resp2(frame), len)),
+      RespVersion::RESP3 => resp3_decode(buf)?.map(|(frame, len)| (SessionFrame::Resp3(frame), len)),
+    };
+
+    match result {
+      Some((frame, len)) if len == buf.len() => Ok(frame),
+      _ => Err(utils::invalid_response("session", "Invalid frame.")),
+    }
+  }
+}
+
+/// An event in a recorded session.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SessionEvent {
+  /// A chunk of bytes passed to the decoder.
+  Input(Vec<u8>),
+  /// A decoded frame.
+  Frame(SessionFrame),
+  /// The description of a decoding error.
+  Error(String),
+}
+
+/// The decoder state shared by the recorder and the replayer.
+struct SessionDecoder {
+  version: RespVersion,
+  buf: BytesMut,
+  streamed: Option<StreamedFrame>,
+}
+
+impl SessionDecoder {
+  fn new(version: RespVersion) -> Self {
+    SessionDecoder {
+      version,
+      buf: BytesMut::new(),
+      streamed: None,
+    }
+  }
+
+  fn decode_resp3(&mut self) -> Result<Option<(Option<SessionFrame>, usize)>, RedisProtocolError> {
+    let (frame, len) = match resp3_stream_decode(&self.buf)? {
+      Some(result) => result,
+      None => return Ok(None),
+    };
+
+    let frame = match (self.streamed.take(), frame) {
+      (Some(_), DecodedFrame::Streaming(_)) => {
+        return Err(RedisProtocolError::new(
+          RedisProtocolErrorKind::DecodeError,
+          "Cannot start a stream while already inside a stream.",
+        ))
+      }
+      (Some(mut streamed), DecodedFrame::Complete(frame)) => {
+        streamed.add_frame(frame)?;
+
+        if streamed.is_finished() {
+          Some(SessionFrame::Resp3(streamed.into_frame()?))
+        } else {
+          self.streamed = Some(streamed);
+          None
+        }
+      }
+      (None, DecodedFrame::Streaming(streamed)) => {
+        self.streamed = Some(streamed);
+        None
+      }
+      (None, DecodedFrame::Complete(frame)) => Some(SessionFrame::Resp3(frame)),
+    };
+
+    Ok(Some((frame, len)))
+  }
+
+  /// Decode every frame that can be read after appending `chunk` to the buffer, stopping at the first error.
+  fn decode(&mut self, chunk: &[u8], events: &mut Vec<SessionEvent>) -> Result<(), RedisProtocolError> {
+    self.buf.extend_from_slice(chunk);
+
+    loop {
+      let result = match self.version {
+        RespVersion::RESP2 => resp2_decode(&self.buf).map(|r| r.map(|(f, l)| (Some(SessionFrame::Resp2(f)), l))),
+        RespVersion::RESP3 => self.decode_resp3(),
+      };
+
+      match result {
+        Ok(Some((frame, len))) => {
+          let _ = self.buf.split_to(len);
+          if let Some(frame) = frame {
+            events.push(SessionEvent::Frame(frame));
+          }
+        }
+        Ok(None) => return Ok(()),
+        Err(e) => {
+          events.push(SessionEvent::Error(e.to_string()));
+          return Err(e);
+        }
+      }
+    }
+  }
+}
+
+/// A wrapper around the decoder that records every input chunk, decoded frame, and decoding error to `W`.
+///
+/// RESP3 streamed frames are combined before they are returned, so every returned frame is complete.
+pub struct DecodeRecorder<W: Write> {
+  writer: W,
+  decoder: SessionDecoder,
+}
+
+impl<W: Write> DecodeRecorder<W> {
+  /// Create a new recorder for the provided RESP version, writing the session header to `writer`.
+  pub fn new(mut writer: W, version: RespVersion) -> Result<Self, RedisProtocolError> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[version.to_byte()])?;
+
+    Ok(DecodeRecorder {
+      writer,
+      decoder: SessionDecoder::new(version),
+    })
+  }
+
+  fn write_event(&mut self, event: &SessionEvent) -> Result<(), RedisProtocolError> {
+    let (tag, payload) = match *event {
+      SessionEvent::Input(ref data) => (INPUT_TAG, data[..].into()),
+      SessionEvent::Frame(ref frame) => (FRAME_TAG, frame.encode()?.to_vec()),
+      SessionEvent::Error(ref description) => (ERROR_TAG, description.as_bytes().to_vec()),
+    };
+
+    self.writer.write_all(&[tag])?;
+    self.writer.write_all(&(payload.len() as u32).to_be_bytes())?;
+    self.writer.write_all(&payload)?;
+    Ok(())
+  }
+
+  /// Append `chunk` to the internal buffer and return every frame that can be decoded, recording each step.
+  ///
+  /// Decoding errors are recorded before they are returned. The connection should be closed after an error since the
+  /// remaining bytes cannot be decoded reliably.
+  pub fn decode(&mut self, chunk: &[u8]) -> Result<Vec<SessionFrame>, RedisProtocolError> {
+    let mut events = vec![SessionEvent::Input(chunk.to_vec())];
+    let result = self.decoder.decode(chunk, &mut events);
+
+    for event in events.iter() {
+      self.write_event(event)?;
+    }
+    result?;
+
+    Ok(
+      events
+        .into_iter()
+        .filter_map(|event| match event {
+          SessionEvent::Frame(frame) => Some(frame),
+          _ => None,
+        })
+        .collect(),
+    )
+  }
+
+  /// Read the writer.
+  pub fn get_ref(&self) -> &W {
+    &self.writer
+  }
+
+  /// Flush and return the writer.
+  pub fn into_inner(mut self) -> W {
+    let _ = self.writer.flush();
+    self.writer
+  }
+}
+
+/// A recorded decoding session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Session {
+  /// The RESP version used by the session.
+  pub version: RespVersion,
+  /// The events in the order they were recorded.
+  pub events: Vec<SessionEvent>,
+}
+
+impl Session {
+  /// Read a session written by a [DecodeRecorder].
+  pub fn read<R: Read>(mut reader: R) -> Result<Self, RedisProtocolError> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    if data.len() < MAGIC.len() + 1 || &data[..MAGIC.len()] != MAGIC {
+      return Err(utils::invalid_response("session", "Missing header."));
+    }
+    let version = match data[MAGIC.len()] {
+      b'2' => RespVersion::RESP2,
+      b'3' => RespVersion::RESP3,
+      _ => return Err(utils::invalid_response("session", "Invalid RESP version.")),
+    };
+
+    let mut events = Vec::new();
+    let mut offset = MAGIC.len() + 1;
+    while offset < data.len() {
+      if data.len() < offset + 5 {
+        return Err(utils::invalid_response("session", "Truncated event header."));
+      }
+      let tag = data[offset];
+      let len = u32::from_be_bytes([data[offset + 1], data[offset + 2], data[offset + 3], data[offset + 4]]) as usize;
+      let payload = match data.get(offset + 5..offset + 5 + len) {
+        Some(payload) => payload,
+        None => return Err(utils::invalid_response("session", "Truncated event payload.")),
+      };
+
+      events.push(match tag {
+        INPUT_TAG => SessionEvent::Input(payload.to_vec()),
+        FRAME_TAG => SessionEvent::Frame(SessionFrame::decode(&version, payload)?),
+        ERROR_TAG => match String::from_utf8(payload.to_vec()) {
+          Ok(description) => SessionEvent::Error(description),
+          Err(_) => return Err(utils::invalid_response("session", "Invalid error description.")),
+        },
+        _ => {
+          return Err(utils::invalid_response(
+            "session",
+            format!("Invalid event tag {}.", tag),
+          ))
+        },
+      });
+      offset += 5 + len;
+    }
+
+    Ok(Session { version, events })
+  }
+
+  /// Pass the recorded input chunks to the current decoder and check that it produces the same frames and errors.
+  ///
+  /// The returned error describes the first event that does not match the recording.
+  pub fn replay(&self) -> Result<(), RedisProtocolError> {
+    let mut decoder = SessionDecoder::new(self.version.clone());
+    let mut replayed = Vec::with_capacity(self.events.len());
+
+    for event in self.events.iter() {
+      if let SessionEvent::Input(ref chunk) = *event {
+        replayed.push(event.clone());
+        if decoder.decode(chunk, &mut replayed).is_err() {
+          break;
+        }
+      }
+    }
+
+    for (idx, (recorded, replayed)) in self.events.iter().zip(replayed.iter()).enumerate() {
+      if recorded != replayed {
+        return Err(RedisProtocolError::new(
+          RedisProtocolErrorKind::Unknown,
+          format!("Event {} does not match. Recorded {:?}, replayed {:?}.", idx, recorded, replayed),
+        ));
+      }
+    }
+    if self.events.len() != replayed.len() {
+      return Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::Unknown,
+        format!("Recorded {} events, replayed {}.", self.events.len(), replayed.len()),
+      ));
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_record_and_replay_resp3_session() {
+    let mut recorder = DecodeRecorder::new(Vec::new(), RespVersion::RESP3).unwrap();
+    assert!(recorder.decode(b"*?\r\n:1\r\n$3\r\nfo").unwrap().is_empty());
+    let frames = recorder.decode(b"o\r\n.\r\n+OK\r\n").unwrap();
+    assert!(recorder.decode(b"&foo\r\n").is_err());
+
+    assert_eq!(frames, vec![
+      SessionFrame::Resp3(Resp3Frame::Array {
+        data: vec![
+          Resp3Frame::from(1),
+          Resp3Frame::BlobString {
+            data: b"foo".to_vec(),
+            attributes: None,
+          },
+        ],
+        attributes: None,
+      }),
+      SessionFrame::Resp3(Resp3Frame::SimpleString {
+        data: "OK".into(),
+        attributes: None,
+      }),
+    ]);
+
+    let session = Session::read(&recorder.into_inner()[..]).unwrap();
+    assert_eq!(session.version, RespVersion::RESP3);
+    assert_eq!(session.events.len(), 6);
+    assert_eq!(session.events[1], SessionEvent::Input(b"o\r\n.\r\n+OK\r\n".to_vec()));
+    assert_eq!(session.events[2], SessionEvent::Frame(frames[0].clone()));
+    assert!(matches!(session.events[5], SessionEvent::Error(_)));
+    session.replay().unwrap();
+  }
+
+  #[test]
+  fn should_detect_replay_mismatch() {
+    let mut recorder = DecodeRecorder::new(Vec::new(), RespVersion::RESP2).unwrap();
+    recorder.decode(b"*2\r\n:1\r\n$-1\r\n").unwrap();
+
+    let mut session = Session::read(&recorder.into_inner()[..]).unwrap();
+    session.replay().unwrap();
+    session.events[1] = SessionEvent::Frame(SessionFrame::Resp2(Resp2Frame::Null));
+    assert!(session.replay().is_err());
+    session.events.pop();
+    assert!(session.replay().is_err());
+  }
+
+  #[test]
+  fn should_error_on_invalid_session() {
+    assert!(Session::read(&b"RESPREC4"[..]).is_err());
+    assert!(Session::read(&b"RESPREC3I\x00\x00\x00\x05ab"[..]).is_err());
+    assert!(Session::read(&b"RESPREC3F\x00\x00\x00\x01:"[..]).is_err());
+  }
+}
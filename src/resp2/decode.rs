@@ -2,15 +2,28 @@
 //!
 //! <https://redis.io/topics/protocol#resp-protocol-description>
 
+use crate::core;
+use crate::observer::{self, FrameObserver};
 use crate::resp2::types::*;
 use crate::types::*;
+#[cfg(feature = "inline-commands")]
+use crate::utils;
 use nom::number::streaming::be_u8;
-use nom::Err as NomError;
+use nom::{Err as NomError, IResult};
+use bytes::Bytes;
+#[cfg(feature = "inline-commands")]
+use std::convert::TryFrom;
+use std::borrow::Cow;
 use std::num::ParseIntError;
 use std::str;
 
 const NULL_LEN: isize = -1;
 
+/// The maximum length of an inline command, including the trailing newline. This matches `PROTO_INLINE_MAX_SIZE` on
+/// the server.
+#[cfg(feature = "inline-commands")]
+pub const INLINE_MAX_SIZE: usize = 64 * 1024;
+
 fn to_isize(s: &str) -> Result<isize, ParseIntError> {
   s.parse::<isize>()
 }
@@ -19,8 +32,8 @@ fn to_i64(s: &str) -> Result<i64, ParseIntError> {
   s.parse::<i64>()
 }
 
-fn map_error(s: &str) -> Frame {
-  Frame::Error(s.to_owned())
+fn map_error<B>(s: &str) -> GenericFrame<B> {
+  GenericFrame::Error(s.to_owned())
 }
 
 fn isize_to_usize<'a>(s: isize) -> Result<usize, RedisProtocolError> {
@@ -34,9 +47,14 @@ fn isize_to_usize<'a>(s: isize) -> Result<usize, RedisProtocolError> {
   }
 }
 
-named!(read_to_crlf<&[u8]>, terminated!(take_until!(CRLF), take!(2)));
+fn read_to_crlf_s(input: &[u8]) -> IResult<&[u8], &str> {
+  core::read_to_crlf_str(input)
+}
 
-named!(read_to_crlf_s<&str>, map_res!(read_to_crlf, str::from_utf8));
+// negative lengths other than the null length are read as very large lengths, so they are always incomplete
+fn read_blob(input: &[u8], len: isize) -> IResult<&[u8], &[u8]> {
+  core::read_blob(input, len as usize)
+}
 
 named!(read_prefix_len<isize>, map_res!(read_to_crlf_s, to_isize));
 
@@ -51,75 +69,318 @@ named!(
   )
 );
 
-named!(
-  parse_simplestring<Frame>,
-  do_parse!(data: read_to_crlf_s >> (Frame::SimpleString(data.to_owned())))
-);
-
-named!(
-  parse_integer<Frame>,
-  do_parse!(data: map_res!(read_to_crlf_s, to_i64) >> (Frame::Integer(data)))
+/// Define the parsers for frames that store bulk strings as `$storage`, created from the decoded bytes with `$bulk`.
+macro_rules! frame_parsers (
+  ($module:ident, $storage:ty, $bulk:expr) => {
+    mod $module {
+      use super::*;
+
+      named!(
+        parse_simplestring<GenericFrame<$storage>>,
+        do_parse!(data: read_to_crlf_s >> (GenericFrame::SimpleString(data.to_owned())))
+      );
+
+      named!(
+        parse_integer<GenericFrame<$storage>>,
+        do_parse!(data: map_res!(read_to_crlf_s, to_i64) >> (GenericFrame::Integer(data)))
+      );
+
+      // assumes the '$-1\r\n' has been consumed already, since nulls look like bulk strings until the length prefix is
+      // parsed, and parsing the length prefix consumes the trailing \r\n in the underlying `terminated!` call
+      named!(parse_null<GenericFrame<$storage>>, do_parse!((GenericFrame::Null)));
+
+      named!(parse_error<GenericFrame<$storage>>, map!(read_to_crlf_s, map_error));
+
+      named_args!(parse_bulkstring(len: isize) <GenericFrame<$storage>>,
+        do_parse!(
+          d: call!(read_blob, len) >>
+          (GenericFrame::BulkString($bulk(d)))
+        )
+      );
+
+      named!(
+        parse_bulkstring_or_null<GenericFrame<$storage>>,
+        switch!(read_prefix_len,
+          NULL_LEN => call!(parse_null) |
+          len      => call!(parse_bulkstring, len)
+        )
+      );
+
+      named_args!(parse_array_frames(len: usize) <Vec<GenericFrame<$storage>>>, count!(parse_frame, len));
+
+      named!(
+        parse_array<GenericFrame<$storage>>,
+        switch!(read_prefix_len,
+          NULL_LEN => call!(parse_null) |
+          len      => do_parse!(
+            size: map_res!(value!(len), isize_to_usize) >>
+            frames: call!(parse_array_frames, size) >>
+            (GenericFrame::Array(frames))
+          )
+        )
+      );
+
+      named!(
+        pub parse_frame<GenericFrame<$storage>>,
+        switch!(frame_type,
+          FrameKind::SimpleString => call!(parse_simplestring) |
+          FrameKind::Error        => call!(parse_error) |
+          FrameKind::Integer      => call!(parse_integer) |
+          FrameKind::BulkString   => call!(parse_bulkstring_or_null) |
+          FrameKind::Array        => call!(parse_array)
+        )
+      );
+    }
+  }
 );
 
-// assumes the '$-1\r\n' has been consumed already, since nulls look like bulk strings until the length prefix is parsed,
-// and parsing the length prefix consumes the trailing \r\n in the underlying `terminated!` call
-named!(parse_null<Frame>, do_parse!((Frame::Null)));
+frame_parsers!(owned, Vec<u8>, Vec::from);
+frame_parsers!(borrowed, Cow<[u8]>, Cow::Borrowed);
 
-named!(parse_error<Frame>, map!(read_to_crlf_s, map_error));
+/// Attempt to parse the contents of `buf`, returning the first valid frame and the number of bytes consumed.
+///
+/// If the byte slice contains an incomplete frame then `None` is returned.
+pub fn decode(buf: &[u8]) -> Result<Option<(Frame, usize)>, RedisProtocolError> {
+  decode_inner(buf, None)
+}
 
-named_args!(parse_bulkstring(len: isize) <Frame>,
-  do_parse!(
-    d: terminated!(take!(len), take!(2)) >>
-    (Frame::BulkString(Vec::from(d)))
-  )
-);
+/// Attempt to parse the contents of `buf`, capturing a hex dump of the bytes within `window` bytes of the failing
+/// offset if decoding fails.
+///
+/// The dump can be read with [hex_dump](crate::types::RedisProtocolError::hex_dump) and is included when the error
+/// is displayed. See [decode] for more information.
+pub fn decode_with_hex_dump(buf: &[u8], window: usize) -> Result<Option<(Frame, usize)>, RedisProtocolError> {
+  decode_inner(buf, Some(window))
+}
 
-named!(
-  parse_bulkstring_or_null<Frame>,
-  switch!(read_prefix_len,
-    NULL_LEN => call!(parse_null) |
-    len      => call!(parse_bulkstring, len)
-  )
-);
+/// Attempt to parse the contents of `buf`, returning the first valid frame and the bytes that follow it.
+///
+/// See [decode] for more information.
+pub fn decode_with_remaining(buf: &[u8]) -> Result<Option<(Frame, &[u8])>, RedisProtocolError> {
+  decode(buf).map(|result| result.map(|(frame, len)| (frame, &buf[len..])))
+}
 
-named_args!(parse_array_frames(len: usize) <Vec<Frame>>, count!(parse_frame, len));
+/// Attempt to parse the contents of `buf` without copying the contents of bulk strings, returning the first valid frame
+/// and the number of bytes consumed.
+///
+/// Bulk strings in the returned frame borrow from `buf`. See [decode] for more information.
+///
+/// ```
+/// # use redis_protocol::resp2::decode::decode_borrowed;
+/// # use redis_protocol::resp2::types::GenericFrame;
+/// # use std::borrow::Cow;
+/// let buf = b"$3\r\nfoo\r\n";
+/// let (frame, len) = decode_borrowed(buf).unwrap().unwrap();
+///
+/// assert_eq!(len, buf.len());
+/// assert!(matches!(frame, GenericFrame::BulkString(Cow::Borrowed(b"foo"))));
+/// ```
+pub fn decode_borrowed(buf: &[u8]) -> Result<Option<(BorrowedFrame<'_>, usize)>, RedisProtocolError> {
+  decode_with_parser(buf, None, borrowed::parse_frame)
+}
 
-named!(
-  parse_array<Frame>,
-  switch!(read_prefix_len,
-    NULL_LEN => call!(parse_null) |
-    len      => do_parse!(
-      size: map_res!(value!(len), isize_to_usize) >>
-      frames: call!(parse_array_frames, size) >>
-      (Frame::Array(frames))
-    )
-  )
-);
+/// Attempt to parse the contents of `buf` without copying the contents of bulk strings, returning the first valid frame
+/// and the number of bytes consumed.
+///
+/// Bulk strings in the returned frame are slices of `buf` that share its reference count. See [decode] for more
+/// information.
+pub fn decode_bytes(buf: &Bytes) -> Result<Option<(BytesFrame, usize)>, RedisProtocolError> {
+  decode_borrowed(buf).map(|result| result.map(|(frame, len)| (frame.map_bytes(|b| buf.slice_ref(&b)), len)))
+}
 
-named!(
-  parse_frame<Frame>,
-  switch!(frame_type,
-    FrameKind::SimpleString => call!(parse_simplestring) |
-    FrameKind::Error        => call!(parse_error) |
-    FrameKind::Integer      => call!(parse_integer) |
-    FrameKind::BulkString   => call!(parse_bulkstring_or_null) |
-    FrameKind::Array        => call!(parse_array)
-  )
-);
+fn decode_inner(buf: &[u8], hex_dump_window: Option<usize>) -> Result<Option<(Frame, usize)>, RedisProtocolError> {
+  decode_with_parser(buf, hex_dump_window, owned::parse_frame)
+}
 
-/// Attempt to parse the contents of `buf`, returning the first valid frame and the number of bytes consumed.
-///
-/// If the byte slice contains an incomplete frame then `None` is returned.
-pub fn decode(buf: &[u8]) -> Result<Option<(Frame, usize)>, RedisProtocolError> {
+fn decode_with_parser<'a, B, P>(
+  buf: &'a [u8],
+  hex_dump_window: Option<usize>,
+  parser: P,
+) -> Result<Option<(GenericFrame<B>, usize)>, RedisProtocolError>
+where
+  P: FnOnce(&'a [u8]) -> IResult<&'a [u8], GenericFrame<B>>,
+{
   let len = buf.len();
 
-  match parse_frame(buf) {
+  let result = match parser(buf) {
     Ok((remaining, frame)) => Ok(Some((frame, len - remaining.len()))),
     Err(NomError::Incomplete(_)) => Ok(None),
-    Err(e) => Err(e.into()),
+    Err(e) => match hex_dump_window {
+      Some(window) => {
+        let offset = match e {
+          NomError::Error(ref inner) | NomError::Failure(ref inner) => len - inner.input.len(),
+          NomError::Incomplete(_) => 0,
+        };
+        Err(RedisProtocolError::from(e).with_hex_dump(buf, offset, window))
+      }
+      None => Err(e.into()),
+    },
+  };
+
+  #[cfg(feature = "metrics")]
+  crate::metrics::record_decode(crate::metrics::RESP2, &result, |frame| observer::resp2_kind_to_resp3(frame.kind()));
+  result
+}
+
+/// Attempt to parse the contents of `buf`, reporting the result to `observer`.
+///
+/// See [decode] for more information.
+pub fn decode_with_observer(
+  buf: &[u8],
+  observer: &dyn FrameObserver,
+) -> Result<Option<(Frame, usize)>, RedisProtocolError> {
+  let start = observer::Stopwatch::start();
+  let result = decode(buf);
+  observer::observe_decode(observer, start, &result, |frame| observer::resp2_kind_to_resp3(frame.kind()));
+  result
+}
+
+/// Attempt to parse an inline command, such as `PING\r\n`, from the start of `buf`.
+///
+/// Inline commands are newline terminated and split into arguments with the same quoting rules as `redis-cli`. The
+/// command is returned as an array of bulk strings, just as if it had been sent as a RESP2 array. An empty line is
+/// returned as an empty array, which servers should ignore.
+///
+/// If the byte slice does not contain a newline then `None` is returned, unless `buf` is longer than
+/// [INLINE_MAX_SIZE].
+#[cfg(feature = "inline-commands")]
+pub fn decode_inline(buf: &[u8]) -> Result<Option<(Frame, usize)>, RedisProtocolError> {
+  let newline = match buf.iter().position(|b| *b == b'\n') {
+    Some(idx) if idx < INLINE_MAX_SIZE => idx,
+    Some(_) => return Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Inline command too long.")),
+    None if buf.len() >= INLINE_MAX_SIZE => {
+      return Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Inline command too long."))
+    },
+    None => return Ok(None),
+  };
+
+  let line = if newline > 0 && buf[newline - 1] == b'\r' {
+    &buf[0..newline - 1]
+  } else {
+    &buf[0..newline]
+  };
+  let args = utils::split_args(line)?;
+
+  Ok(Some((Frame::Array(args.into_iter().map(Frame::BulkString).collect()), newline + 1)))
+}
+
+/// Attempt to parse a command sent by a client, which may use either the RESP2 array format or the inline format.
+///
+/// Like the server, any buffer that does not start with `*` is parsed as an inline command.
+#[cfg(feature = "inline-commands")]
+pub fn decode_request(buf: &[u8]) -> Result<Option<(Frame, usize)>, RedisProtocolError> {
+  match buf.first() {
+    Some(&ARRAY_BYTE) => decode(buf),
+    Some(_) => decode_inline(buf),
+    None => Ok(None),
   }
 }
 
+/// The maximum number of arguments an unauthenticated client can send. This matches the server.
+#[cfg(feature = "inline-commands")]
+const UNAUTHENTICATED_MULTIBULK_LEN: i64 = 10;
+/// The maximum length of an argument sent by an unauthenticated client. This matches the server.
+#[cfg(feature = "inline-commands")]
+const UNAUTHENTICATED_BULK_LEN: i64 = 16384;
+
+#[cfg(feature = "inline-commands")]
+fn protocol_error(message: &str) -> RedisProtocolError {
+  RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, format!("Protocol error: {}", message))
+}
+
+/// Read the length prefix on the line starting at `offset`, returning the length, if it is a valid integer, and the
+/// offset after the line.
+#[cfg(feature = "inline-commands")]
+fn read_request_len(
+  buf: &[u8],
+  offset: usize,
+  too_big: &str,
+) -> Result<Option<(Option<i64>, usize)>, RedisProtocolError> {
+  let line = &buf[offset..];
+
+  match line.iter().position(|b| *b == b'\r') {
+    Some(idx) if idx + 1 < line.len() => {
+      let len = str::from_utf8(&line[0..idx]).ok().and_then(|s| s.parse::<i64>().ok());
+      Ok(Some((len, offset + idx + 2)))
+    },
+    Some(_) => Ok(None),
+    None if line.len() > INLINE_MAX_SIZE => Err(protocol_error(too_big)),
+    None => Ok(None),
+  }
+}
+
+/// Attempt to parse a command sent by a client, enforcing the same limits as the server.
+///
+/// Errors use the same message as the server, such as `Protocol error: invalid bulk length`, so servers can reply
+/// with [err](crate::resp2::replies::err)`("ERR", e.description())` and then close the connection. Limits are checked
+/// as soon as each length prefix is received, so oversized arguments are rejected before they are buffered.
+///
+/// Commands with a length of zero or less are returned as an empty array, which servers should ignore. See
+/// [decode_request] for more information.
+#[cfg(feature = "inline-commands")]
+pub fn decode_request_with_config(
+  buf: &[u8],
+  config: &RequestConfig,
+) -> Result<Option<(Frame, usize)>, RedisProtocolError> {
+  match buf.first() {
+    Some(&ARRAY_BYTE) => {},
+    Some(_) => {
+      return match decode_inline(buf) {
+        Err(_) if !buf.iter().take(INLINE_MAX_SIZE).any(|b| *b == b'\n') => {
+          Err(protocol_error("too big inline request"))
+        },
+        Err(_) => Err(protocol_error("unbalanced quotes in request")),
+        result => result,
+      }
+    },
+    None => return Ok(None),
+  };
+
+  let (count, mut offset) = match read_request_len(buf, 1, "too big mbulk count string")? {
+    Some(result) => result,
+    None => return Ok(None),
+  };
+  let max_count = i64::try_from(config.max_multibulk_len).unwrap_or(i64::MAX);
+  let count = match count {
+    Some(count) if count <= max_count => count,
+    _ => return Err(protocol_error("invalid multibulk length")),
+  };
+  if count <= 0 {
+    return Ok(Some((Frame::Array(Vec::new()), offset)));
+  }
+  if config.auth_required && count > UNAUTHENTICATED_MULTIBULK_LEN {
+    return Err(protocol_error("unauthenticated multibulk length"));
+  }
+
+  let max_len = i64::try_from(config.max_bulk_len).unwrap_or(i64::MAX);
+  for _ in 0..count {
+    match buf.get(offset) {
+      Some(&BULKSTRING_BYTE) => {},
+      Some(b) => return Err(protocol_error(&format!("expected '$', got '{}'", *b as char))),
+      None => return Ok(None),
+    };
+
+    let (len, end) = match read_request_len(buf, offset + 1, "too big bulk count string")? {
+      Some(result) => result,
+      None => return Ok(None),
+    };
+    let len = match len {
+      Some(len) if len >= 0 && len <= max_len => len,
+      _ => return Err(protocol_error("invalid bulk length")),
+    };
+    if config.auth_required && len > UNAUTHENTICATED_BULK_LEN {
+      return Err(protocol_error("unauthenticated bulk length"));
+    }
+
+    offset = end.saturating_add(len as usize).saturating_add(2);
+    if offset > buf.len() {
+      return Ok(None);
+    }
+  }
+
+  decode(buf)
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -189,6 +450,15 @@ mod tests {
     decode_and_verify_padded_some(&mut bytes, &expected);
   }
 
+  #[test]
+  fn should_decode_with_remaining() {
+    let (frame, remaining) = decode_with_remaining(b"$3\r\nfoo\r\n*2\r\n").unwrap().unwrap();
+    assert_eq!(frame, Frame::BulkString(b"foo".to_vec()));
+    assert_eq!(remaining, b"*2\r\n");
+
+    assert!(decode_with_remaining(b"$3\r\nfo").unwrap().is_none());
+  }
+
   #[test]
   #[should_panic]
   fn should_decode_simple_string_incomplete() {
@@ -294,4 +564,158 @@ mod tests {
     let bytes: BytesMut = "foobarbazwibblewobble".into();
     let _ = decode(&bytes).map_err(|e| pretty_print_panic(e));
   }
+
+  #[cfg(feature = "inline-commands")]
+  #[test]
+  fn should_decode_without_copying_bulk_strings() {
+    let buf = Bytes::from_static(b"*3\r\n$3\r\nfoo\r\n:1\r\n$-1\r\n+OK\r\n");
+    let expected = Frame::Array(vec![Frame::BulkString("foo".into()), Frame::Integer(1), Frame::Null]);
+
+    let (frame, len) = decode_borrowed(&buf).unwrap().unwrap();
+    assert_eq!(len, 22);
+    assert_eq!(frame.to_owned_frame(), expected);
+    match frame {
+      GenericFrame::Array(ref frames) => match frames[0] {
+        GenericFrame::BulkString(Cow::Borrowed(data)) => assert_eq!(data.as_ptr(), buf[8..].as_ptr()),
+        _ => panic!("Expected borrowed bulk string."),
+      },
+      _ => panic!("Expected array."),
+    };
+
+    let (frame, len) = decode_bytes(&buf).unwrap().unwrap();
+    assert_eq!(len, 22);
+    match frame {
+      GenericFrame::Array(ref frames) => match frames[0] {
+        GenericFrame::BulkString(ref data) => assert_eq!(data.as_ptr(), buf[8..].as_ptr()),
+        _ => panic!("Expected bulk string."),
+      },
+      _ => panic!("Expected array."),
+    };
+    assert_eq!(frame.into_owned(), expected);
+
+    assert!(decode_bytes(&buf.slice(0..20)).unwrap().is_none());
+    assert!(decode_borrowed(b"foo\r\n").is_err());
+  }
+
+  #[cfg(feature = "inline-commands")]
+  fn bulk_array(args: &[&str]) -> Frame {
+    Frame::Array(args.iter().map(|a| Frame::BulkString(a.as_bytes().to_vec())).collect())
+  }
+
+  #[test]
+  #[cfg(feature = "inline-commands")]
+  fn should_decode_inline_command() {
+    let (frame, len) = decode_inline(b"PING\r\nFOO").unwrap().unwrap();
+    assert_eq!(frame, bulk_array(&["PING"]));
+    assert_eq!(len, 6);
+
+    let (frame, len) = decode_inline(b"set \"my key\" 'b\\'c'\n").unwrap().unwrap();
+    assert_eq!(frame, bulk_array(&["set", "my key", "b'c"]));
+    assert_eq!(len, 20);
+
+    let (frame, len) = decode_inline(b"\r\n").unwrap().unwrap();
+    assert_eq!(frame, Frame::Array(vec![]));
+    assert_eq!(len, 2);
+  }
+
+  #[test]
+  fn should_capture_hex_dump_on_error() {
+    let buf = b"*2\r\n:1\r\n?foo\r\n";
+    let error = decode_with_hex_dump(buf, 4).unwrap_err();
+
+    assert_eq!(error.offset(), Some(8));
+    assert_eq!(
+      error.hex_dump(),
+      Some(concat!(
+        "00000000  2a 32 0d 0a 3a 31 0d 0a 3f 66 6f 6f              |*2..:1..?foo|\n",
+        "                                  ^^"
+      ))
+    );
+    assert!(error.to_string().contains("(at offset 8)\n00000000"));
+    assert_eq!(decode(buf).unwrap_err().hex_dump(), None);
+  }
+
+  #[test]
+  #[cfg(feature = "inline-commands")]
+  fn should_decode_incomplete_inline_command() {
+    assert_eq!(decode_inline(b"GET foo").unwrap(), None);
+    assert_eq!(decode_request(b"").unwrap(), None);
+  }
+
+  #[test]
+  #[cfg(feature = "inline-commands")]
+  fn should_error_on_invalid_inline_command() {
+    assert!(decode_inline(b"GET \"foo\r\n").is_err());
+    assert!(decode_inline(&vec![b'a'; INLINE_MAX_SIZE]).is_err());
+  }
+
+  #[test]
+  #[cfg(feature = "inline-commands")]
+  fn should_decode_request_in_either_format() {
+    let expected = bulk_array(&["GET", "foo"]);
+    assert_eq!(decode_request(b"GET foo\r\n").unwrap(), Some((expected.clone(), 9)));
+    assert_eq!(decode_request(b"*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n").unwrap(), Some((expected, 22)));
+  }
+
+  #[test]
+  #[cfg(feature = "inline-commands")]
+  fn should_decode_inline_command_with_nul_bytes() {
+    let expected = Frame::Array(vec![
+      Frame::BulkString(b"GET".to_vec()),
+      Frame::BulkString(b"a\0b".to_vec()),
+    ]);
+
+    assert_eq!(decode_inline(b"GET a\0b\r\n").unwrap(), Some((expected.clone(), 9)));
+    assert_eq!(decode_request(b"GET a\0b\r\n").unwrap(), Some((expected.clone(), 9)));
+    assert_eq!(
+      decode_request_with_config(b"GET a\0b\r\n", &RequestConfig::default()).unwrap(),
+      Some((expected, 9))
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "inline-commands")]
+  fn should_decode_request_with_limits() {
+    let config = RequestConfig {
+      max_bulk_len: 3,
+      max_multibulk_len: 2,
+      auth_required: false,
+    };
+    let error = |buf: &[u8]| decode_request_with_config(buf, &config).unwrap_err().description().to_owned();
+
+    assert_eq!(
+      decode_request_with_config(b"*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n", &config).unwrap(),
+      Some((bulk_array(&["GET", "foo"]), 22))
+    );
+    assert_eq!(decode_request_with_config(b"*2\r\n$3\r\nGET\r\n$3", &config).unwrap(), None);
+    assert_eq!(
+      decode_request_with_config(b"*0\r\n", &config).unwrap(),
+      Some((Frame::Array(vec![]), 4))
+    );
+    assert_eq!(error(b"*3\r\n"), "Protocol error: invalid multibulk length");
+    assert_eq!(error(b"*x\r\n"), "Protocol error: invalid multibulk length");
+    assert_eq!(error(b"*1\r\n$4\r\n"), "Protocol error: invalid bulk length");
+    assert_eq!(error(b"*1\r\n$-1\r\n"), "Protocol error: invalid bulk length");
+    assert_eq!(error(b"*1\r\n:1\r\n"), "Protocol error: expected '$', got ':'");
+    assert_eq!(error(b"GET \"foo\r\n"), "Protocol error: unbalanced quotes in request");
+    assert_eq!(error(&vec![b'a'; INLINE_MAX_SIZE + 1]), "Protocol error: too big inline request");
+
+    let mut buf = b"*1\r\n$".to_vec();
+    buf.extend_from_slice(&vec![b'1'; INLINE_MAX_SIZE + 1]);
+    assert_eq!(error(&buf), "Protocol error: too big bulk count string");
+  }
+
+  #[test]
+  #[cfg(feature = "inline-commands")]
+  fn should_decode_unauthenticated_request_with_limits() {
+    let config = RequestConfig {
+      auth_required: true,
+      ..Default::default()
+    };
+    let error = |buf: &[u8]| decode_request_with_config(buf, &config).unwrap_err().description().to_owned();
+
+    assert_eq!(error(b"*11\r\n"), "Protocol error: unauthenticated multibulk length");
+    assert_eq!(error(b"*1\r\n$16385\r\n"), "Protocol error: unauthenticated bulk length");
+    assert!(decode_request_with_config(b"*1\r\n$16384\r\n", &config).unwrap().is_none());
+  }
 }
@@ -2,9 +2,15 @@
 //!
 //! <https://redis.io/topics/protocol#resp-protocol-description>
 
+use crate::core;
+use crate::observer::{self, FrameObserver};
 use crate::resp2::types::*;
 use crate::resp2::utils::{self as resp2_utils};
-use crate::types::{RedisProtocolError, CRLF};
+#[cfg(feature = "inline-commands")]
+use crate::resp2::decode::INLINE_MAX_SIZE;
+use crate::types::RedisProtocolError;
+#[cfg(feature = "inline-commands")]
+use crate::types::{RedisProtocolErrorKind, CRLF};
 use crate::utils;
 use bytes::BytesMut;
 use cookie_factory::GenError;
@@ -12,41 +18,25 @@ use cookie_factory::GenError;
 fn gen_simplestring<'a>(x: (&'a mut [u8], usize), data: &str) -> Result<(&'a mut [u8], usize), GenError> {
   encode_checks!(x, resp2_utils::simplestring_encode_len(data));
 
-  do_gen!(
-    x,
-    gen_be_u8!(FrameKind::SimpleString.to_byte()) >> gen_slice!(data.as_bytes()) >> gen_slice!(CRLF.as_bytes())
-  )
+  core::gen_line(x, FrameKind::SimpleString.to_byte(), data.as_bytes())
 }
 
 fn gen_error<'a>(x: (&'a mut [u8], usize), data: &str) -> Result<(&'a mut [u8], usize), GenError> {
   encode_checks!(x, resp2_utils::error_encode_len(data));
 
-  do_gen!(
-    x,
-    gen_be_u8!(FrameKind::Error.to_byte()) >> gen_slice!(data.as_bytes()) >> gen_slice!(CRLF.as_bytes())
-  )
+  core::gen_line(x, FrameKind::Error.to_byte(), data.as_bytes())
 }
 
 fn gen_integer<'a>(x: (&'a mut [u8], usize), data: &i64) -> Result<(&'a mut [u8], usize), GenError> {
   encode_checks!(x, resp2_utils::integer_encode_len(data));
 
-  do_gen!(
-    x,
-    gen_be_u8!(FrameKind::Integer.to_byte()) >> gen_slice!(data.to_string().as_bytes()) >> gen_slice!(CRLF.as_bytes())
-  )
+  core::gen_line(x, FrameKind::Integer.to_byte(), data.to_string().as_bytes())
 }
 
 fn gen_bulkstring<'a>(x: (&'a mut [u8], usize), data: &[u8]) -> Result<(&'a mut [u8], usize), GenError> {
   encode_checks!(x, resp2_utils::bulkstring_encode_len(data));
 
-  do_gen!(
-    x,
-    gen_be_u8!(FrameKind::BulkString.to_byte())
-      >> gen_slice!(data.len().to_string().as_bytes())
-      >> gen_slice!(CRLF.as_bytes())
-      >> gen_slice!(data)
-      >> gen_slice!(CRLF.as_bytes())
-  )
+  core::gen_blob(x, FrameKind::BulkString.to_byte(), data)
 }
 
 fn gen_null(x: (&mut [u8], usize)) -> Result<(&mut [u8], usize), GenError> {
@@ -55,22 +45,22 @@ fn gen_null(x: (&mut [u8], usize)) -> Result<(&mut [u8], usize), GenError> {
   do_gen!(x, gen_slice!(NULL.as_bytes()))
 }
 
-fn gen_array<'a>(x: (&'a mut [u8], usize), data: &Vec<Frame>) -> Result<(&'a mut [u8], usize), GenError> {
+fn gen_array<'a, B: AsRef<[u8]>>(
+  x: (&'a mut [u8], usize),
+  data: &[GenericFrame<B>],
+) -> Result<(&'a mut [u8], usize), GenError> {
   encode_checks!(x, resp2_utils::array_encode_len(data)?);
 
-  let mut x = do_gen!(
-    x,
-    gen_be_u8!(FrameKind::Array.to_byte())
-      >> gen_slice!(data.len().to_string().as_bytes())
-      >> gen_slice!(CRLF.as_bytes())
-  )?;
+  let mut x = core::gen_length_prefix(x, FrameKind::Array.to_byte(), data.len())?;
 
   for frame in data.iter() {
     x = match frame {
-      Frame::BulkString(ref b) => gen_bulkstring(x, &b)?,
-      Frame::Null => gen_null(x)?,
-      Frame::Array(ref frames) => gen_array(x, frames)?,
-      _ => return Err(GenError::CustomError(1)),
+      GenericFrame::BulkString(ref b) => gen_bulkstring(x, b.as_ref())?,
+      GenericFrame::Null => gen_null(x)?,
+      GenericFrame::Array(ref frames) => gen_array(x, frames)?,
+      GenericFrame::Error(ref s) => gen_error(x, s)?,
+      GenericFrame::SimpleString(ref s) => gen_simplestring(x, s)?,
+      GenericFrame::Integer(ref i) => gen_integer(x, i)?,
     };
   }
 
@@ -78,45 +68,175 @@ fn gen_array<'a>(x: (&'a mut [u8], usize), data: &Vec<Frame>) -> Result<(&'a mut
   Ok(x)
 }
 
-fn attempt_encoding(buf: &mut [u8], offset: usize, frame: &Frame) -> Result<usize, GenError> {
+fn attempt_encoding<B: AsRef<[u8]>>(buf: &mut [u8], offset: usize, frame: &GenericFrame<B>) -> Result<usize, GenError> {
   match *frame {
-    Frame::BulkString(ref b) => gen_bulkstring((buf, offset), b).map(|(_, l)| l),
-    Frame::Null => gen_null((buf, offset)).map(|(_, l)| l),
-    Frame::Array(ref frames) => gen_array((buf, offset), frames).map(|(_, l)| l),
-    Frame::Error(ref s) => gen_error((buf, offset), s).map(|(_, l)| l),
-    Frame::SimpleString(ref s) => gen_simplestring((buf, offset), s).map(|(_, l)| l),
-    Frame::Integer(ref i) => gen_integer((buf, offset), i).map(|(_, l)| l),
+    GenericFrame::BulkString(ref b) => gen_bulkstring((buf, offset), b.as_ref()).map(|(_, l)| l),
+    GenericFrame::Null => gen_null((buf, offset)).map(|(_, l)| l),
+    GenericFrame::Array(ref frames) => gen_array((buf, offset), frames).map(|(_, l)| l),
+    GenericFrame::Error(ref s) => gen_error((buf, offset), s).map(|(_, l)| l),
+    GenericFrame::SimpleString(ref s) => gen_simplestring((buf, offset), s).map(|(_, l)| l),
+    GenericFrame::Integer(ref i) => gen_integer((buf, offset), i).map(|(_, l)| l),
   }
 }
 
 /// Attempt to encode a frame into `buf`, assuming a starting offset of 0.
 ///
-/// The caller is responsible for extending the buffer if a `RedisProtocolErrorKind::BufferTooSmall` is returned.
-pub fn encode(buf: &mut [u8], offset: usize, frame: &Frame) -> Result<usize, RedisProtocolError> {
-  attempt_encoding(buf, offset, frame).map_err(|e| e.into())
+/// If the buffer is too small a `RedisProtocolErrorKind::SliceTooSmall` error is returned with the minimum length of
+/// the buffer, including the bytes before `offset`. The caller is responsible for resizing the buffer and retrying.
+///
+/// Frames with any bulk string storage can be encoded, such as a [BytesFrame] or [BorrowedFrame].
+pub fn encode<B: AsRef<[u8]>>(
+  buf: &mut [u8],
+  offset: usize,
+  frame: &GenericFrame<B>,
+) -> Result<usize, RedisProtocolError> {
+  let len = buf.len();
+  let result = attempt_encoding(buf, offset, frame).map_err(|e| core::encode_error(len, e));
+
+  #[cfg(feature = "metrics")]
+  crate::metrics::record_encode(crate::metrics::RESP2, observer::resp2_kind_to_resp3(frame.kind()), offset, &result);
+  result
 }
 
 /// Attempt to encode a frame into `buf`, extending the buffer as needed.
 ///
 /// Returns the number of bytes encoded.
-pub fn encode_bytes(buf: &mut BytesMut, frame: &Frame) -> Result<usize, RedisProtocolError> {
+pub fn encode_bytes<B: AsRef<[u8]>>(buf: &mut BytesMut, frame: &GenericFrame<B>) -> Result<usize, RedisProtocolError> {
+  #[cfg(feature = "metrics")]
   let offset = buf.len();
+  let result = core::encode_extend(buf, |buf, offset| attempt_encoding(buf, offset, frame));
+
+  #[cfg(feature = "metrics")]
+  crate::metrics::record_encode(crate::metrics::RESP2, observer::resp2_kind_to_resp3(frame.kind()), offset, &result);
+  result
+}
+
+/// Attempt to encode a frame into `buf`, extending the buffer as needed and reporting the result to `observer`.
+///
+/// See [encode_bytes] for more information.
+pub fn encode_bytes_with_observer<B: AsRef<[u8]>>(
+  buf: &mut BytesMut,
+  frame: &GenericFrame<B>,
+  observer: &dyn FrameObserver,
+) -> Result<usize, RedisProtocolError> {
+  let (start, offset) = (observer::Stopwatch::start(), buf.len());
+  let result = encode_bytes(buf, frame);
+  observer::observe_encode(observer, start, observer::resp2_kind_to_resp3(frame.kind()), offset, &result);
+  result
+}
+
+/// Encode a frame into a scratch buffer and check that the number of bytes written matches `expected_len` and the
+/// length computed by [Frame::encode_len].
+///
+/// This is intended for debug builds and tests of codecs that pre-compute frame lengths, since a mismatch corrupts
+/// every frame that follows on the connection.
+pub fn verify_encode<B: AsRef<[u8]>>(frame: &GenericFrame<B>, expected_len: usize) -> Result<(), RedisProtocolError> {
+  let computed = resp2_utils::encode_len(frame)?;
+  let mut buf = BytesMut::with_capacity(computed);
+  let written = encode_bytes(&mut buf, frame)?;
+
+  core::check_encoded_len(&buf, written, computed, expected_len)
+}
+
+/// Encode a command as an array of bulk strings, extending the buffer as needed.
+///
+/// This is equivalent to encoding a `Frame::Array` of `Frame::BulkString` arguments, but avoids allocating the frames.
+///
+/// Returns the number of bytes encoded.
+///
+/// ```
+/// # use redis_protocol::resp2::encode::encode_command;
+/// # use bytes::BytesMut;
+/// let mut buf = BytesMut::new();
+/// encode_command(&mut buf, &["GET", "foo"]);
+/// assert_eq!(buf, "*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n".as_bytes());
+/// ```
+pub fn encode_command<I, A>(buf: &mut BytesMut, args: I) -> usize
+where
+  I: IntoIterator<Item = A>,
+  A: AsRef<[u8]>,
+{
+  utils::encode_command_args(buf, args)
+}
 
-  loop {
-    match attempt_encoding(buf, offset, frame) {
-      Ok(size) => return Ok(size),
-      Err(e) => match e {
-        GenError::BufferTooSmall(amt) => utils::zero_extend(buf, amt),
-        _ => return Err(e.into()),
-      },
+#[cfg(feature = "inline-commands")]
+fn invalid_inline<S: Into<String>>(msg: S) -> RedisProtocolError {
+  RedisProtocolError::new(RedisProtocolErrorKind::EncodeError, msg.into())
+}
+
+/// Write an inline argument, quoting and escaping it if it would not otherwise be read back as the same bytes.
+#[cfg(feature = "inline-commands")]
+fn extend_inline_arg(out: &mut Vec<u8>, arg: &[u8]) {
+  let needs_quotes = arg.is_empty() || arg.iter().any(|b| *b <= b' ' || *b >= 0x7f || *b == b'"' || *b == b'\'');
+  if !needs_quotes {
+    out.extend_from_slice(arg);
+    return;
+  }
+
+  out.push(b'"');
+  for b in arg.iter() {
+    match *b {
+      b'\\' => out.extend_from_slice(b"\\\\"),
+      b'"' => out.extend_from_slice(b"\\\""),
+      b'\n' => out.extend_from_slice(b"\\n"),
+      b'\r' => out.extend_from_slice(b"\\r"),
+      b'\t' => out.extend_from_slice(b"\\t"),
+      0x07 => out.extend_from_slice(b"\\a"),
+      0x08 => out.extend_from_slice(b"\\b"),
+      b' '..=b'~' => out.push(*b),
+      _ => out.extend_from_slice(format!("\\x{:02x}", b).as_bytes()),
     }
   }
+  out.push(b'"');
+}
+
+/// Attempt to encode a command as an inline command, such as `PING\r\n`, extending the buffer as needed.
+///
+/// The frame must be a non-empty array of bulk strings or simple strings. Arguments that contain whitespace, quotes,
+/// or non-printable bytes are quoted and escaped so that they are parsed back as the same bytes. An error is returned
+/// if the frame is not a command or if the encoded command would exceed [INLINE_MAX_SIZE].
+///
+/// Returns the number of bytes encoded.
+#[cfg(feature = "inline-commands")]
+pub fn encode_inline_bytes(buf: &mut BytesMut, frame: &Frame) -> Result<usize, RedisProtocolError> {
+  let args = match *frame {
+    Frame::Array(ref args) if !args.is_empty() => args,
+    Frame::Array(_) => return Err(invalid_inline("Cannot encode an empty inline command.")),
+    _ => return Err(invalid_inline("Expected array.")),
+  };
+
+  let mut out = Vec::new();
+  for (idx, arg) in args.iter().enumerate() {
+    if idx > 0 {
+      out.push(b' ');
+    }
+
+    match *arg {
+      Frame::BulkString(ref b) => extend_inline_arg(&mut out, b),
+      Frame::SimpleString(ref s) => extend_inline_arg(&mut out, s.as_bytes()),
+      _ => return Err(invalid_inline("Expected bulk string arguments.")),
+    }
+  }
+  out.extend_from_slice(CRLF.as_bytes());
+
+  if out.len() > INLINE_MAX_SIZE {
+    return Err(invalid_inline(format!(
+      "Inline command is {} bytes, the maximum is {}.",
+      out.len(),
+      INLINE_MAX_SIZE
+    )));
+  }
+
+  buf.extend_from_slice(&out);
+  Ok(out.len())
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::utils::*;
+  use crate::core::ZEROED_KB;
+  use bytes::Bytes;
+  use std::borrow::Cow;
 
   const PADDING: &'static str = "foobar";
 
@@ -158,6 +278,33 @@ mod tests {
     assert_eq!(len, expected.as_bytes().len(), "empty expected len is correct");
   }
 
+  #[test]
+  fn should_encode_frames_with_any_storage() {
+    let frame = Frame::Array(vec![Frame::BulkString("foo".into()), Frame::Null, Frame::Integer(1)]);
+    let shared: BytesFrame = frame.clone().map_bytes(Bytes::from);
+    let borrowed: BorrowedFrame = frame.clone().map_bytes(Cow::Owned);
+
+    let mut expected = BytesMut::new();
+    encode_bytes(&mut expected, &frame).unwrap();
+    for len in [shared.encode_len().unwrap(), borrowed.encode_len().unwrap()].iter() {
+      assert_eq!(*len, expected.len());
+    }
+
+    let mut buf = BytesMut::new();
+    encode_bytes(&mut buf, &shared).unwrap();
+    encode_bytes(&mut buf, &borrowed).unwrap();
+    assert_eq!(&buf[..expected.len()], &expected[..]);
+    assert_eq!(&buf[expected.len()..], &expected[..]);
+  }
+
+  #[test]
+  fn should_verify_encoded_len() {
+    let input = Frame::Array(vec![Frame::BulkString("foo".into()), Frame::Null, Frame::Integer(-10)]);
+
+    verify_encode(&input, 24).unwrap();
+    assert!(verify_encode(&input, 23).is_err());
+  }
+
   #[test]
   fn should_encode_llen_req_example() {
     let expected = "*2\r\n$4\r\nLLEN\r\n$6\r\nmylist\r\n";
@@ -330,4 +477,60 @@ mod tests {
     encode_and_verify_empty(&i2_input, i2_expected);
     encode_and_verify_non_empty(&i2_input, i2_expected);
   }
+
+  #[test]
+  fn should_encode_command_like_frame() {
+    let args: Vec<&[u8]> = vec![b"SET", b"foo", b"\x00bar"];
+    let frame = Frame::Array(args.iter().map(|a| Frame::BulkString(a.to_vec())).collect());
+
+    let mut expected = BytesMut::new();
+    encode_bytes(&mut expected, &frame).unwrap();
+    let mut buf = BytesMut::new();
+
+    assert_eq!(encode_command(&mut buf, args), expected.len());
+    assert_eq!(buf, expected);
+  }
+
+  #[test]
+  #[cfg(feature = "inline-commands")]
+  fn should_encode_inline_command() {
+    let input = Frame::Array(vec![
+      Frame::BulkString("SET".into()),
+      Frame::SimpleString("foo".into()),
+      Frame::BulkString("bar".into()),
+    ]);
+    let mut buf = BytesMut::new();
+
+    assert_eq!(encode_inline_bytes(&mut buf, &input).unwrap(), 13);
+    assert_eq!(buf, "SET foo bar\r\n".as_bytes());
+  }
+
+  #[test]
+  #[cfg(feature = "inline-commands")]
+  fn should_encode_inline_command_with_quotes() {
+    let args: Vec<&[u8]> = vec![b"SET", b"my key", b"", b"it's \"q\"", b"a\\b", b"\x00\xff\n\t"];
+    let input = Frame::Array(args.iter().map(|a| Frame::BulkString(a.to_vec())).collect());
+    let mut buf = BytesMut::new();
+    encode_inline_bytes(&mut buf, &input).unwrap();
+
+    assert_eq!(
+      buf,
+      "SET \"my key\" \"\" \"it's \\\"q\\\"\" a\\b \"\\x00\\xff\\n\\t\"\r\n".as_bytes()
+    );
+    let (decoded, len) = crate::resp2::decode::decode_inline(&buf).unwrap().unwrap();
+    assert_eq!(decoded, input);
+    assert_eq!(len, buf.len());
+  }
+
+  #[test]
+  #[cfg(feature = "inline-commands")]
+  fn should_error_encoding_invalid_inline_command() {
+    let mut buf = BytesMut::new();
+
+    assert!(encode_inline_bytes(&mut buf, &Frame::Array(vec![])).is_err());
+    assert!(encode_inline_bytes(&mut buf, &Frame::BulkString("PING".into())).is_err());
+    assert!(encode_inline_bytes(&mut buf, &Frame::Array(vec![Frame::Integer(1)])).is_err());
+    assert!(encode_inline_bytes(&mut buf, &Frame::Array(vec![Frame::BulkString(vec![0; INLINE_MAX_SIZE])])).is_err());
+    assert!(buf.is_empty());
+  }
 }
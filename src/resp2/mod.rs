@@ -4,6 +4,8 @@ pub mod decode;
 pub mod encode;
 /// RESP2 frame types.
 pub mod types;
+/// Constructors for standard server replies.
+pub mod replies;
 
 pub(crate) mod utils;
 
@@ -0,0 +1,97 @@
+//! Constructors for the standard RESP2 replies sent by the server, for use in server implementations and mocks.
+//!
+//! Every reply is encoded with the same bytes as the reply sent by Redis.
+//!
+//! ```
+//! # use redis_protocol::resp2::encode::encode_bytes;
+//! # use redis_protocol::resp2::replies;
+//! # use bytes::BytesMut;
+//! let mut buf = BytesMut::new();
+//! encode_bytes(&mut buf, &replies::ok()).unwrap();
+//! encode_bytes(&mut buf, &replies::wrong_type()).unwrap();
+//!
+//! assert_eq!(&buf[..], &b"+OK\r\n-WRONGTYPE Operation against a key holding the wrong kind of value\r\n"[..]);
+//! ```
+
+use crate::resp2::types::Frame;
+use crate::types::{SYNTAX_ERROR, WRONG_TYPE_ERROR};
+
+fn simple_string(data: &str) -> Frame {
+  Frame::SimpleString(data.to_owned())
+}
+
+/// Create an `OK` simple string.
+pub fn ok() -> Frame {
+  simple_string("OK")
+}
+
+/// Create a `QUEUED` simple string, sent in response to commands inside a transaction.
+pub fn queued() -> Frame {
+  simple_string("QUEUED")
+}
+
+/// Create a `PONG` simple string.
+pub fn pong() -> Frame {
+  simple_string("PONG")
+}
+
+/// Create a null frame, which is encoded as a null bulk string.
+pub fn null() -> Frame {
+  Frame::Null
+}
+
+/// Create an empty array.
+pub fn empty_array() -> Frame {
+  Frame::Array(Vec::new())
+}
+
+/// Create an error with the provided code, such as `ERR`, and message.
+pub fn err(code: &str, message: &str) -> Frame {
+  Frame::Error(format!("{} {}", code, message))
+}
+
+/// Create the `WRONGTYPE` error sent when a command is used with a key that holds the wrong kind of value.
+pub fn wrong_type() -> Frame {
+  Frame::Error(WRONG_TYPE_ERROR.to_owned())
+}
+
+/// Create the error sent when a command has invalid arguments.
+pub fn syntax_error() -> Frame {
+  Frame::Error(SYNTAX_ERROR.to_owned())
+}
+
+/// Create the error sent when a command is called with the wrong number of arguments.
+pub fn wrong_number_of_args(command: &str) -> Frame {
+  err(
+    "ERR",
+    &format!("wrong number of arguments for '{}' command", command.to_ascii_lowercase()),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp2::encode::encode_bytes;
+  use bytes::BytesMut;
+
+  fn encode(frame: Frame) -> BytesMut {
+    let mut buf = BytesMut::new();
+    encode_bytes(&mut buf, &frame).unwrap();
+    buf
+  }
+
+  #[test]
+  fn should_encode_replies() {
+    assert_eq!(&encode(ok())[..], b"+OK\r\n");
+    assert_eq!(&encode(queued())[..], b"+QUEUED\r\n");
+    assert_eq!(&encode(pong())[..], b"+PONG\r\n");
+    assert_eq!(&encode(null())[..], b"$-1\r\n");
+    assert_eq!(&encode(empty_array())[..], b"*0\r\n");
+    assert_eq!(&encode(err("NOAUTH", "Authentication required."))[..], b"-NOAUTH Authentication required.\r\n");
+    assert_eq!(&encode(syntax_error())[..], b"-ERR syntax error\r\n");
+    assert_eq!(
+      &encode(wrong_number_of_args("GET"))[..],
+      &b"-ERR wrong number of arguments for 'get' command\r\n"[..]
+    );
+  }
+}
@@ -1,6 +1,15 @@
+use crate::commands::request;
+use crate::convert::FromResp2Frame;
+use crate::core::{ProtocolFrame, ProtocolFrameKind};
+use crate::resp2::decode as resp2_decode;
+use crate::resp2::encode as resp2_encode;
 use crate::resp2::utils as resp2_utils;
-use crate::types::{Redirection, RedisProtocolError};
+use crate::tracking::{self, Invalidation};
+use crate::types::{Redirection, RedisErrorFrame, RedisErrorKind, RedisProtocolError, RedisProtocolErrorKind};
+use bytes::{Bytes, BytesMut};
 use crate::utils;
+use std::borrow::Cow;
+use std::fmt;
 use std::mem;
 use std::str;
 
@@ -19,7 +28,7 @@ pub const ARRAY_BYTE: u8 = b'*';
 pub const NULL: &'static str = "$-1\r\n";
 
 /// An enum representing the kind of a Frame without references to any inner data.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum FrameKind {
   SimpleString,
   Error,
@@ -30,6 +39,19 @@ pub enum FrameKind {
 }
 
 impl FrameKind {
+  /// Every kind of frame.
+  pub const ALL: &[FrameKind] = &[
+    FrameKind::SimpleString,
+    FrameKind::Error,
+    FrameKind::Integer,
+    FrameKind::BulkString,
+    FrameKind::Array,
+    FrameKind::Null,
+  ];
+
+  /// Attempt to detect the type of the frame from the first byte.
+  ///
+  /// Nulls share a byte prefix with bulk strings, so this never returns `Null`.
   pub fn from_byte(d: u8) -> Option<FrameKind> {
     use self::FrameKind::*;
 
@@ -43,6 +65,9 @@ impl FrameKind {
     }
   }
 
+  /// Read the byte prefix for the associated frame type.
+  ///
+  /// Nulls are encoded as bulk strings, so this returns the bulk string prefix for `Null`.
   pub fn to_byte(&self) -> u8 {
     use self::FrameKind::*;
 
@@ -54,11 +79,102 @@ impl FrameKind {
       Array => ARRAY_BYTE,
     }
   }
+
+  /// Whether or not the frame is an aggregate type that contains other frames.
+  pub fn is_aggregate(&self) -> bool {
+    matches!(*self, FrameKind::Array)
+  }
+
+  /// Whether or not the frame is a single value rather than an aggregate type.
+  pub fn is_scalar(&self) -> bool {
+    !self.is_aggregate()
+  }
+
+  /// Read the name of the frame type from the RESP2 specification, such as `Bulk String`.
+  ///
+  /// This is also the `Display` output.
+  pub fn name(&self) -> &'static str {
+    use self::FrameKind::*;
+
+    match *self {
+      SimpleString => "Simple String",
+      Error => "Error",
+      Integer => "Integer",
+      BulkString => "Bulk String",
+      Array => "Array",
+      Null => "Null",
+    }
+  }
+}
+
+impl fmt::Display for FrameKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.name())
+  }
+}
+
+impl ProtocolFrameKind for FrameKind {
+  fn all() -> &'static [FrameKind] {
+    FrameKind::ALL
+  }
+
+  fn from_byte(byte: u8) -> Option<FrameKind> {
+    FrameKind::from_byte(byte)
+  }
+
+  fn try_to_byte(&self) -> Option<u8> {
+    Some(self.to_byte())
+  }
+
+  fn name(&self) -> &'static str {
+    FrameKind::name(self)
+  }
+
+  fn is_aggregate(&self) -> bool {
+    FrameKind::is_aggregate(self)
+  }
 }
 
-/// An enum representing a Frame of data.
+/// The default maximum length of a bulk string argument, matching the default `proto-max-bulk-len` on the server.
+pub const PROTO_MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+/// The maximum number of arguments in a command, matching the limit used by Redis 7 and later.
+pub const PROTO_MAX_MULTIBULK_LEN: usize = i32::MAX as usize;
+
+/// Limits applied when decoding the commands sent by clients, mirroring the limits enforced by the server.
+///
+/// See [decode_request_with_config](crate::resp2::decode::decode_request_with_config) for more information.
+#[cfg(feature = "inline-commands")]
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub enum Frame {
+pub struct RequestConfig {
+  /// The maximum length of each argument, similar to `proto-max-bulk-len`.
+  pub max_bulk_len: usize,
+  /// The maximum number of arguments in a command. Redis versions before 7.0 used `1024 * 1024`.
+  pub max_multibulk_len: usize,
+  /// Whether or not the client must authenticate before sending large commands.
+  ///
+  /// Unauthenticated clients may only send 10 arguments of up to 16 KB each, just like on the server.
+  pub auth_required: bool,
+}
+
+#[cfg(feature = "inline-commands")]
+impl Default for RequestConfig {
+  fn default() -> Self {
+    RequestConfig {
+      max_bulk_len: PROTO_MAX_BULK_LEN,
+      max_multibulk_len: PROTO_MAX_MULTIBULK_LEN,
+      auth_required: false,
+    }
+  }
+}
+
+/// An enum representing a Frame of data, generic over the storage `B` used for the contents of bulk strings.
+///
+/// Most code should use one of the aliases. [Frame] owns its bulk strings, [BytesFrame] shares slices of a reference
+/// counted buffer, and [BorrowedFrame] borrows from the buffer it was decoded from. Frames with any storage that
+/// implements `AsRef<[u8]>` can be encoded, so other representations, such as inline stack buffers, can be created
+/// with [map_bytes](Self::map_bytes).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GenericFrame<B> {
   /// A short non binary-safe string.
   SimpleString(String),
   /// A short non binary-safe string representing an error.
@@ -66,17 +182,40 @@ pub enum Frame {
   /// A signed 64 bit integer.
   Integer(i64),
   /// A binary-safe string.
-  BulkString(Vec<u8>),
+  BulkString(B),
   /// An array of frames, arbitrarily nested.
-  Array(Vec<Frame>),
+  Array(Vec<GenericFrame<B>>),
   /// A null value.
   Null,
 }
 
-impl Frame {
+/// A frame that owns the contents of its bulk strings.
+pub type Frame = GenericFrame<Vec<u8>>;
+
+/// A frame that stores bulk strings as `Bytes`, such as frames decoded without copying by
+/// [decode_bytes](crate::resp2::decode::decode_bytes).
+pub type BytesFrame = GenericFrame<Bytes>;
+
+/// A frame that borrows or owns the contents of its bulk strings, such as frames decoded without copying by
+/// [decode_borrowed](crate::resp2::decode::decode_borrowed).
+pub type BorrowedFrame<'a> = GenericFrame<Cow<'a, [u8]>>;
+
+impl<B> GenericFrame<B> {
   /// Replace `self` with Null, returning the original value.
-  pub fn take(&mut self) -> Frame {
-    mem::replace(self, Frame::Null)
+  pub fn take(&mut self) -> Self {
+    mem::replace(self, GenericFrame::Null)
+  }
+
+  /// Read the `FrameKind` value for this frame.
+  pub fn kind(&self) -> FrameKind {
+    match *self {
+      GenericFrame::SimpleString(_) => FrameKind::SimpleString,
+      GenericFrame::Error(_) => FrameKind::Error,
+      GenericFrame::Integer(_) => FrameKind::Integer,
+      GenericFrame::BulkString(_) => FrameKind::BulkString,
+      GenericFrame::Array(_) => FrameKind::Array,
+      GenericFrame::Null => FrameKind::Null,
+    }
   }
 
   /// Whether or not the frame is an error.
@@ -87,83 +226,166 @@ impl Frame {
     }
   }
 
-  /// Whether or not the frame represents a publish-subscribe message, but not a pattern publish-subscribe message.
-  pub fn is_normal_pubsub(&self) -> bool {
-    if let Frame::Array(ref frames) = *self {
-      resp2_utils::is_normal_pubsub(frames)
-    } else {
-      false
+  /// Whether or not the frame is a simple string or bulk string.
+  pub fn is_string(&self) -> bool {
+    match *self {
+      GenericFrame::SimpleString(_) | GenericFrame::BulkString(_) => true,
+      _ => false,
     }
   }
 
-  /// Whether or not the frame represents a message on a publish-subscribe channel.
-  pub fn is_pubsub_message(&self) -> bool {
-    if let Frame::Array(ref frames) = *self {
-      resp2_utils::is_normal_pubsub(frames) || resp2_utils::is_pattern_pubsub(frames)
-    } else {
-      false
+  /// Whether or not the frame is Null.
+  pub fn is_null(&self) -> bool {
+    match *self {
+      GenericFrame::Null => true,
+      _ => false,
     }
   }
 
-  /// Whether or not the frame represents a message on a publish-subscribe channel matched against a pattern subscription.
-  pub fn is_pattern_pubsub_message(&self) -> bool {
-    if let Frame::Array(ref frames) = *self {
-      resp2_utils::is_pattern_pubsub(frames)
-    } else {
-      false
+  /// Whether or not the frame is a nil reply, such as the reply to `GET` for a missing key.
+  ///
+  /// This is the same as [is_null](Self::is_null), for parity with the RESP3 frame.
+  pub fn is_nil(&self) -> bool {
+    self.is_null()
+  }
+
+  /// Whether or not the frame is an array of frames.
+  pub fn is_array(&self) -> bool {
+    match *self {
+      GenericFrame::Array(_) => true,
+      _ => false,
     }
   }
 
-  /// Read the `FrameKind` value for this frame.
-  pub fn kind(&self) -> FrameKind {
+  /// Whether or not the frame is an integer.
+  pub fn is_integer(&self) -> bool {
     match *self {
-      Frame::SimpleString(_) => FrameKind::SimpleString,
-      Frame::Error(_) => FrameKind::Error,
-      Frame::Integer(_) => FrameKind::Integer,
-      Frame::BulkString(_) => FrameKind::BulkString,
-      Frame::Array(_) => FrameKind::Array,
-      Frame::Null => FrameKind::Null,
+      GenericFrame::Integer(_) => true,
+      _ => false,
+    }
+  }
+
+  /// Convert the storage used for bulk strings, including bulk strings inside arrays.
+  ///
+  /// ```
+  /// # use redis_protocol::resp2::types::{BytesFrame, Frame};
+  /// # use bytes::Bytes;
+  /// let frame = Frame::Array(vec![Frame::BulkString("foo".into()), Frame::Integer(1)]);
+  /// let shared: BytesFrame = frame.clone().map_bytes(Bytes::from);
+  /// assert_eq!(shared.into_owned(), frame);
+  /// ```
+  pub fn map_bytes<C, F>(self, mut func: F) -> GenericFrame<C>
+  where
+    F: FnMut(B) -> C,
+  {
+    self.map_bytes_inner(&mut func)
+  }
+
+  fn map_bytes_inner<C, F>(self, func: &mut F) -> GenericFrame<C>
+  where
+    F: FnMut(B) -> C,
+  {
+    match self {
+      GenericFrame::SimpleString(s) => GenericFrame::SimpleString(s),
+      GenericFrame::Error(s) => GenericFrame::Error(s),
+      GenericFrame::Integer(i) => GenericFrame::Integer(i),
+      GenericFrame::BulkString(b) => GenericFrame::BulkString(func(b)),
+      GenericFrame::Array(frames) => GenericFrame::Array(frames.into_iter().map(|f| f.map_bytes_inner(func)).collect()),
+      GenericFrame::Null => GenericFrame::Null,
     }
   }
+}
 
+impl<B: AsRef<[u8]>> GenericFrame<B> {
   /// Attempt to read the frame value as a string slice without allocating.
   pub fn as_str(&self) -> Option<&str> {
     match *self {
-      Frame::BulkString(ref b) => str::from_utf8(b).ok(),
-      Frame::SimpleString(ref s) => Some(s),
-      Frame::Error(ref s) => Some(s),
+      GenericFrame::BulkString(ref b) => str::from_utf8(b.as_ref()).ok(),
+      GenericFrame::SimpleString(ref s) => Some(s),
+      GenericFrame::Error(ref s) => Some(s),
       _ => None,
     }
   }
 
-  /// Whether or not the frame is a simple string or bulk string.
-  pub fn is_string(&self) -> bool {
+  /// Attempt to read the number of bytes needed to encode this frame.
+  pub fn encode_len(&self) -> Result<usize, RedisProtocolError> {
+    resp2_utils::encode_len(self).map_err(|e| e.into())
+  }
+
+  /// Copy the frame into a [Frame] that owns its bulk strings.
+  pub fn to_owned_frame(&self) -> Frame {
     match *self {
-      Frame::SimpleString(_) | Frame::BulkString(_) => true,
-      _ => false,
+      GenericFrame::SimpleString(ref s) => Frame::SimpleString(s.clone()),
+      GenericFrame::Error(ref s) => Frame::Error(s.clone()),
+      GenericFrame::Integer(i) => Frame::Integer(i),
+      GenericFrame::BulkString(ref b) => Frame::BulkString(b.as_ref().to_vec()),
+      GenericFrame::Array(ref frames) => Frame::Array(frames.iter().map(|f| f.to_owned_frame()).collect()),
+      GenericFrame::Null => Frame::Null,
     }
   }
 
-  /// Whether or not the frame is Null.
-  pub fn is_null(&self) -> bool {
-    match *self {
-      Frame::Null => true,
-      _ => false,
+  /// Convert the frame into a [Frame] that owns its bulk strings, copying the contents of each bulk string.
+  pub fn into_owned(self) -> Frame {
+    self.map_bytes(|b| b.as_ref().to_vec())
+  }
+}
+
+impl<B: AsRef<[u8]>> ProtocolFrame for GenericFrame<B> {
+  type Kind = FrameKind;
+
+  fn kind(&self) -> FrameKind {
+    GenericFrame::kind(self)
+  }
+
+  fn encode_len(&self) -> Result<usize, RedisProtocolError> {
+    GenericFrame::encode_len(self)
+  }
+
+  fn is_error(&self) -> bool {
+    GenericFrame::is_error(self)
+  }
+}
+
+impl Frame {
+  /// Whether or not the frame represents a publish-subscribe message, but not a pattern publish-subscribe message.
+  pub fn is_normal_pubsub(&self) -> bool {
+    if let Frame::Array(ref frames) = *self {
+      resp2_utils::is_normal_pubsub(frames)
+    } else {
+      false
     }
   }
 
-  /// Whether or not the frame is an array of frames.
-  pub fn is_array(&self) -> bool {
+  /// Whether or not the frame represents a message on a publish-subscribe channel.
+  pub fn is_pubsub_message(&self) -> bool {
+    if let Frame::Array(ref frames) = *self {
+      resp2_utils::is_normal_pubsub(frames) || resp2_utils::is_pattern_pubsub(frames)
+    } else {
+      false
+    }
+  }
+
+  /// Whether or not the frame represents a message on a publish-subscribe channel matched against a pattern subscription.
+  pub fn is_pattern_pubsub_message(&self) -> bool {
+    if let Frame::Array(ref frames) = *self {
+      resp2_utils::is_pattern_pubsub(frames)
+    } else {
+      false
+    }
+  }
+
+  /// Whether or not the frame is the `OK` status reply.
+  pub fn is_ok(&self) -> bool {
     match *self {
-      Frame::Array(_) => true,
+      Frame::SimpleString(ref data) => data == "OK",
       _ => false,
     }
   }
 
-  /// Whether or not the frame is an integer.
-  pub fn is_integer(&self) -> bool {
+  /// Whether or not the frame is the `QUEUED` status reply sent for commands inside a transaction.
+  pub fn is_queued(&self) -> bool {
     match *self {
-      Frame::Integer(_) => true,
+      Frame::SimpleString(ref data) => data == "QUEUED",
       _ => false,
     }
   }
@@ -185,6 +407,28 @@ impl Frame {
     }
   }
 
+  /// Read a scalar frame as a string using the same coercions as Redis, borrowing the frame's data when possible.
+  ///
+  /// Integers are written as their decimal text. Arrays, nulls, and bulk strings that are not valid UTF-8 return
+  /// `None`.
+  pub fn coerce_str(&self) -> Option<Cow<'_, str>> {
+    match *self {
+      Frame::Integer(ref i) => Some(Cow::Owned(i.to_string())),
+      _ => self.as_str().map(Cow::Borrowed),
+    }
+  }
+
+  /// Read a scalar frame as a string with the same coercions as [coerce_str](Self::coerce_str), replacing any invalid
+  /// UTF-8 sequences with `U+FFFD REPLACEMENT CHARACTER`.
+  ///
+  /// Arrays and nulls return `None`.
+  pub fn to_string_lossy(&self) -> Option<String> {
+    match *self {
+      Frame::BulkString(ref b) => Some(String::from_utf8_lossy(b).into_owned()),
+      _ => self.coerce_str().map(|s| s.into_owned()),
+    }
+  }
+
   /// Attempt to parse the frame as a publish-subscribe message, returning the `(channel, message)` tuple
   /// if successful, or the original frame if the inner data is not a publish-subscribe message.
   pub fn parse_as_pubsub(self) -> Result<(String, String), Self> {
@@ -205,17 +449,170 @@ impl Frame {
     }
   }
 
+  /// Whether or not the frame represents a client-side caching invalidation message sent to a connection in `REDIRECT` mode.
+  pub fn is_invalidation(&self) -> bool {
+    if let Frame::Array(ref frames) = *self {
+      tracking::is_resp2_invalidation(frames)
+    } else {
+      false
+    }
+  }
+
+  /// Attempt to parse the frame as a client-side caching invalidation message sent to a connection in `REDIRECT` mode.
+  pub fn to_invalidation(&self) -> Option<Invalidation> {
+    if let Frame::Array(ref frames) = *self {
+      tracking::parse_resp2_invalidation(frames)
+    } else {
+      None
+    }
+  }
+
   /// Attempt to parse the frame as a cluster redirection.
   pub fn to_redirection(&self) -> Option<Redirection> {
+    Redirection::from_resp2_frame(self)
+  }
+
+  /// Attempt to read the kind of error returned by the server.
+  pub fn to_error_kind(&self) -> Option<RedisErrorKind> {
+    RedisErrorKind::from_resp2_frame(self)
+  }
+
+  /// Read the leading error code, such as `WRONGTYPE`, if the frame is an error.
+  pub fn error_code(&self) -> Option<&str> {
     match *self {
-      Frame::Error(ref s) => utils::read_cluster_error(s),
+      Frame::Error(ref s) => Some(RedisErrorKind::split(s).0),
       _ => None,
     }
   }
 
-  /// Attempt to read the number of bytes needed to encode this frame.
-  pub fn encode_len(&self) -> Result<usize, RedisProtocolError> {
-    resp2_utils::encode_len(self).map_err(|e| e.into())
+  /// Read the error message without the leading error code if the frame is an error.
+  pub fn error_message(&self) -> Option<&str> {
+    match *self {
+      Frame::Error(ref s) => Some(RedisErrorKind::split(s).1),
+      _ => None,
+    }
+  }
+
+  /// Convert the elements of an array frame to `T`.
+  ///
+  /// The returned error includes the index of the first element that cannot be converted.
+  pub fn into_vec_of<T: FromResp2Frame>(self) -> Result<Vec<T>, RedisProtocolError> {
+    Vec::<T>::from_resp2_frame(self)
+  }
+
+  /// Convert an error frame into the `Err` arm, or return any other frame in the `Ok` arm.
+  ///
+  /// ```
+  /// # use redis_protocol::resp2::types::Frame;
+  /// # use redis_protocol::types::RedisErrorKind;
+  /// let error = Frame::Error("WRONGTYPE Operation against a key".into()).into_result().unwrap_err();
+  /// assert_eq!(error.kind, RedisErrorKind::WrongType);
+  /// assert_eq!(error.message, "Operation against a key");
+  /// ```
+  pub fn into_result(self) -> Result<Frame, RedisErrorFrame> {
+    match self {
+      Frame::Error(ref s) => Err(RedisErrorFrame::new(s, None)),
+      _ => Ok(self),
+    }
+  }
+
+  /// Compute a stable digest of the frame's contents, for use in reply deduplication or as a cache key.
+  ///
+  /// The digest is the [crc64_jones](crate::crc64_jones) checksum of the encoded frame.
+  pub fn content_hash(&self) -> Result<u64, RedisProtocolError> {
+    let mut buf = BytesMut::new();
+    resp2_encode::encode_bytes(&mut buf, self)?;
+    Ok(utils::crc64_jones(&buf))
+  }
+
+  /// Read the argument at `idx` as a string if the frame is a command sent by a client.
+  fn command_arg(&self, idx: usize) -> Option<&str> {
+    match *self {
+      Frame::Array(ref args) => args.get(idx).filter(|f| f.is_string()).and_then(|f| f.as_str()),
+      _ => None,
+    }
+  }
+
+  /// Read the uppercase command name if the frame is a command sent by a client, such as `GET` or `CONFIG`.
+  pub fn command_name(&self) -> Option<String> {
+    self.command_arg(0).map(|name| name.to_ascii_uppercase())
+  }
+
+  /// Read the uppercase subcommand name if the frame is a command sent by a client, such as `GET` in `CONFIG GET`.
+  ///
+  /// Returns `None` if the command does not have subcommands.
+  pub fn subcommand_name(&self) -> Option<String> {
+    match self.command_arg(0) {
+      Some(name) if request::is_container_command(name) => self.command_arg(1).map(|s| s.to_ascii_uppercase()),
+      _ => None,
+    }
+  }
+
+  /// Encode the frame and render the bytes as an escaped string, such as `*1\r\n$3\r\nfoo\r\n`.
+  ///
+  /// See [escape_wire_string](crate::escape_wire_string) for the escaping rules.
+  pub fn to_wire_string(&self) -> Result<String, RedisProtocolError> {
+    let mut buf = BytesMut::new();
+    resp2_encode::encode_bytes(&mut buf, self)?;
+    Ok(utils::escape_wire_string(&buf))
+  }
+
+  /// Parse a frame from the escaped notation produced by [to_wire_string](Self::to_wire_string).
+  ///
+  /// An error is returned if the string does not contain exactly one complete frame.
+  pub fn from_wire_string(s: &str) -> Result<Self, RedisProtocolError> {
+    let data = utils::unescape_wire_string(s)?;
+
+    match resp2_decode::decode(&data)? {
+      Some((frame, len)) if len == data.len() => Ok(frame),
+      Some(_) => Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::DecodeError,
+        "Unexpected bytes after frame.",
+      )),
+      None => Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::DecodeError,
+        "Incomplete frame.",
+      )),
+    }
+  }
+
+  /// Wrap the frame in a `Debug` implementation that truncates any payloads longer than `max_len` bytes.
+  ///
+  /// This can be used to log frames that may contain large values.
+  pub fn truncated(&self, max_len: usize) -> TruncatedFrame<'_> {
+    TruncatedFrame { frame: self, max_len }
+  }
+}
+
+/// A wrapper that implements `Debug` for a frame, truncating any payloads longer than `max_len` bytes.
+///
+/// Truncated payloads are followed by their full length, and inner frames are truncated recursively.
+#[derive(Clone, Copy)]
+pub struct TruncatedFrame<'a> {
+  frame: &'a Frame,
+  max_len: usize,
+}
+
+impl<'a> fmt::Debug for TruncatedFrame<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let max_len = self.max_len;
+
+    match *self.frame {
+      Frame::SimpleString(ref data) => f
+        .debug_tuple("SimpleString")
+        .field(&utils::TruncatedStr { data, max_len })
+        .finish(),
+      Frame::Error(ref data) => f.debug_tuple("Error").field(&utils::TruncatedStr { data, max_len }).finish(),
+      Frame::BulkString(ref data) => f
+        .debug_tuple("BulkString")
+        .field(&utils::TruncatedBytes { data, max_len })
+        .finish(),
+      Frame::Array(ref data) => f
+        .debug_tuple("Array")
+        .field(&data.iter().map(|frame| frame.truncated(max_len)).collect::<Vec<_>>())
+        .finish(),
+      Frame::Integer(_) | Frame::Null => fmt::Debug::fmt(self.frame, f),
+    }
   }
 }
 
@@ -235,6 +632,54 @@ impl<'a> From<&'a Redirection> for Frame {
 mod tests {
   use super::*;
 
+  #[test]
+  fn should_convert_frames_to_and_from_wire_strings() {
+    let frame = Frame::Array(vec![Frame::BulkString(b"foo\r\n".to_vec()), Frame::Null]);
+    let wire = "*2\\r\\n$5\\r\\nfoo\\r\\n\\r\\n$-1\\r\\n";
+
+    assert_eq!(frame.to_wire_string().unwrap(), wire);
+    assert_eq!(Frame::from_wire_string(wire).unwrap(), frame);
+    assert!(Frame::from_wire_string("*2\\r\\n$-1\\r\\n").is_err());
+    assert!(Frame::from_wire_string(":1\\r\\n:2\\r\\n").is_err());
+  }
+
+  #[test]
+  fn should_truncate_debug_output() {
+    let frame = Frame::Array(vec![
+      Frame::BulkString(b"foo\r\nbar".to_vec()),
+      Frame::SimpleString("foo".into()),
+      Frame::Null,
+    ]);
+
+    assert_eq!(
+      format!("{:?}", frame.truncated(4)),
+      "Array([BulkString(b\"foo\\r\"... (8 bytes)), SimpleString(\"foo\"), Null])"
+    );
+  }
+
+  #[test]
+  fn should_map_every_frame_kind_to_and_from_bytes() {
+    for kind in FrameKind::ALL.iter() {
+      let expected = if *kind == FrameKind::Null {
+        FrameKind::BulkString
+      } else {
+        *kind
+      };
+      assert_eq!(FrameKind::from_byte(kind.to_byte()), Some(expected));
+      assert_ne!(kind.is_aggregate(), kind.is_scalar());
+    }
+
+    assert_eq!((0..=u8::MAX).filter_map(FrameKind::from_byte).count(), 5);
+    assert!(FrameKind::Array.is_aggregate());
+  }
+
+  #[test]
+  fn should_display_frame_kinds_with_spec_names() {
+    assert_eq!(FrameKind::BulkString.to_string(), "Bulk String");
+    assert_eq!(Frame::Integer(1).kind().to_string(), "Integer");
+    assert_eq!(Frame::Null.kind().name(), "Null");
+  }
+
   #[test]
   fn should_convert_ask_redirection_to_frame() {
     let redirection = Redirection::Ask {
@@ -404,6 +849,18 @@ mod tests {
     assert!(!f.is_moved_or_ask_error());
   }
 
+  #[test]
+  fn should_check_status_replies() {
+    assert!(Frame::SimpleString("OK".into()).is_ok());
+    assert!(!Frame::SimpleString("OK".into()).is_queued());
+    assert!(!Frame::BulkString(b"OK".to_vec()).is_ok());
+    assert!(Frame::SimpleString("QUEUED".into()).is_queued());
+    assert!(!Frame::Error("OK".into()).is_ok());
+    assert!(Frame::Null.is_nil());
+    assert!(!Frame::Array(vec![]).is_nil());
+    assert_eq!(Frame::SimpleString("OK".into()).kind(), FrameKind::SimpleString);
+  }
+
   #[test]
   fn should_decode_frame_kind_byte() {
     assert_eq!(FrameKind::from_byte(SIMPLESTRING_BYTE), Some(FrameKind::SimpleString));
@@ -421,4 +878,64 @@ mod tests {
     assert_eq!(FrameKind::Integer.to_byte(), INTEGER_BYTE);
     assert_eq!(FrameKind::Array.to_byte(), ARRAY_BYTE);
   }
+
+  #[test]
+  fn should_split_error_code_and_message() {
+    let frame = Frame::Error("WRONGTYPE Operation against a key holding the wrong kind of value".into());
+    assert_eq!(frame.error_code(), Some("WRONGTYPE"));
+    assert_eq!(frame.error_message(), Some("Operation against a key holding the wrong kind of value"));
+    assert_eq!(frame.to_error_kind(), Some(RedisErrorKind::WrongType));
+
+    let frame = Frame::Error("ERR".into());
+    assert_eq!(frame.error_code(), Some("ERR"));
+    assert_eq!(frame.error_message(), Some(""));
+
+    let frame = Frame::SimpleString("OK".into());
+    assert_eq!(frame.error_code(), None);
+    assert_eq!(frame.error_message(), None);
+  }
+
+  #[test]
+  fn should_convert_error_frames_into_results() {
+    let frame = Frame::BulkString(b"foo".to_vec());
+    assert_eq!(frame.clone().into_result(), Ok(frame));
+
+    let error = Frame::Error("ERR unknown command 'FOO'".into()).into_result().unwrap_err();
+    assert_eq!(error.kind, RedisErrorKind::Err);
+    assert_eq!(error.code(), "ERR");
+    assert_eq!(error.message, "unknown command 'FOO'");
+    assert_eq!(error.attributes, None);
+    assert_eq!(error.to_string(), "ERR unknown command 'FOO'");
+  }
+
+  #[test]
+  fn should_coerce_scalar_frames_to_strings() {
+    assert_eq!(Frame::BulkString(b"foo".to_vec()).coerce_str(), Some(Cow::Borrowed("foo")));
+    assert_eq!(Frame::BulkString(b"\xff".to_vec()).coerce_str(), None);
+    assert_eq!(Frame::Integer(-12).coerce_str().unwrap(), "-12");
+    assert_eq!(Frame::Error("ERR".into()).coerce_str().unwrap(), "ERR");
+    assert_eq!(Frame::Null.coerce_str(), None);
+
+    assert_eq!(Frame::BulkString(b"a\xffb".to_vec()).to_string_lossy().unwrap(), "a\u{fffd}b");
+    assert_eq!(Frame::Integer(1).to_string_lossy().unwrap(), "1");
+    assert_eq!(Frame::Array(vec![]).to_string_lossy(), None);
+  }
+
+  #[test]
+  fn should_convert_array_frames_into_vecs() {
+    let frame = Frame::Array(vec![Frame::BulkString(b"1".to_vec()), Frame::Integer(2)]);
+    assert_eq!(frame.into_vec_of::<i64>().unwrap(), vec![1, 2]);
+
+    let frame = Frame::Array(vec![Frame::Integer(1), Frame::Null]);
+    let error = frame.into_vec_of::<i64>().unwrap_err();
+    assert_eq!(error.description(), "Element 1: Cannot convert Null to i64.");
+  }
+
+  #[test]
+  fn should_hash_frame_contents() {
+    let frame = Frame::Array(vec![Frame::BulkString(b"foo".to_vec()), Frame::Null]);
+
+    assert_eq!(frame.content_hash().unwrap(), crate::crc64_jones(b"*2\r\n$3\r\nfoo\r\n$-1\r\n"));
+    assert_ne!(frame.content_hash().unwrap(), Frame::Null.content_hash().unwrap());
+  }
 }
@@ -1,13 +1,14 @@
-use crate::resp2::types::{Frame, FrameKind, NULL};
-use crate::utils::{digits_in_number, PATTERN_PUBSUB_PREFIX, PUBSUB_PREFIX};
+use crate::core;
+use crate::resp2::types::{Frame, FrameKind, GenericFrame, NULL};
+use crate::utils::{PATTERN_PUBSUB_PREFIX, PUBSUB_PREFIX};
 use cookie_factory::GenError;
 
 pub fn bulkstring_encode_len(b: &[u8]) -> usize {
-  1 + digits_in_number(b.len()) + 2 + b.len() + 2
+  core::blob_encode_len(b.len())
 }
 
-pub fn array_encode_len(frames: &Vec<Frame>) -> Result<usize, GenError> {
-  let padding = 1 + digits_in_number(frames.len()) + 2;
+pub fn array_encode_len<B: AsRef<[u8]>>(frames: &[GenericFrame<B>]) -> Result<usize, GenError> {
+  let padding = core::length_prefix_encode_len(frames.len());
 
   frames
     .iter()
@@ -15,18 +16,15 @@ pub fn array_encode_len(frames: &Vec<Frame>) -> Result<usize, GenError> {
 }
 
 pub fn simplestring_encode_len(s: &str) -> usize {
-  1 + s.len() + 2
+  core::line_encode_len(s.len())
 }
 
 pub fn error_encode_len(s: &str) -> usize {
-  1 + s.len() + 2
+  core::line_encode_len(s.len())
 }
 
 pub fn integer_encode_len(i: &i64) -> usize {
-  let prefix = if *i < 0 { 1 } else { 0 };
-  let as_usize = if *i < 0 { (*i * -1) as usize } else { *i as usize };
-
-  1 + digits_in_number(as_usize) + 2 + prefix
+  core::integer_encode_len(*i)
 }
 
 pub fn opt_frame_to_string_panic(f: Option<Frame>, msg: &str) -> String {
@@ -46,14 +44,14 @@ pub fn is_pattern_pubsub(frames: &Vec<Frame>) -> bool {
 }
 
 /// Returns the number of bytes necessary to represent the frame.
-pub fn encode_len(data: &Frame) -> Result<usize, GenError> {
+pub fn encode_len<B: AsRef<[u8]>>(data: &GenericFrame<B>) -> Result<usize, GenError> {
   match *data {
-    Frame::BulkString(ref b) => Ok(bulkstring_encode_len(&b)),
-    Frame::Array(ref frames) => array_encode_len(frames),
-    Frame::Null => Ok(NULL.as_bytes().len()),
-    Frame::SimpleString(ref s) => Ok(simplestring_encode_len(s)),
-    Frame::Error(ref s) => Ok(error_encode_len(s)),
-    Frame::Integer(ref i) => Ok(integer_encode_len(i)),
+    GenericFrame::BulkString(ref b) => Ok(bulkstring_encode_len(b.as_ref())),
+    GenericFrame::Array(ref frames) => array_encode_len(frames),
+    GenericFrame::Null => Ok(NULL.len()),
+    GenericFrame::SimpleString(ref s) => Ok(simplestring_encode_len(s)),
+    GenericFrame::Error(ref s) => Ok(error_encode_len(s)),
+    GenericFrame::Integer(ref i) => Ok(integer_encode_len(i)),
   }
 }
 
@@ -2,21 +2,25 @@
 //!
 //! <https://github.com/antirez/RESP3/blob/master/spec.md>
 
+use crate::core;
+use crate::observer::{self, FrameObserver};
 use crate::resp3::types::*;
 use crate::resp3::utils as resp3_utils;
 use crate::types::*;
 use nom::bytes::streaming::{take as nom_take, take_until as nom_take_until};
 use nom::combinator::{map as nom_map, map_res as nom_map_res, opt as nom_opt};
-use nom::multi::count as nom_count;
 use nom::number::streaming::be_u8;
 use nom::sequence::terminated as nom_terminated;
 use nom::{Err as NomErr, IResult};
+use bytes::BytesMut;
 use std::borrow::Cow;
 use std::str;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 macro_rules! e (
   ($err:expr) => {
-    return Err($err.into_nom_error());
+    return Err($err.into_nom_error())
   }
 );
 
@@ -39,6 +43,84 @@ fn unwrap_complete_frame<'a>(frame: DecodedFrame) -> Result<Frame, RedisParseErr
     .map_err(|e| RedisParseError::new_custom("unwrap_complete_frame", format!("{:?}", e)))
 }
 
+fn alloc_bytes<'a>(config: &DecodeConfig, data: &[u8]) -> Result<Vec<u8>, RedisParseError<&'a [u8]>> {
+  match config.allocator {
+    Some(ref allocator) => allocator
+      .alloc_bytes(data)
+      .map_err(|e| RedisParseError::new_custom("alloc_bytes", e.description().to_owned())),
+    None => Ok(data.to_vec()),
+  }
+}
+
+fn alloc_frames<'a>(config: &DecodeConfig, len: usize) -> Result<Vec<Frame>, RedisParseError<&'a [u8]>> {
+  match config.allocator {
+    Some(ref allocator) => allocator
+      .alloc_frames(len)
+      .map_err(|e| RedisParseError::new_custom("alloc_frames", e.description().to_owned())),
+    None => Ok(Vec::with_capacity(len)),
+  }
+}
+
+/// An allocator that tracks the allocations made while decoding one frame so they can be released if the frame is
+/// incomplete or invalid.
+struct TrackedAllocator {
+  inner: Arc<dyn FrameAllocator>,
+  bytes: AtomicUsize,
+  frames: AtomicUsize,
+}
+
+impl TrackedAllocator {
+  fn release(&self) {
+    self.inner.release_bytes(self.bytes.swap(0, Ordering::AcqRel));
+    self.inner.release_frames(self.frames.swap(0, Ordering::AcqRel));
+  }
+}
+
+impl FrameAllocator for TrackedAllocator {
+  fn alloc_bytes(&self, data: &[u8]) -> Result<Vec<u8>, RedisProtocolError> {
+    let out = self.inner.alloc_bytes(data)?;
+    self.bytes.fetch_add(data.len(), Ordering::AcqRel);
+    Ok(out)
+  }
+
+  fn alloc_frames(&self, len: usize) -> Result<Vec<Frame>, RedisProtocolError> {
+    let out = self.inner.alloc_frames(len)?;
+    self.frames.fetch_add(len, Ordering::AcqRel);
+    Ok(out)
+  }
+}
+
+/// Call `parse` with `buf`, releasing the allocations made with the configured allocator if parsing does not return
+/// a frame.
+fn parse_or_release<'a, T, F>(
+  buf: &'a [u8],
+  config: &DecodeConfig,
+  parse: F,
+) -> IResult<&'a [u8], T, RedisParseError<&'a [u8]>>
+where
+  F: FnOnce(&'a [u8], &DecodeConfig) -> IResult<&'a [u8], T, RedisParseError<&'a [u8]>>,
+{
+  let inner = match config.allocator {
+    Some(ref allocator) => allocator.clone(),
+    None => return parse(buf, config),
+  };
+  let tracked = Arc::new(TrackedAllocator {
+    inner,
+    bytes: AtomicUsize::new(0),
+    frames: AtomicUsize::new(0),
+  });
+  let config = DecodeConfig {
+    allocator: Some(tracked.clone()),
+    ..config.clone()
+  };
+
+  let result = parse(buf, &config);
+  if result.is_err() {
+    tracked.release();
+  }
+  result
+}
+
 fn to_usize(s: &str) -> Result<usize, RedisParseError<&[u8]>> {
   s.parse::<usize>()
     .map_err(|e| RedisParseError::new_custom("to_usize", format!("{:?}", e)))
@@ -67,8 +149,14 @@ fn to_i64(s: &str) -> Result<i64, RedisParseError<&[u8]>> {
 }
 
 fn to_f64(s: &str) -> Result<f64, RedisParseError<&[u8]>> {
-  s.parse::<f64>()
-    .map_err(|e| RedisParseError::new_custom("to_f64", format!("{:?}", e)))
+  match s {
+    INFINITY => Ok(f64::INFINITY),
+    NEG_INFINITY => Ok(f64::NEG_INFINITY),
+    NAN => Ok(f64::NAN),
+    _ => s
+      .parse::<f64>()
+      .map_err(|e| RedisParseError::new_custom("to_f64", format!("{:?}", e))),
+  }
 }
 
 fn to_bool(s: &str) -> Result<bool, RedisParseError<&[u8]>> {
@@ -90,17 +178,17 @@ fn to_verbatimstring_format(s: &str) -> Result<VerbatimStringFormat, RedisParseE
   }
 }
 
-fn to_map<'a>(mut data: Vec<Frame>) -> Result<FrameMap, RedisParseError<&'a [u8]>> {
+fn to_map<'a>(data: Vec<Frame>, config: &DecodeConfig) -> Result<FrameMap, RedisParseError<&'a [u8]>> {
   if data.len() % 2 != 0 {
     return Err(RedisParseError::new_custom("to_map", "Invalid hashmap frame length."));
   }
 
   let mut out = resp3_utils::new_map(Some(data.len() / 2));
-  while data.len() >= 2 {
-    let value = data.pop().unwrap();
-    let key = data.pop().unwrap();
-
-    out.insert(key, value);
+  let mut data = data.into_iter();
+  while let (Some(key), Some(value)) = (data.next(), data.next()) {
+    if let Err(e) = resp3_utils::insert_map_pair(&mut out, key, value, config.duplicate_keys) {
+      return Err(RedisParseError::new_custom("to_map", format!("{:?}", e)));
+    }
   }
 
   Ok(out)
@@ -148,11 +236,15 @@ fn attach_attributes<'a>(
 }
 
 fn d_read_to_crlf(input: &[u8]) -> IResult<&[u8], &[u8], RedisParseError<&[u8]>> {
-  nom_terminated(nom_take_until(CRLF), nom_take(2_usize))(input)
+  core::read_to_crlf(input)
 }
 
 fn d_read_to_crlf_s(input: &[u8]) -> IResult<&[u8], &str, RedisParseError<&[u8]>> {
-  nom_map_res(d_read_to_crlf, str::from_utf8)(input)
+  core::read_to_crlf_str(input)
+}
+
+fn d_read_blob(input: &[u8], len: usize) -> IResult<&[u8], &[u8], RedisParseError<&[u8]>> {
+  core::read_blob(input, len)
 }
 
 fn d_read_prefix_len(input: &[u8]) -> IResult<&[u8], usize, RedisParseError<&[u8]>> {
@@ -164,34 +256,40 @@ fn d_read_prefix_len_signed(input: &[u8]) -> IResult<&[u8], isize, RedisParseErr
 }
 
 fn d_frame_type(input: &[u8]) -> IResult<&[u8], FrameKind, RedisParseError<&[u8]>> {
-  let (input, byte) = be_u8(input)?;
+  let (remaining, byte) = be_u8(input)?;
   let kind = match FrameKind::from_byte(byte) {
     Some(k) => k,
-    None => e!(RedisParseError::new_custom("frame_type", "Invalid frame type prefix.")),
+    None => e!(RedisParseError::new_custom("frame_type", "Invalid frame type prefix.").with_input(input)),
   };
 
-  Ok((input, kind))
+  Ok((remaining, kind))
 }
 
-fn d_parse_simplestring(input: &[u8]) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
-  let (input, data) = d_read_to_crlf_s(input)?;
+fn d_parse_simplestring<'a>(
+  input: &'a [u8],
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Frame, RedisParseError<&'a [u8]>> {
+  let (input, data) = d_read_to_crlf(input)?;
 
   Ok((
     input,
     Frame::SimpleString {
-      data: data.to_owned(),
+      data: LazyStr::from_bytes(etry!(alloc_bytes(config, data))),
       attributes: None,
     },
   ))
 }
 
-fn d_parse_simpleerror(input: &[u8]) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
-  let (input, data) = d_read_to_crlf_s(input)?;
+fn d_parse_simpleerror<'a>(
+  input: &'a [u8],
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Frame, RedisParseError<&'a [u8]>> {
+  let (input, data) = d_read_to_crlf(input)?;
 
   Ok((
     input,
     Frame::SimpleError {
-      data: data.to_owned(),
+      data: LazyStr::from_bytes(etry!(alloc_bytes(config, data))),
       attributes: None,
     },
   ))
@@ -217,88 +315,129 @@ fn d_parse_boolean(input: &[u8]) -> IResult<&[u8], Frame, RedisParseError<&[u8]>
 
 fn d_parse_null(input: &[u8]) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
   let (input, _) = d_read_to_crlf_s(input)?;
-  Ok((input, Frame::Null))
+  Ok((input, Frame::Null { attributes: None }))
 }
 
-fn d_parse_blobstring(input: &[u8], len: usize) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
-  let (input, data) = nom_terminated(nom_take(len), nom_take(2_usize))(input)?;
+fn d_parse_blobstring<'a>(
+  input: &'a [u8],
+  len: usize,
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Frame, RedisParseError<&'a [u8]>> {
+  let (input, data) = d_read_blob(input, len)?;
 
   Ok((
     input,
     Frame::BlobString {
-      data: data.to_vec(),
+      data: etry!(alloc_bytes(config, data)),
       attributes: None,
     },
   ))
 }
 
-fn d_parse_bloberror(input: &[u8]) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
+fn d_parse_bloberror<'a>(
+  input: &'a [u8],
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Frame, RedisParseError<&'a [u8]>> {
   let (input, len) = d_read_prefix_len(input)?;
-  let (input, data) = nom_terminated(nom_take(len), nom_take(2_usize))(input)?;
+  let (input, data) = d_read_blob(input, len)?;
 
   Ok((
     input,
     Frame::BlobError {
-      data: data.to_vec(),
+      data: etry!(alloc_bytes(config, data)),
       attributes: None,
     },
   ))
 }
 
-fn d_parse_verbatimstring(input: &[u8]) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
+fn d_parse_verbatimstring<'a>(
+  input: &'a [u8],
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Frame, RedisParseError<&'a [u8]>> {
   let (input, len) = d_read_prefix_len(input)?;
   let (input, format) = nom_map_res(nom_terminated(nom_take(3_usize), nom_take(1_usize)), str::from_utf8)(input)?;
   let format = etry!(to_verbatimstring_format(format));
-  let (input, data) = nom_terminated(nom_take(len - 4), nom_take(2_usize))(input)?;
+  let (input, data) = d_read_blob(input, len - 4)?;
 
   Ok((
     input,
     Frame::VerbatimString {
-      data: data.to_vec(),
+      data: etry!(alloc_bytes(config, data)),
       format,
       attributes: None,
     },
   ))
 }
 
-fn d_parse_bignumber(input: &[u8]) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
+fn d_parse_bignumber<'a>(
+  input: &'a [u8],
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Frame, RedisParseError<&'a [u8]>> {
   let (input, data) = d_read_to_crlf(input)?;
+  #[cfg(feature = "bignum")]
+  {
+    if !resp3_utils::is_big_number(data) {
+      e!(RedisParseError::new_custom("parse_bignumber", "Invalid big number."));
+    }
+  }
 
   Ok((
     input,
     Frame::BigNumber {
-      data: data.to_vec(),
+      data: etry!(alloc_bytes(config, data)),
       attributes: None,
     },
   ))
 }
 
-fn d_parse_array_frames(input: &[u8], len: usize) -> IResult<&[u8], Vec<Frame>, RedisParseError<&[u8]>> {
-  nom_count(nom_map_res(d_parse_frame_or_attribute, unwrap_complete_frame), len)(input)
+fn d_parse_array_frames<'a>(
+  input: &'a [u8],
+  len: usize,
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Vec<Frame>, RedisParseError<&'a [u8]>> {
+  let mut input = input;
+  let mut frames = etry!(alloc_frames(config, len));
+
+  for _ in 0..len {
+    let (remaining, frame) = d_parse_frame_or_attribute(input, config)?;
+    frames.push(etry!(unwrap_complete_frame(frame)));
+    input = remaining;
+  }
+
+  Ok((input, frames))
 }
 
-fn d_parse_kv_pairs(input: &[u8], len: usize) -> IResult<&[u8], FrameMap, RedisParseError<&[u8]>> {
-  nom_map_res(
-    nom_count(nom_map_res(d_parse_frame_or_attribute, unwrap_complete_frame), len * 2),
-    to_map,
-  )(input)
+fn d_parse_kv_pairs<'a>(
+  input: &'a [u8],
+  len: usize,
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], FrameMap, RedisParseError<&'a [u8]>> {
+  nom_map_res(|i| d_parse_array_frames(i, len * 2, config), |frames| to_map(frames, config))(input)
 }
 
-fn d_parse_array(input: &[u8], len: usize) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
-  let (input, data) = d_parse_array_frames(input, len)?;
+fn d_parse_array<'a>(
+  input: &'a [u8],
+  len: usize,
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Frame, RedisParseError<&'a [u8]>> {
+  let (input, data) = d_parse_array_frames(input, len, config)?;
 
   Ok((input, Frame::Array { data, attributes: None }))
 }
 
-fn d_parse_push(input: &[u8]) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
+fn d_parse_push<'a>(input: &'a [u8], config: &DecodeConfig) -> IResult<&'a [u8], Frame, RedisParseError<&'a [u8]>> {
   let (input, len) = d_read_prefix_len(input)?;
-  let (input, data) = d_parse_array_frames(input, len)?;
+  let (input, data) = d_parse_array_frames(input, len, config)?;
 
   Ok((input, Frame::Push { data, attributes: None }))
 }
 
-fn d_parse_set(input: &[u8], len: usize) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
-  let (input, frames) = d_parse_array_frames(input, len)?;
+fn d_parse_set<'a>(
+  input: &'a [u8],
+  len: usize,
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Frame, RedisParseError<&'a [u8]>> {
+  let (input, frames) = d_parse_array_frames(input, len, config)?;
   let set = etry!(to_set(frames));
 
   Ok((
@@ -310,8 +449,12 @@ fn d_parse_set(input: &[u8], len: usize) -> IResult<&[u8], Frame, RedisParseErro
   ))
 }
 
-fn d_parse_map(input: &[u8], len: usize) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
-  let (input, frames) = d_parse_kv_pairs(input, len)?;
+fn d_parse_map<'a>(
+  input: &'a [u8],
+  len: usize,
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Frame, RedisParseError<&'a [u8]>> {
+  let (input, frames) = d_parse_kv_pairs(input, len, config)?;
 
   Ok((
     input,
@@ -322,13 +465,45 @@ fn d_parse_map(input: &[u8], len: usize) -> IResult<&[u8], Frame, RedisParseErro
   ))
 }
 
-fn d_parse_attribute(input: &[u8]) -> IResult<&[u8], Attributes, RedisParseError<&[u8]>> {
-  let (input, len) = d_read_prefix_len(input)?;
-  let (input, attributes) = d_parse_kv_pairs(input, len)?;
+fn d_parse_attribute<'a>(
+  input: &'a [u8],
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Attributes, RedisParseError<&'a [u8]>> {
+  let (mut input, len) = d_read_prefix_len(input)?;
+  let (remaining, mut attributes) = d_parse_kv_pairs(input, len, config)?;
+  input = remaining;
+
+  // consecutive attribute frames describe the same frame, so they're merged rather than treated as an error
+  while input.first() == Some(&ATTRIBUTE_BYTE) {
+    let (remaining, len) = d_read_prefix_len(&input[1..])?;
+    let (remaining, next) = d_parse_kv_pairs(remaining, len, config)?;
+
+    for (key, value) in next.into_iter() {
+      if let Err(e) = resp3_utils::insert_map_pair(&mut attributes, key, value, config.duplicate_keys) {
+        e!(RedisParseError::new_custom("parse_attribute", format!("{:?}", e)));
+      }
+    }
+    input = remaining;
+  }
 
   Ok((input, attributes))
 }
 
+fn d_parse_standalone_attribute<'a>(
+  input: &'a [u8],
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], Attributes, RedisParseError<&'a [u8]>> {
+  let (input, kind) = d_frame_type(input)?;
+  if kind != FrameKind::Attribute {
+    e!(RedisParseError::new_custom(
+      "parse_standalone_attribute",
+      format!("Expected attribute frame, found {}.", kind)
+    ));
+  }
+
+  d_parse_attribute(input, config)
+}
+
 fn d_parse_hello(input: &[u8]) -> IResult<&[u8], Frame, RedisParseError<&[u8]>> {
   let (input, _) = nom_map_res(nom_terminated(nom_take_until(HELLO), nom_take(1_usize)), str::from_utf8)(input)?;
   let (input, version) = be_u8(input)?;
@@ -358,20 +533,24 @@ fn d_parse_hello(input: &[u8]) -> IResult<&[u8], Frame, RedisParseError<&[u8]>>
 /// Check for a streaming variant of a frame, and if found then return the prefix bytes only, otherwise return the complete frame.
 ///
 /// Only supported for arrays, sets, maps, and blob strings.
-fn d_check_streaming(input: &[u8], kind: FrameKind) -> IResult<&[u8], DecodedFrame, RedisParseError<&[u8]>> {
+fn d_check_streaming<'a>(
+  input: &'a [u8],
+  kind: FrameKind,
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], DecodedFrame, RedisParseError<&'a [u8]>> {
   let (input, len) = d_read_prefix_len_signed(input)?;
   let (input, frame) = if len == -1 {
     (input, DecodedFrame::Streaming(StreamedFrame::new(kind)))
   } else {
     let len = etry!(isize_to_usize(len));
     let (input, frame) = match kind {
-      FrameKind::Array => d_parse_array(input, len)?,
-      FrameKind::Set => d_parse_set(input, len)?,
-      FrameKind::Map => d_parse_map(input, len)?,
-      FrameKind::BlobString => d_parse_blobstring(input, len)?,
+      FrameKind::Array => d_parse_array(input, len, config)?,
+      FrameKind::Set => d_parse_set(input, len, config)?,
+      FrameKind::Map => d_parse_map(input, len, config)?,
+      FrameKind::BlobString => d_parse_blobstring(input, len, config)?,
       _ => e!(RedisParseError::new_custom(
         "check_streaming",
-        format!("Invalid frame type: {:?}", kind)
+        format!("Invalid frame type: {}", kind)
       )),
     };
 
@@ -381,13 +560,16 @@ fn d_check_streaming(input: &[u8], kind: FrameKind) -> IResult<&[u8], DecodedFra
   Ok((input, frame))
 }
 
-fn d_parse_chunked_string(input: &[u8]) -> IResult<&[u8], DecodedFrame, RedisParseError<&[u8]>> {
+fn d_parse_chunked_string<'a>(
+  input: &'a [u8],
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], DecodedFrame, RedisParseError<&'a [u8]>> {
   let (input, len) = d_read_prefix_len(input)?;
   let (input, frame) = if len == 0 {
     (input, Frame::new_end_stream())
   } else {
-    let (input, contents) = nom_terminated(nom_take(len), nom_take(2_usize))(input)?;
-    (input, Frame::ChunkedString(Vec::from(contents)))
+    let (input, contents) = d_read_blob(input, len)?;
+    (input, Frame::ChunkedString(etry!(alloc_bytes(config, contents))))
   };
 
   Ok((input, DecodedFrame::Complete(frame)))
@@ -398,27 +580,31 @@ fn d_return_end_stream(input: &[u8]) -> IResult<&[u8], DecodedFrame, RedisParseE
   Ok((input, DecodedFrame::Complete(Frame::new_end_stream())))
 }
 
-fn d_parse_non_attribute_frame(input: &[u8], kind: FrameKind) -> IResult<&[u8], DecodedFrame, RedisParseError<&[u8]>> {
+fn d_parse_non_attribute_frame<'a>(
+  input: &'a [u8],
+  kind: FrameKind,
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], DecodedFrame, RedisParseError<&'a [u8]>> {
   let (input, frame) = match kind {
-    FrameKind::Array => d_check_streaming(input, kind)?,
-    FrameKind::BlobString => d_check_streaming(input, kind)?,
-    FrameKind::Map => d_check_streaming(input, kind)?,
-    FrameKind::Set => d_check_streaming(input, kind)?,
-    FrameKind::SimpleString => nom_map(d_parse_simplestring, map_complete_frame)(input)?,
-    FrameKind::SimpleError => nom_map(d_parse_simpleerror, map_complete_frame)(input)?,
+    FrameKind::Array => d_check_streaming(input, kind, config)?,
+    FrameKind::BlobString => d_check_streaming(input, kind, config)?,
+    FrameKind::Map => d_check_streaming(input, kind, config)?,
+    FrameKind::Set => d_check_streaming(input, kind, config)?,
+    FrameKind::SimpleString => nom_map(|i| d_parse_simplestring(i, config), map_complete_frame)(input)?,
+    FrameKind::SimpleError => nom_map(|i| d_parse_simpleerror(i, config), map_complete_frame)(input)?,
     FrameKind::Number => nom_map(d_parse_number, map_complete_frame)(input)?,
     FrameKind::Null => nom_map(d_parse_null, map_complete_frame)(input)?,
     FrameKind::Double => nom_map(d_parse_double, map_complete_frame)(input)?,
     FrameKind::Boolean => nom_map(d_parse_boolean, map_complete_frame)(input)?,
-    FrameKind::BlobError => nom_map(d_parse_bloberror, map_complete_frame)(input)?,
-    FrameKind::VerbatimString => nom_map(d_parse_verbatimstring, map_complete_frame)(input)?,
-    FrameKind::Push => nom_map(d_parse_push, map_complete_frame)(input)?,
-    FrameKind::BigNumber => nom_map(d_parse_bignumber, map_complete_frame)(input)?,
+    FrameKind::BlobError => nom_map(|i| d_parse_bloberror(i, config), map_complete_frame)(input)?,
+    FrameKind::VerbatimString => nom_map(|i| d_parse_verbatimstring(i, config), map_complete_frame)(input)?,
+    FrameKind::Push => nom_map(|i| d_parse_push(i, config), map_complete_frame)(input)?,
+    FrameKind::BigNumber => nom_map(|i| d_parse_bignumber(i, config), map_complete_frame)(input)?,
     FrameKind::Hello => nom_map(d_parse_hello, map_complete_frame)(input)?,
-    FrameKind::ChunkedString => d_parse_chunked_string(input)?,
+    FrameKind::ChunkedString => d_parse_chunked_string(input, config)?,
     FrameKind::EndStream => d_return_end_stream(input)?,
     FrameKind::Attribute => {
-      error!("Found unexpected attribute frame.");
+      _error!("Found unexpected attribute frame.");
       e!(RedisParseError::new_custom(
         "parse_non_attribute_frame",
         "Unexpected attribute frame.",
@@ -429,26 +615,137 @@ fn d_parse_non_attribute_frame(input: &[u8], kind: FrameKind) -> IResult<&[u8],
   Ok((input, frame))
 }
 
-fn d_parse_attribute_and_frame(input: &[u8]) -> IResult<&[u8], DecodedFrame, RedisParseError<&[u8]>> {
-  let (input, attributes) = d_parse_attribute(input)?;
+fn d_parse_attribute_and_frame<'a>(
+  input: &'a [u8],
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], DecodedFrame, RedisParseError<&'a [u8]>> {
+  let (input, attributes) = d_parse_attribute(input, config)?;
   let (input, kind) = d_frame_type(input)?;
-  let (input, next_frame) = d_parse_non_attribute_frame(input, kind)?;
+  let (input, next_frame) = d_parse_non_attribute_frame(input, kind, config)?;
   let frame = etry!(attach_attributes(attributes, next_frame));
 
   Ok((input, frame))
 }
 
-fn d_parse_frame_or_attribute(input: &[u8]) -> IResult<&[u8], DecodedFrame, RedisParseError<&[u8]>> {
+fn d_parse_frame_or_attribute<'a>(
+  input: &'a [u8],
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], DecodedFrame, RedisParseError<&'a [u8]>> {
   let (input, kind) = d_frame_type(input)?;
   let (input, frame) = if let FrameKind::Attribute = kind {
-    d_parse_attribute_and_frame(input)?
+    d_parse_attribute_and_frame(input, config)?
   } else {
-    d_parse_non_attribute_frame(input, kind)?
+    d_parse_non_attribute_frame(input, kind, config)?
   };
 
   Ok((input, frame))
 }
 
+/// Parse a complete frame into `frame`, reusing the allocations in `frame` when the kinds of the frames match.
+///
+/// Blob strings, simple strings, simple errors, arrays, and push frames are decoded in place. Other frames are decoded
+/// normally and replace `frame`. Every frame is decoded normally when an allocator is configured, so that the
+/// allocator sees every buffer.
+fn d_parse_into<'a>(
+  frame: &mut Frame,
+  input: &'a [u8],
+  config: &DecodeConfig,
+) -> IResult<&'a [u8], (), RedisParseError<&'a [u8]>> {
+  let (remaining, kind) = d_frame_type(input)?;
+  let in_place = if config.allocator.is_some() {
+    None
+  } else {
+    Some(&mut *frame)
+  };
+
+  match (kind, in_place) {
+    (
+      FrameKind::BlobString,
+      Some(&mut Frame::BlobString {
+        ref mut data,
+        ref mut attributes,
+      }),
+    ) => {
+      let (remaining, len) = d_read_prefix_len_signed(remaining)?;
+      if len >= 0 {
+        let (remaining, contents) = d_read_blob(remaining, len as usize)?;
+        data.clear();
+        data.extend_from_slice(contents);
+        *attributes = None;
+        return Ok((remaining, ()));
+      }
+    },
+    (
+      FrameKind::SimpleString,
+      Some(&mut Frame::SimpleString {
+        ref mut data,
+        ref mut attributes,
+      }),
+    )
+    | (
+      FrameKind::SimpleError,
+      Some(&mut Frame::SimpleError {
+        ref mut data,
+        ref mut attributes,
+      }),
+    ) => {
+      let (remaining, contents) = d_read_to_crlf(remaining)?;
+      data.replace_bytes(contents);
+      *attributes = None;
+      return Ok((remaining, ()));
+    },
+    (
+      FrameKind::Array,
+      Some(&mut Frame::Array {
+        ref mut data,
+        ref mut attributes,
+      }),
+    )
+    | (
+      FrameKind::Push,
+      Some(&mut Frame::Push {
+        ref mut data,
+        ref mut attributes,
+      }),
+    ) => {
+      let (mut remaining, len) = d_read_prefix_len_signed(remaining)?;
+      if len >= 0 {
+        let len = len as usize;
+        data.truncate(len);
+
+        for idx in 0..len {
+          if idx == data.len() {
+            data.push(Frame::Null { attributes: None });
+          }
+          remaining = d_parse_into(&mut data[idx], remaining, config)?.0;
+        }
+        *attributes = None;
+        return Ok((remaining, ()));
+      }
+    },
+    _ => {},
+  };
+
+  let (remaining, decoded) = d_parse_frame_or_attribute(input, config)?;
+  *frame = etry!(unwrap_complete_frame(decoded));
+  Ok((remaining, ()))
+}
+
+/// Convert a parsing error from the start of `buf`, capturing a hex dump around the failing offset if enabled.
+///
+/// Errors without a known position are reported at the start of the buffer.
+fn into_protocol_error(buf: &[u8], e: NomErr<RedisParseError<&[u8]>>, config: &DecodeConfig) -> RedisProtocolError {
+  let e = RedisParseError::from(e);
+
+  match config.hex_dump_window {
+    Some(window) => {
+      let offset = e.offset_in(buf).unwrap_or(0);
+      RedisProtocolError::from(e).with_hex_dump(buf, offset, window)
+    }
+    None => e.into(),
+  }
+}
+
 /// Decoding functions for complete frames. **If a streamed frame is detected it will result in an error.**
 ///
 /// Implement a [codec](https://docs.rs/tokio-util/0.6.6/tokio_util/codec/index.html) that only supports complete frames...
@@ -505,12 +802,219 @@ pub mod complete {
   ///
   /// If the byte slice contains an incomplete frame then `None` is returned.
   pub fn decode(buf: &[u8]) -> Result<Option<(Frame, usize)>, RedisProtocolError> {
+    decode_with_config(buf, &DecodeConfig::default())
+  }
+
+  /// Attempt to parse the contents of `buf` with the provided decoding options, returning the first valid frame and
+  /// the number of bytes consumed.
+  ///
+  /// If the byte slice contains an incomplete frame then `None` is returned.
+  pub fn decode_with_config(buf: &[u8], config: &DecodeConfig) -> Result<Option<(Frame, usize)>, RedisProtocolError> {
     let len = buf.len();
 
-    match d_parse_frame_or_attribute(buf) {
-      Ok((remaining, frame)) => Ok(Some((frame.into_complete_frame()?, len - remaining.len()))),
+    let result = match parse_or_release(buf, config, d_parse_frame_or_attribute) {
+      Ok((remaining, frame)) => frame
+        .into_complete_frame()
+        .map(|frame| Some((frame, len - remaining.len()))),
       Err(NomErr::Incomplete(_)) => Ok(None),
-      Err(e) => Err(RedisParseError::from(e).into()),
+      Err(e) => Err(into_protocol_error(buf, e, config)),
+    };
+
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_decode(crate::metrics::RESP3, &result, |frame| frame.kind());
+    result
+  }
+
+  /// Attempt to parse the first frame in `buf` into `frame`, returning the number of bytes consumed.
+  ///
+  /// The allocations in `frame` are reused when the decoded frame has the same shape, such as when the same kind of
+  /// reply is read repeatedly. Blob strings, simple strings, simple errors, arrays, and push frames are decoded in
+  /// place, and other frames replace the contents of `frame`.
+  ///
+  /// If the byte slice contains an incomplete frame then `None` is returned. The contents of `frame` are unspecified
+  /// after an incomplete frame or an error until a later call succeeds.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::decode::complete::decode_into;
+  /// # use redis_protocol::resp3::types::Frame;
+  /// let mut frame = Frame::Null { attributes: None };
+  /// assert_eq!(decode_into(&mut frame, b"*2\r\n$3\r\nfoo\r\n:1\r\n").unwrap(), Some(17));
+  /// assert_eq!(decode_into(&mut frame, b"*2\r\n$3\r\nbar\r\n:2\r\n").unwrap(), Some(17));
+  ///
+  /// match frame {
+  ///   Frame::Array { ref data, .. } => assert_eq!(data[0].as_str(), Some("bar")),
+  ///   _ => panic!("Expected array."),
+  /// }
+  /// ```
+  pub fn decode_into(frame: &mut Frame, buf: &[u8]) -> Result<Option<usize>, RedisProtocolError> {
+    decode_into_with_config(frame, buf, &DecodeConfig::default())
+  }
+
+  /// Attempt to parse the first frame in `buf` into `frame` with the provided decoding options.
+  ///
+  /// See [decode_into] for more information.
+  pub fn decode_into_with_config(
+    frame: &mut Frame,
+    buf: &[u8],
+    config: &DecodeConfig,
+  ) -> Result<Option<usize>, RedisProtocolError> {
+    let result = match parse_or_release(buf, config, |buf, config| d_parse_into(frame, buf, config)) {
+      Ok((remaining, _)) => Ok(Some(((), buf.len() - remaining.len()))),
+      Err(NomErr::Incomplete(_)) => Ok(None),
+      Err(e) => Err(into_protocol_error(buf, e, config)),
+    };
+
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_decode(crate::metrics::RESP3, &result, |_| frame.kind());
+    result.map(|result| result.map(|(_, len)| len))
+  }
+
+  /// Attempt to parse an attribute frame at the start of `buf` without the frame that it describes, returning the
+  /// attributes and the number of bytes consumed.
+  ///
+  /// Attributes are normally attached to the frame that follows them, so [decode] waits for that frame to arrive.
+  /// Callers that want to handle attributes as soon as they are received, such as when an attribute is the last frame
+  /// before a pipeline boundary, can call this when `buf` starts with [ATTRIBUTE_BYTE]. Consecutive attribute frames
+  /// are merged.
+  ///
+  /// If the byte slice contains an incomplete attribute frame then `None` is returned, and an error is returned if
+  /// `buf` starts with any other kind of frame.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::decode::complete::{decode, decode_attributes};
+  /// # use redis_protocol::resp3::types::Frame;
+  /// let buf = b"|1\r\n+ttl\r\n:3600\r\n";
+  /// assert!(decode(buf).unwrap().is_none());
+  ///
+  /// let (attributes, len) = decode_attributes(buf).unwrap().unwrap();
+  /// assert_eq!(len, buf.len());
+  /// let key = Frame::SimpleString {
+  ///   data: "ttl".into(),
+  ///   attributes: None,
+  /// };
+  /// assert_eq!(attributes.get(&key), Some(&Frame::from(3600)));
+  /// ```
+  pub fn decode_attributes(buf: &[u8]) -> Result<Option<(Attributes, usize)>, RedisProtocolError> {
+    decode_attributes_with_config(buf, &DecodeConfig::default())
+  }
+
+  /// Attempt to parse an attribute frame at the start of `buf` with the provided decoding options.
+  ///
+  /// See [decode_attributes] for more information.
+  pub fn decode_attributes_with_config(
+    buf: &[u8],
+    config: &DecodeConfig,
+  ) -> Result<Option<(Attributes, usize)>, RedisProtocolError> {
+    match parse_or_release(buf, config, d_parse_standalone_attribute) {
+      Ok((remaining, attributes)) => Ok(Some((attributes, buf.len() - remaining.len()))),
+      Err(NomErr::Incomplete(_)) => Ok(None),
+      Err(e) => Err(into_protocol_error(buf, e, config)),
+    }
+  }
+
+  /// Attempt to parse the contents of `buf`, returning the first valid frame and the bytes that follow it.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::decode::complete::decode_with_remaining;
+  /// # use redis_protocol::resp3::types::Frame;
+  /// let (frame, remaining) = decode_with_remaining(b":1\r\n:2").unwrap().unwrap();
+  /// assert_eq!(frame, Frame::from(1));
+  /// assert_eq!(remaining, b":2");
+  /// ```
+  ///
+  /// See [decode] for more information.
+  pub fn decode_with_remaining(buf: &[u8]) -> Result<Option<(Frame, &[u8])>, RedisProtocolError> {
+    decode(buf).map(|result| result.map(|(frame, len)| (frame, &buf[len..])))
+  }
+
+  /// Attempt to parse the contents of `buf` with the provided decoding options, reporting the result to `observer`.
+  ///
+  /// See [decode_with_config] for more information.
+  pub fn decode_with_observer(
+    buf: &[u8],
+    config: &DecodeConfig,
+    observer: &dyn FrameObserver,
+  ) -> Result<Option<(Frame, usize)>, RedisProtocolError> {
+    let start = observer::Stopwatch::start();
+    let result = decode_with_config(buf, config);
+    observer::observe_decode(observer, start, &result, |frame| frame.kind());
+    result
+  }
+
+  /// An iterator over the complete frames in a buffer, such as a capture of the bytes sent over a connection.
+  ///
+  /// Each item contains a frame and the number of bytes it used. The iterator stops when the buffer is exhausted or
+  /// only contains an incomplete frame, which can be checked with [remaining](Self::remaining). Iteration also stops
+  /// after the first error.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::decode::complete::FrameIter;
+  /// # use redis_protocol::resp3::types::Frame;
+  /// let mut frames = FrameIter::new(b":1\r\n#t\r\n:3");
+  /// assert_eq!(frames.next().unwrap().unwrap(), (Frame::from(1), 4));
+  /// assert_eq!(frames.next().unwrap().unwrap(), (Frame::from(true), 4));
+  /// assert!(frames.next().is_none());
+  ///
+  /// assert_eq!(frames.offset(), 8);
+  /// assert_eq!(frames.remaining(), b":3");
+  /// ```
+  #[derive(Clone, Debug)]
+  pub struct FrameIter<'a> {
+    buf: &'a [u8],
+    offset: usize,
+    config: DecodeConfig,
+    done: bool,
+  }
+
+  impl<'a> FrameIter<'a> {
+    /// Create an iterator over the frames in `buf`.
+    pub fn new(buf: &'a [u8]) -> Self {
+      Self::with_config(buf, DecodeConfig::default())
+    }
+
+    /// Create an iterator over the frames in `buf` with the provided decoding options.
+    pub fn with_config(buf: &'a [u8], config: DecodeConfig) -> Self {
+      FrameIter {
+        buf,
+        offset: 0,
+        config,
+        done: false,
+      }
+    }
+
+    /// The number of bytes used by the frames returned so far.
+    pub fn offset(&self) -> usize {
+      self.offset
+    }
+
+    /// The bytes that have not been decoded.
+    pub fn remaining(&self) -> &'a [u8] {
+      &self.buf[self.offset..]
+    }
+  }
+
+  impl<'a> Iterator for FrameIter<'a> {
+    type Item = Result<(Frame, usize), RedisProtocolError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+      if self.done || self.offset >= self.buf.len() {
+        return None;
+      }
+
+      match decode_with_config(&self.buf[self.offset..], &self.config) {
+        Ok(Some((frame, len))) => {
+          self.offset += len;
+          Some(Ok((frame, len)))
+        },
+        Ok(None) => {
+          self.done = true;
+          None
+        },
+        Err(e) => {
+          self.done = true;
+          Some(Err(e))
+        },
+      }
     }
   }
 }
@@ -576,7 +1080,7 @@ pub mod complete {
 ///
 ///         // we already checked for streams within streams above
 ///         let frame = frame.into_complete_frame()?;
-///         streamed_frame.add_frame(frame);
+///         streamed_frame.add_frame(frame)?;
 ///
 ///         if streamed_frame.is_finished() {
 ///            // convert the inner stream buffer into the final output frame
@@ -618,12 +1122,170 @@ pub mod streaming {
   ///
   /// If the byte slice contains an incomplete frame then `None` is returned.
   pub fn decode(buf: &[u8]) -> Result<Option<(DecodedFrame, usize)>, RedisProtocolError> {
+    decode_with_config(buf, &DecodeConfig::default())
+  }
+
+  /// Attempt to parse the contents of `buf` with the provided decoding options, returning the first valid frame and
+  /// the number of bytes consumed.
+  ///
+  /// If the byte slice contains an incomplete frame then `None` is returned. Streamed maps should be converted with
+  /// [StreamedFrame::into_frame_with_config](crate::resp3::types::StreamedFrame::into_frame_with_config) to apply the
+  /// same options.
+  pub fn decode_with_config(
+    buf: &[u8],
+    config: &DecodeConfig,
+  ) -> Result<Option<(DecodedFrame, usize)>, RedisProtocolError> {
     let len = buf.len();
 
-    match d_parse_frame_or_attribute(buf) {
+    let result = match parse_or_release(buf, config, d_parse_frame_or_attribute) {
       Ok((remaining, frame)) => Ok(Some((frame, len - remaining.len()))),
       Err(NomErr::Incomplete(_)) => Ok(None),
-      Err(e) => Err(RedisParseError::from(e).into()),
+      Err(e) => Err(into_protocol_error(buf, e, config)),
+    };
+
+    #[cfg(feature = "metrics")]
+    {
+      if let Ok(Some((DecodedFrame::Streaming(ref frame), _))) = result {
+        crate::metrics::record_streamed_frame(frame.kind);
+      }
+      crate::metrics::record_decode(crate::metrics::RESP3, &result, |frame| match *frame {
+        DecodedFrame::Complete(ref frame) => frame.kind(),
+        DecodedFrame::Streaming(ref frame) => frame.kind,
+      });
+    }
+    result
+  }
+
+  /// Attempt to parse the contents of `buf` with the provided decoding options, reporting the result to `observer`.
+  ///
+  /// See [decode_with_config] for more information.
+  pub fn decode_with_observer(
+    buf: &[u8],
+    config: &DecodeConfig,
+    observer: &dyn FrameObserver,
+  ) -> Result<Option<(DecodedFrame, usize)>, RedisProtocolError> {
+    let start = observer::Stopwatch::start();
+    let result = decode_with_config(buf, config);
+    observer::observe_decode(observer, start, &result, |frame| match *frame {
+      DecodedFrame::Complete(ref frame) => frame.kind(),
+      DecodedFrame::Streaming(ref frame) => frame.kind,
+    });
+    result
+  }
+
+  /// A decoder that combines streamed frames, limiting the number of bytes buffered for each stream.
+  ///
+  /// The limit includes the bytes of every chunk combined into the current stream and the bytes in the input buffer
+  /// while a stream is in progress, so a peer cannot force the caller to buffer an unbounded stream.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::decode::streaming::StreamDecoder;
+  /// # use redis_protocol::types::RedisProtocolErrorKind;
+  /// # use bytes::BytesMut;
+  /// let mut decoder = StreamDecoder::new(12);
+  /// let mut buf = BytesMut::from(&b"*?\r\n:1\r\n"[..]);
+  /// assert!(decoder.decode(&mut buf).unwrap().is_none());
+  /// assert_eq!(decoder.buffered(), 8);
+  ///
+  /// buf.extend_from_slice(b":2\r\n:3\r\n");
+  /// let error = decoder.decode(&mut buf).unwrap_err();
+  /// assert_eq!(error.kind(), &RedisProtocolErrorKind::StreamTooLarge(12));
+  /// ```
+  #[derive(Debug)]
+  pub struct StreamDecoder {
+    config: DecodeConfig,
+    max_stream_len: usize,
+    streamed: Option<StreamedFrame>,
+    buffered: usize,
+  }
+
+  impl StreamDecoder {
+    /// Create a new decoder that allows up to `max_stream_len` bytes for each streamed frame.
+    pub fn new(max_stream_len: usize) -> Self {
+      Self::with_config(max_stream_len, DecodeConfig::default())
+    }
+
+    /// Create a new decoder with the provided decoding options.
+    pub fn with_config(max_stream_len: usize, config: DecodeConfig) -> Self {
+      StreamDecoder {
+        config,
+        max_stream_len,
+        streamed: None,
+        buffered: 0,
+      }
+    }
+
+    /// The number of bytes combined into the current streamed frame.
+    pub fn buffered(&self) -> usize {
+      self.buffered
+    }
+
+    /// Whether or not a streamed frame is in progress.
+    pub fn is_streaming(&self) -> bool {
+      self.streamed.is_some()
+    }
+
+    /// Discard the current streamed frame, such as after the connection is closed.
+    pub fn reset(&mut self) {
+      self.streamed = None;
+      self.buffered = 0;
+    }
+
+    fn check_len(&self, len: usize) -> Result<(), RedisProtocolError> {
+      if self.buffered.saturating_add(len) > self.max_stream_len {
+        Err(RedisProtocolError::new(
+          RedisProtocolErrorKind::StreamTooLarge(self.max_stream_len),
+          format!("Streamed frame is larger than {} bytes.", self.max_stream_len),
+        ))
+      } else {
+        Ok(())
+      }
+    }
+
+    /// Decode the next complete frame from `buf`, removing the decoded bytes from the buffer.
+    ///
+    /// The chunks of streamed frames are removed from the buffer as they are decoded, and the combined frame is
+    /// returned once the stream finishes. The connection should be closed after an error since the remaining bytes
+    /// cannot be decoded reliably.
+    pub fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Frame>, RedisProtocolError> {
+      loop {
+        let (frame, len) = match decode_with_config(buf, &self.config)? {
+          Some(result) => result,
+          None if self.streamed.is_some() => return self.check_len(buf.len()).map(|_| None),
+          None => return Ok(None),
+        };
+
+        match (self.streamed.take(), frame) {
+          (Some(_), DecodedFrame::Streaming(_)) => {
+            return Err(RedisProtocolError::new(
+              RedisProtocolErrorKind::DecodeError,
+              "Cannot start a stream while already inside a stream.",
+            ))
+          },
+          (None, DecodedFrame::Streaming(streamed)) => {
+            self.check_len(len)?;
+            let _ = buf.split_to(len);
+            self.streamed = Some(streamed);
+            self.buffered = len;
+          },
+          (None, DecodedFrame::Complete(frame)) => {
+            let _ = buf.split_to(len);
+            return Ok(Some(frame));
+          },
+          (Some(mut streamed), DecodedFrame::Complete(frame)) => {
+            self.check_len(len)?;
+            let _ = buf.split_to(len);
+            streamed.add_frame(frame)?;
+
+            if streamed.is_finished() {
+              self.buffered = 0;
+              return streamed.into_frame_with_config(&self.config).map(Some);
+            }
+            self.streamed = Some(streamed);
+            self.buffered += len;
+          },
+        }
+      }
     }
   }
 }
@@ -631,8 +1293,9 @@ pub mod streaming {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::resp3::decode::complete::decode;
+  use crate::resp3::decode::complete::{decode, decode_into, FrameIter};
   use crate::resp3::decode::streaming::decode as stream_decode;
+  use crate::resp3::decode::streaming::StreamDecoder;
   use bytes::BytesMut;
   use std::str;
 
@@ -713,6 +1376,25 @@ mod tests {
     decode_and_verify_padded_some(&mut bytes, &expected);
   }
 
+  #[test]
+  fn should_decode_simple_string_without_validating_utf8() {
+    let (frame, len) = decode(b"+\xffOK\r\n").unwrap().unwrap();
+    assert_eq!(len, 6);
+
+    match frame {
+      Frame::SimpleString { ref data, .. } => {
+        assert_eq!(data.as_bytes(), b"\xffOK");
+        assert_eq!(data.as_str(), None);
+      },
+      _ => panic!("Expected simple string."),
+    }
+    assert_eq!(frame.as_str(), None);
+
+    let mut buf = BytesMut::new();
+    crate::resp3::encode::complete::encode_bytes(&mut buf, &frame).unwrap();
+    assert_eq!(&buf[..], b"+\xffOK\r\n");
+  }
+
   #[test]
   #[should_panic]
   fn should_decode_simple_string_incomplete() {
@@ -795,7 +1477,7 @@ mod tests {
             data: "Foo".into(),
             attributes: None,
           },
-          Frame::Null,
+          Frame::Null { attributes: None },
           Frame::BlobString {
             data: "Bar".into(),
             attributes: None,
@@ -1007,6 +1689,15 @@ mod tests {
     decode_and_verify_padded_some(&mut bytes, &expected);
   }
 
+  #[test]
+  fn should_decode_double_nan_value() {
+    let bytes: BytesMut = ",nan\r\n".into();
+    let (frame, len) = complete::decode(&bytes).unwrap().unwrap();
+
+    assert!(frame.as_f64().unwrap().is_nan());
+    assert_eq!(len, 6);
+  }
+
   #[test]
   #[should_panic]
   fn should_decode_double_nan() {
@@ -1065,15 +1756,49 @@ mod tests {
     decode_and_verify_padded_some(&mut bytes, &expected);
   }
 
+  #[test]
+  #[cfg(feature = "bignum")]
+  fn should_error_on_invalid_bignumber() {
+    let bytes: BytesMut = "(12a4\r\n".into();
+    assert!(complete::decode(&bytes).is_err());
+
+    let bytes: BytesMut = "(-\r\n".into();
+    assert!(complete::decode(&bytes).is_err());
+  }
+
   #[test]
   fn should_decode_null() {
-    let expected = (Some(Frame::Null), 3);
+    let expected = (Some(Frame::Null { attributes: None }), 3);
     let mut bytes: BytesMut = "_\r\n".into();
 
     decode_and_verify_some(&mut bytes, &expected);
     decode_and_verify_padded_some(&mut bytes, &expected);
   }
 
+  #[test]
+  fn should_decode_null_with_attributes() {
+    let mut attributes = resp3_utils::new_map(None);
+    attributes.insert(
+      Frame::SimpleString {
+        data: "ttl".into(),
+        attributes: None,
+      },
+      3600.into(),
+    );
+    let expected = Frame::Null {
+      attributes: Some(attributes),
+    };
+    let bytes = "|1\r\n+ttl\r\n:3600\r\n_\r\n";
+
+    let (frame, len) = decode(bytes.as_bytes()).unwrap().unwrap();
+    assert_eq!(frame.attributes(), expected.attributes());
+    assert_eq!(len, bytes.len());
+
+    let mut buf = BytesMut::new();
+    let _ = crate::resp3::encode::complete::encode_bytes(&mut buf, &frame).unwrap();
+    assert_eq!(buf, bytes.as_bytes());
+  }
+
   #[test]
   fn should_decode_verbatim_string_mkd() {
     let expected = (
@@ -1155,7 +1880,7 @@ mod tests {
       data: 2,
       attributes: None,
     };
-    let v2 = Frame::Null;
+    let v2 = Frame::Null { attributes: None };
     let k3 = Frame::BlobString {
       data: "second".into(),
       attributes: None,
@@ -1182,6 +1907,272 @@ mod tests {
     decode_and_verify_padded_some(&mut bytes, &expected);
   }
 
+  #[test]
+  fn should_decode_map_with_aggregate_keys() {
+    let k1 = Frame::Array {
+      data: vec![1.into(), 2.into()],
+      attributes: None,
+    };
+    let mut inner = resp3_utils::new_map(None);
+    inner.insert(
+      Frame::SimpleString {
+        data: "a".into(),
+        attributes: None,
+      },
+      Frame::Null { attributes: None },
+    );
+    let k2 = Frame::Map {
+      data: inner,
+      attributes: None,
+    };
+
+    let mut expected_map = resp3_utils::new_map(None);
+    expected_map.insert(k1, 1.into());
+    expected_map.insert(k2, 2.into());
+    let expected = (
+      Some(Frame::Map {
+        data: expected_map,
+        attributes: None,
+      }),
+      35,
+    );
+    let mut bytes: BytesMut = "%2\r\n*2\r\n:1\r\n:2\r\n:1\r\n%1\r\n+a\r\n_\r\n:2\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_capture_hex_dump_on_error() {
+    let buf = b"*3\r\n$1\r\nb\r\n?aaaaaaaaaaaaaaaaaaaa";
+    let config = DecodeConfig {
+      hex_dump_window: Some(8),
+      ..Default::default()
+    };
+    let error = complete::decode_with_config(buf, &config).unwrap_err();
+
+    assert_eq!(error.offset(), Some(11));
+    assert_eq!(
+      error.hex_dump(),
+      Some(concat!(
+        "00000000  2a 33 0d 0a 24 31 0d 0a 62 0d 0a 3f 61 61 61 61  |*3..$1..b..?aaaa|\n",
+        "                                           ^^\n",
+        "00000010  61 61 61                                         |aaa|"
+      ))
+    );
+    assert_eq!(complete::decode(buf).unwrap_err().hex_dump(), None);
+    assert_eq!(streaming::decode_with_config(buf, &config).unwrap_err().offset(), Some(11));
+  }
+
+  #[test]
+  fn should_decode_map_duplicate_keys_with_policy() {
+    let bytes = "%3\r\n+a\r\n:1\r\n+b\r\n:2\r\n+a\r\n:3\r\n";
+    let decode_map = |policy: DuplicateKeyPolicy| {
+      let config = DecodeConfig {
+        duplicate_keys: policy,
+        ..Default::default()
+      };
+      complete::decode_with_config(bytes.as_bytes(), &config).map(|result| match result {
+        Some((Frame::Map { data, .. }, len)) => {
+          assert_eq!(len, bytes.len());
+          data
+        }
+        _ => panic!("Expected map."),
+      })
+    };
+    let key = |s: &str| Frame::SimpleString {
+      data: s.into(),
+      attributes: None,
+    };
+    let array = |data: Vec<i64>| Frame::Array {
+      data: data.into_iter().map(|i| i.into()).collect(),
+      attributes: None,
+    };
+
+    let map = decode_map(DuplicateKeyPolicy::KeepFirst).unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(&key("a")), Some(&1.into()));
+    assert_eq!(map.get(&key("b")), Some(&2.into()));
+
+    let map = decode_map(DuplicateKeyPolicy::KeepLast).unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get(&key("a")), Some(&3.into()));
+
+    let map = decode_map(DuplicateKeyPolicy::Collect).unwrap();
+    assert_eq!(map.get(&key("a")), Some(&array(vec![1, 3])));
+    assert_eq!(map.get(&key("b")), Some(&array(vec![2])));
+
+    assert!(decode_map(DuplicateKeyPolicy::Error).is_err());
+    assert_eq!(decode(bytes.as_bytes()).unwrap().unwrap().0.len(), 2);
+  }
+
+  #[test]
+  fn should_apply_duplicate_key_policy_to_streamed_maps() {
+    let config = DecodeConfig {
+      duplicate_keys: DuplicateKeyPolicy::Error,
+      ..Default::default()
+    };
+    let mut streamed_frame = StreamedFrame::new(FrameKind::Map);
+    streamed_frame.add_frame(1.into()).unwrap();
+    streamed_frame.add_frame(2.into()).unwrap();
+    streamed_frame.add_frame(1.into()).unwrap();
+    streamed_frame.add_frame(3.into()).unwrap();
+
+    assert!(streamed_frame.into_frame_with_config(&config).is_err());
+  }
+
+  #[test]
+  fn should_handle_trailing_and_consecutive_attributes() {
+    let attribute = "|1\r\n+a\r\n:1\r\n";
+    assert_eq!(decode(attribute.as_bytes()).unwrap(), None);
+    assert_eq!(stream_decode(attribute.as_bytes()).unwrap(), None);
+
+    let (attributes, len) = complete::decode_attributes(attribute.as_bytes()).unwrap().unwrap();
+    assert_eq!(len, attribute.len());
+    assert_eq!(attributes.len(), 1);
+    assert!(complete::decode_attributes(&attribute.as_bytes()[..8]).unwrap().is_none());
+    assert!(complete::decode_attributes(b"+OK\r\n").is_err());
+
+    let buf = "|1\r\n+a\r\n:1\r\n|1\r\n+b\r\n:2\r\n+OK\r\n";
+    let (frame, len) = decode(buf.as_bytes()).unwrap().unwrap();
+    assert_eq!(len, buf.len());
+    assert_eq!(frame.attributes().map(|attributes| attributes.len()), Some(2));
+
+    let (attributes, len) = complete::decode_attributes(buf.as_bytes()).unwrap().unwrap();
+    assert_eq!(len, buf.len() - 5);
+    assert_eq!(attributes.len(), 2);
+  }
+
+  #[test]
+  fn should_decode_with_custom_allocator() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct CountingAllocator {
+      bytes: AtomicUsize,
+      frames: AtomicUsize,
+    }
+
+    impl FrameAllocator for CountingAllocator {
+      fn alloc_bytes(&self, data: &[u8]) -> Result<Vec<u8>, RedisProtocolError> {
+        self.bytes.fetch_add(data.len(), Ordering::SeqCst);
+        Ok(data.to_vec())
+      }
+
+      fn alloc_frames(&self, len: usize) -> Result<Vec<Frame>, RedisProtocolError> {
+        self.frames.fetch_add(len, Ordering::SeqCst);
+        Ok(Vec::with_capacity(len))
+      }
+    }
+
+    let allocator = Arc::new(CountingAllocator::default());
+    let config = DecodeConfig {
+      allocator: Some(allocator.clone()),
+      ..Default::default()
+    };
+    let buf = b"*3\r\n$3\r\nfoo\r\n+OK\r\n%1\r\n-ERR\r\n:1\r\n";
+    let (frame, len) = complete::decode_with_config(buf, &config).unwrap().unwrap();
+
+    assert_eq!(len, buf.len());
+    assert_eq!(frame.len(), 3);
+    assert_eq!(allocator.bytes.load(Ordering::SeqCst), 3 + 2 + 3);
+    assert_eq!(allocator.frames.load(Ordering::SeqCst), 3 + 2);
+
+    let bounded = DecodeConfig::default().with_allocator(BoundedFrameAllocator::new(5));
+    assert!(complete::decode_with_config(b"$3\r\nfoo\r\n", &bounded).unwrap().is_some());
+    assert!(complete::decode_with_config(b"*1\r\n$3\r\nfoo\r\n", &bounded).is_err());
+  }
+
+  #[test]
+  fn should_release_allocations_for_incomplete_frames() {
+    let allocator = Arc::new(BoundedFrameAllocator::new(1000));
+    let config = DecodeConfig {
+      allocator: Some(allocator.clone()),
+      ..Default::default()
+    };
+    let buf = b"*2\r\n$3\r\nfoo\r\n$3\r\nbar\r\n";
+
+    for len in 1 .. buf.len() {
+      assert!(streaming::decode_with_config(&buf[.. len], &config).unwrap().is_none());
+      assert_eq!(allocator.used(), 0);
+    }
+    let (frame, len) = streaming::decode_with_config(buf, &config).unwrap().unwrap();
+    assert_eq!(len, buf.len());
+    assert_eq!(frame.into_complete_frame().unwrap().len(), 2);
+    assert_eq!(allocator.used(), 2 * std::mem::size_of::<Frame>() + 6);
+
+    allocator.reset();
+    assert!(complete::decode_with_config(b"*2\r\n$3\r\nfoo\r\n:abc\r\n", &config).is_err());
+    assert_eq!(allocator.used(), 0);
+  }
+
+  #[test]
+  fn should_decode_into_with_allocator() {
+    let config = DecodeConfig::default().with_allocator(BoundedFrameAllocator::new(10));
+    let buf = b"$20\r\n01234567890123456789\r\n";
+
+    let mut frame = Frame::BlobString {
+      data:       Vec::with_capacity(32),
+      attributes: None,
+    };
+    assert!(complete::decode_into_with_config(&mut frame, buf, &config).is_err());
+
+    let mut frame = Frame::Array {
+      data:       Vec::new(),
+      attributes: None,
+    };
+    let buf = b"*1\r\n+OK\r\n";
+    assert!(complete::decode_into_with_config(&mut frame, buf, &config).is_err());
+  }
+
+  #[test]
+  fn should_decode_set_with_double_and_aggregate_members() {
+    let mut expected_set = resp3_utils::new_set(None);
+    expected_set.insert(Frame::Double {
+      data: 1.5,
+      attributes: None,
+    });
+    expected_set.insert(Frame::Double {
+      data: 2.5,
+      attributes: None,
+    });
+    expected_set.insert(Frame::Array {
+      data: vec![Frame::Null { attributes: None }],
+      attributes: None,
+    });
+    let expected = (
+      Some(Frame::Set {
+        data: expected_set,
+        attributes: None,
+      }),
+      23,
+    );
+    let mut bytes: BytesMut = "~3\r\n,1.5\r\n,2.5\r\n*1\r\n_\r\n".into();
+
+    decode_and_verify_some(&mut bytes, &expected);
+    decode_and_verify_padded_some(&mut bytes, &expected);
+  }
+
+  #[test]
+  fn should_decode_doubles_as_set_members_and_map_keys() {
+    let (frame, _) = decode(b"~2\r\n,1.5\r\n,2.5\r\n").unwrap().unwrap();
+    let members: Vec<OrderedDouble> = match frame {
+      Frame::Set { ref data, .. } => data.sorted().iter().map(|f| OrderedDouble(f.as_f64().unwrap())).collect(),
+      _ => panic!("Expected set."),
+    };
+    assert_eq!(members, vec![OrderedDouble(1.5), OrderedDouble(2.5)]);
+
+    let (frame, _) = decode(b"~4\r\n,0\r\n,-0\r\n,nan\r\n,nan\r\n").unwrap().unwrap();
+    assert_eq!(frame.len(), 2);
+
+    let (frame, _) = decode(b"%1\r\n,inf\r\n:1\r\n").unwrap().unwrap();
+    match frame {
+      Frame::Map { ref data, .. } => assert_eq!(data.get(&OrderedDouble(f64::INFINITY).into()), Some(&1.into())),
+      _ => panic!("Expected map."),
+    };
+  }
+
   #[test]
   fn should_decode_set_no_nulls() {
     let mut expected_set = resp3_utils::new_set(None);
@@ -1225,7 +2216,7 @@ mod tests {
       data: "2".into(),
       attributes: None,
     });
-    expected_set.insert(Frame::Null);
+    expected_set.insert(Frame::Null { attributes: None });
     expected_set.insert(Frame::Double {
       data: 4.2,
       attributes: None,
@@ -1516,7 +2507,7 @@ mod tests {
     );
     assert_eq!(amt, 4);
     let _ = bytes.split_to(amt);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     let (frame, amt) = stream_decode(&bytes).unwrap().unwrap();
     assert_eq!(
@@ -1528,7 +2519,7 @@ mod tests {
     );
     assert_eq!(amt, 4);
     let _ = bytes.split_to(amt);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     let (frame, amt) = stream_decode(&bytes).unwrap().unwrap();
     assert_eq!(
@@ -1540,12 +2531,12 @@ mod tests {
     );
     assert_eq!(amt, 4);
     let _ = bytes.split_to(amt);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     let (frame, amt) = stream_decode(&bytes).unwrap().unwrap();
     assert_eq!(frame, DecodedFrame::Complete(Frame::new_end_stream()));
     assert_eq!(amt, 3);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     assert!(streamed.is_finished());
     let actual = streamed.into_frame().unwrap();
@@ -1590,7 +2581,7 @@ mod tests {
     );
     assert_eq!(amt, 4);
     let _ = bytes.split_to(amt);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     let (frame, amt) = stream_decode(&bytes).unwrap().unwrap();
     assert_eq!(
@@ -1602,7 +2593,7 @@ mod tests {
     );
     assert_eq!(amt, 4);
     let _ = bytes.split_to(amt);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     let (frame, amt) = stream_decode(&bytes).unwrap().unwrap();
     assert_eq!(
@@ -1614,12 +2605,12 @@ mod tests {
     );
     assert_eq!(amt, 4);
     let _ = bytes.split_to(amt);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     let (frame, amt) = stream_decode(&bytes).unwrap().unwrap();
     assert_eq!(frame, DecodedFrame::Complete(Frame::new_end_stream()));
     assert_eq!(amt, 3);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     assert!(streamed.is_finished());
     let actual = streamed.into_frame().unwrap();
@@ -1665,7 +2656,7 @@ mod tests {
     );
     assert_eq!(amt, 4);
     let _ = bytes.split_to(amt);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     let (frame, amt) = stream_decode(&bytes).unwrap().unwrap();
     assert_eq!(
@@ -1677,7 +2668,7 @@ mod tests {
     );
     assert_eq!(amt, 4);
     let _ = bytes.split_to(amt);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     let (frame, amt) = stream_decode(&bytes).unwrap().unwrap();
     assert_eq!(
@@ -1689,7 +2680,7 @@ mod tests {
     );
     assert_eq!(amt, 4);
     let _ = bytes.split_to(amt);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     let (frame, amt) = stream_decode(&bytes).unwrap().unwrap();
     assert_eq!(
@@ -1701,12 +2692,12 @@ mod tests {
     );
     assert_eq!(amt, 4);
     let _ = bytes.split_to(amt);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     let (frame, amt) = stream_decode(&bytes).unwrap().unwrap();
     assert_eq!(frame, DecodedFrame::Complete(Frame::new_end_stream()));
     assert_eq!(amt, 3);
-    streamed.add_frame(frame.into_complete_frame().unwrap());
+    streamed.add_frame(frame.into_complete_frame().unwrap()).unwrap();
 
     assert!(streamed.is_finished());
     let actual = streamed.into_frame().unwrap();
@@ -1738,4 +2729,96 @@ mod tests {
 
     assert_eq!(actual, expected);
   }
+
+  #[test]
+  fn should_decode_into_existing_frame() {
+    let mut frame = Frame::Null { attributes: None };
+    let first = b"*3\r\n$3\r\nfoo\r\n+OK\r\n:1\r\n";
+    assert_eq!(decode_into(&mut frame, first).unwrap(), Some(first.len()));
+    assert_eq!(frame, decode(first).unwrap().unwrap().0);
+
+    let pointers = |frame: &Frame| match *frame {
+      Frame::Array { ref data, .. } => match data[0] {
+        Frame::BlobString { data: ref inner, .. } => (data.as_ptr(), inner.as_ptr()),
+        _ => panic!("Expected blob string."),
+      },
+      _ => panic!("Expected array."),
+    };
+    let before = pointers(&frame);
+
+    let second = b"*2\r\n$3\r\nbar\r\n%1\r\n+a\r\n:2\r\n";
+    assert_eq!(decode_into(&mut frame, second).unwrap(), Some(second.len()));
+    assert_eq!(frame, decode(second).unwrap().unwrap().0);
+    assert_eq!(pointers(&frame), before);
+
+    assert_eq!(decode_into(&mut frame, b"*2\r\n$3\r\nba").unwrap(), None);
+    assert!(decode_into(&mut frame, b"*1\r\n$?\r\n").is_err());
+
+    let third = b"|1\r\n+ttl\r\n:1\r\n$3\r\nbaz\r\n";
+    assert_eq!(decode_into(&mut frame, third).unwrap(), Some(third.len()));
+    assert_eq!(frame.attributes(), decode(third).unwrap().unwrap().0.attributes());
+  }
+
+  #[test]
+  fn should_iterate_over_frames() {
+    let buf = b"+foo\r\n:2\r\n~foo\r\n:3\r\n";
+    let mut frames = FrameIter::new(buf);
+
+    assert_eq!(
+      frames.next().unwrap().unwrap(),
+      (
+        Frame::SimpleString {
+          data: "foo".into(),
+          attributes: None
+        },
+        6
+      )
+    );
+    assert_eq!(frames.next().unwrap().unwrap(), (Frame::from(2), 4));
+    assert!(frames.next().unwrap().is_err());
+    assert!(frames.next().is_none());
+    assert_eq!(frames.offset(), 10);
+
+    let lens: Vec<usize> = FrameIter::new(b":1\r\n:22\r\n")
+      .map(|result| result.unwrap().1)
+      .collect();
+    assert_eq!(lens, vec![4, 5]);
+  }
+
+  #[test]
+  fn should_combine_streams_with_stream_decoder() {
+    let mut decoder = StreamDecoder::new(32);
+    let mut bytes: BytesMut = ":1\r\n*?\r\n:2\r\n".into();
+
+    assert_eq!(decoder.decode(&mut bytes).unwrap(), Some(Frame::from(1)));
+    assert_eq!(decoder.decode(&mut bytes).unwrap(), None);
+    assert!(decoder.is_streaming());
+    assert_eq!(decoder.buffered(), 8);
+    assert!(bytes.is_empty());
+
+    bytes.extend_from_slice(b":3\r\n.\r\n:4\r\n");
+    let expected = Frame::Array {
+      data: vec![2.into(), 3.into()],
+      attributes: None,
+    };
+    assert_eq!(decoder.decode(&mut bytes).unwrap(), Some(expected));
+    assert!(!decoder.is_streaming());
+    assert_eq!(decoder.buffered(), 0);
+    assert_eq!(decoder.decode(&mut bytes).unwrap(), Some(Frame::from(4)));
+  }
+
+  #[test]
+  fn should_limit_buffered_stream_bytes() {
+    let mut decoder = StreamDecoder::new(12);
+    let mut bytes: BytesMut = "$?\r\n;3\r\nfoo\r\n".into();
+
+    let error = decoder.decode(&mut bytes).unwrap_err();
+    assert_eq!(error.kind(), &RedisProtocolErrorKind::StreamTooLarge(12));
+
+    decoder.reset();
+    let mut bytes: BytesMut = "*?\r\n:1\r\n$100\r\nfoo".into();
+    let error = decoder.decode(&mut bytes).unwrap_err();
+    assert_eq!(error.kind(), &RedisProtocolErrorKind::StreamTooLarge(12));
+    assert_eq!(decoder.buffered(), 8);
+  }
 }
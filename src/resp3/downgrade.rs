@@ -0,0 +1,358 @@
+//! Functions for converting RESP3 frames to RESP2 frames, such as when a proxy serves RESP2 clients from a RESP3
+//! server.
+//!
+//! The default [DowngradePolicy](crate::resp3::downgrade::DowngradePolicy) matches the replies that the server sends to RESP2 clients.
+//!
+//! <https://github.com/antirez/RESP3/blob/master/spec.md>
+
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::Frame;
+use crate::resp3::utils as resp3_utils;
+use crate::types::{RedisProtocolError, RedisProtocolErrorKind};
+use crate::utils::PUBSUB_PUSH_PREFIX;
+
+/// How to convert attributes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttributePolicy {
+  /// Remove the attributes.
+  Strip,
+  /// Return an error.
+  Error,
+}
+
+/// How to convert booleans.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BooleanPolicy {
+  /// Convert to `1` or `0`.
+  Integer,
+  /// Convert to a bulk string with `true` or `false`.
+  String,
+  /// Return an error.
+  Error,
+}
+
+/// How to convert doubles.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DoublePolicy {
+  /// Convert to a bulk string.
+  BulkString,
+  /// Return an error.
+  Error,
+}
+
+/// How to convert big numbers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BigNumberPolicy {
+  /// Convert to a bulk string.
+  BulkString,
+  /// Return an error.
+  Error,
+}
+
+/// How to convert verbatim strings.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VerbatimStringPolicy {
+  /// Convert to a bulk string without the format.
+  BulkString,
+  /// Convert to a bulk string with the format prefix, such as `txt:`.
+  WithFormat,
+  /// Return an error.
+  Error,
+}
+
+/// How to convert maps.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MapPolicy {
+  /// Convert to an array of alternating keys and values.
+  FlatArray,
+  /// Convert to an array of two element `[key, value]` arrays.
+  Pairs,
+  /// Return an error.
+  Error,
+}
+
+/// How to convert sets.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SetPolicy {
+  /// Convert to an array.
+  Array,
+  /// Return an error.
+  Error,
+}
+
+/// How to convert push frames.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PushPolicy {
+  /// Convert to an array. The `pubsub` prefix is removed from publish-subscribe messages, so they match the messages
+  /// sent to RESP2 clients.
+  Array,
+  /// Return an error.
+  Error,
+}
+
+/// Options for converting each RESP3 type that does not exist in RESP2.
+///
+/// Blob errors are converted to errors, and `HELLO` frames and chunked strings always return an error.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DowngradePolicy {
+  pub attributes: AttributePolicy,
+  pub boolean: BooleanPolicy,
+  pub double: DoublePolicy,
+  pub big_number: BigNumberPolicy,
+  pub verbatim_string: VerbatimStringPolicy,
+  pub map: MapPolicy,
+  pub set: SetPolicy,
+  pub push: PushPolicy,
+}
+
+impl Default for DowngradePolicy {
+  fn default() -> Self {
+    DowngradePolicy {
+      attributes: AttributePolicy::Strip,
+      boolean: BooleanPolicy::Integer,
+      double: DoublePolicy::BulkString,
+      big_number: BigNumberPolicy::BulkString,
+      verbatim_string: VerbatimStringPolicy::BulkString,
+      map: MapPolicy::FlatArray,
+      set: SetPolicy::Array,
+      push: PushPolicy::Array,
+    }
+  }
+}
+
+impl DowngradePolicy {
+  /// A policy that returns an error for any type or attribute that does not exist in RESP2.
+  pub fn strict() -> Self {
+    DowngradePolicy {
+      attributes: AttributePolicy::Error,
+      boolean: BooleanPolicy::Error,
+      double: DoublePolicy::Error,
+      big_number: BigNumberPolicy::Error,
+      verbatim_string: VerbatimStringPolicy::Error,
+      map: MapPolicy::Error,
+      set: SetPolicy::Error,
+      push: PushPolicy::Error,
+    }
+  }
+}
+
+fn unsupported<S: Into<String>>(msg: S) -> RedisProtocolError {
+  RedisProtocolError::new(RedisProtocolErrorKind::Unknown, msg.into())
+}
+
+fn downgrade_all<I>(frames: I, policy: &DowngradePolicy) -> Result<Vec<Resp2Frame>, RedisProtocolError>
+where
+  I: IntoIterator<Item = Frame>,
+{
+  frames.into_iter().map(|f| downgrade_for_resp2(f, policy)).collect()
+}
+
+/// Convert a RESP3 frame to a RESP2 frame using `policy` for any types that do not exist in RESP2.
+pub fn downgrade_for_resp2(frame: Frame, policy: &DowngradePolicy) -> Result<Resp2Frame, RedisProtocolError> {
+  if policy.attributes == AttributePolicy::Error && frame.attributes().is_some() {
+    return Err(unsupported("Cannot convert RESP3 frame with attributes to RESP2."));
+  }
+
+  match frame {
+    Frame::BlobString { data, .. } => Ok(Resp2Frame::BulkString(data)),
+    Frame::SimpleString { data, .. } => data
+      .into_string()
+      .map(Resp2Frame::SimpleString)
+      .map_err(|_| unsupported("Cannot convert non UTF-8 SimpleString to RESP2.")),
+    Frame::SimpleError { data, .. } => data
+      .into_string()
+      .map(Resp2Frame::Error)
+      .map_err(|_| unsupported("Cannot convert non UTF-8 SimpleError to RESP2.")),
+    Frame::BlobError { data, .. } => String::from_utf8(data)
+      .map(Resp2Frame::Error)
+      .map_err(|_| unsupported("Cannot convert non UTF-8 BlobError to RESP2.")),
+    Frame::Number { data, .. } => Ok(Resp2Frame::Integer(data)),
+    Frame::Null { .. } => Ok(Resp2Frame::Null),
+    Frame::Array { data, .. } => Ok(Resp2Frame::Array(downgrade_all(data, policy)?)),
+    Frame::Boolean { data, .. } => match policy.boolean {
+      BooleanPolicy::Integer => Ok(Resp2Frame::Integer(data as i64)),
+      BooleanPolicy::String => Ok(Resp2Frame::BulkString(data.to_string().into_bytes())),
+      BooleanPolicy::Error => Err(unsupported("Cannot convert Boolean to RESP2.")),
+    },
+    Frame::Double { data, .. } => match policy.double {
+      DoublePolicy::BulkString => Ok(Resp2Frame::BulkString(
+        resp3_utils::f64_to_redis_string(&data).into_owned().into_bytes(),
+      )),
+      DoublePolicy::Error => Err(unsupported("Cannot convert Double to RESP2.")),
+    },
+    Frame::BigNumber { data, .. } => match policy.big_number {
+      BigNumberPolicy::BulkString => Ok(Resp2Frame::BulkString(data)),
+      BigNumberPolicy::Error => Err(unsupported("Cannot convert BigNumber to RESP2.")),
+    },
+    Frame::VerbatimString { data, format, .. } => match policy.verbatim_string {
+      VerbatimStringPolicy::BulkString => Ok(Resp2Frame::BulkString(data)),
+      VerbatimStringPolicy::WithFormat => {
+        let mut out = Vec::with_capacity(format.encode_len() + data.len());
+        out.extend_from_slice(format.to_str().as_bytes());
+        out.push(b':');
+        out.extend_from_slice(&data);
+        Ok(Resp2Frame::BulkString(out))
+      }
+      VerbatimStringPolicy::Error => Err(unsupported("Cannot convert VerbatimString to RESP2.")),
+    },
+    Frame::Map { data, .. } => match policy.map {
+      MapPolicy::FlatArray => {
+        let mut out = Vec::with_capacity(data.len() * 2);
+        for (key, value) in data.into_iter() {
+          out.push(downgrade_for_resp2(key, policy)?);
+          out.push(downgrade_for_resp2(value, policy)?);
+        }
+        Ok(Resp2Frame::Array(out))
+      }
+      MapPolicy::Pairs => {
+        let mut out = Vec::with_capacity(data.len());
+        for (key, value) in data.into_iter() {
+          out.push(Resp2Frame::Array(vec![
+            downgrade_for_resp2(key, policy)?,
+            downgrade_for_resp2(value, policy)?,
+          ]));
+        }
+        Ok(Resp2Frame::Array(out))
+      }
+      MapPolicy::Error => Err(unsupported("Cannot convert Map to RESP2.")),
+    },
+    Frame::Set { data, .. } => match policy.set {
+      SetPolicy::Array => Ok(Resp2Frame::Array(downgrade_all(data, policy)?)),
+      SetPolicy::Error => Err(unsupported("Cannot convert Set to RESP2.")),
+    },
+    Frame::Push { mut data, .. } => match policy.push {
+      PushPolicy::Array => {
+        let is_pubsub = data
+          .first()
+          .and_then(|f| f.as_str())
+          .map(|s| s == PUBSUB_PUSH_PREFIX)
+          .unwrap_or(false);
+        if is_pubsub {
+          data.remove(0);
+        }
+
+        Ok(Resp2Frame::Array(downgrade_all(data, policy)?))
+      }
+      PushPolicy::Error => Err(unsupported("Cannot convert Push to RESP2.")),
+    },
+    Frame::Hello { .. } => Err(unsupported("Cannot convert HELLO to RESP2.")),
+    Frame::ChunkedString(_) => Err(unsupported("Cannot convert ChunkedString to RESP2.")),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::test_frames::*;
+  use crate::resp3::types::VerbatimStringFormat;
+  use crate::resp3::utils::{new_map, new_set};
+
+  fn bulk(s: &str) -> Resp2Frame {
+    Resp2Frame::BulkString(s.as_bytes().to_vec())
+  }
+
+  #[test]
+  fn should_downgrade_with_default_policy() {
+    let mut map = new_map(None);
+    map.insert(blob("a"), true.into());
+    let mut set = new_set(None);
+    set.insert(Frame::Double {
+      data: 1.5,
+      attributes: None,
+    });
+    let mut attributes = new_map(None);
+    attributes.insert(blob("ttl"), 1.into());
+
+    let frame = Frame::Array {
+      data: vec![
+        Frame::Map {
+          data: map,
+          attributes: Some(attributes),
+        },
+        Frame::Set {
+          data: set,
+          attributes: None,
+        },
+        Frame::VerbatimString {
+          data: b"hello".to_vec(),
+          format: VerbatimStringFormat::Text,
+          attributes: None,
+        },
+        Frame::BigNumber {
+          data: b"123456789012345678901234567890".to_vec(),
+          attributes: None,
+        },
+      ],
+      attributes: None,
+    };
+
+    assert_eq!(
+      downgrade_for_resp2(frame, &DowngradePolicy::default()).unwrap(),
+      Resp2Frame::Array(vec![
+        Resp2Frame::Array(vec![bulk("a"), Resp2Frame::Integer(1)]),
+        Resp2Frame::Array(vec![bulk("1.5")]),
+        bulk("hello"),
+        bulk("123456789012345678901234567890"),
+      ])
+    );
+  }
+
+  #[test]
+  fn should_downgrade_with_custom_policy() {
+    let policy = DowngradePolicy {
+      boolean: BooleanPolicy::String,
+      map: MapPolicy::Pairs,
+      verbatim_string: VerbatimStringPolicy::WithFormat,
+      ..DowngradePolicy::default()
+    };
+    let mut map = new_map(None);
+    map.insert(blob("a"), false.into());
+    let frame = Frame::Map {
+      data: map,
+      attributes: None,
+    };
+    let verbatim = Frame::VerbatimString {
+      data: b"# title".to_vec(),
+      format: VerbatimStringFormat::Markdown,
+      attributes: None,
+    };
+
+    assert_eq!(
+      downgrade_for_resp2(frame, &policy).unwrap(),
+      Resp2Frame::Array(vec![Resp2Frame::Array(vec![bulk("a"), bulk("false")])])
+    );
+    assert_eq!(downgrade_for_resp2(verbatim, &policy).unwrap(), bulk("mkd:# title"));
+  }
+
+  #[test]
+  fn should_downgrade_pubsub_push() {
+    let frame = Frame::Push {
+      data: vec![blob("pubsub"), blob("message"), blob("foo"), blob("bar")],
+      attributes: None,
+    };
+
+    assert_eq!(
+      downgrade_for_resp2(frame, &DowngradePolicy::default()).unwrap(),
+      Resp2Frame::Array(vec![bulk("message"), bulk("foo"), bulk("bar")])
+    );
+  }
+
+  #[test]
+  fn should_error_with_strict_policy() {
+    let policy = DowngradePolicy::strict();
+    let mut attributes = new_map(None);
+    attributes.insert(blob("ttl"), 1.into());
+
+    assert!(downgrade_for_resp2(true.into(), &policy).is_err());
+    assert!(downgrade_for_resp2(
+      Frame::Array {
+        data: vec![],
+        attributes: Some(attributes),
+      },
+      &policy
+    )
+    .is_err());
+    assert!(downgrade_for_resp2(Frame::new_end_stream(), &DowngradePolicy::default()).is_err());
+    assert_eq!(downgrade_for_resp2(1.into(), &policy).unwrap(), Resp2Frame::Integer(1));
+  }
+}
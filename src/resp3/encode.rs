@@ -2,6 +2,8 @@
 //!
 //! <https://github.com/antirez/RESP3/blob/master/spec.md>
 
+use crate::core;
+use crate::observer::{self, FrameObserver};
 use crate::resp3::types::*;
 use crate::resp3::utils::{self as resp3_utils};
 use crate::types::{RedisProtocolError, RedisProtocolErrorKind, CRLF};
@@ -10,53 +12,53 @@ use bytes::BytesMut;
 use cookie_factory::GenError;
 
 macro_rules! encode_attributes (
-  ($x:ident, $attributes:ident) => {
+  ($x:ident, $attributes:ident, $format:ident) => {
     if let Some(ref attributes) = *$attributes {
-      $x = gen_attribute($x, attributes)?;
+      $x = gen_attribute($x, attributes, $format)?;
     }
   }
 );
 
 fn gen_simplestring<'a>(
   mut x: (&'a mut [u8], usize),
-  data: &str,
+  data: &[u8],
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
 
-  do_gen!(
-    x,
-    gen_be_u8!(FrameKind::SimpleString.to_byte()) >> gen_slice!(data.as_bytes()) >> gen_slice!(CRLF.as_bytes())
-  )
+  core::gen_line(x, FrameKind::SimpleString.to_byte(), data)
 }
 
 fn gen_simpleerror<'a>(
   mut x: (&'a mut [u8], usize),
-  data: &str,
+  data: &[u8],
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
 
-  do_gen!(
-    x,
-    gen_be_u8!(FrameKind::SimpleError.to_byte()) >> gen_slice!(data.as_bytes()) >> gen_slice!(CRLF.as_bytes())
-  )
+  core::gen_line(x, FrameKind::SimpleError.to_byte(), data)
 }
 
 fn gen_number<'a>(
   mut x: (&'a mut [u8], usize),
   data: &i64,
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
 
-  do_gen!(
-    x,
-    gen_be_u8!(FrameKind::Number.to_byte()) >> gen_slice!(data.to_string().as_bytes()) >> gen_slice!(CRLF.as_bytes())
-  )
+  core::gen_line(x, FrameKind::Number.to_byte(), data.to_string().as_bytes())
 }
 
-fn gen_null(x: (&mut [u8], usize)) -> Result<(&mut [u8], usize), GenError> {
+fn gen_null<'a>(
+  mut x: (&'a mut [u8], usize),
+  attributes: &Option<Attributes>,
+  config: &EncodeConfig,
+) -> Result<(&'a mut [u8], usize), GenError> {
+  encode_attributes!(x, attributes, config);
+
   do_gen!(x, gen_slice!(NULL.as_bytes()))
 }
 
@@ -64,22 +66,21 @@ fn gen_double<'a>(
   mut x: (&'a mut [u8], usize),
   data: &f64,
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
 
-  let as_string = resp3_utils::f64_to_redis_string(data);
-  do_gen!(
-    x,
-    gen_be_u8!(FrameKind::Double.to_byte()) >> gen_slice!(as_string.as_bytes()) >> gen_slice!(CRLF.as_bytes())
-  )
+  let as_string = config.double_format.format(*data);
+  core::gen_line(x, FrameKind::Double.to_byte(), as_string.as_bytes())
 }
 
 fn gen_boolean<'a>(
   mut x: (&'a mut [u8], usize),
   data: &bool,
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
 
   let data = if *data { BOOL_TRUE_BYTES } else { BOOL_FALSE_BYTES };
   do_gen!(x, gen_slice!(data.as_bytes()))
@@ -89,47 +90,33 @@ fn gen_bignumber<'a>(
   mut x: (&'a mut [u8], usize),
   data: &[u8],
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
 
-  do_gen!(
-    x,
-    gen_be_u8!(FrameKind::BigNumber.to_byte()) >> gen_slice!(data) >> gen_slice!(CRLF.as_bytes())
-  )
+  core::gen_line(x, FrameKind::BigNumber.to_byte(), data)
 }
 
 fn gen_blobstring<'a>(
   mut x: (&'a mut [u8], usize),
   data: &[u8],
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
 
-  do_gen!(
-    x,
-    gen_be_u8!(FrameKind::BlobString.to_byte())
-      >> gen_slice!(data.len().to_string().as_bytes())
-      >> gen_slice!(CRLF.as_bytes())
-      >> gen_slice!(data)
-      >> gen_slice!(CRLF.as_bytes())
-  )
+  core::gen_blob(x, FrameKind::BlobString.to_byte(), data)
 }
 
 fn gen_bloberror<'a>(
   mut x: (&'a mut [u8], usize),
   data: &[u8],
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
 
-  do_gen!(
-    x,
-    gen_be_u8!(FrameKind::BlobError.to_byte())
-      >> gen_slice!(data.len().to_string().as_bytes())
-      >> gen_slice!(CRLF.as_bytes())
-      >> gen_slice!(data)
-      >> gen_slice!(CRLF.as_bytes())
-  )
+  core::gen_blob(x, FrameKind::BlobError.to_byte(), data)
 }
 
 fn gen_verbatimstring<'a>(
@@ -137,16 +124,15 @@ fn gen_verbatimstring<'a>(
   data: &[u8],
   format: &VerbatimStringFormat,
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
   let total_len = format.encode_len() + data.len();
 
+  let x = core::gen_length_prefix(x, FrameKind::VerbatimString.to_byte(), total_len)?;
   do_gen!(
     x,
-    gen_be_u8!(FrameKind::VerbatimString.to_byte())
-      >> gen_slice!(total_len.to_string().as_bytes())
-      >> gen_slice!(CRLF.as_bytes())
-      >> gen_slice!(format.to_str().as_bytes())
+    gen_slice!(format.to_str().as_bytes())
       >> gen_be_u8!(VERBATIM_FORMAT_BYTE)
       >> gen_slice!(data)
       >> gen_slice!(CRLF.as_bytes())
@@ -157,18 +143,34 @@ fn gen_array<'a>(
   mut x: (&'a mut [u8], usize),
   data: &Vec<Frame>,
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
 
-  let mut x = do_gen!(
-    x,
-    gen_be_u8!(FrameKind::Array.to_byte())
-      >> gen_slice!(data.len().to_string().as_bytes())
-      >> gen_slice!(CRLF.as_bytes())
-  )?;
+  let mut x = core::gen_length_prefix(x, FrameKind::Array.to_byte(), data.len())?;
 
   for frame in data.iter() {
-    x = attempt_encoding(x.0, x.1, frame)?;
+    x = attempt_encoding(x.0, x.1, frame, config)?;
+  }
+
+  Ok(x)
+}
+
+fn gen_map_entries<'a>(
+  mut x: (&'a mut [u8], usize),
+  data: &FrameMap,
+  config: &EncodeConfig,
+) -> Result<(&'a mut [u8], usize), GenError> {
+  if config.canonical {
+    for (key, value) in data.sorted() {
+      x = attempt_encoding(x.0, x.1, key, config)?;
+      x = attempt_encoding(x.0, x.1, value, config)?;
+    }
+  } else {
+    for (key, value) in data.iter() {
+      x = attempt_encoding(x.0, x.1, key, config)?;
+      x = attempt_encoding(x.0, x.1, value, config)?;
+    }
   }
 
   Ok(x)
@@ -178,77 +180,60 @@ fn gen_map<'a>(
   mut x: (&'a mut [u8], usize),
   data: &FrameMap,
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
-
-  x = do_gen!(
-    x,
-    gen_be_u8!(FrameKind::Map.to_byte())
-      >> gen_slice!(data.len().to_string().as_bytes())
-      >> gen_slice!(CRLF.as_bytes())
-  )?;
+  encode_attributes!(x, attributes, config);
 
-  for (key, value) in data.iter() {
-    x = attempt_encoding(x.0, x.1, key)?;
-    x = attempt_encoding(x.0, x.1, value)?;
-  }
+  x = core::gen_length_prefix(x, FrameKind::Map.to_byte(), data.len())?;
 
-  Ok(x)
+  gen_map_entries(x, data, config)
 }
 
 fn gen_set<'a>(
   mut x: (&'a mut [u8], usize),
   data: &FrameSet,
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
 
-  x = do_gen!(
-    x,
-    gen_be_u8!(FrameKind::Set.to_byte())
-      >> gen_slice!(data.len().to_string().as_bytes())
-      >> gen_slice!(CRLF.as_bytes())
-  )?;
+  x = core::gen_length_prefix(x, FrameKind::Set.to_byte(), data.len())?;
 
-  for frame in data.iter() {
-    x = attempt_encoding(x.0, x.1, frame)?;
+  if config.canonical {
+    for frame in data.sorted() {
+      x = attempt_encoding(x.0, x.1, frame, config)?;
+    }
+  } else {
+    for frame in data.iter() {
+      x = attempt_encoding(x.0, x.1, frame, config)?;
+    }
   }
 
   Ok(x)
 }
 
-fn gen_attribute<'a>(x: (&'a mut [u8], usize), data: &FrameMap) -> Result<(&'a mut [u8], usize), GenError> {
-  let mut x = do_gen!(
-    x,
-    gen_be_u8!(FrameKind::Attribute.to_byte())
-      >> gen_slice!(data.len().to_string().as_bytes())
-      >> gen_slice!(CRLF.as_bytes())
-  )?;
-
-  for (key, value) in data.iter() {
-    x = attempt_encoding(x.0, x.1, key)?;
-    x = attempt_encoding(x.0, x.1, value)?;
-  }
+fn gen_attribute<'a>(
+  x: (&'a mut [u8], usize),
+  data: &FrameMap,
+  config: &EncodeConfig,
+) -> Result<(&'a mut [u8], usize), GenError> {
+  let x = core::gen_length_prefix(x, FrameKind::Attribute.to_byte(), data.len())?;
 
-  Ok(x)
+  gen_map_entries(x, data, config)
 }
 
 fn gen_push<'a>(
   mut x: (&'a mut [u8], usize),
   data: &Vec<Frame>,
   attributes: &Option<Attributes>,
+  config: &EncodeConfig,
 ) -> Result<(&'a mut [u8], usize), GenError> {
-  encode_attributes!(x, attributes);
+  encode_attributes!(x, attributes, config);
 
-  x = do_gen!(
-    x,
-    gen_be_u8!(FrameKind::Push.to_byte())
-      >> gen_slice!(data.len().to_string().as_bytes())
-      >> gen_slice!(CRLF.as_bytes())
-  )?;
+  x = core::gen_length_prefix(x, FrameKind::Push.to_byte(), data.len())?;
 
   for frame in data.iter() {
-    x = attempt_encoding(x.0, x.1, frame)?;
+    x = attempt_encoding(x.0, x.1, frame, config)?;
   }
 
   Ok(x)
@@ -287,81 +272,79 @@ fn gen_chunked_string<'a>(x: (&'a mut [u8], usize), data: &[u8]) -> Result<(&'a
     // signal the end of the chunked stream
     do_gen!(x, gen_slice!(END_STREAM_STRING_BYTES.as_bytes()))
   } else {
-    do_gen!(
-      x,
-      gen_be_u8!(FrameKind::ChunkedString.to_byte())
-        >> gen_slice!(data.len().to_string().as_bytes())
-        >> gen_slice!(CRLF.as_bytes())
-        >> gen_slice!(data)
-        >> gen_slice!(CRLF.as_bytes())
-    )
+    core::gen_blob(x, FrameKind::ChunkedString.to_byte(), data)
   }
 }
 
-fn attempt_encoding<'a>(buf: &'a mut [u8], offset: usize, frame: &Frame) -> Result<(&'a mut [u8], usize), GenError> {
+fn attempt_encoding<'a>(
+  buf: &'a mut [u8],
+  offset: usize,
+  frame: &Frame,
+  config: &EncodeConfig,
+) -> Result<(&'a mut [u8], usize), GenError> {
   use crate::resp3::types::Frame::*;
 
   let x = (buf, offset);
-  let total_size = resp3_utils::encode_len(frame)?;
-  trace!("Attempting to encode {:?} with total size {}", frame.kind(), total_size);
+  let total_size = resp3_utils::encode_len_with_format(frame, config.double_format)?;
+  _trace!("Attempting to encode {} with total size {}", frame.kind(), total_size);
   encode_checks!(x, total_size);
 
   match *frame {
     Array {
       ref data,
       ref attributes,
-    } => gen_array(x, data, attributes),
+    } => gen_array(x, data, attributes, config),
     BlobString {
       ref data,
       ref attributes,
-    } => gen_blobstring(x, data, attributes),
+    } => gen_blobstring(x, data, attributes, config),
     SimpleString {
       ref data,
       ref attributes,
-    } => gen_simplestring(x, data, attributes),
+    } => gen_simplestring(x, data.as_bytes(), attributes, config),
     SimpleError {
       ref data,
       ref attributes,
-    } => gen_simpleerror(x, data, attributes),
+    } => gen_simpleerror(x, data.as_bytes(), attributes, config),
     Number {
       ref data,
       ref attributes,
-    } => gen_number(x, data, attributes),
-    Null => gen_null(x),
+    } => gen_number(x, data, attributes, config),
+    Null { ref attributes } => gen_null(x, attributes, config),
     Double {
       ref data,
       ref attributes,
-    } => gen_double(x, data, attributes),
+    } => gen_double(x, data, attributes, config),
     BlobError {
       ref data,
       ref attributes,
-    } => gen_bloberror(x, data, attributes),
+    } => gen_bloberror(x, data, attributes, config),
     VerbatimString {
       ref data,
       ref format,
       ref attributes,
-    } => gen_verbatimstring(x, data, format, attributes),
+    } => gen_verbatimstring(x, data, format, attributes, config),
     Boolean {
       ref data,
       ref attributes,
-    } => gen_boolean(x, data, attributes),
+    } => gen_boolean(x, data, attributes, config),
     Map {
       ref data,
       ref attributes,
-    } => gen_map(x, data, attributes),
+    } => gen_map(x, data, attributes, config),
     Set {
       ref data,
       ref attributes,
-    } => gen_set(x, data, attributes),
+    } => gen_set(x, data, attributes, config),
     Push {
       ref data,
       ref attributes,
-    } => gen_push(x, data, attributes),
+    } => gen_push(x, data, attributes, config),
     Hello { ref version, ref auth } => gen_hello(x, version, auth),
     BigNumber {
       ref data,
       ref attributes,
-    } => gen_bignumber(x, data, attributes),
+    } => gen_bignumber(x, data, attributes, config),
     ChunkedString(ref b) => gen_chunked_string(x, b),
   }
 }
@@ -393,28 +376,138 @@ fn attempt_encoding<'a>(buf: &'a mut [u8], offset: usize, frame: &Frame) -> Resu
 pub mod complete {
   use super::*;
 
+  /// Check the encoded length of the frame against the `max_len` option, if set.
+  fn check_max_len(frame: &Frame, config: &EncodeConfig) -> Result<(), RedisProtocolError> {
+    let max = match config.max_len {
+      Some(max) => max,
+      None => return Ok(()),
+    };
+
+    match resp3_utils::encode_len_within(frame, config.double_format, max)? {
+      Some(_) => Ok(()),
+      None => Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::FrameTooLarge(max),
+        format!("Encoded frame is larger than {} bytes.", max),
+      )),
+    }
+  }
+
   /// Attempt to encode a frame into `buf` at the provided `offset`.
   ///
-  /// The caller is responsible for extending the buffer if a `RedisProtocolErrorKind::BufferTooSmall` is returned.
+  /// If the buffer is too small a `RedisProtocolErrorKind::SliceTooSmall` error is returned with the minimum length of
+  /// the buffer, including the bytes before `offset`. The caller is responsible for resizing the buffer and retrying.
   pub fn encode(buf: &mut [u8], offset: usize, frame: &Frame) -> Result<usize, RedisProtocolError> {
-    attempt_encoding(buf, offset, frame)
-      .map(|(_, amt)| amt)
-      .map_err(|e| e.into())
+    encode_with_config(buf, offset, frame, &EncodeConfig::default())
+  }
+
+  /// Attempt to encode a frame into `buf` at the provided `offset` with the provided encoding options.
+  ///
+  /// If the buffer is too small a `RedisProtocolErrorKind::SliceTooSmall` error is returned with the minimum length of
+  /// the buffer, including the bytes before `offset`. The caller is responsible for resizing the buffer and retrying.
+  pub fn encode_with_config(
+    buf: &mut [u8],
+    offset: usize,
+    frame: &Frame,
+    config: &EncodeConfig,
+  ) -> Result<usize, RedisProtocolError> {
+    let len = buf.len();
+    let result = check_max_len(frame, config).and_then(|_| {
+      attempt_encoding(buf, offset, frame, config)
+        .map(|(_, amt)| amt)
+        .map_err(|e| core::encode_error(len, e))
+    });
+
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_encode(crate::metrics::RESP3, frame.kind(), offset, &result);
+    result
   }
 
   /// Attempt to encode a frame into `buf`, extending the buffer as needed.
   ///
   /// Returns the number of bytes encoded.
   pub fn encode_bytes(buf: &mut BytesMut, frame: &Frame) -> Result<usize, RedisProtocolError> {
+    encode_bytes_with_config(buf, frame, &EncodeConfig::default())
+  }
+
+  /// Attempt to encode a frame into `buf` with the provided encoding options, extending the buffer as needed.
+  ///
+  /// Returns the number of bytes encoded.
+  pub fn encode_bytes_with_config(
+    buf: &mut BytesMut,
+    frame: &Frame,
+    config: &EncodeConfig,
+  ) -> Result<usize, RedisProtocolError> {
+    #[cfg(feature = "metrics")]
     let offset = buf.len();
+    let result = check_max_len(frame, config).and_then(|_| {
+      core::encode_extend(buf, |buf, offset| {
+        attempt_encoding(buf, offset, frame, config).map(|(_, amt)| amt)
+      })
+    });
 
-    loop {
-      match attempt_encoding(buf, offset, frame) {
-        Ok((_, amt)) => return Ok(amt),
-        Err(GenError::BufferTooSmall(amt)) => utils::zero_extend(buf, amt),
-        Err(e) => return Err(e.into()),
-      }
-    }
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_encode(crate::metrics::RESP3, frame.kind(), offset, &result);
+    result
+  }
+
+  /// Attempt to encode a frame into `buf` with the provided encoding options, reporting the result to `observer`.
+  ///
+  /// See [encode_bytes_with_config] for more information.
+  pub fn encode_bytes_with_observer(
+    buf: &mut BytesMut,
+    frame: &Frame,
+    config: &EncodeConfig,
+    observer: &dyn FrameObserver,
+  ) -> Result<usize, RedisProtocolError> {
+    let (start, offset) = (observer::Stopwatch::start(), buf.len());
+    let result = encode_bytes_with_config(buf, frame, config);
+    observer::observe_encode(observer, start, frame.kind(), offset, &result);
+    result
+  }
+
+  /// Encode a frame into a scratch buffer and check that the number of bytes written matches `expected_len` and the
+  /// length computed by [Frame::encode_len].
+  ///
+  /// This is intended for debug builds and tests of codecs that pre-compute frame lengths, since a mismatch corrupts
+  /// every frame that follows on the connection.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::encode::complete::verify_encode;
+  /// # use redis_protocol::resp3::types::Frame;
+  /// assert!(verify_encode(&Frame::from(42), 5).is_ok());
+  /// assert!(verify_encode(&Frame::from(42), 4).is_err());
+  /// ```
+  pub fn verify_encode(frame: &Frame, expected_len: usize) -> Result<(), RedisProtocolError> {
+    verify_encode_with_config(frame, expected_len, &EncodeConfig::default())
+  }
+
+  /// Encode a frame into a scratch buffer with the provided encoding options and check the number of bytes written.
+  ///
+  /// See [verify_encode] for more information.
+  pub fn verify_encode_with_config(
+    frame: &Frame,
+    expected_len: usize,
+    config: &EncodeConfig,
+  ) -> Result<(), RedisProtocolError> {
+    let computed = resp3_utils::encode_len_with_format(frame, config.double_format)?;
+    let mut buf = BytesMut::with_capacity(computed);
+    let written = encode_bytes_with_config(&mut buf, frame, config)?;
+
+    core::check_encoded_len(&buf, written, computed, expected_len)
+  }
+
+  /// Encode a command as an array of blob strings, extending the buffer as needed.
+  ///
+  /// This is equivalent to encoding a `Frame::Array` of `Frame::BlobString` arguments, but avoids allocating the
+  /// frames.
+  ///
+  /// Returns the number of bytes encoded.
+  pub fn encode_command<I, A>(buf: &mut BytesMut, args: I) -> usize
+  where
+    I: IntoIterator<Item = A>,
+    A: AsRef<[u8]>,
+  {
+    utils::encode_command_args(buf, args)
   }
 }
 
@@ -546,14 +639,7 @@ pub mod streaming {
   fn gen_streaming_string_chunk<'a>(x: (&'a mut [u8], usize), data: &[u8]) -> Result<(&'a mut [u8], usize), GenError> {
     encode_checks!(x, resp3_utils::blobstring_encode_len(data));
 
-    do_gen!(
-      x,
-      gen_be_u8!(CHUNKED_STRING_BYTE)
-        >> gen_slice!(data.len().to_string().as_bytes())
-        >> gen_slice!(CRLF.as_bytes())
-        >> gen_slice!(data)
-        >> gen_slice!(CRLF.as_bytes())
-    )
+    core::gen_blob(x, CHUNKED_STRING_BYTE, data)
   }
 
   fn gen_end_streaming_string(x: (&mut [u8], usize)) -> Result<(&mut [u8], usize), GenError> {
@@ -587,7 +673,7 @@ pub mod streaming {
     x: (&'a mut [u8], usize),
     data: &Frame,
   ) -> Result<(&'a mut [u8], usize), GenError> {
-    attempt_encoding(x.0, x.1, data)
+    attempt_encoding(x.0, x.1, data, &EncodeConfig::default())
   }
 
   fn gen_streaming_inner_kv_pair_frames<'a>(
@@ -595,31 +681,34 @@ pub mod streaming {
     key: &Frame,
     value: &Frame,
   ) -> Result<(&'a mut [u8], usize), GenError> {
-    let x = attempt_encoding(x.0, x.1, key)?;
-    attempt_encoding(x.0, x.1, value)
+    let x = attempt_encoding(x.0, x.1, key, &EncodeConfig::default())?;
+    attempt_encoding(x.0, x.1, value, &EncodeConfig::default())
   }
 
   /// Encode the starting bytes for a streaming blob string.
   pub fn encode_start_string(buf: &mut [u8], offset: usize) -> Result<usize, RedisProtocolError> {
+    let len = buf.len();
     gen_start_streaming_string((buf, offset))
       .map(|(_, l)| l)
-      .map_err(|e| e.into())
+      .map_err(|e| core::encode_error(len, e))
   }
 
   /// Encode the bytes making up one chunk of a streaming blob string.
   ///
   /// If `data` is empty this will do the same thing as [encode_end_string] to signal that the streamed string is finished.
   pub fn encode_string_chunk(buf: &mut [u8], offset: usize, data: &[u8]) -> Result<usize, RedisProtocolError> {
+    let len = buf.len();
     gen_streaming_string_chunk((buf, offset), data)
       .map(|(_, l)| l)
-      .map_err(|e| e.into())
+      .map_err(|e| core::encode_error(len, e))
   }
 
   /// Encode the terminating bytes at the end of a streaming blob string.
   pub fn encode_end_string(buf: &mut [u8], offset: usize) -> Result<usize, RedisProtocolError> {
+    let len = buf.len();
     gen_end_streaming_string((buf, offset))
       .map(|(_, l)| l)
-      .map_err(|e| e.into())
+      .map_err(|e| core::encode_error(len, e))
   }
 
   /// Encode the starting bytes for a streaming aggregate type (array, set, or map).
@@ -628,9 +717,10 @@ pub mod streaming {
     offset: usize,
     kind: &FrameKind,
   ) -> Result<usize, RedisProtocolError> {
+    let len = buf.len();
     gen_start_streaming_aggregate_type((buf, offset), kind)
       .map(|(_, l)| l)
-      .map_err(|e| e.into())
+      .map_err(|e| core::encode_error(len, e))
   }
 
   /// Encode the inner frame inside a streamed array or set.
@@ -641,9 +731,10 @@ pub mod streaming {
     offset: usize,
     data: &Frame,
   ) -> Result<usize, RedisProtocolError> {
+    let len = buf.len();
     gen_streaming_inner_value_frame((buf, offset), data)
       .map(|(_, l)| l)
-      .map_err(|e| e.into())
+      .map_err(|e| core::encode_error(len, e))
   }
 
   /// Encode the inner frames that make up a key-value pair in a streamed map.
@@ -653,16 +744,18 @@ pub mod streaming {
     key: &Frame,
     value: &Frame,
   ) -> Result<usize, RedisProtocolError> {
+    let len = buf.len();
     gen_streaming_inner_kv_pair_frames((buf, offset), key, value)
       .map(|(_, l)| l)
-      .map_err(|e| e.into())
+      .map_err(|e| core::encode_error(len, e))
   }
 
   /// Encode the terminating bytes at the end of a streaming aggregate type (array, set, or map).
   pub fn encode_end_aggregate_type(buf: &mut [u8], offset: usize) -> Result<usize, RedisProtocolError> {
+    let len = buf.len();
     gen_end_streaming_aggregate_type((buf, offset))
       .map(|(_, l)| l)
-      .map_err(|e| e.into())
+      .map_err(|e| core::encode_error(len, e))
   }
 
   /// A wrapper function for automatically extending the input buffer while encoding frames with a different encoding function.
@@ -674,7 +767,11 @@ pub mod streaming {
       match func(buf) {
         Ok(amt) => return Ok(amt),
         Err(err) => match err.kind() {
-          RedisProtocolErrorKind::BufferTooSmall(amt) => utils::zero_extend(buf, *amt),
+          RedisProtocolErrorKind::BufferTooSmall(amt) => core::zero_extend(buf, *amt),
+          RedisProtocolErrorKind::SliceTooSmall { required } if *required > buf.len() => {
+            let amt = *required - buf.len();
+            core::zero_extend(buf, amt)
+          },
           _ => return Err(err),
         },
       }
@@ -685,7 +782,7 @@ pub mod streaming {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::utils::ZEROED_KB;
+  use crate::core::ZEROED_KB;
   use std::convert::TryInto;
   use std::str;
 
@@ -833,6 +930,45 @@ mod tests {
     );
   }
 
+  /// Encode a frame that contains a map or set, comparing the decoded frame and the length rather than the bytes since
+  /// the order of the entries depends on the map backend and hasher.
+  fn encode_and_verify_unordered(input: &Frame, expected: &str) {
+    let (attributes, encoded_attributes) = create_attributes();
+    let mut with_attributes = input.clone();
+    with_attributes.add_attributes(attributes).unwrap();
+
+    let decoded = crate::resp3::decode::complete::decode(expected.as_bytes())
+      .unwrap()
+      .unwrap();
+    assert_eq!(
+      decoded,
+      (input.clone(), expected.len()),
+      "expected bytes decode to the input"
+    );
+
+    for (frame, expected_len) in [
+      (input, expected.len()),
+      (&with_attributes, expected.len() + encoded_attributes.len()),
+    ]
+    .iter()
+    {
+      for padding in ["", PADDING].iter() {
+        let mut buf = BytesMut::from(*padding);
+        let len = complete::encode_bytes(&mut buf, frame).unwrap();
+        assert_eq!(len, padding.len() + expected_len, "encoded len is correct");
+
+        let decoded = crate::resp3::decode::complete::decode(&buf[padding.len()..])
+          .unwrap()
+          .unwrap();
+        assert_eq!(
+          decoded,
+          ((*frame).clone(), *expected_len),
+          "encoded bytes decode to the input"
+        );
+      }
+    }
+  }
+
   // ------------- tests adapted from RESP2 --------------------------
 
   #[test]
@@ -883,7 +1019,7 @@ mod tests {
   fn should_encode_array_null_test() {
     let expected = "*3\r\n$4\r\nHSET\r\n$3\r\nfoo\r\n_\r\n";
     let mut input = blobstring_array(vec!["HSET", "foo"]);
-    push_frame_to_array(&mut input, Frame::Null);
+    push_frame_to_array(&mut input, Frame::Null { attributes: None });
 
     encode_and_verify_empty(&input, expected);
     encode_and_verify_non_empty(&input, expected);
@@ -925,7 +1061,7 @@ mod tests {
   fn should_encode_raw_array_null_test() {
     let expected = "*3\r\n$4\r\nHSET\r\n$3\r\nfoo\r\n_\r\n";
     let mut input = blobstring_array(vec!["HSET", "foo"]);
-    push_frame_to_array(&mut input, Frame::Null);
+    push_frame_to_array(&mut input, Frame::Null { attributes: None });
 
     encode_raw_and_verify_empty(&input, expected);
   }
@@ -1050,15 +1186,27 @@ mod tests {
   }
 
   #[test]
-  #[should_panic]
-  fn should_not_encode_double_nan() {
-    // force NaN into the frame avoiding try_into, which also checks for NaN
-    let input = Frame::Double {
-      data: f64::NAN,
-      attributes: None,
-    };
-    let mut buf = empty_bytes();
-    let _ = complete::encode_bytes(&mut buf, &input).unwrap();
+  fn should_encode_double_nan() {
+    let expected = ",nan\r\n";
+    let input: Frame = f64::NAN.try_into().unwrap();
+
+    encode_and_verify_empty(&input, expected);
+    encode_and_verify_non_empty(&input, expected);
+    encode_and_verify_empty_with_attributes(&input, expected);
+    encode_and_verify_non_empty_with_attributes(&input, expected);
+  }
+
+  #[test]
+  fn should_round_trip_special_doubles() {
+    for value in [f64::INFINITY, f64::NEG_INFINITY, f64::NAN].iter() {
+      let input: Frame = (*value).try_into().unwrap();
+      let mut buf = empty_bytes();
+      complete::encode_bytes(&mut buf, &input).unwrap();
+
+      let (decoded, len) = crate::resp3::decode::complete::decode(&buf).unwrap().unwrap();
+      assert_eq!(decoded, input);
+      assert_eq!(len, buf.len());
+    }
   }
 
   #[test]
@@ -1083,6 +1231,170 @@ mod tests {
     encode_and_verify_non_empty_with_attributes(&input, expected);
   }
 
+  #[test]
+  fn should_encode_doubles_like_redis() {
+    let cases: &[(f64, &str)] = &[
+      (0.0, "0"),
+      (-0.0, "-0"),
+      (1.0, "1"),
+      (-42.0, "-42"),
+      (1.5, "1.5"),
+      (0.1 + 0.2, "0.30000000000000004"),
+      (1.23456, "1.23456"),
+      (0.001, "0.001"),
+      (1e-7, "1e-7"),
+      (1.5e-10, "1.5e-10"),
+      (1e15, "1000000000000000"),
+      (1e20, "1e+20"),
+      (1.2345e25, "1.2345e+25"),
+      (-1.7976931348623157e308, "-1.7976931348623157e+308"),
+    ];
+
+    for (value, expected) in cases.iter() {
+      let input: Frame = (*value).try_into().unwrap();
+      let expected = format!(",{}\r\n", expected);
+
+      encode_and_verify_empty(&input, &expected);
+      encode_and_verify_non_empty(&input, &expected);
+    }
+  }
+
+  #[test]
+  fn should_encode_double_with_precision() {
+    let cases: &[(f64, usize, &str)] = &[
+      (1.5, 17, "1.5"),
+      (0.1, 17, "0.10000000000000001"),
+      (1.0, 17, "1"),
+      (1.23456, 3, "1.23"),
+      (123456.0, 3, "1.23e+05"),
+      (0.0001, 5, "0.0001"),
+      (0.00001, 5, "1e-05"),
+      (f64::NEG_INFINITY, 17, "-inf"),
+    ];
+
+    for (value, precision, expected) in cases.iter() {
+      let input: Frame = (*value).try_into().unwrap();
+      let expected = format!(",{}\r\n", expected);
+      let mut buf = empty_bytes();
+
+      let config = EncodeConfig {
+        double_format: DoubleFormat::Precision(*precision),
+        ..Default::default()
+      };
+
+      let len = complete::encode_bytes_with_config(&mut buf, &input, &config).unwrap();
+      assert_eq!(buf, expected.as_bytes(), "{:?} with precision {}", value, precision);
+      assert_eq!(len, expected.len());
+    }
+  }
+
+  #[test]
+  fn should_encode_canonical_maps_and_sets() {
+    let mut inner = resp3_utils::new_set(None);
+    inner.insert(Frame::SimpleString {
+      data: "b".into(),
+      attributes: None,
+    });
+    inner.insert(Frame::SimpleString {
+      data: "a".into(),
+      attributes: None,
+    });
+    let mut map = resp3_utils::new_map(None);
+    map.insert(2.into(), Frame::Null { attributes: None });
+    map.insert(
+      1.into(),
+      Frame::Set {
+        data: inner,
+        attributes: None,
+      },
+    );
+    let mut attributes = resp3_utils::new_map(None);
+    attributes.insert(true.into(), Frame::Null { attributes: None });
+    attributes.insert(false.into(), Frame::Null { attributes: None });
+    let input = Frame::Map {
+      data: map,
+      attributes: Some(attributes),
+    };
+    let expected = "|2\r\n#f\r\n_\r\n#t\r\n_\r\n%2\r\n:1\r\n~2\r\n+a\r\n+b\r\n:2\r\n_\r\n";
+
+    let config = EncodeConfig {
+      canonical: true,
+      ..Default::default()
+    };
+    let mut buf = empty_bytes();
+    let len = complete::encode_bytes_with_config(&mut buf, &input, &config).unwrap();
+    assert_eq!(buf, expected.as_bytes());
+    assert_eq!(len, expected.len());
+  }
+
+  #[test]
+  fn should_enforce_max_encode_len() {
+    let inner: Frame = (FrameKind::BlobString, "foo").try_into().unwrap();
+    let input = Frame::Array {
+      data: vec![inner.clone(), inner],
+      attributes: None,
+    };
+    let expected = "*2\r\n$3\r\nfoo\r\n$3\r\nfoo\r\n";
+
+    let config = EncodeConfig {
+      max_len: Some(expected.len()),
+      ..Default::default()
+    };
+    let mut buf = empty_bytes();
+    assert_eq!(complete::encode_bytes_with_config(&mut buf, &input, &config).unwrap(), expected.len());
+    assert_eq!(buf, expected.as_bytes());
+
+    let config = EncodeConfig {
+      max_len: Some(expected.len() - 1),
+      ..Default::default()
+    };
+    let mut buf = empty_bytes();
+    let error = complete::encode_bytes_with_config(&mut buf, &input, &config).unwrap_err();
+    assert_eq!(error.kind(), &RedisProtocolErrorKind::FrameTooLarge(expected.len() - 1));
+    assert!(buf.is_empty());
+    assert!(complete::encode_with_config(&mut [0; 64], 0, &input, &config).is_err());
+    assert_eq!(resp3_utils::encode_len_within(&input, DoubleFormat::default(), 8).unwrap(), None);
+  }
+
+  #[test]
+  fn should_return_required_len_when_slice_is_too_small() {
+    let input: Frame = (FrameKind::BlobString, "foobar").try_into().unwrap();
+    let mut buf = [0; 10];
+
+    let error = complete::encode(&mut buf, 2, &input).unwrap_err();
+    assert_eq!(error.kind(), &RedisProtocolErrorKind::SliceTooSmall { required: 14 });
+    let mut buf = [0; 14];
+    assert_eq!(complete::encode(&mut buf, 2, &input).unwrap(), 14);
+
+    let error = streaming::encode_string_chunk(&mut [0; 8], 4, b"foo").unwrap_err();
+    assert_eq!(error.kind(), &RedisProtocolErrorKind::SliceTooSmall { required: 13 });
+  }
+
+  #[test]
+  fn should_verify_encoded_len() {
+    let mut attributes = resp3_utils::new_map(None);
+    attributes.insert((FrameKind::SimpleString, "ttl").try_into().unwrap(), 1.into());
+    let input = Frame::Array {
+      data: vec![
+        (FrameKind::BlobString, "foo").try_into().unwrap(),
+        Frame::Double {
+          data: 1.5,
+          attributes: None,
+        },
+      ],
+      attributes: Some(attributes),
+    };
+    let expected = "|1\r\n+ttl\r\n:1\r\n*2\r\n$3\r\nfoo\r\n,1.5\r\n";
+
+    complete::verify_encode(&input, expected.len()).unwrap();
+    let error = complete::verify_encode(&input, expected.len() + 1).unwrap_err();
+    assert_eq!(error.kind(), &RedisProtocolErrorKind::EncodeError);
+    assert_eq!(
+      error.description(),
+      format!("Encoded {} bytes but expected {} bytes.", expected.len(), expected.len() + 1)
+    );
+  }
+
   #[test]
   fn should_encode_bignumber() {
     let expected = "(3492890328409238509324850943850943825024385\r\n";
@@ -1102,7 +1414,7 @@ mod tests {
   #[test]
   fn should_encode_null() {
     let expected = "_\r\n";
-    let input = Frame::Null;
+    let input = Frame::Null { attributes: None };
 
     encode_and_verify_empty(&input, expected);
     encode_and_verify_non_empty(&input, expected);
@@ -1220,10 +1532,7 @@ mod tests {
       attributes: None,
     };
 
-    encode_and_verify_empty(&input, expected);
-    encode_and_verify_non_empty(&input, expected);
-    encode_and_verify_empty_with_attributes(&input, expected);
-    encode_and_verify_non_empty_with_attributes(&input, expected);
+    encode_and_verify_unordered(&input, expected);
   }
 
   #[test]
@@ -1242,10 +1551,7 @@ mod tests {
       attributes: None,
     };
 
-    encode_and_verify_empty(&input, expected);
-    encode_and_verify_non_empty(&input, expected);
-    encode_and_verify_empty_with_attributes(&input, expected);
-    encode_and_verify_non_empty_with_attributes(&input, expected);
+    encode_and_verify_unordered(&input, expected);
   }
 
   #[test]
@@ -1272,10 +1578,7 @@ mod tests {
       attributes: None,
     };
 
-    encode_and_verify_empty(&input, expected);
-    encode_and_verify_non_empty(&input, expected);
-    encode_and_verify_empty_with_attributes(&input, expected);
-    encode_and_verify_non_empty_with_attributes(&input, expected);
+    encode_and_verify_unordered(&input, expected);
   }
 
   #[test]
@@ -1305,6 +1608,28 @@ mod tests {
     encode_and_verify_non_empty(&input, expected);
   }
 
+  #[test]
+  fn should_encode_command_like_frame() {
+    let args = vec!["HGET".to_owned(), "foo".to_owned(), "bar".to_owned()];
+    let frame = Frame::Array {
+      data: args
+        .iter()
+        .map(|a| Frame::BlobString {
+          data: a.as_bytes().to_vec(),
+          attributes: None,
+        })
+        .collect(),
+      attributes: None,
+    };
+
+    let mut expected = BytesMut::new();
+    complete::encode_bytes(&mut expected, &frame).unwrap();
+    let mut buf = BytesMut::new();
+
+    assert_eq!(complete::encode_command(&mut buf, &args), expected.len());
+    assert_eq!(buf, expected);
+  }
+
   #[test]
   fn should_encode_streaming_blobstring() {
     let expected = "$?\r\n;2\r\nhe\r\n;4\r\nllow\r\n;1\r\no\r\n;3\r\nrld\r\n;0\r\n";
@@ -2,11 +2,18 @@
 pub mod decode;
 /// Encoding functions for BytesMut and slices.
 pub mod encode;
+/// Functions for converting RESP3 frames to RESP2 frames.
+pub mod downgrade;
 /// RESP3 frame types.
 pub mod types;
+/// Constructors for standard server replies.
+pub mod replies;
 
 pub(crate) mod utils;
 
+#[cfg(test)]
+pub(crate) mod test_frames;
+
 /// Shorthand for `use`'ing `types`, `encode`, `decode`, etc.
 pub mod prelude {
   pub use super::decode::*;
@@ -0,0 +1,28 @@
+//! Short frame constructors shared by the unit tests for response parsers.
+
+use crate::resp3::types::Frame;
+
+pub(crate) fn simple(s: &str) -> Frame {
+  Frame::new_simple(s)
+}
+
+pub(crate) fn blob(s: &str) -> Frame {
+  Frame::new_blob(s)
+}
+
+pub(crate) fn int(i: i64) -> Frame {
+  Frame::new_number(i)
+}
+
+pub(crate) fn array(data: Vec<Frame>) -> Frame {
+  Frame::new_array(data)
+}
+
+pub(crate) fn push(data: Vec<Frame>) -> Frame {
+  Frame::new_push(data)
+}
+
+/// Create a map with blob string keys.
+pub(crate) fn map(pairs: Vec<(&str, Frame)>) -> Frame {
+  Frame::new_map(pairs.into_iter().map(|(key, value)| (blob(key), value)))
+}
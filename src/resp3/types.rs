@@ -1,15 +1,32 @@
+use crate::commands::{redact, request};
+use crate::convert::FromResp3Frame;
+use crate::core::{ProtocolFrame, ProtocolFrameKind};
+use crate::resp3::decode as resp3_decode;
+use crate::resp3::encode as resp3_encode;
 use crate::resp3::utils as resp3_utils;
-use crate::types::{Redirection, RedisProtocolError, RedisProtocolErrorKind};
+use crate::tracking::{self, Invalidation};
+use crate::types::{Redirection, RedisErrorFrame, RedisErrorKind, RedisProtocolError, RedisProtocolErrorKind};
 use crate::utils;
+use bytes::BytesMut;
 use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::convert::{TryFrom, TryInto};
+use std::convert::{Infallible, TryFrom, TryInto};
+use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::iter::{FromIterator, FusedIterator};
 use std::mem;
 use std::str;
+use std::string::FromUtf8Error;
+use std::sync::atomic::{self, AtomicUsize};
+use std::sync::Arc;
 
 #[cfg(feature = "index-map")]
 use indexmap::{IndexMap, IndexSet};
+#[cfg(feature = "btree-map")]
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "bignum")]
+use num_bigint::BigInt;
 
 /// Byte prefix before a simple string type.
 pub const SIMPLE_STRING_BYTE: u8 = b'+';
@@ -62,6 +79,8 @@ pub const NULL: &'static str = "_\r\n";
 pub const INFINITY: &'static str = "inf";
 /// Byte representation of negative infinity.
 pub const NEG_INFINITY: &'static str = "-inf";
+/// Byte representation of NaN.
+pub const NAN: &'static str = "nan";
 
 /// Byte representation of HELLO.
 pub const HELLO: &'static str = "HELLO";
@@ -74,18 +93,551 @@ pub const EMPTY_SPACE: &'static str = " ";
 /// Byte representation of `AUTH`.
 pub const AUTH: &'static str = "AUTH";
 
+/// A fast, unkeyed hasher for frames.
+///
+/// This uses the same algorithm as the `FxHasher` in the Rust compiler, which hashes a word at a time. It is much
+/// faster than the default `SipHash` hasher for frame keys, but it is not resistant to collision attacks, so it should
+/// only be used with trusted servers. The `fast-hash` feature uses this hasher in [FrameMap] and [FrameSet].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FastHasher {
+  hash: u64,
+}
+
+impl FastHasher {
+  const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+  #[inline]
+  fn add_to_hash(&mut self, word: u64) {
+    self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(Self::SEED);
+  }
+}
+
+impl Hasher for FastHasher {
+  #[inline]
+  fn write(&mut self, mut bytes: &[u8]) {
+    while bytes.len() >= 8 {
+      let mut word = [0; 8];
+      word.copy_from_slice(&bytes[..8]);
+      self.add_to_hash(u64::from_le_bytes(word));
+      bytes = &bytes[8..];
+    }
+    if bytes.len() >= 4 {
+      let mut word = [0; 4];
+      word.copy_from_slice(&bytes[..4]);
+      self.add_to_hash(u32::from_le_bytes(word) as u64);
+      bytes = &bytes[4..];
+    }
+    for byte in bytes {
+      self.add_to_hash(*byte as u64);
+    }
+  }
+
+  #[inline]
+  fn write_u8(&mut self, i: u8) {
+    self.add_to_hash(i as u64);
+  }
+
+  #[inline]
+  fn write_u16(&mut self, i: u16) {
+    self.add_to_hash(i as u64);
+  }
+
+  #[inline]
+  fn write_u32(&mut self, i: u32) {
+    self.add_to_hash(i as u64);
+  }
+
+  #[inline]
+  fn write_u64(&mut self, i: u64) {
+    self.add_to_hash(i);
+  }
+
+  #[inline]
+  fn write_usize(&mut self, i: usize) {
+    self.add_to_hash(i as u64);
+  }
+
+  #[inline]
+  fn finish(&self) -> u64 {
+    self.hash
+  }
+}
+
+/// The hasher used by [FrameMap] and [FrameSet] with the default and `index-map` backends.
+///
+/// This is the randomly keyed `SipHash` hasher from the standard library by default, which is resistant to collision
+/// attacks from untrusted servers. Enable the `fast-hash` feature to use the [FastHasher] instead.
+#[cfg(not(feature = "fast-hash"))]
+pub type FrameHashBuilder = std::collections::hash_map::RandomState;
+/// The hasher used by [FrameMap] and [FrameSet] with the default and `index-map` backends.
+///
+/// This is the [FastHasher] with the `fast-hash` feature, or the randomly keyed `SipHash` hasher from the standard
+/// library otherwise.
+#[cfg(feature = "fast-hash")]
+pub type FrameHashBuilder = std::hash::BuildHasherDefault<FastHasher>;
+
+#[cfg(not(any(feature = "index-map", feature = "btree-map")))]
+type MapBackend = HashMap<Frame, Frame, FrameHashBuilder>;
+#[cfg(not(any(feature = "index-map", feature = "btree-map")))]
+type SetBackend = HashSet<Frame, FrameHashBuilder>;
+#[cfg(all(feature = "index-map", not(feature = "btree-map")))]
+type MapBackend = IndexMap<Frame, Frame, FrameHashBuilder>;
+#[cfg(all(feature = "index-map", not(feature = "btree-map")))]
+type SetBackend = IndexSet<Frame, FrameHashBuilder>;
+#[cfg(feature = "btree-map")]
+type MapBackend = BTreeMap<Frame, Frame>;
+#[cfg(feature = "btree-map")]
+type SetBackend = BTreeSet<Frame>;
+
 /// A map struct for frames.
-#[cfg(not(feature = "index-map"))]
-pub type FrameMap = HashMap<Frame, Frame>;
-/// A set struct for frames.
-#[cfg(not(feature = "index-map"))]
-pub type FrameSet = HashSet<Frame>;
-/// A map struct for frames.
+///
+/// The map is backed by a `HashMap` by default, by an insertion-ordered `IndexMap` with the `index-map` feature, or by
+/// a `BTreeMap` sorted by the [Frame] ordering with the `btree-map` feature. The backend is used when decoding and
+/// encoding frames, but the interface is the same with any backend.
+///
+/// With the `index-map` feature decoded maps keep the order of the entries on the wire, iteration follows insertion
+/// order, and maps are encoded in that order. Replacing the value for an existing key keeps the key's position.
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct FrameMap {
+  inner: MapBackend,
+}
+
+impl FrameMap {
+  /// Create an empty map.
+  pub fn new() -> Self {
+    FrameMap {
+      inner: MapBackend::default(),
+    }
+  }
+
+  /// Create an empty map with space for at least `capacity` entries.
+  pub fn with_capacity(capacity: usize) -> Self {
+    #[cfg(feature = "btree-map")]
+    let inner = {
+      let _ = capacity;
+      MapBackend::new()
+    };
+    #[cfg(not(feature = "btree-map"))]
+    let inner = MapBackend::with_capacity_and_hasher(capacity, FrameHashBuilder::default());
+
+    FrameMap { inner }
+  }
+
+  /// The number of entries in the map.
+  pub fn len(&self) -> usize {
+    self.inner.len()
+  }
+
+  /// Whether or not the map is empty.
+  pub fn is_empty(&self) -> bool {
+    self.inner.is_empty()
+  }
+
+  /// Insert a key/value pair, returning the previous value for the key.
+  pub fn insert(&mut self, key: Frame, value: Frame) -> Option<Frame> {
+    self.inner.insert(key, value)
+  }
+
+  /// Read the value for `key`.
+  pub fn get(&self, key: &Frame) -> Option<&Frame> {
+    self.inner.get(key)
+  }
+
+  /// Read a mutable reference to the value for `key`.
+  pub fn get_mut(&mut self, key: &Frame) -> Option<&mut Frame> {
+    self.inner.get_mut(key)
+  }
+
+  /// Whether or not the map contains `key`.
+  pub fn contains_key(&self, key: &Frame) -> bool {
+    self.inner.contains_key(key)
+  }
+
+  /// Remove `key` from the map, returning its value.
+  ///
+  /// The order of the remaining entries is preserved with the `index-map` feature.
+  pub fn remove(&mut self, key: &Frame) -> Option<Frame> {
+    #[cfg(all(feature = "index-map", not(feature = "btree-map")))]
+    return self.inner.shift_remove(key);
+    #[cfg(any(not(feature = "index-map"), feature = "btree-map"))]
+    return self.inner.remove(key);
+  }
+
+  /// Remove all entries from the map.
+  pub fn clear(&mut self) {
+    self.inner.clear()
+  }
+
+  /// Keep only the entries for which `f` returns `true`.
+  pub fn retain<F>(&mut self, f: F)
+  where
+    F: FnMut(&Frame, &mut Frame) -> bool,
+  {
+    self.inner.retain(f)
+  }
+
+  /// Read the entry at `index` in iteration order.
+  ///
+  /// This is a constant time lookup in insertion order with the `index-map` feature. Other backends walk the map.
+  pub fn get_index(&self, index: usize) -> Option<(&Frame, &Frame)> {
+    #[cfg(all(feature = "index-map", not(feature = "btree-map")))]
+    return self.inner.get_index(index);
+    #[cfg(any(not(feature = "index-map"), feature = "btree-map"))]
+    return self.inner.iter().nth(index);
+  }
+
+  /// Read the position of `key` in iteration order.
+  pub fn get_index_of(&self, key: &Frame) -> Option<usize> {
+    #[cfg(all(feature = "index-map", not(feature = "btree-map")))]
+    return self.inner.get_index_of(key);
+    #[cfg(any(not(feature = "index-map"), feature = "btree-map"))]
+    return self.inner.keys().position(|k| k == key);
+  }
+
+  /// Iterate over the entries in the map.
+  ///
+  /// Entries are returned in insertion order with the `index-map` feature and in sorted order with the `btree-map`
+  /// feature.
+  pub fn iter(&self) -> FrameMapIter<'_> {
+    FrameMapIter {
+      inner: self.inner.iter(),
+    }
+  }
+
+  /// Iterate over the entries in the map with mutable references to the values.
+  pub fn iter_mut(&mut self) -> impl ExactSizeIterator<Item = (&Frame, &mut Frame)> + '_ {
+    self.inner.iter_mut()
+  }
+
+  /// Iterate over the keys in the map.
+  pub fn keys(&self) -> impl ExactSizeIterator<Item = &Frame> + '_ {
+    self.inner.keys()
+  }
+
+  /// Iterate over the values in the map.
+  pub fn values(&self) -> impl ExactSizeIterator<Item = &Frame> + '_ {
+    self.inner.values()
+  }
+
+  /// Iterate over mutable references to the values in the map.
+  pub fn values_mut(&mut self) -> impl ExactSizeIterator<Item = &mut Frame> + '_ {
+    self.inner.values_mut()
+  }
+
+  /// Read the entries in the map, sorted by key.
+  pub fn sorted(&self) -> Vec<(&Frame, &Frame)> {
+    let mut entries: Vec<_> = self.inner.iter().collect();
+    entries.sort();
+    entries
+  }
+}
+
+impl fmt::Debug for FrameMap {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.inner.fmt(f)
+  }
+}
+
+/// An iterator over the entries of a [FrameMap], in the same order as [iter](FrameMap::iter).
+#[derive(Debug)]
+pub struct FrameMapIntoIter {
+  inner: <MapBackend as IntoIterator>::IntoIter,
+}
+
+impl Iterator for FrameMapIntoIter {
+  type Item = (Frame, Frame);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.inner.next()
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.inner.size_hint()
+  }
+}
+
+impl ExactSizeIterator for FrameMapIntoIter {}
+
+impl FusedIterator for FrameMapIntoIter {}
+
+/// An iterator over references to the entries of a [FrameMap], returned by [iter](FrameMap::iter).
+#[derive(Clone, Debug)]
+pub struct FrameMapIter<'a> {
+  inner: <&'a MapBackend as IntoIterator>::IntoIter,
+}
+
+impl<'a> Iterator for FrameMapIter<'a> {
+  type Item = (&'a Frame, &'a Frame);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.inner.next()
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.inner.size_hint()
+  }
+}
+
+impl ExactSizeIterator for FrameMapIter<'_> {}
+
+impl FusedIterator for FrameMapIter<'_> {}
+
+impl IntoIterator for FrameMap {
+  type Item = (Frame, Frame);
+  type IntoIter = FrameMapIntoIter;
+
+  fn into_iter(self) -> Self::IntoIter {
+    FrameMapIntoIter {
+      inner: self.inner.into_iter(),
+    }
+  }
+}
+
+impl<'a> IntoIterator for &'a FrameMap {
+  type Item = (&'a Frame, &'a Frame);
+  type IntoIter = FrameMapIter<'a>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl FromIterator<(Frame, Frame)> for FrameMap {
+  fn from_iter<I: IntoIterator<Item = (Frame, Frame)>>(iter: I) -> Self {
+    FrameMap {
+      inner: iter.into_iter().collect(),
+    }
+  }
+}
+
+impl Extend<(Frame, Frame)> for FrameMap {
+  fn extend<I: IntoIterator<Item = (Frame, Frame)>>(&mut self, iter: I) {
+    self.inner.extend(iter)
+  }
+}
+
+impl From<HashMap<Frame, Frame>> for FrameMap {
+  fn from(map: HashMap<Frame, Frame>) -> Self {
+    map.into_iter().collect()
+  }
+}
+
 #[cfg(feature = "index-map")]
-pub type FrameMap = IndexMap<Frame, Frame>;
+impl From<IndexMap<Frame, Frame>> for FrameMap {
+  fn from(map: IndexMap<Frame, Frame>) -> Self {
+    map.into_iter().collect()
+  }
+}
+
 /// A set struct for frames.
+///
+/// The set is backed by a `HashSet` by default, by an insertion-ordered `IndexSet` with the `index-map` feature, or by
+/// a `BTreeSet` sorted by the [Frame] ordering with the `btree-map` feature. The backend is used when decoding and
+/// encoding frames, but the interface is the same with any backend.
+///
+/// With the `index-map` feature decoded sets keep the order of the members on the wire, iteration follows insertion
+/// order, and sets are encoded in that order.
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct FrameSet {
+  inner: SetBackend,
+}
+
+impl FrameSet {
+  /// Create an empty set.
+  pub fn new() -> Self {
+    FrameSet {
+      inner: SetBackend::default(),
+    }
+  }
+
+  /// Create an empty set with space for at least `capacity` members.
+  pub fn with_capacity(capacity: usize) -> Self {
+    #[cfg(feature = "btree-map")]
+    let inner = {
+      let _ = capacity;
+      SetBackend::new()
+    };
+    #[cfg(not(feature = "btree-map"))]
+    let inner = SetBackend::with_capacity_and_hasher(capacity, FrameHashBuilder::default());
+
+    FrameSet { inner }
+  }
+
+  /// The number of members in the set.
+  pub fn len(&self) -> usize {
+    self.inner.len()
+  }
+
+  /// Whether or not the set is empty.
+  pub fn is_empty(&self) -> bool {
+    self.inner.is_empty()
+  }
+
+  /// Add a member to the set, returning whether or not it was newly inserted.
+  pub fn insert(&mut self, value: Frame) -> bool {
+    self.inner.insert(value)
+  }
+
+  /// Whether or not the set contains `value`.
+  pub fn contains(&self, value: &Frame) -> bool {
+    self.inner.contains(value)
+  }
+
+  /// Remove `value` from the set, returning whether or not it was present.
+  ///
+  /// The order of the remaining members is preserved with the `index-map` feature.
+  pub fn remove(&mut self, value: &Frame) -> bool {
+    #[cfg(all(feature = "index-map", not(feature = "btree-map")))]
+    return self.inner.shift_remove(value);
+    #[cfg(any(not(feature = "index-map"), feature = "btree-map"))]
+    return self.inner.remove(value);
+  }
+
+  /// Remove all members from the set.
+  pub fn clear(&mut self) {
+    self.inner.clear()
+  }
+
+  /// Keep only the members for which `f` returns `true`.
+  pub fn retain<F>(&mut self, f: F)
+  where
+    F: FnMut(&Frame) -> bool,
+  {
+    self.inner.retain(f)
+  }
+
+  /// Read the member at `index` in iteration order.
+  ///
+  /// This is a constant time lookup in insertion order with the `index-map` feature. Other backends walk the set.
+  pub fn get_index(&self, index: usize) -> Option<&Frame> {
+    #[cfg(all(feature = "index-map", not(feature = "btree-map")))]
+    return self.inner.get_index(index);
+    #[cfg(any(not(feature = "index-map"), feature = "btree-map"))]
+    return self.inner.iter().nth(index);
+  }
+
+  /// Read the position of `value` in iteration order.
+  pub fn get_index_of(&self, value: &Frame) -> Option<usize> {
+    #[cfg(all(feature = "index-map", not(feature = "btree-map")))]
+    return self.inner.get_index_of(value);
+    #[cfg(any(not(feature = "index-map"), feature = "btree-map"))]
+    return self.inner.iter().position(|v| v == value);
+  }
+
+  /// Iterate over the members of the set.
+  ///
+  /// Members are returned in insertion order with the `index-map` feature and in sorted order with the `btree-map`
+  /// feature.
+  pub fn iter(&self) -> FrameSetIter<'_> {
+    FrameSetIter {
+      inner: self.inner.iter(),
+    }
+  }
+
+  /// Read the members of the set in sorted order.
+  pub fn sorted(&self) -> Vec<&Frame> {
+    let mut members: Vec<_> = self.inner.iter().collect();
+    members.sort();
+    members
+  }
+}
+
+impl fmt::Debug for FrameSet {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    self.inner.fmt(f)
+  }
+}
+
+/// An iterator over the members of a [FrameSet], in the same order as [iter](FrameSet::iter).
+#[derive(Debug)]
+pub struct FrameSetIntoIter {
+  inner: <SetBackend as IntoIterator>::IntoIter,
+}
+
+impl Iterator for FrameSetIntoIter {
+  type Item = Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.inner.next()
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.inner.size_hint()
+  }
+}
+
+impl ExactSizeIterator for FrameSetIntoIter {}
+
+impl FusedIterator for FrameSetIntoIter {}
+
+/// An iterator over references to the members of a [FrameSet], returned by [iter](FrameSet::iter).
+#[derive(Clone, Debug)]
+pub struct FrameSetIter<'a> {
+  inner: <&'a SetBackend as IntoIterator>::IntoIter,
+}
+
+impl<'a> Iterator for FrameSetIter<'a> {
+  type Item = &'a Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.inner.next()
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    self.inner.size_hint()
+  }
+}
+
+impl ExactSizeIterator for FrameSetIter<'_> {}
+
+impl FusedIterator for FrameSetIter<'_> {}
+
+impl IntoIterator for FrameSet {
+  type Item = Frame;
+  type IntoIter = FrameSetIntoIter;
+
+  fn into_iter(self) -> Self::IntoIter {
+    FrameSetIntoIter {
+      inner: self.inner.into_iter(),
+    }
+  }
+}
+
+impl<'a> IntoIterator for &'a FrameSet {
+  type Item = &'a Frame;
+  type IntoIter = FrameSetIter<'a>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl FromIterator<Frame> for FrameSet {
+  fn from_iter<I: IntoIterator<Item = Frame>>(iter: I) -> Self {
+    FrameSet {
+      inner: iter.into_iter().collect(),
+    }
+  }
+}
+
+impl Extend<Frame> for FrameSet {
+  fn extend<I: IntoIterator<Item = Frame>>(&mut self, iter: I) {
+    self.inner.extend(iter)
+  }
+}
+
+impl From<HashSet<Frame>> for FrameSet {
+  fn from(set: HashSet<Frame>) -> Self {
+    set.into_iter().collect()
+  }
+}
+
 #[cfg(feature = "index-map")]
-pub type FrameSet = IndexSet<Frame>;
+impl From<IndexSet<Frame>> for FrameSet {
+  fn from(set: IndexSet<Frame>) -> Self {
+    set.into_iter().collect()
+  }
+}
 
 /// Additional information returned alongside a frame.
 pub type Attributes = FrameMap;
@@ -98,7 +650,7 @@ pub enum ByteOrder {
 }
 
 /// The RESP version used in the `HELLO` request.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub enum RespVersion {
   RESP2,
   RESP3,
@@ -114,12 +666,23 @@ impl RespVersion {
 }
 
 /// Authentication information used in the `HELLO` request.
-#[derive(Clone, Debug, Eq, PartialEq)]
+///
+/// The password is redacted in the `Debug` output.
+#[derive(Clone, Eq, PartialEq)]
 pub struct Auth {
   pub username: Cow<'static, str>,
   pub password: Cow<'static, str>,
 }
 
+impl fmt::Debug for Auth {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Auth")
+      .field("username", &self.username)
+      .field("password", &redact::REDACTED)
+      .finish()
+  }
+}
+
 impl Auth {
   /// Create an [Auth] struct using the "default" user with the provided password.
   pub fn from_password<S: Into<String>>(password: S) -> Auth {
@@ -131,7 +694,7 @@ impl Auth {
 }
 
 /// The format of a verbatim string frame.
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
 pub enum VerbatimStringFormat {
   Text,
   Markdown,
@@ -154,42 +717,432 @@ impl VerbatimStringFormat {
   }
 }
 
-/// The type of frame without any associated data.
-#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Copy)]
-pub enum FrameKind {
-  Array,
-  BlobString,
-  SimpleString,
-  SimpleError,
-  Number,
-  Null,
-  Double,
-  Boolean,
-  BlobError,
-  VerbatimString,
-  Map,
-  Set,
-  Attribute,
-  Push,
-  Hello,
-  BigNumber,
-  ChunkedString,
-  EndStream,
+/// How to handle a key that appears more than once in a map or attribute frame.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DuplicateKeyPolicy {
+  /// Return an error.
+  Error,
+  /// Keep the first value received for the key.
+  #[default]
+  KeepFirst,
+  /// Keep the last value received for the key.
+  KeepLast,
+  /// Collect the values for each key into an array, in the order they were received.
+  ///
+  /// Every value is wrapped in an array, including values for keys that only appear once.
+  Collect,
 }
 
-impl FrameKind {
-  /// Whether or not the frame is an aggregate type (array, set, map).
-  pub fn is_aggregate_type(&self) -> bool {
-    match *self {
-      FrameKind::Array | FrameKind::Set | FrameKind::Map => true,
-      _ => false,
-    }
+/// Options used when decoding frames.
+#[derive(Clone, Default)]
+pub struct DecodeConfig {
+  /// How to handle duplicate keys in map and attribute frames.
+  pub duplicate_keys: DuplicateKeyPolicy,
+  /// The number of bytes on either side of the failing offset to capture in a hex dump when decoding fails.
+  ///
+  /// The dump can be read with [hex_dump](crate::types::RedisProtocolError::hex_dump) and is included when the error
+  /// is displayed. This is disabled by default.
+  pub hex_dump_window: Option<usize>,
+  /// The allocator used for the payloads and child frames of decoded frames.
+  ///
+  /// The global allocator is used if this is `None`.
+  pub allocator: Option<Arc<dyn FrameAllocator>>,
+}
+
+impl fmt::Debug for DecodeConfig {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("DecodeConfig")
+      .field("duplicate_keys", &self.duplicate_keys)
+      .field("hex_dump_window", &self.hex_dump_window)
+      .field("allocator", &self.allocator.is_some())
+      .finish()
   }
+}
 
-  /// Whether or not the frame is an aggregate type or blob string.
-  pub fn is_streaming_type(&self) -> bool {
-    match *self {
-      FrameKind::Array | FrameKind::Set | FrameKind::Map | FrameKind::BlobString => true,
+impl PartialEq for DecodeConfig {
+  fn eq(&self, other: &Self) -> bool {
+    let allocator = match (self.allocator.as_ref(), other.allocator.as_ref()) {
+      (Some(lhs), Some(rhs)) => Arc::ptr_eq(lhs, rhs),
+      (None, None) => true,
+      _ => false,
+    };
+
+    allocator && self.duplicate_keys == other.duplicate_keys && self.hex_dump_window == other.hex_dump_window
+  }
+}
+
+impl Eq for DecodeConfig {}
+
+impl DecodeConfig {
+  /// Decode frames with the provided allocator.
+  pub fn with_allocator<A: FrameAllocator + 'static>(mut self, allocator: A) -> Self {
+    self.allocator = Some(Arc::new(allocator));
+    self
+  }
+}
+
+/// An interface for allocating the payloads and child frame vectors of decoded frames.
+///
+/// Custom allocators for `Vec` are not available on stable Rust, so the decoder instead asks this interface for every
+/// buffer it creates and then fills the returned buffer. Implementations can hand out buffers carved from a
+/// pre-allocated region, or enforce a budget by returning an error, which stops decoding with a `DecodeError`.
+///
+/// Blob strings, blob errors, simple strings, simple errors, verbatim strings, big numbers, chunked strings, and the
+/// children of arrays, push frames, sets, maps, and attributes are allocated with this interface. The hash tables
+/// backing sets, maps, and attributes always use the global allocator.
+pub trait FrameAllocator: Send + Sync {
+  /// Allocate a buffer containing a copy of `data`.
+  fn alloc_bytes(&self, data: &[u8]) -> Result<Vec<u8>, RedisProtocolError>;
+
+  /// Allocate an empty vector with room for at least `len` frames.
+  fn alloc_frames(&self, len: usize) -> Result<Vec<Frame>, RedisProtocolError>;
+
+  /// Release buffers from [alloc_bytes](Self::alloc_bytes) with a combined length of `len` bytes.
+  ///
+  /// The decoder calls this when the buffers are discarded because the frame is incomplete or invalid, since callers
+  /// decode incomplete frames again once more data arrives. The default implementation does nothing.
+  fn release_bytes(&self, _len: usize) {}
+
+  /// Release vectors from [alloc_frames](Self::alloc_frames) with room for a combined `len` frames.
+  ///
+  /// See [release_bytes](Self::release_bytes) for more information.
+  fn release_frames(&self, _len: usize) {}
+}
+
+/// A [FrameAllocator] that uses the global allocator.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct GlobalFrameAllocator;
+
+impl FrameAllocator for GlobalFrameAllocator {
+  fn alloc_bytes(&self, data: &[u8]) -> Result<Vec<u8>, RedisProtocolError> {
+    Ok(data.to_vec())
+  }
+
+  fn alloc_frames(&self, len: usize) -> Result<Vec<Frame>, RedisProtocolError> {
+    Ok(Vec::with_capacity(len))
+  }
+}
+
+/// A [FrameAllocator] that uses the global allocator but fails once more than `limit` bytes have been allocated.
+///
+/// Child frame vectors count as `len * size_of::<Frame>()` bytes. Allocations for incomplete or invalid frames are
+/// released by the decoder, but the count is not decreased when decoded frames are dropped, so callers should
+/// [reset](Self::reset) the allocator once the frames decoded with it are released.
+///
+/// ```
+/// # use redis_protocol::resp3::decode::complete::decode_with_config;
+/// # use redis_protocol::resp3::types::{BoundedFrameAllocator, DecodeConfig};
+/// # use std::sync::Arc;
+/// let allocator = Arc::new(BoundedFrameAllocator::new(4));
+/// let config = DecodeConfig {
+///   allocator: Some(allocator.clone()),
+///   ..Default::default()
+/// };
+///
+/// assert!(decode_with_config(b"$3\r\nfoo\r\n", &config).is_ok());
+/// assert_eq!(allocator.used(), 3);
+/// assert!(decode_with_config(b"$3\r\nbar\r\n", &config).is_err());
+///
+/// allocator.reset();
+/// assert!(decode_with_config(b"$3\r\nbar\r\n", &config).is_ok());
+/// ```
+#[derive(Debug, Default)]
+pub struct BoundedFrameAllocator {
+  limit: usize,
+  used: AtomicUsize,
+}
+
+impl BoundedFrameAllocator {
+  /// Create a new allocator that allows at most `limit` bytes to be allocated.
+  pub fn new(limit: usize) -> Self {
+    BoundedFrameAllocator {
+      limit,
+      used: AtomicUsize::new(0),
+    }
+  }
+
+  /// The maximum number of bytes that can be allocated.
+  pub fn limit(&self) -> usize {
+    self.limit
+  }
+
+  /// The number of bytes allocated since the allocator was created or reset.
+  pub fn used(&self) -> usize {
+    self.used.load(atomic::Ordering::Acquire)
+  }
+
+  /// Reset the number of allocated bytes to zero.
+  pub fn reset(&self) {
+    self.used.store(0, atomic::Ordering::Release);
+  }
+
+  fn reserve(&self, amt: usize) -> Result<(), RedisProtocolError> {
+    let limit = self.limit;
+    self
+      .used
+      .fetch_update(atomic::Ordering::AcqRel, atomic::Ordering::Acquire, |used| {
+        used.checked_add(amt).filter(|total| *total <= limit)
+      })
+      .map(|_| ())
+      .map_err(|used| {
+        RedisProtocolError::new(
+          RedisProtocolErrorKind::DecodeError,
+          format!("Cannot allocate {} bytes with {} of {} bytes used.", amt, used, limit),
+        )
+      })
+  }
+
+  fn release(&self, amt: usize) {
+    let _ = self
+      .used
+      .fetch_update(atomic::Ordering::AcqRel, atomic::Ordering::Acquire, |used| {
+        Some(used.saturating_sub(amt))
+      });
+  }
+}
+
+impl FrameAllocator for BoundedFrameAllocator {
+  fn alloc_bytes(&self, data: &[u8]) -> Result<Vec<u8>, RedisProtocolError> {
+    self.reserve(data.len())?;
+    Ok(data.to_vec())
+  }
+
+  fn alloc_frames(&self, len: usize) -> Result<Vec<Frame>, RedisProtocolError> {
+    self.reserve(len.saturating_mul(mem::size_of::<Frame>()))?;
+    Ok(Vec::with_capacity(len))
+  }
+
+  fn release_bytes(&self, len: usize) {
+    self.release(len);
+  }
+
+  fn release_frames(&self, len: usize) {
+    self.release(len.saturating_mul(mem::size_of::<Frame>()));
+  }
+}
+
+/// A double with a total order, equality, and hashing, so that doubles can be used as set members, map keys, and in
+/// sorted collections.
+///
+/// Values are compared by a canonical bit pattern where `0.0` and `-0.0` are equal, every `NaN` is equal to every
+/// other `NaN`, and `NaN` is greater than every other value. `Double` frames are compared and hashed the same way.
+///
+/// ```
+/// # use redis_protocol::resp3::types::OrderedDouble;
+/// # use std::collections::HashSet;
+/// let mut set = HashSet::new();
+/// set.insert(OrderedDouble(0.0));
+/// set.insert(OrderedDouble(-0.0));
+/// set.insert(OrderedDouble(f64::NAN));
+/// set.insert(OrderedDouble(-f64::NAN));
+/// assert_eq!(set.len(), 2);
+///
+/// let mut values = vec![OrderedDouble(f64::NAN), OrderedDouble(1.5), OrderedDouble(f64::NEG_INFINITY)];
+/// values.sort();
+/// assert_eq!(values[0], OrderedDouble(f64::NEG_INFINITY));
+/// assert!(values[2].0.is_nan());
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OrderedDouble(pub f64);
+
+impl OrderedDouble {
+  /// Read the bit pattern used for equality and hashing, where zeros and `NaN` have one canonical representation.
+  pub fn canonical_bits(&self) -> u64 {
+    if self.0 == 0.0 {
+      0
+    } else if self.0.is_nan() {
+      f64::NAN.to_bits()
+    } else {
+      self.0.to_bits()
+    }
+  }
+
+  /// Read the inner value.
+  pub fn into_inner(self) -> f64 {
+    self.0
+  }
+}
+
+impl PartialEq for OrderedDouble {
+  fn eq(&self, other: &Self) -> bool {
+    self.canonical_bits() == other.canonical_bits()
+  }
+}
+
+impl Eq for OrderedDouble {}
+
+impl Hash for OrderedDouble {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.canonical_bits().hash(state);
+  }
+}
+
+impl PartialOrd for OrderedDouble {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for OrderedDouble {
+  fn cmp(&self, other: &Self) -> Ordering {
+    match self.0.partial_cmp(&other.0) {
+      Some(ordering) => ordering,
+      None => self.0.is_nan().cmp(&other.0.is_nan()),
+    }
+  }
+}
+
+impl From<f64> for OrderedDouble {
+  fn from(value: f64) -> Self {
+    OrderedDouble(value)
+  }
+}
+
+impl From<OrderedDouble> for f64 {
+  fn from(value: OrderedDouble) -> Self {
+    value.0
+  }
+}
+
+impl fmt::Display for OrderedDouble {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Display::fmt(&self.0, f)
+  }
+}
+
+/// The format used when encoding double frames.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum DoubleFormat {
+  /// The shortest representation that parses back to the same value, matching Redis 7.2 and later.
+  #[default]
+  Shortest,
+  /// At most the provided number of significant digits, similar to `%.<n>g` in C. Older Redis versions use `%.17g`.
+  Precision(usize),
+}
+
+impl DoubleFormat {
+  /// Format a double, accounting for `inf`, `-inf`, and `nan`.
+  pub fn format(&self, value: f64) -> Cow<'static, str> {
+    match *self {
+      DoubleFormat::Shortest => resp3_utils::f64_to_redis_string(&value),
+      DoubleFormat::Precision(precision) => resp3_utils::f64_to_precision_string(&value, precision),
+    }
+  }
+}
+
+/// Options used when encoding frames.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EncodeConfig {
+  /// The format used for double frames.
+  pub double_format: DoubleFormat,
+  /// Whether or not to write map entries, attributes, and set members sorted by the [Frame] ordering.
+  ///
+  /// This makes the output deterministic regardless of the map backend, which is useful for golden files or when
+  /// hashing encoded frames.
+  pub canonical: bool,
+  /// The maximum number of bytes that a frame can be encoded into.
+  ///
+  /// Frames that would be larger are rejected with a `RedisProtocolErrorKind::FrameTooLarge` error before anything is
+  /// written to the buffer. The length of aggregate frames is computed one inner frame at a time and stops once the
+  /// budget is exceeded, so oversized frames are not fully traversed.
+  pub max_len: Option<usize>,
+}
+
+/// The type of frame without any associated data.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Copy)]
+pub enum FrameKind {
+  Array,
+  BlobString,
+  SimpleString,
+  SimpleError,
+  Number,
+  Null,
+  Double,
+  Boolean,
+  BlobError,
+  VerbatimString,
+  Map,
+  Set,
+  Attribute,
+  Push,
+  Hello,
+  BigNumber,
+  ChunkedString,
+  EndStream,
+}
+
+impl FrameKind {
+  /// Every kind of frame, in the order the types are listed in the RESP3 specification.
+  pub const ALL: &[FrameKind] = &[
+    FrameKind::BlobString,
+    FrameKind::SimpleString,
+    FrameKind::SimpleError,
+    FrameKind::Number,
+    FrameKind::Null,
+    FrameKind::Double,
+    FrameKind::Boolean,
+    FrameKind::BlobError,
+    FrameKind::VerbatimString,
+    FrameKind::BigNumber,
+    FrameKind::Array,
+    FrameKind::Map,
+    FrameKind::Set,
+    FrameKind::Attribute,
+    FrameKind::Push,
+    FrameKind::Hello,
+    FrameKind::ChunkedString,
+    FrameKind::EndStream,
+  ];
+
+  /// Whether or not the frame is an aggregate type that contains other frames (array, map, set, attribute, push).
+  ///
+  /// Unlike [is_aggregate_type](Self::is_aggregate_type) this includes attributes and push frames.
+  pub fn is_aggregate(&self) -> bool {
+    matches!(
+      *self,
+      FrameKind::Array | FrameKind::Map | FrameKind::Set | FrameKind::Attribute | FrameKind::Push
+    )
+  }
+
+  /// Whether or not the frame can be sent as a stream of unknown length (blob string, array, set, map).
+  ///
+  /// This is the same as [is_streaming_type](Self::is_streaming_type).
+  pub fn is_streamable(&self) -> bool {
+    self.is_streaming_type()
+  }
+
+  /// Whether or not the frame is a single value rather than an aggregate type.
+  ///
+  /// `HELLO`, chunked strings, and the end of stream marker are not scalar values, since they are only used during
+  /// the handshake or while streaming.
+  pub fn is_scalar(&self) -> bool {
+    matches!(
+      *self,
+      FrameKind::BlobString
+        | FrameKind::SimpleString
+        | FrameKind::SimpleError
+        | FrameKind::Number
+        | FrameKind::Null
+        | FrameKind::Double
+        | FrameKind::Boolean
+        | FrameKind::BlobError
+        | FrameKind::VerbatimString
+        | FrameKind::BigNumber
+    )
+  }
+
+  /// Whether or not the frame is an aggregate type (array, set, map).
+  pub fn is_aggregate_type(&self) -> bool {
+    match *self {
+      FrameKind::Array | FrameKind::Set | FrameKind::Map => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the frame is an aggregate type or blob string.
+  pub fn is_streaming_type(&self) -> bool {
+    match *self {
+      FrameKind::Array | FrameKind::Set | FrameKind::Map | FrameKind::BlobString => true,
       _ => false,
     }
   }
@@ -246,7 +1199,20 @@ impl FrameKind {
     }
   }
 
+  /// Read the byte prefix for the associated frame type, or `None` for `HELLO`, which does not have a byte prefix.
+  ///
+  /// This is the inverse of [from_byte](Self::from_byte).
+  pub fn try_to_byte(&self) -> Option<u8> {
+    if self.is_hello() {
+      None
+    } else {
+      Some(self.to_byte())
+    }
+  }
+
   /// Read the byte prefix for the associated frame type.
+  ///
+  /// Panics for `HELLO`, which does not have a byte prefix. See [try_to_byte](Self::try_to_byte).
   pub fn to_byte(&self) -> u8 {
     use self::FrameKind::*;
 
@@ -281,11 +1247,206 @@ impl FrameKind {
       _ => false,
     }
   }
+
+  /// Read the name of the frame type from the RESP3 specification, such as `Blob String`.
+  ///
+  /// This is also the `Display` output.
+  pub fn name(&self) -> &'static str {
+    use self::FrameKind::*;
+
+    match *self {
+      Array => "Array",
+      BlobString => "Blob String",
+      SimpleString => "Simple String",
+      SimpleError => "Simple Error",
+      Number => "Number",
+      Null => "Null",
+      Double => "Double",
+      Boolean => "Boolean",
+      BlobError => "Blob Error",
+      VerbatimString => "Verbatim String",
+      Map => "Map",
+      Set => "Set",
+      Attribute => "Attribute",
+      Push => "Push",
+      Hello => "HELLO",
+      BigNumber => "Big Number",
+      ChunkedString => "Chunked String",
+      EndStream => "End Stream",
+    }
+  }
+}
+
+impl fmt::Display for FrameKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.name())
+  }
+}
+
+impl ProtocolFrameKind for FrameKind {
+  fn all() -> &'static [FrameKind] {
+    FrameKind::ALL
+  }
+
+  fn from_byte(byte: u8) -> Option<FrameKind> {
+    FrameKind::from_byte(byte)
+  }
+
+  fn try_to_byte(&self) -> Option<u8> {
+    FrameKind::try_to_byte(self)
+  }
+
+  fn name(&self) -> &'static str {
+    FrameKind::name(self)
+  }
+
+  fn is_aggregate(&self) -> bool {
+    FrameKind::is_aggregate(self)
+  }
+}
+
+/// The payload of a `SimpleString` or `SimpleError` frame.
+///
+/// Decoders store the bytes as they were read from the connection without checking that they are valid UTF-8, so
+/// frames that are only forwarded or re-encoded do not pay for UTF-8 validation. The bytes are validated when they
+/// are read as a `str`.
+///
+/// ```
+/// # use redis_protocol::resp3::types::LazyStr;
+/// let data = LazyStr::from("OK");
+/// assert_eq!(data, "OK");
+/// assert_eq!(data.as_str(), Some("OK"));
+///
+/// let data = LazyStr::from(vec![0xff, b'a']);
+/// assert_eq!(data.as_str(), None);
+/// assert_eq!(data.to_str_lossy(), "\u{fffd}a");
+/// ```
+#[derive(Clone, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct LazyStr {
+  data: Vec<u8>,
+}
+
+impl LazyStr {
+  /// Create a new string from bytes that may not be valid UTF-8.
+  pub fn from_bytes<B: Into<Vec<u8>>>(data: B) -> Self {
+    LazyStr { data: data.into() }
+  }
+
+  /// Read the bytes as a `str`, returning `None` if they are not valid UTF-8.
+  pub fn as_str(&self) -> Option<&str> {
+    str::from_utf8(&self.data).ok()
+  }
+
+  /// Read the bytes as a `str`, replacing invalid UTF-8 sequences with `U+FFFD`.
+  pub fn to_str_lossy(&self) -> Cow<'_, str> {
+    String::from_utf8_lossy(&self.data)
+  }
+
+  /// Read the underlying bytes.
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.data
+  }
+
+  /// Read the number of bytes in the string.
+  pub fn len(&self) -> usize {
+    self.data.len()
+  }
+
+  /// Whether or not the string is empty.
+  pub fn is_empty(&self) -> bool {
+    self.data.is_empty()
+  }
+
+  /// Replace the contents with `data`, reusing the existing allocation.
+  pub(crate) fn replace_bytes(&mut self, data: &[u8]) {
+    self.data.clear();
+    self.data.extend_from_slice(data);
+  }
+
+  /// Convert into the underlying bytes.
+  pub fn into_bytes(self) -> Vec<u8> {
+    self.data
+  }
+
+  /// Convert into a `String`, returning an error with the bytes if they are not valid UTF-8.
+  pub fn into_string(self) -> Result<String, FromUtf8Error> {
+    String::from_utf8(self.data)
+  }
+}
+
+impl fmt::Debug for LazyStr {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Debug::fmt(&self.to_str_lossy(), f)
+  }
+}
+
+impl fmt::Display for LazyStr {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fmt::Display::fmt(&self.to_str_lossy(), f)
+  }
+}
+
+impl AsRef<[u8]> for LazyStr {
+  fn as_ref(&self) -> &[u8] {
+    &self.data
+  }
+}
+
+impl From<String> for LazyStr {
+  fn from(data: String) -> Self {
+    LazyStr { data: data.into_bytes() }
+  }
+}
+
+impl<'a> From<&'a str> for LazyStr {
+  fn from(data: &'a str) -> Self {
+    LazyStr {
+      data: data.as_bytes().to_vec(),
+    }
+  }
+}
+
+impl<'a> From<&'a String> for LazyStr {
+  fn from(data: &'a String) -> Self {
+    data.as_str().into()
+  }
+}
+
+impl<'a> From<Cow<'a, str>> for LazyStr {
+  fn from(data: Cow<'a, str>) -> Self {
+    data.into_owned().into()
+  }
+}
+
+impl From<Vec<u8>> for LazyStr {
+  fn from(data: Vec<u8>) -> Self {
+    LazyStr { data }
+  }
+}
+
+impl PartialEq<str> for LazyStr {
+  fn eq(&self, other: &str) -> bool {
+    self.data == other.as_bytes()
+  }
+}
+
+impl<'a> PartialEq<&'a str> for LazyStr {
+  fn eq(&self, other: &&'a str) -> bool {
+    self.data == other.as_bytes()
+  }
+}
+
+impl PartialEq<String> for LazyStr {
+  fn eq(&self, other: &String) -> bool {
+    self.data == other.as_bytes()
+  }
 }
 
 /// An enum describing the possible data types in RESP3 along with the corresponding Rust data type to represent the payload.
 ///
 /// <https://github.com/antirez/RESP3/blob/master/spec.md>
+///
+/// Unlike [GenericFrame](crate::resp2::types::GenericFrame) in RESP2, RESP3 frames always own their contents.
 #[derive(Clone, Debug)]
 pub enum Frame {
   /// A binary-safe blob.
@@ -300,18 +1461,18 @@ pub enum Frame {
   },
   /// A small non binary-safe string.
   SimpleString {
-    data: String,
+    data: LazyStr,
     attributes: Option<Attributes>,
   },
   /// A small non binary-safe string representing an error.
   SimpleError {
-    data: String,
+    data: LazyStr,
     attributes: Option<Attributes>,
   },
   /// A boolean type.
   Boolean { data: bool, attributes: Option<Attributes> },
   /// A null type.
-  Null,
+  Null { attributes: Option<Attributes> },
   /// A signed 64 bit integer.
   Number { data: i64, attributes: Option<Attributes> },
   /// A signed 64 bit floating point number.
@@ -336,10 +1497,9 @@ pub enum Frame {
   },
   /// An unordered map of key-value pairs.
   ///
-  /// According to the spec keys can be any other RESP3 data type. However, it doesn't make sense to implement `Hash` for certain Rust data types like
-  /// `HashMap`, `Vec`, `HashSet`, etc, so this library limits the possible data types for keys to only those that can be hashed in a semi-sane way.
-  ///
-  /// For example, attempting to create a `Frame::Map<HashMap<Frame::Set<HashSet<Frame>>, Frame::Foo>>` from bytes will panic.
+  /// According to the spec keys can be any other RESP3 data type, including doubles and aggregate types. Aggregate
+  /// keys are hashed by their contents, except maps and sets which are hashed by their length since their iteration
+  /// order is not significant.
   Map {
     data: FrameMap,
     attributes: Option<Attributes>,
@@ -370,8 +1530,8 @@ impl Hash for Frame {
       SimpleString { ref data, .. } => data.hash(state),
       SimpleError { ref data, .. } => data.hash(state),
       Number { ref data, .. } => data.hash(state),
-      Null => NULL.hash(state),
-      Double { ref data, .. } => data.to_string().hash(state),
+      Null { .. } => NULL.hash(state),
+      Double { ref data, .. } => OrderedDouble(*data).hash(state),
       Boolean { ref data, .. } => data.hash(state),
       BlobError { ref data, .. } => data.hash(state),
       VerbatimString {
@@ -382,18 +1542,40 @@ impl Hash for Frame {
       }
       ChunkedString(ref data) => data.hash(state),
       BigNumber { ref data, .. } => data.hash(state),
-      _ => panic!("Invalid RESP3 data type to use as hash key."),
+      Array { ref data, .. } | Push { ref data, .. } => data.hash(state),
+      Map { ref data, .. } => data.len().hash(state),
+      Set { ref data, .. } => data.len().hash(state),
+      Hello { ref version, ref auth } => {
+        version.to_byte().hash(state);
+        auth.as_ref().map(|auth| (&auth.username, &auth.password)).hash(state);
+      }
     };
   }
 }
 
-impl PartialEq for Frame {
-  fn eq(&self, other: &Self) -> bool {
-    use self::Frame::*;
+impl ProtocolFrame for Frame {
+  type Kind = FrameKind;
 
-    match *self {
-      ChunkedString(ref b) => match *other {
-        ChunkedString(ref _b) => b == _b,
+  fn kind(&self) -> FrameKind {
+    Frame::kind(self)
+  }
+
+  fn encode_len(&self) -> Result<usize, RedisProtocolError> {
+    Frame::encode_len(self)
+  }
+
+  fn is_error(&self) -> bool {
+    Frame::is_error(self)
+  }
+}
+
+impl PartialEq for Frame {
+  fn eq(&self, other: &Self) -> bool {
+    use self::Frame::*;
+
+    match *self {
+      ChunkedString(ref b) => match *other {
+        ChunkedString(ref _b) => b == _b,
         _ => false,
       },
       Array {
@@ -461,8 +1643,8 @@ impl PartialEq for Frame {
           _ => false,
         }
       }
-      Null => match *other {
-        Null => true,
+      Null { .. } => match *other {
+        Null { .. } => true,
         _ => false,
       },
       Boolean {
@@ -484,10 +1666,11 @@ impl PartialEq for Frame {
       } => {
         let (_data, _attributes) = (data, attributes);
         match *other {
+          // NaN is equal to itself here so that `Eq` holds for frames
           Double {
             ref data,
             attributes: _,
-          } => data == _data && _attributes == _attributes,
+          } => OrderedDouble(*data) == OrderedDouble(*_data) && _attributes == _attributes,
           _ => false,
         }
       }
@@ -584,6 +1767,71 @@ impl PartialEq for Frame {
 
 impl Eq for Frame {}
 
+impl PartialOrd for Frame {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+/// A total order over frames, used for sorting and for `BTreeMap` or `BTreeSet` keys.
+///
+/// Frames are ordered by their [FrameKind] first, and then by their contents:
+///
+/// * Strings, errors, and big numbers are compared byte by byte.
+/// * Doubles are compared numerically, where `0.0` and `-0.0` are equal and `NaN` is greater than every other value.
+/// * Arrays and push frames are compared element by element.
+/// * Maps and sets are compared by their sorted entries, since their iteration order is not significant.
+///
+/// Attributes are not considered, which is consistent with `PartialEq`.
+impl Ord for Frame {
+  fn cmp(&self, other: &Self) -> Ordering {
+    use self::Frame::*;
+
+    let kind_ordering = self.kind().cmp(&other.kind());
+    if kind_ordering != Ordering::Equal {
+      return kind_ordering;
+    }
+
+    match (self, other) {
+      (BlobString { data: a, .. }, BlobString { data: b, .. })
+      | (BlobError { data: a, .. }, BlobError { data: b, .. })
+      | (BigNumber { data: a, .. }, BigNumber { data: b, .. })
+      | (ChunkedString(a), ChunkedString(b)) => a.cmp(b),
+      (SimpleString { data: a, .. }, SimpleString { data: b, .. })
+      | (SimpleError { data: a, .. }, SimpleError { data: b, .. }) => a.cmp(b),
+      (Number { data: a, .. }, Number { data: b, .. }) => a.cmp(b),
+      (Double { data: a, .. }, Double { data: b, .. }) => OrderedDouble(*a).cmp(&OrderedDouble(*b)),
+      (Boolean { data: a, .. }, Boolean { data: b, .. }) => a.cmp(b),
+      (
+        VerbatimString {
+          data: a, format: a_format, ..
+        },
+        VerbatimString {
+          data: b, format: b_format, ..
+        },
+      ) => a_format.cmp(b_format).then_with(|| a.cmp(b)),
+      (Array { data: a, .. }, Array { data: b, .. }) | (Push { data: a, .. }, Push { data: b, .. }) => a.cmp(b),
+      (Map { data: a, .. }, Map { data: b, .. }) => a.sorted().cmp(&b.sorted()),
+      (Set { data: a, .. }, Set { data: b, .. }) => a.sorted().cmp(&b.sorted()),
+      (
+        Hello {
+          version: a_version,
+          auth: a_auth,
+        },
+        Hello {
+          version: b_version,
+          auth: b_auth,
+        },
+      ) => {
+        let a_auth = a_auth.as_ref().map(|auth| (&auth.username, &auth.password));
+        let b_auth = b_auth.as_ref().map(|auth| (&auth.username, &auth.password));
+        a_version.cmp(b_version).then_with(|| a_auth.cmp(&b_auth))
+      }
+      _ => Ordering::Equal,
+    }
+  }
+}
+
 impl TryFrom<(FrameKind, Vec<u8>)> for Frame {
   type Error = RedisProtocolError;
 
@@ -659,7 +1907,7 @@ impl TryFrom<HashMap<Frame, Frame>> for Frame {
 
   fn try_from(value: HashMap<Frame, Frame>) -> Result<Self, Self::Error> {
     Ok(Frame::Map {
-      data: resp3_utils::hashmap_to_frame_map(value),
+      data: value.into(),
       attributes: None,
     })
   }
@@ -670,7 +1918,7 @@ impl TryFrom<HashSet<Frame>> for Frame {
 
   fn try_from(value: HashSet<Frame>) -> Result<Self, Self::Error> {
     Ok(Frame::Set {
-      data: resp3_utils::hashset_to_frame_set(value),
+      data: value.into(),
       attributes: None,
     })
   }
@@ -682,7 +1930,7 @@ impl TryFrom<IndexMap<Frame, Frame>> for Frame {
 
   fn try_from(value: IndexMap<Frame, Frame>) -> Result<Self, Self::Error> {
     Ok(Frame::Map {
-      data: value,
+      data: value.into(),
       attributes: None,
     })
   }
@@ -694,12 +1942,58 @@ impl TryFrom<IndexSet<Frame>> for Frame {
 
   fn try_from(value: IndexSet<Frame>) -> Result<Self, Self::Error> {
     Ok(Frame::Set {
-      data: value,
+      data: value.into(),
       attributes: None,
     })
   }
 }
 
+impl From<FrameMap> for Frame {
+  fn from(data: FrameMap) -> Self {
+    Frame::Map { data, attributes: None }
+  }
+}
+
+impl From<FrameSet> for Frame {
+  fn from(data: FrameSet) -> Self {
+    Frame::Set { data, attributes: None }
+  }
+}
+
+/// Collect frames into an array frame.
+///
+/// Set frames can be created by collecting into a [FrameSet] first.
+///
+/// ```
+/// # use redis_protocol::resp3::types::{Frame, FrameSet};
+/// let array: Frame = (1..3).map(Frame::from).collect();
+/// assert_eq!(array, Frame::Array {
+///   data: vec![Frame::from(1), Frame::from(2)],
+///   attributes: None,
+/// });
+///
+/// let set = Frame::from((1..3).map(Frame::from).collect::<FrameSet>());
+/// assert_eq!(set.len(), 2);
+/// ```
+impl FromIterator<Frame> for Frame {
+  fn from_iter<I: IntoIterator<Item = Frame>>(iter: I) -> Self {
+    Frame::Array {
+      data: iter.into_iter().collect(),
+      attributes: None,
+    }
+  }
+}
+
+/// Collect key-value pairs into a map frame.
+impl FromIterator<(Frame, Frame)> for Frame {
+  fn from_iter<I: IntoIterator<Item = (Frame, Frame)>>(iter: I) -> Self {
+    Frame::Map {
+      data: iter.into_iter().collect(),
+      attributes: None,
+    }
+  }
+}
+
 impl From<i64> for Frame {
   fn from(value: i64) -> Self {
     Frame::Number {
@@ -718,35 +2012,80 @@ impl From<bool> for Frame {
   }
 }
 
+impl From<OrderedDouble> for Frame {
+  fn from(value: OrderedDouble) -> Self {
+    Frame::Double {
+      data: value.0,
+      attributes: None,
+    }
+  }
+}
+
 impl TryFrom<f64> for Frame {
   type Error = RedisProtocolError;
 
   fn try_from(value: f64) -> Result<Self, Self::Error> {
-    if value.is_nan() {
-      Err(RedisProtocolError::new(
+    Ok(Frame::Double {
+      data: value,
+      attributes: None,
+    })
+  }
+}
+
+#[cfg(feature = "bignum")]
+impl From<BigInt> for Frame {
+  fn from(value: BigInt) -> Self {
+    Frame::BigNumber {
+      data: value.to_string().into_bytes(),
+      attributes: None,
+    }
+  }
+}
+
+#[cfg(feature = "bignum")]
+impl<'a> TryFrom<&'a Frame> for BigInt {
+  type Error = RedisProtocolError;
+
+  fn try_from(value: &'a Frame) -> Result<Self, Self::Error> {
+    match *value {
+      Frame::BigNumber { ref data, .. } if resp3_utils::is_big_number(data) => {
+        BigInt::parse_bytes(data, 10).ok_or_else(|| {
+          RedisProtocolError::new(RedisProtocolErrorKind::Unknown, "Invalid big number.")
+        })
+      }
+      Frame::BigNumber { .. } => Err(RedisProtocolError::new(
         RedisProtocolErrorKind::Unknown,
-        "Cannot cast NaN to double.",
-      ))
-    } else {
-      Ok(Frame::Double {
-        data: value,
-        attributes: None,
-      })
+        "Invalid big number.",
+      )),
+      Frame::Number { data, .. } => Ok(data.into()),
+      _ => Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::Unknown,
+        "Cannot convert to BigInt.",
+      )),
     }
   }
 }
 
+#[cfg(feature = "bignum")]
+impl TryFrom<Frame> for BigInt {
+  type Error = RedisProtocolError;
+
+  fn try_from(value: Frame) -> Result<Self, Self::Error> {
+    BigInt::try_from(&value)
+  }
+}
+
 impl TryFrom<(FrameKind, String)> for Frame {
   type Error = RedisProtocolError;
 
   fn try_from((kind, value): (FrameKind, String)) -> Result<Self, Self::Error> {
     let frame = match kind {
       FrameKind::SimpleError => Frame::SimpleError {
-        data: value,
+        data: value.into(),
         attributes: None,
       },
       FrameKind::SimpleString => Frame::SimpleString {
-        data: value,
+        data: value.into(),
         attributes: None,
       },
       FrameKind::BlobError => Frame::BlobError {
@@ -785,23 +2124,9 @@ impl<'a> TryFrom<(FrameKind, &'a str)> for Frame {
 impl Frame {
   /// Whether or not the frame can be used as a key in a `HashMap` or `HashSet`.
   ///
-  /// Not all frame types can be hashed, and trying to do so can panic. This function can be used to handle this gracefully.
+  /// All frame types can be hashed, so this always returns `true`.
   pub fn can_hash(&self) -> bool {
-    match self.kind() {
-      FrameKind::BlobString
-      | FrameKind::BlobError
-      | FrameKind::SimpleString
-      | FrameKind::SimpleError
-      | FrameKind::Number
-      | FrameKind::Double
-      | FrameKind::Boolean
-      | FrameKind::Null
-      | FrameKind::ChunkedString
-      | FrameKind::EndStream
-      | FrameKind::VerbatimString
-      | FrameKind::BigNumber => true,
-      _ => false,
-    }
+    true
   }
 
   /// Read the attributes attached to the frame.
@@ -820,12 +2145,45 @@ impl Frame {
       Frame::SimpleString { ref attributes, .. } => attributes,
       Frame::Set { ref attributes, .. } => attributes,
       Frame::Map { ref attributes, .. } => attributes,
-      Frame::Null | Frame::ChunkedString(_) | Frame::Hello { .. } => return None,
+      Frame::Null { ref attributes } => attributes,
+      Frame::ChunkedString(_) | Frame::Hello { .. } => return None,
     };
 
     attributes.as_ref()
   }
 
+  /// Read the attribute with the provided name, if the key is a string frame.
+  pub fn attribute(&self, key: &str) -> Option<&Frame> {
+    self
+      .attributes()?
+      .iter()
+      .find(|(k, _)| k.as_str() == Some(key))
+      .map(|(_, v)| v)
+  }
+
+  /// Read the `key-popularity` attribute as a list of `(key, popularity)` pairs.
+  ///
+  /// The attribute may be a map or a flat array of alternating keys and doubles. Entries that cannot be read as a
+  /// string and a number are skipped.
+  ///
+  /// <https://github.com/antirez/RESP3/blob/master/spec.md#attribute-type>
+  pub fn key_popularity(&self) -> Option<Vec<(&str, f64)>> {
+    fn read_pair<'a>(key: &'a Frame, value: &Frame) -> Option<(&'a str, f64)> {
+      key.as_str().and_then(|key| value.as_f64().map(|value| (key, value)))
+    }
+
+    match *self.attribute("key-popularity")? {
+      Frame::Map { ref data, .. } => Some(data.iter().filter_map(|(k, v)| read_pair(k, v)).collect()),
+      Frame::Array { ref data, .. } => Some(data.chunks_exact(2).filter_map(|c| read_pair(&c[0], &c[1])).collect()),
+      _ => None,
+    }
+  }
+
+  /// Read the `ttl` attribute as a number of seconds.
+  pub fn ttl(&self) -> Option<i64> {
+    self.attribute("ttl").and_then(|f| f.as_i64())
+  }
+
   /// Take the attributes off this frame.
   pub fn take_attributes(&mut self) -> Option<Attributes> {
     let attributes = match *self {
@@ -842,7 +2200,8 @@ impl Frame {
       Frame::SimpleString { ref mut attributes, .. } => attributes,
       Frame::Set { ref mut attributes, .. } => attributes,
       Frame::Map { ref mut attributes, .. } => attributes,
-      Frame::Null | Frame::ChunkedString(_) | Frame::Hello { .. } => return None,
+      Frame::Null { ref mut attributes } => attributes,
+      Frame::ChunkedString(_) | Frame::Hello { .. } => return None,
     };
 
     attributes.take()
@@ -864,7 +2223,8 @@ impl Frame {
       Frame::SimpleString { ref mut attributes, .. } => attributes,
       Frame::Set { ref mut attributes, .. } => attributes,
       Frame::Map { ref mut attributes, .. } => attributes,
-      Frame::Null | Frame::ChunkedString(_) | Frame::Hello { .. } => return None,
+      Frame::Null { ref mut attributes } => attributes,
+      Frame::ChunkedString(_) | Frame::Hello { .. } => return None,
     };
 
     attributes.as_mut()
@@ -886,10 +2246,11 @@ impl Frame {
       Frame::SimpleString { ref mut attributes, .. } => attributes,
       Frame::Set { ref mut attributes, .. } => attributes,
       Frame::Map { ref mut attributes, .. } => attributes,
-      Frame::Null | Frame::ChunkedString(_) | Frame::Hello { .. } => {
+      Frame::Null { ref mut attributes } => attributes,
+      Frame::ChunkedString(_) | Frame::Hello { .. } => {
         return Err(RedisProtocolError::new(
           RedisProtocolErrorKind::Unknown,
-          format!("{:?} cannot have attributes.", self.kind()),
+          format!("{} cannot have attributes.", self.kind()),
         ))
       }
     };
@@ -903,404 +2264,1687 @@ impl Frame {
     Ok(())
   }
 
-  /// Create a new `Frame` that terminates a stream.
-  pub fn new_end_stream() -> Self {
-    Frame::ChunkedString(vec![])
-  }
-
-  /// A context-aware length function that returns the length of the inner frame contents.
-  ///
-  /// This does not return the encoded length, but rather the length of the contents of the frame such as the number of elements in an array, the size of any inner buffers, etc.
-  ///
-  /// Note: `Null` has a length of 0 and `Hello`, `Number`, `Double`, and `Boolean` have a length of 1.
+  /// Attempt to append frames to an array, push, or set frame.
   ///
-  /// See [encode_len](Self::encode_len) to read the number of bytes necessary to encode the frame.
-  pub fn len(&self) -> usize {
-    use self::Frame::*;
-
+  /// An error is returned without reading from `iter` if the frame is any other kind of frame.
+  pub fn try_extend<I: IntoIterator<Item = Frame>>(&mut self, iter: I) -> Result<(), RedisProtocolError> {
     match *self {
-      Array { ref data, .. } | Push { ref data, .. } => data.len(),
-      BlobString { ref data, .. }
-      | BlobError { ref data, .. }
-      | BigNumber { ref data, .. }
-      | ChunkedString(ref data) => data.len(),
-      SimpleString { ref data, .. } | SimpleError { ref data, .. } => data.len(),
-      Number { .. } | Double { .. } | Boolean { .. } => 1,
-      Null => 0,
-      VerbatimString { ref data, .. } => data.len(),
-      Map { ref data, .. } => data.len(),
-      Set { ref data, .. } => data.len(),
-      Hello { .. } => 1,
-    }
-  }
+      Frame::Array { ref mut data, .. } | Frame::Push { ref mut data, .. } => data.extend(iter),
+      Frame::Set { ref mut data, .. } => data.extend(iter),
+      _ => {
+        return Err(RedisProtocolError::new(
+          RedisProtocolErrorKind::Unknown,
+          format!("Cannot extend {} frame with frames.", self.kind()),
+        ))
+      }
+    };
 
-  /// Replace `self` with Null, returning the original value.
-  pub fn take(&mut self) -> Frame {
-    mem::replace(self, Frame::Null)
+    Ok(())
   }
 
-  /// Read the associated `FrameKind`.
-  pub fn kind(&self) -> FrameKind {
-    use self::Frame::*;
-
+  /// Attempt to insert key-value pairs into a map frame.
+  ///
+  /// An error is returned without reading from `iter` if the frame is not a map frame.
+  pub fn try_extend_pairs<I: IntoIterator<Item = (Frame, Frame)>>(
+    &mut self,
+    iter: I,
+  ) -> Result<(), RedisProtocolError> {
     match *self {
-      Array { .. } => FrameKind::Array,
-      BlobString { .. } => FrameKind::BlobString,
-      SimpleString { .. } => FrameKind::SimpleString,
-      SimpleError { .. } => FrameKind::SimpleError,
-      Number { .. } => FrameKind::Number,
-      Null => FrameKind::Null,
-      Double { .. } => FrameKind::Double,
-      BlobError { .. } => FrameKind::BlobError,
-      VerbatimString { .. } => FrameKind::VerbatimString,
-      Boolean { .. } => FrameKind::Boolean,
-      Map { .. } => FrameKind::Map,
-      Set { .. } => FrameKind::Set,
-      Push { .. } => FrameKind::Push,
-      Hello { .. } => FrameKind::Hello,
-      BigNumber { .. } => FrameKind::BigNumber,
-      ChunkedString(ref inner) => {
-        if inner.is_empty() {
-          FrameKind::EndStream
-        } else {
-          FrameKind::ChunkedString
-        }
+      Frame::Map { ref mut data, .. } => data.extend(iter),
+      _ => {
+        return Err(RedisProtocolError::new(
+          RedisProtocolErrorKind::Unknown,
+          format!("Cannot extend {} frame with key-value pairs.", self.kind()),
+        ))
       }
-    }
+    };
+
+    Ok(())
   }
 
-  /// Whether or not the frame represents an array of frames.
-  pub fn is_array(&self) -> bool {
+  fn attributes_slot(&mut self) -> Option<&mut Option<Attributes>> {
     match *self {
-      Frame::Array { .. } => true,
-      _ => false,
+      Frame::Array { ref mut attributes, .. } => Some(attributes),
+      Frame::Push { ref mut attributes, .. } => Some(attributes),
+      Frame::BlobString { ref mut attributes, .. } => Some(attributes),
+      Frame::BlobError { ref mut attributes, .. } => Some(attributes),
+      Frame::BigNumber { ref mut attributes, .. } => Some(attributes),
+      Frame::Boolean { ref mut attributes, .. } => Some(attributes),
+      Frame::Number { ref mut attributes, .. } => Some(attributes),
+      Frame::Double { ref mut attributes, .. } => Some(attributes),
+      Frame::VerbatimString { ref mut attributes, .. } => Some(attributes),
+      Frame::SimpleError { ref mut attributes, .. } => Some(attributes),
+      Frame::SimpleString { ref mut attributes, .. } => Some(attributes),
+      Frame::Set { ref mut attributes, .. } => Some(attributes),
+      Frame::Map { ref mut attributes, .. } => Some(attributes),
+      Frame::Null { ref mut attributes } => Some(attributes),
+      Frame::ChunkedString(_) | Frame::Hello { .. } => None,
     }
   }
 
-  /// Whether or not the frame represents data pushed to the client from the server.
-  pub fn is_push(&self) -> bool {
-    match *self {
-      Frame::Push { .. } => true,
-      _ => false,
-    }
+  /// Replace the attributes on the frame, returning an error if the frame cannot have attributes.
+  pub fn with_attributes(mut self, attributes: Attributes) -> Result<Self, RedisProtocolError> {
+    match self.attributes_slot() {
+      Some(slot) => *slot = Some(attributes),
+      None => {
+        return Err(RedisProtocolError::new(
+          RedisProtocolErrorKind::Unknown,
+          format!("{} cannot have attributes.", self.kind()),
+        ))
+      }
+    };
+
+    Ok(self)
   }
 
-  /// Whether or not the frame is a boolean value.
-  pub fn is_boolean(&self) -> bool {
-    match *self {
-      Frame::Boolean { .. } => true,
-      _ => false,
+  /// Replace the attributes on the frame with the result of `func`.
+  ///
+  /// Frames that cannot have attributes are returned unchanged without calling `func`.
+  pub fn map_attributes<F>(mut self, func: F) -> Self
+  where
+    F: FnOnce(Option<Attributes>) -> Option<Attributes>,
+  {
+    if let Some(slot) = self.attributes_slot() {
+      *slot = func(slot.take());
     }
+
+    self
   }
 
-  /// Whether or not the frame represents an error.
-  pub fn is_error(&self) -> bool {
-    match *self {
-      Frame::BlobError { .. } | Frame::SimpleError { .. } => true,
-      _ => false,
-    }
+  /// Replace the attributes on the frame and any inner frames with the result of `func`.
+  ///
+  /// Inner frames are visited before the frame that contains them, and map keys are visited before their values.
+  pub fn map_attributes_recursive<F>(mut self, mut func: F) -> Self
+  where
+    F: FnMut(Option<Attributes>) -> Option<Attributes>,
+  {
+    self.map_attributes_in_place(&mut func);
+    self
   }
 
-  /// Whether or not the frame is an array, map, or set.
-  pub fn is_aggregate_type(&self) -> bool {
+  fn map_attributes_in_place<F>(&mut self, func: &mut F)
+  where
+    F: FnMut(Option<Attributes>) -> Option<Attributes>,
+  {
     match *self {
-      Frame::Map { .. } | Frame::Set { .. } | Frame::Array { .. } => true,
-      _ => false,
+      Frame::Array { ref mut data, .. } | Frame::Push { ref mut data, .. } => {
+        for frame in data.iter_mut() {
+          frame.map_attributes_in_place(func);
+        }
+      }
+      Frame::Map { ref mut data, .. } => {
+        let map = mem::replace(data, resp3_utils::new_map(Some(data.len())));
+        for (mut key, mut value) in map.into_iter() {
+          key.map_attributes_in_place(func);
+          value.map_attributes_in_place(func);
+          data.insert(key, value);
+        }
+      }
+      Frame::Set { ref mut data, .. } => {
+        let set = mem::replace(data, resp3_utils::new_set(Some(data.len())));
+        for mut value in set.into_iter() {
+          value.map_attributes_in_place(func);
+          data.insert(value);
+        }
+      }
+      _ => {}
     }
-  }
 
-  /// Whether or not the frame represents a `BlobString` or `BlobError`.
-  pub fn is_blob(&self) -> bool {
-    match *self {
-      Frame::BlobString { .. } | Frame::BlobError { .. } => true,
-      _ => false,
+    if let Some(slot) = self.attributes_slot() {
+      *slot = func(slot.take());
     }
   }
 
-  /// Whether or not the frame represents a chunked string.
-  pub fn is_chunked_string(&self) -> bool {
-    match *self {
-      Frame::ChunkedString(_) => true,
-      _ => false,
+  /// Rebuild the frame by replacing each frame that is not an array, set, map, or push frame with the result of
+  /// `func`, including map keys.
+  ///
+  /// Attributes are removed before calling `func` and added back to the returned frame if it does not have any
+  /// attributes of its own. Attributes on aggregate frames are preserved.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::types::Frame;
+  /// let frame = Frame::Array {
+  ///   data: vec![Frame::from(1), Frame::from(2)],
+  ///   attributes: None,
+  /// };
+  /// let doubled = frame.map_values(|f| Frame::from(f.as_i64().unwrap_or(0) * 2));
+  /// assert_eq!(doubled.into_vec_of::<i64>().unwrap(), vec![2, 4]);
+  /// ```
+  pub fn map_values<F>(self, mut func: F) -> Self
+  where
+    F: FnMut(Frame) -> Frame,
+  {
+    match self.try_map_inner(&mut |frame| Ok::<_, Infallible>(func(frame)), false) {
+      Ok(frame) => frame,
+      Err(e) => match e {},
     }
   }
 
-  /// Whether or not the frame is an empty chunked string, signifying the end of a chunked string stream.
-  pub fn is_end_stream_frame(&self) -> bool {
-    match *self {
-      Frame::ChunkedString(ref s) => s.is_empty(),
-      _ => false,
-    }
+  /// Rebuild the frame by replacing each frame with the result of `func`, stopping at the first error.
+  ///
+  /// Inner frames are visited before the frame that contains them, and map keys are visited before their values.
+  /// Aggregate frames are passed to `func` after their inner frames are replaced, so `func` can return them unchanged
+  /// to only modify the other frames. Attributes are handled in the same way as [map_values](Self::map_values).
+  pub fn try_map<F, E>(self, mut func: F) -> Result<Self, E>
+  where
+    F: FnMut(Frame) -> Result<Frame, E>,
+  {
+    self.try_map_inner(&mut func, true)
   }
 
-  /// Whether or not the frame is a verbatim string.
-  pub fn is_verbatim_string(&self) -> bool {
-    match *self {
-      Frame::VerbatimString { .. } => true,
-      _ => false,
+  fn try_map_inner<F, E>(self, func: &mut F, aggregates: bool) -> Result<Self, E>
+  where
+    F: FnMut(Frame) -> Result<Frame, E>,
+  {
+    let frame = match self {
+      Frame::Array { data, attributes } => Frame::Array {
+        data: data
+          .into_iter()
+          .map(|frame| frame.try_map_inner(func, aggregates))
+          .collect::<Result<_, _>>()?,
+        attributes,
+      },
+      Frame::Push { data, attributes } => Frame::Push {
+        data: data
+          .into_iter()
+          .map(|frame| frame.try_map_inner(func, aggregates))
+          .collect::<Result<_, _>>()?,
+        attributes,
+      },
+      Frame::Map { data, attributes } => {
+        let mut out = resp3_utils::new_map(Some(data.len()));
+        for (key, value) in data.into_iter() {
+          let key = key.try_map_inner(func, aggregates)?;
+          out.insert(key, value.try_map_inner(func, aggregates)?);
+        }
+
+        Frame::Map { data: out, attributes }
+      }
+      Frame::Set { data, attributes } => {
+        let mut out = resp3_utils::new_set(Some(data.len()));
+        for value in data.into_iter() {
+          out.insert(value.try_map_inner(func, aggregates)?);
+        }
+
+        Frame::Set { data: out, attributes }
+      }
+      frame => return frame.map_preserving_attributes(func),
+    };
+
+    if aggregates {
+      frame.map_preserving_attributes(func)
+    } else {
+      Ok(frame)
     }
   }
 
-  /// If the frame is a verbatim string then read the associated format.
-  pub fn verbatim_string_format(&self) -> Option<&VerbatimStringFormat> {
-    match *self {
-      Frame::VerbatimString { ref format, .. } => Some(format),
-      _ => None,
+  fn map_preserving_attributes<F, E>(mut self, func: &mut F) -> Result<Self, E>
+  where
+    F: FnMut(Frame) -> Result<Frame, E>,
+  {
+    let attributes = self.take_attributes();
+    let mut frame = func(self)?;
+
+    if let (Some(attributes), Some(slot)) = (attributes, frame.attributes_slot()) {
+      if slot.is_none() {
+        *slot = Some(attributes);
+      }
     }
+    Ok(frame)
   }
 
-  /// Read the frame as a string slice if it can be parsed as a UTF-8 string without allocating.
+  /// Whether or not two frames are equal when attributes are ignored on the frames and any inner frames.
   ///
-  /// Numbers and Doubles will not be cast to a string since that would require allocating.
-  pub fn as_str(&self) -> Option<&str> {
-    match *self {
-      Frame::SimpleError { ref data, .. } | Frame::SimpleString { ref data, .. } => Some(data),
-      Frame::BlobError { ref data, .. } | Frame::BlobString { ref data, .. } | Frame::BigNumber { ref data, .. } => {
-        str::from_utf8(data).ok()
+  /// This is useful when comparing server replies against expected values, since servers may attach attributes such
+  /// as client tracking metadata to any frame.
+  pub fn eq_ignoring_attributes(&self, other: &Frame) -> bool {
+    use self::Frame::*;
+
+    match (self, other) {
+      (Array { data: a, .. }, Array { data: b, .. }) | (Push { data: a, .. }, Push { data: b, .. }) => {
+        a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.eq_ignoring_attributes(b))
       }
-      Frame::VerbatimString { ref data, .. } => str::from_utf8(data).ok(),
-      Frame::ChunkedString(ref data) => str::from_utf8(data).ok(),
-      _ => None,
+      (Map { data: a, .. }, Map { data: b, .. }) => {
+        a.len() == b.len()
+          && a
+            .sorted()
+            .into_iter()
+            .zip(b.sorted())
+            .all(|((ak, av), (bk, bv))| ak.eq_ignoring_attributes(bk) && av.eq_ignoring_attributes(bv))
+      }
+      (Set { data: a, .. }, Set { data: b, .. }) => {
+        a.len() == b.len()
+          && a
+            .sorted()
+            .into_iter()
+            .zip(b.sorted())
+            .all(|(a, b)| a.eq_ignoring_attributes(b))
+      },
+      (
+        VerbatimString {
+          data: a, format: af, ..
+        },
+        VerbatimString {
+          data: b, format: bf, ..
+        },
+      ) => a == b && af == bf,
+      _ => self.cmp(other) == Ordering::Equal,
     }
   }
 
-  /// Read the frame as a `String` if it can be parsed as a UTF-8 string.
-  pub fn to_string(&self) -> Option<String> {
+  /// Remove the attributes from the frame and any inner frames.
+  pub fn strip_attributes(&mut self) {
+    let _ = self.take_attributes();
+
     match *self {
-      Frame::SimpleError { ref data, .. } | Frame::SimpleString { ref data, .. } => Some(data.to_owned()),
-      Frame::BlobError { ref data, .. } | Frame::BlobString { ref data, .. } | Frame::BigNumber { ref data, .. } => {
-        String::from_utf8(data.to_vec()).ok()
+      Frame::Array { ref mut data, .. } | Frame::Push { ref mut data, .. } => {
+        for frame in data.iter_mut() {
+          frame.strip_attributes();
+        }
       }
-      Frame::VerbatimString { ref data, .. } => String::from_utf8(data.to_vec()).ok(),
-      Frame::ChunkedString(ref b) => String::from_utf8(b.to_vec()).ok(),
-      Frame::Double { ref data, .. } => Some(data.to_string()),
-      Frame::Number { ref data, .. } => Some(data.to_string()),
-      _ => None,
+      Frame::Map { ref mut data, .. } => {
+        let map = mem::replace(data, resp3_utils::new_map(Some(data.len())));
+        for (mut key, mut value) in map.into_iter() {
+          key.strip_attributes();
+          value.strip_attributes();
+          data.insert(key, value);
+        }
+      }
+      Frame::Set { ref mut data, .. } => {
+        let set = mem::replace(data, resp3_utils::new_set(Some(data.len())));
+        for mut value in set.into_iter() {
+          value.strip_attributes();
+          data.insert(value);
+        }
+      }
+      _ => {}
     }
   }
 
-  /// Attempt to read the frame as a byte slice.
+  /// Create a new `Frame` that terminates a stream.
+  pub fn new_end_stream() -> Self {
+    Frame::ChunkedString(vec![])
+  }
+
+  /// Create a new `BlobString` frame without attributes.
   ///
-  /// Number and Double will not be returned as a byte slice. Use [number_or_double_as_bytes](Self::number_or_double_as_bytes) instead.
-  pub fn as_bytes(&self) -> Option<&[u8]> {
-    match *self {
-      Frame::SimpleError { ref data, .. } | Frame::SimpleString { ref data, .. } => Some(data.as_bytes()),
-      Frame::BlobError { ref data, .. } | Frame::BlobString { ref data, .. } | Frame::BigNumber { ref data, .. } => {
-        Some(data)
-      }
-      Frame::VerbatimString { ref data, .. } => Some(data),
-      Frame::ChunkedString(ref b) => Some(b),
-      _ => None,
+  /// The constructors for each frame type are shorthand for the struct literals, which can still be used to create
+  /// frames with attributes.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::types::Frame;
+  /// let frame = Frame::new_map(vec![(Frame::new_simple("foo"), Frame::new_blob("bar"))]);
+  /// let (key, value) = frame.map_pairs().unwrap().next().unwrap();
+  /// assert_eq!((key, value), (&Frame::new_simple("foo"), &Frame::new_blob("bar")));
+  /// assert_eq!(Frame::new_number(1), Frame::Number {
+  ///   data: 1,
+  ///   attributes: None,
+  /// });
+  /// ```
+  pub fn new_blob<B: Into<Vec<u8>>>(data: B) -> Self {
+    Frame::BlobString {
+      data: data.into(),
+      attributes: None,
     }
   }
 
-  /// Attempt the read the frame as bytes if the inner type is an `i64` or `f64`.
-  pub fn number_or_double_as_bytes(&self, ordering: ByteOrder) -> Option<[u8; 8]> {
-    match *self {
-      Frame::Double { ref data, .. } => Some(match ordering {
-        ByteOrder::BigEndian => data.to_be_bytes(),
-        ByteOrder::LittleEndian => data.to_le_bytes(),
-      }),
-      Frame::Number { ref data, .. } => Some(match ordering {
-        ByteOrder::BigEndian => data.to_be_bytes(),
-        ByteOrder::LittleEndian => data.to_le_bytes(),
-      }),
-      _ => None,
+  /// Create a new `BlobError` frame without attributes.
+  pub fn new_blob_error<B: Into<Vec<u8>>>(data: B) -> Self {
+    Frame::BlobError {
+      data: data.into(),
+      attributes: None,
     }
   }
 
-  /// Attempt to read the frame as an `i64`.
-  pub fn as_i64(&self) -> Option<i64> {
-    match *self {
-      Frame::Number { ref data, .. } => Some(*data),
-      Frame::Double { ref data, .. } => Some(*data as i64),
-      Frame::BlobString { ref data, .. } => str::from_utf8(data).ok().and_then(|s| s.parse::<i64>().ok()),
-      Frame::SimpleString { ref data, .. } => data.parse::<i64>().ok(),
-      _ => None,
+  /// Create a new `SimpleString` frame without attributes.
+  ///
+  /// The string is not checked for `CRLF` sequences, which cannot be encoded in a simple string.
+  pub fn new_simple<S: Into<LazyStr>>(data: S) -> Self {
+    Frame::SimpleString {
+      data: data.into(),
+      attributes: None,
     }
   }
 
-  /// Attempt to read the frame as an `f64`.
-  pub fn as_f64(&self) -> Option<f64> {
-    match *self {
-      Frame::Double { ref data, .. } => Some(*data),
-      Frame::Number { ref data, .. } => Some(*data as f64),
-      Frame::BlobString { ref data, .. } => str::from_utf8(data).ok().and_then(|s| s.parse::<f64>().ok()),
-      Frame::SimpleString { ref data, .. } => data.parse::<f64>().ok(),
-      _ => None,
+  /// Create a new `SimpleError` frame without attributes.
+  ///
+  /// The string is not checked for `CRLF` sequences, which cannot be encoded in a simple error.
+  pub fn new_simple_error<S: Into<LazyStr>>(data: S) -> Self {
+    Frame::SimpleError {
+      data: data.into(),
+      attributes: None,
     }
   }
 
-  /// Whether or not the frame represents a MOVED or ASK error.
-  pub fn is_moved_or_ask_error(&self) -> bool {
-    match *self {
-      Frame::SimpleError { ref data, .. } => utils::is_cluster_error(data),
-      _ => false,
-    }
+  /// Create a new `Boolean` frame without attributes.
+  pub fn new_boolean(data: bool) -> Self {
+    Frame::Boolean { data, attributes: None }
   }
 
-  /// Attempt to parse the frame as a cluster redirection error.
-  pub fn to_redirection(&self) -> Option<Redirection> {
-    match *self {
-      Frame::SimpleError { ref data, .. } => utils::read_cluster_error(data),
-      _ => None,
+  /// Create a new `Null` frame without attributes.
+  pub fn new_null() -> Self {
+    Frame::Null { attributes: None }
+  }
+
+  /// Create a new `Number` frame without attributes.
+  pub fn new_number(data: i64) -> Self {
+    Frame::Number { data, attributes: None }
+  }
+
+  /// Create a new `Double` frame without attributes.
+  pub fn new_double(data: f64) -> Self {
+    Frame::Double { data, attributes: None }
+  }
+
+  /// Create a new `BigNumber` frame without attributes.
+  ///
+  /// The number is not checked. With the `bignum` feature a `BigInt` can be converted into a frame instead.
+  pub fn new_big_number<B: Into<Vec<u8>>>(data: B) -> Self {
+    Frame::BigNumber {
+      data: data.into(),
+      attributes: None,
     }
   }
 
-  /// Whether or not the frame represents a publish-subscribe message, but not a pattern publish-subscribe message.
-  pub fn is_normal_pubsub(&self) -> bool {
-    if let Frame::Push { ref data, .. } = *self {
-      resp3_utils::is_normal_pubsub(data)
-    } else {
-      false
+  /// Create a new `VerbatimString` frame without attributes.
+  pub fn new_verbatim<B: Into<Vec<u8>>>(format: VerbatimStringFormat, data: B) -> Self {
+    Frame::VerbatimString {
+      data: data.into(),
+      format,
+      attributes: None,
     }
   }
 
-  /// Whether or not the frame represents a message on a publish-subscribe channel.
-  pub fn is_pubsub_message(&self) -> bool {
-    if let Frame::Push { ref data, .. } = *self {
-      resp3_utils::is_normal_pubsub(data) || resp3_utils::is_pattern_pubsub(data)
-    } else {
-      false
+  /// Create a new `Array` frame without attributes.
+  pub fn new_array<I: IntoIterator<Item = Frame>>(data: I) -> Self {
+    Frame::Array {
+      data: data.into_iter().collect(),
+      attributes: None,
     }
   }
 
-  /// Whether or not the frame represents a message on a publish-subscribe channel matched against a pattern subscription.
-  pub fn is_pattern_pubsub_message(&self) -> bool {
-    if let Frame::Push { ref data, .. } = *self {
-      resp3_utils::is_pattern_pubsub(data)
-    } else {
-      false
+  /// Create a new `Map` frame without attributes.
+  ///
+  /// If a key is repeated the last value is used.
+  pub fn new_map<I: IntoIterator<Item = (Frame, Frame)>>(pairs: I) -> Self {
+    Frame::Map {
+      data: pairs.into_iter().collect(),
+      attributes: None,
     }
   }
 
-  /// Attempt to parse the frame as a publish-subscribe message, returning the `(channel, message)` tuple
-  /// if successful, or the original frame if the inner data is not a publish-subscribe message.
-  pub fn parse_as_pubsub(self) -> Result<(Frame, Frame), Self> {
-    if self.is_pubsub_message() {
-      if let Frame::Push { mut data, .. } = self {
-        // array len checked in `is_pubsub_message`
-        let message = data.pop().unwrap();
-        let channel = data.pop().unwrap();
+  /// Create a new `Set` frame without attributes.
+  pub fn new_set<I: IntoIterator<Item = Frame>>(data: I) -> Self {
+    Frame::Set {
+      data: data.into_iter().collect(),
+      attributes: None,
+    }
+  }
 
-        Ok((channel, message))
-      } else {
-        warn!("Invalid pubsub frame. Expected a Push frame.");
-        Err(self)
-      }
-    } else {
-      Err(self)
+  /// Create a new `Push` frame without attributes.
+  pub fn new_push<I: IntoIterator<Item = Frame>>(data: I) -> Self {
+    Frame::Push {
+      data: data.into_iter().collect(),
+      attributes: None,
     }
   }
 
-  /// Attempt to read the number of bytes needed to encode the frame.
-  pub fn encode_len(&self) -> Result<usize, RedisProtocolError> {
-    resp3_utils::encode_len(self).map_err(|e| e.into())
+  /// Create a new `HELLO` frame.
+  pub fn new_hello(version: RespVersion, auth: Option<Auth>) -> Self {
+    Frame::Hello { version, auth }
   }
-}
 
-/// A helper struct for reading and managing streaming data types.
-///
-/// ```rust edition2018
-/// use redis_protocol::resp3::decode::streaming::decode;
-///
-/// fn main() {
-///   let parts = vec!["*?\r\n", ":1\r\n", ":2\r\n:3\r\n", ".\r\n"];
-///
-///   let (frame, _) = decode(parts[0].as_bytes()).unwrap().unwrap();
-///   assert!(frame.is_streaming());
-///   let mut streaming = frame.into_streaming_frame().unwrap();
-///   println!("Reading streaming {:?}", streaming.kind);
-///
-///   let (frame, _) = decode(parts[1].as_bytes()).unwrap().unwrap();
-///   assert!(frame.is_complete());
-///   // add frames to the buffer until we reach the terminating byte sequence
-///   streaming.add_frame(frame.into_complete_frame().unwrap());
-///
-///   let (frame, _) = decode(parts[2].as_bytes()).unwrap().unwrap();
-///   assert!(frame.is_complete());
-///   streaming.add_frame(frame.into_complete_frame().unwrap());
-///
-///   let (frame, _) = decode(parts[3].as_bytes()).unwrap().unwrap();
-///   assert!(frame.is_complete());
-///   streaming.add_frame(frame.into_complete_frame().unwrap());
-///
-///   assert!(streaming.is_finished());
-///   // convert the buffer into one frame
-///   let result = streaming.into_frame().unwrap();
-///
-///   // Frame::Array { data: [1, 2, 3], attributes: None }
-///   println!("{:?}", result);
-/// }
-/// ```
-#[derive(Debug, Eq, PartialEq)]
-pub struct StreamedFrame {
-  /// The internal buffer of frames and attributes.
-  buffer: VecDeque<Frame>,
-  /// Any leading attributes before the stream starts.
-  pub attributes: Option<Attributes>,
-  /// The data type being streamed.  
-  pub kind: FrameKind,
-}
+  /// Create a new `Frame` containing one chunk of a streaming string.
+  ///
+  /// An empty chunk terminates the stream, see [new_end_stream](Self::new_end_stream).
+  pub fn new_chunk<B: Into<Vec<u8>>>(data: B) -> Self {
+    Frame::ChunkedString(data.into())
+  }
 
-impl StreamedFrame {
-  /// Create a new `StreamedFrame` from the first section of data in a streaming response.
-  pub fn new(kind: FrameKind) -> Self {
-    let buffer = VecDeque::new();
-    StreamedFrame {
-      buffer,
-      kind,
-      attributes: None,
+  /// A context-aware length function that returns the length of the inner frame contents.
+  ///
+  /// This does not return the encoded length, but rather the length of the contents of the frame such as the number of elements in an array, the size of any inner buffers, etc.
+  ///
+  /// Note: `Null` has a length of 0 and `Hello`, `Number`, `Double`, and `Boolean` have a length of 1.
+  ///
+  /// See [encode_len](Self::encode_len) to read the number of bytes necessary to encode the frame.
+  pub fn len(&self) -> usize {
+    use self::Frame::*;
+
+    match *self {
+      Array { ref data, .. } | Push { ref data, .. } => data.len(),
+      BlobString { ref data, .. }
+      | BlobError { ref data, .. }
+      | BigNumber { ref data, .. }
+      | ChunkedString(ref data) => data.len(),
+      SimpleString { ref data, .. } | SimpleError { ref data, .. } => data.len(),
+      Number { .. } | Double { .. } | Boolean { .. } => 1,
+      Null { .. } => 0,
+      VerbatimString { ref data, .. } => data.len(),
+      Map { ref data, .. } => data.len(),
+      Set { ref data, .. } => data.len(),
+      Hello { .. } => 1,
     }
   }
 
-  /// Convert the internal buffer into one frame matching `self.kind`, clearing the internal buffer.
-  pub fn into_frame(&mut self) -> Result<Frame, RedisProtocolError> {
-    if !self.kind.is_streaming_type() {
-      // try to catch invalid type errors early so the caller can modify the frame before we clear the buffer
-      return Err(RedisProtocolError::new(
-        RedisProtocolErrorKind::DecodeError,
-        "Only blob strings, sets, maps, and arrays can be streamed.",
-      ));
+  /// Replace `self` with Null, returning the original value.
+  pub fn take(&mut self) -> Frame {
+    mem::replace(self, Frame::Null { attributes: None })
+  }
+
+  /// Read the inner frames of an aggregate type, where map entries are written as alternating keys and values.
+  fn into_entries(self) -> Result<Vec<Frame>, Box<Frame>> {
+    match self {
+      Frame::Array { data, .. } | Frame::Push { data, .. } => Ok(data),
+      Frame::Set { data, .. } => Ok(data.into_iter().collect()),
+      Frame::Map { data, .. } => Ok(data.into_iter().flat_map(|(key, value)| vec![key, value]).collect()),
+      frame => Err(Box::new(frame)),
     }
+  }
 
-    if self.is_finished() {
-      // the last frame is an empty chunked string when the stream is finished
-      let _ = self.buffer.pop_back();
+  /// Flatten the frame into a list of frames, following the RESP2 representation of RESP3 aggregate types.
+  ///
+  /// Maps are converted to alternating keys and values, and any arrays, sets, pushes, or maps inside an aggregate
+  /// frame are unnested into their inner frames. Only one level of nesting is removed, so deeper aggregate frames are
+  /// returned as they are. Other frames are returned as a single element list. Attributes on aggregate frames are
+  /// discarded, and map entries and set members are read in the iteration order of the map backend.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::types::Frame;
+  /// let array = |data: Vec<Frame>| Frame::Array { data, attributes: None };
+  /// let frame = array(vec![array(vec![1.into(), 2.into()]), 3.into()]);
+  ///
+  /// assert_eq!(frame.flatten(), vec![Frame::from(1), Frame::from(2), Frame::from(3)]);
+  /// ```
+  pub fn flatten(self) -> Vec<Frame> {
+    match self.into_entries() {
+      Ok(entries) => entries
+        .into_iter()
+        .flat_map(|frame| match frame.into_entries() {
+          Ok(inner) => inner,
+          Err(frame) => vec![*frame],
+        })
+        .collect(),
+      Err(frame) => vec![*frame],
     }
-    let buffer = mem::replace(&mut self.buffer, VecDeque::new());
-    let attributes = self.attributes.take();
+  }
 
-    let frame = match self.kind {
-      FrameKind::BlobString => resp3_utils::reconstruct_blobstring(buffer, attributes)?,
-      FrameKind::Map => resp3_utils::reconstruct_map(buffer, attributes)?,
-      FrameKind::Set => resp3_utils::reconstruct_set(buffer, attributes)?,
-      FrameKind::Array => resp3_utils::reconstruct_array(buffer, attributes)?,
-      _ => {
-        return Err(RedisProtocolError::new(
-          RedisProtocolErrorKind::DecodeError,
-          "Streaming frames only supported for blob strings, maps, sets, and arrays.",
-        ))
-      }
+  /// Iterate over the inner frames of an array, set, map, or push frame, where map entries are returned as
+  /// alternating keys and values.
+  ///
+  /// Other frames do not have any inner frames. Frames can also be iterated by value or by reference with `for`
+  /// loops.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::types::Frame;
+  /// let frame = Frame::Array {
+  ///   data: vec![Frame::from(1), Frame::from(2)],
+  ///   attributes: None,
+  /// };
+  /// let sum: i64 = frame.children().filter_map(|f| f.as_i64()).sum();
+  /// assert_eq!(sum, 3);
+  ///
+  /// for inner in frame {
+  ///   assert!(inner.as_i64().is_some());
+  /// }
+  /// ```
+  pub fn children(&self) -> Children<'_> {
+    let inner = match *self {
+      Frame::Array { ref data, .. } | Frame::Push { ref data, .. } => ChildrenInner::Frames(data.iter()),
+      Frame::Set { ref data, .. } => ChildrenInner::Set(data.into_iter()),
+      Frame::Map { ref data, .. } => ChildrenInner::Map(data.into_iter(), None),
+      _ => ChildrenInner::Empty,
     };
 
-    Ok(frame)
+    Children { inner }
   }
 
-  /// Add a frame to the internal buffer.
-  pub fn add_frame(&mut self, data: Frame) {
-    self.buffer.push_back(data);
+  /// Iterate over the entries of a map frame without copying them, or return `None` if the frame is not a map.
+  pub fn map_pairs(&self) -> Option<impl ExactSizeIterator<Item = (&Frame, &Frame)> + '_> {
+    match *self {
+      Frame::Map { ref data, .. } => Some(data.iter()),
+      _ => None,
+    }
   }
 
-  /// Whether or not the last frame represents the terminating sequence at the end of a frame stream.
-  pub fn is_finished(&self) -> bool {
-    self.buffer.back().map(|f| f.is_end_stream_frame()).unwrap_or(false)
+  /// Convert a map frame into an iterator over its entries, or return the original boxed frame if it is not a map.
+  pub fn into_map_pairs(self) -> Result<impl ExactSizeIterator<Item = (Frame, Frame)>, Box<Self>> {
+    match self {
+      Frame::Map { data, .. } => Ok(data.into_iter()),
+      _ => Err(Box::new(self)),
+    }
   }
-}
 
-/// Wrapper enum around a decoded frame that supports streaming frames.
-#[derive(Debug, Eq, PartialEq)]
-pub enum DecodedFrame {
+  /// Read the value in a map frame with the provided key, if the key is a string frame.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::types::{Frame, FrameMap};
+  /// let mut data = FrameMap::new();
+  /// data.insert(
+  ///   Frame::SimpleString {
+  ///     data: "maxmemory".into(),
+  ///     attributes: None,
+  ///   },
+  ///   Frame::from(0),
+  /// );
+  /// let frame = Frame::Map { data, attributes: None };
+  ///
+  /// assert_eq!(frame.get("maxmemory"), Some(&Frame::from(0)));
+  /// assert_eq!(frame.get("timeout"), None);
+  /// ```
+  pub fn get(&self, key: &str) -> Option<&Frame> {
+    self
+      .map_pairs()?
+      .find(|(k, _)| k.as_str() == Some(key))
+      .map(|(_, v)| v)
+  }
+
+  /// Convert the elements of an array, set, or push frame to `T`.
+  ///
+  /// The returned error includes the index of the first element that cannot be converted.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::types::Frame;
+  /// let frame = Frame::Array {
+  ///   data: vec![Frame::from(1), Frame::from(2)],
+  ///   attributes: None,
+  /// };
+  /// assert_eq!(frame.into_vec_of::<i64>().unwrap(), vec![1, 2]);
+  /// ```
+  pub fn into_vec_of<T: FromResp3Frame>(self) -> Result<Vec<T>, RedisProtocolError> {
+    Vec::<T>::from_resp3_frame(self)
+  }
+
+  /// Read the associated `FrameKind`.
+  pub fn kind(&self) -> FrameKind {
+    use self::Frame::*;
+
+    match *self {
+      Array { .. } => FrameKind::Array,
+      BlobString { .. } => FrameKind::BlobString,
+      SimpleString { .. } => FrameKind::SimpleString,
+      SimpleError { .. } => FrameKind::SimpleError,
+      Number { .. } => FrameKind::Number,
+      Null { .. } => FrameKind::Null,
+      Double { .. } => FrameKind::Double,
+      BlobError { .. } => FrameKind::BlobError,
+      VerbatimString { .. } => FrameKind::VerbatimString,
+      Boolean { .. } => FrameKind::Boolean,
+      Map { .. } => FrameKind::Map,
+      Set { .. } => FrameKind::Set,
+      Push { .. } => FrameKind::Push,
+      Hello { .. } => FrameKind::Hello,
+      BigNumber { .. } => FrameKind::BigNumber,
+      ChunkedString(ref inner) => {
+        if inner.is_empty() {
+          FrameKind::EndStream
+        } else {
+          FrameKind::ChunkedString
+        }
+      }
+    }
+  }
+
+  /// Whether or not the frame represents an array of frames.
+  pub fn is_array(&self) -> bool {
+    match *self {
+      Frame::Array { .. } => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the frame represents data pushed to the client from the server.
+  pub fn is_push(&self) -> bool {
+    match *self {
+      Frame::Push { .. } => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the frame is a boolean value.
+  pub fn is_boolean(&self) -> bool {
+    match *self {
+      Frame::Boolean { .. } => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the frame represents an error.
+  pub fn is_error(&self) -> bool {
+    match *self {
+      Frame::BlobError { .. } | Frame::SimpleError { .. } => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the frame is a nil reply, such as the reply to `GET` for a missing key.
+  pub fn is_nil(&self) -> bool {
+    matches!(*self, Frame::Null { .. })
+  }
+
+  /// Whether or not the frame is the `OK` status reply.
+  pub fn is_ok(&self) -> bool {
+    match *self {
+      Frame::SimpleString { ref data, .. } => data == "OK",
+      _ => false,
+    }
+  }
+
+  /// Whether or not the frame is the `QUEUED` status reply sent for commands inside a transaction.
+  pub fn is_queued(&self) -> bool {
+    match *self {
+      Frame::SimpleString { ref data, .. } => data == "QUEUED",
+      _ => false,
+    }
+  }
+
+  /// Whether or not the frame is an array, map, or set.
+  pub fn is_aggregate_type(&self) -> bool {
+    match *self {
+      Frame::Map { .. } | Frame::Set { .. } | Frame::Array { .. } => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the frame represents a `BlobString` or `BlobError`.
+  pub fn is_blob(&self) -> bool {
+    match *self {
+      Frame::BlobString { .. } | Frame::BlobError { .. } => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the frame represents a chunked string.
+  pub fn is_chunked_string(&self) -> bool {
+    match *self {
+      Frame::ChunkedString(_) => true,
+      _ => false,
+    }
+  }
+
+  /// Whether or not the frame is an empty chunked string, signifying the end of a chunked string stream.
+  pub fn is_end_stream_frame(&self) -> bool {
+    match *self {
+      Frame::ChunkedString(ref s) => s.is_empty(),
+      _ => false,
+    }
+  }
+
+  /// Whether or not the frame is a verbatim string.
+  pub fn is_verbatim_string(&self) -> bool {
+    match *self {
+      Frame::VerbatimString { .. } => true,
+      _ => false,
+    }
+  }
+
+  /// If the frame is a verbatim string then read the associated format.
+  pub fn verbatim_string_format(&self) -> Option<&VerbatimStringFormat> {
+    match *self {
+      Frame::VerbatimString { ref format, .. } => Some(format),
+      _ => None,
+    }
+  }
+
+  /// Read the frame as a string slice if it can be parsed as a UTF-8 string without allocating.
+  ///
+  /// Numbers and Doubles will not be cast to a string since that would require allocating.
+  pub fn as_str(&self) -> Option<&str> {
+    match *self {
+      Frame::SimpleError { ref data, .. } | Frame::SimpleString { ref data, .. } => data.as_str(),
+      Frame::BlobError { ref data, .. } | Frame::BlobString { ref data, .. } | Frame::BigNumber { ref data, .. } => {
+        str::from_utf8(data).ok()
+      }
+      Frame::VerbatimString { ref data, .. } => str::from_utf8(data).ok(),
+      Frame::ChunkedString(ref data) => str::from_utf8(data).ok(),
+      _ => None,
+    }
+  }
+
+  /// Read the frame as a `String` if it can be parsed as a UTF-8 string.
+  pub fn to_string(&self) -> Option<String> {
+    match *self {
+      Frame::SimpleError { ref data, .. } | Frame::SimpleString { ref data, .. } => data.as_str().map(str::to_owned),
+      Frame::BlobError { ref data, .. } | Frame::BlobString { ref data, .. } | Frame::BigNumber { ref data, .. } => {
+        String::from_utf8(data.to_vec()).ok()
+      }
+      Frame::VerbatimString { ref data, .. } => String::from_utf8(data.to_vec()).ok(),
+      Frame::ChunkedString(ref b) => String::from_utf8(b.to_vec()).ok(),
+      Frame::Double { ref data, .. } => Some(resp3_utils::f64_to_redis_string(data).into_owned()),
+      Frame::Number { ref data, .. } => Some(data.to_string()),
+      _ => None,
+    }
+  }
+
+  /// Read a scalar frame as a string using the same coercions as Redis, borrowing the frame's data when possible.
+  ///
+  /// Numbers and doubles are written as their decimal text, as in `INCRBYFLOAT` replies, and booleans are written as
+  /// `1` or `0`, as when Redis converts them to RESP2 integers. Aggregate types, nulls, and payloads that are not
+  /// valid UTF-8 return `None`.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::types::Frame;
+  /// assert_eq!(Frame::from(42).coerce_str().unwrap(), "42");
+  /// assert_eq!(Frame::from(true).coerce_str().unwrap(), "1");
+  /// assert_eq!(Frame::Null { attributes: None }.coerce_str(), None);
+  /// ```
+  pub fn coerce_str(&self) -> Option<Cow<'_, str>> {
+    match *self {
+      Frame::Number { ref data, .. } => Some(Cow::Owned(data.to_string())),
+      Frame::Double { ref data, .. } => Some(resp3_utils::f64_to_redis_string(data)),
+      Frame::Boolean { ref data, .. } => Some(Cow::Borrowed(if *data { "1" } else { "0" })),
+      _ => self.as_str().map(Cow::Borrowed),
+    }
+  }
+
+  /// Read a scalar frame as a string with the same coercions as [coerce_str](Self::coerce_str), replacing any invalid
+  /// UTF-8 sequences with `U+FFFD REPLACEMENT CHARACTER`.
+  ///
+  /// Aggregate types and nulls return `None`.
+  pub fn to_string_lossy(&self) -> Option<String> {
+    match *self {
+      Frame::BlobError { ref data, .. }
+      | Frame::BlobString { ref data, .. }
+      | Frame::BigNumber { ref data, .. }
+      | Frame::VerbatimString { ref data, .. }
+      | Frame::ChunkedString(ref data) => Some(String::from_utf8_lossy(data).into_owned()),
+      _ => self.coerce_str().map(|s| s.into_owned()),
+    }
+  }
+
+  /// Attempt to read the frame as a byte slice.
+  ///
+  /// Number and Double will not be returned as a byte slice. Use [number_or_double_as_bytes](Self::number_or_double_as_bytes) instead.
+  pub fn as_bytes(&self) -> Option<&[u8]> {
+    match *self {
+      Frame::SimpleError { ref data, .. } | Frame::SimpleString { ref data, .. } => Some(data.as_bytes()),
+      Frame::BlobError { ref data, .. } | Frame::BlobString { ref data, .. } | Frame::BigNumber { ref data, .. } => {
+        Some(data)
+      }
+      Frame::VerbatimString { ref data, .. } => Some(data),
+      Frame::ChunkedString(ref b) => Some(b),
+      _ => None,
+    }
+  }
+
+  /// Attempt the read the frame as bytes if the inner type is an `i64` or `f64`.
+  pub fn number_or_double_as_bytes(&self, ordering: ByteOrder) -> Option<[u8; 8]> {
+    match *self {
+      Frame::Double { ref data, .. } => Some(match ordering {
+        ByteOrder::BigEndian => data.to_be_bytes(),
+        ByteOrder::LittleEndian => data.to_le_bytes(),
+      }),
+      Frame::Number { ref data, .. } => Some(match ordering {
+        ByteOrder::BigEndian => data.to_be_bytes(),
+        ByteOrder::LittleEndian => data.to_le_bytes(),
+      }),
+      _ => None,
+    }
+  }
+
+  /// Attempt to read the frame as an `i64`.
+  pub fn as_i64(&self) -> Option<i64> {
+    match *self {
+      Frame::Number { ref data, .. } => Some(*data),
+      Frame::Double { ref data, .. } => Some(*data as i64),
+      Frame::BlobString { ref data, .. } => str::from_utf8(data).ok().and_then(|s| s.parse::<i64>().ok()),
+      Frame::SimpleString { ref data, .. } => data.as_str().and_then(|s| s.parse::<i64>().ok()),
+      _ => None,
+    }
+  }
+
+  /// Attempt to read the frame as an `f64`.
+  pub fn as_f64(&self) -> Option<f64> {
+    match *self {
+      Frame::Double { ref data, .. } => Some(*data),
+      Frame::Number { ref data, .. } => Some(*data as f64),
+      Frame::BlobString { ref data, .. } => str::from_utf8(data).ok().and_then(|s| s.parse::<f64>().ok()),
+      Frame::SimpleString { ref data, .. } => data.as_str().and_then(|s| s.parse::<f64>().ok()),
+      _ => None,
+    }
+  }
+
+  /// Encode the frame and render the bytes as an escaped string, such as `*1\r\n$3\r\nfoo\r\n`.
+  ///
+  /// See [escape_wire_string](crate::escape_wire_string) for the escaping rules.
+  pub fn to_wire_string(&self) -> Result<String, RedisProtocolError> {
+    let mut buf = BytesMut::new();
+    resp3_encode::complete::encode_bytes(&mut buf, self)?;
+    Ok(utils::escape_wire_string(&buf))
+  }
+
+  /// Parse a frame from the escaped notation produced by [to_wire_string](Self::to_wire_string).
+  ///
+  /// An error is returned if the string does not contain exactly one complete frame.
+  pub fn from_wire_string(s: &str) -> Result<Self, RedisProtocolError> {
+    let data = utils::unescape_wire_string(s)?;
+
+    match resp3_decode::complete::decode(&data)? {
+      Some((frame, len)) if len == data.len() => Ok(frame),
+      Some(_) => Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::DecodeError,
+        "Unexpected bytes after frame.",
+      )),
+      None => Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::DecodeError,
+        "Incomplete frame.",
+      )),
+    }
+  }
+
+  /// Wrap the frame in a `Debug` implementation that truncates any payloads longer than `max_len` bytes.
+  ///
+  /// This can be used to log frames that may contain large values.
+  pub fn truncated(&self, max_len: usize) -> TruncatedFrame<'_> {
+    TruncatedFrame { frame: self, max_len }
+  }
+
+  /// Compare the frame with `other`, returning a report of every difference found by walking both frames together.
+  ///
+  /// Unlike `==`, attributes are compared as well. This is mostly useful for explaining test failures involving large
+  /// nested frames.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::types::Frame;
+  /// let left = Frame::Array {
+  ///   data: vec![Frame::from(1), Frame::from(2)],
+  ///   attributes: None,
+  /// };
+  /// let right = Frame::Array {
+  ///   data: vec![Frame::from(1), Frame::from(3)],
+  ///   attributes: None,
+  /// };
+  ///
+  /// assert!(left.diff(&left).is_empty());
+  /// assert_eq!(format!("{}", left.diff(&right)), "[1]: Number(2) != Number(3)");
+  /// ```
+  pub fn diff(&self, other: &Frame) -> FrameDiff {
+    let mut diff = FrameDiff::default();
+    diff.compare(String::new(), self, other);
+    diff
+  }
+
+  /// Whether or not the frame represents a MOVED or ASK error.
+  pub fn is_moved_or_ask_error(&self) -> bool {
+    match *self {
+      Frame::SimpleError { ref data, .. } => data.as_str().map(utils::is_cluster_error).unwrap_or(false),
+      _ => false,
+    }
+  }
+
+  /// Attempt to parse the frame as a cluster redirection error.
+  pub fn to_redirection(&self) -> Option<Redirection> {
+    Redirection::from_frame(self)
+  }
+
+  /// Attempt to read the kind of error returned by the server.
+  pub fn to_error_kind(&self) -> Option<RedisErrorKind> {
+    RedisErrorKind::from_frame(self)
+  }
+
+  /// Convert a simple or blob error frame into the `Err` arm, or return any other frame in the `Ok` arm.
+  ///
+  /// Blob errors that are not valid UTF-8 are converted lossily.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::types::Frame;
+  /// # use redis_protocol::types::RedisErrorKind;
+  /// let frame = Frame::SimpleError {
+  ///   data: "WRONGTYPE Operation against a key".into(),
+  ///   attributes: None,
+  /// };
+  /// let error = frame.into_result().unwrap_err();
+  /// assert_eq!(error.kind, RedisErrorKind::WrongType);
+  /// assert_eq!(error.message, "Operation against a key");
+  /// ```
+  pub fn into_result(self) -> Result<Frame, RedisErrorFrame> {
+    match self {
+      Frame::SimpleError { data, attributes } => Err(RedisErrorFrame::new(&data.to_str_lossy(), attributes)),
+      Frame::BlobError { data, attributes } => Err(RedisErrorFrame::new(&String::from_utf8_lossy(&data), attributes)),
+      _ => Ok(self),
+    }
+  }
+
+  /// Whether or not the frame represents a publish-subscribe message, but not a pattern publish-subscribe message.
+  pub fn is_normal_pubsub(&self) -> bool {
+    if let Frame::Push { ref data, .. } = *self {
+      resp3_utils::is_normal_pubsub(data)
+    } else {
+      false
+    }
+  }
+
+  /// Whether or not the frame represents a message on a publish-subscribe channel.
+  pub fn is_pubsub_message(&self) -> bool {
+    if let Frame::Push { ref data, .. } = *self {
+      resp3_utils::is_normal_pubsub(data) || resp3_utils::is_pattern_pubsub(data)
+    } else {
+      false
+    }
+  }
+
+  /// Whether or not the frame represents a message on a publish-subscribe channel matched against a pattern subscription.
+  pub fn is_pattern_pubsub_message(&self) -> bool {
+    if let Frame::Push { ref data, .. } = *self {
+      resp3_utils::is_pattern_pubsub(data)
+    } else {
+      false
+    }
+  }
+
+  /// Attempt to parse the frame as a publish-subscribe message, returning the `(channel, message)` tuple
+  /// if successful, or the original frame if the inner data is not a publish-subscribe message.
+  pub fn parse_as_pubsub(self) -> Result<(Frame, Frame), Self> {
+    if self.is_pubsub_message() {
+      if let Frame::Push { mut data, .. } = self {
+        // array len checked in `is_pubsub_message`
+        let message = data.pop().unwrap();
+        let channel = data.pop().unwrap();
+
+        Ok((channel, message))
+      } else {
+        _warn!("Invalid pubsub frame. Expected a Push frame.");
+        Err(self)
+      }
+    } else {
+      Err(self)
+    }
+  }
+
+  /// Whether or not the frame represents a client-side caching invalidation message.
+  pub fn is_invalidation(&self) -> bool {
+    if let Frame::Push { ref data, .. } = *self {
+      tracking::is_resp3_invalidation(data)
+    } else {
+      false
+    }
+  }
+
+  /// Attempt to parse the frame as a client-side caching invalidation message.
+  pub fn to_invalidation(&self) -> Option<Invalidation> {
+    if let Frame::Push { ref data, .. } = *self {
+      tracking::parse_resp3_invalidation(data)
+    } else {
+      None
+    }
+  }
+
+  /// Attempt to read the number of bytes needed to encode the frame.
+  pub fn encode_len(&self) -> Result<usize, RedisProtocolError> {
+    resp3_utils::encode_len(self).map_err(|e| e.into())
+  }
+
+  /// Compute a stable digest of the frame's contents, for use in reply deduplication or as a cache key.
+  ///
+  /// The digest is the [crc64_jones](crate::crc64_jones) checksum of the canonical encoding of the frame, so it does
+  /// not depend on the map backend or the iteration order of maps and sets, and it is the same across processes and
+  /// platforms. Attributes on the frame and any inner frames are only included if `include_attributes` is true.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::types::Frame;
+  /// let frame = Frame::from(1);
+  /// assert_eq!(frame.content_hash(false).unwrap(), redis_protocol::crc64_jones(b":1\r\n"));
+  /// ```
+  pub fn content_hash(&self, include_attributes: bool) -> Result<u64, RedisProtocolError> {
+    let frame = if include_attributes {
+      Cow::Borrowed(self)
+    } else {
+      let mut frame = self.clone();
+      frame.strip_attributes();
+      Cow::Owned(frame)
+    };
+    let config = EncodeConfig {
+      canonical: true,
+      ..Default::default()
+    };
+
+    let mut buf = BytesMut::new();
+    resp3_encode::complete::encode_bytes_with_config(&mut buf, &frame, &config)?;
+    Ok(utils::crc64_jones(&buf))
+  }
+
+  /// Read the argument at `idx` as a string if the frame is a command sent by a client.
+  fn command_arg(&self, idx: usize) -> Option<&str> {
+    match *self {
+      Frame::Array { ref data, .. } => match data.get(idx) {
+        Some(Frame::BlobString { data, .. }) => str::from_utf8(data).ok(),
+        Some(Frame::SimpleString { data, .. }) => data.as_str(),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+
+  /// Read the uppercase command name if the frame is a command sent by a client, such as `GET` or `CONFIG`.
+  pub fn command_name(&self) -> Option<String> {
+    self.command_arg(0).map(|name| name.to_ascii_uppercase())
+  }
+
+  /// Read the uppercase subcommand name if the frame is a command sent by a client, such as `GET` in `CONFIG GET`.
+  ///
+  /// Returns `None` if the command does not have subcommands.
+  pub fn subcommand_name(&self) -> Option<String> {
+    match self.command_arg(0) {
+      Some(name) if request::is_container_command(name) => self.command_arg(1).map(|s| s.to_ascii_uppercase()),
+      _ => None,
+    }
+  }
+}
+
+/// The default maximum number of bytes in a streamed frame, matching the default `proto-max-bulk-len` on the server.
+pub const STREAM_MAX_BYTES: usize = 512 * 1024 * 1024;
+/// The default maximum number of frames in a streamed frame, matching the maximum number of elements in an aggregate
+/// type used by Redis 7 and later.
+pub const STREAM_MAX_FRAMES: usize = i32::MAX as usize;
+
+/// A helper struct for reading and managing streaming data types.
+///
+/// Streams are limited to [STREAM_MAX_BYTES] bytes and [STREAM_MAX_FRAMES] frames by default, and
+/// [add_frame](Self::add_frame) returns an error once either limit is exceeded. See [with_limit](Self::with_limit) to
+/// change the limits.
+///
+/// ```rust edition2018
+/// use redis_protocol::resp3::decode::streaming::decode;
+///
+/// fn main() {
+///   let parts = vec!["*?\r\n", ":1\r\n", ":2\r\n:3\r\n", ".\r\n"];
+///
+///   let (frame, _) = decode(parts[0].as_bytes()).unwrap().unwrap();
+///   assert!(frame.is_streaming());
+///   let mut streaming = frame.into_streaming_frame().unwrap();
+///   println!("Reading streaming {:?}", streaming.kind);
+///
+///   let (frame, _) = decode(parts[1].as_bytes()).unwrap().unwrap();
+///   assert!(frame.is_complete());
+///   // add frames to the buffer until we reach the terminating byte sequence
+///   streaming.add_frame(frame.into_complete_frame().unwrap()).unwrap();
+///
+///   let (frame, _) = decode(parts[2].as_bytes()).unwrap().unwrap();
+///   assert!(frame.is_complete());
+///   streaming.add_frame(frame.into_complete_frame().unwrap()).unwrap();
+///
+///   let (frame, _) = decode(parts[3].as_bytes()).unwrap().unwrap();
+///   assert!(frame.is_complete());
+///   streaming.add_frame(frame.into_complete_frame().unwrap()).unwrap();
+///
+///   assert!(streaming.is_finished());
+///   // convert the buffer into one frame
+///   let result = streaming.into_frame().unwrap();
+///
+///   // Frame::Array { data: [1, 2, 3], attributes: None }
+///   println!("{:?}", result);
+/// }
+/// ```
+#[derive(Debug, Eq, PartialEq)]
+pub struct StreamedFrame {
+  /// The internal buffer of frames and attributes.
+  buffer: VecDeque<Frame>,
+  /// Any leading attributes before the stream starts.
+  pub attributes: Option<Attributes>,
+  /// The data type being streamed.  
+  pub kind: FrameKind,
+  /// The encoded length of the frames in the internal buffer.
+  buffered_bytes: usize,
+  max_bytes: usize,
+  max_frames: usize,
+}
+
+impl StreamedFrame {
+  /// Create a new `StreamedFrame` from the first section of data in a streaming response.
+  pub fn new(kind: FrameKind) -> Self {
+    Self::with_limit(kind, STREAM_MAX_BYTES, STREAM_MAX_FRAMES)
+  }
+
+  /// Create a new `StreamedFrame` that allows at most `max_bytes` encoded bytes and `max_frames` frames, not including
+  /// the frame that ends the stream.
+  ///
+  /// ```
+  /// # use redis_protocol::resp3::types::{Frame, FrameKind, StreamedFrame};
+  /// # use redis_protocol::types::RedisProtocolErrorKind;
+  /// let mut streaming = StreamedFrame::with_limit(FrameKind::Array, 1024, 2);
+  /// streaming.add_frame(1.into()).unwrap();
+  /// streaming.add_frame(2.into()).unwrap();
+  ///
+  /// let error = streaming.add_frame(3.into()).unwrap_err();
+  /// assert_eq!(error.kind(), &RedisProtocolErrorKind::StreamTooLarge(2));
+  /// streaming.add_frame(Frame::new_end_stream()).unwrap();
+  /// ```
+  pub fn with_limit(kind: FrameKind, max_bytes: usize, max_frames: usize) -> Self {
+    StreamedFrame {
+      buffer: VecDeque::new(),
+      kind,
+      attributes: None,
+      buffered_bytes: 0,
+      max_bytes,
+      max_frames,
+    }
+  }
+
+  /// The maximum number of encoded bytes in the stream.
+  pub fn max_bytes(&self) -> usize {
+    self.max_bytes
+  }
+
+  /// The maximum number of frames in the stream.
+  pub fn max_frames(&self) -> usize {
+    self.max_frames
+  }
+
+  /// The encoded length of the frames added to the stream.
+  pub fn buffered_bytes(&self) -> usize {
+    self.buffered_bytes
+  }
+
+  /// Convert the internal buffer into one frame matching `self.kind`, clearing the internal buffer.
+  pub fn into_frame(&mut self) -> Result<Frame, RedisProtocolError> {
+    self.into_frame_with_config(&DecodeConfig::default())
+  }
+
+  /// Convert the internal buffer into one frame matching `self.kind` using the provided decoding options, clearing the
+  /// internal buffer.
+  pub fn into_frame_with_config(&mut self, config: &DecodeConfig) -> Result<Frame, RedisProtocolError> {
+    if !self.kind.is_streaming_type() {
+      // try to catch invalid type errors early so the caller can modify the frame before we clear the buffer
+      return Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::DecodeError,
+        "Only blob strings, sets, maps, and arrays can be streamed.",
+      ));
+    }
+
+    if self.is_finished() {
+      // the last frame is an empty chunked string when the stream is finished
+      let _ = self.buffer.pop_back();
+    }
+    let buffer = mem::replace(&mut self.buffer, VecDeque::new());
+    let attributes = self.attributes.take();
+    self.buffered_bytes = 0;
+
+    let frame = match self.kind {
+      FrameKind::BlobString => resp3_utils::reconstruct_blobstring(buffer, attributes)?,
+      FrameKind::Map => resp3_utils::reconstruct_map(buffer, attributes, config.duplicate_keys)?,
+      FrameKind::Set => resp3_utils::reconstruct_set(buffer, attributes)?,
+      FrameKind::Array => resp3_utils::reconstruct_array(buffer, attributes)?,
+      _ => {
+        return Err(RedisProtocolError::new(
+          RedisProtocolErrorKind::DecodeError,
+          "Streaming frames only supported for blob strings, maps, sets, and arrays.",
+        ))
+      }
+    };
+
+    Ok(frame)
+  }
+
+  /// Add a frame to the internal buffer.
+  ///
+  /// A `StreamTooLarge` error is returned without adding the frame if the stream would exceed its limits. The
+  /// connection should be closed after an error since the rest of the stream cannot be skipped reliably.
+  pub fn add_frame(&mut self, data: Frame) -> Result<(), RedisProtocolError> {
+    if data.is_end_stream_frame() {
+      self.buffer.push_back(data);
+      return Ok(());
+    }
+
+    if self.buffer.len() >= self.max_frames {
+      return Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::StreamTooLarge(self.max_frames),
+        format!("Streamed frame has more than {} frames.", self.max_frames),
+      ));
+    }
+    let buffered_bytes = self.buffered_bytes.saturating_add(data.encode_len()?);
+    if buffered_bytes > self.max_bytes {
+      return Err(RedisProtocolError::new(
+        RedisProtocolErrorKind::StreamTooLarge(self.max_bytes),
+        format!("Streamed frame is larger than {} bytes.", self.max_bytes),
+      ));
+    }
+
+    self.buffered_bytes = buffered_bytes;
+    self.buffer.push_back(data);
+    Ok(())
+  }
+
+  /// Whether or not the last frame represents the terminating sequence at the end of a frame stream.
+  pub fn is_finished(&self) -> bool {
+    self.buffer.back().map(|f| f.is_end_stream_frame()).unwrap_or(false)
+  }
+}
+
+/// A wrapper that implements `Debug` for a frame, truncating any payloads longer than `max_len` bytes.
+///
+/// Truncated payloads are followed by their full length, and inner frames and attributes are truncated recursively.
+/// The alternate `{:#?}` form is supported.
+///
+/// ```rust
+/// # use redis_protocol::resp3::types::Frame;
+/// let frame = Frame::BlobString {
+///   data: vec![b'a'; 1024],
+///   attributes: None,
+/// };
+///
+/// assert_eq!(
+///   format!("{:?}", frame.truncated(3)),
+///   "BlobString { data: b\"aaa\"... (1024 bytes), attributes: None }"
+/// );
+/// ```
+#[derive(Clone, Copy)]
+pub struct TruncatedFrame<'a> {
+  frame: &'a Frame,
+  max_len: usize,
+}
+
+struct TruncatedMap<'a> {
+  data: &'a FrameMap,
+  max_len: usize,
+}
+
+struct TruncatedSet<'a> {
+  data: &'a FrameSet,
+  max_len: usize,
+}
+
+impl<'a> TruncatedFrame<'a> {
+  fn wrap(&self, frame: &'a Frame) -> TruncatedFrame<'a> {
+    TruncatedFrame {
+      frame,
+      max_len: self.max_len,
+    }
+  }
+
+  fn bytes(&self, data: &'a [u8]) -> utils::TruncatedBytes<'a> {
+    utils::TruncatedBytes {
+      data,
+      max_len: self.max_len,
+    }
+  }
+
+  fn str(&self, data: &'a str) -> utils::TruncatedStr<'a> {
+    utils::TruncatedStr {
+      data,
+      max_len: self.max_len,
+    }
+  }
+
+  fn lazy_str(&self, data: &'a LazyStr) -> Box<dyn fmt::Debug + 'a> {
+    match data.as_str() {
+      Some(s) => Box::new(self.str(s)),
+      None => Box::new(self.bytes(data.as_bytes())),
+    }
+  }
+
+  fn map(&self, data: &'a FrameMap) -> TruncatedMap<'a> {
+    TruncatedMap {
+      data,
+      max_len: self.max_len,
+    }
+  }
+}
+
+impl<'a> fmt::Debug for TruncatedMap<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let wrap = |frame| TruncatedFrame {
+      frame,
+      max_len: self.max_len,
+    };
+    f.debug_map().entries(self.data.iter().map(|(k, v)| (wrap(k), wrap(v)))).finish()
+  }
+}
+
+impl<'a> fmt::Debug for TruncatedSet<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let wrap = |frame| TruncatedFrame {
+      frame,
+      max_len: self.max_len,
+    };
+    f.debug_set().entries(self.data.iter().map(wrap)).finish()
+  }
+}
+
+impl<'a> fmt::Debug for TruncatedFrame<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let attributes = self.frame.attributes().map(|a| self.map(a));
+    let debug_data = |f: &mut fmt::Formatter<'_>, name: &str, data: &dyn fmt::Debug| {
+      f.debug_struct(name)
+        .field("data", data)
+        .field("attributes", &attributes)
+        .finish()
+    };
+
+    match *self.frame {
+      Frame::BlobString { ref data, .. } => debug_data(f, "BlobString", &self.bytes(data)),
+      Frame::BlobError { ref data, .. } => debug_data(f, "BlobError", &self.bytes(data)),
+      Frame::BigNumber { ref data, .. } => debug_data(f, "BigNumber", &self.bytes(data)),
+      Frame::SimpleString { ref data, .. } => debug_data(f, "SimpleString", &self.lazy_str(data)),
+      Frame::SimpleError { ref data, .. } => debug_data(f, "SimpleError", &self.lazy_str(data)),
+      Frame::Number { ref data, .. } => debug_data(f, "Number", data),
+      Frame::Double { ref data, .. } => debug_data(f, "Double", data),
+      Frame::Boolean { ref data, .. } => debug_data(f, "Boolean", data),
+      Frame::Array { ref data, .. } => {
+        let data: Vec<_> = data.iter().map(|frame| self.wrap(frame)).collect();
+        debug_data(f, "Array", &data)
+      }
+      Frame::Push { ref data, .. } => {
+        let data: Vec<_> = data.iter().map(|frame| self.wrap(frame)).collect();
+        debug_data(f, "Push", &data)
+      }
+      Frame::Map { ref data, .. } => debug_data(f, "Map", &self.map(data)),
+      Frame::Set { ref data, .. } => debug_data(f, "Set", &TruncatedSet {
+        data,
+        max_len: self.max_len,
+      }),
+      Frame::VerbatimString {
+        ref data, ref format, ..
+      } => f
+        .debug_struct("VerbatimString")
+        .field("data", &self.bytes(data))
+        .field("format", format)
+        .field("attributes", &attributes)
+        .finish(),
+      Frame::Null { .. } => f.debug_struct("Null").field("attributes", &attributes).finish(),
+      Frame::ChunkedString(ref data) => f.debug_tuple("ChunkedString").field(&self.bytes(data)).finish(),
+      Frame::Hello { .. } => fmt::Debug::fmt(self.frame, f),
+    }
+  }
+}
+
+/// The text used in a [FrameDifference] for a value that only exists in one of the frames.
+const MISSING: &str = "<missing>";
+/// The maximum number of payload bytes shown in a [FrameDifference].
+const DIFF_MAX_LEN: usize = 64;
+
+/// One difference between two frames, found by [Frame::diff].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FrameDifference {
+  /// The location of the difference, such as `[2]."config"@attributes."ttl"`.
+  ///
+  /// Array and push elements are written as `[index]`, map values as `.key`, and attributes as `@attributes`. The
+  /// path is empty for a difference at the top level.
+  pub path: String,
+  /// A short description of the value in the left frame.
+  pub left: String,
+  /// A short description of the value in the right frame.
+  pub right: String,
+}
+
+impl fmt::Display for FrameDifference {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let path = if self.path.is_empty() { "." } else { &self.path };
+    write!(f, "{}: {} != {}", path, self.left, self.right)
+  }
+}
+
+/// A report of the differences between two frames, created by [Frame::diff].
+///
+/// The `Display` implementation writes one difference per line.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FrameDiff {
+  differences: Vec<FrameDifference>,
+}
+
+impl FrameDiff {
+  /// Whether or not the frames are equal.
+  pub fn is_empty(&self) -> bool {
+    self.differences.is_empty()
+  }
+
+  /// The number of differences.
+  pub fn len(&self) -> usize {
+    self.differences.len()
+  }
+
+  /// Read the differences, in the order they were found.
+  pub fn differences(&self) -> &[FrameDifference] {
+    &self.differences
+  }
+
+  /// Describe a frame without its attributes, truncating payloads and summarizing aggregate types by their length.
+  fn describe(frame: &Frame) -> String {
+    let bytes = |data| utils::TruncatedBytes {
+      data,
+      max_len: DIFF_MAX_LEN,
+    };
+    let lazy_str = |name: &str, data: &LazyStr| match data.as_str() {
+      Some(data) => format!("{}({:?})", name, utils::TruncatedStr {
+        data,
+        max_len: DIFF_MAX_LEN,
+      }),
+      None => format!("{}({:?})", name, utils::TruncatedBytes {
+        data: data.as_bytes(),
+        max_len: DIFF_MAX_LEN,
+      }),
+    };
+
+    match *frame {
+      Frame::BlobString { ref data, .. } => format!("BlobString({:?})", bytes(data)),
+      Frame::BlobError { ref data, .. } => format!("BlobError({:?})", bytes(data)),
+      Frame::BigNumber { ref data, .. } => format!("BigNumber({:?})", bytes(data)),
+      Frame::SimpleString { ref data, .. } => lazy_str("SimpleString", data),
+      Frame::SimpleError { ref data, .. } => lazy_str("SimpleError", data),
+      Frame::Number { ref data, .. } => format!("Number({})", data),
+      Frame::Double { ref data, .. } => format!("Double({:?})", data),
+      Frame::Boolean { ref data, .. } => format!("Boolean({})", data),
+      Frame::Null { .. } => "Null".into(),
+      Frame::VerbatimString {
+        ref data, ref format, ..
+      } => format!("VerbatimString({:?}, {:?})", format, bytes(data)),
+      Frame::Array { ref data, .. } => format!("Array(len {})", data.len()),
+      Frame::Push { ref data, .. } => format!("Push(len {})", data.len()),
+      Frame::Map { ref data, .. } => format!("Map(len {})", data.len()),
+      Frame::Set { ref data, .. } => format!("Set(len {})", data.len()),
+      Frame::ChunkedString(ref data) => format!("ChunkedString({:?})", bytes(data)),
+      Frame::Hello { .. } => format!("{:?}", frame),
+    }
+  }
+
+  /// Describe a map key as a path segment, quoting string keys.
+  fn key_segment(key: &Frame) -> String {
+    match *key {
+      Frame::BlobString { .. } | Frame::SimpleString { .. } => match key.as_str() {
+        Some(s) => format!(".{:?}", s),
+        None => format!(".{}", Self::describe(key)),
+      },
+      _ => format!(".{}", Self::describe(key)),
+    }
+  }
+
+  fn push(&mut self, path: String, left: String, right: String) {
+    self.differences.push(FrameDifference { path, left, right });
+  }
+
+  fn compare(&mut self, path: String, left: &Frame, right: &Frame) {
+    match (left, right) {
+      (Frame::Array { data: left_data, .. }, Frame::Array { data: right_data, .. })
+      | (Frame::Push { data: left_data, .. }, Frame::Push { data: right_data, .. }) => {
+        for idx in 0..left_data.len().max(right_data.len()) {
+          let path = format!("{}[{}]", path, idx);
+          match (left_data.get(idx), right_data.get(idx)) {
+            (Some(left), Some(right)) => self.compare(path, left, right),
+            (Some(left), None) => self.push(path, Self::describe(left), MISSING.into()),
+            (None, Some(right)) => self.push(path, MISSING.into(), Self::describe(right)),
+            (None, None) => {}
+          }
+        }
+      }
+      (Frame::Map { data: left_data, .. }, Frame::Map { data: right_data, .. }) => {
+        self.compare_maps(&path, left_data, right_data);
+      }
+      (Frame::Set { data: left_data, .. }, Frame::Set { data: right_data, .. }) => {
+        for member in left_data.sorted().into_iter().filter(|m| !right_data.contains(m)) {
+          self.push(path.clone(), Self::describe(member), MISSING.into());
+        }
+        for member in right_data.sorted().into_iter().filter(|m| !left_data.contains(m)) {
+          self.push(path.clone(), MISSING.into(), Self::describe(member));
+        }
+      }
+      _ => {
+        if !left.eq_ignoring_attributes(right) {
+          self.push(path.clone(), Self::describe(left), Self::describe(right));
+        }
+      }
+    }
+
+    let attributes_path = format!("{}@attributes", path);
+    match (left.attributes(), right.attributes()) {
+      (Some(left), Some(right)) => self.compare_maps(&attributes_path, left, right),
+      (Some(left), None) => self.push(attributes_path, format!("Map(len {})", left.len()), MISSING.into()),
+      (None, Some(right)) => self.push(attributes_path, MISSING.into(), format!("Map(len {})", right.len())),
+      (None, None) => {}
+    }
+  }
+
+  fn compare_maps(&mut self, path: &str, left: &FrameMap, right: &FrameMap) {
+    for (key, value) in left.sorted() {
+      let path = format!("{}{}", path, Self::key_segment(key));
+      match right.get(key) {
+        Some(other) => self.compare(path, value, other),
+        None => self.push(path, Self::describe(value), MISSING.into()),
+      }
+    }
+    for (key, value) in right.sorted().into_iter().filter(|(k, _)| !left.contains_key(k)) {
+      self.push(
+        format!("{}{}", path, Self::key_segment(key)),
+        MISSING.into(),
+        Self::describe(value),
+      );
+    }
+  }
+}
+
+impl fmt::Display for FrameDiff {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (idx, difference) in self.differences.iter().enumerate() {
+      if idx > 0 {
+        f.write_str("\n")?;
+      }
+      write!(f, "{}", difference)?;
+    }
+
+    Ok(())
+  }
+}
+
+enum ChildrenInner<'a> {
+  Frames(std::slice::Iter<'a, Frame>),
+  Set(<&'a FrameSet as IntoIterator>::IntoIter),
+  Map(<&'a FrameMap as IntoIterator>::IntoIter, Option<&'a Frame>),
+  Empty,
+}
+
+/// An iterator over references to the inner frames of an aggregate frame, created by [Frame::children].
+///
+/// Map entries are returned as alternating keys and values, and frames that are not an array, set, map, or push frame
+/// do not have any inner frames.
+pub struct Children<'a> {
+  inner: ChildrenInner<'a>,
+}
+
+impl<'a> Iterator for Children<'a> {
+  type Item = &'a Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    match self.inner {
+      ChildrenInner::Frames(ref mut iter) => iter.next(),
+      ChildrenInner::Set(ref mut iter) => iter.next(),
+      ChildrenInner::Map(ref mut iter, ref mut value) => match value.take() {
+        Some(value) => Some(value),
+        None => iter.next().map(|(key, next)| {
+          *value = Some(next);
+          key
+        }),
+      },
+      ChildrenInner::Empty => None,
+    }
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let len = match self.inner {
+      ChildrenInner::Frames(ref iter) => iter.len(),
+      ChildrenInner::Set(ref iter) => iter.len(),
+      ChildrenInner::Map(ref iter, ref value) => iter.len() * 2 + value.is_some() as usize,
+      ChildrenInner::Empty => 0,
+    };
+
+    (len, Some(len))
+  }
+}
+
+impl<'a> ExactSizeIterator for Children<'a> {}
+
+enum IntoChildrenInner {
+  Frames(std::vec::IntoIter<Frame>),
+  Set(<FrameSet as IntoIterator>::IntoIter),
+  Map(<FrameMap as IntoIterator>::IntoIter, Option<Frame>),
+  Empty,
+}
+
+/// An iterator that moves the inner frames out of an aggregate frame, created by the `IntoIterator` implementation
+/// for [Frame].
+///
+/// See [Children] for more information.
+pub struct IntoChildren {
+  inner: IntoChildrenInner,
+}
+
+impl Iterator for IntoChildren {
+  type Item = Frame;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    match self.inner {
+      IntoChildrenInner::Frames(ref mut iter) => iter.next(),
+      IntoChildrenInner::Set(ref mut iter) => iter.next(),
+      IntoChildrenInner::Map(ref mut iter, ref mut value) => match value.take() {
+        Some(value) => Some(value),
+        None => iter.next().map(|(key, next)| {
+          *value = Some(next);
+          key
+        }),
+      },
+      IntoChildrenInner::Empty => None,
+    }
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let len = match self.inner {
+      IntoChildrenInner::Frames(ref iter) => iter.len(),
+      IntoChildrenInner::Set(ref iter) => iter.len(),
+      IntoChildrenInner::Map(ref iter, ref value) => iter.len() * 2 + value.is_some() as usize,
+      IntoChildrenInner::Empty => 0,
+    };
+
+    (len, Some(len))
+  }
+}
+
+impl ExactSizeIterator for IntoChildren {}
+
+impl IntoIterator for Frame {
+  type Item = Frame;
+  type IntoIter = IntoChildren;
+
+  fn into_iter(self) -> Self::IntoIter {
+    let inner = match self {
+      Frame::Array { data, .. } | Frame::Push { data, .. } => IntoChildrenInner::Frames(data.into_iter()),
+      Frame::Set { data, .. } => IntoChildrenInner::Set(data.into_iter()),
+      Frame::Map { data, .. } => IntoChildrenInner::Map(data.into_iter(), None),
+      _ => IntoChildrenInner::Empty,
+    };
+
+    IntoChildren { inner }
+  }
+}
+
+impl<'a> IntoIterator for &'a Frame {
+  type Item = &'a Frame;
+  type IntoIter = Children<'a>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.children()
+  }
+}
+
+/// Wrapper enum around a decoded frame that supports streaming frames.
+#[derive(Debug, Eq, PartialEq)]
+pub enum DecodedFrame {
   Streaming(StreamedFrame),
   Complete(Frame),
 }
@@ -1350,20 +3994,139 @@ impl DecodedFrame {
   pub fn is_complete(&self) -> bool {
     !self.is_streaming()
   }
+
+  /// Read the complete frame, if the decoded frame is not the start of a stream.
+  pub fn as_complete(&self) -> Option<&Frame> {
+    match *self {
+      DecodedFrame::Complete(ref frame) => Some(frame),
+      DecodedFrame::Streaming(_) => None,
+    }
+  }
+
+  /// Read the streamed frame, if the decoded frame starts a stream.
+  pub fn as_streaming(&self) -> Option<&StreamedFrame> {
+    match *self {
+      DecodedFrame::Streaming(ref frame) => Some(frame),
+      DecodedFrame::Complete(_) => None,
+    }
+  }
+
+  /// Read the kind of the complete frame, or the kind of data being streamed.
+  pub fn kind(&self) -> FrameKind {
+    match *self {
+      DecodedFrame::Complete(ref frame) => frame.kind(),
+      DecodedFrame::Streaming(ref frame) => frame.kind,
+    }
+  }
+
+  /// Whether or not the decoded frame is the empty chunked string that ends a stream.
+  pub fn is_end_stream(&self) -> bool {
+    self.as_complete().map(|frame| frame.is_end_stream_frame()).unwrap_or(false)
+  }
+}
+
+impl TryFrom<DecodedFrame> for Frame {
+  type Error = RedisProtocolError;
+
+  fn try_from(value: DecodedFrame) -> Result<Self, Self::Error> {
+    value.into_complete_frame()
+  }
 }
 
-#[cfg(test)]
-mod tests {
-  use super::*;
-  use crate::resp3::utils::new_map;
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::utils::new_map;
+  use std::hash::BuildHasher;
+
+  #[test]
+  fn should_map_every_frame_kind_to_and_from_bytes() {
+    for kind in FrameKind::ALL.iter() {
+      match kind.try_to_byte() {
+        Some(byte) => assert_eq!(FrameKind::from_byte(byte), Some(*kind)),
+        None => assert!(kind.is_hello()),
+      };
+      assert!(!(kind.is_aggregate() && kind.is_scalar()));
+    }
+    for byte in 0..=u8::MAX {
+      if let Some(kind) = FrameKind::from_byte(byte) {
+        assert_eq!(kind.to_byte(), byte);
+      }
+    }
+
+    assert_eq!(FrameKind::ALL.len(), 18);
+    assert!(FrameKind::Push.is_aggregate() && !FrameKind::Push.is_streamable());
+    assert!(FrameKind::BlobString.is_streamable() && FrameKind::BlobString.is_scalar());
+    assert!(!FrameKind::EndStream.is_scalar() && !FrameKind::EndStream.is_aggregate());
+  }
+
+  #[test]
+  fn should_display_frame_kinds_with_spec_names() {
+    assert_eq!(FrameKind::BlobString.to_string(), "Blob String");
+    assert_eq!(FrameKind::VerbatimString.to_string(), "Verbatim String");
+    assert_eq!(Frame::new_big_number("1").kind().to_string(), "Big Number");
+
+    let mut names: Vec<_> = FrameKind::ALL.iter().map(|kind| kind.name()).collect();
+    names.sort_unstable();
+    names.dedup();
+    assert_eq!(names.len(), FrameKind::ALL.len());
+  }
+
+  #[test]
+  fn should_read_decoded_frames_without_consuming() {
+    let complete = DecodedFrame::Complete(Frame::new_end_stream());
+    let streaming = DecodedFrame::Streaming(StreamedFrame::new(FrameKind::Map));
+
+    assert_eq!(complete.as_complete(), Some(&Frame::new_end_stream()));
+    assert!(complete.as_streaming().is_none());
+    assert!(complete.is_end_stream());
+    assert_eq!(streaming.as_streaming().map(|frame| frame.kind), Some(FrameKind::Map));
+    assert!(streaming.as_complete().is_none());
+    assert!(!streaming.is_end_stream());
+    assert_eq!(streaming.kind(), FrameKind::Map);
+
+    let frame: Frame = 1.into();
+    assert_eq!(DecodedFrame::Complete(frame.clone()).kind(), FrameKind::Number);
+    assert_eq!(Frame::try_from(DecodedFrame::Complete(frame.clone())), Ok(frame));
+    assert!(Frame::try_from(streaming).is_err());
+  }
+
+  #[test]
+  fn should_limit_streamed_frame_size() {
+    let mut streaming_buf = StreamedFrame::with_limit(FrameKind::BlobString, 9, 4);
+    streaming_buf
+      .add_frame((FrameKind::ChunkedString, "foo").try_into().unwrap())
+      .unwrap();
+    assert_eq!(streaming_buf.buffered_bytes(), 9);
+
+    let error = streaming_buf
+      .add_frame((FrameKind::ChunkedString, "a").try_into().unwrap())
+      .unwrap_err();
+    assert_eq!(error.kind(), &RedisProtocolErrorKind::StreamTooLarge(9));
+    assert_eq!(streaming_buf.buffered_bytes(), 9);
+
+    streaming_buf.add_frame(Frame::new_end_stream()).unwrap();
+    assert_eq!(streaming_buf.into_frame().unwrap().as_str(), Some("foo"));
+    assert_eq!(streaming_buf.buffered_bytes(), 0);
+
+    let streaming_buf = StreamedFrame::new(FrameKind::Array);
+    assert_eq!(streaming_buf.max_bytes(), STREAM_MAX_BYTES);
+    assert_eq!(streaming_buf.max_frames(), STREAM_MAX_FRAMES);
+  }
 
   #[test]
   fn should_convert_basic_streaming_buffer_to_frame() {
     let mut streaming_buf = StreamedFrame::new(FrameKind::BlobString);
-    streaming_buf.add_frame((FrameKind::ChunkedString, "foo").try_into().unwrap());
-    streaming_buf.add_frame((FrameKind::ChunkedString, "bar").try_into().unwrap());
-    streaming_buf.add_frame((FrameKind::ChunkedString, "baz").try_into().unwrap());
-    streaming_buf.add_frame(Frame::new_end_stream());
+    streaming_buf
+      .add_frame((FrameKind::ChunkedString, "foo").try_into().unwrap())
+      .unwrap();
+    streaming_buf
+      .add_frame((FrameKind::ChunkedString, "bar").try_into().unwrap())
+      .unwrap();
+    streaming_buf
+      .add_frame((FrameKind::ChunkedString, "baz").try_into().unwrap())
+      .unwrap();
+    streaming_buf.add_frame(Frame::new_end_stream()).unwrap();
     let frame = streaming_buf
       .into_frame()
       .expect("Failed to build frame from chunked stream");
@@ -1381,10 +4144,16 @@ mod tests {
     let mut streaming_buf = StreamedFrame::new(FrameKind::BlobString);
     streaming_buf.attributes = Some(attributes.clone());
 
-    streaming_buf.add_frame((FrameKind::ChunkedString, "foo").try_into().unwrap());
-    streaming_buf.add_frame((FrameKind::ChunkedString, "bar").try_into().unwrap());
-    streaming_buf.add_frame((FrameKind::ChunkedString, "baz").try_into().unwrap());
-    streaming_buf.add_frame(Frame::new_end_stream());
+    streaming_buf
+      .add_frame((FrameKind::ChunkedString, "foo").try_into().unwrap())
+      .unwrap();
+    streaming_buf
+      .add_frame((FrameKind::ChunkedString, "bar").try_into().unwrap())
+      .unwrap();
+    streaming_buf
+      .add_frame((FrameKind::ChunkedString, "baz").try_into().unwrap())
+      .unwrap();
+    streaming_buf.add_frame(Frame::new_end_stream()).unwrap();
 
     let frame = streaming_buf
       .into_frame()
@@ -1393,4 +4162,889 @@ mod tests {
     assert_eq!(frame.as_str(), Some("foobarbaz"));
     assert_eq!(frame.attributes(), Some(&attributes));
   }
+
+  #[test]
+  fn should_strip_nested_attributes() {
+    let mut attributes = new_map(None);
+    attributes.insert((FrameKind::SimpleString, "ttl").try_into().unwrap(), 1.into());
+    let with_attributes = |frame: Frame| {
+      let mut frame = frame;
+      frame.add_attributes(attributes.clone()).unwrap();
+      frame
+    };
+
+    let mut map = new_map(None);
+    map.insert(
+      with_attributes((FrameKind::BlobString, "foo").try_into().unwrap()),
+      with_attributes(1.into()),
+    );
+    let mut frame = with_attributes(Frame::Array {
+      data: vec![
+        with_attributes(Frame::Map {
+          data: map,
+          attributes: None,
+        }),
+        Frame::Null { attributes: None },
+      ],
+      attributes: None,
+    });
+    frame.strip_attributes();
+
+    assert!(frame.attributes().is_none());
+    let inner = match frame {
+      Frame::Array { ref data, .. } => &data[0],
+      _ => panic!("Expected array."),
+    };
+    assert!(inner.attributes().is_none());
+    match *inner {
+      Frame::Map { ref data, .. } => {
+        let (key, value) = data.iter().next().unwrap();
+        assert!(key.attributes().is_none());
+        assert!(value.attributes().is_none());
+      }
+      _ => panic!("Expected map."),
+    }
+  }
+
+  #[test]
+  fn should_check_status_replies() {
+    let simple = |data: &str| Frame::SimpleString {
+      data: data.into(),
+      attributes: None,
+    };
+
+    assert!(simple("OK").is_ok());
+    assert!(!simple("OK").is_queued());
+    assert!(simple("QUEUED").is_queued());
+    assert!(!Frame::BlobString {
+      data: b"OK".to_vec(),
+      attributes: None,
+    }
+    .is_ok());
+    assert!(!Frame::SimpleError {
+      data: "OK".into(),
+      attributes: None,
+    }
+    .is_ok());
+    assert!(Frame::Null { attributes: None }.is_nil());
+    assert!(!Frame::from(false).is_nil());
+    assert_eq!(simple("OK").kind(), FrameKind::SimpleString);
+  }
+
+  #[test]
+  fn should_convert_error_frames_into_results() {
+    assert_eq!(Frame::from(1).into_result(), Ok(Frame::from(1)));
+
+    let mut attributes = FrameMap::new();
+    attributes.insert(Frame::from(1), Frame::from(2));
+    let error = Frame::BlobError {
+      data: b"NOSCRIPT No matching script".to_vec(),
+      attributes: Some(attributes.clone()),
+    }
+    .into_result()
+    .unwrap_err();
+    assert_eq!(error, RedisErrorFrame {
+      kind: RedisErrorKind::NoScript,
+      message: "No matching script".into(),
+      attributes: Some(attributes),
+    });
+    assert_eq!(error.to_string(), "NOSCRIPT No matching script");
+
+    let error = Frame::SimpleError {
+      data: "CUSTOM".into(),
+      attributes: None,
+    }
+    .into_result()
+    .unwrap_err();
+    assert_eq!(error.kind, RedisErrorKind::Unknown("CUSTOM".into()));
+    assert_eq!(error.message, "");
+    assert_eq!(error.to_string(), "CUSTOM");
+  }
+
+  #[test]
+  fn should_coerce_scalar_frames_to_strings() {
+    let blob = |data: &[u8]| Frame::BlobString {
+      data: data.to_vec(),
+      attributes: None,
+    };
+    let double = |data: f64| Frame::Double { data, attributes: None };
+
+    assert_eq!(blob(b"foo").coerce_str(), Some(Cow::Borrowed("foo")));
+    assert_eq!(blob(b"\xff").coerce_str(), None);
+    assert_eq!(Frame::from(-12).coerce_str().unwrap(), "-12");
+    assert_eq!(double(1.5).coerce_str().unwrap(), "1.5");
+    assert_eq!(double(3.0).coerce_str().unwrap(), "3");
+    assert_eq!(double(f64::INFINITY).coerce_str().unwrap(), "inf");
+    assert_eq!(Frame::from(false).coerce_str().unwrap(), "0");
+    let array = Frame::Array {
+      data: vec![],
+      attributes: None,
+    };
+    assert_eq!(array.coerce_str(), None);
+
+    assert_eq!(blob(b"a\xffb").to_string_lossy().unwrap(), "a\u{fffd}b");
+    assert_eq!(Frame::from(true).to_string_lossy().unwrap(), "1");
+    assert_eq!(Frame::Null { attributes: None }.to_string_lossy(), None);
+  }
+
+  #[test]
+  fn should_flatten_aggregate_frames() {
+    let array = |data: Vec<Frame>| Frame::Array { data, attributes: None };
+    let map = |key: Frame, value: Frame| {
+      let mut data = FrameMap::new();
+      data.insert(key, value);
+      Frame::Map { data, attributes: None }
+    };
+
+    assert_eq!(Frame::from(1).flatten(), vec![Frame::from(1)]);
+    assert_eq!(map(1.into(), 2.into()).flatten(), vec![Frame::from(1), Frame::from(2)]);
+    assert_eq!(map(1.into(), array(vec![2.into(), 3.into()])).flatten(), vec![
+      Frame::from(1),
+      Frame::from(2),
+      Frame::from(3)
+    ]);
+    assert_eq!(
+      array(vec![map(1.into(), 2.into()), array(vec![array(vec![3.into()])]), 4.into()]).flatten(),
+      vec![Frame::from(1), Frame::from(2), array(vec![3.into()]), Frame::from(4)]
+    );
+  }
+
+  #[test]
+  fn should_convert_aggregate_frames_into_vecs() {
+    let set: FrameSet = vec![Frame::from(1)].into_iter().collect();
+    let set = Frame::Set {
+      data: set,
+      attributes: None,
+    };
+    assert_eq!(set.into_vec_of::<String>().unwrap(), vec!["1".to_owned()]);
+
+    let array = Frame::Array {
+      data: vec![Frame::from(1), Frame::from(true)],
+      attributes: None,
+    };
+    let error = array.into_vec_of::<i64>().unwrap_err();
+    assert_eq!(error.description(), "Element 1: Cannot convert Boolean to i64.");
+    assert!(Frame::from(1).into_vec_of::<i64>().is_err());
+  }
+
+  #[test]
+  fn should_read_map_pairs() {
+    let blob = |s: &str| Frame::BlobString {
+      data: s.as_bytes().to_vec(),
+      attributes: None,
+    };
+    let mut data = FrameMap::new();
+    data.insert(blob("timeout"), 300.into());
+    data.insert(blob("maxmemory"), 0.into());
+    let frame = Frame::Map { data, attributes: None };
+
+    let mut pairs: Vec<_> = frame.map_pairs().unwrap().collect();
+    pairs.sort();
+    assert_eq!(pairs, vec![
+      (&blob("maxmemory"), &Frame::from(0)),
+      (&blob("timeout"), &Frame::from(300))
+    ]);
+    assert_eq!(frame.get("timeout"), Some(&Frame::from(300)));
+    assert_eq!(frame.get("foo"), None);
+
+    let mut pairs: Vec<_> = frame.into_map_pairs().unwrap().collect();
+    pairs.sort();
+    assert_eq!(pairs, vec![(blob("maxmemory"), Frame::from(0)), (blob("timeout"), Frame::from(300))]);
+
+    assert!(Frame::from(1).map_pairs().is_none());
+    assert_eq!(Frame::from(1).get("foo"), None);
+    assert_eq!(Frame::from(1).into_map_pairs().err(), Some(Box::new(Frame::from(1))));
+  }
+
+  #[test]
+  fn should_iterate_over_inner_frames() {
+    let array = Frame::Array {
+      data: vec![1.into(), 2.into()],
+      attributes: None,
+    };
+    assert_eq!(array.children().len(), 2);
+    assert_eq!(
+      (&array).into_iter().collect::<Vec<_>>(),
+      vec![&Frame::from(1), &Frame::from(2)]
+    );
+    assert_eq!(
+      array.into_iter().collect::<Vec<_>>(),
+      vec![Frame::from(1), Frame::from(2)]
+    );
+
+    let mut data = FrameMap::new();
+    data.insert(1.into(), 2.into());
+    let map = Frame::Map { data, attributes: None };
+    let mut children = map.children();
+    assert_eq!(children.len(), 2);
+    assert_eq!(children.next(), Some(&Frame::from(1)));
+    assert_eq!(children.len(), 1);
+    assert_eq!(children.next(), Some(&Frame::from(2)));
+    assert_eq!(children.next(), None);
+    assert_eq!(
+      map.into_iter().collect::<Vec<_>>(),
+      vec![Frame::from(1), Frame::from(2)]
+    );
+
+    let set: FrameSet = vec![Frame::from(1)].into_iter().collect();
+    let set = Frame::Set {
+      data: set,
+      attributes: None,
+    };
+    assert_eq!(set.children().collect::<Vec<_>>(), vec![&Frame::from(1)]);
+    assert_eq!(set.into_iter().len(), 1);
+
+    assert_eq!(Frame::from(1).children().next(), None);
+    assert_eq!(Frame::from(1).into_iter().len(), 0);
+  }
+
+  #[test]
+  fn should_collect_aggregate_frames() {
+    let mut array: Frame = vec![Frame::from(1)].into_iter().collect();
+    array.try_extend(vec![Frame::from(2)]).unwrap();
+    assert_eq!(array, Frame::Array {
+      data: vec![1.into(), 2.into()],
+      attributes: None,
+    });
+
+    let mut set = Frame::from(vec![Frame::from(1)].into_iter().collect::<FrameSet>());
+    set.try_extend(vec![Frame::from(1), Frame::from(2)]).unwrap();
+    assert_eq!(set.len(), 2);
+
+    let mut map: Frame = vec![(Frame::from(1), Frame::from(true))].into_iter().collect();
+    map
+      .try_extend_pairs(vec![(Frame::from(2), Frame::from(false))])
+      .unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.map_pairs().unwrap().find(|(k, _)| **k == Frame::from(2)).unwrap().1, &Frame::from(false));
+
+    let doubled: Frame = array
+      .into_iter()
+      .map(|f| Frame::from(f.as_i64().unwrap() * 2))
+      .collect();
+    assert_eq!(doubled.into_vec_of::<i64>().unwrap(), vec![2, 4]);
+  }
+
+  #[test]
+  fn should_error_extending_mismatched_frames() {
+    let mut map = Frame::from(FrameMap::new());
+    let error = map.try_extend(vec![Frame::from(1)]).unwrap_err();
+    assert_eq!(error.description(), "Cannot extend Map frame with frames.");
+    assert_eq!(map.len(), 0);
+
+    let mut array: Frame = vec![Frame::from(1)].into_iter().collect();
+    let error = array
+      .try_extend_pairs(vec![(Frame::from(1), Frame::from(2))])
+      .unwrap_err();
+    assert_eq!(error.description(), "Cannot extend Array frame with key-value pairs.");
+    assert!(Frame::from(1).try_extend(vec![Frame::from(2)]).is_err());
+    assert_eq!(array.len(), 1);
+  }
+
+  #[test]
+  fn should_map_values_preserving_attributes() {
+    let blob = |s: &str| Frame::BlobString {
+      data: s.as_bytes().to_vec(),
+      attributes: None,
+    };
+    let mut attributes = FrameMap::new();
+    attributes.insert(blob("ttl"), 10.into());
+    let mut data = FrameMap::new();
+    data.insert(blob("key"), Frame::BlobString {
+      data: b"secret".to_vec(),
+      attributes: Some(attributes.clone()),
+    });
+    let frame = Frame::Array {
+      data: vec![Frame::Map { data, attributes: None }, 1.into()],
+      attributes: Some(attributes.clone()),
+    };
+
+    let redacted = frame.clone().map_values(|f| match f {
+      Frame::BlobString { ref data, .. } if data == b"secret" => blob("***"),
+      f => f,
+    });
+    let mut data = FrameMap::new();
+    data.insert(blob("key"), Frame::BlobString {
+      data: b"***".to_vec(),
+      attributes: Some(attributes.clone()),
+    });
+    let expected = Frame::Array {
+      data: vec![Frame::Map { data, attributes: None }, 1.into()],
+      attributes: Some(attributes),
+    };
+    assert!(redacted.diff(&expected).is_empty());
+
+    let mut visited = Vec::new();
+    let result = frame.clone().try_map(|f| {
+      visited.push(f.kind());
+      Ok::<_, ()>(f)
+    });
+    assert!(result.unwrap().diff(&frame).is_empty());
+    assert_eq!(visited, vec![
+      FrameKind::BlobString,
+      FrameKind::BlobString,
+      FrameKind::Map,
+      FrameKind::Number,
+      FrameKind::Array
+    ]);
+
+    let result = frame.try_map(|f| if f.is_aggregate_type() { Ok(f) } else { Err(f.kind()) });
+    assert_eq!(result, Err(FrameKind::BlobString));
+  }
+
+  #[test]
+  fn should_order_frames_by_kind_then_contents() {
+    let double = |data: f64| Frame::Double { data, attributes: None };
+    let mut frames = [
+      double(f64::NAN),
+      Frame::Null { attributes: None },
+      double(1.5),
+      Frame::Number {
+        data: 2,
+        attributes: None,
+      },
+      double(f64::NEG_INFINITY),
+      Frame::Number {
+        data: -1,
+        attributes: None,
+      },
+    ];
+    frames.sort();
+
+    assert_eq!(frames[0], (-1).into());
+    assert_eq!(frames[1], 2.into());
+    assert_eq!(frames[2], Frame::Null { attributes: None });
+    assert_eq!(frames[3], double(f64::NEG_INFINITY));
+    assert_eq!(frames[4], double(1.5));
+    assert_eq!(frames[5], double(f64::NAN));
+    assert_eq!(double(0.0).cmp(&double(-0.0)), Ordering::Equal);
+    assert_eq!(double(f64::NAN).cmp(&double(f64::NAN)), Ordering::Equal);
+  }
+
+  #[test]
+  fn should_iterate_maps_and_sets() {
+    let map: FrameMap = (0..4).map(|i| (Frame::from(i), Frame::from(i * 10))).collect();
+    let set: FrameSet = (0..4).map(Frame::from).collect();
+
+    let iter: FrameMapIter = map.iter();
+    assert_eq!(iter.len(), 4);
+    let entries: Vec<(Frame, Frame)> = map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    let into_iter: FrameMapIntoIter = map.into_iter();
+    assert_eq!(into_iter.len(), 4);
+    assert_eq!(into_iter.collect::<Vec<_>>(), entries);
+
+    let iter: FrameSetIter = (&set).into_iter();
+    assert_eq!(iter.len(), 4);
+    let members: Vec<Frame> = set.iter().cloned().collect();
+    let into_iter: FrameSetIntoIter = set.into_iter();
+    assert_eq!(into_iter.len(), 4);
+    assert_eq!(into_iter.collect::<Vec<_>>(), members);
+  }
+
+  #[test]
+  fn should_read_map_and_set_entries_by_index() {
+    let map: FrameMap = (0..4).map(|i| (Frame::from(i), Frame::from(i * 10))).collect();
+    for (index, (key, value)) in map.iter().enumerate() {
+      assert_eq!(map.get_index(index), Some((key, value)));
+      assert_eq!(map.get_index_of(key), Some(index));
+    }
+    assert_eq!(map.get_index(4), None);
+    assert_eq!(map.get_index_of(&4.into()), None);
+
+    let set: FrameSet = (0..4).map(Frame::from).collect();
+    for (index, member) in set.iter().enumerate() {
+      assert_eq!(set.get_index(index), Some(member));
+      assert_eq!(set.get_index_of(member), Some(index));
+    }
+    assert_eq!(set.get_index(4), None);
+  }
+
+  #[test]
+  #[cfg(all(feature = "index-map", not(feature = "btree-map")))]
+  fn should_preserve_wire_order_with_index_map() {
+    use crate::resp3::{decode::complete::decode, encode::complete::encode_bytes};
+    use bytes::BytesMut;
+
+    let map_bytes = b"%3\r\n+c\r\n:1\r\n+a\r\n:2\r\n+b\r\n:3\r\n";
+    let (frame, _) = decode(map_bytes).unwrap().unwrap();
+    let keys: Vec<_> = match frame {
+      Frame::Map { ref data, .. } => data.keys().map(|k| k.as_str().unwrap().to_owned()).collect(),
+      _ => panic!("Expected map."),
+    };
+    assert_eq!(keys, vec!["c", "a", "b"]);
+    let mut buf = BytesMut::new();
+    encode_bytes(&mut buf, &frame).unwrap();
+    assert_eq!(&buf[..], &map_bytes[..]);
+
+    let set_bytes = b"~3\r\n:3\r\n:1\r\n:2\r\n";
+    let (frame, _) = decode(set_bytes).unwrap().unwrap();
+    match frame {
+      Frame::Set { ref data, .. } => assert_eq!(data.get_index(0), Some(&3.into())),
+      _ => panic!("Expected set."),
+    };
+    let mut buf = BytesMut::new();
+    encode_bytes(&mut buf, &frame).unwrap();
+    assert_eq!(&buf[..], &set_bytes[..]);
+  }
+
+  #[test]
+  fn should_hash_frames_with_fast_hasher() {
+    let hash = |frame: &Frame| {
+      let mut hasher = FastHasher::default();
+      frame.hash(&mut hasher);
+      hasher.finish()
+    };
+    let blob = |data: &str| Frame::BlobString {
+      data: data.as_bytes().to_vec(),
+      attributes: None,
+    };
+
+    assert_eq!(hash(&blob("abcdefghijk")), hash(&blob("abcdefghijk")));
+    assert_ne!(hash(&blob("abcdefghijk")), hash(&blob("abcdefghijj")));
+    assert_ne!(hash(&Frame::from(1)), hash(&Frame::from(2)));
+
+    let mut map: HashMap<Frame, usize, std::hash::BuildHasherDefault<FastHasher>> = HashMap::default();
+    for i in 0..100 {
+      map.insert(blob(&i.to_string()), i);
+    }
+    assert_eq!(map.len(), 100);
+    assert_eq!(map.get(&blob("42")), Some(&42));
+  }
+
+  #[test]
+  fn should_hash_map_keys_with_frame_hash_builder() {
+    let builder = FrameHashBuilder::default();
+    assert_eq!(builder.hash_one(Frame::from(42)), builder.hash_one(Frame::from(42)));
+
+    #[cfg(feature = "fast-hash")]
+    {
+      let mut hasher = FastHasher::default();
+      Frame::from(42).hash(&mut hasher);
+      assert_eq!(builder.hash_one(Frame::from(42)), hasher.finish());
+      assert_eq!(FrameHashBuilder::default().hash_one(Frame::from(42)), hasher.finish());
+    }
+
+    let map: FrameMap = (0..1000).map(|i| (Frame::from(i), Frame::from(i * 2))).collect();
+    let set: FrameSet = (0..1000).map(Frame::from).collect();
+    assert_eq!((map.len(), set.len()), (1000, 1000));
+    for i in 0..1000 {
+      assert_eq!(map.get(&Frame::from(i)), Some(&Frame::from(i * 2)));
+      assert!(set.contains(&Frame::from(i)));
+    }
+    assert_eq!(map.get(&Frame::from(1000)), None);
+  }
+
+  #[test]
+  fn should_create_frames_without_attributes() {
+    assert_eq!(
+      Frame::new_blob("foo"),
+      Frame::BlobString {
+        data: b"foo".to_vec(),
+        attributes: None,
+      }
+    );
+    assert_eq!(
+      Frame::new_blob_error(b"ERR".to_vec()),
+      Frame::BlobError {
+        data: b"ERR".to_vec(),
+        attributes: None,
+      }
+    );
+    assert_eq!(
+      Frame::new_simple("OK".to_owned()),
+      Frame::SimpleString {
+        data: "OK".into(),
+        attributes: None,
+      }
+    );
+    assert_eq!(
+      Frame::new_simple_error("ERR"),
+      Frame::SimpleError {
+        data: "ERR".into(),
+        attributes: None,
+      }
+    );
+    assert_eq!(Frame::new_boolean(true), true.into());
+    assert_eq!(Frame::new_null(), Frame::Null { attributes: None });
+    assert_eq!(Frame::new_number(-1), (-1).into());
+    assert_eq!(
+      Frame::new_double(1.5),
+      Frame::Double {
+        data: 1.5,
+        attributes: None,
+      }
+    );
+    assert_eq!(
+      Frame::new_big_number("123"),
+      Frame::BigNumber {
+        data: b"123".to_vec(),
+        attributes: None,
+      }
+    );
+    assert_eq!(
+      Frame::new_verbatim(VerbatimStringFormat::Text, "foo"),
+      Frame::VerbatimString {
+        data: b"foo".to_vec(),
+        format: VerbatimStringFormat::Text,
+        attributes: None,
+      }
+    );
+    assert_eq!(
+      Frame::new_array(vec![1.into(), 2.into()]),
+      Frame::Array {
+        data: vec![1.into(), 2.into()],
+        attributes: None,
+      }
+    );
+    assert_eq!(
+      Frame::new_push(vec![Frame::new_simple("message")]),
+      Frame::Push {
+        data: vec![Frame::new_simple("message")],
+        attributes: None,
+      }
+    );
+    assert_eq!(
+      Frame::new_hello(RespVersion::RESP3, None),
+      Frame::Hello {
+        version: RespVersion::RESP3,
+        auth: None,
+      }
+    );
+    assert_eq!(Frame::new_chunk(""), Frame::new_end_stream());
+
+    let map = Frame::new_map(vec![(1.into(), 2.into()), (1.into(), 3.into())]);
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.map_pairs().unwrap().next(), Some((&1.into(), &3.into())));
+    let set = Frame::new_set((0..3).map(Frame::new_number).chain(Some(Frame::new_number(0))));
+    assert_eq!(set.kind(), FrameKind::Set);
+    assert_eq!(set.len(), 3);
+  }
+
+  #[test]
+  fn should_order_maps_and_sets_by_contents() {
+    let mut first = resp3_utils::new_map(None);
+    first.insert(1.into(), true.into());
+    first.insert(2.into(), false.into());
+    let mut second = resp3_utils::new_map(None);
+    second.insert(2.into(), false.into());
+    second.insert(1.into(), true.into());
+    assert_eq!(first.sorted(), second.sorted());
+
+    let first = Frame::Map {
+      data: first,
+      attributes: None,
+    };
+    let second = Frame::Map {
+      data: second,
+      attributes: None,
+    };
+    assert_eq!(first.cmp(&second), Ordering::Equal);
+
+    let set: FrameSet = vec![3.into(), 1.into(), 2.into()].into_iter().collect();
+    let expected: Vec<Frame> = vec![1.into(), 2.into(), 3.into()];
+    assert_eq!(set.sorted(), expected.iter().collect::<Vec<_>>());
+  }
+
+  #[test]
+  fn should_read_typed_attributes() {
+    let blob = |s: &str| Frame::BlobString {
+      data: s.as_bytes().to_vec(),
+      attributes: None,
+    };
+    let double = |data: f64| Frame::Double { data, attributes: None };
+
+    let mut popularity = FrameMap::new();
+    popularity.insert(blob("a"), double(0.1923));
+    let mut attributes = FrameMap::new();
+    attributes.insert(blob("key-popularity"), Frame::Map {
+      data: popularity,
+      attributes: None,
+    });
+    attributes.insert(
+      Frame::SimpleString {
+        data: "ttl".into(),
+        attributes: None,
+      },
+      3600.into(),
+    );
+    let frame = Frame::Number {
+      data: 1,
+      attributes: Some(attributes),
+    };
+
+    assert_eq!(frame.attribute("ttl"), Some(&Frame::from(3600)));
+    assert_eq!(frame.attribute("foo"), None);
+    assert_eq!(frame.ttl(), Some(3600));
+    assert_eq!(frame.key_popularity(), Some(vec![("a", 0.1923)]));
+
+    let mut attributes = FrameMap::new();
+    attributes.insert(blob("key-popularity"), Frame::Array {
+      data: vec![blob("a"), double(0.5), blob("b"), 1.into()],
+      attributes: None,
+    });
+    let frame = Frame::Null {
+      attributes: Some(attributes),
+    };
+    assert_eq!(frame.key_popularity(), Some(vec![("a", 0.5), ("b", 1.0)]));
+    assert_eq!(frame.ttl(), None);
+    assert_eq!(Frame::from(1).attribute("ttl"), None);
+  }
+
+  #[test]
+  fn should_map_attributes() {
+    let mut attributes = FrameMap::new();
+    attributes.insert(Frame::from(1), Frame::from(2));
+
+    let frame = Frame::from(1).with_attributes(attributes.clone()).unwrap();
+    assert_eq!(frame.attributes(), Some(&attributes));
+    assert!(Frame::ChunkedString(vec![]).with_attributes(attributes.clone()).is_err());
+
+    let frame = frame.map_attributes(|attrs| {
+      let mut attrs = attrs.unwrap();
+      attrs.insert(Frame::from(3), Frame::from(4));
+      Some(attrs)
+    });
+    assert_eq!(frame.attributes().unwrap().len(), 2);
+    let mut frame = frame.map_attributes(|_| None);
+    assert_eq!(frame.take_attributes(), None);
+
+    let frame = Frame::Array {
+      data: vec![Frame::from(1), Frame::Null { attributes: None }],
+      attributes: None,
+    };
+    let mut calls = 0;
+    let frame = frame.map_attributes_recursive(|_| {
+      calls += 1;
+      Some(attributes.clone())
+    });
+    assert_eq!(calls, 3);
+    match frame {
+      Frame::Array {
+        ref data,
+        attributes: Some(_),
+      } => assert!(data.iter().all(|f| f.attributes().is_some())),
+      _ => panic!("Expected array with attributes."),
+    };
+  }
+
+  #[test]
+  fn should_compare_frames_ignoring_attributes() {
+    let mut attributes = FrameMap::new();
+    attributes.insert(Frame::from(1), Frame::from(2));
+    let verbatim = |attributes| Frame::VerbatimString {
+      data: b"foo".to_vec(),
+      format: VerbatimStringFormat::Text,
+      attributes,
+    };
+
+    let mut map = FrameMap::new();
+    map.insert(Frame::from(1), verbatim(Some(attributes.clone())));
+    let expected_map = {
+      let mut map = FrameMap::new();
+      map.insert(Frame::from(1), verbatim(None));
+      map
+    };
+    let actual = Frame::Array {
+      data: vec![
+        Frame::Map {
+          data: map,
+          attributes: None,
+        },
+        Frame::Null {
+          attributes: Some(attributes.clone()),
+        },
+      ],
+      attributes: Some(attributes),
+    };
+    let expected = Frame::Array {
+      data: vec![
+        Frame::Map {
+          data: expected_map,
+          attributes: None,
+        },
+        Frame::Null { attributes: None },
+      ],
+      attributes: None,
+    };
+
+    assert!(actual.eq_ignoring_attributes(&expected));
+    assert!(!actual.eq_ignoring_attributes(&Frame::Array {
+      data: vec![],
+      attributes: None,
+    }));
+    assert!(!verbatim(None).eq_ignoring_attributes(&Frame::from(1)));
+  }
+
+  #[test]
+  fn should_truncate_debug_output() {
+    let mut attributes = FrameMap::new();
+    attributes.insert(
+      Frame::SimpleString {
+        data: "ключ".into(),
+        attributes: None,
+      },
+      Frame::Null { attributes: None },
+    );
+    let frame = Frame::Array {
+      data: vec![
+        Frame::BlobString {
+          data: b"\x00abcdef".to_vec(),
+          attributes: Some(attributes),
+        },
+        Frame::from(1),
+      ],
+      attributes: None,
+    };
+
+    assert_eq!(
+      format!("{:?}", frame.truncated(3)),
+      "Array { data: [BlobString { data: b\"\\x00ab\"... (7 bytes), attributes: Some({SimpleString { data: \
+       \"к\"... (8 bytes), attributes: None }: Null { attributes: None }}) }, Number { data: 1, attributes: None }], \
+       attributes: None }"
+    );
+    assert_eq!(
+      format!("{:?}", Frame::from(1).truncated(0)),
+      format!("{:?}", Frame::from(1))
+    );
+    assert!(format!("{:#?}", frame.truncated(100)).contains("b\"\\x00abcdef\",\n"));
+  }
+
+  #[test]
+  fn should_convert_frames_to_and_from_wire_strings() {
+    let frame = Frame::Push {
+      data: vec![
+        Frame::SimpleString {
+          data: "a\tb".into(),
+          attributes: None,
+        },
+        Frame::Null { attributes: None },
+      ],
+      attributes: None,
+    };
+    let wire = ">2\\r\\n+a\\tb\\r\\n_\\r\\n";
+
+    assert_eq!(frame.to_wire_string().unwrap(), wire);
+    assert_eq!(Frame::from_wire_string(wire).unwrap(), frame);
+    assert!(Frame::from_wire_string(">2\\r\\n_\\r\\n").is_err());
+  }
+
+  #[test]
+  #[cfg(feature = "bignum")]
+  fn should_convert_big_numbers() {
+    let value: BigInt = "-3492890328409238509324850943850943825024385".parse().unwrap();
+    let frame: Frame = value.clone().into();
+
+    assert_eq!(
+      frame.as_bytes(),
+      Some("-3492890328409238509324850943850943825024385".as_bytes())
+    );
+    assert_eq!(BigInt::try_from(frame).unwrap(), value);
+    assert_eq!(BigInt::try_from(Frame::from(42)).unwrap(), BigInt::from(42));
+
+    let invalid = Frame::BigNumber {
+      data: b"1_000".to_vec(),
+      attributes: None,
+    };
+    assert!(BigInt::try_from(&invalid).is_err());
+    assert!(BigInt::try_from(Frame::Null { attributes: None }).is_err());
+  }
+
+  #[test]
+  fn should_diff_nested_frames() {
+    let blob = |s: &str| Frame::BlobString {
+      data: s.as_bytes().to_vec(),
+      attributes: None,
+    };
+    let config = |ttl: i64, extra: Option<(&str, Frame)>| {
+      let mut data = FrameMap::new();
+      data.insert(blob("ttl"), ttl.into());
+      if let Some((key, value)) = extra {
+        data.insert(blob(key), value);
+      }
+      let mut outer = FrameMap::new();
+      outer.insert(blob("config"), Frame::Map { data, attributes: None });
+      Frame::Map {
+        data: outer,
+        attributes: None,
+      }
+    };
+
+    let left = Frame::Array {
+      data: vec![1.into(), blob("foo"), config(3600, Some(("name", blob("bar")))), true.into()],
+      attributes: None,
+    };
+    let mut attributes = FrameMap::new();
+    attributes.insert(blob("ttl"), 10.into());
+    let right = Frame::Array {
+      data: vec![
+        1.into(),
+        Frame::Number {
+          data: 2,
+          attributes: Some(attributes),
+        },
+        config(60, None),
+      ],
+      attributes: None,
+    };
+
+    assert!(left.diff(&left.clone()).is_empty());
+    let diff = left.diff(&right);
+    assert_eq!(diff.len(), 5);
+    assert_eq!(diff.differences()[0], FrameDifference {
+      path: "[1]".into(),
+      left: "BlobString(b\"foo\")".into(),
+      right: "Number(2)".into(),
+    });
+    assert_eq!(
+      diff.to_string(),
+      concat!(
+        "[1]: BlobString(b\"foo\") != Number(2)\n",
+        "[1]@attributes: <missing> != Map(len 1)\n",
+        "[2].\"config\".\"name\": BlobString(b\"bar\") != <missing>\n",
+        "[2].\"config\".\"ttl\": Number(3600) != Number(60)\n",
+        "[3]: Boolean(true) != <missing>"
+      )
+    );
+
+    let left: FrameSet = vec![1.into(), 2.into()].into_iter().collect();
+    let right: FrameSet = vec![2.into(), 3.into()].into_iter().collect();
+    let diff = Frame::Set {
+      data: left,
+      attributes: None,
+    }
+    .diff(&Frame::Set {
+      data: right,
+      attributes: None,
+    });
+    assert_eq!(diff.to_string(), ".: Number(1) != <missing>\n.: <missing> != Number(3)");
+  }
+
+  #[test]
+  fn should_hash_frame_contents() {
+    let blob = |s: &str| Frame::BlobString {
+      data: s.as_bytes().to_vec(),
+      attributes: None,
+    };
+    let mut first = FrameMap::new();
+    let mut second = FrameMap::new();
+    for idx in 0..20 {
+      first.insert(blob(&idx.to_string()), idx.into());
+      second.insert(blob(&(19 - idx).to_string()), (19 - idx).into());
+    }
+    let first = Frame::Map {
+      data: first,
+      attributes: None,
+    };
+    let mut attributes = FrameMap::new();
+    attributes.insert(blob("ttl"), 3600.into());
+    let second = Frame::Map {
+      data: second,
+      attributes: Some(attributes),
+    };
+
+    assert_eq!(first.content_hash(false).unwrap(), second.content_hash(false).unwrap());
+    assert_ne!(first.content_hash(true).unwrap(), second.content_hash(true).unwrap());
+    assert_ne!(first.content_hash(false).unwrap(), Frame::from(1).content_hash(false).unwrap());
+    assert_eq!(Frame::from(1).content_hash(true).unwrap(), 0xa86f13e58c8f228e);
+  }
 }
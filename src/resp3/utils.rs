@@ -1,146 +1,85 @@
 use crate::resp3::types::*;
 use crate::types::{RedisProtocolError, RedisProtocolErrorKind};
-use crate::utils::{digits_in_number, PATTERN_PUBSUB_PREFIX, PUBSUB_PREFIX, PUBSUB_PUSH_PREFIX};
+use crate::core;
+use crate::utils::{PATTERN_PUBSUB_PREFIX, PUBSUB_PREFIX, PUBSUB_PUSH_PREFIX};
 use cookie_factory::GenError;
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet, VecDeque};
-
-#[cfg(feature = "index-map")]
-use indexmap::{IndexMap, IndexSet};
+use std::collections::VecDeque;
 
 pub const BOOLEAN_ENCODE_LEN: usize = 4;
 
-#[cfg(not(feature = "index-map"))]
-pub fn new_set<K>(capacity: Option<usize>) -> HashSet<K> {
-  if let Some(capacity) = capacity {
-    HashSet::with_capacity(capacity)
-  } else {
-    HashSet::new()
-  }
-}
-
-#[cfg(feature = "index-map")]
-pub fn new_set<K>(capacity: Option<usize>) -> IndexSet<K> {
-  if let Some(capacity) = capacity {
-    IndexSet::with_capacity(capacity)
-  } else {
-    IndexSet::new()
-  }
-}
-
-#[cfg(not(feature = "index-map"))]
-pub fn new_map<K, V>(capacity: Option<usize>) -> HashMap<K, V> {
+pub fn new_set(capacity: Option<usize>) -> FrameSet {
   if let Some(capacity) = capacity {
-    HashMap::with_capacity(capacity)
+    FrameSet::with_capacity(capacity)
   } else {
-    HashMap::new()
+    FrameSet::new()
   }
 }
 
-#[cfg(feature = "index-map")]
-pub fn new_map<K, V>(capacity: Option<usize>) -> IndexMap<K, V> {
+pub fn new_map(capacity: Option<usize>) -> FrameMap {
   if let Some(capacity) = capacity {
-    IndexMap::with_capacity(capacity)
+    FrameMap::with_capacity(capacity)
   } else {
-    IndexMap::new()
+    FrameMap::new()
   }
 }
 
-#[cfg(feature = "index-map")]
-pub fn hashmap_to_frame_map(data: HashMap<Frame, Frame>) -> FrameMap {
-  let mut out = IndexMap::with_capacity(data.len());
-  for (key, value) in data.into_iter() {
-    let _ = out.insert(key, value);
-  }
-
-  out
-}
-
-#[cfg(not(feature = "index-map"))]
-pub fn hashmap_to_frame_map(data: HashMap<Frame, Frame>) -> FrameMap {
-  data
-}
-
-#[cfg(feature = "index-map")]
-pub fn hashset_to_frame_set(data: HashSet<Frame>) -> FrameSet {
-  let mut out = IndexSet::with_capacity(data.len());
-  for key in data.into_iter() {
-    let _ = out.insert(key);
-  }
-
-  out
-}
-
-#[cfg(not(feature = "index-map"))]
-pub fn hashset_to_frame_set(data: HashSet<Frame>) -> FrameSet {
-  data
+/// Whether or not `data` is a valid big number, which is a decimal integer with an optional leading `-`.
+#[cfg(feature = "bignum")]
+pub fn is_big_number(data: &[u8]) -> bool {
+  let digits = if data.first() == Some(&b'-') { &data[1..] } else { data };
+  !digits.is_empty() && digits.iter().all(|b| b.is_ascii_digit())
 }
 
 pub fn blobstring_encode_len(b: &[u8]) -> usize {
-  1 + digits_in_number(b.len()) + 2 + b.len() + 2
+  core::blob_encode_len(b.len())
 }
 
-pub fn array_or_push_encode_len(frames: &Vec<Frame>) -> Result<usize, GenError> {
-  let mut total_len = 1 + digits_in_number(frames.len()) + 2;
+pub fn array_or_push_encode_len(frames: &Vec<Frame>, double_format: DoubleFormat) -> Result<usize, GenError> {
+  let mut total_len = core::length_prefix_encode_len(frames.len());
 
   for frame in frames.iter() {
-    total_len += encode_len(frame)?;
+    total_len += encode_len_with_format(frame, double_format)?;
   }
   Ok(total_len)
 }
 
 pub fn bignumber_encode_len(b: &[u8]) -> usize {
-  1 + b.len() + 2
+  core::line_encode_len(b.len())
 }
 
-pub fn simplestring_encode_len(s: &str) -> usize {
-  1 + s.as_bytes().len() + 2
+pub fn simplestring_encode_len(s: &[u8]) -> usize {
+  core::line_encode_len(s.len())
 }
 
 pub fn verbatimstring_encode_len(format: &VerbatimStringFormat, data: &[u8]) -> usize {
   // prefix, data len + format len, crlf, format, colon, data, crlf
-  1 + digits_in_number(data.len() + format.encode_len()) + 2 + format.encode_len() + data.len() + 2
+  core::blob_encode_len(data.len() + format.encode_len())
 }
 
 pub fn number_encode_len(i: &i64) -> usize {
-  let prefix = if *i < 0 { 1 } else { 0 };
-  let as_usize = if *i < 0 { (*i * -1) as usize } else { *i as usize };
-
-  1 + digits_in_number(as_usize) + 2 + prefix
+  core::integer_encode_len(*i)
 }
 
-pub fn double_encode_len(f: &f64) -> Result<usize, GenError> {
-  if f.is_nan() {
-    Err(GenError::CustomError(2))
-  } else if f.is_infinite() {
-    let inf_len = if f.is_sign_negative() {
-      NEG_INFINITY.as_bytes().len()
-    } else {
-      INFINITY.as_bytes().len()
-    };
-
-    // comma, inf|-inf, CRLF
-    Ok(1 + inf_len + 2)
-  } else {
-    // TODO there's probably a more clever way to do this
-    Ok(1 + f.to_string().as_bytes().len() + 2)
-  }
+pub fn double_encode_len(f: &f64, double_format: DoubleFormat) -> Result<usize, GenError> {
+  // comma, double, CRLF
+  Ok(1 + double_format.format(*f).len() + 2)
 }
 
-pub fn map_encode_len(map: &FrameMap) -> Result<usize, GenError> {
-  let mut total_len = 1 + digits_in_number(map.len()) + 2;
+pub fn map_encode_len(map: &FrameMap, double_format: DoubleFormat) -> Result<usize, GenError> {
+  let mut total_len = core::length_prefix_encode_len(map.len());
 
   for (key, value) in map.iter() {
-    total_len += encode_len(key)? + encode_len(value)?;
+    total_len += encode_len_with_format(key, double_format)? + encode_len_with_format(value, double_format)?;
   }
   Ok(total_len)
 }
 
-pub fn set_encode_len(set: &FrameSet) -> Result<usize, GenError> {
-  let mut total_len = 1 + digits_in_number(set.len()) + 2;
+pub fn set_encode_len(set: &FrameSet, double_format: DoubleFormat) -> Result<usize, GenError> {
+  let mut total_len = core::length_prefix_encode_len(set.len());
 
   for frame in set.iter() {
-    total_len += encode_len(frame)?;
+    total_len += encode_len_with_format(frame, double_format)?;
   }
   Ok(total_len)
 }
@@ -154,22 +93,22 @@ pub fn hello_encode_len(_version: &RespVersion, auth: &Option<Auth>) -> usize {
   total_len
 }
 
-pub fn attribute_encode_len(attributes: &Option<Attributes>) -> Result<usize, GenError> {
+pub fn attribute_encode_len(attributes: &Option<Attributes>, double_format: DoubleFormat) -> Result<usize, GenError> {
   let attribute_len = match attributes {
-    Some(attributes) => map_encode_len(attributes)?,
+    Some(attributes) => map_encode_len(attributes, double_format)?,
     None => 0,
   };
 
   Ok(attribute_len)
 }
 
-pub fn is_normal_pubsub(frames: &Vec<Frame>) -> bool {
+pub fn is_normal_pubsub(frames: &[Frame]) -> bool {
   (frames.len() == 4 || frames.len() == 5)
     && frames[0].as_str().map(|s| s == PUBSUB_PUSH_PREFIX).unwrap_or(false)
     && frames[1].as_str().map(|s| s == PUBSUB_PREFIX).unwrap_or(false)
 }
 
-pub fn is_pattern_pubsub(frames: &Vec<Frame>) -> bool {
+pub fn is_pattern_pubsub(frames: &[Frame]) -> bool {
   (frames.len() == 4 || frames.len() == 5)
     && frames[0].as_str().map(|s| s == PUBSUB_PUSH_PREFIX).unwrap_or(false)
     && frames[1].as_str().map(|s| s == PATTERN_PUBSUB_PREFIX).unwrap_or(false)
@@ -177,63 +116,69 @@ pub fn is_pattern_pubsub(frames: &Vec<Frame>) -> bool {
 
 /// Returns the number of bytes necessary to represent the frame and any associated attributes.
 pub fn encode_len(data: &Frame) -> Result<usize, GenError> {
+  encode_len_with_format(data, DoubleFormat::default())
+}
+
+/// Returns the number of bytes necessary to represent the frame and any associated attributes when doubles are
+/// formatted with `double_format`.
+pub fn encode_len_with_format(data: &Frame, double_format: DoubleFormat) -> Result<usize, GenError> {
   use crate::resp3::types::Frame::*;
 
   let total_len = match *data {
     Array {
       ref data,
       ref attributes,
-    } => array_or_push_encode_len(data)? + attribute_encode_len(attributes)?,
+    } => array_or_push_encode_len(data, double_format)? + attribute_encode_len(attributes, double_format)?,
     Push {
       ref data,
       ref attributes,
-    } => array_or_push_encode_len(data)? + attribute_encode_len(attributes)?,
+    } => array_or_push_encode_len(data, double_format)? + attribute_encode_len(attributes, double_format)?,
     BlobString {
       ref data,
       ref attributes,
-    } => blobstring_encode_len(data) + attribute_encode_len(attributes)?,
+    } => blobstring_encode_len(data) + attribute_encode_len(attributes, double_format)?,
     BlobError {
       ref data,
       ref attributes,
-    } => blobstring_encode_len(data) + attribute_encode_len(attributes)?,
+    } => blobstring_encode_len(data) + attribute_encode_len(attributes, double_format)?,
     SimpleString {
       ref data,
       ref attributes,
-    } => simplestring_encode_len(data) + attribute_encode_len(attributes)?,
+    } => simplestring_encode_len(data.as_bytes()) + attribute_encode_len(attributes, double_format)?,
     SimpleError {
       ref data,
       ref attributes,
-    } => simplestring_encode_len(data) + attribute_encode_len(attributes)?,
+    } => simplestring_encode_len(data.as_bytes()) + attribute_encode_len(attributes, double_format)?,
     Number {
       ref data,
       ref attributes,
-    } => number_encode_len(data) + attribute_encode_len(attributes)?,
+    } => number_encode_len(data) + attribute_encode_len(attributes, double_format)?,
     Double {
       ref data,
       ref attributes,
-    } => double_encode_len(data)? + attribute_encode_len(attributes)?,
+    } => double_encode_len(data, double_format)? + attribute_encode_len(attributes, double_format)?,
     Boolean {
       data: _,
       ref attributes,
-    } => BOOLEAN_ENCODE_LEN + attribute_encode_len(attributes)?,
+    } => BOOLEAN_ENCODE_LEN + attribute_encode_len(attributes, double_format)?,
     VerbatimString {
       ref data,
       ref attributes,
       ref format,
       ..
-    } => verbatimstring_encode_len(format, data) + attribute_encode_len(attributes)?,
+    } => verbatimstring_encode_len(format, data) + attribute_encode_len(attributes, double_format)?,
     Map {
       ref data,
       ref attributes,
-    } => map_encode_len(data)? + attribute_encode_len(attributes)?,
+    } => map_encode_len(data, double_format)? + attribute_encode_len(attributes, double_format)?,
     Set {
       ref data,
       ref attributes,
-    } => set_encode_len(data)? + attribute_encode_len(attributes)?,
+    } => set_encode_len(data, double_format)? + attribute_encode_len(attributes, double_format)?,
     BigNumber {
       ref data,
       ref attributes,
-    } => bignumber_encode_len(data) + attribute_encode_len(attributes)?,
+    } => bignumber_encode_len(data) + attribute_encode_len(attributes, double_format)?,
     Hello { ref version, ref auth } => hello_encode_len(version, auth),
     ChunkedString(ref data) => {
       if data.is_empty() {
@@ -242,24 +187,188 @@ pub fn encode_len(data: &Frame) -> Result<usize, GenError> {
         blobstring_encode_len(data)
       }
     }
-    Null => NULL.as_bytes().len(),
+    Null { ref attributes } => NULL.len() + attribute_encode_len(attributes, double_format)?,
   };
 
   Ok(total_len)
 }
 
-/// Return the string representation of a double, accounting for `inf` and `-inf`.
+/// Returns the number of bytes necessary to represent the frame, or `None` as soon as the length exceeds `max`.
+pub fn encode_len_within(data: &Frame, double_format: DoubleFormat, max: usize) -> Result<Option<usize>, GenError> {
+  let mut total = 0;
+
+  if add_encode_len_within(data, double_format, max, &mut total)? {
+    Ok(Some(total))
+  } else {
+    Ok(None)
+  }
+}
+
+/// Add the encoded length of the frame to `total`, returning `false` once `total` exceeds `max`.
+fn add_encode_len_within(
+  data: &Frame,
+  double_format: DoubleFormat,
+  max: usize,
+  total: &mut usize,
+) -> Result<bool, GenError> {
+  let header_len = core::length_prefix_encode_len;
+
+  match *data {
+    Frame::Array {
+      ref data,
+      ref attributes,
+    }
+    | Frame::Push {
+      ref data,
+      ref attributes,
+    } => {
+      *total += header_len(data.len()) + attribute_encode_len(attributes, double_format)?;
+      for frame in data.iter() {
+        if !add_encode_len_within(frame, double_format, max, total)? {
+          return Ok(false);
+        }
+      }
+    },
+    Frame::Set {
+      ref data,
+      ref attributes,
+    } => {
+      *total += header_len(data.len()) + attribute_encode_len(attributes, double_format)?;
+      for frame in data.iter() {
+        if !add_encode_len_within(frame, double_format, max, total)? {
+          return Ok(false);
+        }
+      }
+    },
+    Frame::Map {
+      ref data,
+      ref attributes,
+    } => {
+      *total += header_len(data.len()) + attribute_encode_len(attributes, double_format)?;
+      for (key, value) in data.iter() {
+        if !add_encode_len_within(key, double_format, max, total)?
+          || !add_encode_len_within(value, double_format, max, total)?
+        {
+          return Ok(false);
+        }
+      }
+    },
+    _ => *total += encode_len_with_format(data, double_format)?,
+  };
+
+  Ok(*total <= max)
+}
+
+/// Return the string representation of a double, accounting for `inf`, `-inf`, and `nan`.
 ///
-/// NaN is not checked here.
+/// This matches the formatting used by Redis (`d2string`): integral values in the range of a `long long` are written
+/// without a fractional part, and all other values are written with the shortest representation that parses back to
+/// the same value, switching to scientific notation for very large or very small values.
 pub fn f64_to_redis_string(data: &f64) -> Cow<'static, str> {
-  if data.is_infinite() {
+  if let Some(special) = special_f64_to_str(data) {
+    return Cow::Borrowed(special);
+  }
+  if *data == 0.0 {
+    return Cow::Borrowed(if data.is_sign_negative() { "-0" } else { "0" });
+  }
+  if data.fract() == 0.0 && data.abs() <= (i64::MAX / 2) as f64 {
+    return Cow::Owned((*data as i64).to_string());
+  }
+
+  // the shortest round trip digits and the decimal exponent of the first digit
+  let scientific = format!("{:e}", data.abs());
+  let (mantissa, exponent) = match scientific.split_once('e') {
+    Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().unwrap_or(0)),
+    None => (scientific.as_str(), 0),
+  };
+  let digits = mantissa.replace('.', "");
+
+  let mut out = String::with_capacity(24);
+  if data.is_sign_negative() {
+    out.push('-');
+  }
+  emit_digits(&mut out, &digits, exponent);
+  Cow::Owned(out)
+}
+
+/// Return the string representation of a double with at most `precision` significant digits, similar to
+/// `%.<precision>g` in C, accounting for `inf`, `-inf`, and `nan`.
+pub fn f64_to_precision_string(data: &f64, precision: usize) -> Cow<'static, str> {
+  if let Some(special) = special_f64_to_str(data) {
+    return Cow::Borrowed(special);
+  }
+  let precision = if precision == 0 { 1 } else { precision };
+
+  let scientific = format!("{:.*e}", precision - 1, data);
+  let (mantissa, exponent) = match scientific.split_once('e') {
+    Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().unwrap_or(0)),
+    None => (scientific.as_str(), 0),
+  };
+
+  if exponent < -4 || exponent >= precision as i32 {
+    let sign = if exponent < 0 { '-' } else { '+' };
+    Cow::Owned(format!("{}e{}{:02}", trim_fraction(mantissa), sign, exponent.abs()))
+  } else {
+    let decimals = (precision as i32 - 1 - exponent) as usize;
+    Cow::Owned(trim_fraction(&format!("{:.*}", decimals, data)).to_owned())
+  }
+}
+
+fn special_f64_to_str(data: &f64) -> Option<&'static str> {
+  if data.is_nan() {
+    Some(NAN)
+  } else if data.is_infinite() {
     if data.is_sign_negative() {
-      Cow::Borrowed(NEG_INFINITY)
+      Some(NEG_INFINITY)
+    } else {
+      Some(INFINITY)
+    }
+  } else {
+    None
+  }
+}
+
+/// Remove trailing zeros after the decimal point, and the decimal point itself if nothing follows it.
+fn trim_fraction(s: &str) -> &str {
+  if s.contains('.') {
+    s.trim_end_matches('0').trim_end_matches('.')
+  } else {
+    s
+  }
+}
+
+/// Write `digits`, where the first digit is scaled by `10^exponent`, using the same rules as `fpconv_dtoa`.
+fn emit_digits(out: &mut String, digits: &str, exponent: i32) {
+  let ndigits = digits.len() as i32;
+  // the exponent of the last digit
+  let k = exponent - (ndigits - 1);
+
+  if k >= 0 && exponent < ndigits + 7 {
+    // write plain integer
+    out.push_str(digits);
+    out.push_str(&"0".repeat(k as usize));
+  } else if k < 0 && (k > -7 || exponent.abs() < 4) {
+    // write decimal without an exponent
+    let offset = ndigits + k;
+    if offset <= 0 {
+      out.push_str("0.");
+      out.push_str(&"0".repeat(-offset as usize));
+      out.push_str(digits);
     } else {
-      Cow::Borrowed(INFINITY)
+      out.push_str(&digits[..offset as usize]);
+      out.push('.');
+      out.push_str(&digits[offset as usize..]);
     }
   } else {
-    Cow::Owned(data.to_string())
+    // write the digits in scientific notation
+    out.push_str(&digits[..1]);
+    if ndigits > 1 {
+      out.push('.');
+      out.push_str(&digits[1..]);
+    }
+    out.push('e');
+    out.push(if exponent < 0 { '-' } else { '+' });
+    out.push_str(&exponent.abs().to_string());
   }
 }
 
@@ -277,7 +386,7 @@ pub fn reconstruct_blobstring(
       _ => {
         return Err(RedisProtocolError::new(
           RedisProtocolErrorKind::DecodeError,
-          format!("Cannot create blob string from {:?}", frame.kind()),
+          format!("Cannot create blob string from {}", frame.kind()),
         ))
       }
     });
@@ -298,6 +407,7 @@ pub fn reconstruct_array(frames: VecDeque<Frame>, attributes: Option<Attributes>
 pub fn reconstruct_map(
   mut frames: VecDeque<Frame>,
   attributes: Option<Attributes>,
+  policy: DuplicateKeyPolicy,
 ) -> Result<Frame, RedisProtocolError> {
   if frames.is_empty() {
     return Ok(Frame::Map {
@@ -314,47 +424,109 @@ pub fn reconstruct_map(
   }
 
   let mut data = new_map(Some(frames.len() / 2));
-  while frames.len() > 0 {
-    let value = frames.pop_back().unwrap();
-    let key = match frames.pop_back() {
+  while let Some(key) = frames.pop_front() {
+    let value = match frames.pop_front() {
       Some(f) => f,
       None => {
         return Err(RedisProtocolError::new(
           RedisProtocolErrorKind::DecodeError,
-          "Missing map key.",
+          "Missing map value.",
         ))
       }
     };
 
-    if !key.can_hash() {
-      return Err(RedisProtocolError::new(
-        RedisProtocolErrorKind::DecodeError,
-        format!("{:?} cannot be used as hash key.", key.kind()),
-      ));
-    }
-
-    data.insert(key, value);
+    insert_map_pair(&mut data, key, value, policy)?;
   }
 
   Ok(Frame::Map { data, attributes })
 }
 
+/// Insert a key/value pair into a map, handling duplicate keys according to `policy`.
+pub fn insert_map_pair(
+  map: &mut FrameMap,
+  key: Frame,
+  value: Frame,
+  policy: DuplicateKeyPolicy,
+) -> Result<(), RedisProtocolError> {
+  match policy {
+    DuplicateKeyPolicy::Error => {
+      if map.contains_key(&key) {
+        return Err(RedisProtocolError::new(
+          RedisProtocolErrorKind::DecodeError,
+          format!("Duplicate map key: {:?}", key),
+        ));
+      }
+      map.insert(key, value);
+    }
+    DuplicateKeyPolicy::KeepFirst => {
+      if !map.contains_key(&key) {
+        map.insert(key, value);
+      }
+    }
+    DuplicateKeyPolicy::KeepLast => {
+      map.insert(key, value);
+    }
+    DuplicateKeyPolicy::Collect => {
+      if let Some(Frame::Array { ref mut data, .. }) = map.get_mut(&key) {
+        data.push(value);
+      } else {
+        map.insert(
+          key,
+          Frame::Array {
+            data: vec![value],
+            attributes: None,
+          },
+        );
+      }
+    }
+  }
+
+  Ok(())
+}
+
 pub fn reconstruct_set(frames: VecDeque<Frame>, attributes: Option<Attributes>) -> Result<Frame, RedisProtocolError> {
   let mut data = new_set(Some(frames.len()));
 
   for frame in frames.into_iter() {
-    if !frame.can_hash() {
-      return Err(RedisProtocolError::new(
-        RedisProtocolErrorKind::DecodeError,
-        format!("{:?} cannot be used as hash key.", frame.kind()),
-      ));
-    }
-
     data.insert(frame);
   }
   Ok(Frame::Set { data, attributes })
 }
 
+/// Read the key/value pairs from a map, or from an array with alternating keys and values.
+///
+/// Many commands respond with a map to RESP3 clients and a flat array to RESP2 clients.
+pub fn frame_to_pairs(frame: &Frame) -> Option<Vec<(&Frame, &Frame)>> {
+  match *frame {
+    Frame::Map { ref data, .. } => Some(data.iter().collect()),
+    Frame::Array { ref data, .. } => {
+      if data.len() % 2 != 0 {
+        return None;
+      }
+
+      Some(data.chunks(2).map(|pair| (&pair[0], &pair[1])).collect())
+    },
+    _ => None,
+  }
+}
+
+/// Read the value for `key` from the key/value pairs returned by [frame_to_pairs], ignoring case.
+pub fn pairs_get<'a>(pairs: &[(&Frame, &'a Frame)], key: &str) -> Option<&'a Frame> {
+  pairs
+    .iter()
+    .find(|(k, _)| k.as_str().map(|k| k.eq_ignore_ascii_case(key)).unwrap_or(false))
+    .map(|(_, v)| *v)
+}
+
+/// Read the inner frames of an array or set as a list of strings.
+pub fn frame_to_strings(frame: &Frame) -> Option<Vec<String>> {
+  match *frame {
+    Frame::Array { ref data, .. } => data.iter().map(|f| f.to_string()).collect(),
+    Frame::Set { ref data, .. } => data.iter().map(|f| f.to_string()).collect(),
+    _ => None,
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use crate::resp3::types::*;
@@ -378,9 +550,15 @@ mod tests {
   #[test]
   fn should_reconstruct_blobstring() {
     let mut streamed_frame = StreamedFrame::new(FrameKind::BlobString);
-    streamed_frame.add_frame(Frame::ChunkedString("foo".as_bytes().to_vec()));
-    streamed_frame.add_frame(Frame::ChunkedString("bar".as_bytes().to_vec()));
-    streamed_frame.add_frame(Frame::ChunkedString("baz".as_bytes().to_vec()));
+    streamed_frame
+      .add_frame(Frame::ChunkedString("foo".as_bytes().to_vec()))
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::ChunkedString("bar".as_bytes().to_vec()))
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::ChunkedString("baz".as_bytes().to_vec()))
+      .unwrap();
 
     let expected = Frame::BlobString {
       data: "foobarbaz".as_bytes().to_vec(),
@@ -389,9 +567,15 @@ mod tests {
     assert_eq!(streamed_frame.into_frame().unwrap(), expected);
 
     let mut streamed_frame = StreamedFrame::new(FrameKind::BlobString);
-    streamed_frame.add_frame(Frame::ChunkedString("foo".as_bytes().to_vec()));
-    streamed_frame.add_frame(Frame::ChunkedString("bar".as_bytes().to_vec()));
-    streamed_frame.add_frame(Frame::ChunkedString("baz".as_bytes().to_vec()));
+    streamed_frame
+      .add_frame(Frame::ChunkedString("foo".as_bytes().to_vec()))
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::ChunkedString("bar".as_bytes().to_vec()))
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::ChunkedString("baz".as_bytes().to_vec()))
+      .unwrap();
     let (attributes, _) = create_attributes();
     streamed_frame.attributes = Some(attributes.clone());
 
@@ -405,18 +589,24 @@ mod tests {
   #[test]
   fn should_reconstruct_array() {
     let mut streamed_frame = StreamedFrame::new(FrameKind::Array);
-    streamed_frame.add_frame(Frame::SimpleString {
-      data: "foo".into(),
-      attributes: None,
-    });
-    streamed_frame.add_frame(Frame::Number {
-      data: 42,
-      attributes: None,
-    });
-    streamed_frame.add_frame(Frame::Boolean {
-      data: true,
-      attributes: None,
-    });
+    streamed_frame
+      .add_frame(Frame::SimpleString {
+        data: "foo".into(),
+        attributes: None,
+      })
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::Number {
+        data: 42,
+        attributes: None,
+      })
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::Boolean {
+        data: true,
+        attributes: None,
+      })
+      .unwrap();
 
     let expected = Frame::Array {
       data: vec![
@@ -439,18 +629,24 @@ mod tests {
 
     let (attributes, _) = create_attributes();
     let mut streamed_frame = StreamedFrame::new(FrameKind::Array);
-    streamed_frame.add_frame(Frame::SimpleString {
-      data: "foo".into(),
-      attributes: None,
-    });
-    streamed_frame.add_frame(Frame::Number {
-      data: 42,
-      attributes: Some(attributes.clone()),
-    });
-    streamed_frame.add_frame(Frame::Boolean {
-      data: true,
-      attributes: None,
-    });
+    streamed_frame
+      .add_frame(Frame::SimpleString {
+        data: "foo".into(),
+        attributes: None,
+      })
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::Number {
+        data: 42,
+        attributes: Some(attributes.clone()),
+      })
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::Boolean {
+        data: true,
+        attributes: None,
+      })
+      .unwrap();
     streamed_frame.attributes = Some(attributes.clone());
 
     let expected = Frame::Array {
@@ -493,10 +689,10 @@ mod tests {
     };
 
     let mut streamed_frame = StreamedFrame::new(FrameKind::Map);
-    streamed_frame.add_frame(k1.clone());
-    streamed_frame.add_frame(v1.clone());
-    streamed_frame.add_frame(k2.clone());
-    streamed_frame.add_frame(v2.clone());
+    streamed_frame.add_frame(k1.clone()).unwrap();
+    streamed_frame.add_frame(v1.clone()).unwrap();
+    streamed_frame.add_frame(k2.clone()).unwrap();
+    streamed_frame.add_frame(v2.clone()).unwrap();
 
     let mut expected = new_map(None);
     expected.insert(k1.clone(), v1.clone());
@@ -511,10 +707,10 @@ mod tests {
     let _ = k1.add_attributes(attributes.clone()).unwrap();
 
     let mut streamed_frame = StreamedFrame::new(FrameKind::Map);
-    streamed_frame.add_frame(k1.clone());
-    streamed_frame.add_frame(v1.clone());
-    streamed_frame.add_frame(k2.clone());
-    streamed_frame.add_frame(v2.clone());
+    streamed_frame.add_frame(k1.clone()).unwrap();
+    streamed_frame.add_frame(v1.clone()).unwrap();
+    streamed_frame.add_frame(k2.clone()).unwrap();
+    streamed_frame.add_frame(v2.clone()).unwrap();
     streamed_frame.attributes = Some(attributes.clone());
 
     let mut expected = new_map(None);
@@ -544,13 +740,35 @@ mod tests {
     };
 
     let mut streamed_frame = StreamedFrame::new(FrameKind::Map);
-    streamed_frame.add_frame(k1.clone());
-    streamed_frame.add_frame(v1.clone());
-    streamed_frame.add_frame(k2.clone());
+    streamed_frame.add_frame(k1.clone()).unwrap();
+    streamed_frame.add_frame(v1.clone()).unwrap();
+    streamed_frame.add_frame(k2.clone()).unwrap();
 
     let _ = streamed_frame.into_frame().unwrap();
   }
 
+  #[test]
+  fn should_reconstruct_map_with_aggregate_keys() {
+    let key = Frame::Array {
+      data: vec![1.into(), 2.into()],
+      attributes: None,
+    };
+
+    let mut streamed_frame = StreamedFrame::new(FrameKind::Map);
+    streamed_frame.add_frame(key.clone()).unwrap();
+    streamed_frame.add_frame(Frame::Null { attributes: None }).unwrap();
+
+    let mut expected = new_map(None);
+    expected.insert(key, Frame::Null { attributes: None });
+    assert_eq!(
+      streamed_frame.into_frame().unwrap(),
+      Frame::Map {
+        data: expected,
+        attributes: None,
+      }
+    );
+  }
+
   #[test]
   fn should_reconstruct_set() {
     let mut v1 = Frame::SimpleString {
@@ -571,10 +789,10 @@ mod tests {
     };
 
     let mut streamed_frame = StreamedFrame::new(FrameKind::Set);
-    streamed_frame.add_frame(v1.clone());
-    streamed_frame.add_frame(v2.clone());
-    streamed_frame.add_frame(v3.clone());
-    streamed_frame.add_frame(v4.clone());
+    streamed_frame.add_frame(v1.clone()).unwrap();
+    streamed_frame.add_frame(v2.clone()).unwrap();
+    streamed_frame.add_frame(v3.clone()).unwrap();
+    streamed_frame.add_frame(v4.clone()).unwrap();
 
     let mut expected = new_set(None);
     expected.insert(v1.clone());
@@ -591,10 +809,10 @@ mod tests {
     let _ = v1.add_attributes(attributes.clone()).unwrap();
 
     let mut streamed_frame = StreamedFrame::new(FrameKind::Set);
-    streamed_frame.add_frame(v1.clone());
-    streamed_frame.add_frame(v2.clone());
-    streamed_frame.add_frame(v3.clone());
-    streamed_frame.add_frame(v4.clone());
+    streamed_frame.add_frame(v1.clone()).unwrap();
+    streamed_frame.add_frame(v2.clone()).unwrap();
+    streamed_frame.add_frame(v3.clone()).unwrap();
+    streamed_frame.add_frame(v4.clone()).unwrap();
     streamed_frame.attributes = Some(attributes.clone());
 
     let mut expected = new_set(None);
@@ -612,28 +830,34 @@ mod tests {
   #[test]
   fn should_reconstruct_nested_array() {
     let mut streamed_frame = StreamedFrame::new(FrameKind::Array);
-    streamed_frame.add_frame(Frame::SimpleString {
-      data: "foo".into(),
-      attributes: None,
-    });
-    streamed_frame.add_frame(Frame::Array {
-      data: vec![
-        Frame::SimpleString {
-          data: "foo".into(),
-          attributes: None,
-        },
-        Frame::Null,
-        Frame::BigNumber {
-          data: "123456789".as_bytes().to_vec(),
-          attributes: None,
-        },
-      ],
-      attributes: None,
-    });
-    streamed_frame.add_frame(Frame::Boolean {
-      data: true,
-      attributes: None,
-    });
+    streamed_frame
+      .add_frame(Frame::SimpleString {
+        data: "foo".into(),
+        attributes: None,
+      })
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::Array {
+        data: vec![
+          Frame::SimpleString {
+            data: "foo".into(),
+            attributes: None,
+          },
+          Frame::Null { attributes: None },
+          Frame::BigNumber {
+            data: "123456789".as_bytes().to_vec(),
+            attributes: None,
+          },
+        ],
+        attributes: None,
+      })
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::Boolean {
+        data: true,
+        attributes: None,
+      })
+      .unwrap();
 
     let expected = Frame::Array {
       data: vec![
@@ -647,7 +871,7 @@ mod tests {
               data: "foo".into(),
               attributes: None,
             },
-            Frame::Null,
+            Frame::Null { attributes: None },
             Frame::BigNumber {
               data: "123456789".as_bytes().to_vec(),
               attributes: None,
@@ -666,28 +890,34 @@ mod tests {
 
     let (attributes, _) = create_attributes();
     let mut streamed_frame = StreamedFrame::new(FrameKind::Array);
-    streamed_frame.add_frame(Frame::SimpleString {
-      data: "foo".into(),
-      attributes: None,
-    });
-    streamed_frame.add_frame(Frame::Array {
-      data: vec![
-        Frame::SimpleString {
-          data: "foo".into(),
-          attributes: None,
-        },
-        Frame::Null,
-        Frame::BigNumber {
-          data: "123456789".as_bytes().to_vec(),
-          attributes: Some(attributes.clone()),
-        },
-      ],
-      attributes: None,
-    });
-    streamed_frame.add_frame(Frame::Boolean {
-      data: true,
-      attributes: Some(attributes.clone()),
-    });
+    streamed_frame
+      .add_frame(Frame::SimpleString {
+        data: "foo".into(),
+        attributes: None,
+      })
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::Array {
+        data: vec![
+          Frame::SimpleString {
+            data: "foo".into(),
+            attributes: None,
+          },
+          Frame::Null { attributes: None },
+          Frame::BigNumber {
+            data: "123456789".as_bytes().to_vec(),
+            attributes: Some(attributes.clone()),
+          },
+        ],
+        attributes: None,
+      })
+      .unwrap();
+    streamed_frame
+      .add_frame(Frame::Boolean {
+        data: true,
+        attributes: Some(attributes.clone()),
+      })
+      .unwrap();
     streamed_frame.attributes = Some(attributes.clone());
 
     let expected = Frame::Array {
@@ -702,7 +932,7 @@ mod tests {
               data: "foo".into(),
               attributes: None,
             },
-            Frame::Null,
+            Frame::Null { attributes: None },
             Frame::BigNumber {
               data: "123456789".as_bytes().to_vec(),
               attributes: Some(attributes.clone()),
@@ -750,10 +980,10 @@ mod tests {
     };
 
     let mut streamed_frame = StreamedFrame::new(FrameKind::Map);
-    streamed_frame.add_frame(k1.clone());
-    streamed_frame.add_frame(v1.clone());
-    streamed_frame.add_frame(k2.clone());
-    streamed_frame.add_frame(v2.clone());
+    streamed_frame.add_frame(k1.clone()).unwrap();
+    streamed_frame.add_frame(v1.clone()).unwrap();
+    streamed_frame.add_frame(k2.clone()).unwrap();
+    streamed_frame.add_frame(v2.clone()).unwrap();
 
     let mut expected = new_map(None);
     expected.insert(k1.clone(), v1.clone());
@@ -775,10 +1005,10 @@ mod tests {
     };
 
     let mut streamed_frame = StreamedFrame::new(FrameKind::Map);
-    streamed_frame.add_frame(k1.clone());
-    streamed_frame.add_frame(v1.clone());
-    streamed_frame.add_frame(k2.clone());
-    streamed_frame.add_frame(v2.clone());
+    streamed_frame.add_frame(k1.clone()).unwrap();
+    streamed_frame.add_frame(v1.clone()).unwrap();
+    streamed_frame.add_frame(k2.clone()).unwrap();
+    streamed_frame.add_frame(v2.clone()).unwrap();
     streamed_frame.attributes = Some(attributes.clone());
 
     let mut expected = new_map(None);
@@ -941,7 +1171,7 @@ mod tests {
 
   #[test]
   fn should_get_encode_len_null() {
-    let frame = Frame::Null;
+    let frame = Frame::Null { attributes: None };
     let expected_len = 3;
     assert_eq!(encode_len(&frame).unwrap(), expected_len);
   }
@@ -0,0 +1,286 @@
+//! Types and functions for parsing sentinel responses.
+//!
+//! <https://redis.io/topics/sentinel>
+
+use crate::resp3::types::Frame;
+use crate::resp3::utils::frame_to_pairs;
+use crate::types::RedisProtocolError;
+use crate::utils;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// A primary node monitored by a sentinel, as returned by `SENTINEL masters` or `SENTINEL master`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SentinelMaster {
+  pub name: String,
+  pub ip: String,
+  pub port: u16,
+  pub run_id: Option<String>,
+  /// The node flags, such as `master`, `s_down`, `o_down`, or `disconnected`.
+  pub flags: Vec<String>,
+  pub num_replicas: u64,
+  pub num_other_sentinels: u64,
+  pub quorum: u64,
+  pub config_epoch: Option<u64>,
+  pub down_after_milliseconds: Option<u64>,
+  pub failover_timeout: Option<u64>,
+  pub parallel_syncs: Option<u64>,
+  /// Every key/value pair in the response, in the order they were received.
+  pub fields: Vec<(String, String)>,
+}
+
+/// A replica of a monitored primary node, as returned by `SENTINEL replicas`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SentinelReplica {
+  pub name: String,
+  pub ip: String,
+  pub port: u16,
+  pub run_id: Option<String>,
+  /// The node flags, such as `slave`, `s_down`, or `disconnected`.
+  pub flags: Vec<String>,
+  /// The status of the replication link, either `ok` or `err`.
+  pub master_link_status: Option<String>,
+  pub master_host: Option<String>,
+  pub master_port: Option<u16>,
+  pub replica_priority: Option<i64>,
+  pub replica_repl_offset: Option<i64>,
+  /// Every key/value pair in the response, in the order they were received.
+  pub fields: Vec<(String, String)>,
+}
+
+macro_rules! impl_sentinel_node {
+  ($t:ty) => {
+    impl $t {
+      /// Read the value for `key`.
+      pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+      }
+
+      /// Whether or not the node has the provided flag.
+      pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+      }
+
+      /// Whether or not the node is subjectively or objectively down.
+      pub fn is_down(&self) -> bool {
+        self.has_flag("s_down") || self.has_flag("o_down")
+      }
+
+      /// Whether or not the sentinel is disconnected from the node.
+      pub fn is_disconnected(&self) -> bool {
+        self.has_flag("disconnected")
+      }
+    }
+  };
+}
+
+impl_sentinel_node!(SentinelMaster);
+impl_sentinel_node!(SentinelReplica);
+
+fn read_fields(frame: &Frame) -> Result<Vec<(String, String)>, RedisProtocolError> {
+  frame_to_pairs(frame)
+    .ok_or_else(|| utils::invalid_response("sentinel response", "Expected map."))?
+    .into_iter()
+    .map(|(key, value)| match (key.to_string(), value.to_string()) {
+      (Some(key), Some(value)) => Ok((key, value)),
+      _ => Err(utils::invalid_response("sentinel response", "Invalid field.")),
+    })
+    .collect()
+}
+
+fn get<'a>(fields: &'a [(String, String)], key: &str) -> Option<&'a str> {
+  fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+}
+
+fn get_parsed<T: FromStr>(fields: &[(String, String)], key: &str) -> Result<Option<T>, RedisProtocolError> {
+  match get(fields, key) {
+    Some(value) => value
+      .parse()
+      .map(Some)
+      .map_err(|_| utils::invalid_response("sentinel response", format!("Invalid {}.", key))),
+    None => Ok(None),
+  }
+}
+
+fn get_required<'a>(fields: &'a [(String, String)], key: &str) -> Result<&'a str, RedisProtocolError> {
+  get(fields, key).ok_or_else(|| utils::invalid_response("sentinel response", format!("Missing {}.", key)))
+}
+
+fn read_flags(fields: &[(String, String)]) -> Vec<String> {
+  get(fields, "flags")
+    .map(|flags| flags.split(',').filter(|f| !f.is_empty()).map(|f| f.to_owned()).collect())
+    .unwrap_or_default()
+}
+
+impl TryFrom<&Frame> for SentinelMaster {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let fields = read_fields(frame)?;
+
+    Ok(SentinelMaster {
+      name: get_required(&fields, "name")?.to_owned(),
+      ip: get_required(&fields, "ip")?.to_owned(),
+      port: get_parsed(&fields, "port")?
+        .ok_or_else(|| utils::invalid_response("sentinel response", "Missing port."))?,
+      run_id: get(&fields, "runid").map(|s| s.to_owned()),
+      flags: read_flags(&fields),
+      num_replicas: get_parsed(&fields, "num-slaves")?.unwrap_or(0),
+      num_other_sentinels: get_parsed(&fields, "num-other-sentinels")?.unwrap_or(0),
+      quorum: get_parsed(&fields, "quorum")?.unwrap_or(0),
+      config_epoch: get_parsed(&fields, "config-epoch")?,
+      down_after_milliseconds: get_parsed(&fields, "down-after-milliseconds")?,
+      failover_timeout: get_parsed(&fields, "failover-timeout")?,
+      parallel_syncs: get_parsed(&fields, "parallel-syncs")?,
+      fields,
+    })
+  }
+}
+
+impl TryFrom<&Frame> for SentinelReplica {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let fields = read_fields(frame)?;
+
+    Ok(SentinelReplica {
+      name: get_required(&fields, "name")?.to_owned(),
+      ip: get_required(&fields, "ip")?.to_owned(),
+      port: get_parsed(&fields, "port")?
+        .ok_or_else(|| utils::invalid_response("sentinel response", "Missing port."))?,
+      run_id: get(&fields, "runid").filter(|s| !s.is_empty()).map(|s| s.to_owned()),
+      flags: read_flags(&fields),
+      master_link_status: get(&fields, "master-link-status").map(|s| s.to_owned()),
+      master_host: get(&fields, "master-host").map(|s| s.to_owned()),
+      master_port: get_parsed(&fields, "master-port")?,
+      replica_priority: get_parsed(&fields, "slave-priority")?,
+      replica_repl_offset: get_parsed(&fields, "slave-repl-offset")?,
+      fields,
+    })
+  }
+}
+
+fn parse_array<'a, T>(frame: &'a Frame) -> Result<Vec<T>, RedisProtocolError>
+where
+  T: TryFrom<&'a Frame, Error = RedisProtocolError>,
+{
+  match *frame {
+    Frame::Array { ref data, .. } => data.iter().map(T::try_from).collect(),
+    _ => Err(utils::invalid_response("sentinel response", "Expected array.")),
+  }
+}
+
+/// Parse the response to `SENTINEL masters`.
+pub fn parse_sentinel_masters(frame: &Frame) -> Result<Vec<SentinelMaster>, RedisProtocolError> {
+  parse_array(frame)
+}
+
+/// Parse the response to `SENTINEL replicas` or `SENTINEL slaves`.
+pub fn parse_sentinel_replicas(frame: &Frame) -> Result<Vec<SentinelReplica>, RedisProtocolError> {
+  parse_array(frame)
+}
+
+/// Parse the response to `SENTINEL get-master-addr-by-name`, returning `None` if the primary is not known.
+pub fn parse_master_addr(frame: &Frame) -> Result<Option<(String, u16)>, RedisProtocolError> {
+  match *frame {
+    Frame::Null { .. } => Ok(None),
+    Frame::Array { ref data, .. } if data.len() == 2 => {
+      let host = data[0]
+        .to_string()
+        .ok_or_else(|| utils::invalid_response("sentinel response", "Invalid host."))?;
+      let port = data[1]
+        .as_i64()
+        .filter(|p| *p >= 0 && *p <= u16::MAX as i64)
+        .ok_or_else(|| utils::invalid_response("sentinel response", "Invalid port."))?;
+
+      Ok(Some((host, port as u16)))
+    },
+    _ => Err(utils::invalid_response(
+      "sentinel response",
+      "Expected array with 2 elements.",
+    )),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::test_frames::*;
+
+  fn flat(pairs: &[(&str, &str)]) -> Frame {
+    array(pairs.iter().flat_map(|(k, v)| vec![blob(k), blob(v)]).collect())
+  }
+
+  #[test]
+  fn should_parse_sentinel_masters() {
+    let frame = array(vec![flat(&[
+      ("name", "mymaster"),
+      ("ip", "127.0.0.1"),
+      ("port", "6379"),
+      ("runid", "953ae6a589449c13ddefaee3538d356d287f509b"),
+      ("flags", "master,disconnected"),
+      ("link-pending-commands", "0"),
+      ("config-epoch", "0"),
+      ("num-slaves", "2"),
+      ("num-other-sentinels", "2"),
+      ("quorum", "2"),
+      ("failover-timeout", "180000"),
+      ("parallel-syncs", "1"),
+    ])]);
+    let masters = parse_sentinel_masters(&frame).unwrap();
+
+    assert_eq!(masters.len(), 1);
+    let master = &masters[0];
+    assert_eq!(master.name, "mymaster");
+    assert_eq!((master.ip.as_str(), master.port), ("127.0.0.1", 6379));
+    assert_eq!(master.flags, vec!["master", "disconnected"]);
+    assert!(master.is_disconnected());
+    assert!(!master.is_down());
+    assert_eq!(master.num_replicas, 2);
+    assert_eq!(master.quorum, 2);
+    assert_eq!(master.failover_timeout, Some(180000));
+    assert_eq!(master.down_after_milliseconds, None);
+    assert_eq!(master.get("link-pending-commands"), Some("0"));
+  }
+
+  #[test]
+  fn should_parse_sentinel_replicas_map() {
+    let fields = [
+      ("name", "127.0.0.1:6380"),
+      ("ip", "127.0.0.1"),
+      ("port", "6380"),
+      ("runid", ""),
+      ("flags", "s_down,slave"),
+      ("master-link-status", "err"),
+      ("master-host", "127.0.0.1"),
+      ("master-port", "6379"),
+      ("slave-priority", "100"),
+      ("slave-repl-offset", "0"),
+    ];
+    let frame = array(vec![map(fields.iter().map(|(k, v)| (*k, blob(v))).collect())]);
+    let replicas = parse_sentinel_replicas(&frame).unwrap();
+
+    assert_eq!(replicas[0].port, 6380);
+    assert_eq!(replicas[0].run_id, None);
+    assert!(replicas[0].is_down());
+    assert_eq!(replicas[0].master_link_status.as_deref(), Some("err"));
+    assert_eq!(replicas[0].master_port, Some(6379));
+    assert_eq!(replicas[0].replica_priority, Some(100));
+  }
+
+  #[test]
+  fn should_parse_master_addr() {
+    assert_eq!(
+      parse_master_addr(&array(vec![blob("10.0.0.1"), blob("6379")])).unwrap(),
+      Some(("10.0.0.1".to_owned(), 6379))
+    );
+    assert_eq!(parse_master_addr(&Frame::Null { attributes: None }).unwrap(), None);
+    assert!(parse_master_addr(&array(vec![blob("10.0.0.1"), blob("abc")])).is_err());
+  }
+
+  #[test]
+  fn should_error_on_invalid_sentinel_node() {
+    assert!(parse_sentinel_masters(&array(vec![flat(&[("name", "mymaster")])])).is_err());
+    assert!(parse_sentinel_masters(&array(vec![flat(&[("name", "a"), ("ip", "b"), ("port", "c")])])).is_err());
+  }
+}
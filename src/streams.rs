@@ -0,0 +1,629 @@
+//! Types and functions for working with stream IDs and parsing stream responses.
+//!
+//! <https://redis.io/topics/streams-intro>
+
+use crate::resp3::types::Frame;
+use crate::resp3::utils::{frame_to_pairs, pairs_get};
+use crate::types::RedisProtocolError;
+use crate::utils;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::{self, FromStr};
+
+/// A stream entry ID, such as `1526919030474-55`.
+///
+/// IDs are ordered by their millisecond timestamp and then their sequence number.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct XId {
+  /// The millisecond timestamp.
+  pub ms: u64,
+  /// The sequence number within the millisecond.
+  pub seq: u64,
+}
+
+impl XId {
+  /// The smallest possible ID, equivalent to `-`.
+  pub const MIN: XId = XId { ms: 0, seq: 0 };
+  /// The largest possible ID, equivalent to `+`.
+  pub const MAX: XId = XId {
+    ms: u64::MAX,
+    seq: u64::MAX,
+  };
+
+  /// Create a new ID.
+  pub fn new(ms: u64, seq: u64) -> Self {
+    XId { ms, seq }
+  }
+
+  /// Read the next ID, or `None` if this is the largest possible ID.
+  ///
+  /// This is typically used to build exclusive ranges with older servers that do not support the `(` prefix.
+  pub fn increment(&self) -> Option<XId> {
+    if self.seq < u64::MAX {
+      Some(XId::new(self.ms, self.seq + 1))
+    } else if self.ms < u64::MAX {
+      Some(XId::new(self.ms + 1, 0))
+    } else {
+      None
+    }
+  }
+
+  /// Read the previous ID, or `None` if this is the smallest possible ID.
+  pub fn decrement(&self) -> Option<XId> {
+    if self.seq > 0 {
+      Some(XId::new(self.ms, self.seq - 1))
+    } else if self.ms > 0 {
+      Some(XId::new(self.ms - 1, u64::MAX))
+    } else {
+      None
+    }
+  }
+}
+
+impl fmt::Display for XId {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}-{}", self.ms, self.seq)
+  }
+}
+
+impl FromStr for XId {
+  type Err = RedisProtocolError;
+
+  /// Parse an ID in the `ms-seq` or `ms` form. The special `-` and `+` IDs are parsed as [MIN](Self::MIN) and
+  /// [MAX](Self::MAX).
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "-" => return Ok(XId::MIN),
+      "+" => return Ok(XId::MAX),
+      _ => {},
+    };
+
+    let (ms, seq) = match s.find('-') {
+      Some(idx) => (&s[0..idx], Some(&s[idx + 1..])),
+      None => (s, None),
+    };
+    let ms = ms
+      .parse::<u64>()
+      .map_err(|_| utils::invalid_response("stream response", "Invalid stream ID."))?;
+    let seq = match seq {
+      Some(seq) => seq
+        .parse::<u64>()
+        .map_err(|_| utils::invalid_response("stream response", "Invalid stream ID."))?,
+      None => 0,
+    };
+
+    Ok(XId { ms, seq })
+  }
+}
+
+impl TryFrom<&Frame> for XId {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    frame
+      .as_str()
+      .ok_or_else(|| utils::invalid_response("stream response", "Expected string stream ID."))?
+      .parse()
+  }
+}
+
+/// Pairs of stream keys and entries returned by `XREAD` or `XREADGROUP`.
+pub type XReadResponse = Vec<(Vec<u8>, Vec<StreamEntry>)>;
+
+/// An entry in a stream.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamEntry {
+  pub id: XId,
+  /// The field/value pairs in the entry, in the order they were received.
+  ///
+  /// This is empty for entries that were deleted while pending in a consumer group.
+  pub fields: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl StreamEntry {
+  /// Read the value for `field`.
+  pub fn get(&self, field: &[u8]) -> Option<&[u8]> {
+    self.fields.iter().find(|(k, _)| k == field).map(|(_, v)| v.as_slice())
+  }
+}
+
+impl TryFrom<&Frame> for StreamEntry {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let data = match *frame {
+      Frame::Array { ref data, .. } if data.len() == 2 => data,
+      _ => {
+        return Err(utils::invalid_response(
+          "stream response",
+          "Expected array with 2 elements.",
+        ))
+      },
+    };
+    let id = XId::try_from(&data[0])?;
+    let fields = match data[1] {
+      Frame::Null { .. } => Vec::new(),
+      Frame::Array { ref data, .. } => {
+        if data.len() % 2 != 0 {
+          return Err(utils::invalid_response(
+            "stream response",
+            "Expected an even number of fields.",
+          ));
+        }
+
+        data
+          .chunks(2)
+          .map(|pair| match (pair[0].as_bytes(), pair[1].as_bytes()) {
+            (Some(field), Some(value)) => Ok((field.to_vec(), value.to_vec())),
+            _ => Err(utils::invalid_response("stream response", "Invalid stream field.")),
+          })
+          .collect::<Result<_, _>>()?
+      },
+      _ => return Err(utils::invalid_response("stream response", "Invalid stream fields.")),
+    };
+
+    Ok(StreamEntry { id, fields })
+  }
+}
+
+/// Parse a list of stream entries, such as the response to `XRANGE`, `XREVRANGE`, or `XCLAIM`.
+pub fn parse_stream_entries(frame: &Frame) -> Result<Vec<StreamEntry>, RedisProtocolError> {
+  match *frame {
+    Frame::Array { ref data, .. } => data.iter().map(StreamEntry::try_from).collect(),
+    Frame::Null { .. } => Ok(Vec::new()),
+    _ => Err(utils::invalid_response("stream response", "Expected array.")),
+  }
+}
+
+/// Parse the response to `XREAD` or `XREADGROUP` into pairs of stream keys and entries.
+///
+/// Both the RESP2 array and RESP3 map forms are supported. A null response, returned when the command times out, is
+/// parsed as an empty list.
+pub fn parse_xread(frame: &Frame) -> Result<XReadResponse, RedisProtocolError> {
+  let pairs = match *frame {
+    Frame::Null { .. } => return Ok(Vec::new()),
+    Frame::Map { .. } => {
+      frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("stream response", "Invalid map."))?
+    },
+    Frame::Array { ref data, .. } => data
+      .iter()
+      .map(|stream| match *stream {
+        Frame::Array { ref data, .. } if data.len() == 2 => Ok((&data[0], &data[1])),
+        _ => Err(utils::invalid_response(
+          "stream response",
+          "Expected array with 2 elements.",
+        )),
+      })
+      .collect::<Result<_, _>>()?,
+    _ => return Err(utils::invalid_response("stream response", "Expected array or map.")),
+  };
+
+  pairs
+    .into_iter()
+    .map(|(key, entries)| {
+      let key = key
+        .as_bytes()
+        .ok_or_else(|| utils::invalid_response("stream response", "Invalid stream key."))?;
+      Ok((key.to_vec(), parse_stream_entries(entries)?))
+    })
+    .collect()
+}
+
+/// The response to `XINFO STREAM`, without the `FULL` modifier.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamInfo {
+  pub length: u64,
+  pub radix_tree_keys: u64,
+  pub radix_tree_nodes: u64,
+  pub groups: u64,
+  pub last_generated_id: XId,
+  /// Added in Redis 7.0.
+  pub max_deleted_entry_id: Option<XId>,
+  /// Added in Redis 7.0.
+  pub entries_added: Option<u64>,
+  pub first_entry: Option<StreamEntry>,
+  pub last_entry: Option<StreamEntry>,
+}
+
+/// A consumer group, as returned by `XINFO GROUPS`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamGroupInfo {
+  pub name: String,
+  pub consumers: u64,
+  pub pending: u64,
+  pub last_delivered_id: XId,
+  /// Added in Redis 7.0. This is `None` when the server cannot determine the value.
+  pub entries_read: Option<u64>,
+  /// Added in Redis 7.0. This is `None` when the server cannot determine the value.
+  pub lag: Option<u64>,
+}
+
+/// A consumer in a group, as returned by `XINFO CONSUMERS`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamConsumerInfo {
+  pub name: String,
+  pub pending: u64,
+  /// The number of milliseconds since the consumer last interacted with the server.
+  pub idle: u64,
+  /// The number of milliseconds since the consumer last read or claimed an entry. Added in Redis 7.2.
+  pub inactive: Option<i64>,
+}
+
+/// The response to `XPENDING` without the range arguments.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PendingSummary {
+  /// The total number of pending entries in the group.
+  pub count: u64,
+  pub min_id: Option<XId>,
+  pub max_id: Option<XId>,
+  /// Pairs of consumer names and the number of pending entries for each consumer.
+  pub consumers: Vec<(String, u64)>,
+}
+
+/// A pending entry, as returned by `XPENDING` with the range arguments.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingEntry {
+  pub id: XId,
+  pub consumer: String,
+  /// The number of milliseconds since the entry was last delivered.
+  pub idle: u64,
+  pub delivery_count: u64,
+}
+
+fn read_u64(frame: Option<&Frame>, field: &str) -> Result<u64, RedisProtocolError> {
+  frame
+    .and_then(|f| f.as_i64())
+    .filter(|i| *i >= 0)
+    .map(|i| i as u64)
+    .ok_or_else(|| utils::invalid_response("stream response", format!("Invalid {}.", field)))
+}
+
+fn read_optional_u64(frame: Option<&Frame>) -> Option<u64> {
+  frame.and_then(|f| f.as_i64()).filter(|i| *i >= 0).map(|i| i as u64)
+}
+
+fn read_string(frame: Option<&Frame>, field: &str) -> Result<String, RedisProtocolError> {
+  frame
+    .and_then(|f| f.to_string())
+    .ok_or_else(|| utils::invalid_response("stream response", format!("Invalid {}.", field)))
+}
+
+fn read_id(frame: Option<&Frame>, field: &str) -> Result<XId, RedisProtocolError> {
+  match frame {
+    Some(frame) => XId::try_from(frame),
+    None => Err(utils::invalid_response(
+      "stream response",
+      format!("Missing {}.", field),
+    )),
+  }
+}
+
+fn read_optional_id(frame: Option<&Frame>) -> Result<Option<XId>, RedisProtocolError> {
+  match frame {
+    Some(Frame::Null { .. }) | None => Ok(None),
+    Some(frame) => XId::try_from(frame).map(Some),
+  }
+}
+
+fn read_optional_entry(frame: Option<&Frame>) -> Result<Option<StreamEntry>, RedisProtocolError> {
+  match frame {
+    Some(Frame::Null { .. }) | None => Ok(None),
+    Some(frame) => StreamEntry::try_from(frame).map(Some),
+  }
+}
+
+impl TryFrom<&Frame> for StreamInfo {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let pairs = frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("stream response", "Expected map."))?;
+
+    Ok(StreamInfo {
+      length: read_u64(pairs_get(&pairs, "length"), "length")?,
+      radix_tree_keys: read_u64(pairs_get(&pairs, "radix-tree-keys"), "radix-tree-keys")?,
+      radix_tree_nodes: read_u64(pairs_get(&pairs, "radix-tree-nodes"), "radix-tree-nodes")?,
+      groups: read_u64(pairs_get(&pairs, "groups"), "groups")?,
+      last_generated_id: read_id(pairs_get(&pairs, "last-generated-id"), "last-generated-id")?,
+      max_deleted_entry_id: read_optional_id(pairs_get(&pairs, "max-deleted-entry-id"))?,
+      entries_added: read_optional_u64(pairs_get(&pairs, "entries-added")),
+      first_entry: read_optional_entry(pairs_get(&pairs, "first-entry"))?,
+      last_entry: read_optional_entry(pairs_get(&pairs, "last-entry"))?,
+    })
+  }
+}
+
+impl TryFrom<&Frame> for StreamGroupInfo {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let pairs = frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("stream response", "Expected map."))?;
+
+    Ok(StreamGroupInfo {
+      name: read_string(pairs_get(&pairs, "name"), "name")?,
+      consumers: read_u64(pairs_get(&pairs, "consumers"), "consumers")?,
+      pending: read_u64(pairs_get(&pairs, "pending"), "pending")?,
+      last_delivered_id: read_id(pairs_get(&pairs, "last-delivered-id"), "last-delivered-id")?,
+      entries_read: read_optional_u64(pairs_get(&pairs, "entries-read")),
+      lag: read_optional_u64(pairs_get(&pairs, "lag")),
+    })
+  }
+}
+
+impl TryFrom<&Frame> for StreamConsumerInfo {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let pairs = frame_to_pairs(frame).ok_or_else(|| utils::invalid_response("stream response", "Expected map."))?;
+
+    Ok(StreamConsumerInfo {
+      name: read_string(pairs_get(&pairs, "name"), "name")?,
+      pending: read_u64(pairs_get(&pairs, "pending"), "pending")?,
+      idle: read_u64(pairs_get(&pairs, "idle"), "idle")?,
+      inactive: pairs_get(&pairs, "inactive").and_then(|f| f.as_i64()),
+    })
+  }
+}
+
+impl TryFrom<&Frame> for PendingSummary {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let data = match *frame {
+      Frame::Array { ref data, .. } if data.len() == 4 => data,
+      _ => {
+        return Err(utils::invalid_response(
+          "stream response",
+          "Expected array with 4 elements.",
+        ))
+      },
+    };
+    let consumers = match data[3] {
+      Frame::Null { .. } => Vec::new(),
+      Frame::Array { ref data, .. } => data
+        .iter()
+        .map(|consumer| match *consumer {
+          Frame::Array { ref data, .. } if data.len() == 2 => Ok((
+            read_string(data.first(), "consumer name")?,
+            read_u64(data.get(1), "consumer pending count")?,
+          )),
+          _ => Err(utils::invalid_response("stream response", "Invalid pending consumer.")),
+        })
+        .collect::<Result<_, _>>()?,
+      _ => return Err(utils::invalid_response("stream response", "Invalid pending consumers.")),
+    };
+
+    Ok(PendingSummary {
+      count: read_u64(data.first(), "count")?,
+      min_id: read_optional_id(data.get(1))?,
+      max_id: read_optional_id(data.get(2))?,
+      consumers,
+    })
+  }
+}
+
+impl TryFrom<&Frame> for PendingEntry {
+  type Error = RedisProtocolError;
+
+  fn try_from(frame: &Frame) -> Result<Self, Self::Error> {
+    let data = match *frame {
+      Frame::Array { ref data, .. } if data.len() == 4 => data,
+      _ => {
+        return Err(utils::invalid_response(
+          "stream response",
+          "Expected array with 4 elements.",
+        ))
+      },
+    };
+
+    Ok(PendingEntry {
+      id: read_id(data.first(), "id")?,
+      consumer: read_string(data.get(1), "consumer")?,
+      idle: read_u64(data.get(2), "idle")?,
+      delivery_count: read_u64(data.get(3), "delivery count")?,
+    })
+  }
+}
+
+fn parse_array<'a, T>(frame: &'a Frame) -> Result<Vec<T>, RedisProtocolError>
+where
+  T: TryFrom<&'a Frame, Error = RedisProtocolError>,
+{
+  match *frame {
+    Frame::Array { ref data, .. } => data.iter().map(T::try_from).collect(),
+    _ => Err(utils::invalid_response("stream response", "Expected array.")),
+  }
+}
+
+/// Parse the response to `XINFO GROUPS`.
+pub fn parse_xinfo_groups(frame: &Frame) -> Result<Vec<StreamGroupInfo>, RedisProtocolError> {
+  parse_array(frame)
+}
+
+/// Parse the response to `XINFO CONSUMERS`.
+pub fn parse_xinfo_consumers(frame: &Frame) -> Result<Vec<StreamConsumerInfo>, RedisProtocolError> {
+  parse_array(frame)
+}
+
+/// Parse the response to `XPENDING` with the range arguments.
+pub fn parse_xpending_entries(frame: &Frame) -> Result<Vec<PendingEntry>, RedisProtocolError> {
+  parse_array(frame)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::resp3::test_frames::*;
+
+  fn entry(id: &str, fields: &[&str]) -> Frame {
+    array(vec![blob(id), array(fields.iter().map(|f| blob(f)).collect())])
+  }
+
+  #[test]
+  fn should_parse_and_format_xid() {
+    assert_eq!("1526919030474-55".parse::<XId>().unwrap(), XId::new(1526919030474, 55));
+    assert_eq!("1526919030474".parse::<XId>().unwrap(), XId::new(1526919030474, 0));
+    assert_eq!("-".parse::<XId>().unwrap(), XId::MIN);
+    assert_eq!("+".parse::<XId>().unwrap(), XId::MAX);
+    assert_eq!(XId::new(1, 2).to_string(), "1-2");
+    assert!("abc-1".parse::<XId>().is_err());
+    assert!("1-".parse::<XId>().is_err());
+  }
+
+  #[test]
+  fn should_order_xid() {
+    assert!(XId::new(1, 5) < XId::new(2, 0));
+    assert!(XId::new(2, 1) > XId::new(2, 0));
+    assert_eq!(XId::new(1, 1).max(XId::new(1, 0)), XId::new(1, 1));
+  }
+
+  #[test]
+  fn should_increment_and_decrement_xid() {
+    assert_eq!(XId::new(1, 1).increment(), Some(XId::new(1, 2)));
+    assert_eq!(XId::new(1, u64::MAX).increment(), Some(XId::new(2, 0)));
+    assert_eq!(XId::MAX.increment(), None);
+    assert_eq!(XId::new(2, 0).decrement(), Some(XId::new(1, u64::MAX)));
+    assert_eq!(XId::MIN.decrement(), None);
+  }
+
+  #[test]
+  fn should_parse_xrange_response() {
+    let frame = array(vec![
+      entry("1-0", &["name", "foo", "age", "10"]),
+      array(vec![blob("1-1"), Frame::Null { attributes: None }]),
+    ]);
+    let entries = parse_stream_entries(&frame).unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].id, XId::new(1, 0));
+    assert_eq!(entries[0].get(b"age"), Some(&b"10"[..]));
+    assert_eq!(entries[0].fields.len(), 2);
+    assert!(entries[1].fields.is_empty());
+  }
+
+  #[test]
+  fn should_parse_xread_response() {
+    let resp2 = array(vec![array(vec![blob("mystream"), array(vec![entry("5-1", &["a", "b"])])])]);
+    let resp3 = map(vec![("mystream", array(vec![entry("5-1", &["a", "b"])]))]);
+
+    let expected = vec![(b"mystream".to_vec(), vec![StreamEntry {
+      id: XId::new(5, 1),
+      fields: vec![(b"a".to_vec(), b"b".to_vec())],
+    }])];
+    assert_eq!(parse_xread(&resp2).unwrap(), expected);
+    assert_eq!(parse_xread(&resp3).unwrap(), expected);
+    assert!(parse_xread(&Frame::Null { attributes: None }).unwrap().is_empty());
+  }
+
+  #[test]
+  fn should_error_on_invalid_stream_entry() {
+    assert!(parse_stream_entries(&array(vec![entry("1-0", &["a"])])).is_err());
+    assert!(parse_stream_entries(&array(vec![array(vec![blob("1-0")])])).is_err());
+    assert!(parse_xread(&array(vec![blob("foo")])).is_err());
+  }
+
+  #[test]
+  fn should_parse_xinfo_stream() {
+    let frame = map(vec![
+      ("length", int(2)),
+      ("radix-tree-keys", int(1)),
+      ("radix-tree-nodes", int(2)),
+      ("last-generated-id", blob("1638125141232-0")),
+      ("max-deleted-entry-id", blob("0-0")),
+      ("entries-added", int(2)),
+      ("groups", int(1)),
+      ("first-entry", entry("1638125133432-0", &["message", "apple"])),
+      ("last-entry", Frame::Null { attributes: None }),
+    ]);
+    let info = StreamInfo::try_from(&frame).unwrap();
+
+    assert_eq!(info.length, 2);
+    assert_eq!(info.radix_tree_nodes, 2);
+    assert_eq!(info.last_generated_id, XId::new(1638125141232, 0));
+    assert_eq!(info.max_deleted_entry_id, Some(XId::MIN));
+    assert_eq!(info.entries_added, Some(2));
+    assert_eq!(info.groups, 1);
+    assert_eq!(info.first_entry.unwrap().get(b"message"), Some(&b"apple"[..]));
+    assert_eq!(info.last_entry, None);
+  }
+
+  #[test]
+  fn should_parse_xinfo_groups_and_consumers() {
+    // RESP2 servers send a flat array of fields
+    let frame = array(vec![array(vec![
+      blob("name"),
+      blob("mygroup"),
+      blob("consumers"),
+      int(2),
+      blob("pending"),
+      int(3),
+      blob("last-delivered-id"),
+      blob("1638126030001-0"),
+      blob("entries-read"),
+      Frame::Null { attributes: None },
+      blob("lag"),
+      int(0),
+    ])]);
+    let groups = parse_xinfo_groups(&frame).unwrap();
+
+    assert_eq!(groups, vec![StreamGroupInfo {
+      name: "mygroup".into(),
+      consumers: 2,
+      pending: 3,
+      last_delivered_id: XId::new(1638126030001, 0),
+      entries_read: None,
+      lag: Some(0),
+    }]);
+
+    let frame = array(vec![map(vec![
+      ("name", blob("Alice")),
+      ("pending", int(1)),
+      ("idle", int(9104628)),
+    ])]);
+    let consumers = parse_xinfo_consumers(&frame).unwrap();
+    assert_eq!(consumers[0].name, "Alice");
+    assert_eq!(consumers[0].idle, 9104628);
+    assert_eq!(consumers[0].inactive, None);
+  }
+
+  #[test]
+  fn should_parse_xpending_summary() {
+    let frame = array(vec![
+      int(2),
+      blob("1526984818136-0"),
+      blob("1526984818137-0"),
+      array(vec![array(vec![blob("consumer-123"), blob("2")])]),
+    ]);
+    let summary = PendingSummary::try_from(&frame).unwrap();
+
+    assert_eq!(summary.count, 2);
+    assert_eq!(summary.min_id, Some(XId::new(1526984818136, 0)));
+    assert_eq!(summary.max_id, Some(XId::new(1526984818137, 0)));
+    assert_eq!(summary.consumers, vec![("consumer-123".to_owned(), 2)]);
+
+    let empty = array(vec![
+      int(0),
+      Frame::Null { attributes: None },
+      Frame::Null { attributes: None },
+      Frame::Null { attributes: None },
+    ]);
+    assert_eq!(PendingSummary::try_from(&empty).unwrap(), PendingSummary::default());
+  }
+
+  #[test]
+  fn should_parse_xpending_entries() {
+    let frame = array(vec![array(vec![
+      blob("1526984818136-0"),
+      blob("consumer-123"),
+      int(196415),
+      int(1),
+    ])]);
+
+    assert_eq!(parse_xpending_entries(&frame).unwrap(), vec![PendingEntry {
+      id: XId::new(1526984818136, 0),
+      consumer: "consumer-123".into(),
+      idle: 196415,
+      delivery_count: 1,
+    }]);
+    assert!(parse_xpending_entries(&array(vec![array(vec![blob("1-0")])])).is_err());
+  }
+}
@@ -0,0 +1,230 @@
+//! Assertions for testing codecs and other code built on the decoding and encoding functions.
+//!
+//! Every function panics with a description of the problem, including the escaped bytes that were decoded, so they
+//! can be used directly in tests.
+//!
+//! ```
+//! # use redis_protocol::resp3::types::Frame;
+//! # use redis_protocol::test_util::*;
+//! assert_decodes(b":42\r\n", &Frame::from(42));
+//! assert_roundtrip(&Frame::from(42));
+//! assert_corpus_roundtrip(&[Frame::from(1), Frame::from(true)]);
+//! ```
+
+use crate::resp2::decode::decode as resp2_decode;
+use crate::resp2::encode::encode_bytes as resp2_encode_bytes;
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::decode::complete::decode;
+use crate::resp3::encode::complete::encode_bytes;
+use crate::resp3::types::Frame;
+use crate::types::RedisProtocolError;
+use crate::utils::escape_wire_string;
+use bytes::BytesMut;
+use std::fmt::Debug;
+
+/// Bytes appended to encoded frames to check that decoding stops at the end of the frame.
+const PADDING: &[u8] = b"FOOBARBAZ";
+
+type DecodeFn<T> = fn(&[u8]) -> Result<Option<(T, usize)>, RedisProtocolError>;
+
+fn decode_or_panic<T>(decode: DecodeFn<T>, buf: &[u8]) -> Option<(T, usize)> {
+  match decode(buf) {
+    Ok(result) => result,
+    Err(e) => panic!("Failed to decode \"{}\": {:?}", escape_wire_string(buf), e),
+  }
+}
+
+fn assert_decodes_with<T: Debug + PartialEq>(decode: DecodeFn<T>, buf: &[u8], expected: &T) {
+  match decode_or_panic(decode, buf) {
+    Some((frame, len)) => {
+      assert_eq!(&frame, expected, "decoded frame from \"{}\"", escape_wire_string(buf));
+      assert_eq!(len, buf.len(), "decoded frame len from \"{}\"", escape_wire_string(buf));
+    }
+    None => panic!("Failed to decode \"{}\": incomplete frame.", escape_wire_string(buf)),
+  }
+
+  let mut padded = buf.to_vec();
+  padded.extend_from_slice(PADDING);
+  match decode_or_panic(decode, &padded) {
+    Some((frame, len)) => {
+      assert_eq!(&frame, expected, "decoded frame from padded \"{}\"", escape_wire_string(buf));
+      assert_eq!(len, buf.len(), "decoded frame len from padded \"{}\"", escape_wire_string(buf));
+    }
+    None => panic!("Failed to decode padded \"{}\": incomplete frame.", escape_wire_string(buf)),
+  }
+
+  for idx in 0..buf.len() {
+    if let Some((frame, _)) = decode_or_panic(decode, &buf[..idx]) {
+      panic!(
+        "Decoded {:?} from incomplete prefix \"{}\".",
+        frame,
+        escape_wire_string(&buf[..idx])
+      );
+    }
+  }
+}
+
+fn decode_all_with<T>(decode: DecodeFn<T>, buf: &[u8]) -> Vec<T> {
+  let mut frames = Vec::new();
+  let mut offset = 0;
+
+  while offset < buf.len() {
+    match decode_or_panic(decode, &buf[offset..]) {
+      Some((frame, len)) => {
+        frames.push(frame);
+        offset += len;
+      }
+      None => panic!(
+        "Found incomplete frame at offset {}: \"{}\"",
+        offset,
+        escape_wire_string(&buf[offset..])
+      ),
+    }
+  }
+
+  frames
+}
+
+/// Assert that `buf` contains exactly one RESP3 frame equal to `expected`.
+///
+/// This also checks that decoding stops at the end of the frame when more bytes follow it, and that every shorter
+/// prefix of `buf` is treated as an incomplete frame.
+pub fn assert_decodes(buf: &[u8], expected: &Frame) {
+  assert_decodes_with(decode, buf, expected);
+}
+
+/// Assert that `buf` contains exactly one RESP2 frame equal to `expected`.
+///
+/// See [assert_decodes] for more information.
+pub fn assert_resp2_decodes(buf: &[u8], expected: &Resp2Frame) {
+  assert_decodes_with(resp2_decode, buf, expected);
+}
+
+/// Assert that two RESP3 frames are equal, including their attributes.
+///
+/// The panic message lists each difference with its path in the frame, which is easier to read than the output of
+/// `assert_eq!` for large nested frames. See [Frame::diff] for more information.
+pub fn assert_frame_eq(left: &Frame, right: &Frame) {
+  let diff = left.diff(right);
+  if !diff.is_empty() {
+    panic!("Frames are not equal:\n{}", diff);
+  }
+}
+
+/// Assert that `buf` contains an incomplete RESP3 frame.
+pub fn assert_incomplete(buf: &[u8]) {
+  if let Some((frame, _)) = decode_or_panic(decode, buf) {
+    panic!("Decoded {:?} from incomplete \"{}\".", frame, escape_wire_string(buf));
+  }
+}
+
+/// Assert that `buf` contains an incomplete RESP2 frame.
+pub fn assert_resp2_incomplete(buf: &[u8]) {
+  if let Some((frame, _)) = decode_or_panic(resp2_decode, buf) {
+    panic!("Decoded {:?} from incomplete \"{}\".", frame, escape_wire_string(buf));
+  }
+}
+
+/// Assert that a RESP3 frame can be encoded and then decoded back to an equal frame with the same attributes.
+///
+/// The encoded bytes are checked with [assert_decodes].
+pub fn assert_roundtrip(frame: &Frame) {
+  let buf = encode_all(std::slice::from_ref(frame));
+  assert_decodes(&buf, frame);
+
+  let (decoded, _) = decode(&buf).unwrap().unwrap();
+  assert_eq!(
+    decoded.attributes(),
+    frame.attributes(),
+    "decoded attributes from \"{}\"",
+    escape_wire_string(&buf)
+  );
+}
+
+/// Assert that a RESP2 frame can be encoded and then decoded back to an equal frame.
+///
+/// The encoded bytes are checked with [assert_resp2_decodes].
+pub fn assert_resp2_roundtrip(frame: &Resp2Frame) {
+  assert_resp2_decodes(&encode_resp2_all(std::slice::from_ref(frame)), frame);
+}
+
+/// Encode a sequence of RESP3 frames into one buffer, panicking if any frame cannot be encoded.
+pub fn encode_all(frames: &[Frame]) -> BytesMut {
+  let mut buf = BytesMut::new();
+  for frame in frames.iter() {
+    if let Err(e) = encode_bytes(&mut buf, frame) {
+      panic!("Failed to encode {:?}: {:?}", frame, e);
+    }
+  }
+
+  buf
+}
+
+/// Encode a sequence of RESP2 frames into one buffer, panicking if any frame cannot be encoded.
+pub fn encode_resp2_all(frames: &[Resp2Frame]) -> BytesMut {
+  let mut buf = BytesMut::new();
+  for frame in frames.iter() {
+    if let Err(e) = resp2_encode_bytes(&mut buf, frame) {
+      panic!("Failed to encode {:?}: {:?}", frame, e);
+    }
+  }
+
+  buf
+}
+
+/// Decode every RESP3 frame in `buf`, panicking if the buffer contains an invalid or incomplete frame.
+///
+/// This is useful for reading a corpus of frames, such as a capture of the bytes sent by a server.
+pub fn decode_all(buf: &[u8]) -> Vec<Frame> {
+  decode_all_with(decode, buf)
+}
+
+/// Decode every RESP2 frame in `buf`, panicking if the buffer contains an invalid or incomplete frame.
+pub fn decode_resp2_all(buf: &[u8]) -> Vec<Resp2Frame> {
+  decode_all_with(resp2_decode, buf)
+}
+
+/// Assert that a sequence of RESP3 frames can be encoded into one buffer and then decoded back to equal frames.
+pub fn assert_corpus_roundtrip(frames: &[Frame]) {
+  assert_eq!(decode_all(&encode_all(frames)), frames);
+}
+
+/// Assert that a sequence of RESP2 frames can be encoded into one buffer and then decoded back to equal frames.
+pub fn assert_resp2_corpus_roundtrip(frames: &[Resp2Frame]) {
+  assert_eq!(decode_resp2_all(&encode_resp2_all(frames)), frames);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_assert_resp2_frames() {
+    let frame = Resp2Frame::Array(vec![Resp2Frame::BulkString(b"foo".to_vec()), Resp2Frame::Null]);
+
+    assert_resp2_decodes(b"*2\r\n$3\r\nfoo\r\n$-1\r\n", &frame);
+    assert_resp2_incomplete(b"*2\r\n$3\r\nfoo\r\n");
+    assert_resp2_roundtrip(&frame);
+    assert_resp2_corpus_roundtrip(&[frame, Resp2Frame::Integer(1)]);
+  }
+
+  #[test]
+  #[should_panic(expected = "decoded frame len")]
+  fn should_panic_on_trailing_bytes() {
+    assert_decodes(b":1\r\n:2\r\n", &Frame::from(1));
+  }
+
+  #[test]
+  #[should_panic(expected = "Frames are not equal:\n[1]: Number(2) != Number(3)")]
+  fn should_panic_with_frame_diff() {
+    let frame = |data: Vec<Frame>| Frame::Array { data, attributes: None };
+    assert_frame_eq(&frame(vec![1.into(), 2.into()]), &frame(vec![1.into(), 2.into()]));
+    assert_frame_eq(&frame(vec![1.into(), 2.into()]), &frame(vec![1.into(), 3.into()]));
+  }
+
+  #[test]
+  #[should_panic(expected = "Found incomplete frame at offset 4")]
+  fn should_panic_on_incomplete_corpus() {
+    decode_all(b":1\r\n:2");
+  }
+}
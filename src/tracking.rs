@@ -0,0 +1,310 @@
+//! Types and functions for implementing client-side caching.
+//!
+//! <https://redis.io/topics/client-side-caching>
+
+use crate::resp2::types::Frame as Resp2Frame;
+use crate::resp3::types::Frame as Resp3Frame;
+
+/// Prefix on RESP3 invalidation push messages.
+pub const INVALIDATION_PREFIX: &str = "invalidate";
+/// The channel used to deliver invalidation messages to a client in `REDIRECT` mode.
+pub const INVALIDATION_CHANNEL: &str = "__redis__:invalidate";
+
+/// A client-side caching invalidation message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Invalidation {
+  /// The provided keys were modified and should be evicted from the local cache.
+  Keys(Vec<Vec<u8>>),
+  /// The server flushed the keyspace (via `FLUSHALL`, `FLUSHDB`, etc) and the entire local cache should be evicted.
+  All,
+}
+
+impl Invalidation {
+  /// Whether or not the entire local cache should be evicted.
+  pub fn is_flush_all(&self) -> bool {
+    matches!(*self, Invalidation::All)
+  }
+
+  /// Read the invalidated keys, or `None` if the entire cache should be evicted.
+  pub fn keys(&self) -> Option<&[Vec<u8>]> {
+    match *self {
+      Invalidation::Keys(ref keys) => Some(keys),
+      Invalidation::All => None,
+    }
+  }
+}
+
+/// Options used with the `CLIENT TRACKING ON` command.
+///
+/// <https://redis.io/commands/client-tracking>
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TrackingOptions {
+  /// Send invalidation messages to the connection with this client ID.
+  pub redirect: Option<i64>,
+  /// The key prefixes to track in broadcasting mode.
+  pub prefixes: Vec<String>,
+  /// Enable broadcasting mode.
+  pub bcast: bool,
+  /// Only track keys read after a `CLIENT CACHING yes` command.
+  pub optin: bool,
+  /// Track all keys except those read after a `CLIENT CACHING no` command.
+  pub optout: bool,
+  /// Don't send invalidation messages for keys modified by this connection.
+  pub noloop: bool,
+}
+
+impl TrackingOptions {
+  fn extend_args(&self, args: &mut Vec<Vec<u8>>) {
+    if let Some(ref id) = self.redirect {
+      args.push(b"REDIRECT".to_vec());
+      args.push(id.to_string().into_bytes());
+    }
+    for prefix in self.prefixes.iter() {
+      args.push(b"PREFIX".to_vec());
+      args.push(prefix.as_bytes().to_vec());
+    }
+    if self.bcast {
+      args.push(b"BCAST".to_vec());
+    }
+    if self.optin {
+      args.push(b"OPTIN".to_vec());
+    }
+    if self.optout {
+      args.push(b"OPTOUT".to_vec());
+    }
+    if self.noloop {
+      args.push(b"NOLOOP".to_vec());
+    }
+  }
+}
+
+/// A command used to manage client-side caching on a connection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TrackingCommand {
+  /// `CLIENT TRACKING ON` with the provided options.
+  On(TrackingOptions),
+  /// `CLIENT TRACKING OFF`.
+  Off,
+  /// `CLIENT CACHING YES|NO`, used with the `OPTIN` and `OPTOUT` options.
+  Caching(bool),
+}
+
+impl TrackingCommand {
+  /// Read the command arguments, including the `CLIENT` command name.
+  pub fn to_args(&self) -> Vec<Vec<u8>> {
+    let mut args = vec![b"CLIENT".to_vec()];
+
+    match *self {
+      TrackingCommand::On(ref options) => {
+        args.push(b"TRACKING".to_vec());
+        args.push(b"ON".to_vec());
+        options.extend_args(&mut args);
+      }
+      TrackingCommand::Off => {
+        args.push(b"TRACKING".to_vec());
+        args.push(b"OFF".to_vec());
+      }
+      TrackingCommand::Caching(yes) => {
+        args.push(b"CACHING".to_vec());
+        args.push(if yes { b"YES".to_vec() } else { b"NO".to_vec() });
+      }
+    }
+
+    args
+  }
+
+  /// Create a RESP2 command frame.
+  pub fn to_resp2_frame(&self) -> Resp2Frame {
+    Resp2Frame::Array(self.to_args().into_iter().map(Resp2Frame::BulkString).collect())
+  }
+
+  /// Create a RESP3 command frame.
+  pub fn to_resp3_frame(&self) -> Resp3Frame {
+    let data = self
+      .to_args()
+      .into_iter()
+      .map(|arg| Resp3Frame::BlobString {
+        data: arg,
+        attributes: None,
+      })
+      .collect();
+
+    Resp3Frame::Array { data, attributes: None }
+  }
+}
+
+fn resp3_frame_to_invalidation(frame: &Resp3Frame) -> Option<Invalidation> {
+  match *frame {
+    Resp3Frame::Null { .. } => Some(Invalidation::All),
+    Resp3Frame::Array { ref data, .. } | Resp3Frame::Push { ref data, .. } => {
+      let mut keys = Vec::with_capacity(data.len());
+      for frame in data.iter() {
+        keys.push(frame.as_bytes()?.to_vec());
+      }
+
+      Some(Invalidation::Keys(keys))
+    }
+    _ => None,
+  }
+}
+
+fn resp2_frame_to_invalidation(frame: &Resp2Frame) -> Option<Invalidation> {
+  match *frame {
+    Resp2Frame::Null => Some(Invalidation::All),
+    Resp2Frame::Array(ref data) => {
+      let mut keys = Vec::with_capacity(data.len());
+      for frame in data.iter() {
+        match *frame {
+          Resp2Frame::BulkString(ref b) => keys.push(b.clone()),
+          Resp2Frame::SimpleString(ref s) => keys.push(s.as_bytes().to_vec()),
+          _ => return None,
+        };
+      }
+
+      Some(Invalidation::Keys(keys))
+    }
+    _ => None,
+  }
+}
+
+/// Whether or not the inner frames of a push frame represent an invalidation message.
+///
+/// Both the `>2 invalidate <keys>` form and a publish-subscribe message on the [INVALIDATION_CHANNEL] are supported.
+pub(crate) fn is_resp3_invalidation(frames: &[Resp3Frame]) -> bool {
+  if frames.len() == 2 {
+    frames[0].as_str().map(|s| s == INVALIDATION_PREFIX).unwrap_or(false)
+  } else if crate::resp3::utils::is_normal_pubsub(frames) {
+    frames[frames.len() - 2]
+      .as_str()
+      .map(|s| s == INVALIDATION_CHANNEL)
+      .unwrap_or(false)
+  } else {
+    false
+  }
+}
+
+/// Parse the inner frames of a push frame as an invalidation message.
+pub(crate) fn parse_resp3_invalidation(frames: &[Resp3Frame]) -> Option<Invalidation> {
+  if is_resp3_invalidation(frames) {
+    frames.last().and_then(resp3_frame_to_invalidation)
+  } else {
+    None
+  }
+}
+
+/// Whether or not the inner frames of an array represent an invalidation message sent to a RESP2 connection in `REDIRECT` mode.
+pub(crate) fn is_resp2_invalidation(frames: &[Resp2Frame]) -> bool {
+  frames.len() == 3
+    && frames[0].as_str().map(|s| s == crate::utils::PUBSUB_PREFIX).unwrap_or(false)
+    && frames[1].as_str().map(|s| s == INVALIDATION_CHANNEL).unwrap_or(false)
+}
+
+/// Parse the inner frames of an array as an invalidation message sent to a RESP2 connection in `REDIRECT` mode.
+pub(crate) fn parse_resp2_invalidation(frames: &[Resp2Frame]) -> Option<Invalidation> {
+  if is_resp2_invalidation(frames) {
+    resp2_frame_to_invalidation(&frames[2])
+  } else {
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn should_parse_resp3_invalidation_keys() {
+    let frame = Resp3Frame::new_push(vec![
+      Resp3Frame::new_blob(INVALIDATION_PREFIX),
+      Resp3Frame::new_array(vec![Resp3Frame::new_blob("foo"), Resp3Frame::new_blob("bar")]),
+    ]);
+
+    assert!(frame.is_invalidation());
+    assert_eq!(
+      frame.to_invalidation(),
+      Some(Invalidation::Keys(vec![b"foo".to_vec(), b"bar".to_vec()]))
+    );
+  }
+
+  #[test]
+  fn should_parse_resp3_invalidation_flush_all() {
+    let frame = Resp3Frame::new_push(vec![Resp3Frame::new_blob(INVALIDATION_PREFIX), Resp3Frame::new_null()]);
+
+    assert!(frame.is_invalidation());
+    assert!(frame.to_invalidation().unwrap().is_flush_all());
+  }
+
+  #[test]
+  fn should_parse_resp3_invalidation_pubsub() {
+    let frame = Resp3Frame::new_push(vec![
+      Resp3Frame::new_blob("pubsub"),
+      Resp3Frame::new_blob("message"),
+      Resp3Frame::new_blob(INVALIDATION_CHANNEL),
+      Resp3Frame::new_array(vec![Resp3Frame::new_blob("foo")]),
+    ]);
+
+    assert_eq!(frame.to_invalidation(), Some(Invalidation::Keys(vec![b"foo".to_vec()])));
+  }
+
+  #[test]
+  fn should_not_parse_resp3_pubsub_as_invalidation() {
+    let frame = Resp3Frame::new_push(
+      ["pubsub", "message", "foo", "bar"]
+        .iter()
+        .map(|s| Resp3Frame::new_blob(*s)),
+    );
+
+    assert!(!frame.is_invalidation());
+    assert_eq!(frame.to_invalidation(), None);
+  }
+
+  #[test]
+  fn should_parse_resp2_invalidation() {
+    let frame = Resp2Frame::Array(vec![
+      Resp2Frame::BulkString("message".into()),
+      Resp2Frame::BulkString(INVALIDATION_CHANNEL.into()),
+      Resp2Frame::Array(vec![Resp2Frame::BulkString("foo".into())]),
+    ]);
+    assert!(frame.is_invalidation());
+    assert_eq!(frame.to_invalidation(), Some(Invalidation::Keys(vec![b"foo".to_vec()])));
+
+    let frame = Resp2Frame::Array(vec![
+      Resp2Frame::BulkString("message".into()),
+      Resp2Frame::BulkString(INVALIDATION_CHANNEL.into()),
+      Resp2Frame::Null,
+    ]);
+    assert_eq!(frame.to_invalidation(), Some(Invalidation::All));
+  }
+
+  #[test]
+  fn should_create_tracking_on_frame() {
+    let command = TrackingCommand::On(TrackingOptions {
+      redirect: Some(10),
+      prefixes: vec!["foo".into(), "bar".into()],
+      bcast: true,
+      noloop: true,
+      ..Default::default()
+    });
+    let expected: Vec<&[u8]> = vec![
+      b"CLIENT", b"TRACKING", b"ON", b"REDIRECT", b"10", b"PREFIX", b"foo", b"PREFIX", b"bar", b"BCAST", b"NOLOOP",
+    ];
+
+    assert_eq!(command.to_args(), expected);
+    assert_eq!(
+      command.to_resp2_frame(),
+      Resp2Frame::Array(expected.iter().map(|s| Resp2Frame::BulkString(s.to_vec())).collect())
+    );
+  }
+
+  #[test]
+  fn should_create_tracking_off_and_caching_frames() {
+    assert_eq!(
+      TrackingCommand::Off.to_resp3_frame(),
+      Resp3Frame::new_array(["CLIENT", "TRACKING", "OFF"].iter().map(|s| Resp3Frame::new_blob(*s)))
+    );
+    assert_eq!(
+      TrackingCommand::Caching(true).to_resp3_frame(),
+      Resp3Frame::new_array(["CLIENT", "CACHING", "YES"].iter().map(|s| Resp3Frame::new_blob(*s)))
+    );
+  }
+}
@@ -1,28 +1,43 @@
 use crate::resp2::types::Frame as Resp2Frame;
-use crate::resp3::types::Frame as Resp3Frame;
+use crate::resp3::types::{Attributes, Frame as Resp3Frame};
+use crate::utils;
 
 use cookie_factory::GenError;
 use nom::error::{ErrorKind, FromExternalError, ParseError};
 use nom::{Err as NomError, Needed};
 use std::borrow::Borrow;
 use std::borrow::Cow;
+use std::error::Error;
 use std::fmt::{self, Debug};
-use std::io::Error as IoError;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
 
 use std::str;
 
 /// Terminating bytes between frames.
 pub const CRLF: &'static str = "\r\n";
+/// The error returned by the server when a command is used with a key that holds the wrong kind of value.
+pub const WRONG_TYPE_ERROR: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+/// The error returned by the server when a command has invalid arguments.
+pub const SYNTAX_ERROR: &str = "ERR syntax error";
 
 /// The kind of error without any associated data.
 #[derive(Debug)]
 pub enum RedisProtocolErrorKind {
   /// An error that occurred while encoding data.
   EncodeError,
-  /// An error indicating that the provided buffer needs to be extended by the inner `usize` bytes before encoding can continue.
+  /// An error indicating that the provided buffer is too small, with the number of additional bytes needed.
   BufferTooSmall(usize),
+  /// An error indicating that the slice passed to an encoding function is too small.
+  SliceTooSmall {
+    /// The minimum length of the slice, including the bytes before the starting offset.
+    required: usize,
+  },
   /// An error that occurred while decoding data.
   DecodeError,
+  /// An error indicating that the encoded frame would be larger than the inner `usize` byte budget.
+  FrameTooLarge(usize),
+  /// An error indicating that a streamed frame is larger than the inner `usize` byte or frame limit.
+  StreamTooLarge(usize),
   /// An IO error.
   IO(IoError),
   /// An unknown error, or an error that can occur during encoding or decoding.
@@ -43,7 +58,21 @@ impl PartialEq for RedisProtocolErrorKind {
         _ => false,
       },
       BufferTooSmall(amt) => match *other {
-        BufferTooSmall(_amt) => amt == amt,
+        BufferTooSmall(other_amt) => amt == other_amt,
+        _ => false,
+      },
+      SliceTooSmall { required } => match *other {
+        SliceTooSmall {
+          required: other_required,
+        } => required == other_required,
+        _ => false,
+      },
+      FrameTooLarge(max) => match *other {
+        FrameTooLarge(other_max) => max == other_max,
+        _ => false,
+      },
+      StreamTooLarge(max) => match *other {
+        StreamTooLarge(other_max) => max == other_max,
         _ => false,
       },
       IO(_) => match *other {
@@ -62,23 +91,81 @@ impl Eq for RedisProtocolErrorKind {}
 
 impl RedisProtocolErrorKind {
   pub fn to_str(&self) -> &'static str {
+    self.error_type().to_str()
+  }
+
+  /// Read the kind of error without any associated data.
+  pub fn error_type(&self) -> RedisProtocolErrorType {
     use self::RedisProtocolErrorKind::*;
 
+    match *self {
+      EncodeError => RedisProtocolErrorType::EncodeError,
+      DecodeError => RedisProtocolErrorType::DecodeError,
+      Unknown => RedisProtocolErrorType::Unknown,
+      IO(_) => RedisProtocolErrorType::IO,
+      BufferTooSmall(_) => RedisProtocolErrorType::BufferTooSmall,
+      SliceTooSmall { .. } => RedisProtocolErrorType::SliceTooSmall,
+      FrameTooLarge(_) => RedisProtocolErrorType::FrameTooLarge,
+      StreamTooLarge(_) => RedisProtocolErrorType::StreamTooLarge,
+    }
+  }
+}
+
+/// A copyable version of [RedisProtocolErrorKind] without the associated data, for matching on errors without borrowing
+/// or cloning them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum RedisProtocolErrorType {
+  EncodeError,
+  BufferTooSmall,
+  SliceTooSmall,
+  DecodeError,
+  FrameTooLarge,
+  StreamTooLarge,
+  IO,
+  Unknown,
+}
+
+impl RedisProtocolErrorType {
+  pub fn to_str(&self) -> &'static str {
+    use self::RedisProtocolErrorType::*;
+
     match *self {
       EncodeError => "Encode Error",
       DecodeError => "Decode Error",
       Unknown => "Unknown Error",
-      IO(_) => "IO Error",
-      BufferTooSmall(_) => "Buffer too small",
+      IO => "IO Error",
+      BufferTooSmall => "Buffer too small",
+      SliceTooSmall => "Slice too small",
+      FrameTooLarge => "Frame too large",
+      StreamTooLarge => "Stream too large",
+    }
+  }
+
+  /// The closest [std::io::ErrorKind] to the kind of error, used when converting to an IO error.
+  pub fn to_io_kind(&self) -> IoErrorKind {
+    use self::RedisProtocolErrorType::*;
+
+    match *self {
+      EncodeError | FrameTooLarge => IoErrorKind::InvalidInput,
+      DecodeError | StreamTooLarge => IoErrorKind::InvalidData,
+      BufferTooSmall | SliceTooSmall | IO | Unknown => IoErrorKind::Other,
     }
   }
 }
 
+impl fmt::Display for RedisProtocolErrorType {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.to_str())
+  }
+}
+
 /// The default error type used with all external functions in this library.
 #[derive(Debug, Eq, PartialEq)]
 pub struct RedisProtocolError {
   desc: Cow<'static, str>,
   kind: RedisProtocolErrorKind,
+  offset: Option<usize>,
+  hex_dump: Option<String>,
 }
 
 impl RedisProtocolError {
@@ -86,10 +173,18 @@ impl RedisProtocolError {
     RedisProtocolError::new(RedisProtocolErrorKind::BufferTooSmall(amt), "")
   }
 
+  /// Create an error for a slice that must be at least `required` bytes long, including the bytes before the starting
+  /// offset.
+  pub fn slice_too_small(required: usize) -> Self {
+    RedisProtocolError::new(RedisProtocolErrorKind::SliceTooSmall { required }, "")
+  }
+
   pub fn new<S: Into<Cow<'static, str>>>(kind: RedisProtocolErrorKind, desc: S) -> Self {
     RedisProtocolError {
       kind,
       desc: desc.into(),
+      offset: None,
+      hex_dump: None,
     }
   }
 
@@ -98,24 +193,81 @@ impl RedisProtocolError {
   }
 
   pub fn new_empty() -> Self {
-    RedisProtocolError {
-      kind: RedisProtocolErrorKind::Unknown,
-      desc: "".into(),
-    }
+    RedisProtocolError::new(RedisProtocolErrorKind::Unknown, "")
   }
 
   pub fn to_string(&self) -> String {
-    format!("{}: {}", self.kind.to_str(), self.desc)
+    format!("{}", self)
   }
 
   pub fn kind(&self) -> &RedisProtocolErrorKind {
     &self.kind
   }
+
+  /// Read the kind of error without any associated data.
+  pub fn error_type(&self) -> RedisProtocolErrorType {
+    self.kind.error_type()
+  }
+
+  /// Read the offset in the decoded buffer at which the error occurred, if known.
+  ///
+  /// This is only set when hex dump diagnostics are enabled while decoding.
+  pub fn offset(&self) -> Option<usize> {
+    self.offset
+  }
+
+  /// Read the hex dump of the bytes around the failing offset, if one was captured while decoding.
+  ///
+  /// See [hex_dump](crate::hex_dump) for the format.
+  pub fn hex_dump(&self) -> Option<&str> {
+    self.hex_dump.as_deref()
+  }
+
+  /// Prepend `context` to the description of the error, keeping the error kind.
+  pub(crate) fn with_context<S: fmt::Display>(mut self, context: S) -> Self {
+    self.desc = format!("{}: {}", context, self.desc).into();
+    self
+  }
+
+  /// Attach a hex dump of the bytes within `window` bytes of `offset` in `buf` to the error.
+  pub(crate) fn with_hex_dump(mut self, buf: &[u8], offset: usize, window: usize) -> Self {
+    self.hex_dump = Some(utils::hex_dump(buf, offset, window));
+    self.offset = Some(offset);
+    self
+  }
 }
 
 impl fmt::Display for RedisProtocolError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    write!(f, "{}: {}", self.kind.to_str(), self.desc)
+    write!(f, "{}: {}", self.kind.to_str(), self.desc)?;
+
+    match (self.offset, self.hex_dump.as_ref()) {
+      (Some(offset), Some(dump)) => write!(f, " (at offset {})\n{}", offset, dump),
+      _ => Ok(()),
+    }
+  }
+}
+
+impl Error for RedisProtocolError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self.kind {
+      RedisProtocolErrorKind::IO(ref e) => Some(e),
+      _ => None,
+    }
+  }
+}
+
+/// IO errors are unwrapped, and all other errors are wrapped in an IO error with the kind from
+/// [to_io_kind](crate::types::RedisProtocolErrorType::to_io_kind).
+impl From<RedisProtocolError> for IoError {
+  fn from(e: RedisProtocolError) -> Self {
+    match e {
+      RedisProtocolError {
+        kind: RedisProtocolErrorKind::IO(inner),
+        ..
+      } => inner,
+      e => IoError::new(e.error_type().to_io_kind(), e),
+    }
   }
 }
 
@@ -138,10 +290,7 @@ impl From<GenError> for RedisProtocolError {
 impl From<NomError<nom::error::Error<&[u8]>>> for RedisProtocolError {
   fn from(e: NomError<nom::error::Error<&[u8]>>) -> Self {
     if let NomError::Incomplete(Needed::Size(ref s)) = e {
-      RedisProtocolError {
-        kind: RedisProtocolErrorKind::BufferTooSmall(s.get()),
-        desc: Cow::Borrowed(""),
-      }
+      RedisProtocolError::buffer_too_small(s.get())
     } else {
       let desc = match e {
         NomError::Failure(inner) => format!("Failure: {:?}", inner.code),
@@ -149,10 +298,7 @@ impl From<NomError<nom::error::Error<&[u8]>>> for RedisProtocolError {
         _ => format!("{:?}", e),
       };
 
-      RedisProtocolError {
-        kind: RedisProtocolErrorKind::DecodeError,
-        desc: Cow::Owned(desc),
-      }
+      RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, desc)
     }
   }
 }
@@ -160,15 +306,9 @@ impl From<NomError<nom::error::Error<&[u8]>>> for RedisProtocolError {
 impl From<NomError<&[u8]>> for RedisProtocolError {
   fn from(e: NomError<&[u8]>) -> Self {
     if let NomError::Incomplete(Needed::Size(ref s)) = e {
-      RedisProtocolError {
-        kind: RedisProtocolErrorKind::BufferTooSmall(s.get()),
-        desc: Cow::Borrowed(""),
-      }
+      RedisProtocolError::buffer_too_small(s.get())
     } else {
-      RedisProtocolError {
-        kind: RedisProtocolErrorKind::DecodeError,
-        desc: Cow::Owned(format!("{:?}", e)),
-      }
+      RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, format!("{:?}", e))
     }
   }
 }
@@ -193,6 +333,7 @@ pub enum RedisParseError<I> {
   Custom {
     context: &'static str,
     message: Cow<'static, str>,
+    input: Option<I>,
   },
   Incomplete(Needed),
   Nom(I, ErrorKind),
@@ -207,6 +348,7 @@ where
       RedisParseError::Custom {
         ref context,
         ref message,
+        ..
       } => write!(f, "{}: {}", context, message),
       RedisParseError::Nom(input, kind) => write!(f, "{:?} at {:?}", kind, input),
       RedisParseError::Incomplete(ref needed) => write!(f, "Incomplete({:?})", needed),
@@ -214,11 +356,36 @@ where
   }
 }
 
+impl<I> fmt::Display for RedisParseError<I>
+where
+  I: Debug,
+{
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fmt::Debug::fmt(self, f)
+  }
+}
+
+/// Parse errors do not have a source since nom discards external errors before they reach the parse error.
+impl<I> Error for RedisParseError<I> where I: Debug {}
+
 impl<I> RedisParseError<I> {
   pub fn new_custom<S: Into<Cow<'static, str>>>(ctx: &'static str, message: S) -> Self {
     RedisParseError::Custom {
       context: ctx,
       message: message.into(),
+      input: None,
+    }
+  }
+
+  /// Set the input at which a custom error occurred, so that the offset of the error can be read later.
+  pub fn with_input(self, input: I) -> Self {
+    match self {
+      RedisParseError::Custom { context, message, .. } => RedisParseError::Custom {
+        context,
+        message,
+        input: Some(input),
+      },
+      _ => self,
     }
   }
 
@@ -230,6 +397,18 @@ impl<I> RedisParseError<I> {
   }
 }
 
+impl RedisParseError<&[u8]> {
+  /// Read the offset of the error in `buf`, where `buf` is the buffer that was originally passed to the parser.
+  pub(crate) fn offset_in(&self, buf: &[u8]) -> Option<usize> {
+    match *self {
+      RedisParseError::Nom(input, _) | RedisParseError::Custom { input: Some(input), .. } => {
+        buf.len().checked_sub(input.len())
+      }
+      _ => None,
+    }
+  }
+}
+
 impl<I> ParseError<I> for RedisParseError<I> {
   fn from_error_kind(input: I, kind: ErrorKind) -> Self {
     RedisParseError::Nom(input, kind)
@@ -255,6 +434,196 @@ impl<I> From<nom::Err<RedisParseError<I>>> for RedisParseError<I> {
   }
 }
 
+/// The kind of error returned by the server, read from the leading error code.
+///
+/// <https://redis.io/topics/protocol#resp-errors>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RedisErrorKind {
+  /// A generic `ERR` error.
+  Err,
+  WrongType,
+  NoAuth,
+  WrongPass,
+  NoPerm,
+  ReadOnly,
+  ClusterDown,
+  CrossSlot,
+  TryAgain,
+  Moved,
+  Ask,
+  Loading,
+  Busy,
+  BusyKey,
+  NoScript,
+  OutOfMemory,
+  ExecAbort,
+  MasterDown,
+  NoReplicas,
+  MisConf,
+  NoProto,
+  /// An error code not known to this crate.
+  Unknown(String),
+}
+
+impl RedisErrorKind {
+  /// Read the error kind from an error code such as `WRONGTYPE`.
+  pub fn from_code(code: &str) -> Self {
+    match code {
+      "ERR" => RedisErrorKind::Err,
+      "WRONGTYPE" => RedisErrorKind::WrongType,
+      "NOAUTH" => RedisErrorKind::NoAuth,
+      "WRONGPASS" => RedisErrorKind::WrongPass,
+      "NOPERM" => RedisErrorKind::NoPerm,
+      "READONLY" => RedisErrorKind::ReadOnly,
+      "CLUSTERDOWN" => RedisErrorKind::ClusterDown,
+      "CROSSSLOT" => RedisErrorKind::CrossSlot,
+      "TRYAGAIN" => RedisErrorKind::TryAgain,
+      "MOVED" => RedisErrorKind::Moved,
+      "ASK" => RedisErrorKind::Ask,
+      "LOADING" => RedisErrorKind::Loading,
+      "BUSY" => RedisErrorKind::Busy,
+      "BUSYKEY" => RedisErrorKind::BusyKey,
+      "NOSCRIPT" => RedisErrorKind::NoScript,
+      "OOM" => RedisErrorKind::OutOfMemory,
+      "EXECABORT" => RedisErrorKind::ExecAbort,
+      "MASTERDOWN" => RedisErrorKind::MasterDown,
+      "NOREPLICAS" => RedisErrorKind::NoReplicas,
+      "MISCONF" => RedisErrorKind::MisConf,
+      "NOPROTO" => RedisErrorKind::NoProto,
+      _ => RedisErrorKind::Unknown(code.to_owned()),
+    }
+  }
+
+  /// Read the error kind from the leading code in an error payload such as `WRONGTYPE Operation against a key...`.
+  pub fn parse(payload: &str) -> Self {
+    RedisErrorKind::from_code(RedisErrorKind::split(payload).0)
+  }
+
+  /// Split an error payload into the leading error code and the rest of the message.
+  ///
+  /// ```
+  /// # use redis_protocol::types::RedisErrorKind;
+  /// assert_eq!(RedisErrorKind::split("ERR unknown command"), ("ERR", "unknown command"));
+  /// assert_eq!(RedisErrorKind::split("ERR"), ("ERR", ""));
+  /// ```
+  pub fn split(payload: &str) -> (&str, &str) {
+    match payload.find(' ') {
+      Some(idx) => (&payload[0..idx], &payload[idx + 1..]),
+      None => (payload, ""),
+    }
+  }
+
+  /// Attempt to read the error kind from a RESP3 simple or blob error.
+  pub fn from_frame(frame: &Resp3Frame) -> Option<Self> {
+    match *frame {
+      Resp3Frame::SimpleError { ref data, .. } => Some(RedisErrorKind::parse(&data.to_str_lossy())),
+      Resp3Frame::BlobError { ref data, .. } => Some(RedisErrorKind::parse(&String::from_utf8_lossy(data))),
+      _ => None,
+    }
+  }
+
+  /// Attempt to read the error kind from a RESP2 error.
+  pub fn from_resp2_frame(frame: &Resp2Frame) -> Option<Self> {
+    match *frame {
+      Resp2Frame::Error(ref s) => Some(RedisErrorKind::parse(s)),
+      _ => None,
+    }
+  }
+
+  /// Read the error code, such as `WRONGTYPE`.
+  pub fn code(&self) -> &str {
+    match *self {
+      RedisErrorKind::Err => "ERR",
+      RedisErrorKind::WrongType => "WRONGTYPE",
+      RedisErrorKind::NoAuth => "NOAUTH",
+      RedisErrorKind::WrongPass => "WRONGPASS",
+      RedisErrorKind::NoPerm => "NOPERM",
+      RedisErrorKind::ReadOnly => "READONLY",
+      RedisErrorKind::ClusterDown => "CLUSTERDOWN",
+      RedisErrorKind::CrossSlot => "CROSSSLOT",
+      RedisErrorKind::TryAgain => "TRYAGAIN",
+      RedisErrorKind::Moved => "MOVED",
+      RedisErrorKind::Ask => "ASK",
+      RedisErrorKind::Loading => "LOADING",
+      RedisErrorKind::Busy => "BUSY",
+      RedisErrorKind::BusyKey => "BUSYKEY",
+      RedisErrorKind::NoScript => "NOSCRIPT",
+      RedisErrorKind::OutOfMemory => "OOM",
+      RedisErrorKind::ExecAbort => "EXECABORT",
+      RedisErrorKind::MasterDown => "MASTERDOWN",
+      RedisErrorKind::NoReplicas => "NOREPLICAS",
+      RedisErrorKind::MisConf => "MISCONF",
+      RedisErrorKind::NoProto => "NOPROTO",
+      RedisErrorKind::Unknown(ref code) => code,
+    }
+  }
+
+  /// Whether or not the command may succeed if retried against the same node after a delay.
+  pub fn is_retryable(&self) -> bool {
+    matches!(
+      *self,
+      RedisErrorKind::TryAgain
+        | RedisErrorKind::ClusterDown
+        | RedisErrorKind::Loading
+        | RedisErrorKind::Busy
+        | RedisErrorKind::MasterDown
+    )
+  }
+
+  /// Whether or not the error is a `MOVED` or `ASK` redirection.
+  pub fn is_cluster_redirect(&self) -> bool {
+    matches!(*self, RedisErrorKind::Moved | RedisErrorKind::Ask)
+  }
+
+  /// Whether or not the error is caused by missing or invalid credentials.
+  pub fn is_auth_error(&self) -> bool {
+    matches!(
+      *self,
+      RedisErrorKind::NoAuth | RedisErrorKind::WrongPass | RedisErrorKind::NoPerm
+    )
+  }
+}
+
+/// An error reply from the server, split into the error kind and message.
+///
+/// This is the error returned by `into_result` on the RESP2 and RESP3 frame types.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedisErrorFrame {
+  /// The kind of error, read from the leading error code.
+  pub kind: RedisErrorKind,
+  /// The error message without the leading error code.
+  pub message: String,
+  /// Any attributes attached to the error frame. This is always `None` for RESP2 frames.
+  pub attributes: Option<Attributes>,
+}
+
+impl RedisErrorFrame {
+  pub(crate) fn new(payload: &str, attributes: Option<Attributes>) -> Self {
+    let (code, message) = RedisErrorKind::split(payload);
+
+    RedisErrorFrame {
+      kind: RedisErrorKind::from_code(code),
+      message: message.to_owned(),
+      attributes,
+    }
+  }
+
+  /// Read the error code, such as `WRONGTYPE`.
+  pub fn code(&self) -> &str {
+    self.kind.code()
+  }
+}
+
+impl fmt::Display for RedisErrorFrame {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.message.is_empty() {
+      f.write_str(self.code())
+    } else {
+      write!(f, "{} {}", self.code(), self.message)
+    }
+  }
+}
+
 /// A cluster redirection message.
 ///
 /// <https://redis.io/topics/cluster-spec#redirection-and-resharding>
@@ -264,7 +633,77 @@ pub enum Redirection {
   Ask { slot: u16, server: String },
 }
 
+/// The kind of cluster redirection.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RedirectionKind {
+  Moved,
+  Ask,
+}
+
 impl Redirection {
+  /// Attempt to parse a RESP3 `MOVED` or `ASK` simple or blob error.
+  pub fn from_frame(frame: &Resp3Frame) -> Option<Redirection> {
+    match *frame {
+      Resp3Frame::SimpleError { ref data, .. } => data.as_str().and_then(utils::read_cluster_error),
+      Resp3Frame::BlobError { ref data, .. } => str::from_utf8(data).ok().and_then(utils::read_cluster_error),
+      _ => None,
+    }
+  }
+
+  /// Attempt to parse a RESP2 `MOVED` or `ASK` error.
+  pub fn from_resp2_frame(frame: &Resp2Frame) -> Option<Redirection> {
+    match *frame {
+      Resp2Frame::Error(ref s) => utils::read_cluster_error(s),
+      _ => None,
+    }
+  }
+
+  /// Read the kind of redirection.
+  pub fn kind(&self) -> RedirectionKind {
+    match *self {
+      Redirection::Moved { .. } => RedirectionKind::Moved,
+      Redirection::Ask { .. } => RedirectionKind::Ask,
+    }
+  }
+
+  /// Read the hash slot.
+  pub fn slot(&self) -> u16 {
+    match *self {
+      Redirection::Moved { slot, .. } | Redirection::Ask { slot, .. } => slot,
+    }
+  }
+
+  /// Read the `host:port` endpoint, as sent by the server.
+  pub fn server(&self) -> &str {
+    match *self {
+      Redirection::Moved { ref server, .. } | Redirection::Ask { ref server, .. } => server,
+    }
+  }
+
+  /// Read the host, removing any brackets around IPv6 addresses.
+  ///
+  /// This is empty when the server does not know its own endpoint, in which case clients should use the host of the
+  /// node that sent the redirection.
+  pub fn host(&self) -> &str {
+    let server = self.server();
+    let host = match server.rfind(':') {
+      Some(idx) => &server[0..idx],
+      None => server,
+    };
+
+    if host.starts_with('[') && host.ends_with(']') {
+      &host[1..host.len() - 1]
+    } else {
+      host
+    }
+  }
+
+  /// Read the port.
+  pub fn port(&self) -> Option<u16> {
+    let server = self.server();
+    server.rfind(':').and_then(|idx| server[idx + 1..].parse::<u16>().ok())
+  }
+
   pub fn to_resp2_frame(&self) -> Resp2Frame {
     let inner = match *self {
       Redirection::Moved { ref slot, ref server } => format!("MOVED {} {}", slot, server),
@@ -281,7 +720,7 @@ impl Redirection {
     };
 
     Resp3Frame::SimpleError {
-      data: inner,
+      data: inner.into(),
       attributes: None,
     }
   }
@@ -322,6 +761,12 @@ mod tests {
 
     assert_eq!(e.description(), "foo");
     assert_eq!(e.kind(), &RedisProtocolErrorKind::BufferTooSmall(10));
+    assert_ne!(e.kind(), &RedisProtocolErrorKind::BufferTooSmall(11));
+
+    let e = RedisProtocolError::slice_too_small(10);
+    assert_eq!(e.kind(), &RedisProtocolErrorKind::SliceTooSmall { required: 10 });
+    assert_ne!(e.kind(), &RedisProtocolErrorKind::SliceTooSmall { required: 11 });
+    assert_ne!(e.kind(), &RedisProtocolErrorKind::BufferTooSmall(10));
   }
 
   #[test]
@@ -338,5 +783,138 @@ mod tests {
     assert_eq!(RedisProtocolErrorKind::DecodeError.to_str(), "Decode Error");
     assert_eq!(RedisProtocolErrorKind::Unknown.to_str(), "Unknown Error");
     assert_eq!(RedisProtocolErrorKind::BufferTooSmall(10).to_str(), "Buffer too small");
+    assert_eq!(
+      RedisProtocolErrorKind::SliceTooSmall { required: 10 }.to_str(),
+      "Slice too small"
+    );
+  }
+
+  #[test]
+  fn should_read_error_types() {
+    let e = RedisProtocolError::buffer_too_small(10);
+    assert_eq!(e.error_type(), RedisProtocolErrorType::BufferTooSmall);
+    assert_eq!(e.error_type().to_string(), "Buffer too small");
+
+    let e = RedisProtocolError::from(IoError::new(IoErrorKind::BrokenPipe, "foo"));
+    assert_eq!(e.error_type(), RedisProtocolErrorType::IO);
+  }
+
+  #[test]
+  fn should_chain_error_sources() {
+    let e = RedisProtocolError::from(IoError::new(IoErrorKind::BrokenPipe, "foo"));
+    assert_eq!(e.source().unwrap().to_string(), "foo");
+
+    let e = RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "foo");
+    assert!(e.source().is_none());
+
+    let e: RedisParseError<&[u8]> = RedisParseError::new_custom("parse_foo", "bar");
+    assert_eq!(e.to_string(), "parse_foo: bar");
+    assert!(e.source().is_none());
+  }
+
+  #[test]
+  fn should_convert_to_io_errors() {
+    fn decode() -> Result<(), RedisProtocolError> {
+      Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "foo"))
+    }
+    fn read() -> Result<(), IoError> {
+      decode()?;
+      Ok(())
+    }
+
+    let e = read().unwrap_err();
+    assert_eq!(e.kind(), IoErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "Decode Error: foo");
+    let inner = e.into_inner().unwrap().downcast::<RedisProtocolError>().unwrap();
+    assert_eq!(inner.error_type(), RedisProtocolErrorType::DecodeError);
+
+    let e = IoError::from(RedisProtocolError::from(IoError::new(IoErrorKind::BrokenPipe, "foo")));
+    assert_eq!(e.kind(), IoErrorKind::BrokenPipe);
+    assert_eq!(e.to_string(), "foo");
+  }
+
+  #[test]
+  fn should_parse_redirection_from_frame() {
+    let frame = Resp3Frame::SimpleError {
+      data: "MOVED 3999 127.0.0.1:6381".into(),
+      attributes: None,
+    };
+    let redirection = Redirection::from_frame(&frame).unwrap();
+
+    assert_eq!(redirection.kind(), RedirectionKind::Moved);
+    assert_eq!(redirection.slot(), 3999);
+    assert_eq!(redirection.server(), "127.0.0.1:6381");
+    assert_eq!(redirection.host(), "127.0.0.1");
+    assert_eq!(redirection.port(), Some(6381));
+
+    let frame = Resp3Frame::BlobError {
+      data: "ASK 1 redis-1.example.com:6380".into(),
+      attributes: None,
+    };
+    let redirection = Redirection::from_frame(&frame).unwrap();
+    assert_eq!(redirection.kind(), RedirectionKind::Ask);
+    assert_eq!(redirection.host(), "redis-1.example.com");
+
+    let frame = Resp2Frame::Error("ASK 1 :6380".into());
+    let redirection = Redirection::from_resp2_frame(&frame).unwrap();
+    assert_eq!(redirection.host(), "");
+    assert_eq!(redirection.port(), Some(6380));
+  }
+
+  #[test]
+  fn should_parse_ipv6_redirection() {
+    let frame = Resp2Frame::Error("MOVED 3999 ::1:6380".into());
+    let redirection = Redirection::from_resp2_frame(&frame).unwrap();
+    assert_eq!(redirection.host(), "::1");
+    assert_eq!(redirection.port(), Some(6380));
+
+    let frame = Resp2Frame::Error("MOVED 3999 [2001:db8::1]:6380".into());
+    let redirection = Redirection::from_resp2_frame(&frame).unwrap();
+    assert_eq!(redirection.host(), "2001:db8::1");
+    assert_eq!(redirection.port(), Some(6380));
+  }
+
+  #[test]
+  fn should_not_parse_redirection_from_other_errors() {
+    let frame = Resp3Frame::SimpleError {
+      data: "ERR unknown command".into(),
+      attributes: None,
+    };
+    assert_eq!(Redirection::from_frame(&frame), None);
+    assert_eq!(Redirection::from_resp2_frame(&Resp2Frame::SimpleString("MOVED 1 a:1".into())), None);
+  }
+
+  #[test]
+  fn should_classify_server_errors() {
+    let kind = RedisErrorKind::parse("WRONGTYPE Operation against a key holding the wrong kind of value");
+    assert_eq!(kind, RedisErrorKind::WrongType);
+    assert_eq!(kind.code(), "WRONGTYPE");
+    assert!(!kind.is_retryable());
+
+    assert!(RedisErrorKind::parse("LOADING Redis is loading the dataset in memory").is_retryable());
+    assert!(RedisErrorKind::parse("TRYAGAIN Multiple keys request during rehashing of slot").is_retryable());
+    assert!(RedisErrorKind::parse("MOVED 3999 127.0.0.1:6381").is_cluster_redirect());
+    assert!(RedisErrorKind::parse("NOAUTH Authentication required.").is_auth_error());
+    assert_eq!(RedisErrorKind::parse("OOM command not allowed"), RedisErrorKind::OutOfMemory);
+    assert_eq!(RedisErrorKind::parse("ERR"), RedisErrorKind::Err);
+
+    let kind = RedisErrorKind::parse("CUSTOM module error");
+    assert_eq!(kind, RedisErrorKind::Unknown("CUSTOM".into()));
+    assert_eq!(kind.code(), "CUSTOM");
+  }
+
+  #[test]
+  fn should_classify_error_frames() {
+    let frame = Resp3Frame::BlobError {
+      data: "NOSCRIPT No matching script.".into(),
+      attributes: None,
+    };
+    assert_eq!(RedisErrorKind::from_frame(&frame), Some(RedisErrorKind::NoScript));
+    assert_eq!(frame.to_error_kind(), Some(RedisErrorKind::NoScript));
+    assert_eq!(RedisErrorKind::from_frame(&Resp3Frame::Null { attributes: None }), None);
+
+    let frame = Resp2Frame::Error("READONLY You can't write against a read only replica.".into());
+    assert_eq!(frame.to_error_kind(), Some(RedisErrorKind::ReadOnly));
+    assert_eq!(Resp2Frame::Null.to_error_kind(), None);
   }
 }
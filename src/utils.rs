@@ -1,15 +1,11 @@
 use crate::resp2::types::Frame as Resp2Frame;
-use crate::resp3::types::Frame as Resp3Frame;
+use crate::resp3::types::{Frame as Resp3Frame, LazyStr};
 use crate::types::*;
+use crate::core::{digits_in_number, extend_with_length};
 use bytes::BytesMut;
-use cookie_factory::GenError;
-use crc16::{State, XMODEM};
+use std::fmt;
 use std::str;
 
-pub const KB: usize = 1024;
-/// A pre-defined zeroed out KB of data, used to speed up extending buffers while encoding.
-pub const ZEROED_KB: &'static [u8; 1024] = &[0; 1024];
-
 pub const REDIS_CLUSTER_SLOTS: u16 = 16384;
 
 /// Prefix on normal pubsub messages.
@@ -19,6 +15,45 @@ pub const PATTERN_PUBSUB_PREFIX: &'static str = "pmessage";
 /// Prefix on push pubsub messages.
 pub const PUBSUB_PUSH_PREFIX: &'static str = "pubsub";
 
+#[cfg(feature = "tracing")]
+macro_rules! _trace(
+  ($($arg:tt)*) => { ::tracing::trace!($($arg)*) };
+);
+#[cfg(all(feature = "logging", not(feature = "tracing")))]
+macro_rules! _trace(
+  ($($arg:tt)*) => { ::log::trace!($($arg)*) };
+);
+#[cfg(not(any(feature = "logging", feature = "tracing")))]
+macro_rules! _trace(
+  ($($arg:tt)*) => {};
+);
+
+#[cfg(feature = "tracing")]
+macro_rules! _warn(
+  ($($arg:tt)*) => { ::tracing::warn!($($arg)*) };
+);
+#[cfg(all(feature = "logging", not(feature = "tracing")))]
+macro_rules! _warn(
+  ($($arg:tt)*) => { ::log::warn!($($arg)*) };
+);
+#[cfg(not(any(feature = "logging", feature = "tracing")))]
+macro_rules! _warn(
+  ($($arg:tt)*) => {};
+);
+
+#[cfg(feature = "tracing")]
+macro_rules! _error(
+  ($($arg:tt)*) => { ::tracing::error!($($arg)*) };
+);
+#[cfg(all(feature = "logging", not(feature = "tracing")))]
+macro_rules! _error(
+  ($($arg:tt)*) => { ::log::error!($($arg)*) };
+);
+#[cfg(not(any(feature = "logging", feature = "tracing")))]
+macro_rules! _error(
+  ($($arg:tt)*) => {};
+);
+
 macro_rules! unwrap_return(
   ($expr:expr) => {
     match $expr {
@@ -30,7 +65,7 @@ macro_rules! unwrap_return(
 
 macro_rules! encode_checks(
   ($x:ident, $required:expr) => {
-    let _ = crate::utils::check_offset(&$x)?;
+    let _ = crate::core::check_offset(&$x)?;
     let required = $required;
     let remaining = $x.0.len() - $x.1;
 
@@ -40,6 +75,15 @@ macro_rules! encode_checks(
   }
 );
 
+fn lazy_str_to_string(data: LazyStr, kind: &str) -> Result<String, RedisProtocolError> {
+  data.into_string().map_err(|_| {
+    RedisProtocolError::new(
+      RedisProtocolErrorKind::Unknown,
+      format!("Cannot convert non UTF-8 {} to RESP2 frame.", kind),
+    )
+  })
+}
+
 /// Utility function to translate RESP2 frames to RESP3 frames.
 ///
 /// RESP2 frames and RESP3 frames are quite different, but RESP3 is largely a superset of RESP2 so this function will never return an error.
@@ -52,11 +96,11 @@ pub fn resp2_frame_to_resp3(frame: Resp2Frame) -> Resp3Frame {
   if frame.is_normal_pubsub() {
     let mut out = Vec::with_capacity(4);
     out.push(Resp3Frame::SimpleString {
-      data: PUBSUB_PUSH_PREFIX.to_owned(),
+      data: PUBSUB_PUSH_PREFIX.into(),
       attributes: None,
     });
     out.push(Resp3Frame::SimpleString {
-      data: PUBSUB_PREFIX.to_owned(),
+      data: PUBSUB_PREFIX.into(),
       attributes: None,
     });
     if let Resp2Frame::Array(mut inner) = frame {
@@ -77,11 +121,11 @@ pub fn resp2_frame_to_resp3(frame: Resp2Frame) -> Resp3Frame {
   if frame.is_pattern_pubsub_message() {
     let mut out = Vec::with_capacity(4);
     out.push(Resp3Frame::SimpleString {
-      data: PUBSUB_PUSH_PREFIX.to_owned(),
+      data: PUBSUB_PUSH_PREFIX.into(),
       attributes: None,
     });
     out.push(Resp3Frame::SimpleString {
-      data: PATTERN_PUBSUB_PREFIX.to_owned(),
+      data: PATTERN_PUBSUB_PREFIX.into(),
       attributes: None,
     });
     if let Resp2Frame::Array(mut inner) = frame {
@@ -106,7 +150,7 @@ pub fn resp2_frame_to_resp3(frame: Resp2Frame) -> Resp3Frame {
       attributes: None,
     },
     Resp2Frame::Error(s) => Resp3Frame::SimpleError {
-      data: s,
+      data: s.into(),
       attributes: None,
     },
     Resp2Frame::BulkString(d) => {
@@ -139,10 +183,10 @@ pub fn resp2_frame_to_resp3(frame: Resp2Frame) -> Resp3Frame {
       }
     }
     Resp2Frame::SimpleString(s) => Resp3Frame::SimpleString {
-      data: s,
+      data: s.into(),
       attributes: None,
     },
-    Resp2Frame::Null => Resp3Frame::Null,
+    Resp2Frame::Null => Resp3Frame::Null { attributes: None },
     Resp2Frame::Array(data) => {
       let mut out = Vec::with_capacity(data.len());
       for frame in data.into_iter() {
@@ -173,6 +217,9 @@ pub fn resp2_frame_to_resp3(frame: Resp2Frame) -> Resp3Frame {
 ///
 /// **Calling this with any RESP3 frame with attributes will result in an error.**
 ///
+/// See [downgrade_for_resp2](crate::resp3::downgrade::downgrade_for_resp2) for a conversion with configurable policies
+/// for each RESP3 type.
+///
 /// As seen above the conversion from RESP3 to RESP2 is lossy and error-prone, so callers are encouraged to use [resp2_frame_to_resp3] instead by exposing the RESP3 interface up
 /// the stack even if RESP2 decoding functions are used.
 pub fn resp3_frame_to_resp2(frame: Resp3Frame) -> Result<Resp2Frame, RedisProtocolError> {
@@ -227,10 +274,12 @@ pub fn resp3_frame_to_resp2(frame: Resp3Frame) -> Result<Resp2Frame, RedisProtoc
       }
     }
     Resp3Frame::Number { data, .. } => Ok(Resp2Frame::Integer(data)),
-    Resp3Frame::Double { data, .. } => Ok(Resp2Frame::BulkString(data.to_string().into_bytes())),
+    Resp3Frame::Double { data, .. } => Ok(Resp2Frame::BulkString(
+      crate::resp3::utils::f64_to_redis_string(&data).into_owned().into_bytes(),
+    )),
     Resp3Frame::VerbatimString { data, .. } => Ok(Resp2Frame::BulkString(data)),
-    Resp3Frame::SimpleError { data, .. } => Ok(Resp2Frame::Error(data)),
-    Resp3Frame::SimpleString { data, .. } => Ok(Resp2Frame::SimpleString(data)),
+    Resp3Frame::SimpleError { data, .. } => lazy_str_to_string(data, "SimpleError").map(Resp2Frame::Error),
+    Resp3Frame::SimpleString { data, .. } => lazy_str_to_string(data, "SimpleString").map(Resp2Frame::SimpleString),
     Resp3Frame::Set { data, .. } => {
       let mut out = Vec::with_capacity(data.len());
       for frame in data.into_iter() {
@@ -242,7 +291,7 @@ pub fn resp3_frame_to_resp2(frame: Resp3Frame) -> Result<Resp2Frame, RedisProtoc
       RedisProtocolErrorKind::Unknown,
       "Cannot convert Map to RESP2 frame.",
     )),
-    Resp3Frame::Null => Ok(Resp2Frame::Null),
+    Resp3Frame::Null { .. } => Ok(Resp2Frame::Null),
     Resp3Frame::ChunkedString(_) => Err(RedisProtocolError::new(
       RedisProtocolErrorKind::Unknown,
       "Cannot convert ChunkedString to RESP2 frame.",
@@ -254,36 +303,41 @@ pub fn resp3_frame_to_resp2(frame: Resp3Frame) -> Result<Resp2Frame, RedisProtoc
   }
 }
 
-pub fn check_offset(x: &(&mut [u8], usize)) -> Result<(), GenError> {
-  if x.1 > x.0.len() {
-    error!("Invalid offset of {} with buf len {}", x.1, x.0.len());
-    Err(GenError::InvalidOffset)
-  } else {
-    Ok(())
-  }
+/// Create a decode error for an invalid `context`, such as `"ACL response"`, described by `e`.
+pub(crate) fn invalid_response<E: fmt::Debug>(context: &str, e: E) -> RedisProtocolError {
+  RedisProtocolError::new(
+    RedisProtocolErrorKind::DecodeError,
+    format!("Invalid {}: {:?}", context, e),
+  )
 }
 
-/// Returns the number of bytes necessary to encode a string representation of `d`.
-pub fn digits_in_number(d: usize) -> usize {
-  if d == 0 {
-    return 1;
-  }
+/// Write a command as an array of bulk strings, returning the number of bytes written.
+///
+/// The encoding is the same in RESP2 and RESP3.
+pub(crate) fn encode_command_args<I, A>(buf: &mut BytesMut, args: I) -> usize
+where
+  I: IntoIterator<Item = A>,
+  A: AsRef<[u8]>,
+{
+  let offset = buf.len();
+  let mut count = 0;
 
-  ((d as f64).log10()).floor() as usize + 1
-}
+  for arg in args.into_iter() {
+    let arg = arg.as_ref();
+    buf.reserve(1 + digits_in_number(arg.len()) + 2 + arg.len() + 2);
+    extend_with_length(buf, b'$', arg.len());
+    buf.extend_from_slice(arg);
+    buf.extend_from_slice(CRLF.as_bytes());
+    count += 1;
+  }
 
-// this is faster than repeat(0).take(amt) at the cost of some memory
-pub fn zero_extend(buf: &mut BytesMut, mut amt: usize) {
-  trace!("allocating more, len: {}, amt: {}", buf.len(), amt);
+  // the number of arguments isn't known up front, so write the array header at the end and rotate it into place
+  let args_end = buf.len();
+  extend_with_length(buf, b'*', count);
+  let header_len = buf.len() - args_end;
+  buf[offset..].rotate_right(header_len);
 
-  buf.reserve(amt);
-  while amt >= KB {
-    buf.extend_from_slice(ZEROED_KB);
-    amt -= KB;
-  }
-  if amt > 0 {
-    buf.extend_from_slice(&ZEROED_KB[0..amt]);
-  }
+  buf.len() - offset
 }
 
 pub fn is_cluster_error(payload: &str) -> bool {
@@ -320,9 +374,467 @@ pub fn read_cluster_error(payload: &str) -> Option<Redirection> {
   }
 }
 
-/// Perform a crc16 XMODEM operation against a string slice.
-fn crc16_xmodem(key: &str) -> u16 {
-  State::<XMODEM>::calculate(key.as_bytes()) % REDIS_CLUSTER_SLOTS
+fn is_split_args_space(c: u8) -> bool {
+  c == b' ' || c == b'\n' || c == b'\r' || c == b'\t' || c == 0x0b || c == 0x0c
+}
+
+fn hex_digit_to_u8(c: u8) -> Option<u8> {
+  match c {
+    b'0'..=b'9' => Some(c - b'0'),
+    b'a'..=b'f' => Some(c - b'a' + 10),
+    b'A'..=b'F' => Some(c - b'A' + 10),
+    _ => None,
+  }
+}
+
+fn unbalanced_quotes() -> RedisProtocolError {
+  RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Unbalanced quotes.")
+}
+
+/// Split a line into arguments using the same quoting and escaping rules as `redis-cli` and `MONITOR` (`sdssplitargs`).
+///
+/// Double quoted arguments support `\n`, `\r`, `\t`, `\b`, `\a`, and `\xHH` escapes. Single quoted arguments only
+/// support `\'`. A closing quote must be followed by whitespace or the end of the line. Unquoted NUL bytes are part of
+/// the argument rather than the end of the line.
+pub fn split_args(line: &[u8]) -> Result<Vec<Vec<u8>>, RedisProtocolError> {
+  let mut out = Vec::new();
+  let mut idx = 0;
+
+  loop {
+    while idx < line.len() && is_split_args_space(line[idx]) {
+      idx += 1;
+    }
+    if idx >= line.len() {
+      return Ok(out);
+    }
+
+    let (mut in_double, mut in_single) = (false, false);
+    let mut current = Vec::new();
+
+    loop {
+      let c = match line.get(idx) {
+        Some(c) => *c,
+        None => {
+          if in_double || in_single {
+            return Err(unbalanced_quotes());
+          }
+          break;
+        }
+      };
+
+      if in_double {
+        if c == b'\\' && line.get(idx + 1) == Some(&b'x') {
+          let hi = line.get(idx + 2).and_then(|c| hex_digit_to_u8(*c));
+          let lo = line.get(idx + 3).and_then(|c| hex_digit_to_u8(*c));
+
+          if let (Some(hi), Some(lo)) = (hi, lo) {
+            current.push(hi * 16 + lo);
+            idx += 4;
+            continue;
+          }
+        }
+
+        if c == b'\\' && idx + 1 < line.len() {
+          current.push(match line[idx + 1] {
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'b' => 0x08,
+            b'a' => 0x07,
+            other => other,
+          });
+          idx += 2;
+        } else if c == b'"' {
+          if line.get(idx + 1).map(|c| !is_split_args_space(*c)).unwrap_or(false) {
+            return Err(unbalanced_quotes());
+          }
+          idx += 1;
+          break;
+        } else {
+          current.push(c);
+          idx += 1;
+        }
+      } else if in_single {
+        if c == b'\\' && line.get(idx + 1) == Some(&b'\'') {
+          current.push(b'\'');
+          idx += 2;
+        } else if c == b'\'' {
+          if line.get(idx + 1).map(|c| !is_split_args_space(*c)).unwrap_or(false) {
+            return Err(unbalanced_quotes());
+          }
+          idx += 1;
+          break;
+        } else {
+          current.push(c);
+          idx += 1;
+        }
+      } else if is_split_args_space(c) {
+        break;
+      } else if c == b'"' {
+        in_double = true;
+        idx += 1;
+      } else if c == b'\'' {
+        in_single = true;
+        idx += 1;
+      } else {
+        current.push(c);
+        idx += 1;
+      }
+    }
+
+    out.push(current);
+  }
+}
+
+/// Parse a command typed by a human, such as `SET "my key" "va\x00lue"`, into an array of bulk strings.
+///
+/// Arguments are split with the same quoting and escaping rules as `redis-cli`. Double quoted arguments support `\n`,
+/// `\r`, `\t`, `\b`, `\a`, and `\xHH` escapes, and single quoted arguments only support `\'`.
+///
+/// ```
+/// # use redis_protocol::parse_command_str;
+/// # use redis_protocol::resp2::types::Frame;
+/// let frame = parse_command_str("SET \"my key\" \"va\\x00lue\"").unwrap();
+/// assert_eq!(frame, Frame::Array(vec![
+///   Frame::BulkString("SET".into()),
+///   Frame::BulkString("my key".into()),
+///   Frame::BulkString(b"va\x00lue".to_vec()),
+/// ]));
+/// ```
+pub fn parse_command_str(command: &str) -> Result<Resp2Frame, RedisProtocolError> {
+  let args = split_args(command.as_bytes())?;
+
+  if args.is_empty() {
+    Err(RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, "Empty command."))
+  } else {
+    Ok(Resp2Frame::Array(args.into_iter().map(Resp2Frame::BulkString).collect()))
+  }
+}
+
+/// Render bytes as an escaped string that can be used to write readable protocol test vectors, such as
+/// `*1\r\n$3\r\nfoo\r\n`.
+///
+/// Backslashes and the `\r`, `\n`, and `\t` characters are escaped with a backslash, and any other bytes that are not
+/// printable ASCII are written as `\xHH`.
+///
+/// ```
+/// # use redis_protocol::{escape_wire_string, unescape_wire_string};
+/// assert_eq!(escape_wire_string(b"$3\r\na\x00b\r\n"), "$3\\r\\na\\x00b\\r\\n");
+/// assert_eq!(unescape_wire_string("$3\\r\\na\\x00b\\r\\n").unwrap(), b"$3\r\na\x00b\r\n");
+/// ```
+pub fn escape_wire_string(data: &[u8]) -> String {
+  let mut out = String::with_capacity(data.len());
+  for b in data.iter() {
+    match *b {
+      b'\\' => out.push_str("\\\\"),
+      b'\r' => out.push_str("\\r"),
+      b'\n' => out.push_str("\\n"),
+      b'\t' => out.push_str("\\t"),
+      0x20..=0x7e => out.push(*b as char),
+      _ => out.push_str(&format!("\\x{:02x}", b)),
+    }
+  }
+
+  out
+}
+
+/// Parse bytes from the escaped notation produced by [escape_wire_string].
+///
+/// The `\\`, `\r`, `\n`, `\t`, `\"`, `\0`, and `\xHH` escapes are supported, and all other characters are read as
+/// their UTF-8 bytes.
+pub fn unescape_wire_string(s: &str) -> Result<Vec<u8>, RedisProtocolError> {
+  let invalid_escape =
+    |idx: usize| RedisProtocolError::new(RedisProtocolErrorKind::DecodeError, format!("Invalid escape at {}.", idx));
+  let data = s.as_bytes();
+  let mut out = Vec::with_capacity(data.len());
+  let mut idx = 0;
+
+  while idx < data.len() {
+    if data[idx] != b'\\' {
+      out.push(data[idx]);
+      idx += 1;
+      continue;
+    }
+
+    let (byte, len) = match data.get(idx + 1) {
+      Some(b'\\') => (b'\\', 2),
+      Some(b'r') => (b'\r', 2),
+      Some(b'n') => (b'\n', 2),
+      Some(b't') => (b'\t', 2),
+      Some(b'"') => (b'"', 2),
+      Some(b'0') => (0, 2),
+      Some(b'x') => {
+        let hi = data.get(idx + 2).cloned().and_then(hex_digit_to_u8);
+        let lo = data.get(idx + 3).cloned().and_then(hex_digit_to_u8);
+        match (hi, lo) {
+          (Some(hi), Some(lo)) => (hi * 16 + lo, 4),
+          _ => return Err(invalid_escape(idx)),
+        }
+      }
+      _ => return Err(invalid_escape(idx)),
+    };
+
+    out.push(byte);
+    idx += len;
+  }
+
+  Ok(out)
+}
+
+#[derive(Clone)]
+enum HashTagState {
+  /// No `{` has been seen.
+  Searching,
+  /// A `{` has been seen, followed by `len` bytes that may be part of the hash tag.
+  InTag { crc: u16, len: usize },
+  /// A non-empty hash tag was found.
+  Found(u16),
+  /// The key has an empty hash tag, so the entire key is hashed.
+  WholeKey,
+}
+
+/// An incremental hasher that maps a key written in multiple segments to its cluster key slot.
+///
+/// Hash tags are supported across segment boundaries, so the result is the same as calling [redis_keyslot] on the
+/// concatenated key.
+///
+/// ```
+/// # use redis_protocol::{redis_keyslot, KeyslotHasher};
+/// let mut hasher = KeyslotHasher::new();
+/// hasher.write(b"user:{12");
+/// hasher.write(b"34}:profile");
+/// assert_eq!(hasher.finish(), redis_keyslot("user:{1234}:profile"));
+/// ```
+#[derive(Clone)]
+pub struct KeyslotHasher {
+  crc: u16,
+  tag: HashTagState,
+}
+
+impl Default for KeyslotHasher {
+  fn default() -> Self {
+    KeyslotHasher::new()
+  }
+}
+
+impl KeyslotHasher {
+  /// Create a new hasher for an empty key.
+  pub fn new() -> Self {
+    KeyslotHasher {
+      crc: 0,
+      tag: HashTagState::Searching,
+    }
+  }
+
+  /// Append a segment of the key.
+  pub fn write(&mut self, mut bytes: &[u8]) {
+    self.crc = crc16_xmodem_update(self.crc, bytes);
+
+    while !bytes.is_empty() {
+      match self.tag {
+        HashTagState::Searching => match bytes.iter().position(|b| *b == b'{') {
+          Some(idx) => {
+            self.tag = HashTagState::InTag { crc: 0, len: 0 };
+            bytes = &bytes[idx + 1..];
+          },
+          None => return,
+        },
+        HashTagState::InTag { ref mut crc, ref mut len } => match bytes.iter().position(|b| *b == b'}') {
+          Some(idx) => {
+            self.tag = if *len + idx == 0 {
+              HashTagState::WholeKey
+            } else {
+              HashTagState::Found(crc16_xmodem_update(*crc, &bytes[0..idx]))
+            };
+            return;
+          },
+          None => {
+            *crc = crc16_xmodem_update(*crc, bytes);
+            *len += bytes.len();
+            return;
+          },
+        },
+        HashTagState::Found(_) | HashTagState::WholeKey => return,
+      }
+    }
+  }
+
+  /// Read the cluster key slot for the key written so far.
+  pub fn finish(&self) -> u16 {
+    let crc = match self.tag {
+      HashTagState::Found(crc) => crc,
+      _ => self.crc,
+    };
+
+    crc % REDIS_CLUSTER_SLOTS
+  }
+}
+
+const CRC16_XMODEM_POLY: u16 = 0x1021;
+/// Lookup tables for a slice-by-8 CRC16 XMODEM implementation, where `table[k][b]` is the CRC of the byte `b`
+/// followed by `k` zero bytes.
+const CRC16_XMODEM_TABLES: [[u16; 256]; 8] = crc16_xmodem_tables();
+
+const fn crc16_xmodem_tables() -> [[u16; 256]; 8] {
+  let mut tables = [[0_u16; 256]; 8];
+
+  let mut i = 0;
+  while i < 256 {
+    let mut crc = (i as u16) << 8;
+    let mut bit = 0;
+    while bit < 8 {
+      crc = if crc & 0x8000 != 0 {
+        (crc << 1) ^ CRC16_XMODEM_POLY
+      } else {
+        crc << 1
+      };
+      bit += 1;
+    }
+    tables[0][i] = crc;
+    i += 1;
+  }
+
+  let mut k = 1;
+  while k < 8 {
+    let mut i = 0;
+    while i < 256 {
+      let prev = tables[k - 1][i];
+      tables[k][i] = (prev << 8) ^ tables[0][(prev >> 8) as usize];
+      i += 1;
+    }
+    k += 1;
+  }
+
+  tables
+}
+
+/// Update a CRC16 XMODEM value with the bytes in `bytes[start..end]`.
+///
+/// Slices cannot be indexed by range in a `const fn`, so the range is passed separately.
+const fn crc16_xmodem_range(mut crc: u16, bytes: &[u8], start: usize, end: usize) -> u16 {
+  let t = &CRC16_XMODEM_TABLES;
+  let mut idx = start;
+
+  while idx + 8 <= end {
+    crc = t[7][(bytes[idx] ^ (crc >> 8) as u8) as usize]
+      ^ t[6][(bytes[idx + 1] ^ crc as u8) as usize]
+      ^ t[5][bytes[idx + 2] as usize]
+      ^ t[4][bytes[idx + 3] as usize]
+      ^ t[3][bytes[idx + 4] as usize]
+      ^ t[2][bytes[idx + 5] as usize]
+      ^ t[1][bytes[idx + 6] as usize]
+      ^ t[0][bytes[idx + 7] as usize];
+    idx += 8;
+  }
+  while idx < end {
+    crc = (crc << 8) ^ t[0][(bytes[idx] ^ (crc >> 8) as u8) as usize];
+    idx += 1;
+  }
+
+  crc
+}
+
+/// Update a CRC16 XMODEM value with the provided bytes.
+fn crc16_xmodem_update(crc: u16, bytes: &[u8]) -> u16 {
+  crc16_xmodem_range(crc, bytes, 0, bytes.len())
+}
+
+/// Perform a CRC16 XMODEM operation against a byte slice.
+///
+/// This is the checksum used by Redis to map keys to cluster hash slots.
+pub const fn crc16_xmodem(bytes: &[u8]) -> u16 {
+  crc16_xmodem_range(0, bytes, 0, bytes.len())
+}
+
+/// The bit-reversed form of the Jones polynomial `0xad93d23594c935a9`.
+const CRC64_JONES_POLY: u64 = 0x95ac_9329_ac4b_c9b5;
+/// Lookup table for the reflected CRC64 Jones implementation used by Redis.
+const CRC64_JONES_TABLE: [u64; 256] = crc64_jones_table();
+
+const fn crc64_jones_table() -> [u64; 256] {
+  let mut table = [0_u64; 256];
+
+  let mut i = 0;
+  while i < 256 {
+    let mut crc = i as u64;
+    let mut bit = 0;
+    while bit < 8 {
+      crc = if crc & 1 != 0 {
+        (crc >> 1) ^ CRC64_JONES_POLY
+      } else {
+        crc >> 1
+      };
+      bit += 1;
+    }
+    table[i] = crc;
+    i += 1;
+  }
+
+  table
+}
+
+/// Perform a CRC64 operation against a byte slice, using the Jones polynomial.
+///
+/// This is the checksum Redis uses in RDB files and `DUMP` payloads.
+pub fn crc64_jones(bytes: &[u8]) -> u64 {
+  crc64_jones_update(0, bytes)
+}
+
+/// Update a CRC64 value with the provided bytes.
+pub(crate) fn crc64_jones_update(crc: u64, bytes: &[u8]) -> u64 {
+  bytes.iter().fold(crc, |crc, b| {
+    CRC64_JONES_TABLE[(crc as u8 ^ *b) as usize] ^ (crc >> 8)
+  })
+}
+
+/// Verify the footer of a payload returned by `DUMP` or provided to `RESTORE`, returning the RDB version.
+///
+/// The payload ends with a 2-byte little-endian RDB version and an 8-byte little-endian CRC64 of everything before
+/// the checksum. Callers should check that the returned version is supported by the server that will restore it.
+pub fn verify_dump_payload(payload: &[u8]) -> Result<u16, RedisProtocolError> {
+  if payload.len() < 10 {
+    return Err(RedisProtocolError::new(
+      RedisProtocolErrorKind::DecodeError,
+      "DUMP payload is too short.",
+    ));
+  }
+
+  let (data, footer) = payload.split_at(payload.len() - 8);
+  let mut checksum = [0_u8; 8];
+  checksum.copy_from_slice(footer);
+  if crc64_jones(data) != u64::from_le_bytes(checksum) {
+    return Err(RedisProtocolError::new(
+      RedisProtocolErrorKind::DecodeError,
+      "Invalid DUMP payload checksum.",
+    ));
+  }
+
+  Ok(u16::from_le_bytes([data[data.len() - 2], data[data.len() - 1]]))
+}
+
+/// Find the range of bytes used to compute the hash slot, which is either the hash tag or the entire key.
+const fn hash_tag_range(key: &[u8]) -> (usize, usize) {
+  let mut open = 0;
+  while open < key.len() {
+    if key[open] == b'{' {
+      let mut close = open + 1;
+      while close < key.len() {
+        if key[close] == b'}' {
+          return if close > open + 1 {
+            (open + 1, close)
+          } else {
+            (0, key.len())
+          };
+        }
+        close += 1;
+      }
+
+      return (0, key.len());
+    }
+    open += 1;
+  }
+
+  (0, key.len())
 }
 
 /// Map a Redis key to its cluster key slot.
@@ -337,46 +849,348 @@ fn crc16_xmodem(key: &str) -> u16 {
 /// assert_eq!(redis_keyslot("8xjx7vWrfPq54mKfFD3Y1CcjjofpnAcQ"), 5458);
 /// ```
 pub fn redis_keyslot(key: &str) -> u16 {
-  let (mut i, mut j): (Option<usize>, Option<usize>) = (None, None);
+  let out = redis_keyslot_const(key);
 
-  for (idx, c) in key.chars().enumerate() {
-    if c == '{' {
-      i = Some(idx);
-      break;
-    }
+  _trace!("mapped {} to redis slot {}", key, out);
+  out
+}
+
+/// Map a Redis key to its cluster key slot in a const context.
+///
+/// This is the same as [redis_keyslot], without logging.
+///
+/// ```
+/// # use redis_protocol::redis_keyslot_const;
+/// const SLOT: u16 = redis_keyslot_const("{user1000}.following");
+/// assert_eq!(SLOT, 3443);
+/// ```
+pub const fn redis_keyslot_const(key: &str) -> u16 {
+  redis_keyslot_bytes(key.as_bytes())
+}
+
+/// Map a binary Redis key to its cluster key slot.
+pub(crate) const fn redis_keyslot_bytes(key: &[u8]) -> u16 {
+  let (start, end) = hash_tag_range(key);
+
+  crc16_xmodem_range(0, key, start, end) % REDIS_CLUSTER_SLOTS
+}
+
+const HASH_TAG_CHARS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Generate a short alphanumeric hash tag that maps to the provided cluster slot, or `None` if the slot is invalid.
+///
+/// The same tag is returned for a slot on every call.
+///
+/// ```
+/// # use redis_protocol::{hash_tag_for_slot, redis_keyslot};
+/// let tag = hash_tag_for_slot(1234).unwrap();
+/// assert_eq!(redis_keyslot(&tag), 1234);
+/// ```
+pub fn hash_tag_for_slot(slot: u16) -> Option<String> {
+  if slot >= REDIS_CLUSTER_SLOTS {
+    return None;
   }
 
-  if i.is_none() || (i.is_some() && i.unwrap() == key.len() - 1) {
-    return crc16_xmodem(key);
+  let base = HASH_TAG_CHARS.len();
+  let mut tag = Vec::with_capacity(4);
+  // every slot is covered by a tag with at most 4 characters
+  for n in 0..base.pow(4) {
+    tag.clear();
+    let mut rem = n;
+    loop {
+      tag.push(HASH_TAG_CHARS[rem % base]);
+      rem /= base;
+      if rem == 0 {
+        break;
+      }
+    }
+
+    if crc16_xmodem(&tag) % REDIS_CLUSTER_SLOTS == slot {
+      return String::from_utf8(tag).ok();
+    }
   }
 
-  let i = i.unwrap();
-  for (idx, c) in key[i + 1..].chars().enumerate() {
-    if c == '}' {
-      j = Some(idx);
-      break;
+  None
+}
+
+/// Generate a key in the form `{tag}base` that maps to the provided cluster slot, or `None` if the slot is invalid.
+///
+/// ```
+/// # use redis_protocol::{key_for_slot, redis_keyslot};
+/// let key = key_for_slot("user:1", 1234).unwrap();
+/// assert!(key.ends_with("user:1"));
+/// assert_eq!(redis_keyslot(&key), 1234);
+/// ```
+pub fn key_for_slot(base: &str, slot: u16) -> Option<String> {
+  hash_tag_for_slot(slot).map(|tag| format!("{{{}}}{}", tag, base))
+}
+
+/// Render the bytes within `window` bytes of `offset` as a hex and ASCII dump, with a marker under the byte at
+/// `offset`.
+///
+/// Rows contain 16 bytes and start with the offset of their first byte in `buf`.
+///
+/// ```
+/// # use redis_protocol::hex_dump;
+/// let dump = hex_dump(b"*1\r\n?foo\r\n", 4, 16);
+/// assert_eq!(dump, concat!(
+///   "00000000  2a 31 0d 0a 3f 66 6f 6f 0d 0a                    |*1..?foo..|\n",
+///   "                      ^^"
+/// ));
+/// ```
+pub fn hex_dump(buf: &[u8], offset: usize, window: usize) -> String {
+  let start = offset.saturating_sub(window) / 16 * 16;
+  let end = buf.len().min(offset.saturating_add(window));
+  let mut out = String::new();
+
+  for (row, chunk) in buf.get(start..end).unwrap_or(&[]).chunks(16).enumerate() {
+    let row_offset = start + row * 16;
+    if !out.is_empty() {
+      out.push('\n');
+    }
+
+    out.push_str(&format!("{:08x}  ", row_offset));
+    for idx in 0..16 {
+      match chunk.get(idx) {
+        Some(b) => out.push_str(&format!("{:02x} ", b)),
+        None => out.push_str("   "),
+      };
+    }
+    out.push_str(" |");
+    out.extend(chunk.iter().map(|b| if b.is_ascii_graphic() || *b == b' ' { *b as char } else { '.' }));
+    out.push('|');
+
+    if offset >= row_offset && offset < row_offset + chunk.len() {
+      out.push('\n');
+      out.push_str(&" ".repeat(10 + (offset - row_offset) * 3));
+      out.push_str("^^");
     }
   }
 
-  if j.is_none() {
-    return crc16_xmodem(key);
+  out
+}
+
+/// A wrapper that implements `Debug` for a byte slice as an escaped byte string, truncated to `max_len` bytes.
+pub(crate) struct TruncatedBytes<'a> {
+  pub data: &'a [u8],
+  pub max_len: usize,
+}
+
+impl<'a> fmt::Debug for TruncatedBytes<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let len = self.data.len().min(self.max_len);
+    f.write_str("b\"")?;
+    for c in self.data[..len].iter().flat_map(|b| std::ascii::escape_default(*b)) {
+      fmt::Write::write_char(f, c as char)?;
+    }
+    f.write_str("\"")?;
+
+    if len < self.data.len() {
+      write!(f, "... ({} bytes)", self.data.len())
+    } else {
+      Ok(())
+    }
   }
+}
 
-  let j = j.unwrap();
-  let out = if i + j == key.len() || j == 0 {
-    crc16_xmodem(key)
-  } else {
-    crc16_xmodem(&key[i + 1..i + j + 1])
-  };
+/// A wrapper that implements `Debug` for a string, truncated to at most `max_len` bytes on a char boundary.
+pub(crate) struct TruncatedStr<'a> {
+  pub data: &'a str,
+  pub max_len: usize,
+}
 
-  trace!("mapped {} to redis slot {}", key, out);
-  out
+impl<'a> fmt::Debug for TruncatedStr<'a> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.data.len() <= self.max_len {
+      return fmt::Debug::fmt(self.data, f);
+    }
+
+    let mut len = self.max_len;
+    while !self.data.is_char_boundary(len) {
+      len -= 1;
+    }
+    write!(f, "{:?}... ({} bytes)", &self.data[..len], self.data.len())
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  #[test]
+  fn should_escape_and_unescape_wire_strings() {
+    let data = b"*2\r\n$3\r\nfoo\r\n$4\r\n\\\x00\xff\t\r\n";
+    let escaped = escape_wire_string(data);
+
+    assert_eq!(escaped, "*2\\r\\n$3\\r\\nfoo\\r\\n$4\\r\\n\\\\\\x00\\xff\\t\\r\\n");
+    assert_eq!(unescape_wire_string(&escaped).unwrap(), data.to_vec());
+    assert_eq!(unescape_wire_string("\\\"\\0\\xAb é").unwrap(), b"\"\x00\xab \xc3\xa9".to_vec());
+    assert!(unescape_wire_string("foo\\").is_err());
+    assert!(unescape_wire_string("\\x4").is_err());
+    assert!(unescape_wire_string("\\q").is_err());
+  }
+
+  #[test]
+  fn should_generate_keys_for_slots() {
+    for slot in (0..REDIS_CLUSTER_SLOTS).step_by(997).chain(Some(REDIS_CLUSTER_SLOTS - 1)) {
+      let key = key_for_slot("foo{bar}", slot).unwrap();
+      assert_eq!(redis_keyslot(&key), slot, "{}", key);
+      assert_eq!(hash_tag_for_slot(slot), hash_tag_for_slot(slot));
+    }
+
+    assert_eq!(hash_tag_for_slot(REDIS_CLUSTER_SLOTS), None);
+  }
+
+  #[test]
+  fn should_hash_keyslot_incrementally() {
+    let keys = [
+      "8xjx7vWrfPq54mKfFD3Y1CcjjofpnAcQ",
+      "foo{bar}baz",
+      "{}foo",
+      "foo{}{bar}",
+      "foo{bar",
+      "{user1000}.following",
+      "",
+    ];
+
+    for key in keys.iter() {
+      for split in 0..=key.len() {
+        let mut hasher = KeyslotHasher::new();
+        hasher.write(&key.as_bytes()[..split]);
+        hasher.write(&key.as_bytes()[split..]);
+        assert_eq!(hasher.finish(), redis_keyslot(key), "{} split at {}", key, split);
+      }
+    }
+  }
+
+  #[test]
+  fn should_split_args_unquoted() {
+    let args = split_args(b"  SET foo   bar ").unwrap();
+    assert_eq!(args, vec![b"SET".to_vec(), b"foo".to_vec(), b"bar".to_vec()]);
+  }
+
+  #[test]
+  fn should_split_args_with_quotes_and_escapes() {
+    let args = split_args(b"SET \"my key\" \"va\\x00lue\\n\" 'it\\'s'").unwrap();
+    assert_eq!(args, vec![
+      b"SET".to_vec(),
+      b"my key".to_vec(),
+      b"va\x00lue\n".to_vec(),
+      b"it's".to_vec()
+    ]);
+  }
+
+  #[test]
+  fn should_split_args_empty_quotes() {
+    let args = split_args(b"GET \"\"").unwrap();
+    assert_eq!(args, vec![b"GET".to_vec(), b"".to_vec()]);
+  }
+
+  #[test]
+  fn should_split_args_with_unquoted_nul_bytes() {
+    let args = split_args(b"GET a\0b \0").unwrap();
+    assert_eq!(args, vec![b"GET".to_vec(), b"a\0b".to_vec(), b"\0".to_vec()]);
+
+    let frame = parse_command_str("GET a\0b").unwrap();
+    assert_eq!(
+      frame,
+      Resp2Frame::Array(vec![
+        Resp2Frame::BulkString("GET".into()),
+        Resp2Frame::BulkString("a\0b".into()),
+      ])
+    );
+  }
+
+  #[test]
+  fn should_error_split_args_unbalanced_quotes() {
+    assert!(split_args(b"GET \"foo").is_err());
+    assert!(split_args(b"GET 'foo").is_err());
+    assert!(split_args(b"GET \"foo\"bar").is_err());
+  }
+
+  #[test]
+  fn should_encode_command() {
+    let mut buf = BytesMut::from("foo");
+    let len = encode_command_args(&mut buf, vec!["SET", "", "1234567890"]);
+
+    assert_eq!(buf, "foo*3\r\n$3\r\nSET\r\n$0\r\n\r\n$10\r\n1234567890\r\n".as_bytes());
+    assert_eq!(len, buf.len() - 3);
+
+    let mut buf = BytesMut::new();
+    assert_eq!(encode_command_args(&mut buf, Vec::<&[u8]>::new()), 4);
+    assert_eq!(buf, "*0\r\n".as_bytes());
+  }
+
+  #[test]
+  fn should_parse_command_str() {
+    let frame = parse_command_str("  hset 'my hash' field \"\\xe2\\x9c\\x93\" ").unwrap();
+    assert_eq!(
+      frame,
+      Resp2Frame::Array(vec![
+        Resp2Frame::BulkString("hset".into()),
+        Resp2Frame::BulkString("my hash".into()),
+        Resp2Frame::BulkString("field".into()),
+        Resp2Frame::BulkString("\u{2713}".into()),
+      ])
+    );
+
+    assert!(parse_command_str("  ").is_err());
+    assert!(parse_command_str("GET 'foo").is_err());
+  }
+
+  #[test]
+  fn should_crc16_xmodem_check_value() {
+    assert_eq!(crc16_xmodem(b"123456789"), 0x31c3);
+    assert_eq!(crc16_xmodem(b""), 0);
+  }
+
+  #[test]
+  fn should_crc16_xmodem_match_bytewise() {
+    let data: Vec<u8> = (0..100_u32).map(|i| (i * 31 + 7) as u8).collect();
+
+    for len in 0..data.len() {
+      let mut expected = 0_u16;
+      for b in data[0..len].iter() {
+        expected = (expected << 8) ^ CRC16_XMODEM_TABLES[0][(*b ^ (expected >> 8) as u8) as usize];
+      }
+      assert_eq!(crc16_xmodem(&data[0..len]), expected);
+    }
+  }
+
+  #[test]
+  fn should_compute_keyslot_in_const_context() {
+    const SLOTS: [u16; 3] = [
+      redis_keyslot_const("foo"),
+      redis_keyslot_const("foo{bar}baz"),
+      redis_keyslot_const("{}foo"),
+    ];
+
+    assert_eq!(SLOTS, [redis_keyslot("foo"), redis_keyslot("bar"), redis_keyslot("{}foo")]);
+    assert_eq!(SLOTS[0], 12182);
+  }
+
+  #[test]
+  fn should_crc16_with_multibyte_chars() {
+    assert_eq!(redis_keyslot("ü{foo}"), redis_keyslot("foo"));
+  }
+
+  #[test]
+  fn should_crc64_jones_check_value() {
+    assert_eq!(crc64_jones(b"123456789"), 0xe9c6_d914_c4b8_d9ca);
+    assert_eq!(crc64_jones(b""), 0);
+  }
+
+  #[test]
+  fn should_verify_dump_payload() {
+    // DUMP of the integer 10, from https://redis.io/commands/dump
+    let mut payload = b"\x00\xc0\n\t\x00\xbem\x06\x89Z(\x00\n".to_vec();
+    assert_eq!(verify_dump_payload(&payload).unwrap(), 9);
+
+    payload[1] = 0xc1;
+    assert!(verify_dump_payload(&payload).is_err());
+    assert!(verify_dump_payload(b"\x09\x00").is_err());
+  }
+
   #[test]
   fn should_crc16_123456789() {
     let key = "123456789";